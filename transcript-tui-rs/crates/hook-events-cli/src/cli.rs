@@ -15,6 +15,11 @@ pub struct Cli {
     #[arg(long, global = true, env = "TRANSCRIPT_DB_PATH")]
     pub db_path: Option<PathBuf>,
 
+    /// Database profile from ~/.claude-code-sdk/config.json, not a separate
+    /// config.toml (`--db-path` wins)
+    #[arg(long, global = true, env = "TRANSCRIPT_PROFILE")]
+    pub profile: Option<String>,
+
     /// Output format (auto-detects based on TTY if not specified)
     #[arg(long, short = 'f', global = true)]
     pub format: Option<OutputFormat>,
@@ -93,7 +98,14 @@ pub enum OutputFormat {
     Minimal,
 }
 
-#[derive(Subcommand, Debug)]
+/// Grouping for `view --group-by`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One section per turn
+    Turn,
+}
+
+#[derive(Subcommand, Debug, Clone)]
 pub enum Command {
     /// View hook events for a session
     View {
@@ -132,6 +144,15 @@ pub enum Command {
         #[arg(long)]
         to_time: Option<String>,
 
+        /// Events from this long ago on (30m, 2h, 3d, 1w), or from today,
+        /// yesterday or YYYY-MM-DD
+        #[arg(long, conflicts_with = "from_time")]
+        since: Option<String>,
+
+        /// Only events from today (since local midnight)
+        #[arg(long, conflicts_with_all = ["from_time", "since"])]
+        today: bool,
+
         /// Stream new events (poll every 500ms)
         #[arg(long)]
         tail: bool,
@@ -139,6 +160,24 @@ pub enum Command {
         /// Show last event, update on change
         #[arg(long)]
         watch: bool,
+
+        /// Merge each PreToolUse with its PostToolUse on one row
+        #[arg(long, conflicts_with_all = ["tail", "watch"])]
+        pair: bool,
+
+        /// Group events under per-turn headers with a summary
+        #[arg(long, value_enum, conflicts_with_all = ["tail", "watch"])]
+        group_by: Option<GroupBy>,
+    },
+
+    /// Interleave hook events with the session's transcript lines by time
+    Timeline {
+        /// Session name, ID, or "." for most recent
+        session: String,
+
+        /// Show the last N entries
+        #[arg(long)]
+        last: Option<usize>,
     },
 
     /// List sessions with hook events
@@ -77,11 +77,8 @@ pub fn run(cli: &Cli, db: &TranscriptDb, session: &str) -> Result<()> {
     println!("\nEvent Types:");
     for (event_type, count) in &info.event_counts {
         if use_color {
-            println!(
-                "  {} {}",
-                format!("{:<24}", colors::colored_event_type(event_type)),
-                count
-            );
+            let padded = format!("{:<24}", colors::colored_event_type(event_type));
+            println!("  {} {}", padded, count);
         } else {
             println!("  {:<24} {}", event_type, count);
         }
@@ -92,11 +89,8 @@ pub fn run(cli: &Cli, db: &TranscriptDb, session: &str) -> Result<()> {
         println!("\nTool Usage:");
         for (tool, count) in &info.tool_counts {
             if use_color {
-                println!(
-                    "  {} {}",
-                    format!("{:<24}", colors::colored_tool(tool)),
-                    count
-                );
+                let padded = format!("{:<24}", colors::colored_tool(tool));
+                println!("  {} {}", padded, count);
             } else {
                 println!("  {:<24} {}", tool, count);
             }
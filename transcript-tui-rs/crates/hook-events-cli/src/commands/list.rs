@@ -84,7 +84,7 @@ pub fn run(
         let date = session
             .last_timestamp
             .as_deref()
-            .map(|ts| colors::format_date(ts))
+            .map(colors::format_date)
             .unwrap_or_else(|| "-".to_string());
 
         if use_color {
@@ -4,4 +4,5 @@ pub mod files;
 pub mod info;
 pub mod list;
 pub mod search;
+pub mod timeline;
 pub mod view;
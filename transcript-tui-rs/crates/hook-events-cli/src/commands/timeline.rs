@@ -0,0 +1,47 @@
+//! Interleave a session's hook events with its transcript lines
+
+use anyhow::{bail, Result};
+use transcript_core::{merge_timeline, HookEventFilter, TimelineEntry};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::output::{human, json};
+
+pub fn run(cli: &Cli, db: &TranscriptDb, session: &str, last: Option<usize>) -> Result<()> {
+    let session_id = match db.resolve_hook_session(session)? {
+        Some(id) => id,
+        None => bail!(
+            "No hook events found for session: {}\nTip: Use \".\" for most recent session",
+            session
+        ),
+    };
+
+    let events = db.get_hook_events(&HookEventFilter {
+        session_id: Some(session_id.clone()),
+        ..Default::default()
+    })?;
+    let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+    let timeline = merge_timeline(&lines, &events);
+    let skip = last.map_or(0, |n| timeline.len().saturating_sub(n));
+
+    let format = cli.effective_format();
+    let use_color = cli.use_color();
+    for entry in &timeline[skip..] {
+        match format {
+            OutputFormat::Json => println!("{}", json::format_timeline_entry(entry, cli.pretty)),
+            OutputFormat::Human => println!("{}", human::format_timeline_entry(entry, use_color)),
+            OutputFormat::Minimal => println!(
+                "{}\t{}\t{}\t{}",
+                entry.format_time(),
+                entry.kind(),
+                match entry {
+                    TimelineEntry::Hook(_) => "hook".to_string(),
+                    TimelineEntry::Line(line) => line.line_number.to_string(),
+                },
+                entry.summary(60)
+            ),
+        }
+    }
+
+    Ok(())
+}
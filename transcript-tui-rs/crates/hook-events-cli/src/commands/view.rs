@@ -1,12 +1,15 @@
 //! View hook events for a session
 
 use anyhow::{bail, Result};
-use transcript_core::{HookEventFilter, Order};
+use transcript_core::{HookEvent, HookEventFilter, Order};
 use transcript_db::TranscriptDb;
 
-use crate::cli::{Cli, OutputFormat};
+use crate::cli::{Cli, GroupBy, OutputFormat};
+use crate::grouping::{group_by_turn, TurnGroup};
 use crate::output::{human, json};
+use crate::pairing::{pair_events, Row};
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     cli: &Cli,
     db: &TranscriptDb,
@@ -21,6 +24,8 @@ pub fn run(
     to_time: Option<&str>,
     tail: bool,
     watch: bool,
+    pair: bool,
+    group_by: Option<GroupBy>,
 ) -> Result<()> {
     let session_id = match db.resolve_hook_session(session)? {
         Some(id) => id,
@@ -49,7 +54,7 @@ pub fn run(
         event_types: event_types.map(|v| v.to_vec()),
         tool_names: tool_names.map(|v| v.to_vec()),
         limit: query_limit.or(Some(100)),
-        offset: offset,
+        offset,
         from_time: from_time.map(|s| s.to_string()),
         to_time: to_time.map(|s| s.to_string()),
         order,
@@ -70,18 +75,17 @@ pub fn run(
     let format = cli.effective_format();
     let use_color = cli.use_color();
 
-    for event in &events {
-        match format {
-            OutputFormat::Json => {
-                println!("{}", json::format_event(event, cli.pretty));
-            }
-            OutputFormat::Minimal => {
-                println!("{}", human::format_event_minimal(event, use_color));
-            }
-            OutputFormat::Human => {
-                println!("{}", human::format_event(event, use_color));
-                println!();
-            }
+    if group_by == Some(GroupBy::Turn) {
+        for group in group_by_turn(events) {
+            print_turn(group, &format, use_color, cli.pretty, pair);
+        }
+        return Ok(());
+    }
+
+    for row in format_rows(events, &format, use_color, cli.pretty, pair) {
+        println!("{}", row);
+        if format == OutputFormat::Human {
+            println!();
         }
     }
 
@@ -185,8 +189,69 @@ fn watch_mode(
     }
 }
 
+/// Format events one row each, merging tool calls when `pair` is set
+fn format_rows(
+    events: Vec<HookEvent>,
+    format: &OutputFormat,
+    use_color: bool,
+    pretty: bool,
+    pair: bool,
+) -> Vec<String> {
+    if !pair {
+        return events
+            .iter()
+            .map(|event| format_event(event, format, use_color, pretty))
+            .collect();
+    }
+    pair_events(events)
+        .into_iter()
+        .map(|row| match (row, format) {
+            (Row::Call(call), OutputFormat::Json) => json::format_tool_call(&call, pretty),
+            (Row::Call(call), OutputFormat::Minimal) => human::format_tool_call_minimal(&call, use_color),
+            (Row::Call(call), OutputFormat::Human) => human::format_tool_call(&call, use_color),
+            (Row::Event(event), _) => format_event(&event, format, use_color, pretty),
+        })
+        .collect()
+}
+
+/// Print a turn: header, indented rows and summary (one object in JSON)
+fn print_turn(group: TurnGroup, format: &OutputFormat, use_color: bool, pretty: bool, pair: bool) {
+    if *format == OutputFormat::Json {
+        let rows = if pair {
+            pair_events(group.events.clone())
+                .into_iter()
+                .map(|row| match row {
+                    Row::Call(call) => json::tool_call_value(&call),
+                    Row::Event(event) => json::event_value(&event),
+                })
+                .collect()
+        } else {
+            group.events.iter().map(json::event_value).collect()
+        };
+        println!("{}", json::format_turn(&group, rows, pretty));
+        return;
+    }
+
+    println!("{}", human::format_turn_header(&group, use_color));
+    for row in format_rows(group.events.clone(), format, use_color, pretty, pair) {
+        for line in row.lines() {
+            println!("  {}", line);
+        }
+    }
+    println!("  {}", human::format_turn_summary(&group, use_color));
+    println!();
+}
+
+fn format_event(event: &HookEvent, format: &OutputFormat, use_color: bool, pretty: bool) -> String {
+    match format {
+        OutputFormat::Json => json::format_event(event, pretty),
+        OutputFormat::Minimal => human::format_event_minimal(event, use_color),
+        OutputFormat::Human => human::format_event(event, use_color),
+    }
+}
+
 fn print_event(
-    event: &transcript_core::HookEvent,
+    event: &HookEvent,
     format: &OutputFormat,
     use_color: bool,
     pretty: bool,
@@ -0,0 +1,212 @@
+//! Turn grouping for `view --group-by turn`
+//!
+//! Events are grouped by the turn id the turn-tracker handler recorded. Events
+//! without one join the turn in progress, and a Stop event closes it, so
+//! sessions indexed without turn tracking still split at turn boundaries.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::DateTime;
+use transcript_core::HookEvent;
+
+/// Events of one turn, in order
+#[derive(Debug, Clone)]
+pub struct TurnGroup {
+    pub turn_id: Option<String>,
+    pub sequence: i64,
+    pub session_name: Option<String>,
+    pub events: Vec<HookEvent>,
+}
+
+impl TurnGroup {
+    /// Time from the first to the last event
+    pub fn duration_ms(&self) -> Option<i64> {
+        let start = DateTime::parse_from_rfc3339(&self.events.first()?.timestamp).ok()?;
+        let end = DateTime::parse_from_rfc3339(&self.events.last()?.timestamp).ok()?;
+        Some((end - start).num_milliseconds().max(0))
+    }
+
+    /// Tool names with their call counts, in order of first use
+    pub fn tools(&self) -> Vec<(String, usize)> {
+        let mut seen_calls = HashSet::new();
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for event in &self.events {
+            if !matches!(event.event_type.as_str(), "PreToolUse" | "PostToolUse") {
+                continue;
+            }
+            let Some(name) = &event.tool_name else {
+                continue;
+            };
+            // Pre and Post of the same call count once
+            let call = event.tool_use_id.clone().unwrap_or_else(|| event.id.to_string());
+            if !seen_calls.insert(call) {
+                continue;
+            }
+            match counts.iter_mut().find(|(n, _)| n == name) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((name.clone(), 1)),
+            }
+        }
+        counts
+    }
+
+    /// Events a hook blocked
+    pub fn blocked(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|e| matches!(e.decision.as_deref(), Some("block" | "deny")))
+            .count()
+    }
+}
+
+/// Group chronological events into turns
+pub fn group_by_turn(events: Vec<HookEvent>) -> Vec<TurnGroup> {
+    let mut groups: Vec<TurnGroup> = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    let mut open: Option<usize> = None;
+
+    for event in events {
+        let idx = match (&event.turn_id, open) {
+            (Some(id), _) => *by_id.entry(id.clone()).or_insert_with(|| {
+                groups.push(TurnGroup {
+                    turn_id: Some(id.clone()),
+                    sequence: 0,
+                    session_name: None,
+                    events: Vec::new(),
+                });
+                groups.len() - 1
+            }),
+            (None, Some(idx)) => idx,
+            (None, None) => {
+                groups.push(TurnGroup {
+                    turn_id: None,
+                    sequence: 0,
+                    session_name: None,
+                    events: Vec::new(),
+                });
+                groups.len() - 1
+            }
+        };
+
+        let group = &mut groups[idx];
+        if group.sequence == 0 {
+            group.sequence = event.turn_sequence.unwrap_or(0);
+        }
+        if group.session_name.is_none() {
+            group.session_name = event.session_name.clone();
+        }
+        open = (event.event_type != "Stop").then_some(idx);
+        group.events.push(event);
+    }
+
+    // Untracked turns are numbered after the turn before them
+    let mut previous = 0;
+    for group in &mut groups {
+        if group.sequence == 0 {
+            group.sequence = previous + 1;
+        }
+        previous = group.sequence;
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: i64, event_type: &str, turn: Option<(&str, i64)>) -> HookEvent {
+        HookEvent {
+            id,
+            session_id: "s1".to_string(),
+            timestamp: format!("2025-01-01T10:00:{:02}.000Z", id),
+            event_type: event_type.to_string(),
+            tool_use_id: None,
+            tool_name: None,
+            decision: None,
+            handler_results_json: None,
+            input_json: None,
+            context_json: None,
+            file_path: "hooks.jsonl".to_string(),
+            line_number: id,
+            turn_id: turn.map(|(id, _)| id.to_string()),
+            turn_sequence: turn.map(|(_, seq)| seq),
+            session_name: None,
+            git_hash: None,
+            git_branch: None,
+            git_dirty: None,
+        }
+    }
+
+    fn ids(group: &TurnGroup) -> Vec<i64> {
+        group.events.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn test_groups_by_turn_id() {
+        let groups = group_by_turn(vec![
+            event(1, "UserPromptSubmit", Some(("t1", 1))),
+            event(2, "PreToolUse", Some(("t1", 1))),
+            // Untracked events join the turn in progress
+            event(3, "Notification", None),
+            event(4, "Stop", Some(("t1", 1))),
+            event(5, "UserPromptSubmit", Some(("t2", 2))),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].turn_id.as_deref(), Some("t1"));
+        assert_eq!(ids(&groups[0]), [1, 2, 3, 4]);
+        assert_eq!(groups[0].duration_ms(), Some(3000));
+        assert_eq!(ids(&groups[1]), [5]);
+        assert_eq!(groups[1].sequence, 2);
+    }
+
+    #[test]
+    fn test_stop_closes_untracked_turn() {
+        let groups = group_by_turn(vec![
+            event(1, "UserPromptSubmit", None),
+            event(2, "PreToolUse", None),
+            event(3, "Stop", None),
+            event(4, "UserPromptSubmit", None),
+            event(5, "Stop", None),
+            event(6, "SessionEnd", None),
+        ]);
+
+        let split: Vec<_> = groups.iter().map(ids).collect();
+        assert_eq!(split, [vec![1, 2, 3], vec![4, 5], vec![6]]);
+        // Numbered in order since no turn sequence was recorded
+        let sequences: Vec<_> = groups.iter().map(|g| g.sequence).collect();
+        assert_eq!(sequences, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_untracked_turn_numbered_after_tracked() {
+        let groups = group_by_turn(vec![
+            event(1, "UserPromptSubmit", Some(("t4", 4))),
+            event(2, "Stop", Some(("t4", 4))),
+            event(3, "UserPromptSubmit", None),
+        ]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].turn_id, None);
+        assert_eq!(groups[1].sequence, 5);
+    }
+
+    #[test]
+    fn test_tools_count_calls_once() {
+        let mut events = Vec::new();
+        for (id, event_type, call) in [
+            (1, "PreToolUse", "a"),
+            (2, "PostToolUse", "a"),
+            (3, "PreToolUse", "b"),
+        ] {
+            let mut e = event(id, event_type, None);
+            e.tool_name = Some("Bash".to_string());
+            e.tool_use_id = Some(call.to_string());
+            events.push(e);
+        }
+        events[2].decision = Some("deny".to_string());
+        let groups = group_by_turn(events);
+        assert_eq!(groups[0].tools(), [("Bash".to_string(), 2)]);
+        assert_eq!(groups[0].blocked(), 1);
+    }
+}
@@ -0,0 +1,80 @@
+//! hook-events-cli - CLI for viewing and querying Claude Code hook events
+//!
+//! Also runs as `transcript hooks`.
+
+pub mod cli;
+mod commands;
+mod grouping;
+mod output;
+mod pairing;
+
+use anyhow::Result;
+use transcript_db::TranscriptDb;
+
+pub use cli::{Cli, Command};
+
+/// Run a command with parsed arguments
+pub fn run(mut cli: Cli) -> Result<()> {
+    cli.db_path = transcript_core::resolve_db_path(cli.db_path.take(), cli.profile.as_deref())?;
+
+    // Open database connection
+    let db = match &cli.db_path {
+        Some(path) => TranscriptDb::open(path)?,
+        None => TranscriptDb::open_default()?,
+    };
+
+    match &cli.command {
+        Command::View {
+            session,
+            event,
+            tool,
+            last,
+            first,
+            limit,
+            offset,
+            from_time,
+            to_time,
+            since,
+            today,
+            tail,
+            watch,
+            pair,
+            group_by,
+        } => {
+            let start = transcript_core::resolve_since(since.as_deref(), *today)?;
+            commands::view::run(
+                &cli,
+                &db,
+                session,
+                event.as_deref(),
+                tool.as_deref(),
+                *last,
+                *first,
+                *limit,
+                *offset,
+                start.as_deref().or(from_time.as_deref()),
+                to_time.as_deref(),
+                *tail,
+                *watch,
+                *pair,
+                *group_by,
+            )
+        }
+
+        Command::Timeline { session, last } => commands::timeline::run(&cli, &db, session, *last),
+
+        Command::List { recent, names } => {
+            commands::list::run(&cli, &db, *recent, *names)
+        }
+
+        Command::Info { session } => commands::info::run(&cli, &db, session),
+
+        Command::Search { query, limit } => {
+            commands::search::run(&cli, &db, query, *limit)
+        }
+
+        Command::Files { session, stats } => {
+            commands::files::run(&cli, &db, session, *stats)
+        }
+    }
+}
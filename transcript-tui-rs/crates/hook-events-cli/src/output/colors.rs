@@ -62,6 +62,26 @@ pub fn colored_decision(decision: &str) -> String {
     }
 }
 
+/// Get colored tool call status (from `view --pair`)
+pub fn colored_status(status: &str) -> String {
+    match status {
+        "ok" => status.green().to_string(),
+        "error" => status.red().bold().to_string(),
+        "blocked" => status.red().to_string(),
+        _ => status.yellow().to_string(),
+    }
+}
+
+/// Get colored blocked-event count (red when non-zero)
+pub fn colored_blocked(count: usize) -> String {
+    let s = format!("{} blocked", count);
+    if count > 0 {
+        s.red().bold().to_string()
+    } else {
+        s.white().dimmed().to_string()
+    }
+}
+
 /// Format a header line
 pub fn header(text: &str) -> String {
     text.bold().underline().to_string()
@@ -72,18 +92,9 @@ pub fn label(text: &str) -> String {
     text.white().dimmed().to_string()
 }
 
-/// Format count with comma separators
+/// Format count with the locale's thousands separators
 pub fn format_count(n: i64) -> String {
-    let s = n.to_string();
-    let chars: Vec<char> = s.chars().collect();
-    let mut result = String::new();
-    for (i, c) in chars.iter().enumerate() {
-        if i > 0 && (chars.len() - i) % 3 == 0 {
-            result.push(',');
-        }
-        result.push(*c);
-    }
-    result
+    transcript_core::locale().format_count(n)
 }
 
 /// Format date from ISO timestamp for display
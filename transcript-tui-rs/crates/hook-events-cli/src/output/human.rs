@@ -1,8 +1,10 @@
 //! Human-readable output formatting for hook events
 
-use transcript_core::HookEvent;
+use transcript_core::{HookEvent, TimelineEntry};
 
 use super::colors;
+use crate::grouping::TurnGroup;
+use crate::pairing::{format_duration, ToolCall};
 
 /// Format a hook event in human-readable format (multiline)
 pub fn format_event(event: &HookEvent, use_color: bool) -> String {
@@ -148,3 +150,146 @@ pub fn format_event_minimal(event: &HookEvent, use_color: bool) -> String {
         time, event_str, tool_str, preview, decision_info, usage_str, turn_str
     )
 }
+
+/// Format a paired tool call in human-readable format (multiline)
+pub fn format_tool_call(call: &ToolCall, use_color: bool) -> String {
+    let mut lines = Vec::new();
+    let first = call.first();
+
+    let time = first.format_time();
+    let tool = call.tool_name().unwrap_or("?");
+    let decision = call.decision().unwrap_or("-");
+    let duration = call.duration_ms().map(format_duration).unwrap_or_else(|| "-".to_string());
+    let status = call.status().as_str();
+
+    if use_color {
+        lines.push(format!(
+            "[{}] {} -> {} {} {}",
+            colors::colored_time(&time),
+            colors::colored_tool(tool),
+            colors::colored_decision(decision),
+            colors::label(&duration),
+            colors::colored_status(status)
+        ));
+    } else {
+        lines.push(format!("[{}] {} -> {} {} {}", time, tool, decision, duration, status));
+    }
+
+    if let Some(preview) = first.input_preview(100) {
+        lines.push(format!("  Input: {}", preview));
+    }
+    if let Some(preview) = call.post.as_ref().and_then(|e| e.output_preview(80)) {
+        lines.push(format!("  Output: {}", preview));
+    }
+
+    lines.join("\n")
+}
+
+/// Format a paired tool call in minimal format (single line)
+pub fn format_tool_call_minimal(call: &ToolCall, use_color: bool) -> String {
+    let time = call.first().format_time();
+    let tool = format!("{:<12}", call.tool_name().unwrap_or("?"));
+    let decision = format!("{:<6}", call.decision().unwrap_or("-"));
+    let duration = format!(
+        "{:>7}",
+        call.duration_ms().map(format_duration).unwrap_or_else(|| "-".to_string())
+    );
+    let status = format!("{:<7}", call.status().as_str());
+    let preview = call.first().input_preview(30).unwrap_or_default();
+
+    if use_color {
+        format!(
+            "{} {} {} {} {} {}",
+            time,
+            colors::colored_tool(&tool),
+            colors::colored_decision(&decision),
+            colors::label(&duration),
+            colors::colored_status(&status),
+            preview
+        )
+    } else {
+        format!("{} {} {} {} {} {}", time, tool, decision, duration, status, preview)
+    }
+}
+
+/// Format the header line of a turn group
+pub fn format_turn_header(group: &TurnGroup, use_color: bool) -> String {
+    let mut parts = vec![format!("Turn {}", group.sequence)];
+    if let Some(name) = &group.session_name {
+        parts.push(if use_color { colors::colored_session(name) } else { name.clone() });
+    }
+    if let Some(ms) = group.duration_ms() {
+        parts.push(format_duration(ms));
+    }
+    let header = format!("━━ {} ━━", parts.join(" · "));
+    if use_color {
+        colors::header(&header)
+    } else {
+        header
+    }
+}
+
+/// Format the summary line of a turn group: tools used and blocked count
+pub fn format_turn_summary(group: &TurnGroup, use_color: bool) -> String {
+    let tools = group.tools();
+    let tools = if tools.is_empty() {
+        "none".to_string()
+    } else {
+        tools
+            .iter()
+            .map(|(name, count)| {
+                let name = if use_color { colors::colored_tool(name) } else { name.clone() };
+                if *count > 1 {
+                    format!("{} ×{}", name, count)
+                } else {
+                    name
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let blocked = if use_color {
+        colors::colored_blocked(group.blocked())
+    } else {
+        format!("{} blocked", group.blocked())
+    };
+
+    let summary = format!("Tools: {} · {} · {} events", tools, blocked, group.events.len());
+    if use_color {
+        format!("{} {}", colors::label("Σ"), summary)
+    } else {
+        format!("Σ {}", summary)
+    }
+}
+
+/// Format one row of the merged hook/transcript timeline (single line)
+///
+/// Hook rows are marked `hook` where transcript rows show their line number.
+pub fn format_timeline_entry(entry: &TimelineEntry, use_color: bool) -> String {
+    let time = entry.format_time();
+    let time = if use_color { colors::colored_time(&time) } else { time };
+    let kind = entry.kind();
+    let padding = " ".repeat(20usize.saturating_sub(kind.chars().count()));
+    let summary = entry.summary(80);
+
+    match entry {
+        TimelineEntry::Hook(event) => {
+            let kind = if use_color {
+                colors::colored_event_type(&event.event_type)
+            } else {
+                kind.to_string()
+            };
+            format!("[{}] {:<6} {}{}{}", time, "hook", kind, padding, summary).trim_end().to_string()
+        }
+        TimelineEntry::Line(line) => {
+            let number = format!("#{}", line.line_number);
+            let (number, kind) = if use_color {
+                (colors::label(&format!("{:<6}", number)), colors::label(kind))
+            } else {
+                (format!("{:<6}", number), kind.to_string())
+            };
+            format!("[{}] {} {}{}{}", time, number, kind, padding, summary).trim_end().to_string()
+        }
+    }
+}
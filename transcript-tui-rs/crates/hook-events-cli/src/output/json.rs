@@ -1,10 +1,86 @@
 //! JSON output formatting for hook events
 
-use serde_json::json;
-use transcript_core::HookEvent;
+use serde_json::{json, Value};
+use transcript_core::{HookEvent, TimelineEntry};
+
+use crate::grouping::TurnGroup;
+use crate::pairing::ToolCall;
 
 /// Format a hook event as JSON
 pub fn format_event(event: &HookEvent, pretty: bool) -> String {
+    to_string(&event_value(event), pretty)
+}
+
+/// Format a paired tool call as JSON, with both events nested
+pub fn format_tool_call(call: &ToolCall, pretty: bool) -> String {
+    to_string(&tool_call_value(call), pretty)
+}
+
+/// Format a turn as JSON, with its summary and already-built rows
+pub fn format_turn(group: &TurnGroup, rows: Vec<Value>, pretty: bool) -> String {
+    let tools: serde_json::Map<String, Value> = group
+        .tools()
+        .into_iter()
+        .map(|(name, count)| (name, json!(count)))
+        .collect();
+    let obj = json!({
+        "turnId": group.turn_id,
+        "turnSequence": group.sequence,
+        "sessionName": group.session_name,
+        "start": group.events.first().map(|e| &e.timestamp),
+        "end": group.events.last().map(|e| &e.timestamp),
+        "durationMs": group.duration_ms(),
+        "tools": tools,
+        "blocked": group.blocked(),
+        "events": rows,
+    });
+    to_string(&obj, pretty)
+}
+
+/// Format one row of the merged timeline as JSON, tagged with its source
+pub fn format_timeline_entry(entry: &TimelineEntry, pretty: bool) -> String {
+    let obj = match entry {
+        TimelineEntry::Hook(event) => {
+            let mut obj = event_value(event);
+            obj["source"] = json!("hook");
+            obj
+        }
+        TimelineEntry::Line(line) => json!({
+            "source": "transcript",
+            "sessionId": line.session_id,
+            "timestamp": line.timestamp,
+            "lineNumber": line.line_number,
+            "uuid": line.uuid,
+            "type": line.display_type(),
+            "preview": line.preview(200),
+        }),
+    };
+    to_string(&obj, pretty)
+}
+
+/// JSON object for a paired tool call
+pub fn tool_call_value(call: &ToolCall) -> Value {
+    json!({
+        "toolUseId": call.tool_use_id(),
+        "toolName": call.tool_name(),
+        "decision": call.decision(),
+        "durationMs": call.duration_ms(),
+        "status": call.status().as_str(),
+        "pre": call.pre.as_ref().map(event_value),
+        "post": call.post.as_ref().map(event_value),
+    })
+}
+
+fn to_string(obj: &Value, pretty: bool) -> String {
+    if pretty {
+        serde_json::to_string_pretty(obj).unwrap_or_default()
+    } else {
+        serde_json::to_string(obj).unwrap_or_default()
+    }
+}
+
+/// JSON object for a hook event
+pub fn event_value(event: &HookEvent) -> Value {
     let mut obj = json!({
         "id": event.id,
         "sessionId": event.session_id,
@@ -64,9 +140,5 @@ pub fn format_event(event: &HookEvent, pretty: bool) -> String {
         obj["gitDirty"] = json!(event.git_dirty);
     }
 
-    if pretty {
-        serde_json::to_string_pretty(&obj).unwrap_or_else(|_| format!("{:?}", event))
-    } else {
-        serde_json::to_string(&obj).unwrap_or_else(|_| format!("{:?}", event))
-    }
+    obj
 }
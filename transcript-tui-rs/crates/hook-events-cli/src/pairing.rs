@@ -0,0 +1,227 @@
+//! Pre/PostToolUse pairing for `view --pair`
+//!
+//! Each PreToolUse is merged with the PostToolUse sharing its tool_use_id, so
+//! a tool call reads as one row with its decision, duration and result.
+
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use transcript_core::HookEvent;
+
+/// How a tool call ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallStatus {
+    /// PostToolUse seen, tool succeeded
+    Ok,
+    /// PostToolUse seen, tool reported an error
+    Error,
+    /// No PostToolUse and the hook blocked the call
+    Blocked,
+    /// No PostToolUse (yet)
+    Pending,
+}
+
+impl CallStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CallStatus::Ok => "ok",
+            CallStatus::Error => "error",
+            CallStatus::Blocked => "blocked",
+            CallStatus::Pending => "pending",
+        }
+    }
+}
+
+/// One tool call: its PreToolUse and PostToolUse, at least one present
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub pre: Option<HookEvent>,
+    pub post: Option<HookEvent>,
+}
+
+impl ToolCall {
+    /// The event the row is shown at (Pre if present)
+    pub fn first(&self) -> &HookEvent {
+        match (&self.pre, &self.post) {
+            (Some(event), _) | (None, Some(event)) => event,
+            (None, None) => unreachable!("a tool call has at least one event"),
+        }
+    }
+
+    pub fn tool_name(&self) -> Option<&str> {
+        self.first().tool_name.as_deref()
+    }
+
+    pub fn tool_use_id(&self) -> Option<&str> {
+        self.first().tool_use_id.as_deref()
+    }
+
+    /// Hook decision on the PreToolUse
+    pub fn decision(&self) -> Option<&str> {
+        self.pre.as_ref().and_then(|e| e.decision.as_deref())
+    }
+
+    /// Time from PreToolUse to PostToolUse
+    pub fn duration_ms(&self) -> Option<i64> {
+        let start = DateTime::parse_from_rfc3339(&self.pre.as_ref()?.timestamp).ok()?;
+        let end = DateTime::parse_from_rfc3339(&self.post.as_ref()?.timestamp).ok()?;
+        Some((end - start).num_milliseconds().max(0))
+    }
+
+    pub fn status(&self) -> CallStatus {
+        match &self.post {
+            Some(post) if post.tool_failed() == Some(true) => CallStatus::Error,
+            Some(_) => CallStatus::Ok,
+            None if matches!(self.decision(), Some("block" | "deny")) => CallStatus::Blocked,
+            None => CallStatus::Pending,
+        }
+    }
+}
+
+/// A row of paired output
+#[derive(Debug, Clone)]
+pub enum Row {
+    /// A tool call (paired or not)
+    Call(Box<ToolCall>),
+    /// Any other event, shown as-is
+    Event(Box<HookEvent>),
+}
+
+/// Merge Pre/PostToolUse events by tool_use_id, keeping chronological order
+pub fn pair_events(events: Vec<HookEvent>) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut open: HashMap<String, usize> = HashMap::new();
+
+    for event in events {
+        match (event.event_type.as_str(), event.tool_use_id.clone()) {
+            ("PreToolUse", Some(id)) => {
+                open.insert(id, rows.len());
+                rows.push(Row::Call(Box::new(ToolCall {
+                    pre: Some(event),
+                    post: None,
+                })));
+            }
+            ("PostToolUse", Some(id)) => {
+                if let Some(Row::Call(call)) = open.remove(&id).and_then(|i| rows.get_mut(i)) {
+                    call.post = Some(event);
+                } else {
+                    rows.push(Row::Call(Box::new(ToolCall {
+                        pre: None,
+                        post: Some(event),
+                    })));
+                }
+            }
+            _ => rows.push(Row::Event(Box::new(event))),
+        }
+    }
+
+    rows
+}
+
+/// Format a duration as `350ms`, `1.25s` or `2m05s`
+pub fn format_duration(ms: i64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else if ms < 60_000 {
+        format!("{:.2}s", ms as f64 / 1000.0)
+    } else {
+        format!("{}m{:02}s", ms / 60_000, (ms % 60_000) / 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: i64, event_type: &str, tool_use_id: Option<&str>, timestamp: &str) -> HookEvent {
+        HookEvent {
+            id,
+            session_id: "s1".to_string(),
+            timestamp: timestamp.to_string(),
+            event_type: event_type.to_string(),
+            tool_use_id: tool_use_id.map(String::from),
+            tool_name: tool_use_id.map(|_| "Bash".to_string()),
+            decision: None,
+            handler_results_json: None,
+            input_json: None,
+            context_json: None,
+            file_path: "hooks.jsonl".to_string(),
+            line_number: id,
+            turn_id: None,
+            turn_sequence: None,
+            session_name: None,
+            git_hash: None,
+            git_branch: None,
+            git_dirty: None,
+        }
+    }
+
+    fn calls(rows: &[Row]) -> Vec<&ToolCall> {
+        rows.iter()
+            .filter_map(|row| match row {
+                Row::Call(call) => Some(call.as_ref()),
+                Row::Event(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pairs_pre_and_post() {
+        let rows = pair_events(vec![
+            event(1, "PreToolUse", Some("a"), "2025-01-01T10:00:00.000Z"),
+            event(2, "PreToolUse", Some("b"), "2025-01-01T10:00:01.000Z"),
+            event(3, "PostToolUse", Some("b"), "2025-01-01T10:00:01.250Z"),
+            event(4, "Notification", None, "2025-01-01T10:00:02.000Z"),
+            event(5, "PostToolUse", Some("a"), "2025-01-01T10:00:03.000Z"),
+        ]);
+
+        // Rows stay in order of the Pre events; the Posts fold into them
+        assert_eq!(rows.len(), 3);
+        assert!(matches!(&rows[2], Row::Event(e) if e.id == 4));
+        let calls = calls(&rows);
+        assert_eq!(calls[0].tool_use_id(), Some("a"));
+        assert_eq!(calls[0].post.as_ref().map(|e| e.id), Some(5));
+        assert_eq!(calls[0].duration_ms(), Some(3000));
+        assert_eq!(calls[1].duration_ms(), Some(250));
+        assert_eq!(calls[1].status(), CallStatus::Ok);
+    }
+
+    #[test]
+    fn test_unmatched_events() {
+        let mut blocked = event(2, "PreToolUse", Some("b"), "2025-01-01T10:00:01.000Z");
+        blocked.decision = Some("block".to_string());
+        let rows = pair_events(vec![
+            event(1, "PreToolUse", Some("a"), "2025-01-01T10:00:00.000Z"),
+            blocked,
+            event(3, "PostToolUse", Some("c"), "2025-01-01T10:00:02.000Z"),
+        ]);
+
+        let calls = calls(&rows);
+        assert_eq!(calls.len(), 3);
+        assert!(calls[0].post.is_none());
+        assert_eq!(calls[0].status(), CallStatus::Pending);
+        assert_eq!(calls[0].duration_ms(), None);
+        assert_eq!(calls[1].status(), CallStatus::Blocked);
+        // A Post without its Pre is still shown, at its own time
+        assert!(calls[2].pre.is_none());
+        assert_eq!(calls[2].first().id, 3);
+    }
+
+    #[test]
+    fn test_failed_post() {
+        let mut post = event(2, "PostToolUse", Some("a"), "2025-01-01T10:00:01.000Z");
+        post.input_json = Some(r#"{"tool_response":{"is_error":true}}"#.to_string());
+        let rows = pair_events(vec![
+            event(1, "PreToolUse", Some("a"), "2025-01-01T10:00:00.000Z"),
+            post,
+        ]);
+        assert_eq!(calls(&rows)[0].status(), CallStatus::Error);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(350), "350ms");
+        assert_eq!(format_duration(1250), "1.25s");
+        assert_eq!(format_duration(125_000), "2m05s");
+    }
+}
@@ -0,0 +1,124 @@
+// Transcript dashboard: session list, turn viewer and search over /api
+"use strict";
+
+const $ = (id) => document.getElementById(id);
+let sessions = [];
+let changeCursor = null;
+const CHANGE_POLL_MS = 2000;
+
+async function api(path) {
+  const response = await fetch(path);
+  const body = await response.json();
+  if (!response.ok) throw new Error(body.error || response.statusText);
+  return body;
+}
+
+function el(tag, attrs, ...children) {
+  const node = document.createElement(tag);
+  for (const [key, value] of Object.entries(attrs || {})) node.setAttribute(key, value);
+  for (const child of children) node.append(child);
+  return node;
+}
+
+const sessionName = (s) => s.slug || s.sessionId.slice(0, 8);
+const shortTime = (t) => (t || "").replace("T", " ").slice(0, 19);
+
+function showError(error) {
+  $("main").replaceChildren(el("p", { class: "error" }, String(error.message || error)));
+}
+
+function renderSessions() {
+  const filter = $("session-filter").value.toLowerCase();
+  const current = decodeURIComponent((location.hash.match(/^#\/session\/([^/]+)/) || [])[1] || "");
+  const items = sessions
+    .filter((s) => !filter || sessionName(s).toLowerCase().includes(filter) || s.sessionId.includes(filter))
+    .map((s) => {
+      const link = el("a", { href: `#/session/${encodeURIComponent(s.sessionId)}` },
+        sessionName(s), el("div", { class: "meta" }, `${s.lineCount} lines · ${shortTime(s.lastTimestamp)}`));
+      if (s.sessionId === current) link.classList.add("active");
+      return el("li", {}, link);
+    });
+  $("sessions").replaceChildren(...items);
+}
+
+async function showSession(id, line, keepScroll) {
+  const { session, turns } = await api(`/api/sessions/${encodeURIComponent(id)}/turns`);
+  const sections = turns.map((turn) => el("section", { class: "turn" },
+    el("h3", {}, `Turn ${turn.number} `, el("span", { class: "meta" }, shortTime(turn.started))),
+    ...turn.lines.map((l) => el("div", { class: "line", id: `line-${l.lineNumber}` },
+      el("span", { class: "meta" }, `#${l.lineNumber} ${shortTime(l.timestamp).slice(11)}`),
+      el("span", { class: `type type-${l.type}` }, l.type),
+      el("pre", { class: "text" }, l.text)))));
+  $("main").replaceChildren(
+    el("h2", {}, sessionName(session), " ", el("span", { class: "meta" }, `${turns.length} turns`)),
+    ...sections);
+  renderSessions();
+  const target = line && $(`line-${line}`);
+  if (keepScroll) {
+    // Refreshed by new lines: stay where the reader is
+  } else if (target) {
+    target.classList.add("highlight");
+    target.scrollIntoView({ block: "center" });
+  } else {
+    $("main").scrollTop = 0;
+  }
+}
+
+async function showSearch(query) {
+  $("search").value = query;
+  const results = await api(`/api/search?q=${encodeURIComponent(query)}`);
+  const items = results.map((r) => el("a", {
+      class: "result",
+      href: `#/session/${encodeURIComponent(r.sessionId)}/${r.lineNumber}`,
+    },
+    el("div", { class: "meta" }, `${r.slug || r.sessionId.slice(0, 8)} #${r.lineNumber} · ${r.type} · ${shortTime(r.timestamp)}`),
+    el("div", {}, r.preview)));
+  $("main").replaceChildren(
+    el("h2", {}, `${results.length} result(s) for “${query}”`),
+    ...(items.length ? items : [el("p", { class: "hint" }, "Nothing found.")]));
+}
+
+function route() {
+  const [, view, arg, line] = location.hash.split("/");
+  const run = view === "session" ? showSession(decodeURIComponent(arg), line)
+    : view === "search" ? showSearch(decodeURIComponent(arg || ""))
+    : Promise.resolve(renderSessions());
+  run.catch(showError);
+}
+
+// Refresh the session list, and the open session, when the indexer adds lines
+async function watchChanges() {
+  try {
+    const since = changeCursor === null ? "" : `?since=${changeCursor}`;
+    const { latest, changes } = await api(`/api/changes${since}`);
+    changeCursor = latest;
+    if (!changes.length) return;
+    sessions = await api("/api/sessions");
+    const [, view, arg] = location.hash.split("/");
+    const open = view === "session" && decodeURIComponent(arg);
+    if (open && changes.some((c) => c.sessionId === open)) {
+      const main = $("main");
+      const atEnd = main.scrollTop + main.clientHeight >= main.scrollHeight - 4;
+      await showSession(open, null, true);
+      if (atEnd) main.scrollTop = main.scrollHeight;
+    } else {
+      renderSessions();
+    }
+  } catch (error) {
+    // The server may be restarting; try again on the next tick
+  } finally {
+    setTimeout(watchChanges, CHANGE_POLL_MS);
+  }
+}
+
+$("search-form").addEventListener("submit", (event) => {
+  event.preventDefault();
+  const query = $("search").value.trim();
+  if (query) location.hash = `#/search/${encodeURIComponent(query)}`;
+});
+$("session-filter").addEventListener("input", renderSessions);
+window.addEventListener("hashchange", route);
+
+api("/api/sessions")
+  .then((list) => { sessions = list; route(); watchChanges(); })
+  .catch(showError);
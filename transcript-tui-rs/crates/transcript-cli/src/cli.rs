@@ -15,9 +15,14 @@ pub struct Cli {
     #[arg(long, global = true, env = "TRANSCRIPT_DB_PATH")]
     pub db_path: Option<PathBuf>,
 
+    /// Database profile from ~/.claude-code-sdk/config.json, not a separate
+    /// config.toml (`--db-path` wins)
+    #[arg(long, global = true, env = "TRANSCRIPT_PROFILE")]
+    pub profile: Option<String>,
+
     /// Output format (auto-detects based on TTY if not specified)
     #[arg(long, short = 'f', global = true)]
-    pub format: Option<OutputFormat>,
+    pub format: Option<FormatArg>,
 
     /// Output raw JSON (alias for --format json)
     #[arg(long, global = true)]
@@ -43,6 +48,10 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub no_color: bool,
 
+    /// Print phase timings (db open, query, render, total) to stderr
+    #[arg(long, global = true)]
+    pub timings: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -62,8 +71,11 @@ impl Cli {
         }
 
         // Explicit --format flag
-        if let Some(f) = self.format {
-            return f;
+        match self.format {
+            Some(FormatArg::Human | FormatArg::Md) => return OutputFormat::Human,
+            Some(FormatArg::Json) => return OutputFormat::Json,
+            Some(FormatArg::Minimal) => return OutputFormat::Minimal,
+            None => {}
         }
 
         // Auto-detect: human for TTY, json otherwise
@@ -74,19 +86,27 @@ impl Cli {
         }
     }
 
-    /// Check if colors should be used
-    pub fn use_color(&self) -> bool {
-        if self.no_color {
-            return false;
-        }
-        if self.color {
-            return true;
-        }
-        // Auto-detect based on TTY
-        atty::is(atty::Stream::Stdout)
+    /// Whether `--format md` was asked for (and not overridden by an alias flag)
+    pub fn wants_markdown(&self) -> bool {
+        self.format == Some(FormatArg::Md) && !self.json && !self.human && !self.minimal
     }
 }
 
+/// Value of `--format`: an output format, or Markdown for the commands that
+/// render it (others refuse it)
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum FormatArg {
+    /// Human-readable output with colors
+    Human,
+    /// JSON output (one object per line for lists)
+    Json,
+    /// Minimal text output (content only)
+    Minimal,
+    /// Markdown (`turn` and `thinking` only)
+    #[value(alias = "markdown")]
+    Md,
+}
+
 /// Output format for commands
 #[derive(Debug, Clone, Copy, ValueEnum, Default, PartialEq, Eq)]
 pub enum OutputFormat {
@@ -104,7 +124,17 @@ pub enum Command {
     /// View transcript lines for a session
     View {
         /// Session name, ID, or file path
-        session: String,
+        #[arg(required_unless_present_any = ["tag", "all"])]
+        session: Option<String>,
+
+        /// Every session's lines in the time window, interleaved by time
+        /// (default window: `--date`, else today)
+        #[arg(long, conflicts_with_all = ["session", "tag", "follow", "from_line", "to_line"])]
+        all: bool,
+
+        /// Only a session with this tag (without a session: the most recent one)
+        #[arg(long)]
+        tag: Option<String>,
 
         /// Filter by line type (user, assistant, system, tool)
         #[arg(short, long, value_delimiter = ',')]
@@ -122,14 +152,27 @@ pub enum Command {
         #[arg(long, short)]
         search: Option<String>,
 
-        /// Start from this time (ISO format or HH:MM:SS)
-        #[arg(long)]
+        /// Start from this time (ISO format, or local HH:MM[:SS] on `--date`)
+        #[arg(long, visible_alias = "from")]
         from_time: Option<String>,
 
-        /// End at this time (ISO format or HH:MM:SS)
-        #[arg(long)]
+        /// End at this time (ISO format, or local HH:MM[:SS] on `--date`)
+        #[arg(long, visible_alias = "to")]
         to_time: Option<String>,
 
+        /// Day of HH:MM times: today (default), yesterday or YYYY-MM-DD
+        #[arg(long)]
+        date: Option<String>,
+
+        /// Start this long ago (30m, 2h, 3d, 1w), or at today, yesterday or
+        /// YYYY-MM-DD
+        #[arg(long, conflicts_with_all = ["from_time", "date"])]
+        since: Option<String>,
+
+        /// Only lines from today (since local midnight)
+        #[arg(long, conflicts_with_all = ["from_time", "date", "since"])]
+        today: bool,
+
         /// Start from line number
         #[arg(long)]
         from_line: Option<i64>,
@@ -141,6 +184,18 @@ pub enum Command {
         /// Reverse order (newest first)
         #[arg(short, long)]
         reverse: bool,
+
+        /// Apply a jq filter to each raw line (e.g. '.message.usage | select(. != null)')
+        #[arg(long)]
+        jq: Option<String>,
+
+        /// Keep printing lines as they are indexed (needs `transcript index watch`)
+        #[arg(short = 'F', long, conflicts_with_all = ["reverse", "first", "to_line", "to_time"])]
+        follow: bool,
+
+        /// Include the sessions this one resumes, in time order
+        #[arg(long, conflicts_with_all = ["all", "follow", "from_line", "to_line"])]
+        with_ancestors: bool,
     },
 
     /// List recent sessions
@@ -156,6 +211,35 @@ pub enum Command {
         /// Search for sessions matching pattern
         #[arg(short, long)]
         search: Option<String>,
+
+        /// Only sessions indexed from this user's home (see TRANSCRIPT_HOMES)
+        #[arg(short, long)]
+        user: Option<String>,
+
+        /// Include teammates' snapshots (see `sync pull --federate`)
+        #[arg(long, conflicts_with = "user")]
+        team: bool,
+
+        /// Only sessions with this tag
+        #[arg(long, conflicts_with = "team")]
+        tag: Option<String>,
+
+        /// Only sessions with this review status
+        #[arg(long, value_enum, conflicts_with = "team")]
+        review: Option<ReviewArg>,
+
+        /// Only sessions run in this project: a name from `transcript projects`,
+        /// or a directory (sessions run there or below; "." for the current one)
+        #[arg(long, conflicts_with = "team")]
+        project: Option<String>,
+    },
+
+    /// List projects: the repositories (or directories outside one) sessions
+    /// ran in, most recent first
+    Projects {
+        /// Number of projects to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
     },
 
     /// Show session information and statistics
@@ -164,10 +248,100 @@ pub enum Command {
         session: String,
     },
 
+    /// Show one turn of a session (`--format md` for Markdown to paste into PRs)
+    Turn {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Turn number (hook turn sequence, or counted from user prompts)
+        seq: usize,
+
+        /// Leave out tool calls and their results (Markdown)
+        #[arg(long)]
+        no_tools: bool,
+
+        /// Include assistant thinking blocks (Markdown)
+        #[arg(long)]
+        thinking: bool,
+
+        /// Characters of each tool result to keep (Markdown)
+        #[arg(long, default_value = "2000")]
+        max_output: usize,
+    },
+
+    /// Tick which turns of a session to keep in a checklist, then export them
+    /// (to share, or resume, a tidied-up session)
+    PruneTurns {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Export format
+        #[arg(long, value_enum, default_value = "claude-jsonl")]
+        to: PruneFormat,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Turns to keep without the checklist: numbers and ranges,
+        /// comma-separated (e.g. 1..3,5)
+        #[arg(long)]
+        keep: Option<String>,
+
+        /// Leave out tool calls and their results (Markdown)
+        #[arg(long)]
+        no_tools: bool,
+
+        /// Include assistant thinking blocks (Markdown)
+        #[arg(long)]
+        thinking: bool,
+    },
+
+    /// Draft a bug report from a session (problem, steps, errors, environment)
+    Issue {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Turns to include: a..b, a.., ..b or n (default: all)
+        #[arg(long)]
+        turns: Option<String>,
+    },
+
+    /// Draft release notes from the sessions behind a range of commits
+    Changelog {
+        /// Git repository
+        #[arg(long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Start of the range (tag, branch or commit), exclusive
+        #[arg(long)]
+        since: String,
+
+        /// End of the range
+        #[arg(long, default_value = "HEAD")]
+        until: String,
+    },
+
+    /// Summarize recent sessions in a project as context for a new one
+    Digest {
+        /// Project name or directory (sessions run there or below)
+        #[arg(long, default_value = ".")]
+        project: String,
+
+        /// Sessions to include: N-sessions or N-days
+        #[arg(long, default_value = "3-sessions")]
+        last: String,
+
+        /// Approximate size limit in tokens
+        #[arg(long, default_value = "2000")]
+        budget: usize,
+    },
+
     /// Search transcripts using full-text search
     Search {
         /// Search query
-        query: String,
+        #[arg(required_unless_present_any = ["history", "saved"])]
+        query: Option<String>,
 
         /// Limit results
         #[arg(short = 'n', long, default_value = "50")]
@@ -177,15 +351,128 @@ pub enum Command {
         #[arg(short, long)]
         session: Option<String>,
 
-        /// Show context around matches
+        /// Lines of the session to show before and after each match
         #[arg(short = 'C', long, default_value = "0")]
         context: usize,
+
+        /// Search teammates' snapshots too (see `sync pull --federate`)
+        #[arg(long, conflicts_with = "session")]
+        team: bool,
+
+        /// Search the archive database too (see `transcript archive`)
+        #[arg(long, conflicts_with = "session")]
+        include_archive: bool,
+
+        /// Only sessions with this tag
+        #[arg(long, conflicts_with_all = ["session", "team", "include_archive"])]
+        tag: Option<String>,
+
+        /// Only lines run in this project (name or directory, see `list --project`)
+        #[arg(long, conflicts_with_all = ["session", "team", "include_archive", "tag"])]
+        project: Option<String>,
+
+        /// Only lines from this long ago on (30m, 2h, 3d, 1w), or from today,
+        /// yesterday or YYYY-MM-DD
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only lines from today (since local midnight)
+        #[arg(long, conflicts_with = "since")]
+        today: bool,
+
+        /// Show previous search queries, most recent first
+        #[arg(long, conflicts_with_all = ["query", "session", "team", "include_archive", "tag", "project", "since", "today"])]
+        history: bool,
+
+        /// Save the query and its filters under this name, then search
+        #[arg(long, value_name = "NAME", conflicts_with = "history")]
+        save: Option<String>,
+
+        /// Run a saved search; filters given here override the saved ones
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["query", "history", "save"])]
+        saved: Option<String>,
+    },
+
+    /// Search the inputs and results of tool calls on files matching a glob
+    /// (Read, Edit, MultiEdit, Write, NotebookEdit), across sessions
+    Grep {
+        /// Regular expression to find
+        pattern: String,
+
+        /// Only calls on files matching this glob (e.g. src/schema.rs, "src/**/*.rs")
+        #[arg(long, value_name = "GLOB")]
+        path: Option<String>,
+
+        /// Filter to specific session
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Maximum matching lines
+        #[arg(short = 'n', long, default_value = "200")]
+        limit: usize,
+    },
+
+    /// Every Edit and Write applied to a file, across sessions, with diffs
+    FileHistory {
+        /// The file, or a glob (e.g. src/schema.rs, "src/**/*.rs")
+        path: String,
+
+        /// Print the file as of this time instead (2h, yesterday, 2026-01-15, ISO time)
+        #[arg(long, value_name = "TIME")]
+        at: Option<String>,
+
+        /// Filter to specific session
+        #[arg(short, long)]
+        session: Option<String>,
+    },
+
+    /// Attribute a file's lines to the sessions and turns that wrote them (git blame + edit calls)
+    Blame {
+        /// A file in a git repository
+        file: PathBuf,
+
+        /// Lines to blame: 42 or 10-20 (default: the whole file)
+        lines: Option<String>,
+    },
+
+    /// A session's thinking blocks, with estimated thinking tokens against output tokens
+    /// per turn (`-o` or `--format md` exports the blocks as Markdown)
+    Thinking {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Write the blocks as Markdown to this file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Index management subcommands
     #[command(subcommand)]
     Index(IndexCommand),
 
+    /// Import a conversation saved in API messages format, or a Claude.ai export
+    Import {
+        /// JSON file with a messages array or a request body, or a Claude.ai
+        /// export (.zip or conversations.json) ("-" for stdin)
+        file: PathBuf,
+
+        /// Message format of the file (`--format` is the global output format)
+        #[arg(long, value_enum, default_value = "anthropic")]
+        from: ImportFormat,
+
+        /// Session ID to file the conversation under (default: import-<file name>)
+        #[arg(long)]
+        session_id: Option<String>,
+    },
+
+    /// Compare sessions for evaluation pipelines
+    #[command(subcommand)]
+    Eval(EvalCommand),
+
     /// Search and recall across sessions grouped by session
     Recall {
         /// Search query
@@ -198,10 +485,768 @@ pub enum Command {
         /// Maximum matches per session
         #[arg(long, default_value = "3")]
         max_matches: usize,
+
+        /// How to rank sessions
+        #[arg(long, value_enum, default_value = "bm25")]
+        rank: RecallRank,
+
+        /// Only sessions run in this project (name or directory, see `list --project`)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Turns of context to show before and after each match
+        #[arg(long, default_value = "0")]
+        context: usize,
+    },
+
+    /// Run read-only SQL against the database
+    Query {
+        /// SQL statement (e.g. "SELECT type, COUNT(*) FROM lines GROUP BY type")
+        sql: Option<String>,
+
+        /// Run a named example query instead (see --examples)
+        #[arg(short, long)]
+        example: Option<String>,
+
+        /// List the named example queries
+        #[arg(long)]
+        examples: bool,
+
+        /// Output as CSV
+        #[arg(long)]
+        csv: bool,
+    },
+
+    /// Database inspection for maintainers
+    #[command(subcommand)]
+    Db(DbCommand),
+
+    /// Estimated cost by project, branch, model, session or day
+    Cost {
+        /// Dimension to attribute cost to
+        #[arg(short, long, value_enum, default_value = "project")]
+        by: CostBy,
+
+        /// Only include usage from this session (name, ID, or partial match)
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Only include usage from the last N days
+        #[arg(short, long)]
+        days: Option<i64>,
+
+        /// Output as CSV (for chargeback reporting)
+        #[arg(long)]
+        csv: bool,
+    },
+
+    /// Tool call counts, failure rates, durations and most edited files
+    Tools {
+        /// Session name, ID, or file path
+        #[arg(required_unless_present = "all")]
+        session: Option<String>,
+
+        /// Report on every indexed session
+        #[arg(long, conflicts_with = "session")]
+        all: bool,
+
+        /// Most edited files to list
+        #[arg(long, default_value = "10")]
+        top_files: usize,
+    },
+
+    /// Chart how a session's context grows turn by turn
+    Usage {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Context window size in tokens (default: the session model's, see
+        /// `context_windows` in the config)
+        #[arg(long)]
+        context_size: Option<u64>,
+    },
+
+    /// Check how full a session's context is, e.g. from a PreToolUse hook
+    ///
+    /// With --hook it reads the hook input on stdin and prints a warning
+    /// Claude Code shows once the threshold is crossed (nothing below it).
+    ContextCheck {
+        /// Session name or ID (default: the hook input's session, else the
+        /// latest session in the current directory)
+        session: Option<String>,
+
+        /// Warn at this percentage of the context window
+        #[arg(long, default_value = "80", value_parser = clap::value_parser!(u8).range(1..=100))]
+        threshold: u8,
+
+        /// Context window size in tokens (default: the session model's, see
+        /// `context_windows` in the config)
+        #[arg(long)]
+        context_size: Option<u64>,
+
+        /// Print Claude Code hook output (a `systemMessage` warning) instead
+        /// of a report
+        #[arg(long)]
+        hook: bool,
+    },
+
+    /// Usage statistics (prompt cache reuse, largest tool payloads, API failures)
+    #[command(subcommand)]
+    Stats(StatsCommand),
+
+    /// Export data to external formats
+    #[command(subcommand)]
+    Export(ExportCommand),
+
+    /// Browse sessions in a web dashboard served by this binary
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value = "8484")]
+        port: u16,
+
+        /// Address to listen on (0.0.0.0 to share on the network)
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+    },
+
+    /// Write a session to an encrypted, expiring bundle for sharing
+    Share {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// How long the bundle can be opened (e.g. 12h, 7d, 2w)
+        #[arg(short, long, default_value = "7d")]
+        expires: String,
+
+        /// Only include these line types (user, assistant, system, ...)
+        #[arg(short, long, value_delimiter = ',')]
+        types: Option<Vec<String>>,
+
+        /// Output file (default: <session>.transcript.age)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// View a bundle created by `share` without indexing it
+    OpenShared {
+        /// Bundle file
+        file: PathBuf,
+
+        /// Passphrase (prompted for if omitted)
+        #[arg(long, env = "TRANSCRIPT_SHARE_PASSPHRASE", hide_env_values = true)]
+        passphrase: Option<String>,
+    },
+
+    /// Share database snapshots between machines (S3, SSH or a shared directory)
+    #[command(subcommand)]
+    Sync(SyncCommand),
+
+    /// Move old sessions to the archive database, keeping them searchable
+    Archive {
+        /// Archive sessions whose last line is older than this (e.g. 90d, 12w)
+        #[arg(long)]
+        older_than: String,
+
+        /// List what would be archived without moving anything
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+
+    /// Rows removed by `index prune` and `index rebuild`, kept until the trash is emptied
+    #[command(subcommand)]
+    Trash(TrashCommand),
+
+    /// List the lines bookmarked in the viewers, with previews
+    Bookmarks {
+        /// Session name, ID, or file path
+        session: String,
+    },
+
+    /// Attach a note to a line (found by search, marked with ✎ in the viewers),
+    /// or reply to, list and remove notes
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Annotate {
+        #[command(subcommand)]
+        command: Option<AnnotateCommand>,
+
+        /// Session name, ID, or file path
+        #[arg(required = true)]
+        session: Option<String>,
+
+        /// Line number
+        #[arg(required = true)]
+        line: Option<i64>,
+
+        /// Note text
+        #[arg(required = true)]
+        text: Option<String>,
+
+        /// Author (default: $USER)
+        #[arg(long)]
+        author: Option<String>,
+    },
+
+    /// Label sessions ("bug-hunt", "release") to find them later with --tag
+    #[command(subcommand)]
+    Tag(TagCommand),
+
+    /// Show or set a session's review status (unreviewed, in-review, approved, flagged)
+    Review {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// New status (omit to show the current one)
+        #[arg(value_enum)]
+        status: Option<ReviewArg>,
+
+        /// Who is reviewing (default: $USER)
+        #[arg(long)]
+        reviewer: Option<String>,
     },
 
     /// Diagnose transcript indexing pipeline
     Doctor,
+
+    /// Browse a session in the interactive viewer (same arguments as transcript-tui-rs)
+    #[command(disable_help_flag = true)]
+    Tui {
+        /// Viewer arguments (see `transcript tui --help`)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// View and query hook events (same commands as hook-events)
+    #[command(subcommand)]
+    Hooks(hook_events_cli::Command),
+
+    /// Tmux helpers: the viewer in a split pane, a status-line snippet
+    #[command(subcommand)]
+    Tmux(TmuxCommand),
+
+    /// Print this project's latest session, turn and context usage for a
+    /// shell prompt (e.g. a Starship custom command); cached, so it is fast
+    PromptSegment {
+        /// Project directory (default: the current directory)
+        #[arg(long)]
+        cwd: Option<PathBuf>,
+
+        /// Context window size in tokens (default: the session model's, see
+        /// `context_windows` in the config)
+        #[arg(long)]
+        context_size: Option<u64>,
+
+        /// Seconds a cached segment is reused even after the index changed
+        #[arg(long, default_value = "10")]
+        max_age: u64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportCommand {
+    /// Write tables as Parquet files (for DuckDB, pandas, polars)
+    #[cfg(feature = "parquet")]
+    Parquet {
+        /// Tables to export (comma-separated)
+        #[arg(short, long, value_enum, value_delimiter = ',', default_value = "lines")]
+        table: Vec<ExportTable>,
+
+        /// Export lines, hooks and sessions
+        #[arg(long, conflicts_with = "table")]
+        all: bool,
+
+        /// Output file, or directory for several tables (default: <table>.parquet
+        /// in the current directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Columns to include (comma-separated, default: all; one table only)
+        #[arg(short, long, value_delimiter = ',')]
+        columns: Option<Vec<String>>,
+
+        /// Filter to a session (name, ID, or partial match)
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Start from this time (ISO format)
+        #[arg(long)]
+        from_time: Option<String>,
+
+        /// End at this time (ISO format)
+        #[arg(long)]
+        to_time: Option<String>,
+    },
+
+    /// Write a consistent copy of the database, for analysis without
+    /// touching the live one
+    Sqlite {
+        /// Output file (default: transcripts-<date>.db)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Render a session as a Markdown document (for PRs and wikis)
+    Markdown {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Leave out tool calls and their results
+        #[arg(long)]
+        no_tools: bool,
+
+        /// Include assistant thinking blocks
+        #[arg(long)]
+        thinking: bool,
+
+        /// Characters of each tool result to keep
+        #[arg(long, default_value = "2000")]
+        max_output: usize,
+
+        /// Quote line annotations under the lines they are on
+        #[arg(long)]
+        annotations: bool,
+    },
+
+    /// Render a session through a Tera template (audit forms, weekly summaries)
+    Template {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Template file; it gets the session, turns, tools, usage and files
+        #[arg(long)]
+        template: PathBuf,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Write a session's lines back out as a transcript JSONL file (e.g. a minimal repro)
+    Jsonl {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only include these line types (user, assistant, system, ...)
+        #[arg(short, long, value_delimiter = ',')]
+        types: Option<Vec<String>>,
+
+        /// Start from this time (ISO format)
+        #[arg(long)]
+        from_time: Option<String>,
+
+        /// End at this time (ISO format)
+        #[arg(long)]
+        to_time: Option<String>,
+
+        /// Turns to include: a..b, a.., ..b or n (default: all)
+        #[arg(long)]
+        turns: Option<String>,
+    },
+
+    /// Send sessions as OpenTelemetry traces (OTLP/HTTP JSON)
+    Otlp {
+        /// Session to export (name, ID, or partial match)
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Export all sessions active in the last N days
+        #[arg(short, long, conflicts_with = "session")]
+        days: Option<i64>,
+
+        /// OTLP/HTTP collector endpoint (e.g. http://localhost:4318)
+        #[arg(short, long)]
+        endpoint: Option<String>,
+
+        /// Extra request header, KEY=VALUE (repeatable)
+        #[arg(long = "header")]
+        headers: Vec<String>,
+
+        /// Write the OTLP JSON payload to a file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// service.name resource attribute
+        #[arg(long, default_value = "claude-code")]
+        service_name: String,
+    },
+
+    /// Send sessions to an LLM observability platform (Langfuse, LangSmith)
+    Observability {
+        /// Target platform
+        #[arg(short = 'P', long, value_enum)]
+        platform: ObservabilityPlatform,
+
+        /// Session to export (name, ID, or partial match)
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Export all sessions active in the last N days
+        #[arg(short, long, conflicts_with = "session")]
+        days: Option<i64>,
+
+        /// API base URL (default: platform cloud; env LANGFUSE_HOST / LANGSMITH_ENDPOINT)
+        #[arg(short, long)]
+        endpoint: Option<String>,
+
+        /// Langfuse public key (env LANGFUSE_PUBLIC_KEY)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Langfuse secret key or LangSmith API key (env LANGFUSE_SECRET_KEY / LANGSMITH_API_KEY)
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// LangSmith project name
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Write the request payload to a file instead of sending it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Checks available to `eval compare`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum RubricCheck {
+    /// Same tools used, without many more calls
+    Tools,
+    /// Same files edited
+    Files,
+    /// Not many more tokens spent
+    Tokens,
+}
+
+/// Review states for `review` and `list --review`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum ReviewArg {
+    Unreviewed,
+    InReview,
+    Approved,
+    Flagged,
+}
+
+impl From<ReviewArg> for transcript_core::ReviewStatus {
+    fn from(arg: ReviewArg) -> Self {
+        match arg {
+            ReviewArg::Unreviewed => transcript_core::ReviewStatus::Unreviewed,
+            ReviewArg::InReview => transcript_core::ReviewStatus::InReview,
+            ReviewArg::Approved => transcript_core::ReviewStatus::Approved,
+            ReviewArg::Flagged => transcript_core::ReviewStatus::Flagged,
+        }
+    }
+}
+
+/// API message formats accepted by `import`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// Anthropic Messages API
+    Anthropic,
+    /// OpenAI Chat Completions
+    #[value(name = "openai")]
+    OpenAi,
+    /// Claude.ai data export (one session per conversation)
+    #[value(name = "claude-ai")]
+    ClaudeAi,
+}
+
+/// LLM observability platforms supported by `export observability`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum ObservabilityPlatform {
+    Langfuse,
+    Langsmith,
+}
+
+/// Session ranking for recall
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum RecallRank {
+    /// Full-text relevance of the best match
+    Bm25,
+    /// Relevance weighted towards recent matches
+    Recency,
+    /// Share of the session's turns that match
+    Density,
+}
+
+/// Cost attribution dimensions
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum CostBy {
+    /// Project (repository, or working directory outside one)
+    Project,
+    /// Project and git branch
+    Branch,
+    /// Model
+    Model,
+    /// Session
+    Session,
+    /// Day
+    Day,
+    /// User (multi-home indexing)
+    User,
+    /// Turn (needs --session or --days)
+    Turn,
+}
+
+/// Formats `prune-turns` exports to
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum PruneFormat {
+    /// Transcript JSONL as Claude Code writes it (to resume or import)
+    ClaudeJsonl,
+    /// Markdown document
+    Markdown,
+}
+
+/// Tables available for export
+#[cfg(feature = "parquet")]
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum ExportTable {
+    /// Transcript lines
+    Lines,
+    /// Hook events
+    Hooks,
+    /// Turns (aggregated from lines)
+    Turns,
+    /// Sessions (one row per transcript file)
+    Sessions,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DbCommand {
+    /// Show how SQLite runs a canned query (or any SQL) with the current
+    /// indexes, flagging table scans; every canned query when none is given
+    Explain {
+        /// Canned query name (search, lines, sessions, ...) or a SQL statement
+        query: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagCommand {
+    /// Add a tag to a session
+    Add {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Tag (no spaces or commas)
+        tag: String,
+    },
+
+    /// Remove a tag from a session
+    Remove {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// Tag
+        tag: String,
+    },
+
+    /// List a session's tags, or every tag with its session count
+    List {
+        /// Session name, ID, or file path
+        session: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AnnotateCommand {
+    /// Reply to an annotation
+    Reply {
+        /// Session name, ID, or file path
+        session: String,
+
+        /// ID of the annotation being answered
+        id: i64,
+
+        /// Reply text
+        text: String,
+
+        /// Author (default: $USER)
+        #[arg(long)]
+        author: Option<String>,
+    },
+
+    /// List a session's annotations as threads, by line
+    List {
+        /// Session name, ID, or file path
+        session: String,
+    },
+
+    /// Remove an annotation and its replies
+    Remove {
+        /// Annotation ID
+        id: i64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SyncCommand {
+    /// Upload a snapshot of the local database
+    Push {
+        /// Remote: s3://bucket/prefix, [user@]host:/path, or a directory
+        #[arg(short, long, env = "TRANSCRIPT_SYNC_REMOTE")]
+        remote: String,
+
+        /// Snapshot name for this machine (default: <hostname>-<user>)
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+
+    /// Download other machines' snapshots and merge them into the local database
+    Pull {
+        /// Remote: s3://bucket/prefix, [user@]host:/path, or a directory
+        #[arg(short, long, env = "TRANSCRIPT_SYNC_REMOTE")]
+        remote: String,
+
+        /// Snapshot name for this machine, skipped when pulling
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Keep snapshots separate for `--team` queries instead of merging
+        #[arg(long)]
+        federate: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TmuxCommand {
+    /// Open the viewer in a pane beside the Claude Code pane of this window
+    Open {
+        /// Session name, ID, or "." for the latest one in the Claude Code pane's directory
+        #[arg(default_value = ".")]
+        session: String,
+
+        /// Split below instead of beside
+        #[arg(long)]
+        below: bool,
+
+        /// Size of the new pane, in percent
+        #[arg(long, default_value = "40", value_parser = clap::value_parser!(u8).range(10..=90))]
+        size: u8,
+    },
+
+    /// Print the latest session's name and context usage, for status-right
+    ///
+    /// Plain text unless --json or --format json is given; over 80% and 90%
+    /// it is colored with tmux style tags (none with --no-color).
+    Status {
+        /// Directory whose latest session is shown (tmux: "#{pane_current_path}")
+        #[arg(long)]
+        cwd: Option<PathBuf>,
+
+        /// Context window size in tokens (default: the session model's, see
+        /// `context_windows` in the config)
+        #[arg(long)]
+        context_size: Option<u64>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrashCommand {
+    /// List bundles of removed rows, newest first
+    List,
+
+    /// Put a bundle's rows back into the index
+    Restore {
+        /// Bundle ID (or a unique prefix) from `trash list`
+        id: String,
+    },
+
+    /// Delete bundles for good
+    Empty {
+        /// Only bundles older than this many days
+        #[arg(long)]
+        older_than: Option<u32>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EvalCommand {
+    /// Check a re-run's behavior against a golden session (fails when a check fails)
+    Compare {
+        /// Session name or ID of the known-good run
+        #[arg(long)]
+        golden: String,
+
+        /// Session name or ID of the run being checked
+        #[arg(long)]
+        candidate: String,
+
+        /// Checks to run
+        #[arg(long, value_enum, value_delimiter = ',', default_value = "tools,files,tokens")]
+        rubric: Vec<RubricCheck>,
+
+        /// Allowed increase in tool calls and tokens, in percent
+        #[arg(long, default_value = "20")]
+        tolerance: f64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StatsCommand {
+    /// Prompt cache writes vs reads, flagging groups that rarely reuse their cache
+    Cache {
+        /// Dimension to group by
+        #[arg(short, long, value_enum, default_value = "session")]
+        by: CacheBy,
+
+        /// Only include usage from this session (name, ID, or partial match)
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Only include usage from the last N days
+        #[arg(short, long)]
+        days: Option<i64>,
+
+        /// Flag groups reading back fewer cached tokens than this per token written
+        #[arg(long, default_value = "1.0")]
+        min_reuse: f64,
+    },
+
+    /// Largest tool inputs and outputs recorded at index time
+    Payloads {
+        /// Number of payloads to list
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// Only include payloads from this session (name, ID, or partial match)
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Only include payloads of this tool (e.g. Read, Bash)
+        #[arg(short, long)]
+        tool: Option<String>,
+    },
+
+    /// API errors, rate limits and overloads by type and time of day
+    Errors {
+        /// Only include failures from the last N days
+        #[arg(short, long, default_value = "7")]
+        days: i64,
+
+        /// Only include failures from this session (name, ID, or partial match)
+        #[arg(short, long)]
+        session: Option<String>,
+    },
+}
+
+/// Cache statistics dimensions
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum CacheBy {
+    /// Session
+    Session,
+    /// Project (repository, or working directory outside one)
+    Project,
+    /// Model
+    Model,
+    /// Day
+    Day,
 }
 
 #[derive(Subcommand, Debug)]
@@ -218,6 +1263,45 @@ pub enum IndexCommand {
     /// Clear and rebuild the entire index
     Rebuild,
 
+    /// Remove rows of transcript and hook files deleted or rotated on disk
+    Prune {
+        /// Only list the files that would be pruned
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+
+    /// Check the database and search indexes, refresh statistics and reclaim free space
+    Maintain,
+
+    /// Review files and lines that failed to index
+    Errors {
+        /// Empty the log
+        #[arg(long, conflicts_with = "retry")]
+        clear: bool,
+
+        /// Index the failed files again, dropping the entries of those that succeed
+        #[arg(long)]
+        retry: bool,
+
+        /// Most recent entries to show
+        #[arg(short = 'n', long, default_value = "50")]
+        limit: i64,
+    },
+
+    /// Encrypt the index, its archive and the trash with the database key
+    /// (TRANSCRIPT_DB_KEY or encryption.key_command; needs the encryption feature)
+    Encrypt,
+
+    /// Decrypt the index, its archive and the trash back to plain SQLite
+    Decrypt,
+
     /// Watch for file changes and auto-index (foreground daemon)
     Watch,
+
+    /// Index JSONL piped on stdin as it arrives (e.g. from an Anthropic SDK wrapper)
+    Stdin {
+        /// Session ID to file the lines under
+        #[arg(long)]
+        session_id: String,
+    },
 }
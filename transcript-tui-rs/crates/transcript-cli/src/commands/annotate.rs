@@ -0,0 +1,231 @@
+//! Annotate command - notes on lines of a session, with threaded replies
+
+use anyhow::{bail, Result};
+use serde_json::json;
+use transcript_db::{annotation_threads, Annotation, GetLinesOptions, TranscriptDb};
+use transcript_indexer::IndexerDb;
+
+use crate::cli::{AnnotateCommand, Cli, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors;
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session: &str,
+    line: i64,
+    text: &str,
+    author: Option<&str>,
+) -> Result<()> {
+    let text = validate_text(text)?;
+    let session_id = resolve_session_id(db, session)?;
+    let options = GetLinesOptions {
+        from_line: Some(line),
+        to_line: Some(line),
+        ..GetLinesOptions::for_session(&session_id)
+    };
+    let Some(target) = db.get_lines(&options)?.into_iter().next() else {
+        bail!("No line {} in session {}", line, session_id);
+    };
+
+    // Annotations are written through the indexer, which owns all writes
+    let indexer_db = IndexerDb::open_or_create(db.path())?;
+    let id = transcript_indexer::add_annotation(
+        indexer_db.connection(),
+        &session_id,
+        &target.uuid,
+        target.line_number,
+        &author_or_default(author),
+        text,
+        None,
+    )?;
+    report(cli, id, &session_id, target.line_number, None)
+}
+
+/// Reply to, list or remove annotations
+pub fn run_command(cli: &Cli, db: &TranscriptDb, command: &AnnotateCommand) -> Result<()> {
+    match command {
+        AnnotateCommand::Reply {
+            session,
+            id,
+            text,
+            author,
+        } => {
+            let text = validate_text(text)?;
+            let session_id = resolve_session_id(db, session)?;
+            let indexer_db = IndexerDb::open_or_create(db.path())?;
+            let reply = transcript_indexer::add_annotation(
+                indexer_db.connection(),
+                &session_id,
+                "",
+                0,
+                &author_or_default(author.as_deref()),
+                text,
+                Some(*id),
+            )?;
+            let line = db
+                .get_annotations(&session_id)?
+                .iter()
+                .find(|a| a.id == reply)
+                .map_or(0, |a| a.line_number);
+            report(cli, reply, &session_id, line, Some(*id))
+        }
+        AnnotateCommand::List { session } => {
+            let session_id = resolve_session_id(db, session)?;
+            let annotations = db.get_annotations(&session_id)?;
+            list(cli, session, &annotations)
+        }
+        AnnotateCommand::Remove { id } => {
+            let indexer_db = IndexerDb::open_or_create(db.path())?;
+            let removed = transcript_indexer::remove_annotation(indexer_db.connection(), *id)?;
+            match cli.effective_format() {
+                OutputFormat::Human => {
+                    if removed == 0 {
+                        println!("No annotation {}", id);
+                    } else {
+                        println!(
+                            "{}",
+                            colors::success(&format!("Removed annotation {} ({} with replies)", id, removed))
+                        );
+                    }
+                }
+                OutputFormat::Json => print_json(cli, &json!({ "id": id, "removed": removed }))?,
+                OutputFormat::Minimal => println!("{}", removed),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn validate_text(text: &str) -> Result<&str> {
+    let text = text.trim();
+    if text.is_empty() {
+        bail!("Annotation text is empty");
+    }
+    Ok(text)
+}
+
+/// The given author, else the login name
+pub fn author_or_default(author: Option<&str>) -> String {
+    author
+        .map(str::to_string)
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .filter(|a| !a.trim().is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn report(cli: &Cli, id: i64, session_id: &str, line: i64, reply_to: Option<i64>) -> Result<()> {
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            let message = match reply_to {
+                Some(parent) => format!("Replied to annotation {} on line {} (id {})", parent, line, id),
+                None => format!("Annotated line {} of {} (id {})", line, session_id, id),
+            };
+            println!("{}", colors::success(&message));
+        }
+        OutputFormat::Json => print_json(
+            cli,
+            &json!({ "id": id, "sessionId": session_id, "lineNumber": line, "parentId": reply_to }),
+        )?,
+        OutputFormat::Minimal => println!("{}", id),
+    }
+    Ok(())
+}
+
+fn list(cli: &Cli, session: &str, annotations: &[Annotation]) -> Result<()> {
+    let threads = annotation_threads(annotations);
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if threads.is_empty() {
+                println!(
+                    "No annotations in {} (add one with: transcript annotate <session> <line> <text>)",
+                    session
+                );
+                return Ok(());
+            }
+            println!(
+                "{}",
+                colors::header(&format!("Annotations in {} ({})", session, threads.len()))
+            );
+            let mut current_line = None;
+            for (depth, annotation) in threads {
+                if current_line != Some(annotation.line_number) {
+                    current_line = Some(annotation.line_number);
+                    println!();
+                    println!("  {}", colors::colored_line_num(annotation.line_number));
+                }
+                let indent = "  ".repeat(depth + 2);
+                println!(
+                    "{}{} {} {}",
+                    indent,
+                    colors::label(&format!("#{}", annotation.id)),
+                    annotation.author,
+                    colors::label(&annotation.created_at.get(..16).unwrap_or_default().replace('T', " "))
+                );
+                for text in annotation.body.lines() {
+                    println!("{}  {}", indent, text);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let value: Vec<_> = threads
+                .iter()
+                .map(|(depth, a)| {
+                    json!({
+                        "id": a.id,
+                        "sessionId": a.session_id,
+                        "uuid": a.uuid,
+                        "lineNumber": a.line_number,
+                        "parentId": a.parent_id,
+                        "depth": depth,
+                        "author": a.author,
+                        "body": a.body,
+                        "createdAt": a.created_at,
+                    })
+                })
+                .collect();
+            print_json(cli, &json!(value))?;
+        }
+        OutputFormat::Minimal => {
+            for (_, a) in threads {
+                println!("{}\t{}\t{}\t{}", a.id, a.line_number, a.author, a.body.replace('\n', " "));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_json(cli: &Cli, value: &serde_json::Value) -> Result<()> {
+    if cli.pretty {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", serde_json::to_string(value)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::cli::{AnnotateCommand, Cli, Command};
+
+    #[test]
+    fn test_annotate_arguments() {
+        // A note on a line, as before replies existed
+        let cli = Cli::try_parse_from(["transcript", "annotate", "happy-dog", "42", "Why Bash?"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Annotate { command: None, line: Some(42), .. }
+        ));
+
+        let cli = Cli::try_parse_from(["transcript", "annotate", "reply", "happy-dog", "1", "Denied"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Annotate { command: Some(AnnotateCommand::Reply { id: 1, .. }), .. }
+        ));
+
+        assert!(Cli::try_parse_from(["transcript", "annotate", "happy-dog", "42"]).is_err());
+    }
+}
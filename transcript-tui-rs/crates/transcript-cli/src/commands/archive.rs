@@ -0,0 +1,114 @@
+//! Archive command - move old sessions to the archive database
+//!
+//! The rows move to `archive.db` beside the index (see
+//! `transcript_indexer::archive`), where `search --include-archive` still
+//! finds them.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::json;
+use transcript_db::{archive_db_path, default_db_path};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::index::open_indexer;
+use crate::commands::parse_duration;
+use crate::output::colors;
+
+pub fn run(cli: &Cli, older_than: &str, dry_run: bool) -> Result<()> {
+    let before = Utc::now()
+        .checked_sub_signed(parse_duration(older_than)?)
+        .with_context(|| format!("--older-than {} reaches back further than a date can", older_than))?;
+    let db_path = cli.db_path.clone().unwrap_or_else(default_db_path);
+    let archive_path = archive_db_path(&db_path);
+
+    let indexer_db = open_indexer(cli)?;
+    let conn = indexer_db.connection();
+    let candidates = transcript_indexer::find_archivable(conn, before)?;
+    let moved = if dry_run {
+        None
+    } else {
+        Some(transcript_indexer::archive_sessions(conn, &archive_path, &candidates)?)
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if candidates.is_empty() {
+                println!("No sessions older than {}", older_than);
+                return Ok(());
+            }
+            match moved {
+                None => {
+                    let lines: i64 = candidates.iter().map(|c| c.line_count).sum();
+                    println!(
+                        "{}",
+                        colors::header(&format!(
+                            "Would archive {} sessions ({} lines) to {}",
+                            candidates.len(),
+                            lines,
+                            archive_path.display()
+                        ))
+                    );
+                    println!();
+                    for candidate in &candidates {
+                        println!(
+                            "  {}  {}  {} lines",
+                            colors::value(candidate.slug.as_deref().unwrap_or(&candidate.session_id)),
+                            candidate.last_timestamp.get(..10).unwrap_or(&candidate.last_timestamp),
+                            candidate.line_count
+                        );
+                    }
+                }
+                Some(result) => {
+                    println!(
+                        "{} {} sessions, {} lines, {} hook events to {}",
+                        colors::success("Archived:"),
+                        result.sessions,
+                        result.lines,
+                        result.hook_events,
+                        archive_path.display()
+                    );
+                    println!(
+                        "{}",
+                        colors::label("Search them with: transcript search --include-archive <query>")
+                    );
+                    println!(
+                        "{}",
+                        colors::label("Return the freed space with: transcript index maintain")
+                    );
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let sessions: Vec<serde_json::Value> = candidates
+                .iter()
+                .map(|c| {
+                    json!({
+                        "session_id": c.session_id,
+                        "slug": c.slug,
+                        "file_path": c.file_path,
+                        "last_timestamp": c.last_timestamp,
+                        "lines": c.line_count,
+                    })
+                })
+                .collect();
+            let value = json!({
+                "archive": archive_path.to_string_lossy(),
+                "dry_run": dry_run,
+                "sessions": sessions,
+                "moved": moved.map(|r| json!({
+                    "sessions": r.sessions,
+                    "lines": r.lines,
+                    "hook_files": r.hook_files,
+                    "hook_events": r.hook_events,
+                })),
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                println!("{}", serde_json::to_string(&value)?);
+            }
+        }
+        OutputFormat::Minimal => println!("{}", candidates.len()),
+    }
+    Ok(())
+}
@@ -0,0 +1,376 @@
+//! Blame command - which session and turn wrote each line of a file
+//!
+//! `git blame` gives each line's commit; the file's Edit, MultiEdit and Write
+//! calls (see `file_history`) give the text each session added. A line is
+//! attributed to the latest call that added its text no later than its
+//! commit (any time for uncommitted lines). Lines without letters or digits
+//! (`}`, blank lines) match too many calls to tell apart, so they follow the
+//! line above when that call added them too. Calls on the same file in
+//! other checkouts of the repository count when none were made on this one.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use transcript_core::{path_key, HookEventFilter, EDIT_TOOLS};
+use transcript_db::TranscriptDb;
+
+use super::changelog::git;
+use super::file_history::{file_changes, Change, FileChange};
+use crate::cli::{Cli, OutputFormat};
+use crate::output::colors;
+
+/// Characters of a session's name shown per line
+const SESSION_WIDTH: usize = 20;
+
+/// A line of `git blame --line-porcelain`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlamedLine {
+    pub line: usize,
+    /// None for lines not committed yet
+    pub commit: Option<String>,
+    pub author: String,
+    pub committed_at: Option<DateTime<Utc>>,
+    pub content: String,
+}
+
+/// The call a line came from
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Origin {
+    pub session_id: String,
+    pub session_name: Option<String>,
+    pub turn: Option<i64>,
+    pub timestamp: String,
+    pub tool: String,
+}
+
+/// A line with its commit and, when found, the call that wrote it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlameLine {
+    pub line: usize,
+    pub commit: Option<String>,
+    pub author: String,
+    pub content: String,
+    pub origin: Option<Origin>,
+}
+
+pub fn run(cli: &Cli, db: &TranscriptDb, file: &Path, range: Option<&str>) -> Result<()> {
+    // One spelling for both, so `\\?\C:\repo\x` is under git's `C:/repo` on Windows
+    let path = PathBuf::from(path_key(
+        &file.canonicalize().with_context(|| format!("Can't read {}", file.display()))?,
+    ));
+    let dir = path.parent().unwrap_or(Path::new("/"));
+    let root = PathBuf::from(path_key(Path::new(git(dir, &["rev-parse", "--show-toplevel"])?.trim())));
+    let relative = path.strip_prefix(&root).unwrap_or(&path).to_string_lossy().to_string();
+
+    let mut args = vec!["blame".to_string(), "--line-porcelain".to_string()];
+    if let Some(range) = range {
+        args.push("-L".to_string());
+        args.push(line_range(range)?);
+    }
+    args.extend(["--".to_string(), relative.clone()]);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let blamed = parse_blame(&git(&root, &args)?);
+
+    let lines = db.get_tool_lines(None)?;
+    let events = db.get_hook_events(&HookEventFilter {
+        event_types: Some(vec!["PostToolUse".to_string()]),
+        tool_names: Some(EDIT_TOOLS.iter().map(|(name, _)| name.to_string()).collect()),
+        ..Default::default()
+    })?;
+    let mut changes = file_changes(&lines, &events, &relative);
+    let absolute = path.to_string_lossy();
+    if changes.iter().any(|c| c.path == absolute) {
+        changes.retain(|c| c.path == absolute);
+    }
+    let blame = attribute(&blamed, &changes);
+
+    match cli.effective_format() {
+        OutputFormat::Human => print_human(&blame, &relative, range),
+        OutputFormat::Json => {
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&blame)?);
+            } else {
+                println!("{}", serde_json::to_string(&blame)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for line in &blame {
+                let origin = line.origin.as_ref();
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    line.line,
+                    line.commit.as_deref().unwrap_or("-"),
+                    origin.map_or("-", |o| o.session_id.as_str()),
+                    origin.and_then(|o| o.turn).map_or("-".to_string(), |t| t.to_string())
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `10-20`, `10,20` or `10` as git's `-L 10,20`
+fn line_range(range: &str) -> Result<String> {
+    let (start, end) = range.split_once(['-', ',']).unwrap_or((range, range));
+    match (start.trim().parse::<usize>(), end.trim().parse::<usize>()) {
+        (Ok(start), Ok(end)) if start >= 1 && start <= end => Ok(format!("{},{}", start, end)),
+        _ => bail!("Invalid line range '{}' (expected e.g. 42 or 10-20)", range),
+    }
+}
+
+/// Parse `git blame --line-porcelain` output
+pub fn parse_blame(output: &str) -> Vec<BlamedLine> {
+    let mut blamed = Vec::new();
+    let mut current: Option<BlamedLine> = None;
+    for line in output.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            if let Some(mut entry) = current.take() {
+                entry.content = content.to_string();
+                blamed.push(entry);
+            }
+            continue;
+        }
+        let Some(entry) = current.as_mut() else {
+            // Header: <hash> <original line> <final line> [<lines in group>]
+            let mut fields = line.split_whitespace();
+            let (Some(hash), Some(_), Some(Ok(final_line))) =
+                (fields.next(), fields.next(), fields.next().map(str::parse::<usize>))
+            else {
+                continue;
+            };
+            current = Some(BlamedLine {
+                line: final_line,
+                commit: Some(hash.to_string()).filter(|h| h.chars().any(|c| c != '0')),
+                author: String::new(),
+                committed_at: None,
+                content: String::new(),
+            });
+            continue;
+        };
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "author" => entry.author = value.to_string(),
+            "committer-time" if entry.commit.is_some() => {
+                entry.committed_at = value.parse().ok().and_then(|t| Utc.timestamp_opt(t, 0).single());
+            }
+            _ => {}
+        }
+    }
+    blamed
+}
+
+/// Trimmed lines a call added to the file
+fn added_lines(change: &FileChange) -> HashSet<&str> {
+    match &change.change {
+        Change::Edit { edits } => edits
+            .iter()
+            .flat_map(|(old, new, _)| {
+                let old: HashSet<&str> = old.lines().map(str::trim).collect();
+                new.lines().map(str::trim).filter(move |l| !old.contains(l))
+            })
+            .collect(),
+        Change::Write { content } => {
+            let before: HashSet<&str> = change.before.iter().flat_map(|b| b.lines().map(str::trim)).collect();
+            content.lines().map(str::trim).filter(|l| !before.contains(l)).collect()
+        }
+    }
+}
+
+/// The call each blamed line came from, from `changes` (oldest first)
+pub fn attribute(blamed: &[BlamedLine], changes: &[FileChange]) -> Vec<BlameLine> {
+    let added: Vec<HashSet<&str>> = changes.iter().map(added_lines).collect();
+    let times: Vec<Option<DateTime<Utc>>> = changes
+        .iter()
+        .map(|c| DateTime::parse_from_rfc3339(&c.timestamp).ok().map(|t| t.with_timezone(&Utc)))
+        .collect();
+
+    let mut out = Vec::with_capacity(blamed.len());
+    let mut previous: Option<usize> = None;
+    for line in blamed {
+        let text = line.content.trim();
+        let before_commit = |i: usize| match (line.commit.as_ref(), line.committed_at, times[i]) {
+            (None, _, _) => true,
+            (Some(_), Some(committed), Some(at)) => at <= committed,
+            _ => false,
+        };
+        let found = if text.chars().any(char::is_alphanumeric) {
+            (0..changes.len()).rev().find(|&i| before_commit(i) && added[i].contains(text))
+        } else {
+            previous.filter(|&i| added[i].contains(text))
+        };
+        previous = found;
+
+        out.push(BlameLine {
+            line: line.line,
+            commit: line.commit.clone(),
+            author: line.author.clone(),
+            content: line.content.clone(),
+            origin: found.map(|i| {
+                let change = &changes[i];
+                Origin {
+                    session_id: change.session_id.clone(),
+                    session_name: change.session_name.clone(),
+                    turn: change.turn,
+                    timestamp: change.timestamp.clone(),
+                    tool: change.tool.clone(),
+                }
+            }),
+        });
+    }
+    out
+}
+
+fn print_human(blame: &[BlameLine], path: &str, range: Option<&str>) {
+    // Lines per session, and the turns they came from
+    let mut sessions: HashMap<&str, (&Origin, usize, BTreeSet<i64>)> = HashMap::new();
+    for origin in blame.iter().filter_map(|l| l.origin.as_ref()) {
+        let entry = sessions.entry(origin.session_id.as_str()).or_insert((origin, 0, BTreeSet::new()));
+        entry.1 += 1;
+        entry.2.extend(origin.turn);
+    }
+    let attributed: usize = sessions.values().map(|(_, n, _)| n).sum();
+
+    let title = match range {
+        Some(range) => format!("{}:{}", path, range),
+        None => path.to_string(),
+    };
+    println!(
+        "{}",
+        colors::header(&format!(
+            "Blame of {} ({} of {} lines from {} sessions)",
+            title,
+            attributed,
+            blame.len(),
+            sessions.len()
+        ))
+    );
+    println!();
+
+    let width = blame.last().map_or(1, |l| l.line.to_string().len());
+    for line in blame {
+        let commit = line.commit.as_deref().map_or("uncommitted".to_string(), |c| c[..c.len().min(8)].to_string());
+        let (session, turn) = match &line.origin {
+            Some(origin) => {
+                let name: String = origin
+                    .session_name
+                    .as_deref()
+                    .unwrap_or(&origin.session_id)
+                    .chars()
+                    .take(SESSION_WIDTH)
+                    .collect();
+                let turn = origin.turn.map_or(String::new(), |t| format!("turn {}", t));
+                (colors::colored_session(&format!("{:<w$}", name, w = SESSION_WIDTH)), turn)
+            }
+            None => (colors::label(&format!("{:<w$}", "-", w = SESSION_WIDTH)), String::new()),
+        };
+        println!(
+            "{:>width$} {} {} {:<8} {} {}",
+            line.line,
+            colors::label(&format!("{:<11}", commit)),
+            session,
+            turn,
+            colors::label("│"),
+            line.content,
+            width = width
+        );
+    }
+
+    if sessions.is_empty() {
+        return;
+    }
+    println!();
+    let mut sessions: Vec<_> = sessions.into_values().collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.1));
+    for (origin, count, turns) in sessions {
+        let turns: Vec<String> = turns.iter().map(|t| t.to_string()).collect();
+        let turns = if turns.is_empty() {
+            String::new()
+        } else {
+            format!(", turns {}", turns.join(", "))
+        };
+        println!(
+            "  {} {} {}",
+            colors::colored_session(origin.session_name.as_deref().unwrap_or(&origin.session_id)),
+            colors::label(&origin.session_id),
+            colors::value(&format!("{} lines{}", count, turns))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PORCELAIN: &str = "\
+1111111111111111111111111111111111111111 1 1 2
+author Ada
+author-time 1767261600
+committer-time 1767265200
+summary Add schema
+filename src/schema.rs
+\tpub const DB_VERSION: i32 = 2;
+1111111111111111111111111111111111111111 2 2
+author Ada
+committer-time 1767265200
+filename src/schema.rs
+\t}
+0000000000000000000000000000000000000000 3 3 1
+author Not Committed Yet
+committer-time 1767272400
+filename src/schema.rs
+\tfn migrate() {}
+";
+
+    fn change(timestamp: &str, session: &str, old: &str, new: &str) -> FileChange {
+        FileChange {
+            session_id: session.to_string(),
+            session_name: None,
+            timestamp: timestamp.to_string(),
+            turn: Some(3),
+            tool: "Edit".to_string(),
+            path: "/app/src/schema.rs".to_string(),
+            change: Change::Edit {
+                edits: vec![(old.to_string(), new.to_string(), false)],
+            },
+            before: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_blame_porcelain() {
+        let blamed = parse_blame(PORCELAIN);
+        assert_eq!(blamed.len(), 3);
+        assert_eq!(blamed[0].commit.as_deref(), Some("1111111111111111111111111111111111111111"));
+        assert_eq!(blamed[0].committed_at.map(|t| t.timestamp()), Some(1767265200));
+        assert_eq!((blamed[1].line, blamed[1].content.as_str()), (2, "}"));
+        assert_eq!((blamed[2].commit.as_ref(), blamed[2].committed_at), (None, None));
+        assert_eq!(line_range("10-20").unwrap(), "10,20");
+        assert!(line_range("20-10").is_err());
+    }
+
+    #[test]
+    fn test_lines_go_to_the_latest_call_before_their_commit() {
+        // Commit at 11:00; the s3 edit came after it, so it can't be the source
+        let changes = vec![
+            change("2026-01-01T09:00:00Z", "s1", "", "pub const DB_VERSION: i32 = 2;\n}"),
+            change("2026-01-01T10:00:00Z", "s2", "DB_VERSION: i32 = 1;", "pub const DB_VERSION: i32 = 2;"),
+            change("2026-01-01T12:00:00Z", "s3", "x", "pub const DB_VERSION: i32 = 2;\nfn migrate() {}"),
+        ];
+        let blame = attribute(&parse_blame(PORCELAIN), &changes);
+        let sessions: Vec<Option<&str>> = blame
+            .iter()
+            .map(|l| l.origin.as_ref().map(|o| o.session_id.as_str()))
+            .collect();
+        // The brace follows its line only when the same call added it
+        assert_eq!(sessions, [Some("s2"), None, Some("s3")]);
+
+        let blame = attribute(&parse_blame(PORCELAIN), &changes[..1]);
+        assert_eq!(blame[1].origin.as_ref().map(|o| o.session_id.as_str()), Some("s1"));
+    }
+}
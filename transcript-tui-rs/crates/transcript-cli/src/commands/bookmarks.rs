@@ -0,0 +1,67 @@
+//! Bookmarks command - list lines bookmarked in the viewers
+
+use anyhow::Result;
+use serde_json::json;
+use transcript_db::TranscriptDb;
+
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors;
+
+/// Characters of each line shown as a preview
+const PREVIEW_CHARS: usize = 100;
+
+pub fn run(cli: &Cli, db: &TranscriptDb, session: &str) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    let lines = db.get_bookmarked_lines(&session_id)?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if lines.is_empty() {
+                println!("No bookmarks in {} (press Space in the viewer to add one)", session);
+            } else {
+                println!(
+                    "{}",
+                    colors::header(&format!("Bookmarks in {} ({})", session, lines.len()))
+                );
+                println!();
+                for line in &lines {
+                    println!(
+                        "  ★ {} {} {} {}",
+                        colors::colored_line_num(line.line_number),
+                        colors::colored_time(&line.timestamp),
+                        colors::colored_type(line.line_type),
+                        line.preview(PREVIEW_CHARS)
+                    );
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let value: Vec<_> = lines
+                .iter()
+                .map(|line| {
+                    json!({
+                        "sessionId": line.session_id,
+                        "uuid": line.uuid,
+                        "lineNumber": line.line_number,
+                        "type": line.line_type.to_string(),
+                        "timestamp": line.timestamp,
+                        "preview": line.preview(PREVIEW_CHARS),
+                    })
+                })
+                .collect();
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                println!("{}", serde_json::to_string(&value)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for line in &lines {
+                println!("{}\t{}", line.line_number, line.preview(PREVIEW_CHARS));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,356 @@
+//! Changelog command - draft release notes from the sessions behind a commit range
+//!
+//! The git tracker hook records the HEAD commit a session was working on.
+//! Sessions that saw a commit in the range (or the range's base, where the
+//! first change started) are summarized by their goal, the files they edited
+//! and the commits they saw; commits no session saw are listed on their own.
+//! Each session also gets a short summary from the configured summarizer.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use transcript_core::{
+    edited_file, summarizer_from_config, Config, ExtractiveSummarizer, LineType, Summarizer,
+    TranscriptLine,
+};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use super::export::markdown::plain_text;
+use super::export::trace::is_prompt;
+use crate::cli::{Cli, OutputFormat};
+
+/// Characters kept from a session's first prompt
+const MAX_GOAL_CHARS: usize = 100;
+
+/// Characters of assistant text handed to the summarizer (the latest are kept)
+const MAX_SUMMARY_INPUT_CHARS: usize = 20_000;
+
+/// Characters in a session summary
+const MAX_SUMMARY_CHARS: usize = 300;
+
+/// Shortest recorded hash prefix trusted to name a commit
+const MIN_HASH_LEN: usize = 7;
+
+/// A commit from `git log`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Commit {
+    pub hash: String,
+    pub subject: String,
+    pub date: String,
+}
+
+/// A session's part of the changelog
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionEntry {
+    session_id: String,
+    name: String,
+    goal: String,
+    summary: Option<String>,
+    started: String,
+    files: Vec<String>,
+    commits: Vec<Commit>,
+    uri: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Changelog {
+    since: String,
+    until: String,
+    sessions: Vec<SessionEntry>,
+    other_commits: Vec<Commit>,
+}
+
+pub fn run(cli: &Cli, db: &TranscriptDb, repo: &Path, since: &str, until: &str) -> Result<()> {
+    let root = PathBuf::from(git(repo, &["rev-parse", "--show-toplevel"])?.trim());
+    let base = git(repo, &["rev-parse", &format!("{}^{{commit}}", since)])?
+        .trim()
+        .to_string();
+    let commits = parse_log(&git(
+        repo,
+        &["log", "--format=%H%x1f%s%x1f%aI", &format!("{}..{}", since, until)],
+    )?);
+
+    // Every hash a session recorded, matched to the range or its base
+    let observed = observed_hashes(db)?;
+    let mut by_session: HashMap<String, Vec<Commit>> = HashMap::new();
+    for (session_id, hash) in &observed {
+        if hash_matches(hash, &base) {
+            by_session.entry(session_id.clone()).or_default();
+        }
+        if let Some(commit) = commits.iter().find(|c| hash_matches(hash, &c.hash)) {
+            let list = by_session.entry(session_id.clone()).or_default();
+            if !list.contains(commit) {
+                list.push(commit.clone());
+            }
+        }
+    }
+
+    let summarizer: Box<dyn Summarizer> = match summarizer_from_config(&Config::load().summarizer) {
+        Ok(summarizer) => summarizer,
+        Err(e) => {
+            eprintln!("Warning: {}; using the extractive summarizer", e);
+            Box::new(ExtractiveSummarizer)
+        }
+    };
+
+    let mut sessions = Vec::new();
+    for (session_id, mut session_commits) in by_session {
+        let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+        if lines.is_empty() {
+            continue;
+        }
+        // git log is newest first; list a session's commits oldest first
+        session_commits.sort_by(|a, b| a.date.cmp(&b.date));
+        let mut entry = session_entry(&session_id, &lines, &root, session_commits, db)?;
+        entry.summary = summarize_session(summarizer.as_ref(), &session_id, &lines);
+        sessions.push(entry);
+    }
+    sessions.sort_by(|a, b| a.started.cmp(&b.started));
+
+    // Hashes recorded by sessions that are no longer indexed don't count
+    let seen: BTreeSet<&str> = sessions
+        .iter()
+        .flat_map(|s| s.commits.iter().map(|c| c.hash.as_str()))
+        .collect();
+    let other_commits = commits
+        .iter()
+        .filter(|c| !seen.contains(c.hash.as_str()))
+        .cloned()
+        .collect();
+
+    let changelog = Changelog {
+        since: since.to_string(),
+        until: until.to_string(),
+        sessions,
+        other_commits,
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Json => {
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&changelog)?);
+            } else {
+                println!("{}", serde_json::to_string(&changelog)?);
+            }
+        }
+        OutputFormat::Human | OutputFormat::Minimal => print!("{}", render(&changelog)),
+    }
+
+    Ok(())
+}
+
+/// Run git in `repo` and return its stdout
+pub fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to run 'git' (is it installed and on PATH?)")?;
+    if !output.status.success() {
+        bail!(
+            "'git {}' failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `git log --format=%H%x1f%s%x1f%aI` output
+pub fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                date: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Whether a recorded (possibly abbreviated) hash names `full`
+pub fn hash_matches(recorded: &str, full: &str) -> bool {
+    recorded.len() >= MIN_HASH_LEN && full.starts_with(recorded)
+}
+
+/// Distinct (session, hash) pairs from hook events and transcript lines
+fn observed_hashes(db: &TranscriptDb) -> Result<Vec<(String, String)>> {
+    let mut stmt = db.connection().prepare(
+        "SELECT DISTINCT session_id, git_hash FROM hook_events WHERE git_hash IS NOT NULL
+         UNION
+         SELECT DISTINCT session_id, git_hash FROM lines WHERE git_hash IS NOT NULL",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+fn session_entry(
+    session_id: &str,
+    lines: &[TranscriptLine],
+    root: &Path,
+    commits: Vec<Commit>,
+    db: &TranscriptDb,
+) -> Result<SessionEntry> {
+    let goal = lines
+        .iter()
+        .find(|l| is_prompt(l))
+        .and_then(|l| l.message())
+        .map(|m| plain_text(m.content.as_text().trim()))
+        .unwrap_or_default();
+    let goal = goal.lines().next().unwrap_or("").to_string();
+    let goal = if goal.chars().count() > MAX_GOAL_CHARS {
+        format!("{}…", goal.chars().take(MAX_GOAL_CHARS).collect::<String>())
+    } else {
+        goal
+    };
+
+    let mut files = BTreeSet::new();
+    for line in lines {
+        let Some(msg) = line.message() else {
+            continue;
+        };
+        for (_, name, input) in msg.content.tool_uses() {
+            if let Some(path) = edited_file(name, input) {
+                files.insert(relative_to(Path::new(path), root));
+            }
+        }
+    }
+
+    let uri: Option<String> = db
+        .connection()
+        .query_row(
+            "SELECT file_path FROM lines WHERE session_id = ?1 LIMIT 1",
+            [session_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    Ok(SessionEntry {
+        session_id: session_id.to_string(),
+        name: lines
+            .iter()
+            .find_map(|l| l.session_name.clone().or_else(|| l.slug.clone()))
+            .unwrap_or_else(|| session_id.to_string()),
+        goal,
+        summary: None,
+        started: lines.first().map(|l| l.timestamp.clone()).unwrap_or_default(),
+        files: files.into_iter().collect(),
+        commits,
+        uri: uri.map(|path| format!("file://{}", path)),
+    })
+}
+
+/// A summary of what the assistant said, or `None` when there is nothing to
+/// summarize or the summarizer fails
+fn summarize_session(
+    summarizer: &dyn Summarizer,
+    session_id: &str,
+    lines: &[TranscriptLine],
+) -> Option<String> {
+    let text: Vec<String> = lines
+        .iter()
+        .filter(|l| l.line_type == LineType::Assistant)
+        .filter_map(|l| l.message())
+        .map(|m| plain_text(m.content.as_text().trim()))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let text = text.join("\n");
+    let skip = text.chars().count().saturating_sub(MAX_SUMMARY_INPUT_CHARS);
+    let text: String = text.chars().skip(skip).collect();
+    if text.trim().is_empty() {
+        return None;
+    }
+
+    match summarizer.summarize(&text, MAX_SUMMARY_CHARS) {
+        Ok(summary) if !summary.is_empty() => Some(summary),
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("Warning: could not summarize {} ({}): {}", session_id, summarizer.name(), e);
+            None
+        }
+    }
+}
+
+/// A path relative to the repo root when it is inside it
+fn relative_to(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(MIN_HASH_LEN)]
+}
+
+/// The changelog as Markdown
+fn render(changelog: &Changelog) -> String {
+    let mut out = format!("# Changes since {}\n\n", changelog.since);
+    if changelog.sessions.is_empty() && changelog.other_commits.is_empty() {
+        out.push_str("No commits in range.\n");
+        return out;
+    }
+
+    for session in &changelog.sessions {
+        let title = if session.goal.is_empty() { &session.name } else { &session.goal };
+        out.push_str(&format!("## {}\n\n", title));
+        match &session.uri {
+            Some(uri) => out.push_str(&format!("Session: [{}]({})", session.name, uri)),
+            None => out.push_str(&format!("Session: {}", session.name)),
+        }
+        out.push_str(&format!(" (`{}`)\n\n", session.session_id));
+        if let Some(summary) = &session.summary {
+            out.push_str(&format!("{}\n\n", summary));
+        }
+        if !session.files.is_empty() {
+            let files: Vec<String> = session.files.iter().map(|f| format!("`{}`", f)).collect();
+            out.push_str(&format!("- Files: {}\n", files.join(", ")));
+        }
+        for commit in &session.commits {
+            out.push_str(&format!("- {} {}\n", short(&commit.hash), commit.subject));
+        }
+        if !session.files.is_empty() || !session.commits.is_empty() {
+            out.push('\n');
+        }
+    }
+
+    if !changelog.other_commits.is_empty() {
+        out.push_str("## Other commits\n\n");
+        for commit in &changelog.other_commits {
+            out.push_str(&format!("- {} {}\n", short(&commit.hash), commit.subject));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_and_match() {
+        let output = "0123456789abcdef\x1fFix parser\x1f2026-01-02T00:00:00+00:00\n\
+                      fedcba9876543210\x1fAdd flag\x1f2026-01-01T00:00:00+00:00\n";
+        let commits = parse_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].subject, "Fix parser");
+        assert_eq!(commits[1].date, "2026-01-01T00:00:00+00:00");
+
+        assert!(hash_matches("0123456", &commits[0].hash));
+        assert!(hash_matches("0123456789abcdef", &commits[0].hash));
+        assert!(!hash_matches("0123", &commits[0].hash));
+        assert!(!hash_matches("fedcba9", &commits[0].hash));
+    }
+}
@@ -0,0 +1,252 @@
+//! Context check command - how full a session's context is, for hooks
+//!
+//! Meant to run in a PreToolUse hook:
+//!
+//! ```json
+//! {"hooks": {"PreToolUse": [{"hooks": [{"type": "command",
+//!   "command": "transcript context-check --threshold 80 --hook"}]}]}}
+//! ```
+//!
+//! Claude Code passes the hook input on stdin, and its `transcript_path` is
+//! read directly, so the check doesn't wait for the index. Usage is counted
+//! as in `transcript usage` (input, output and cache tokens of the last
+//! main-thread reply). With `--hook` the warning is a `systemMessage`,
+//! printed when the threshold is crossed and again every 5 points more rather
+//! than on every tool call; tools are never blocked.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use transcript_db::{context_by_turn, TranscriptDb, TurnContext};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::{cache_dir, resolve_session_id};
+use crate::output::colors::{self, format_count};
+
+/// Percentage points between repeated hook warnings
+const WARN_STEP: f64 = 5.0;
+
+/// The fields of Claude Code's hook input used here
+#[derive(Debug, Default)]
+struct HookInput {
+    session_id: Option<String>,
+    transcript_path: Option<PathBuf>,
+    cwd: Option<PathBuf>,
+}
+
+/// A session's context at its latest turn
+struct ContextState {
+    session_id: String,
+    name: String,
+    /// None before the first reply reported usage
+    turn: Option<TurnContext>,
+}
+
+pub fn run(
+    cli: &Cli,
+    db: Option<&TranscriptDb>,
+    session: Option<&str>,
+    threshold: u8,
+    context_size: Option<u64>,
+    hook: bool,
+) -> Result<()> {
+    let input = if session.is_none() && !atty::is(atty::Stream::Stdin) {
+        read_hook_input()
+    } else {
+        HookInput::default()
+    };
+
+    let state = match measure(db, session, &input, context_size) {
+        Ok(state) => state,
+        // A hook mustn't get in the way of the tool call
+        Err(_) if hook => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let percent = state.as_ref().and_then(|s| s.turn.as_ref()).map_or(0.0, |t| t.percent);
+    let over = percent >= f64::from(threshold);
+
+    if hook {
+        if let Some(state) = &state {
+            if record_warning(&state.session_id, percent, threshold) {
+                println!("{}", json!({ "systemMessage": warning_text(state) }));
+            }
+        }
+        return Ok(());
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            let Some(state) = &state else {
+                println!("No session found (give one, or run inside a Claude Code hook)");
+                return Ok(());
+            };
+            match &state.turn {
+                Some(turn) => println!(
+                    "{} turn {}: {:.0}% of the context window ({} of {} tokens)",
+                    colors::colored_session(&state.name),
+                    turn.turn,
+                    turn.percent,
+                    format_count(turn.context_tokens as i64),
+                    format_count(turn.context_size as i64)
+                ),
+                None => println!("{} has no token usage yet", colors::colored_session(&state.name)),
+            }
+            if over {
+                println!("{}", colors::warning(&format!("Over the {}% threshold", threshold)));
+            }
+        }
+        OutputFormat::Json => {
+            let value = json!({
+                "session_id": state.as_ref().map(|s| &s.session_id),
+                "name": state.as_ref().map(|s| &s.name),
+                "turn": state.as_ref().and_then(|s| s.turn.as_ref()).map(|t| t.turn),
+                "context_tokens": state.as_ref().and_then(|s| s.turn.as_ref()).map_or(0, |t| t.context_tokens),
+                "context_size": state.as_ref().and_then(|s| s.turn.as_ref()).map(|t| t.context_size).or(context_size),
+                "percent": (percent * 10.0).round() / 10.0,
+                "threshold": threshold,
+                "over": over,
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                println!("{}", serde_json::to_string(&value)?);
+            }
+        }
+        OutputFormat::Minimal => println!("{:.0}", percent),
+    }
+    Ok(())
+}
+
+/// The hook input on stdin (empty when there is none)
+fn read_hook_input() -> HookInput {
+    let mut text = String::new();
+    if std::io::stdin().read_to_string(&mut text).is_err() {
+        return HookInput::default();
+    }
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) else {
+        return HookInput::default();
+    };
+    let field = |key: &str| value.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+    HookInput {
+        session_id: field("session_id").map(String::from),
+        transcript_path: field("transcript_path").map(PathBuf::from),
+        cwd: field("cwd").map(PathBuf::from),
+    }
+}
+
+/// The context of the named session, else the hook's transcript, else the
+/// latest session in the hook's (or the current) directory
+fn measure(
+    db: Option<&TranscriptDb>,
+    session: Option<&str>,
+    input: &HookInput,
+    context_size: Option<u64>,
+) -> Result<Option<ContextState>> {
+    if session.is_none() {
+        if let Some(path) = input.transcript_path.as_deref().filter(|p| p.exists()) {
+            return Ok(Some(from_file(path, input.session_id.as_deref(), context_size)?));
+        }
+    }
+
+    let db = db.context("No transcript database (run: transcript index build)")?;
+    let info = match session {
+        Some(session) => db.resolve_session(&resolve_session_id(db, session)?)?,
+        None => {
+            let cwd = match &input.cwd {
+                Some(cwd) => cwd.clone(),
+                None => std::env::current_dir()?,
+            };
+            db.latest_session_in(&cwd.to_string_lossy())?
+        }
+    };
+    let Some(info) = info else {
+        return Ok(None);
+    };
+    let turn = db.context_usage_by_turn(&info.session_id, context_size)?.pop();
+    Ok(Some(ContextState {
+        name: info.slug.unwrap_or_else(|| short_id(&info.session_id)),
+        session_id: info.session_id,
+        turn,
+    }))
+}
+
+fn from_file(path: &Path, session_id: Option<&str>, context_size: Option<u64>) -> Result<ContextState> {
+    let lines = transcript_core::parse_file(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let session_id = session_id
+        .map(String::from)
+        .or_else(|| lines.first().map(|l| l.session_id.clone()))
+        .unwrap_or_default();
+    let name = lines
+        .iter()
+        .rev()
+        .find_map(|l| l.slug.clone())
+        .unwrap_or_else(|| short_id(&session_id));
+    Ok(ContextState {
+        session_id,
+        name,
+        turn: context_by_turn(&lines, context_size).pop(),
+    })
+}
+
+fn short_id(session_id: &str) -> String {
+    session_id.chars().take(8).collect()
+}
+
+fn warning_text(state: &ContextState) -> String {
+    let (percent, tokens, context_size) = state
+        .turn
+        .as_ref()
+        .map_or((0.0, 0, 0), |t| (t.percent, t.context_tokens, t.context_size));
+    format!(
+        "Context {:.0}% full in {} ({} of {} tokens); consider /compact or a fresh session",
+        percent,
+        state.name,
+        format_count(tokens as i64),
+        format_count(context_size as i64)
+    )
+}
+
+/// Whether the hook should warn now, remembering the warning per session
+fn record_warning(session_id: &str, percent: f64, threshold: u8) -> bool {
+    let marker = cache_dir().join(format!("context-check-{}", session_id));
+    let last = std::fs::read_to_string(&marker).ok().and_then(|s| s.trim().parse().ok());
+    let (warn, step) = warn_step(percent, threshold, last);
+    let _ = match step {
+        Some(step) => std::fs::create_dir_all(cache_dir()).and_then(|_| std::fs::write(&marker, step.to_string())),
+        None => std::fs::remove_file(&marker),
+    };
+    warn
+}
+
+/// Warn at the threshold and then every `WARN_STEP` points more; returns
+/// whether to warn and the step to remember (none below the threshold, so
+/// a compaction starts over)
+fn warn_step(percent: f64, threshold: u8, last: Option<u32>) -> (bool, Option<u32>) {
+    if percent < f64::from(threshold) {
+        return (false, None);
+    }
+    let step = (percent / WARN_STEP) as u32;
+    match last {
+        Some(last) if last >= step => (false, Some(step)),
+        _ => (true, Some(step)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warn_step() {
+        assert_eq!(warn_step(50.0, 80, None), (false, None));
+        assert_eq!(warn_step(81.0, 80, None), (true, Some(16)));
+        // Not again until 5 points more
+        assert_eq!(warn_step(84.0, 80, Some(16)), (false, Some(16)));
+        assert_eq!(warn_step(85.5, 80, Some(16)), (true, Some(17)));
+        // Compacted below the threshold: start over
+        assert_eq!(warn_step(30.0, 80, Some(17)), (false, None));
+    }
+}
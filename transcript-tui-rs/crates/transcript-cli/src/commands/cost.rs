@@ -0,0 +1,221 @@
+//! Cost command - estimated spend attributed by project, branch, model, session or day
+
+use anyhow::{bail, Result};
+use chrono::{Duration, Utc};
+use serde_json::{json, Map, Value};
+use transcript_core::{aggregate_costs, CostPeriod};
+use transcript_db::{CostGroupBy, CostRow, GetLinesOptions, TranscriptDb};
+
+use crate::cli::{Cli, CostBy, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::{colors, csv};
+
+const VALUE_COLUMNS: &[&str] = &[
+    "sessions",
+    "messages",
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_tokens",
+    "cache_read_tokens",
+    "cost_usd",
+];
+
+const TURN_KEY_COLUMNS: &[&str] = &["turn", "started", "model"];
+
+/// Database grouping for a dimension; turns are rolled up from lines instead
+fn sql_group_by(by: CostBy) -> Option<CostGroupBy> {
+    match by {
+        CostBy::Project => Some(CostGroupBy::Project),
+        CostBy::Branch => Some(CostGroupBy::Branch),
+        CostBy::Model => Some(CostGroupBy::Model),
+        CostBy::Session => Some(CostGroupBy::Session),
+        CostBy::Day => Some(CostGroupBy::Day),
+        CostBy::User => Some(CostGroupBy::User),
+        CostBy::Turn => None,
+    }
+}
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    by: CostBy,
+    session: Option<&str>,
+    days: Option<i64>,
+    as_csv: bool,
+) -> Result<()> {
+    let session_id = session.map(|s| resolve_session_id(db, s)).transpose()?;
+    let (rows, key_columns) = match sql_group_by(by) {
+        Some(group_by) => (
+            db.cost_breakdown(group_by, session_id.as_deref(), days)?,
+            group_by.key_columns(),
+        ),
+        None => (turn_rows(db, session_id.as_deref(), days)?, TURN_KEY_COLUMNS),
+    };
+
+    if as_csv {
+        let header: Vec<&str> = key_columns.iter().chain(VALUE_COLUMNS).copied().collect();
+        csv::print_row(&header);
+        for row in &rows {
+            csv::print_row(&record(row));
+        }
+        return Ok(());
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => print_human(&rows, key_columns, days),
+        OutputFormat::Json => {
+            let objects: Vec<Value> = rows.iter().map(|r| to_json(r, key_columns)).collect();
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&objects)?);
+            } else {
+                println!("{}", serde_json::to_string(&objects)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for row in &rows {
+                println!("{}", record(row).join("\t"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Per-turn rows, in order, rolled up from the session's lines
+fn turn_rows(db: &TranscriptDb, session_id: Option<&str>, days: Option<i64>) -> Result<Vec<CostRow>> {
+    if session_id.is_none() && days.is_none() {
+        bail!("--by turn needs --session <name> or --days <n>");
+    }
+    let options = GetLinesOptions {
+        session_id: session_id.map(String::from),
+        types: Some(vec!["user".to_string(), "assistant".to_string()]),
+        from_time: days.map(|d| (Utc::now() - Duration::days(d)).format("%Y-%m-%dT%H:%M:%S").to_string()),
+        ..Default::default()
+    };
+    let lines = db.get_lines(&options)?;
+
+    Ok(aggregate_costs(&lines, CostPeriod::Turn)
+        .into_iter()
+        .map(|group| CostRow {
+            keys: vec![group.key, group.started, group.models.join(",")],
+            sessions: 1,
+            messages: group.totals.messages as i64,
+            input_tokens: group.totals.input_tokens as i64,
+            output_tokens: group.totals.output_tokens as i64,
+            cache_creation_tokens: group.totals.cache_creation_tokens as i64,
+            cache_read_tokens: group.totals.cache_read_tokens as i64,
+            cost_usd: group.totals.cost_usd,
+        })
+        .collect())
+}
+
+/// Key and value fields of a row as plain text
+fn record(row: &CostRow) -> Vec<String> {
+    let mut fields = row.keys.clone();
+    fields.extend([
+        row.sessions.to_string(),
+        row.messages.to_string(),
+        row.input_tokens.to_string(),
+        row.output_tokens.to_string(),
+        row.cache_creation_tokens.to_string(),
+        row.cache_read_tokens.to_string(),
+        format!("{:.4}", row.cost_usd),
+    ]);
+    fields
+}
+
+fn to_json(row: &CostRow, key_columns: &[&str]) -> Value {
+    let mut obj = Map::new();
+    for (column, key) in key_columns.iter().zip(&row.keys) {
+        obj.insert(column.to_string(), json!(key));
+    }
+    obj.insert("sessions".into(), json!(row.sessions));
+    obj.insert("messages".into(), json!(row.messages));
+    obj.insert("input_tokens".into(), json!(row.input_tokens));
+    obj.insert("output_tokens".into(), json!(row.output_tokens));
+    obj.insert("cache_creation_tokens".into(), json!(row.cache_creation_tokens));
+    obj.insert("cache_read_tokens".into(), json!(row.cache_read_tokens));
+    obj.insert("cost_usd".into(), json!((row.cost_usd * 10000.0).round() / 10000.0));
+    Value::Object(obj)
+}
+
+fn print_human(rows: &[CostRow], key_columns: &[&str], days: Option<i64>) {
+    let title = match days {
+        Some(d) => format!("Estimated cost (last {} days)", d),
+        None => "Estimated cost (all time)".to_string(),
+    };
+    println!("{}", colors::header(&title));
+    println!();
+
+    if rows.is_empty() {
+        println!("{}", colors::label("No assistant usage found"));
+        return;
+    }
+
+    let headers: Vec<&str> = key_columns
+        .iter()
+        .copied()
+        .chain(["sessions", "input", "output", "cache w", "cache r", "cost"])
+        .collect();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|r| {
+            let mut c = r.keys.clone();
+            c.extend([
+                r.sessions.to_string(),
+                colors::format_count(r.input_tokens),
+                colors::format_count(r.output_tokens),
+                colors::format_count(r.cache_creation_tokens),
+                colors::format_count(r.cache_read_tokens),
+                format_usd(r.cost_usd),
+            ]);
+            c
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &cells {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    // Keys left-aligned, numbers right-aligned
+    let keys = key_columns.len();
+    let format_row = |row: &[String]| -> String {
+        row.iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if i < keys {
+                    format!("{:<w$}", c, w = widths[i])
+                } else {
+                    format!("{:>w$}", c, w = widths[i])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let header_cells: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    println!("{}", colors::header(format_row(&header_cells).trim_end()));
+    for row in &cells {
+        println!("{}", format_row(row).trim_end());
+    }
+
+    let total: f64 = rows.iter().map(|r| r.cost_usd).sum();
+    println!();
+    println!(
+        "{} {}",
+        colors::label("Total:"),
+        colors::value(&format_usd(total))
+    );
+}
+
+/// Dollar amount, with extra precision for sub-cent values
+fn format_usd(amount: f64) -> String {
+    if amount > 0.0 && amount < 0.01 {
+        format!("${:.4}", amount)
+    } else {
+        format!("${:.2}", amount)
+    }
+}
@@ -0,0 +1,166 @@
+//! Db command - inspect how the database runs the library's queries
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use transcript_db::{canned_query, PlanStep, TranscriptDb, CANNED_QUERIES};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::output::colors;
+
+/// A query and its plan, for JSON output
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Explained {
+    name: Option<String>,
+    sql: String,
+    plan: Vec<Step>,
+    table_scans: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Step {
+    id: i64,
+    parent: i64,
+    detail: String,
+    table_scan: bool,
+    #[serde(skip)]
+    depth: usize,
+}
+
+/// `db explain`: the plan of one canned query or SQL statement, or of every
+/// canned query
+pub fn explain(cli: &Cli, db: &TranscriptDb, query: Option<&str>) -> Result<()> {
+    let explained = match query {
+        Some(query) => {
+            let (name, sql) = match canned_query(query.trim()) {
+                Some(canned) => (Some(canned.name.to_string()), canned.sql.to_string()),
+                // A single word is a mistyped name rather than SQL
+                None if !query.trim().contains(char::is_whitespace) => {
+                    let names: Vec<_> = CANNED_QUERIES.iter().map(|q| q.name).collect();
+                    bail!("Unknown query '{}'. Canned queries: {}", query, names.join(", "));
+                }
+                None => (None, query.to_string()),
+            };
+            let plan = db.explain(&sql)?;
+            vec![explained_query(name, sql, Ok(plan))]
+        }
+        // Every canned query; one a database lacks the tables for doesn't stop the rest
+        None => CANNED_QUERIES
+            .iter()
+            .map(|q| explained_query(Some(q.name.to_string()), q.sql.to_string(), db.explain(q.sql).map_err(Into::into)))
+            .collect(),
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Human => print_human(&explained),
+        OutputFormat::Json => {
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&explained)?);
+            } else {
+                println!("{}", serde_json::to_string(&explained)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for query in &explained {
+                let scans = if query.table_scans.is_empty() {
+                    "-".to_string()
+                } else {
+                    query.table_scans.join(",")
+                };
+                println!("{}\t{}", query.name.as_deref().unwrap_or("sql"), scans);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn explained_query(name: Option<String>, sql: String, plan: Result<Vec<PlanStep>>) -> Explained {
+    let (plan, error) = match plan {
+        Ok(plan) => (plan, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    Explained {
+        table_scans: plan
+            .iter()
+            .filter(|s| s.is_table_scan())
+            .map(|s| scanned_table(&s.detail).to_string())
+            .collect(),
+        plan: plan
+            .iter()
+            .map(|s| Step {
+                id: s.id,
+                parent: s.parent,
+                detail: s.detail.clone(),
+                table_scan: s.is_table_scan(),
+                depth: s.depth(&plan),
+            })
+            .collect(),
+        name,
+        sql,
+        error,
+    }
+}
+
+/// Table of a `SCAN lines` or `SCAN TABLE lines AS l` step
+fn scanned_table(detail: &str) -> &str {
+    let rest = detail.trim_start_matches("SCAN ").trim_start_matches("TABLE ");
+    rest.split_whitespace().next().unwrap_or(rest)
+}
+
+fn print_human(explained: &[Explained]) {
+    for query in explained {
+        match &query.name {
+            Some(name) => {
+                let description = CANNED_QUERIES
+                    .iter()
+                    .find(|q| q.name == name)
+                    .map_or("", |q| q.description);
+                println!("{} {}", colors::header(name), colors::label(description));
+            }
+            None => println!("{}", colors::header(&crate::timings::one_line(&query.sql))),
+        }
+        if let Some(error) = &query.error {
+            println!("  {}", colors::error(error));
+            println!();
+            continue;
+        }
+        for step in &query.plan {
+            let indent = "  ".repeat(step.depth + 1);
+            if step.table_scan {
+                println!("{}{}", indent, colors::warning(&format!("{}  (table scan)", step.detail)));
+            } else {
+                println!("{}{}", indent, step.detail);
+            }
+        }
+        println!();
+    }
+
+    let scanning = explained.iter().filter(|q| !q.table_scans.is_empty()).count();
+    if scanning == 0 {
+        println!("{}", colors::success("No table scans"));
+    } else {
+        println!(
+            "{}",
+            colors::warning(&format!(
+                "{} of {} queries read a whole table; scans of lines and hook_events grow with the database",
+                scanning,
+                explained.len()
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scanned_table_names() {
+        assert_eq!(scanned_table("SCAN lines"), "lines");
+        assert_eq!(scanned_table("SCAN TABLE hook_events AS h"), "hook_events");
+    }
+}
@@ -0,0 +1,413 @@
+//! Digest command - carry context from recent sessions into a new one
+//!
+//! For the last few sessions in a project, the digest lists what each set out
+//! to do, the decisions the assistant stated, the files it edited and the
+//! TODOs still open in its last TodoWrite. Lists are shortened until the
+//! Markdown fits the token budget, so it can be pasted into a fresh session.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use transcript_core::{
+    edited_file, split_sentences, summarizer_from_config, Config, ExtractiveSummarizer, LineType, Summarizer,
+    TranscriptLine,
+};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use super::export::markdown::plain_text;
+use super::export::trace::is_prompt;
+use super::resolve_project;
+use crate::cli::{Cli, FormatArg};
+
+/// Rough size of a token, for the budget
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Characters kept from a session's first prompt
+const MAX_GOAL_CHARS: usize = 120;
+
+/// Characters kept from each decision
+const MAX_DECISION_CHARS: usize = 240;
+
+/// Characters of assistant text handed to the summarizer (the latest are kept)
+const MAX_SUMMARY_INPUT_CHARS: usize = 20_000;
+
+/// Phrases that mark a sentence as a decision
+const DECISION_MARKERS: &[&str] = &[
+    "decided",
+    "decision",
+    "instead of",
+    "rather than",
+    "going with",
+    "i'll use",
+    "we'll use",
+    "chose",
+    "opted",
+    "switched to",
+    "the approach",
+];
+
+/// How many sessions to digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LastSpec {
+    /// The N most recent sessions
+    Sessions(usize),
+    /// Sessions active in the last N days
+    Days(i64),
+}
+
+/// Parse `3-sessions`, `1-session`, `7-days`, `1-day` or a bare count of sessions
+pub fn parse_last(spec: &str) -> Result<LastSpec> {
+    let spec = spec.trim();
+    let (count, unit) = spec.split_once('-').unwrap_or((spec, "sessions"));
+    let Ok(count) = count.parse::<usize>() else {
+        bail!("Invalid --last '{}' (expected e.g. 3-sessions or 7-days)", spec);
+    };
+    if count == 0 {
+        bail!("--last must be at least 1");
+    }
+    match unit {
+        "session" | "sessions" => Ok(LastSpec::Sessions(count)),
+        "day" | "days" => Ok(LastSpec::Days(count as i64)),
+        _ => bail!("Invalid --last unit '{}' (expected sessions or days)", unit),
+    }
+}
+
+/// An item from a TodoWrite list
+#[derive(Debug, Clone, Serialize)]
+pub struct Todo {
+    pub content: String,
+    pub status: String,
+}
+
+/// What one session contributes to the digest
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDigest {
+    pub session_id: String,
+    pub name: String,
+    pub started: String,
+    pub ended: String,
+    pub goal: String,
+    pub summary: Option<String>,
+    pub decisions: Vec<String>,
+    pub files: Vec<String>,
+    pub open_todos: Vec<Todo>,
+}
+
+/// How much of each session to show
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    summary_chars: usize,
+    decisions: usize,
+    files: usize,
+    todos: usize,
+}
+
+/// Tried in order until the digest fits the budget
+const LEVELS: &[Limits] = &[
+    Limits { summary_chars: 400, decisions: 6, files: 20, todos: 12 },
+    Limits { summary_chars: 250, decisions: 4, files: 10, todos: 8 },
+    Limits { summary_chars: 150, decisions: 2, files: 5, todos: 5 },
+    Limits { summary_chars: 0, decisions: 1, files: 3, todos: 3 },
+];
+
+pub fn run(cli: &Cli, db: &TranscriptDb, project: &str, last: &str, budget: usize) -> Result<()> {
+    let last = parse_last(last)?;
+    let project = resolve_project(Some(db), project)?;
+    let session_ids = recent_sessions(db, &project, last)?;
+    if session_ids.is_empty() {
+        bail!("No indexed sessions in {}", project);
+    }
+
+    let summarizer: Box<dyn Summarizer> = match summarizer_from_config(&Config::load().summarizer) {
+        Ok(summarizer) => summarizer,
+        Err(e) => {
+            eprintln!("Warning: {}; using the extractive summarizer", e);
+            Box::new(ExtractiveSummarizer)
+        }
+    };
+
+    let mut digests = Vec::new();
+    for session_id in &session_ids {
+        let lines = db.get_lines(&GetLinesOptions::for_session(session_id))?;
+        if lines.is_empty() {
+            continue;
+        }
+        let mut digest = build_digest(session_id, &lines, Path::new(&project));
+        digest.summary = summarize(summarizer.as_ref(), session_id, &lines, LEVELS[0].summary_chars);
+        digests.push(digest);
+    }
+
+    // Pasted into a chat, so Markdown unless JSON is asked for explicitly
+    if cli.json || cli.format == Some(FormatArg::Json) {
+        if cli.pretty {
+            println!("{}", serde_json::to_string_pretty(&digests)?);
+        } else {
+            println!("{}", serde_json::to_string(&digests)?);
+        }
+    } else {
+        print!("{}", fit(&digests, &project, budget));
+    }
+
+    Ok(())
+}
+
+/// Sessions with a line in `project` (or below it), newest first
+fn recent_sessions(db: &TranscriptDb, project: &str, last: LastSpec) -> Result<Vec<String>> {
+    let (since, limit) = match last {
+        LastSpec::Sessions(n) => (None, n as i64),
+        LastSpec::Days(days) => {
+            let since = Utc::now() - Duration::days(days);
+            (Some(since.format("%Y-%m-%dT%H:%M:%S").to_string()), -1)
+        }
+    };
+    let mut stmt = db.connection().prepare(
+        "SELECT session_id FROM lines
+         WHERE (cwd = ?1 OR substr(cwd, 1, length(?1) + 1) IN (?1 || '/', ?1 || '\\'))
+           AND (?2 IS NULL OR timestamp >= ?2)
+         GROUP BY session_id
+         ORDER BY MAX(timestamp) DESC
+         LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![project, since, limit], |row| row.get(0))?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Goal, decisions, edited files and open TODOs of one session
+pub fn build_digest(session_id: &str, lines: &[TranscriptLine], root: &Path) -> SessionDigest {
+    let goal = lines
+        .iter()
+        .find(|l| is_prompt(l))
+        .and_then(|l| l.message())
+        .map(|m| plain_text(m.content.as_text().trim()))
+        .unwrap_or_default();
+    let goal = clip(goal.lines().next().unwrap_or(""), MAX_GOAL_CHARS);
+
+    let mut decisions: Vec<String> = Vec::new();
+    let mut files = BTreeSet::new();
+    let mut open_todos = Vec::new();
+    for line in lines {
+        let Some(msg) = line.message() else {
+            continue;
+        };
+        if line.line_type == LineType::Assistant {
+            let text = plain_text(msg.content.as_text().trim());
+            for sentence in split_sentences(&text) {
+                let lower = sentence.to_lowercase();
+                if DECISION_MARKERS.iter().any(|m| lower.contains(m)) {
+                    let decision = clip(sentence, MAX_DECISION_CHARS);
+                    if !decisions.contains(&decision) {
+                        decisions.push(decision);
+                    }
+                }
+            }
+        }
+        for (_, name, input) in msg.content.tool_uses() {
+            if name == "TodoWrite" {
+                // Each TodoWrite replaces the whole list, so the last one is current
+                if let Some(todos) = input.get("todos").and_then(|v| v.as_array()) {
+                    open_todos = todos
+                        .iter()
+                        .map(|todo| Todo {
+                            content: todo.get("content").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                            status: todo.get("status").and_then(|v| v.as_str()).unwrap_or("pending").to_string(),
+                        })
+                        .filter(|todo| todo.status != "completed" && !todo.content.is_empty())
+                        .collect();
+                }
+                continue;
+            }
+            if let Some(path) = edited_file(name, input) {
+                let path = Path::new(path);
+                files.insert(path.strip_prefix(root).unwrap_or(path).to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    SessionDigest {
+        session_id: session_id.to_string(),
+        name: lines
+            .iter()
+            .find_map(|l| l.session_name.clone().or_else(|| l.slug.clone()))
+            .unwrap_or_else(|| session_id.to_string()),
+        started: lines.first().map(|l| l.timestamp.clone()).unwrap_or_default(),
+        ended: lines.last().map(|l| l.timestamp.clone()).unwrap_or_default(),
+        goal,
+        summary: None,
+        decisions,
+        files: files.into_iter().collect(),
+        open_todos,
+    }
+}
+
+/// A summary of the assistant's text, or `None` when there is nothing to
+/// summarize or the summarizer fails
+fn summarize(
+    summarizer: &dyn Summarizer,
+    session_id: &str,
+    lines: &[TranscriptLine],
+    max_chars: usize,
+) -> Option<String> {
+    let text: Vec<String> = lines
+        .iter()
+        .filter(|l| l.line_type == LineType::Assistant)
+        .filter_map(|l| l.message())
+        .map(|m| plain_text(m.content.as_text().trim()))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let text = text.join("\n");
+    let skip = text.chars().count().saturating_sub(MAX_SUMMARY_INPUT_CHARS);
+    let text: String = text.chars().skip(skip).collect();
+    if text.trim().is_empty() {
+        return None;
+    }
+
+    match summarizer.summarize(&text, max_chars) {
+        Ok(summary) if !summary.is_empty() => Some(summary),
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("Warning: could not summarize {} ({}): {}", session_id, summarizer.name(), e);
+            None
+        }
+    }
+}
+
+/// The digest at the most detailed level that fits `budget` tokens; if even
+/// the shortest doesn't, it is cut at a line boundary
+pub fn fit(digests: &[SessionDigest], project: &str, budget: usize) -> String {
+    let max_chars = budget.saturating_mul(CHARS_PER_TOKEN);
+    let mut out = String::new();
+    for limits in LEVELS {
+        out = render(digests, project, limits);
+        if out.chars().count() <= max_chars {
+            return out;
+        }
+    }
+
+    let marker = "\n_(digest cut to fit the token budget)_\n";
+    let keep = max_chars.saturating_sub(marker.chars().count() + 1);
+    let cut: String = out.chars().take(keep).collect();
+    let cut = match cut.rfind('\n') {
+        Some(end) => &cut[..end],
+        None => "",
+    };
+    format!("{}\n{}", cut, marker)
+}
+
+fn render(digests: &[SessionDigest], project: &str, limits: &Limits) -> String {
+    let mut out = String::from("# Context from previous sessions\n\n");
+    out.push_str(&format!(
+        "Project `{}`, {} session{}, newest first.\n\n",
+        project,
+        digests.len(),
+        if digests.len() == 1 { "" } else { "s" }
+    ));
+
+    for digest in digests {
+        let title = if digest.goal.is_empty() { &digest.name } else { &digest.goal };
+        let date = digest.ended.get(..10).unwrap_or(&digest.ended);
+        out.push_str(&format!("## {} ({})\n\n", title, date));
+
+        if limits.summary_chars > 0 {
+            if let Some(summary) = &digest.summary {
+                out.push_str(&format!("{}\n\n", clip(summary, limits.summary_chars)));
+            }
+        }
+
+        if !digest.decisions.is_empty() && limits.decisions > 0 {
+            out.push_str("Decisions:\n");
+            // The latest decisions supersede earlier ones
+            let skip = digest.decisions.len().saturating_sub(limits.decisions);
+            for decision in &digest.decisions[skip..] {
+                out.push_str(&format!("- {}\n", decision));
+            }
+            out.push('\n');
+        }
+
+        if !digest.files.is_empty() && limits.files > 0 {
+            let mut files: Vec<String> =
+                digest.files.iter().take(limits.files).map(|f| format!("`{}`", f)).collect();
+            if digest.files.len() > limits.files {
+                files.push(format!("and {} more", digest.files.len() - limits.files));
+            }
+            out.push_str(&format!("Files touched: {}\n\n", files.join(", ")));
+        }
+
+        if !digest.open_todos.is_empty() && limits.todos > 0 {
+            out.push_str("Open TODOs:\n");
+            for todo in digest.open_todos.iter().take(limits.todos) {
+                let mark = if todo.status == "in_progress" { "[→]" } else { "[ ]" };
+                out.push_str(&format!("- {} {}\n", mark, todo.content));
+            }
+            if digest.open_todos.len() > limits.todos {
+                out.push_str(&format!("- … {} more\n", digest.open_todos.len() - limits.todos));
+            }
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+fn clip(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        format!("{}…", text.chars().take(max).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(files: usize) -> SessionDigest {
+        SessionDigest {
+            session_id: "s1".to_string(),
+            name: "happy-dog".to_string(),
+            started: "2026-01-01T00:00:00Z".to_string(),
+            ended: "2026-01-01T01:00:00Z".to_string(),
+            goal: "Fix the parser".to_string(),
+            summary: Some("Fixed trailing commas.".to_string()),
+            decisions: vec!["We decided to keep the old API.".to_string()],
+            files: (0..files).map(|i| format!("src/file{}.rs", i)).collect(),
+            open_todos: vec![Todo { content: "Add tests".to_string(), status: "pending".to_string() }],
+        }
+    }
+
+    #[test]
+    fn test_parse_last() {
+        assert_eq!(parse_last("3-sessions").unwrap(), LastSpec::Sessions(3));
+        assert_eq!(parse_last("1-session").unwrap(), LastSpec::Sessions(1));
+        assert_eq!(parse_last("7-days").unwrap(), LastSpec::Days(7));
+        assert_eq!(parse_last("2").unwrap(), LastSpec::Sessions(2));
+        assert!(parse_last("0").is_err());
+        assert!(parse_last("3-weeks").is_err());
+        assert!(parse_last("x-days").is_err());
+    }
+
+    #[test]
+    fn test_fit_shrinks_to_budget() {
+        let digests = vec![digest(40)];
+        let full = fit(&digests, "/p", 10_000);
+        assert!(full.contains("Decisions:\n- We decided to keep the old API."));
+        assert!(full.contains("`src/file19.rs`, and 20 more"));
+        assert!(full.contains("- [ ] Add tests"));
+
+        let small = fit(&digests, "/p", 80);
+        assert!(small.chars().count() <= 80 * CHARS_PER_TOKEN);
+        assert!(small.contains("and 35 more"));
+        assert!(small.contains("Fixed trailing commas."));
+
+        let smaller = fit(&digests, "/p", 70);
+        assert!(smaller.contains("and 37 more"));
+        assert!(!smaller.contains("Fixed trailing commas."));
+
+        let cut = fit(&digests, "/p", 20);
+        assert!(cut.chars().count() <= 20 * CHARS_PER_TOKEN);
+        assert!(cut.ends_with("_(digest cut to fit the token budget)_\n"));
+    }
+}
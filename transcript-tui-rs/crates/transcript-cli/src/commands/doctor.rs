@@ -1,8 +1,7 @@
 //! Doctor command - diagnose transcript indexing pipeline
 
 use anyhow::Result;
-use std::path::PathBuf;
-use transcript_db::{default_db_path, TranscriptDb, DB_VERSION};
+use transcript_db::{default_db_path, TranscriptDb, DB_VERSION, MAX_DB_VERSION, READER_VERSION};
 
 use crate::cli::{Cli, OutputFormat};
 use crate::output::colors;
@@ -35,18 +34,59 @@ pub fn run(cli: &Cli, db: Option<TranscriptDb>) -> Result<()> {
         },
     });
 
+    // Check 2b: The database and the key agree on encryption
+    if db_exists {
+        let key = transcript_core::database_key();
+        let encrypted = transcript_indexer::is_encrypted(&db_path).unwrap_or(false);
+        let (passed, details) = match (key, encrypted) {
+            (Err(e), _) => (false, e.to_string()),
+            (Ok(Some(_)), _) if !cfg!(feature = "encryption") => {
+                (false, "Key set, but encryption is not built in".to_string())
+            }
+            (Ok(Some(_)), true) => (true, "On".to_string()),
+            (Ok(Some(_)), false) => (false, "Key set, but the database is plain (run: transcript index encrypt)".to_string()),
+            (Ok(None), true) => (false, format!("Database is encrypted, but no key is set ({})", transcript_core::DB_KEY_ENV)),
+            (Ok(None), false) => (true, "Off".to_string()),
+        };
+        checks.push(Check {
+            name: "Encryption".to_string(),
+            passed,
+            details,
+        });
+    }
+
     // Check 3: Schema version
     if let Some(ref db) = db {
         let stats = db.stats();
         if let Ok(stats) = stats {
-            let version_ok = stats.version >= DB_VERSION;
+            let version_ok = stats.version >= DB_VERSION && db.compatibility_mode().is_none();
             checks.push(Check {
                 name: "Schema version".to_string(),
                 passed: version_ok,
-                details: format!(
-                    "v{} (expected >= v{})",
-                    stats.version, DB_VERSION
-                ),
+                details: if db.compatibility_mode().is_some() {
+                    format!(
+                        "v{} is newer than this reader (v{}); compatibility mode, upgrade transcript",
+                        stats.version, MAX_DB_VERSION
+                    )
+                } else {
+                    format!("v{} (expected v{} to v{})", stats.version, DB_VERSION, MAX_DB_VERSION)
+                },
+            });
+        }
+    }
+
+    // Check 3b: Indexer version matches this binary
+    if let Some(ref db) = db {
+        if let Some(writer) = db.writer_version() {
+            let skewed = db.version_skew().is_some();
+            checks.push(Check {
+                name: "Indexer version".to_string(),
+                passed: !skewed,
+                details: if skewed {
+                    format!("Written by v{}, reading with v{}", writer, READER_VERSION)
+                } else {
+                    format!("v{}", writer)
+                },
             });
         }
     }
@@ -91,10 +131,29 @@ pub fn run(cli: &Cli, db: Option<TranscriptDb>) -> Result<()> {
         }
     }
 
+    // Check 5b: Slow queries logged in the last 7 days (informational)
+    if let Some(ref db) = db {
+        let since = (chrono::Utc::now() - chrono::Duration::days(7)).to_rfc3339();
+        if let Ok(slow) = db.get_slow_queries(&since, 1000) {
+            checks.push(Check {
+                name: "Slow queries".to_string(),
+                passed: true,
+                details: match slow.first() {
+                    None => "None in the last 7 days".to_string(),
+                    Some(slowest) => format!(
+                        "{} in the last 7 days; slowest {} ms in `{}`: {}",
+                        slow.len(),
+                        colors::format_count(slowest.millis),
+                        slowest.command,
+                        crate::timings::one_line(&slowest.sql)
+                    ),
+                },
+            });
+        }
+    }
+
     // Check 6: Transcript source directories
-    let claude_dir = dirs::home_dir()
-        .map(|h| h.join(".claude"))
-        .unwrap_or_else(|| PathBuf::from("~/.claude"));
+    let claude_dir = transcript_core::home_dir().join(".claude");
     let projects_dir = claude_dir.join("projects");
     let projects_exist = projects_dir.exists();
     checks.push(Check {
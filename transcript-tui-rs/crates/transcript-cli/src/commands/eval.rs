@@ -0,0 +1,278 @@
+//! Eval command - compare a re-run of an agent task with a golden session
+//!
+//! Each rubric check looks at behavior rather than output text: which tools
+//! were called, which files were edited and how many tokens were spent. The
+//! report is printed first, then the command fails when any check does, so CI
+//! can gate on the exit code.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::json;
+use transcript_core::{edited_file, response_usage, LineType, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use super::resolve_session_id;
+use crate::cli::{Cli, OutputFormat, RubricCheck};
+use crate::output::colors;
+
+/// What a session did, as far as the rubric is concerned
+#[derive(Debug, Default)]
+pub struct Behavior {
+    /// Calls per tool name
+    pub tools: BTreeMap<String, usize>,
+    /// Edited files, relative to the session's working directory
+    pub files: BTreeSet<String>,
+    /// Total tokens over assistant messages
+    pub tokens: u64,
+}
+
+impl Behavior {
+    pub fn from_lines(lines: &[TranscriptLine]) -> Self {
+        let cwd = lines.iter().find_map(|l| l.cwd.clone()).unwrap_or_default();
+        let mut behavior = Behavior::default();
+        let usages = response_usage(lines);
+        for (line, usage) in lines.iter().zip(&usages).filter(|(l, _)| l.line_type == LineType::Assistant) {
+            let Some(msg) = line.message() else {
+                continue;
+            };
+            if let Some(usage) = usage {
+                behavior.tokens += usage.total();
+            }
+            for (_, name, input) in msg.content.tool_uses() {
+                *behavior.tools.entry(name.to_string()).or_default() += 1;
+                if let Some(path) = edited_file(name, input) {
+                    // Re-runs usually happen in a fresh checkout
+                    let path = Path::new(path);
+                    let relative = path.strip_prefix(&cwd).unwrap_or(path);
+                    behavior.files.insert(relative.to_string_lossy().into_owned());
+                }
+            }
+        }
+        behavior
+    }
+
+    fn tool_calls(&self) -> usize {
+        self.tools.values().sum()
+    }
+}
+
+/// Result of one rubric check
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub golden: serde_json::Value,
+    pub candidate: serde_json::Value,
+    /// Why it failed, or a short description when it passed
+    pub detail: String,
+}
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    golden: &str,
+    candidate: &str,
+    rubric: &[RubricCheck],
+    tolerance: f64,
+) -> Result<()> {
+    let golden_id = resolve_session_id(db, golden)?;
+    let candidate_id = resolve_session_id(db, candidate)?;
+    let golden_behavior = Behavior::from_lines(&db.get_lines(&GetLinesOptions::for_session(&golden_id))?);
+    let candidate_behavior =
+        Behavior::from_lines(&db.get_lines(&GetLinesOptions::for_session(&candidate_id))?);
+
+    let checks: Vec<Check> = rubric
+        .iter()
+        .enumerate()
+        .filter(|(i, check)| !rubric[..*i].contains(check))
+        .map(|(_, check)| check)
+        .map(|check| match check {
+            RubricCheck::Tools => check_tools(&golden_behavior, &candidate_behavior, tolerance),
+            RubricCheck::Files => check_files(&golden_behavior, &candidate_behavior),
+            RubricCheck::Tokens => check_tokens(&golden_behavior, &candidate_behavior, tolerance),
+        })
+        .collect();
+    let failed = checks.iter().filter(|c| !c.passed).count();
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{} {} {} {}",
+                colors::header("Eval:"),
+                colors::colored_session(&golden_id),
+                colors::label("(golden) vs"),
+                colors::colored_session(&candidate_id)
+            );
+            for check in &checks {
+                let status = if check.passed {
+                    "PASS".green().bold()
+                } else {
+                    "FAIL".red().bold()
+                };
+                println!("  {} {:<7} {}", status, check.name, check.detail);
+            }
+            if failed == 0 {
+                println!("{}", colors::success(&format!("All {} checks passed", checks.len())));
+            }
+        }
+        OutputFormat::Json => {
+            let report = json!({
+                "golden": golden_id,
+                "candidate": candidate_id,
+                "tolerance_percent": tolerance,
+                "passed": failed == 0,
+                "checks": checks,
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", serde_json::to_string(&report)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for check in &checks {
+                println!("{}\t{}", check.name, if check.passed { "pass" } else { "fail" });
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} checks failed", failed, checks.len());
+    }
+    Ok(())
+}
+
+/// Whether `candidate` is at most `tolerance` percent above `golden`
+fn within(golden: f64, candidate: f64, tolerance: f64) -> bool {
+    candidate <= golden * (1.0 + tolerance / 100.0)
+}
+
+fn percent_change(golden: f64, candidate: f64) -> String {
+    if golden == 0.0 {
+        return if candidate == 0.0 { "±0%".to_string() } else { "new".to_string() };
+    }
+    format!("{:+.1}%", (candidate - golden) / golden * 100.0)
+}
+
+/// The candidate uses the same tools, without many more calls
+pub fn check_tools(golden: &Behavior, candidate: &Behavior, tolerance: f64) -> Check {
+    let missing: Vec<&str> = golden
+        .tools
+        .keys()
+        .filter(|t| !candidate.tools.contains_key(*t))
+        .map(String::as_str)
+        .collect();
+    let extra: Vec<&str> = candidate
+        .tools
+        .keys()
+        .filter(|t| !golden.tools.contains_key(*t))
+        .map(String::as_str)
+        .collect();
+    let (golden_calls, candidate_calls) = (golden.tool_calls(), candidate.tool_calls());
+    let calls_ok = within(golden_calls as f64, candidate_calls as f64, tolerance);
+
+    let mut problems = Vec::new();
+    if !missing.is_empty() {
+        problems.push(format!("missing {}", missing.join(", ")));
+    }
+    if !extra.is_empty() {
+        problems.push(format!("unexpected {}", extra.join(", ")));
+    }
+    if !calls_ok {
+        problems.push(format!("{} calls, limit +{}%", percent_change(golden_calls as f64, candidate_calls as f64), tolerance));
+    }
+    let detail = if problems.is_empty() {
+        format!("{} → {} calls", golden_calls, candidate_calls)
+    } else {
+        problems.join("; ")
+    };
+
+    Check {
+        name: "tools",
+        passed: problems.is_empty(),
+        golden: json!(golden.tools),
+        candidate: json!(candidate.tools),
+        detail,
+    }
+}
+
+/// The candidate edits exactly the golden session's files
+pub fn check_files(golden: &Behavior, candidate: &Behavior) -> Check {
+    let missing: Vec<&str> = golden.files.difference(&candidate.files).map(String::as_str).collect();
+    let extra: Vec<&str> = candidate.files.difference(&golden.files).map(String::as_str).collect();
+
+    let mut problems = Vec::new();
+    if !missing.is_empty() {
+        problems.push(format!("not edited: {}", missing.join(", ")));
+    }
+    if !extra.is_empty() {
+        problems.push(format!("also edited: {}", extra.join(", ")));
+    }
+    let detail = if problems.is_empty() {
+        format!("{} file(s) edited", golden.files.len())
+    } else {
+        problems.join("; ")
+    };
+
+    Check {
+        name: "files",
+        passed: problems.is_empty(),
+        golden: json!(golden.files),
+        candidate: json!(candidate.files),
+        detail,
+    }
+}
+
+/// The candidate spends at most `tolerance` percent more tokens
+pub fn check_tokens(golden: &Behavior, candidate: &Behavior, tolerance: f64) -> Check {
+    let passed = within(golden.tokens as f64, candidate.tokens as f64, tolerance);
+    Check {
+        name: "tokens",
+        passed,
+        golden: json!(golden.tokens),
+        candidate: json!(candidate.tokens),
+        detail: format!(
+            "{} → {} ({}, limit +{}%)",
+            colors::format_count(golden.tokens as i64),
+            colors::format_count(candidate.tokens as i64),
+            percent_change(golden.tokens as f64, candidate.tokens as f64),
+            tolerance
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn behavior(tools: &[(&str, usize)], files: &[&str], tokens: u64) -> Behavior {
+        Behavior {
+            tools: tools.iter().map(|(t, n)| (t.to_string(), *n)).collect(),
+            files: files.iter().map(|f| f.to_string()).collect(),
+            tokens,
+        }
+    }
+
+    #[test]
+    fn test_rubric_checks() {
+        let golden = behavior(&[("Read", 4), ("Edit", 2)], &["src/lib.rs"], 10_000);
+
+        let same = behavior(&[("Read", 5), ("Edit", 2)], &["src/lib.rs"], 11_000);
+        assert!(check_tools(&golden, &same, 20.0).passed);
+        assert!(check_files(&golden, &same).passed);
+        assert!(check_tokens(&golden, &same, 20.0).passed);
+
+        let drifted = behavior(&[("Read", 9), ("Bash", 1)], &["src/main.rs"], 13_000);
+        let tools = check_tools(&golden, &drifted, 20.0);
+        assert!(!tools.passed);
+        assert_eq!(tools.detail, "missing Edit; unexpected Bash; +66.7% calls, limit +20%");
+        let files = check_files(&golden, &drifted);
+        assert_eq!(files.detail, "not edited: src/lib.rs; also edited: src/main.rs");
+        assert!(!check_tokens(&golden, &drifted, 20.0).passed);
+        assert!(check_tokens(&golden, &drifted, 30.0).passed);
+    }
+}
@@ -0,0 +1,271 @@
+//! JSONL export - write a session's lines back out as a transcript file
+//!
+//! The database keeps trimmed copies of large lines, so each line is read from
+//! the original transcript when it is still there and its uuid matches; the
+//! indexed copy is used otherwise. When lines are filtered out, `parentUuid`
+//! is pointed at the nearest kept ancestor so the thread stays connected.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use transcript_core::{decode_line, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::issue::parse_turn_range;
+use crate::commands::resolve_session_id;
+use crate::commands::turn::split_turns;
+use crate::output::colors;
+
+/// Which lines of a session to export
+#[derive(Debug, Default)]
+pub struct JsonlFilter {
+    /// Line types to keep (user, assistant, system, ...)
+    pub types: Option<Vec<String>>,
+    pub from_time: Option<String>,
+    pub to_time: Option<String>,
+    /// Turn range: a..b, a.., ..b or n
+    pub turns: Option<String>,
+}
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session: &str,
+    output: Option<&Path>,
+    filter: &JsonlFilter,
+) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+    let kept = select_lines(&lines, filter)?;
+    if kept.is_empty() {
+        let numbers: Vec<String> = split_turns(&lines).iter().map(|(n, _)| n.to_string()).collect();
+        bail!(
+            "No lines in {} match the filters (turns: {})",
+            session,
+            if numbers.is_empty() { "none".to_string() } else { numbers.join(", ") }
+        );
+    }
+
+    export(cli, db, &session_id, &lines, &kept, output)
+}
+
+/// Write the kept lines of a session's `lines` as a transcript file (to
+/// stdout without `output`) and report it
+pub fn export(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session_id: &str,
+    lines: &[TranscriptLine],
+    kept: &[&TranscriptLine],
+    output: Option<&Path>,
+) -> Result<()> {
+    let originals = read_originals(db, session_id);
+    let relinked = relink_parents(lines, kept);
+    let mut trimmed = 0;
+    let mut document = String::new();
+    for line in kept {
+        let original = originals.get(&line.line_number).filter(|text| {
+            serde_json::from_str::<Value>(text)
+                .is_ok_and(|v| v.get("uuid").and_then(|u| u.as_str()) == Some(line.uuid.as_str()))
+        });
+        let text = match original {
+            Some(text) => text.as_str(),
+            None => {
+                trimmed += 1;
+                line.raw.as_str()
+            }
+        };
+        // Untouched lines are copied byte for byte
+        match relinked.get(line.uuid.as_str()) {
+            Some(parent) => {
+                let mut record: Value = serde_json::from_str(text)?;
+                if let Some(object) = record.as_object_mut() {
+                    object.insert("parentUuid".to_string(), json!(parent));
+                }
+                document.push_str(&serde_json::to_string(&record)?);
+            }
+            None => document.push_str(text),
+        }
+        document.push('\n');
+    }
+    if trimmed > 0 {
+        eprintln!(
+            "Warning: {} line(s) came from the index (original transcript missing or changed) and may be trimmed",
+            trimmed
+        );
+    }
+
+    // Without --output the transcript itself is the command's output
+    let Some(path) = output else {
+        print!("{}", document);
+        return Ok(());
+    };
+
+    std::fs::write(path, &document).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{}",
+                colors::success(&format!(
+                    "Exported {} of {} line(s) to {}",
+                    kept.len(),
+                    lines.len(),
+                    path.display()
+                ))
+            );
+        }
+        OutputFormat::Json => {
+            let summary = json!({
+                "session_id": session_id,
+                "lines": kept.len(),
+                "bytes": document.len(),
+                "output": path.to_string_lossy(),
+            });
+            println!("{}", serde_json::to_string(&summary)?);
+        }
+        OutputFormat::Minimal => {
+            println!("{}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Lines passing the turn, type and time filters, in file order
+pub fn select_lines<'a>(
+    lines: &'a [TranscriptLine],
+    filter: &JsonlFilter,
+) -> Result<Vec<&'a TranscriptLine>> {
+    // Turns are numbered over the whole session, before other filters apply
+    let candidates: Vec<&TranscriptLine> = match &filter.turns {
+        Some(spec) => {
+            let (from, to) = parse_turn_range(spec)?;
+            split_turns(lines)
+                .into_iter()
+                .filter(|(n, _)| from.is_none_or(|f| *n >= f) && to.is_none_or(|t| *n <= t))
+                .flat_map(|(_, turn)| turn.iter())
+                .collect()
+        }
+        None => lines.iter().collect(),
+    };
+
+    Ok(candidates
+        .into_iter()
+        .filter(|line| {
+            filter
+                .types
+                .as_ref()
+                .is_none_or(|types| types.iter().any(|t| *t == line.line_type.to_string()))
+        })
+        .filter(|line| {
+            filter
+                .from_time
+                .as_deref()
+                .is_none_or(|from| line.timestamp.as_str() >= from)
+        })
+        .filter(|line| filter.to_time.as_deref().is_none_or(|to| line.timestamp.as_str() <= to))
+        .collect())
+}
+
+/// Original lines of the session's transcript by line number (empty when the
+/// file can't be read)
+fn read_originals(db: &TranscriptDb, session_id: &str) -> HashMap<i64, String> {
+    let file_path: Option<String> = db
+        .connection()
+        .query_row(
+            "SELECT file_path FROM lines WHERE session_id = ?1 LIMIT 1",
+            [session_id],
+            |row| row.get(0),
+        )
+        .ok();
+    let Some(bytes) = file_path.and_then(|p| transcript_indexer::compressed::read_file(Path::new(&p)).ok()) else {
+        return HashMap::new();
+    };
+    // Decoded as the indexer read them, bad bytes and all
+    bytes
+        .split(|&b| b == b'\n')
+        .enumerate()
+        .map(|(i, line)| (i as i64 + 1, decode_line(line.strip_suffix(b"\r").unwrap_or(line)).0))
+        .collect()
+}
+
+/// New `parentUuid`s for kept lines whose parent was filtered out: the
+/// nearest kept ancestor, or `None` when there is none
+pub fn relink_parents<'a>(
+    all: &'a [TranscriptLine],
+    kept: &[&'a TranscriptLine],
+) -> HashMap<&'a str, Option<&'a str>> {
+    let parents: HashMap<&str, Option<&str>> = all
+        .iter()
+        .map(|l| (l.uuid.as_str(), l.parent_uuid.as_deref()))
+        .collect();
+    let kept_uuids: HashSet<&str> = kept.iter().map(|l| l.uuid.as_str()).collect();
+
+    let mut relinked = HashMap::new();
+    for line in kept {
+        let mut parent = line.parent_uuid.as_deref();
+        let mut seen = HashSet::new();
+        // Parents outside the session (or already kept) are left as they are
+        while let Some(uuid) = parent {
+            if kept_uuids.contains(uuid) || !parents.contains_key(uuid) {
+                break;
+            }
+            if !seen.insert(uuid) {
+                parent = None;
+                break;
+            }
+            parent = parents[uuid];
+        }
+        if parent != line.parent_uuid.as_deref() {
+            relinked.insert(line.uuid.as_str(), parent);
+        }
+    }
+    relinked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transcript_core::LineType;
+
+    fn line(line_type: LineType, uuid: &str, parent: Option<&str>, raw: &str) -> TranscriptLine {
+        TranscriptLine {
+            uuid: uuid.to_string(),
+            parent_uuid: parent.map(str::to_string),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_select_and_relink() {
+        let prompt = r#"{"message":{"role":"user","content":"hi"}}"#;
+        let reply = r#"{"message":{"role":"assistant","content":"x"}}"#;
+        let lines = vec![
+            line(LineType::User, "u1", None, prompt),
+            line(LineType::Assistant, "a1", Some("u1"), reply),
+            line(LineType::User, "u2", Some("a1"), prompt),
+            line(LineType::Assistant, "a2", Some("u2"), reply),
+            line(LineType::Assistant, "a3", Some("a2"), reply),
+        ];
+
+        let filter = JsonlFilter { turns: Some("2".to_string()), ..Default::default() };
+        let kept = select_lines(&lines, &filter).unwrap();
+        let uuids: Vec<&str> = kept.iter().map(|l| l.uuid.as_str()).collect();
+        assert_eq!(uuids, ["u2", "a2", "a3"]);
+
+        let filter = JsonlFilter {
+            types: Some(vec!["user".to_string()]),
+            ..Default::default()
+        };
+        let kept = select_lines(&lines, &filter).unwrap();
+        let relinked = relink_parents(&lines, &kept);
+        // u2's parent a1 was dropped, so it hangs off u1 instead
+        assert_eq!(relinked.get("u2"), Some(&Some("u1")));
+        assert!(!relinked.contains_key("u1"));
+        assert_eq!(relinked.len(), 1);
+    }
+}
@@ -0,0 +1,461 @@
+//! Markdown transcript export for pasting session reviews into PRs and wikis
+//!
+//! Each user prompt starts a turn. Prompts are quoted, assistant text is kept
+//! as Markdown, tool calls become collapsed `<details>` blocks with their input
+//! and result in code fences, and per-turn token usage goes in footnotes.
+//! Compactions, API errors and rate limits are noted where they happened.
+//! With `--annotations`, line comments are quoted under the line they are on.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use transcript_core::{
+    display_text, is_binary, noise_classifier, response_usage, strip_ansi, system_summary, ContentBlock, LineType,
+    MessageContent, SystemLevel, TranscriptLine,
+};
+use transcript_db::{annotation_threads, Annotation, GetLinesOptions, TranscriptDb};
+
+use super::trace::{is_prompt, result_text};
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors::{self, format_count};
+use crate::output::human::get_tool_preview;
+
+/// What goes into the document
+#[derive(Debug, Clone)]
+pub struct MarkdownOptions {
+    /// Include tool calls and their results
+    pub tools: bool,
+    /// Include assistant thinking blocks
+    pub thinking: bool,
+    /// Characters of each tool result to keep
+    pub max_output: usize,
+    /// Include line annotations
+    pub annotations: bool,
+}
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session: &str,
+    output: Option<&Path>,
+    options: &MarkdownOptions,
+) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+    let annotations = if options.annotations {
+        db.get_annotations(&session_id)?
+    } else {
+        Vec::new()
+    };
+    let (document, turns) = render(&session_id, &lines, options, &annotations);
+    write_document(cli, &session_id, &document, turns, output)
+}
+
+/// Write a rendered document (to stdout without `output`) and report it
+pub fn write_document(
+    cli: &Cli,
+    session_id: &str,
+    document: &str,
+    turns: usize,
+    output: Option<&Path>,
+) -> Result<()> {
+    // Without --output the document itself is the command's output
+    let Some(path) = output else {
+        print!("{}", document);
+        return Ok(());
+    };
+
+    std::fs::write(path, document).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{}",
+                colors::success(&format!("Exported {} turn(s) to {}", turns, path.display()))
+            );
+        }
+        OutputFormat::Json => {
+            let summary = json!({
+                "session_id": session_id,
+                "turns": turns,
+                "bytes": document.len(),
+                "output": path.to_string_lossy(),
+            });
+            println!("{}", serde_json::to_string(&summary)?);
+        }
+        OutputFormat::Minimal => {
+            println!("{}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Token usage summed over one turn
+#[derive(Debug, Default)]
+struct TurnUsage {
+    input: u64,
+    output: u64,
+    cache_read: u64,
+    cache_creation: u64,
+    model: Option<String>,
+}
+
+/// A turn being written; the heading is added once its usage is known
+struct Turn {
+    number: usize,
+    body: String,
+    usage: TurnUsage,
+}
+
+/// Render a session as Markdown, returning the document and its turn count
+pub fn render(
+    session_id: &str,
+    lines: &[TranscriptLine],
+    options: &MarkdownOptions,
+    annotations: &[Annotation],
+) -> (String, usize) {
+    let mut out = String::new();
+    write_header(&mut out, session_id, lines);
+    let count = render_turns(&mut out, lines, options, 1, annotations);
+    (out, count)
+}
+
+/// Render the turns in `lines` numbered from `first`, with usage footnotes
+///
+/// Annotations are quoted after the line they are on (or after the last
+/// line written before it, for lines the document leaves out). Returns the
+/// number of turns written.
+pub fn render_turns(
+    out: &mut String,
+    lines: &[TranscriptLine],
+    options: &MarkdownOptions,
+    first: usize,
+    annotations: &[Annotation],
+) -> usize {
+    // Tool results arrive on later user lines: id -> (text, is_error)
+    let mut results: HashMap<String, (String, bool)> = HashMap::new();
+    if options.tools {
+        for line in lines {
+            let Some(MessageContent::Blocks(blocks)) = line.message().map(|m| m.content) else {
+                continue;
+            };
+            for block in blocks {
+                if let ContentBlock::ToolResult { tool_use_id, content, is_error } = block {
+                    results.insert(tool_use_id, (result_text(&content), is_error));
+                }
+            }
+        }
+    }
+
+    let noise = noise_classifier();
+    let mut footnotes = Vec::new();
+    let mut current: Option<Turn> = None;
+    let mut count = 0;
+    // Quoted annotations of the previous line, written once its content is
+    let mut notes = String::new();
+
+    for (line, usage) in lines.iter().zip(response_usage(lines)) {
+        if let Some(turn) = current.as_mut() {
+            turn.body.push_str(&notes);
+            notes.clear();
+        }
+        let on_line: Vec<Annotation> = annotations.iter().filter(|a| a.uuid == line.uuid).cloned().collect();
+        write_annotations(&mut notes, &on_line);
+
+        // Compactions, API errors and rate limits explain gaps in the turn
+        if let Some(summary) = system_summary(line).filter(|s| s.level >= SystemLevel::Warning) {
+            if let Some(turn) = current.as_mut() {
+                let _ = write!(turn.body, "_System: {}_\n\n", plain_text(&summary.text));
+            }
+            continue;
+        }
+        let Some(msg) = line.message() else {
+            continue;
+        };
+        if noise.is_noise(line) {
+            continue;
+        }
+
+        let prompt = is_prompt(line);
+        if line.line_type != LineType::Assistant && !prompt {
+            continue;
+        }
+        if prompt || current.is_none() {
+            if let Some(turn) = current.take() {
+                finish_turn(out, &mut footnotes, turn);
+            }
+            count += 1;
+            current = Some(Turn {
+                number: first + count - 1,
+                body: String::new(),
+                usage: TurnUsage::default(),
+            });
+        }
+        let Some(turn) = current.as_mut() else {
+            continue;
+        };
+
+        if prompt {
+            let text = plain_text(&msg.content.as_text());
+            turn.body.push_str("**User:**\n\n");
+            for l in text.trim().lines() {
+                let _ = writeln!(turn.body, "> {}", l);
+            }
+            turn.body.push('\n');
+            continue;
+        }
+
+        // Assistant line
+        if let Some(model) = &line.model {
+            turn.usage.model = Some(model.clone());
+        }
+        if let Some(usage) = &usage {
+            turn.usage.input += usage.input_tokens;
+            turn.usage.output += usage.output_tokens;
+            turn.usage.cache_read += usage.cache_read_input_tokens.unwrap_or(0);
+            turn.usage.cache_creation += usage.cache_creation_input_tokens.unwrap_or(0);
+        }
+
+        let blocks = match msg.content {
+            MessageContent::Text(text) => vec![ContentBlock::Text { text }],
+            MessageContent::Blocks(blocks) => blocks,
+        };
+        for block in blocks {
+            match block {
+                ContentBlock::Text { text } if !text.trim().is_empty() => {
+                    let _ = write!(turn.body, "**Assistant:**\n\n{}\n\n", plain_text(text.trim()));
+                }
+                ContentBlock::Thinking { thinking, .. } if options.thinking && !thinking.trim().is_empty() => {
+                    let _ = write!(
+                        turn.body,
+                        "<details>\n<summary>Thinking</summary>\n\n{}\n</details>\n\n",
+                        fence(&plain_text(thinking.trim()), "")
+                    );
+                }
+                ContentBlock::ToolUse { id, name, input } if options.tools => {
+                    write_tool(&mut turn.body, &name, &input, results.get(&id), options.max_output);
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(mut turn) = current.take() {
+        turn.body.push_str(&notes);
+        finish_turn(out, &mut footnotes, turn);
+    }
+
+    if !footnotes.is_empty() {
+        out.push_str("---\n\n");
+        for note in footnotes {
+            out.push_str(&note);
+            out.push('\n');
+        }
+    }
+
+    count
+}
+
+/// Annotation threads as nested block quotes
+fn write_annotations(out: &mut String, annotations: &[Annotation]) {
+    for (depth, annotation) in annotation_threads(annotations) {
+        let quote = "> ".repeat(depth + 1);
+        let created = annotation.created_at.get(..16).unwrap_or_default().replace('T', " ");
+        let _ = writeln!(out, "{}**{}** ({}):", quote, annotation.author, created);
+        for text in plain_text(annotation.body.trim()).lines() {
+            let _ = writeln!(out, "{}{}", quote, text);
+        }
+        out.push('\n');
+    }
+}
+
+/// Title and a short metadata list
+fn write_header(out: &mut String, session_id: &str, lines: &[TranscriptLine]) {
+    let name = lines
+        .iter()
+        .find_map(|l| l.session_name.clone().or_else(|| l.slug.clone()))
+        .unwrap_or_else(|| session_id.to_string());
+    let _ = writeln!(out, "# {}\n", name);
+    let _ = writeln!(out, "- **Session:** `{}`", session_id);
+    if let Some(cwd) = lines.iter().find_map(|l| l.cwd.as_deref()) {
+        let _ = writeln!(out, "- **Directory:** `{}`", cwd);
+    }
+    if let (Some(first), Some(last)) = (lines.first(), lines.last()) {
+        let _ = writeln!(out, "- **Time:** {} → {}", first.timestamp, last.timestamp);
+    }
+    let mut models: Vec<&str> = Vec::new();
+    for model in lines.iter().filter_map(|l| l.model.as_deref()) {
+        if !models.contains(&model) {
+            models.push(model);
+        }
+    }
+    if !models.is_empty() {
+        let _ = writeln!(out, "- **Model:** {}", models.join(", "));
+    }
+    out.push('\n');
+}
+
+/// Write a turn under its heading, adding a usage footnote if it has one
+fn finish_turn(out: &mut String, footnotes: &mut Vec<String>, turn: Turn) {
+    let usage = &turn.usage;
+    if usage.input + usage.output + usage.cache_read + usage.cache_creation > 0 {
+        let _ = writeln!(out, "## Turn {}[^turn-{}]\n", turn.number, turn.number);
+        let mut note = format!(
+            "[^turn-{}]: {} input / {} output tokens",
+            turn.number,
+            format_count(usage.input as i64),
+            format_count(usage.output as i64)
+        );
+        if usage.cache_read + usage.cache_creation > 0 {
+            let _ = write!(
+                note,
+                " ({} cache read, {} cache write)",
+                format_count(usage.cache_read as i64),
+                format_count(usage.cache_creation as i64)
+            );
+        }
+        if let Some(model) = &usage.model {
+            let _ = write!(note, ", {}", model);
+        }
+        footnotes.push(note);
+    } else {
+        let _ = writeln!(out, "## Turn {}\n", turn.number);
+    }
+    out.push_str(&turn.body);
+}
+
+/// A collapsed tool call: input as JSON, result as plain text
+fn write_tool(
+    out: &mut String,
+    name: &str,
+    input: &serde_json::Value,
+    result: Option<&(String, bool)>,
+    max_output: usize,
+) {
+    let preview = get_tool_preview(name, input);
+    let summary = if preview.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}: {}", name, preview)
+    };
+    let input = serde_json::to_string_pretty(input).unwrap_or_default();
+
+    let _ = write!(
+        out,
+        "<details>\n<summary>🔧 {}</summary>\n\n{}\n",
+        escape_html(&summary),
+        fence(&input, "json")
+    );
+    if let Some((text, is_error)) = result {
+        let text = plain_text(text);
+        let _ = write!(
+            out,
+            "\n{}\n\n{}\n",
+            if *is_error { "**Error:**" } else { "**Result:**" },
+            fence(&clip(text.trim_end(), max_output), "")
+        );
+    }
+    out.push_str("</details>\n\n");
+}
+
+/// Text without terminal escapes; binary content becomes a hex preview
+pub fn plain_text(text: &str) -> String {
+    if is_binary(text) {
+        display_text(text).into_owned()
+    } else {
+        strip_ansi(text)
+    }
+}
+
+/// Fence text in a code block that its own backticks can't close
+pub fn fence(text: &str, lang: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        if c == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    let ticks = "`".repeat((longest + 1).max(3));
+    format!("{}{}\n{}\n{}", ticks, lang, text, ticks)
+}
+
+/// Keep the first `max` characters, noting how much was cut
+pub fn clip(text: &str, max: usize) -> String {
+    let total = text.chars().count();
+    if total <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max).collect();
+    format!("{}\n… ({} more characters)", kept, total - max)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_type: LineType, raw: serde_json::Value) -> TranscriptLine {
+        TranscriptLine {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            slug: Some("happy-dog".to_string()),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_fence_outlasts_content_backticks() {
+        assert_eq!(fence("a", "json"), "```json\na\n```");
+        assert_eq!(fence("```x```", ""), "````\n```x```\n````");
+    }
+
+    #[test]
+    fn test_render_turn_with_tool_and_usage() {
+        let lines = vec![
+            line(LineType::User, json!({"message": {"role": "user", "content": "run tests"}})),
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "text", "text": "Running them."},
+                {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "cargo test"}}
+            ], "usage": {"input_tokens": 1200, "output_tokens": 30}}})),
+            line(LineType::User, json!({"message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "\u{1b}[32mok\u{1b}[0m"}
+            ]}})),
+        ];
+        let mut lines = lines;
+        lines[1].uuid = "a1".to_string();
+        let note = |id, parent_id, author: &str, body: &str| Annotation {
+            id,
+            session_id: "s1".to_string(),
+            uuid: "a1".to_string(),
+            line_number: 2,
+            parent_id,
+            author: author.to_string(),
+            body: body.to_string(),
+            created_at: "2026-01-02T09:30:00+00:00".to_string(),
+        };
+        let annotations = vec![note(1, None, "ann", "Why not nextest?"), note(2, Some(1), "bob", "Not installed")];
+        let options = MarkdownOptions { tools: true, thinking: false, max_output: 100, annotations: true };
+        let (doc, turns) = render("s1", &lines, &options, &annotations);
+
+        assert_eq!(turns, 1);
+        assert!(doc.starts_with("# happy-dog\n"));
+        assert!(doc.contains("## Turn 1[^turn-1]\n\n**User:**\n\n> run tests\n"));
+        assert!(doc.contains("**Assistant:**\n\nRunning them."));
+        assert!(doc.contains("<summary>🔧 Bash: cargo test</summary>"));
+        assert!(doc.contains("**Result:**\n\n```\nok\n```"));
+        assert!(doc.contains(
+            "</details>\n\n> **ann** (2026-01-02 09:30):\n> Why not nextest?\n\n> > **bob** (2026-01-02 09:30):\n> > Not installed\n"
+        ));
+        assert!(doc.contains("[^turn-1]: 1,200 input / 30 output tokens"));
+    }
+}
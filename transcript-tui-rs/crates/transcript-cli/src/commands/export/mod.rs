@@ -0,0 +1,16 @@
+//! Export command - write transcript data to external formats
+
+pub mod jsonl;
+pub mod observability;
+pub mod otlp;
+pub mod markdown;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod sqlite;
+#[cfg(feature = "parquet")]
+mod table;
+pub mod template;
+pub mod trace;
+
+#[cfg(feature = "parquet")]
+pub use table::*;
@@ -0,0 +1,408 @@
+//! Export to LLM observability platforms (Langfuse, LangSmith)
+//!
+//! Sessions become traces, turns become generations (LLM runs) carrying model
+//! and token usage, and tool calls become child spans (tool runs). IDs are
+//! derived from session/turn/tool IDs so re-exporting updates rather than
+//! duplicates.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use transcript_db::TranscriptDb;
+
+use super::trace::{build_session_trace, fnv1a, select_sessions, truncate, SessionTrace};
+use crate::cli::{Cli, ObservabilityPlatform, OutputFormat};
+use crate::output::colors;
+
+/// Longest input/output text sent per observation
+const MAX_TEXT: usize = 10_000;
+
+const LANGFUSE_DEFAULT_HOST: &str = "https://cloud.langfuse.com";
+const LANGSMITH_DEFAULT_ENDPOINT: &str = "https://api.smith.langchain.com";
+
+/// Credentials and target for one platform
+pub struct PlatformConfig {
+    pub endpoint: Option<String>,
+    pub public_key: Option<String>,
+    pub api_key: Option<String>,
+    pub project: Option<String>,
+}
+
+impl ObservabilityPlatform {
+    fn name(self) -> &'static str {
+        match self {
+            ObservabilityPlatform::Langfuse => "Langfuse",
+            ObservabilityPlatform::Langsmith => "LangSmith",
+        }
+    }
+}
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    platform: ObservabilityPlatform,
+    session: Option<&str>,
+    days: Option<i64>,
+    config: PlatformConfig,
+    output: Option<&Path>,
+) -> Result<()> {
+    let session_ids = select_sessions(db, session, days)?;
+    let mut traces = Vec::new();
+    for id in &session_ids {
+        if let Some(trace) = build_session_trace(db, id)? {
+            traces.push(trace);
+        }
+    }
+
+    let (payload, observations) = match platform {
+        ObservabilityPlatform::Langfuse => langfuse_batch(&traces),
+        ObservabilityPlatform::Langsmith => {
+            langsmith_batch(&traces, config.project.as_deref().unwrap_or("claude-code"))
+        }
+    };
+
+    let target = if let Some(path) = output {
+        let text = if cli.pretty {
+            serde_json::to_string_pretty(&payload)?
+        } else {
+            serde_json::to_string(&payload)?
+        };
+        std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+        path.display().to_string()
+    } else {
+        send(platform, &config, &payload)?
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{}",
+                colors::success(&format!(
+                    "Exported {} trace(s), {} observation(s) to {} ({})",
+                    traces.len(),
+                    observations,
+                    platform.name(),
+                    target
+                ))
+            );
+        }
+        OutputFormat::Json => {
+            let summary = json!({
+                "platform": platform.name(),
+                "target": target,
+                "traces": traces.len(),
+                "observations": observations,
+            });
+            println!("{}", serde_json::to_string(&summary)?);
+        }
+        OutputFormat::Minimal => {
+            println!("{}", observations);
+        }
+    }
+
+    Ok(())
+}
+
+/// POST the payload to the platform's ingestion API, returning the URL used
+fn send(platform: ObservabilityPlatform, config: &PlatformConfig, payload: &Value) -> Result<String> {
+    let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+    let request = match platform {
+        ObservabilityPlatform::Langfuse => {
+            let host = config
+                .endpoint
+                .clone()
+                .or_else(|| env("LANGFUSE_HOST"))
+                .unwrap_or_else(|| LANGFUSE_DEFAULT_HOST.to_string());
+            let public_key = config.public_key.clone().or_else(|| env("LANGFUSE_PUBLIC_KEY"));
+            let secret_key = config.api_key.clone().or_else(|| env("LANGFUSE_SECRET_KEY"));
+            let (Some(public_key), Some(secret_key)) = (public_key, secret_key) else {
+                bail!("Langfuse needs --public-key and --api-key (or LANGFUSE_PUBLIC_KEY / LANGFUSE_SECRET_KEY)");
+            };
+            let auth = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", public_key, secret_key));
+            let url = format!("{}/api/public/ingestion", host.trim_end_matches('/'));
+            ureq::post(&url).set("Authorization", &format!("Basic {}", auth))
+        }
+        ObservabilityPlatform::Langsmith => {
+            let endpoint = config
+                .endpoint
+                .clone()
+                .or_else(|| env("LANGSMITH_ENDPOINT"))
+                .unwrap_or_else(|| LANGSMITH_DEFAULT_ENDPOINT.to_string());
+            let Some(api_key) = config.api_key.clone().or_else(|| env("LANGSMITH_API_KEY")) else {
+                bail!("LangSmith needs --api-key (or LANGSMITH_API_KEY)");
+            };
+            let url = format!("{}/runs/batch", endpoint.trim_end_matches('/'));
+            ureq::post(&url).set("x-api-key", &api_key)
+        }
+    };
+
+    let url = request.url().to_string();
+    request
+        .set("Content-Type", "application/json")
+        .send_string(&payload.to_string())
+        .with_context(|| format!("Failed to send to {}", url))?;
+    Ok(url)
+}
+
+/// Deterministic UUID-formatted ID for a key
+fn uuid_for(key: &str) -> String {
+    let hi = fnv1a(key);
+    let lo = fnv1a(&format!("{}#lo", key));
+    format!(
+        "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+        hi >> 32,
+        (hi >> 16) & 0xffff,
+        hi & 0x0fff,
+        ((lo >> 48) & 0x3fff) | 0x8000,
+        lo & 0xffff_ffff_ffff
+    )
+}
+
+fn iso(t: &DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Langfuse ingestion batch: trace-create, generation-create, span-create events
+fn langfuse_batch(traces: &[SessionTrace]) -> (Value, usize) {
+    let mut batch = Vec::new();
+    let mut observations = 0;
+
+    let mut event = |kind: &str, id: &str, timestamp: &DateTime<Utc>, body: Value| {
+        batch.push(json!({
+            "id": uuid_for(&format!("{}#{}", id, kind)),
+            "type": kind,
+            "timestamp": iso(timestamp),
+            "body": body,
+        }));
+    };
+
+    for trace in traces {
+        let trace_id = uuid_for(&trace.session_id);
+        let first_input = trace.turns.first().map(|t| truncate(&t.input, MAX_TEXT));
+        let last_output = trace.turns.last().map(|t| truncate(&t.output, MAX_TEXT));
+
+        event(
+            "trace-create",
+            &trace_id,
+            &trace.start,
+            json!({
+                "id": trace_id,
+                "name": trace.name,
+                "sessionId": trace.session_id,
+                "timestamp": iso(&trace.start),
+                "input": first_input,
+                "output": last_output,
+                "metadata": { "cwd": trace.cwd, "turns": trace.turns.len() },
+                "tags": ["claude-code"],
+            }),
+        );
+
+        for turn in &trace.turns {
+            let generation_id = uuid_for(&format!("{}#turn#{}", trace.session_id, turn.key));
+            observations += 1;
+
+            event(
+                "generation-create",
+                &generation_id,
+                &turn.start,
+                json!({
+                    "id": generation_id,
+                    "traceId": trace_id,
+                    "name": format!("turn {}", turn.sequence),
+                    "startTime": iso(&turn.start),
+                    "endTime": iso(&turn.end),
+                    "model": turn.model,
+                    "input": truncate(&turn.input, MAX_TEXT),
+                    "output": truncate(&turn.output, MAX_TEXT),
+                    "usage": {
+                        "input": turn.input_tokens,
+                        "output": turn.output_tokens,
+                        "total": turn.input_tokens + turn.output_tokens,
+                        "unit": "TOKENS",
+                    },
+                    "metadata": {
+                        "turn_id": turn.key,
+                        "cache_read_input_tokens": turn.cache_read_tokens,
+                        "cache_creation_input_tokens": turn.cache_creation_tokens,
+                    },
+                }),
+            );
+
+            for tool in &turn.tools {
+                let span_id = uuid_for(&format!("{}#tool#{}", trace.session_id, tool.tool_use_id));
+                observations += 1;
+
+                event(
+                    "span-create",
+                    &span_id,
+                    &tool.start,
+                    json!({
+                        "id": span_id,
+                        "traceId": trace_id,
+                        "parentObservationId": generation_id,
+                        "name": tool.name,
+                        "startTime": iso(&tool.start),
+                        "endTime": iso(&tool.end),
+                        "input": tool.input,
+                        "output": truncate(&tool.output, MAX_TEXT),
+                        "level": if tool.is_error { "ERROR" } else { "DEFAULT" },
+                        "metadata": { "tool_use_id": tool.tool_use_id },
+                    }),
+                );
+            }
+        }
+    }
+
+    (json!({ "batch": batch }), observations)
+}
+
+/// LangSmith dotted_order segment: start time + run ID
+fn dotted_segment(t: &DateTime<Utc>, run_id: &str) -> String {
+    format!("{}{}", t.format("%Y%m%dT%H%M%S%6fZ"), run_id)
+}
+
+/// LangSmith batch: chain (session) → llm (turn) → tool runs
+fn langsmith_batch(traces: &[SessionTrace], project: &str) -> (Value, usize) {
+    let mut runs = Vec::new();
+    let mut observations = 0;
+
+    for trace in traces {
+        let root_id = uuid_for(&trace.session_id);
+        let root_order = dotted_segment(&trace.start, &root_id);
+
+        runs.push(json!({
+            "id": root_id,
+            "trace_id": root_id,
+            "dotted_order": root_order,
+            "name": trace.name,
+            "run_type": "chain",
+            "start_time": iso(&trace.start),
+            "end_time": iso(&trace.end),
+            "session_name": project,
+            "inputs": { "prompt": trace.turns.first().map(|t| truncate(&t.input, MAX_TEXT)) },
+            "outputs": { "response": trace.turns.last().map(|t| truncate(&t.output, MAX_TEXT)) },
+            "extra": { "metadata": { "session_id": trace.session_id, "cwd": trace.cwd } },
+            "tags": ["claude-code"],
+        }));
+
+        for turn in &trace.turns {
+            let turn_id = uuid_for(&format!("{}#turn#{}", trace.session_id, turn.key));
+            let turn_order = format!("{}.{}", root_order, dotted_segment(&turn.start, &turn_id));
+            observations += 1;
+
+            runs.push(json!({
+                "id": turn_id,
+                "trace_id": root_id,
+                "parent_run_id": root_id,
+                "dotted_order": turn_order,
+                "name": format!("turn {}", turn.sequence),
+                "run_type": "llm",
+                "start_time": iso(&turn.start),
+                "end_time": iso(&turn.end),
+                "session_name": project,
+                "inputs": { "prompt": truncate(&turn.input, MAX_TEXT) },
+                "outputs": {
+                    "response": truncate(&turn.output, MAX_TEXT),
+                    "usage_metadata": {
+                        "input_tokens": turn.input_tokens,
+                        "output_tokens": turn.output_tokens,
+                        "total_tokens": turn.input_tokens + turn.output_tokens,
+                    },
+                },
+                "extra": {
+                    "metadata": {
+                        "ls_provider": "anthropic",
+                        "ls_model_name": turn.model,
+                        "turn_id": turn.key,
+                    },
+                },
+            }));
+
+            for tool in &turn.tools {
+                let tool_id = uuid_for(&format!("{}#tool#{}", trace.session_id, tool.tool_use_id));
+                observations += 1;
+
+                let mut run = json!({
+                    "id": tool_id,
+                    "trace_id": root_id,
+                    "parent_run_id": turn_id,
+                    "dotted_order": format!("{}.{}", turn_order, dotted_segment(&tool.start, &tool_id)),
+                    "name": tool.name,
+                    "run_type": "tool",
+                    "start_time": iso(&tool.start),
+                    "end_time": iso(&tool.end),
+                    "session_name": project,
+                    "inputs": tool.input,
+                    "outputs": { "output": truncate(&tool.output, MAX_TEXT) },
+                    "extra": { "metadata": { "tool_use_id": tool.tool_use_id } },
+                });
+                if tool.is_error {
+                    run["error"] = json!(truncate(&tool.output, 1000));
+                }
+                runs.push(run);
+            }
+        }
+    }
+
+    (json!({ "post": runs }), observations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::export::trace::tests::sample_trace;
+
+    #[test]
+    fn test_uuid_for() {
+        let id = uuid_for("session");
+        assert_eq!(id, uuid_for("session"));
+        assert_ne!(id, uuid_for("other"));
+        let groups: Vec<usize> = id.split('-').map(str::len).collect();
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        // Version 4, RFC 4122 variant
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+    }
+
+    #[test]
+    fn test_langfuse_batch() {
+        let (payload, observations) = langfuse_batch(&[sample_trace()]);
+        assert_eq!(observations, 3);
+        let batch = payload["batch"].as_array().unwrap();
+        let kinds: Vec<_> = batch.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["trace-create", "generation-create", "span-create", "span-create"]);
+
+        let trace = &batch[0]["body"];
+        let generation = &batch[1]["body"];
+        assert_eq!(trace["input"], "Run the tests");
+        assert_eq!(generation["traceId"], trace["id"]);
+        assert_eq!(generation["usage"]["total"], 120);
+        assert_eq!(batch[2]["body"]["parentObservationId"], generation["id"]);
+        assert_eq!(batch[2]["body"]["level"], "ERROR");
+        assert_eq!(batch[3]["body"]["level"], "DEFAULT");
+    }
+
+    #[test]
+    fn test_langsmith_batch() {
+        let (payload, observations) = langsmith_batch(&[sample_trace()], "agents");
+        assert_eq!(observations, 3);
+        let runs = payload["post"].as_array().unwrap();
+        let kinds: Vec<_> = runs.iter().map(|r| r["run_type"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["chain", "llm", "tool", "tool"]);
+        assert!(runs.iter().all(|r| r["session_name"] == "agents" && r["trace_id"] == runs[0]["id"]));
+
+        // Each dotted_order extends its parent's
+        let order = |i: usize| runs[i]["dotted_order"].as_str().unwrap().to_string();
+        assert!(order(0).starts_with("20260101T100000000000Z"));
+        assert!(order(1).starts_with(&format!("{}.", order(0))));
+        assert!(order(2).starts_with(&format!("{}.", order(1))));
+        assert_eq!(runs[2]["parent_run_id"], runs[1]["id"]);
+        assert_eq!(runs[2]["error"], "ok");
+        assert!(runs[3].get("error").is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//! OpenTelemetry (OTLP/HTTP JSON) trace export
+//!
+//! Sessions map to traces, turns to spans under a session root span, and tool
+//! calls to child spans of their turn.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use transcript_db::TranscriptDb;
+
+use super::trace::{build_session_trace, fnv1a, select_sessions, truncate, SessionTrace};
+use crate::cli::{Cli, OutputFormat};
+use crate::output::colors;
+
+/// OTLP span kind INTERNAL
+const SPAN_KIND_INTERNAL: i64 = 1;
+/// OTLP status code ERROR
+const STATUS_CODE_ERROR: i64 = 2;
+
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session: Option<&str>,
+    days: Option<i64>,
+    endpoint: Option<&str>,
+    headers: &[String],
+    output: Option<&Path>,
+    service_name: &str,
+) -> Result<()> {
+    if endpoint.is_none() && output.is_none() {
+        bail!("Specify --endpoint <url> to send traces or --output <file> to write OTLP JSON");
+    }
+
+    let session_ids = select_sessions(db, session, days)?;
+    let mut traces = Vec::new();
+    for id in &session_ids {
+        if let Some(trace) = build_session_trace(db, id)? {
+            traces.push(trace);
+        }
+    }
+
+    let payload = build_payload(&traces, service_name);
+    let span_count: usize = traces
+        .iter()
+        .map(|t| 1 + t.turns.len() + t.turns.iter().map(|turn| turn.tools.len()).sum::<usize>())
+        .sum();
+
+    if let Some(path) = output {
+        let text = if cli.pretty {
+            serde_json::to_string_pretty(&payload)?
+        } else {
+            serde_json::to_string(&payload)?
+        };
+        std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    if let Some(endpoint) = endpoint {
+        let url = traces_url(endpoint);
+        let mut request = ureq::post(&url).set("Content-Type", "application/json");
+        for header in headers {
+            let Some((key, value)) = header.split_once('=') else {
+                bail!("Invalid header '{}', expected KEY=VALUE", header);
+            };
+            request = request.set(key.trim(), value.trim());
+        }
+        request
+            .send_string(&payload.to_string())
+            .with_context(|| format!("Failed to send traces to {}", url))?;
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            let target = match (endpoint, output) {
+                (Some(e), _) => traces_url(e),
+                (None, Some(p)) => p.display().to_string(),
+                (None, None) => unreachable!(),
+            };
+            println!(
+                "{}",
+                colors::success(&format!(
+                    "Exported {} trace(s), {} span(s) to {}",
+                    traces.len(),
+                    span_count,
+                    target
+                ))
+            );
+        }
+        OutputFormat::Json => {
+            let summary = json!({
+                "traces": traces.len(),
+                "spans": span_count,
+                "endpoint": endpoint.map(traces_url),
+                "output": output.map(|p| p.to_string_lossy().to_string()),
+            });
+            println!("{}", serde_json::to_string(&summary)?);
+        }
+        OutputFormat::Minimal => {
+            println!("{}", span_count);
+        }
+    }
+
+    Ok(())
+}
+
+/// Normalize an OTLP/HTTP endpoint to the traces path
+fn traces_url(endpoint: &str) -> String {
+    let trimmed = endpoint.trim_end_matches('/');
+    if trimmed.ends_with("/v1/traces") {
+        trimmed.to_string()
+    } else {
+        format!("{}/v1/traces", trimmed)
+    }
+}
+
+/// 128-bit trace ID: the session UUID when it is one, otherwise a hash
+fn trace_id(session_id: &str) -> String {
+    let hex: String = session_id.chars().filter(|c| *c != '-').collect();
+    if hex.len() == 32 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        hex.to_lowercase()
+    } else {
+        format!("{:016x}{:016x}", fnv1a(session_id), fnv1a(&format!("{}#trace", session_id)))
+    }
+}
+
+/// 64-bit span ID derived from a stable key
+fn span_id(key: &str) -> String {
+    format!("{:016x}", fnv1a(key))
+}
+
+fn nanos(t: &chrono::DateTime<chrono::Utc>) -> String {
+    t.timestamp_nanos_opt().unwrap_or(0).to_string()
+}
+
+fn attr(key: &str, value: Value) -> Value {
+    let typed = match value {
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({ "intValue": n.to_string() }),
+        Value::Number(n) => json!({ "doubleValue": n }),
+        Value::Bool(b) => json!({ "boolValue": b }),
+        Value::String(s) => json!({ "stringValue": s }),
+        other => json!({ "stringValue": other.to_string() }),
+    };
+    json!({ "key": key, "value": typed })
+}
+
+/// Build the OTLP ExportTraceServiceRequest JSON body
+fn build_payload(traces: &[SessionTrace], service_name: &str) -> Value {
+    let mut spans = Vec::new();
+
+    for trace in traces {
+        let trace_id = trace_id(&trace.session_id);
+        let root_id = span_id(&trace.session_id);
+
+        let mut root_attrs = vec![
+            attr("session.id", json!(trace.session_id)),
+            attr("session.name", json!(trace.name)),
+            attr("session.turns", json!(trace.turns.len())),
+        ];
+        if let Some(cwd) = &trace.cwd {
+            root_attrs.push(attr("session.cwd", json!(cwd)));
+        }
+
+        spans.push(json!({
+            "traceId": trace_id,
+            "spanId": root_id,
+            "name": format!("session {}", trace.name),
+            "kind": SPAN_KIND_INTERNAL,
+            "startTimeUnixNano": nanos(&trace.start),
+            "endTimeUnixNano": nanos(&trace.end),
+            "attributes": root_attrs,
+        }));
+
+        for turn in &trace.turns {
+            let turn_id = span_id(&format!("{}#turn#{}", trace.session_id, turn.key));
+
+            let mut attrs = vec![
+                attr("turn.id", json!(turn.key)),
+                attr("turn.sequence", json!(turn.sequence)),
+                attr("gen_ai.usage.input_tokens", json!(turn.input_tokens)),
+                attr("gen_ai.usage.output_tokens", json!(turn.output_tokens)),
+                attr("gen_ai.usage.cache_read_input_tokens", json!(turn.cache_read_tokens)),
+                attr("gen_ai.usage.cache_creation_input_tokens", json!(turn.cache_creation_tokens)),
+            ];
+            if let Some(model) = &turn.model {
+                attrs.push(attr("gen_ai.request.model", json!(model)));
+            }
+            if !turn.input.is_empty() {
+                attrs.push(attr("turn.prompt", json!(truncate(&turn.input, 1000))));
+            }
+
+            spans.push(json!({
+                "traceId": trace_id,
+                "spanId": turn_id,
+                "parentSpanId": root_id,
+                "name": format!("turn {}", turn.sequence),
+                "kind": SPAN_KIND_INTERNAL,
+                "startTimeUnixNano": nanos(&turn.start),
+                "endTimeUnixNano": nanos(&turn.end),
+                "attributes": attrs,
+            }));
+
+            for tool in &turn.tools {
+                let mut attrs = vec![
+                    attr("tool.name", json!(tool.name)),
+                    attr("tool.use_id", json!(tool.tool_use_id)),
+                ];
+                if let Some(path) = tool.input.get("file_path").and_then(|v| v.as_str()) {
+                    attrs.push(attr("tool.file_path", json!(path)));
+                }
+                if let Some(cmd) = tool.input.get("command").and_then(|v| v.as_str()) {
+                    attrs.push(attr("tool.command", json!(truncate(cmd, 1000))));
+                }
+
+                let mut span = json!({
+                    "traceId": trace_id,
+                    "spanId": span_id(&format!("{}#tool#{}", trace.session_id, tool.tool_use_id)),
+                    "parentSpanId": turn_id,
+                    "name": tool.name,
+                    "kind": SPAN_KIND_INTERNAL,
+                    "startTimeUnixNano": nanos(&tool.start),
+                    "endTimeUnixNano": nanos(&tool.end),
+                    "attributes": attrs,
+                });
+                if tool.is_error {
+                    span["status"] = json!({ "code": STATUS_CODE_ERROR });
+                }
+                spans.push(span);
+            }
+        }
+    }
+
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [attr("service.name", json!(service_name))],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "transcript-cli", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::export::trace::tests::sample_trace;
+
+    #[test]
+    fn test_traces_url() {
+        assert_eq!(traces_url("http://localhost:4318"), "http://localhost:4318/v1/traces");
+        assert_eq!(traces_url("http://localhost:4318/v1/traces/"), "http://localhost:4318/v1/traces");
+    }
+
+    #[test]
+    fn test_trace_id() {
+        // Session UUIDs are used as-is, other ids hashed to 32 hex digits
+        assert_eq!(trace_id("0F8FAD5B-D9CB-469F-A165-70867728950E"), "0f8fad5bd9cb469fa16570867728950e");
+        let hashed = trace_id("imported-session");
+        assert_eq!(hashed.len(), 32);
+        assert_eq!(hashed, trace_id("imported-session"));
+    }
+
+    #[test]
+    fn test_build_payload() {
+        let payload = build_payload(&[sample_trace()], "agents");
+        let spans = payload["resourceSpans"][0]["scopeSpans"][0]["spans"].as_array().unwrap();
+        let names: Vec<_> = spans.iter().map(|s| s["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["session loyal-whippet", "turn 1", "Bash", "Bash"]);
+
+        // Session -> turn -> tool call
+        assert!(spans[0].get("parentSpanId").is_none());
+        assert_eq!(spans[1]["parentSpanId"], spans[0]["spanId"]);
+        assert_eq!(spans[2]["parentSpanId"], spans[1]["spanId"]);
+        assert_ne!(spans[2]["spanId"], spans[3]["spanId"]);
+        assert!(spans.iter().all(|s| s["traceId"] == "0f8fad5bd9cb469fa16570867728950e"));
+
+        assert_eq!(spans[2]["status"]["code"], STATUS_CODE_ERROR);
+        assert!(spans[3].get("status").is_none());
+        assert_eq!(spans[2]["startTimeUnixNano"], "1767261605000000000");
+        let tokens = spans[1]["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["key"] == "gen_ai.usage.input_tokens")
+            .unwrap();
+        assert_eq!(tokens["value"]["intValue"], "100");
+        assert_eq!(
+            payload["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"],
+            "agents"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Parquet export for columnar analysis (DuckDB, pandas, polars)
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use parquet::basic::Compression;
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use transcript_db::TranscriptDb;
+
+use super::{fetch_rows, Cell, ColumnKind, ExportFilter, ExportRows};
+use crate::cli::{Cli, ExportTable, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors;
+
+/// Rows per row group (keeps memory bounded for very large tables)
+const ROW_GROUP_SIZE: usize = 50_000;
+
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    tables: &[ExportTable],
+    output: Option<&Path>,
+    columns: Option<&[String]>,
+    session: Option<&str>,
+    from_time: Option<&str>,
+    to_time: Option<&str>,
+) -> Result<()> {
+    let mut tables = tables.to_vec();
+    tables.dedup();
+    let several = tables.len() > 1;
+    if several && columns.is_some() {
+        bail!("--columns needs a single --table");
+    }
+    let filter = ExportFilter {
+        session_id: session.map(|s| resolve_session_id(db, s)).transpose()?,
+        from_time: from_time.map(String::from),
+        to_time: to_time.map(String::from),
+    };
+
+    // Several tables go into a directory, one file each
+    let dir = match output {
+        Some(dir) if several => {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+            dir.to_path_buf()
+        }
+        _ => PathBuf::new(),
+    };
+
+    let mut exported = Vec::new();
+    for table in tables {
+        let data = fetch_rows(db, table, columns, &filter)?;
+        let path = match output {
+            Some(path) if !several => path.to_path_buf(),
+            _ => dir.join(format!("{}.parquet", table.name())),
+        };
+
+        write_parquet(&path, table.name(), &data)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        exported.push((table, path, data, size));
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            for (table, path, data, size) in &exported {
+                println!(
+                    "{}",
+                    colors::success(&format!(
+                        "Exported {} {} rows ({} columns) to {} ({})",
+                        colors::format_count(data.rows.len() as i64),
+                        table.name(),
+                        data.columns.len(),
+                        path.display(),
+                        colors::format_size(*size)
+                    ))
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let mut files: Vec<_> = exported
+                .iter()
+                .map(|(table, path, data, size)| {
+                    serde_json::json!({
+                        "table": table.name(),
+                        "path": path.to_string_lossy(),
+                        "rows": data.rows.len(),
+                        "columns": data.columns.iter().map(|(c, _)| *c).collect::<Vec<_>>(),
+                        "bytes": size,
+                    })
+                })
+                .collect();
+            // One table prints an object, several an array
+            let output = if several { serde_json::Value::Array(files) } else { files.remove(0) };
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        OutputFormat::Minimal => {
+            for (_, path, _, _) in &exported {
+                println!("{}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Build the Parquet message type for the exported columns (all optional)
+fn schema_for(name: &str, columns: &[(&'static str, ColumnKind)]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|(col, kind)| match kind {
+            ColumnKind::Int => format!("OPTIONAL INT64 {};", col),
+            ColumnKind::Text => format!("OPTIONAL BYTE_ARRAY {} (UTF8);", col),
+        })
+        .collect();
+    format!("message {} {{ {} }}", name, fields.join(" "))
+}
+
+/// Write rows to a Snappy-compressed Parquet file
+fn write_parquet(path: &Path, name: &str, data: &ExportRows) -> Result<()> {
+    let schema = Arc::new(parse_message_type(&schema_for(name, &data.columns))?);
+    let props = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+
+    let file = File::create(path)?;
+    let mut writer = SerializedFileWriter::new(file, schema, props)?;
+
+    for chunk in data.rows.chunks(ROW_GROUP_SIZE) {
+        let mut row_group = writer.next_row_group()?;
+        let mut col_index = 0;
+
+        while let Some(mut column) = row_group.next_column()? {
+            let kind = data.columns[col_index].1;
+            let mut def_levels = Vec::with_capacity(chunk.len());
+
+            match kind {
+                ColumnKind::Int => {
+                    let mut values = Vec::new();
+                    for row in chunk {
+                        match &row[col_index] {
+                            Cell::Int(n) => {
+                                values.push(*n);
+                                def_levels.push(1);
+                            }
+                            Cell::Text(s) => match s.parse::<i64>() {
+                                Ok(n) => {
+                                    values.push(n);
+                                    def_levels.push(1);
+                                }
+                                Err(_) => def_levels.push(0),
+                            },
+                            Cell::Null => def_levels.push(0),
+                        }
+                    }
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&values, Some(&def_levels), None)?;
+                }
+                ColumnKind::Text => {
+                    let mut values = Vec::new();
+                    for row in chunk {
+                        match &row[col_index] {
+                            Cell::Text(s) => {
+                                values.push(ByteArray::from(s.as_str()));
+                                def_levels.push(1);
+                            }
+                            Cell::Int(n) => {
+                                values.push(ByteArray::from(n.to_string().as_str()));
+                                def_levels.push(1);
+                            }
+                            Cell::Null => def_levels.push(0),
+                        }
+                    }
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, Some(&def_levels), None)?;
+                }
+            }
+
+            column.close()?;
+            col_index += 1;
+        }
+
+        row_group.close()?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_write_parquet_round_trip() {
+        let data = ExportRows {
+            columns: vec![("id", ColumnKind::Int), ("type", ColumnKind::Text), ("turn_sequence", ColumnKind::Int)],
+            rows: vec![
+                vec![Cell::Int(1), Cell::Text("user".to_string()), Cell::Null],
+                vec![Cell::Int(2), Cell::Null, Cell::Text("7".to_string())],
+            ],
+        };
+        let path = std::env::temp_dir().join(format!("transcript-parquet-{}.parquet", std::process::id()));
+        write_parquet(&path, "lines", &data).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let schema = reader.metadata().file_metadata().schema_descr();
+        let names: Vec<_> = schema.columns().iter().map(|c| c.name().to_string()).collect();
+        assert_eq!(names, ["id", "type", "turn_sequence"]);
+
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                r#"{id: 1, type: "user", turn_sequence: null}"#,
+                r#"{id: 2, type: null, turn_sequence: 7}"#,
+            ]
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+//! SQLite export - a consistent copy of the database for analytics tooling
+//!
+//! DuckDB, pandas and friends can read the copy while the indexer keeps
+//! writing to the live database. An encrypted database is copied encrypted,
+//! under the same key.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use transcript_db::TranscriptDb;
+
+use crate::cli::{Cli, OutputFormat};
+use crate::output::colors;
+
+pub fn run(cli: &Cli, db: &TranscriptDb, output: Option<&Path>) -> Result<()> {
+    let path = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(format!("transcripts-{}.db", chrono::Local::now().format("%Y%m%d"))));
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+
+    // VACUUM INTO produces a consistent, compacted copy even while indexing runs
+    db.connection()
+        .execute("VACUUM INTO ?1", [path.to_string_lossy()])
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{}",
+                colors::success(&format!("Exported the database to {} ({})", path.display(), colors::format_size(size)))
+            );
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "path": path.to_string_lossy(),
+                "bytes": size,
+            });
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        OutputFormat::Minimal => {
+            println!("{}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_export_copies_the_database() {
+        let dir = std::env::temp_dir().join(format!("transcript-export-sqlite-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("transcripts.db");
+        let mut conn = rusqlite::Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO sessions (file_path, session_id, slug, line_count, indexed_at) VALUES ('a.jsonl', 's1', 'loyal-whippet', 1, '')",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+        let db = TranscriptDb::open(&path).unwrap();
+        let cli = Cli::try_parse_from(["transcript", "--minimal", "list"]).unwrap();
+
+        let copy = dir.join("copy.db");
+        run(&cli, &db, Some(&copy)).unwrap();
+        let slug: String = rusqlite::Connection::open(&copy)
+            .unwrap()
+            .query_row("SELECT slug FROM sessions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(slug, "loyal-whippet");
+
+        // An existing file is never overwritten
+        let err = run(&cli, &db, Some(&copy)).unwrap_err();
+        assert!(err.to_string().ends_with("already exists"), "{}", err);
+
+        drop(db);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,259 @@
+//! Rows of the indexed tables, for the columnar exports
+
+use anyhow::{bail, Result};
+use rusqlite::types::{ToSql, ValueRef};
+use transcript_db::TranscriptDb;
+
+use crate::cli::ExportTable;
+
+/// Tables written by `export parquet --all`
+pub const ALL_TABLES: &[ExportTable] = &[ExportTable::Lines, ExportTable::Hooks, ExportTable::Sessions];
+
+/// Storage type of an exported column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Int,
+    Text,
+}
+
+/// A single exported cell
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Null,
+    Int(i64),
+    Text(String),
+}
+
+/// Rows fetched for export, with their column layout
+pub struct ExportRows {
+    pub columns: Vec<(&'static str, ColumnKind)>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+/// Filters shared by the table exporters
+#[derive(Debug, Default)]
+pub struct ExportFilter {
+    pub session_id: Option<String>,
+    pub from_time: Option<String>,
+    pub to_time: Option<String>,
+}
+
+const LINE_COLUMNS: &[(&str, ColumnKind)] = &[
+    ("id", ColumnKind::Int),
+    ("session_id", ColumnKind::Text),
+    ("uuid", ColumnKind::Text),
+    ("parent_uuid", ColumnKind::Text),
+    ("line_number", ColumnKind::Int),
+    ("type", ColumnKind::Text),
+    ("subtype", ColumnKind::Text),
+    ("timestamp", ColumnKind::Text),
+    ("slug", ColumnKind::Text),
+    ("role", ColumnKind::Text),
+    ("model", ColumnKind::Text),
+    ("cwd", ColumnKind::Text),
+    ("content", ColumnKind::Text),
+    ("raw", ColumnKind::Text),
+    ("file_path", ColumnKind::Text),
+    ("turn_id", ColumnKind::Text),
+    ("turn_sequence", ColumnKind::Int),
+    ("session_name", ColumnKind::Text),
+    ("git_hash", ColumnKind::Text),
+    ("git_branch", ColumnKind::Text),
+    ("git_dirty", ColumnKind::Int),
+];
+
+const HOOK_COLUMNS: &[(&str, ColumnKind)] = &[
+    ("id", ColumnKind::Int),
+    ("session_id", ColumnKind::Text),
+    ("timestamp", ColumnKind::Text),
+    ("event_type", ColumnKind::Text),
+    ("tool_use_id", ColumnKind::Text),
+    ("tool_name", ColumnKind::Text),
+    ("decision", ColumnKind::Text),
+    ("handler_results", ColumnKind::Text),
+    ("input_json", ColumnKind::Text),
+    ("context_json", ColumnKind::Text),
+    ("file_path", ColumnKind::Text),
+    ("line_number", ColumnKind::Int),
+    ("turn_id", ColumnKind::Text),
+    ("turn_sequence", ColumnKind::Int),
+    ("session_name", ColumnKind::Text),
+    ("git_hash", ColumnKind::Text),
+    ("git_branch", ColumnKind::Text),
+    ("git_dirty", ColumnKind::Int),
+];
+
+const SESSION_COLUMNS: &[(&str, ColumnKind)] = &[
+    ("session_id", ColumnKind::Text),
+    ("slug", ColumnKind::Text),
+    ("file_path", ColumnKind::Text),
+    ("line_count", ColumnKind::Int),
+    ("first_timestamp", ColumnKind::Text),
+    ("last_timestamp", ColumnKind::Text),
+    ("indexed_at", ColumnKind::Text),
+    ("user", ColumnKind::Text),
+];
+
+const TURN_COLUMNS: &[(&str, ColumnKind)] = &[
+    ("session_id", ColumnKind::Text),
+    ("turn_id", ColumnKind::Text),
+    ("turn_sequence", ColumnKind::Int),
+    ("session_name", ColumnKind::Text),
+    ("started_at", ColumnKind::Text),
+    ("ended_at", ColumnKind::Text),
+    ("line_count", ColumnKind::Int),
+    ("user_lines", ColumnKind::Int),
+    ("assistant_lines", ColumnKind::Int),
+    ("output_tokens", ColumnKind::Int),
+];
+
+impl ExportTable {
+    /// Source table or view, its timestamp column, and available columns
+    fn source(self) -> (&'static str, &'static str, &'static [(&'static str, ColumnKind)]) {
+        match self {
+            ExportTable::Lines => ("lines", "timestamp", LINE_COLUMNS),
+            ExportTable::Hooks => ("hook_events", "timestamp", HOOK_COLUMNS),
+            ExportTable::Turns => ("v_turns", "started_at", TURN_COLUMNS),
+            ExportTable::Sessions => ("sessions", "first_timestamp", SESSION_COLUMNS),
+        }
+    }
+
+    /// Short name used for default file names
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportTable::Lines => "lines",
+            ExportTable::Hooks => "hooks",
+            ExportTable::Turns => "turns",
+            ExportTable::Sessions => "sessions",
+        }
+    }
+}
+
+/// Fetch rows for a table, restricted to `columns` (all when `None`)
+pub fn fetch_rows(
+    db: &TranscriptDb,
+    table: ExportTable,
+    columns: Option<&[String]>,
+    filter: &ExportFilter,
+) -> Result<ExportRows> {
+    let (source, time_column, available) = table.source();
+
+    let columns: Vec<(&'static str, ColumnKind)> = match columns {
+        None => available.to_vec(),
+        Some(requested) => {
+            let mut selected = Vec::new();
+            for name in requested {
+                match available.iter().find(|(c, _)| c == name) {
+                    Some(col) => selected.push(*col),
+                    None => {
+                        let names: Vec<_> = available.iter().map(|(c, _)| *c).collect();
+                        bail!(
+                            "Unknown column '{}' for {}. Available: {}",
+                            name,
+                            table.name(),
+                            names.join(", ")
+                        );
+                    }
+                }
+            }
+            selected
+        }
+    };
+
+    let mut conditions = Vec::new();
+    let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+
+    if let Some(session_id) = &filter.session_id {
+        conditions.push("session_id = ?".to_string());
+        params.push(Box::new(session_id.clone()));
+    }
+    if let Some(from) = &filter.from_time {
+        conditions.push(format!("{} >= ?", time_column));
+        params.push(Box::new(from.clone()));
+    }
+    if let Some(to) = &filter.to_time {
+        conditions.push(format!("{} <= ?", time_column));
+        params.push(Box::new(to.clone()));
+    }
+
+    let names: Vec<&str> = columns.iter().map(|(c, _)| *c).collect();
+    let mut sql = format!("SELECT {} FROM {}", names.join(", "), source);
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(&format!(" ORDER BY {}", time_column));
+
+    let conn = db.connection();
+    let mut stmt = conn.prepare(&sql)?;
+    let param_refs: Vec<&dyn ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    let mut query = stmt.query(param_refs.as_slice())?;
+
+    let mut rows = Vec::new();
+    while let Some(row) = query.next()? {
+        let mut cells = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            cells.push(match row.get_ref(i)? {
+                ValueRef::Null => Cell::Null,
+                ValueRef::Integer(n) => Cell::Int(n),
+                ValueRef::Real(f) => Cell::Int(f as i64),
+                ValueRef::Text(t) => Cell::Text(String::from_utf8_lossy(t).into_owned()),
+                ValueRef::Blob(b) => Cell::Text(String::from_utf8_lossy(b).into_owned()),
+            });
+        }
+        rows.push(cells);
+    }
+
+    Ok(ExportRows { columns, rows })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_rows() {
+        let dir = std::env::temp_dir().join(format!("transcript-table-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("transcripts.db");
+        let mut conn = rusqlite::Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut conn).unwrap();
+        for (session_id, line_number, timestamp) in [
+            ("s1", 1, "2026-01-01T09:00:00Z"),
+            ("s1", 2, "2026-01-01T11:00:00Z"),
+            ("s2", 1, "2026-01-01T10:00:00Z"),
+        ] {
+            conn.execute(
+                "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path)
+                 VALUES (?1, ?1 || ?2, ?2, 'user', ?3, '{}', ?1 || '.jsonl')",
+                rusqlite::params![session_id, line_number, timestamp],
+            )
+            .unwrap();
+        }
+        drop(conn);
+        let db = TranscriptDb::open(&path).unwrap();
+
+        // Every column by default, in time order
+        let all = fetch_rows(&db, ExportTable::Lines, None, &ExportFilter::default()).unwrap();
+        assert_eq!(all.columns.len(), LINE_COLUMNS.len());
+        let uuids: Vec<_> = all.rows.iter().map(|r| r[2].clone()).collect();
+        assert_eq!(uuids, [Cell::Text("s11".into()), Cell::Text("s21".into()), Cell::Text("s12".into())]);
+
+        let columns = ["session_id".to_string(), "line_number".to_string(), "git_hash".to_string()];
+        let filter = ExportFilter {
+            session_id: Some("s1".to_string()),
+            from_time: Some("2026-01-01T10:00:00Z".to_string()),
+            to_time: None,
+        };
+        let rows = fetch_rows(&db, ExportTable::Lines, Some(&columns), &filter).unwrap();
+        assert_eq!(rows.columns, [("session_id", ColumnKind::Text), ("line_number", ColumnKind::Int), ("git_hash", ColumnKind::Text)]);
+        assert_eq!(rows.rows, [vec![Cell::Text("s1".into()), Cell::Int(2), Cell::Null]]);
+
+        let err = fetch_rows(&db, ExportTable::Hooks, Some(&["content".to_string()]), &ExportFilter::default()).err().unwrap();
+        assert!(err.to_string().starts_with("Unknown column 'content' for hooks. Available: id, session_id"), "{}", err);
+        assert!(fetch_rows(&db, ExportTable::Sessions, None, &ExportFilter::default()).unwrap().rows.is_empty());
+
+        drop(db);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,306 @@
+//! Template export - render a session through a user's Tera template
+//!
+//! The template gets the session as structured data instead of a fixed
+//! document, so audit forms or weekly summaries need a template file rather
+//! than a new export format. The context has these top-level values:
+//!
+//! - `session`: id, name, cwd, started, ended, models
+//! - `turns`: number, prompt, replies, tools (name, preview, input, result,
+//!   is_error), usage and files of each turn
+//! - `tools`: calls and errors per tool, most used first
+//! - `usage`: token totals (input, output, cache_read, cache_creation, total)
+//! - `files`: files edited in the session, in the order they were first edited
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use transcript_core::{edited_file, response_usage, ContentBlock, LineType, MessageContent, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use super::markdown::{plain_text, write_document};
+use super::trace::{is_prompt, result_text};
+use crate::cli::Cli;
+use crate::commands::resolve_session_id;
+use crate::commands::turn::split_turns;
+use crate::output::human::get_tool_preview;
+
+/// Session metadata
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub name: String,
+    pub cwd: Option<String>,
+    pub started: Option<String>,
+    pub ended: Option<String>,
+    pub models: Vec<String>,
+}
+
+/// Token counts summed over assistant messages
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct UsageTotals {
+    pub input: u64,
+    pub output: u64,
+    pub cache_read: u64,
+    pub cache_creation: u64,
+    pub total: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, other: &UsageTotals) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_read += other.cache_read;
+        self.cache_creation += other.cache_creation;
+        self.total += other.total;
+    }
+}
+
+/// A tool call and its result
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCall {
+    pub name: String,
+    pub preview: String,
+    pub input: serde_json::Value,
+    pub result: Option<String>,
+    pub is_error: bool,
+}
+
+/// A user prompt and everything up to the next one
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnModel {
+    pub number: usize,
+    pub started: String,
+    pub ended: String,
+    pub prompt: String,
+    /// Assistant text blocks, in order
+    pub replies: Vec<String>,
+    pub tools: Vec<ToolCall>,
+    pub usage: UsageTotals,
+    pub files: Vec<String>,
+}
+
+/// Calls of one tool across the session
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCount {
+    pub name: String,
+    pub calls: usize,
+    pub errors: usize,
+}
+
+/// Everything a template can use
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionModel {
+    pub session: SessionInfo,
+    pub turns: Vec<TurnModel>,
+    pub tools: Vec<ToolCount>,
+    pub usage: UsageTotals,
+    pub files: Vec<String>,
+}
+
+pub fn run(cli: &Cli, db: &TranscriptDb, session: &str, template: &Path, output: Option<&Path>) -> Result<()> {
+    let source = std::fs::read_to_string(template)
+        .with_context(|| format!("Failed to read template {}", template.display()))?;
+    let session_id = resolve_session_id(db, session)?;
+    let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+
+    let model = build_model(&session_id, &lines);
+    let document = render(&template.to_string_lossy(), &source, &model)?;
+    write_document(cli, &session_id, &document, model.turns.len(), output)
+}
+
+/// Render `source` (named `name` in errors) with the session model as context
+pub fn render(name: &str, source: &str, model: &SessionModel) -> Result<String> {
+    let mut tera = tera::Tera::default();
+    tera.add_raw_template(name, source)
+        .with_context(|| format!("Invalid template {}", name))?;
+    let context = tera::Context::from_serialize(model)?;
+    tera.render(name, &context)
+        .with_context(|| format!("Failed to render template {}", name))
+}
+
+/// Collect the session's turns, tool calls, usage and edited files
+pub fn build_model(session_id: &str, lines: &[TranscriptLine]) -> SessionModel {
+    // Tool results arrive on later user lines: id -> (text, is_error)
+    let mut results: HashMap<String, (String, bool)> = HashMap::new();
+    for line in lines {
+        let Some(MessageContent::Blocks(blocks)) = line.message().map(|m| m.content) else {
+            continue;
+        };
+        for block in blocks {
+            if let ContentBlock::ToolResult { tool_use_id, content, is_error } = block {
+                results.insert(tool_use_id, (plain_text(&result_text(&content)), is_error));
+            }
+        }
+    }
+
+    let mut session = SessionInfo {
+        id: session_id.to_string(),
+        name: lines
+            .iter()
+            .find_map(|l| l.session_name.clone().or_else(|| l.slug.clone()))
+            .unwrap_or_else(|| session_id.to_string()),
+        cwd: lines.iter().find_map(|l| l.cwd.clone()),
+        started: lines.first().map(|l| l.timestamp.clone()),
+        ended: lines.last().map(|l| l.timestamp.clone()),
+        models: Vec::new(),
+    };
+    for model in lines.iter().filter_map(|l| l.model.as_deref()) {
+        if !session.models.iter().any(|m| m == model) {
+            session.models.push(model.to_string());
+        }
+    }
+
+    let mut turns = Vec::new();
+    let mut usage = UsageTotals::default();
+    let mut files: Vec<String> = Vec::new();
+    let mut counts: Vec<ToolCount> = Vec::new();
+
+    for (number, turn_lines) in split_turns(lines) {
+        let mut turn = TurnModel {
+            number,
+            started: turn_lines.first().map(|l| l.timestamp.clone()).unwrap_or_default(),
+            ended: turn_lines.last().map(|l| l.timestamp.clone()).unwrap_or_default(),
+            prompt: String::new(),
+            replies: Vec::new(),
+            tools: Vec::new(),
+            usage: UsageTotals::default(),
+            files: Vec::new(),
+        };
+
+        for (line, usage) in turn_lines.iter().zip(response_usage(turn_lines)) {
+            let Some(msg) = line.message() else {
+                continue;
+            };
+            if is_prompt(line) && turn.prompt.is_empty() {
+                turn.prompt = plain_text(msg.content.as_text().trim());
+                continue;
+            }
+            if line.line_type != LineType::Assistant {
+                continue;
+            }
+
+            if let Some(u) = &usage {
+                turn.usage.input += u.input_tokens;
+                turn.usage.output += u.output_tokens;
+                turn.usage.cache_read += u.cache_read_input_tokens.unwrap_or(0);
+                turn.usage.cache_creation += u.cache_creation_input_tokens.unwrap_or(0);
+                turn.usage.total += u.total();
+            }
+
+            let blocks = match msg.content {
+                MessageContent::Text(text) => vec![ContentBlock::Text { text }],
+                MessageContent::Blocks(blocks) => blocks,
+            };
+            for block in blocks {
+                match block {
+                    ContentBlock::Text { text } if !text.trim().is_empty() => {
+                        turn.replies.push(plain_text(text.trim()));
+                    }
+                    ContentBlock::ToolUse { id, name, input } => {
+                        if let Some(path) = edited_file(&name, &input) {
+                            if !turn.files.iter().any(|f| f == path) {
+                                turn.files.push(path.to_string());
+                            }
+                        }
+                        let result = results.get(&id);
+                        let is_error = result.is_some_and(|(_, e)| *e);
+                        match counts.iter_mut().find(|c| c.name == name) {
+                            Some(count) => {
+                                count.calls += 1;
+                                count.errors += usize::from(is_error);
+                            }
+                            None => counts.push(ToolCount {
+                                name: name.clone(),
+                                calls: 1,
+                                errors: usize::from(is_error),
+                            }),
+                        }
+                        turn.tools.push(ToolCall {
+                            preview: get_tool_preview(&name, &input),
+                            name,
+                            input,
+                            result: result.map(|(text, _)| text.clone()),
+                            is_error,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        usage.add(&turn.usage);
+        for path in &turn.files {
+            if !files.contains(path) {
+                files.push(path.clone());
+            }
+        }
+        turns.push(turn);
+    }
+
+    // Stable sort keeps first-use order among tools called equally often
+    counts.sort_by_key(|c| std::cmp::Reverse(c.calls));
+
+    SessionModel {
+        session,
+        turns,
+        tools: counts,
+        usage,
+        files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn line(line_type: LineType, raw: serde_json::Value) -> TranscriptLine {
+        TranscriptLine {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            cwd: Some("/work/app".to_string()),
+            slug: Some("happy-dog".to_string()),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_template_renders_session_model() {
+        let lines = vec![
+            line(LineType::User, json!({"message": {"role": "user", "content": "fix the parser"}})),
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "text", "text": "Editing it."},
+                {"type": "tool_use", "id": "t1", "name": "Edit", "input": {"file_path": "src/parser.rs"}},
+                {"type": "tool_use", "id": "t2", "name": "Bash", "input": {"command": "cargo test"}}
+            ], "usage": {"input_tokens": 100, "output_tokens": 20}}})),
+            line(LineType::User, json!({"message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "ok"},
+                {"type": "tool_result", "tool_use_id": "t2", "content": "1 failed", "is_error": true}
+            ]}})),
+        ];
+        let model = build_model("s1", &lines);
+
+        assert_eq!(model.turns.len(), 1);
+        assert_eq!(model.turns[0].prompt, "fix the parser");
+        assert_eq!(model.files, vec!["src/parser.rs".to_string()]);
+        assert_eq!(model.usage.total, 120);
+
+        let source = "{{ session.name }} ({{ session.cwd }})\n\
+            {% for turn in turns %}{{ turn.number }}. {{ turn.prompt }}: \
+            {% for tool in turn.tools %}{{ tool.name }}{% if tool.is_error %}!{% endif %} {% endfor %}\n{% endfor %}\
+            files: {{ files | join(sep=\", \") }}, tokens: {{ usage.total }}";
+        let doc = render("report.tera", source, &model).unwrap();
+        assert_eq!(
+            doc,
+            "happy-dog (/work/app)\n1. fix the parser: Edit Bash! \nfiles: src/parser.rs, tokens: 120"
+        );
+    }
+
+    #[test]
+    fn test_invalid_template_names_the_file() {
+        let model = build_model("s1", &[]);
+        let err = render("report.tera", "{% for %}", &model).unwrap_err();
+        assert!(err.to_string().contains("report.tera"));
+    }
+}
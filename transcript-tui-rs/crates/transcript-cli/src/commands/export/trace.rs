@@ -0,0 +1,382 @@
+//! Session → turn → tool-call trace model shared by the telemetry exporters
+//!
+//! Turns come from `turn_id` (hook correlation) when present, otherwise each
+//! user prompt starts a new turn. Tool call timing comes from PreToolUse /
+//! PostToolUse hook events, falling back to the transcript's tool_use and
+//! tool_result lines.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use transcript_core::{response_usage, ContentBlock, HookEventFilter, LineType, MessageContent, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+/// A whole session (one trace)
+#[derive(Debug, Clone)]
+pub struct SessionTrace {
+    pub session_id: String,
+    pub name: String,
+    pub cwd: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub turns: Vec<TurnSpan>,
+}
+
+/// One prompt/response cycle
+#[derive(Debug, Clone)]
+pub struct TurnSpan {
+    pub key: String,
+    pub sequence: usize,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub model: Option<String>,
+    pub input: String,
+    pub output: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub tools: Vec<ToolSpan>,
+}
+
+/// One tool call within a turn
+#[derive(Debug, Clone)]
+pub struct ToolSpan {
+    pub tool_use_id: String,
+    pub name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub input: serde_json::Value,
+    pub output: String,
+    pub is_error: bool,
+}
+
+/// Parse an ISO timestamp, if valid
+pub fn parse_time(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// Build the trace for one session
+pub fn build_session_trace(db: &TranscriptDb, session_id: &str) -> Result<Option<SessionTrace>> {
+    let lines = db.get_lines(&GetLinesOptions::for_session(session_id))?;
+    let Some(first) = lines.first() else {
+        return Ok(None);
+    };
+
+    let name = lines
+        .iter()
+        .find_map(|l| l.session_name.clone())
+        .or_else(|| first.slug.clone())
+        .unwrap_or_else(|| session_id.to_string());
+    let cwd = lines.iter().find_map(|l| l.cwd.clone());
+
+    let times: Vec<DateTime<Utc>> = lines.iter().filter_map(|l| parse_time(&l.timestamp)).collect();
+    let (Some(&start), Some(&end)) = (times.iter().min(), times.iter().max()) else {
+        return Ok(None);
+    };
+
+    let mut turns = group_turns(&lines);
+    attach_tools(db, session_id, &lines, &mut turns)?;
+
+    Ok(Some(SessionTrace {
+        session_id: session_id.to_string(),
+        name,
+        cwd,
+        start,
+        end,
+        turns,
+    }))
+}
+
+/// Is this line a real user prompt (not a tool_result carrier)?
+pub fn is_prompt(line: &TranscriptLine) -> bool {
+    if line.line_type != LineType::User {
+        return false;
+    }
+    match line.message().map(|m| m.content) {
+        Some(MessageContent::Text(text)) => !text.trim().is_empty(),
+        Some(MessageContent::Blocks(blocks)) => blocks
+            .iter()
+            .any(|b| matches!(b, ContentBlock::Text { text } if !text.trim().is_empty())),
+        None => false,
+    }
+}
+
+/// Group lines into turns
+fn group_turns(lines: &[TranscriptLine]) -> Vec<TurnSpan> {
+    let use_turn_ids = lines.iter().any(|l| l.turn_id.is_some());
+
+    let mut turns: Vec<TurnSpan> = Vec::new();
+    let mut index_by_key: HashMap<String, usize> = HashMap::new();
+    let mut synthetic = 0usize;
+
+    for (line, usage) in lines.iter().zip(response_usage(lines)) {
+        let Some(ts) = parse_time(&line.timestamp) else {
+            continue;
+        };
+
+        let key = if use_turn_ids {
+            match &line.turn_id {
+                Some(id) => id.clone(),
+                None => continue,
+            }
+        } else {
+            if is_prompt(line) || synthetic == 0 {
+                synthetic += 1;
+            }
+            format!("{}:{}", line.session_id, synthetic)
+        };
+
+        let idx = *index_by_key.entry(key.clone()).or_insert_with(|| {
+            turns.push(TurnSpan {
+                key: key.clone(),
+                sequence: turns.len() + 1,
+                start: ts,
+                end: ts,
+                model: None,
+                input: String::new(),
+                output: String::new(),
+                input_tokens: 0,
+                output_tokens: 0,
+                cache_read_tokens: 0,
+                cache_creation_tokens: 0,
+                tools: Vec::new(),
+            });
+            turns.len() - 1
+        });
+
+        let turn = &mut turns[idx];
+        turn.start = turn.start.min(ts);
+        turn.end = turn.end.max(ts);
+
+        let Some(msg) = line.message() else {
+            continue;
+        };
+        match line.line_type {
+            LineType::User if turn.input.is_empty() && is_prompt(line) => {
+                turn.input = msg.content.as_text();
+            }
+            LineType::Assistant => {
+                if line.model.is_some() {
+                    turn.model = line.model.clone();
+                }
+                let text = msg.content.as_text();
+                if !text.trim().is_empty() {
+                    turn.output = text;
+                }
+                if let Some(usage) = usage {
+                    turn.input_tokens += usage.input_tokens;
+                    turn.output_tokens += usage.output_tokens;
+                    turn.cache_read_tokens += usage.cache_read_input_tokens.unwrap_or(0);
+                    turn.cache_creation_tokens += usage.cache_creation_input_tokens.unwrap_or(0);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    turns
+}
+
+/// Attach tool calls to their turns, preferring hook timing
+fn attach_tools(
+    db: &TranscriptDb,
+    session_id: &str,
+    lines: &[TranscriptLine],
+    turns: &mut [TurnSpan],
+) -> Result<()> {
+    // Tool uses from the transcript: id -> (name, input, start, turn index)
+    let mut tool_order: Vec<String> = Vec::new();
+    let mut tools: HashMap<String, ToolSpan> = HashMap::new();
+    let mut tool_turn: HashMap<String, usize> = HashMap::new();
+
+    for line in lines {
+        let Some(ts) = parse_time(&line.timestamp) else {
+            continue;
+        };
+        let Some(msg) = line.message() else {
+            continue;
+        };
+        let MessageContent::Blocks(blocks) = &msg.content else {
+            continue;
+        };
+
+        for block in blocks {
+            match block {
+                ContentBlock::ToolUse { id, name, input } => {
+                    let turn_idx = turns
+                        .iter()
+                        .rposition(|t| t.start <= ts)
+                        .unwrap_or(0);
+                    tool_order.push(id.clone());
+                    tool_turn.insert(id.clone(), turn_idx);
+                    tools.insert(
+                        id.clone(),
+                        ToolSpan {
+                            tool_use_id: id.clone(),
+                            name: name.clone(),
+                            start: ts,
+                            end: ts,
+                            input: input.clone(),
+                            output: String::new(),
+                            is_error: false,
+                        },
+                    );
+                }
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                } => {
+                    if let Some(tool) = tools.get_mut(tool_use_id) {
+                        tool.end = tool.end.max(ts);
+                        tool.output = result_text(content);
+                        tool.is_error = *is_error;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Hook events give more precise timing when available
+    let filter = HookEventFilter {
+        session_id: Some(session_id.to_string()),
+        event_types: Some(vec!["PreToolUse".to_string(), "PostToolUse".to_string()]),
+        ..Default::default()
+    };
+    for event in db.get_hook_events(&filter).unwrap_or_default() {
+        let (Some(id), Some(ts)) = (&event.tool_use_id, parse_time(&event.timestamp)) else {
+            continue;
+        };
+        if let Some(tool) = tools.get_mut(id) {
+            match event.event_type.as_str() {
+                "PreToolUse" => tool.start = ts,
+                _ => tool.end = ts,
+            }
+            if tool.end < tool.start {
+                tool.end = tool.start;
+            }
+        }
+    }
+
+    for id in tool_order {
+        if let (Some(tool), Some(&idx)) = (tools.remove(&id), tool_turn.get(&id)) {
+            if let Some(turn) = turns.get_mut(idx) {
+                turn.end = turn.end.max(tool.end);
+                turn.tools.push(tool);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Flatten a tool_result content value (string or text blocks) to text
+pub fn result_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Resolve which sessions to export: an explicit session or all from the last N days
+pub fn select_sessions(db: &TranscriptDb, session: Option<&str>, days: Option<i64>) -> Result<Vec<String>> {
+    match (session, days) {
+        (Some(s), _) => Ok(vec![crate::commands::resolve_session_id(db, s)?]),
+        (None, Some(d)) => {
+            // A session can span several files; keep the first occurrence
+            let mut seen = std::collections::HashSet::new();
+            Ok(db
+                .get_sessions(Some(d))?
+                .into_iter()
+                .map(|s| s.session_id)
+                .filter(|id| seen.insert(id.clone()))
+                .collect())
+        }
+        (None, None) => anyhow::bail!("Specify --session <name> or --days <n>"),
+    }
+}
+
+/// Stable 64-bit FNV-1a hash, used to derive span IDs
+pub fn fnv1a(input: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in input.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Truncate text for span attributes, marking the cut
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() > max {
+        let t: String = s.chars().take(max).collect();
+        format!("{}...", t)
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn time(s: &str) -> DateTime<Utc> {
+        parse_time(&format!("2026-01-01T{}Z", s)).unwrap()
+    }
+
+    /// One session with a turn that ran a failed and a successful tool call
+    pub(crate) fn sample_trace() -> SessionTrace {
+        let tool = |id: &str, name: &str, start: &str, end: &str, is_error: bool| ToolSpan {
+            tool_use_id: id.to_string(),
+            name: name.to_string(),
+            start: time(start),
+            end: time(end),
+            input: serde_json::json!({ "command": "cargo test" }),
+            output: "ok".to_string(),
+            is_error,
+        };
+        SessionTrace {
+            session_id: "0f8fad5b-d9cb-469f-a165-70867728950e".to_string(),
+            name: "loyal-whippet".to_string(),
+            cwd: Some("/app".to_string()),
+            start: time("10:00:00"),
+            end: time("10:00:30"),
+            turns: vec![TurnSpan {
+                key: "t1".to_string(),
+                sequence: 1,
+                start: time("10:00:00"),
+                end: time("10:00:30"),
+                model: Some("claude-sonnet-4".to_string()),
+                input: "Run the tests".to_string(),
+                output: "All green".to_string(),
+                input_tokens: 100,
+                output_tokens: 20,
+                cache_read_tokens: 5,
+                cache_creation_tokens: 0,
+                tools: vec![
+                    tool("toolu_1", "Bash", "10:00:05", "10:00:10", true),
+                    tool("toolu_2", "Bash", "10:00:12", "10:00:20", false),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_ne!(fnv1a("a"), fnv1a("b"));
+        assert_eq!(truncate("héllo", 2), "hé...");
+        assert_eq!(truncate("hi", 2), "hi");
+        assert_eq!(result_text(&serde_json::json!([{ "text": "a" }, { "text": "b" }])), "a\nb");
+        assert_eq!(result_text(&serde_json::Value::Null), "");
+    }
+}
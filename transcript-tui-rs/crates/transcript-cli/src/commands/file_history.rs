@@ -0,0 +1,427 @@
+//! File-history command - every change made to a file, across sessions
+//!
+//! Changes are the Edit, MultiEdit and Write calls on the file, from
+//! transcript lines and from PostToolUse hook events for sessions recorded
+//! only through hooks. Failed calls are left out. `--at` replays them to
+//! rebuild the file as of a point in time; that needs a full copy of the file
+//! to start from: a Write, or an edit recorded with the file's original
+//! content (Claude Code keeps it in the tool result). Changes made outside
+//! the sessions are only picked up at the next such copy.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+use chrono::Local;
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{json, Value};
+use transcript_core::{
+    edit_diff, edited_file, path_glob_match, since_time, ContentBlock, HookEvent, HookEventFilter, LineType,
+    MessageContent, TranscriptLine, EDIT_TOOLS,
+};
+use transcript_db::TranscriptDb;
+
+use super::resolve_session_id;
+use crate::cli::{Cli, OutputFormat};
+use crate::output::colors;
+
+/// Lines of a Write shown in the history (the whole file otherwise)
+const MAX_WRITE_LINES: usize = 20;
+
+/// What a call did to the file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Change {
+    /// The whole content
+    Write { content: String },
+    /// Replacements, applied in order: (old, new, replace_all)
+    Edit { edits: Vec<(String, String, bool)> },
+}
+
+impl Change {
+    fn from_input(tool: &str, input: &Value) -> Option<Self> {
+        let text = |v: &Value, key: &str| v.get(key).and_then(|s| s.as_str()).map(str::to_string);
+        let replacement = |v: &Value| {
+            Some((
+                text(v, "old_string")?,
+                text(v, "new_string")?,
+                v.get("replace_all").and_then(|b| b.as_bool()).unwrap_or(false),
+            ))
+        };
+        match tool {
+            "Write" => Some(Change::Write {
+                content: text(input, "content")?,
+            }),
+            "Edit" => Some(Change::Edit {
+                edits: vec![replacement(input)?],
+            }),
+            "MultiEdit" => Some(Change::Edit {
+                edits: input.get("edits")?.as_array()?.iter().map(replacement).collect::<Option<_>>()?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// `content` with the change applied; None if an edit's text isn't there
+    pub fn apply(&self, content: &str) -> Option<String> {
+        match self {
+            Change::Write { content } => Some(content.clone()),
+            Change::Edit { edits } => {
+                let mut content = content.to_string();
+                for (old, new, replace_all) in edits {
+                    if !content.contains(old.as_str()) {
+                        return None;
+                    }
+                    content = if *replace_all {
+                        content.replace(old.as_str(), new)
+                    } else {
+                        content.replacen(old.as_str(), new, 1)
+                    };
+                }
+                Some(content)
+            }
+        }
+    }
+}
+
+/// A change to the file by one tool call
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub session_id: String,
+    pub session_name: Option<String>,
+    pub timestamp: String,
+    /// The session's turn the call was made in, once correlated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn: Option<i64>,
+    pub tool: String,
+    pub path: String,
+    pub change: Change,
+    /// The file's content before the call, when the result recorded it
+    #[serde(skip)]
+    pub before: Option<String>,
+}
+
+pub fn run(cli: &Cli, db: &TranscriptDb, path: &str, at: Option<&str>, session: Option<&str>) -> Result<()> {
+    let session_id = session.map(|s| resolve_session_id(db, s)).transpose()?;
+    let lines = db.get_tool_lines(session_id.as_deref())?;
+    let events = db.get_hook_events(&HookEventFilter {
+        session_id,
+        event_types: Some(vec!["PostToolUse".to_string()]),
+        tool_names: Some(EDIT_TOOLS.iter().map(|(name, _)| name.to_string()).collect()),
+        ..Default::default()
+    })?;
+    let changes = file_changes(&lines, &events, path);
+    if changes.is_empty() {
+        bail!("No recorded changes to {}", path);
+    }
+
+    match at {
+        Some(at) => print_at(cli, &changes, path, at),
+        None => {
+            print_history(cli, &changes, path)?;
+            Ok(())
+        }
+    }
+}
+
+/// `--at`: the file rebuilt as of `at`
+fn print_at(cli: &Cli, changes: &[FileChange], path: &str, at: &str) -> Result<()> {
+    let mut paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+    paths.sort();
+    paths.dedup();
+    if paths.len() > 1 {
+        bail!("{} matches several files; give one of: {}", path, paths.join(", "));
+    }
+
+    let at = since_time(at, Local::now())?;
+    let Some(content) = reconstruct(changes, &at) else {
+        bail!(
+            "Can't rebuild {} as of {}: no Write or recorded original content to start from before then",
+            paths[0],
+            at
+        );
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Json => {
+            let out = json!({ "path": paths[0], "at": at, "content": content });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&out)?);
+            } else {
+                println!("{}", serde_json::to_string(&out)?);
+            }
+        }
+        // The bare content, to redirect into a file
+        _ => print!("{}", content),
+    }
+    Ok(())
+}
+
+fn print_history(cli: &Cli, changes: &[FileChange], path: &str) -> Result<()> {
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            let sessions: HashSet<&str> = changes.iter().map(|c| c.session_id.as_str()).collect();
+            println!(
+                "{}",
+                colors::header(&format!(
+                    "History of {} ({} changes in {} sessions)",
+                    path,
+                    changes.len(),
+                    sessions.len()
+                ))
+            );
+            for change in changes {
+                println!();
+                println!(
+                    "{} {} {} {}",
+                    colors::colored_session(change.session_name.as_deref().unwrap_or(&change.session_id)),
+                    colors::colored_time(&change.timestamp),
+                    change.tool.bold(),
+                    change.path
+                );
+                for line in diff_lines(&change.change) {
+                    let line = if line.starts_with("- ") {
+                        line.red().to_string()
+                    } else if line.starts_with("+ ") {
+                        line.green().to_string()
+                    } else {
+                        colors::label(&line)
+                    };
+                    println!("  {}", line);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(changes)?);
+            } else {
+                println!("{}", serde_json::to_string(changes)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for change in changes {
+                println!("{}\t{}\t{}\t{}", change.timestamp, change.session_id, change.tool, change.path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A change as diff lines, via the viewer's Edit rendering
+fn diff_lines(change: &Change) -> Vec<String> {
+    match change {
+        Change::Edit { edits } => edits.iter().flat_map(|(old, new, _)| edit_diff(old, new)).collect(),
+        Change::Write { content } => {
+            let mut lines = edit_diff("", content);
+            let total = lines.len() - 1;
+            if total > MAX_WRITE_LINES {
+                lines.truncate(MAX_WRITE_LINES + 1);
+                lines.push(format!("… {} more lines written", total - MAX_WRITE_LINES));
+            }
+            lines
+        }
+    }
+}
+
+/// Successful changes to files matching `path`, oldest first
+pub fn file_changes(lines: &[TranscriptLine], events: &[HookEvent], path: &str) -> Vec<FileChange> {
+    // Results arrive on later user lines: id -> (failed, the line's toolUseResult)
+    let mut results: HashMap<String, (bool, Option<Value>)> = HashMap::new();
+    for line in lines.iter().filter(|l| l.line_type == LineType::User) {
+        let Ok(parsed) = line.parse() else {
+            continue;
+        };
+        let Some(MessageContent::Blocks(blocks)) = parsed.message.map(|m| m.content) else {
+            continue;
+        };
+        let ids: Vec<(String, bool)> = blocks
+            .into_iter()
+            .filter_map(|b| match b {
+                ContentBlock::ToolResult { tool_use_id, is_error, .. } => Some((tool_use_id, is_error)),
+                _ => None,
+            })
+            .collect();
+        // toolUseResult belongs to the line's only result
+        let single = ids.len() == 1;
+        for (id, failed) in ids {
+            let detail = if single { parsed.tool_use_result.clone() } else { None };
+            results.insert(id, (failed, detail));
+        }
+    }
+
+    let mut changes = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    for line in lines {
+        let Some(MessageContent::Blocks(blocks)) = line.message().map(|m| m.content) else {
+            continue;
+        };
+        for block in blocks {
+            let ContentBlock::ToolUse { id, name, input } = block else {
+                continue;
+            };
+            let Some(file) = edited_file(&name, &input).filter(|f| path_glob_match(path, f)) else {
+                continue;
+            };
+            let (failed, detail) = results.remove(&id).unwrap_or((false, None));
+            seen.insert(id);
+            let Some(change) = Change::from_input(&name, &input).filter(|_| !failed) else {
+                continue;
+            };
+            changes.push(FileChange {
+                session_id: line.session_id.clone(),
+                session_name: line.session_name.clone().or_else(|| line.slug.clone()),
+                timestamp: line.timestamp.clone(),
+                turn: line.turn_sequence,
+                tool: name.clone(),
+                path: file.to_string(),
+                change,
+                before: detail.as_ref().and_then(original_file),
+            });
+        }
+    }
+
+    for event in events {
+        if event.tool_use_id.as_ref().is_some_and(|id| seen.contains(id)) || event.tool_failed() == Some(true) {
+            continue;
+        }
+        let Some(payload) = event.input_json.as_deref().and_then(|j| serde_json::from_str::<Value>(j).ok()) else {
+            continue;
+        };
+        let (Some(tool), Some(input)) = (event.tool_name.as_deref(), payload.get("tool_input")) else {
+            continue;
+        };
+        let Some(file) = edited_file(tool, input).filter(|f| path_glob_match(path, f)) else {
+            continue;
+        };
+        let Some(change) = Change::from_input(tool, input) else {
+            continue;
+        };
+        changes.push(FileChange {
+            session_id: event.session_id.clone(),
+            session_name: event.session_name.clone(),
+            timestamp: event.timestamp.clone(),
+            turn: event.turn_sequence,
+            tool: tool.to_string(),
+            path: file.to_string(),
+            change,
+            before: payload.get("tool_response").and_then(original_file),
+        });
+    }
+
+    changes.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    changes
+}
+
+/// The file's content before an edit, as Claude Code records it in the result
+fn original_file(result: &Value) -> Option<String> {
+    result.get("originalFile").and_then(|v| v.as_str()).map(str::to_string)
+}
+
+/// The file as of `at` (a timestamp, compared to the second), replaying the
+/// changes from the last full copy before it
+pub fn reconstruct(changes: &[FileChange], at: &str) -> Option<String> {
+    let mut content: Option<String> = None;
+    for change in changes.iter().filter(|c| at_or_before(&c.timestamp, at)) {
+        if let Some(before) = &change.before {
+            content = Some(before.clone());
+        }
+        content = match (&change.change, content) {
+            (Change::Write { content }, _) => Some(content.clone()),
+            (edit, Some(current)) => edit.apply(&current),
+            (_, None) => None,
+        };
+    }
+    content
+}
+
+/// Whether `timestamp` is no later than `at`, ignoring the precision `at` lacks
+fn at_or_before(timestamp: &str, at: &str) -> bool {
+    timestamp.get(..at.len()).unwrap_or(timestamp) <= at
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(timestamp: &str, change: Change, before: Option<&str>) -> FileChange {
+        FileChange {
+            session_id: "s1".to_string(),
+            session_name: None,
+            timestamp: timestamp.to_string(),
+            turn: None,
+            tool: "Edit".to_string(),
+            path: "/app/src/schema.rs".to_string(),
+            change,
+            before: before.map(str::to_string),
+        }
+    }
+
+    fn edit(old: &str, new: &str) -> Change {
+        Change::Edit {
+            edits: vec![(old.to_string(), new.to_string(), false)],
+        }
+    }
+
+    #[test]
+    fn test_reconstruct_replays_from_a_full_copy() {
+        let changes = vec![
+            change("2026-01-01T10:00:00.000Z", edit("v1", "v2"), None),
+            change("2026-01-01T11:00:00.000Z", edit("v2", "v3"), Some("const V = v2;\n")),
+            change("2026-01-01T12:00:00.500Z", edit("v3", "v4"), None),
+        ];
+
+        // The first edit has nothing to apply to
+        assert_eq!(reconstruct(&changes, "2026-01-01T10:30:00"), None);
+        assert_eq!(reconstruct(&changes, "2026-01-01T11:30:00").as_deref(), Some("const V = v3;\n"));
+        // Same second as the last edit: included
+        assert_eq!(reconstruct(&changes, "2026-01-01T12:00:00").as_deref(), Some("const V = v4;\n"));
+
+        let written = vec![
+            change("2026-01-01T09:00:00Z", Change::Write { content: "a a".to_string() }, None),
+            change(
+                "2026-01-01T09:30:00Z",
+                Change::Edit {
+                    edits: vec![("a".to_string(), "b".to_string(), true)],
+                },
+                None,
+            ),
+            change("2026-01-01T09:45:00Z", edit("missing", "x"), None),
+        ];
+        assert_eq!(reconstruct(&written, "2026-01-01T09:40:00").as_deref(), Some("b b"));
+        // An edit whose text isn't in the file loses track of it
+        assert_eq!(reconstruct(&written, "2026-01-01T10:00:00"), None);
+    }
+
+    #[test]
+    fn test_changes_come_from_successful_calls() {
+        let line = |line_type: LineType, raw: Value| TranscriptLine {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        };
+        let lines = vec![
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t1", "name": "Edit", "input": {
+                    "file_path": "/app/src/schema.rs", "old_string": "v1", "new_string": "v2"}}
+            ]}})),
+            line(LineType::User, json!({
+                "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "t1", "content": "ok"}]},
+                "toolUseResult": {"originalFile": "v1"}
+            })),
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t2", "name": "Edit", "input": {
+                    "file_path": "/app/src/schema.rs", "old_string": "nope", "new_string": "v3"}},
+                {"type": "tool_use", "id": "t3", "name": "Edit", "input": {
+                    "file_path": "/app/src/main.rs", "old_string": "a", "new_string": "b"}}
+            ]}})),
+            line(LineType::User, json!({"message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t2", "content": "String not found", "is_error": true}
+            ]}})),
+        ];
+
+        let changes = file_changes(&lines, &[], "src/schema.rs");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].before.as_deref(), Some("v1"));
+        assert_eq!(reconstruct(&changes, "2026-01-02").as_deref(), Some("v2"));
+    }
+}
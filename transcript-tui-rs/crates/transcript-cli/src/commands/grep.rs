@@ -0,0 +1,338 @@
+//! Grep command - search tool inputs and results by the files they involve
+//!
+//! Calls come from transcript lines, with their results joined on
+//! tool_use_id; PostToolUse hook events add the calls of sessions recorded
+//! only through hooks. A call is searched when the file it reads or changes
+//! (Read and the edit tools) matches `--path`.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use transcript_core::{path_glob_match, touched_file, ContentBlock, HookEvent, HookEventFilter, MessageContent, TranscriptLine, EDIT_TOOLS};
+use transcript_db::TranscriptDb;
+
+use super::export::trace::result_text;
+use super::resolve_session_id;
+use crate::cli::{Cli, OutputFormat};
+use crate::output::colors;
+
+/// A tool call on a file, with its result when recorded
+#[derive(Debug, Clone)]
+pub struct FileCall {
+    pub session_id: String,
+    pub session_name: Option<String>,
+    pub timestamp: String,
+    pub tool: String,
+    pub path: String,
+    pub input: Value,
+    pub result: Option<String>,
+}
+
+/// A line of a call's input or result that matched
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrepHit {
+    pub session_id: String,
+    pub session_name: Option<String>,
+    pub timestamp: String,
+    pub tool: String,
+    pub path: String,
+    /// Input field (`new_string`, `edits[0].old_string`) or `result`
+    pub field: String,
+    /// Line within the field, from 1
+    pub line: usize,
+    pub text: String,
+}
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    pattern: &str,
+    path: Option<&str>,
+    session: Option<&str>,
+    ignore_case: bool,
+    limit: usize,
+) -> Result<()> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .with_context(|| format!("Invalid pattern '{}'", pattern))?;
+    let session_id = session.map(|s| resolve_session_id(db, s)).transpose()?;
+
+    let lines = db.get_tool_lines(session_id.as_deref())?;
+    let mut tools: Vec<String> = EDIT_TOOLS.iter().map(|(name, _)| name.to_string()).collect();
+    tools.push("Read".to_string());
+    let events = db.get_hook_events(&HookEventFilter {
+        session_id: session_id.clone(),
+        event_types: Some(vec!["PostToolUse".to_string()]),
+        tool_names: Some(tools),
+        ..Default::default()
+    })?;
+
+    let mut calls = file_calls(&lines, &events);
+    calls.retain(|c| path.is_none_or(|glob| path_glob_match(glob, &c.path)));
+    calls.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let hits = grep_calls(&calls, &regex, limit);
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if hits.is_empty() {
+                println!("No matches for {}", pattern.cyan());
+                return Ok(());
+            }
+            let mut last_call: Option<(&str, &str, &str)> = None;
+            for hit in &hits {
+                // One header per call, then its matching lines
+                let call = (hit.session_id.as_str(), hit.timestamp.as_str(), hit.path.as_str());
+                if last_call != Some(call) {
+                    if last_call.is_some() {
+                        println!();
+                    }
+                    println!(
+                        "{} {} {} {}",
+                        colors::colored_session(hit.session_name.as_deref().unwrap_or(&hit.session_id)),
+                        colors::colored_time(&hit.timestamp),
+                        hit.tool.bold(),
+                        hit.path
+                    );
+                    last_call = Some(call);
+                }
+                let text = regex.replace_all(&hit.text, |m: &regex::Captures| m[0].black().on_yellow().to_string());
+                println!("  {} {}", colors::label(&format!("{}:{}", hit.field, hit.line)), text);
+            }
+            if hits.len() == limit {
+                println!();
+                println!("{}", colors::label(&format!("First {} matches; --limit shows more", limit)));
+            }
+        }
+        OutputFormat::Json => {
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&hits)?);
+            } else {
+                println!("{}", serde_json::to_string(&hits)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for hit in &hits {
+                println!("{}:{}:{}: {}", hit.path, hit.field, hit.line, hit.text);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Calls on files from transcript lines, plus hook events for calls the
+/// transcripts don't have
+pub fn file_calls(lines: &[TranscriptLine], events: &[HookEvent]) -> Vec<FileCall> {
+    // Tool results arrive on later user lines
+    let mut results: HashMap<String, String> = HashMap::new();
+    for line in lines {
+        let Some(MessageContent::Blocks(blocks)) = line.message().map(|m| m.content) else {
+            continue;
+        };
+        for block in blocks {
+            if let ContentBlock::ToolResult { tool_use_id, content, .. } = block {
+                results.insert(tool_use_id, result_text(&content));
+            }
+        }
+    }
+
+    let mut calls = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    for line in lines {
+        let Some(MessageContent::Blocks(blocks)) = line.message().map(|m| m.content) else {
+            continue;
+        };
+        for block in blocks {
+            let ContentBlock::ToolUse { id, name, input } = block else {
+                continue;
+            };
+            let Some(path) = touched_file(&name, &input).map(str::to_string) else {
+                continue;
+            };
+            calls.push(FileCall {
+                session_id: line.session_id.clone(),
+                session_name: line.session_name.clone().or_else(|| line.slug.clone()),
+                timestamp: line.timestamp.clone(),
+                tool: name,
+                path,
+                input,
+                result: results.remove(&id),
+            });
+            seen.insert(id);
+        }
+    }
+
+    for event in events {
+        if event.tool_use_id.as_ref().is_some_and(|id| seen.contains(id)) {
+            continue;
+        }
+        let Some(payload) = event.input_json.as_deref().and_then(|j| serde_json::from_str::<Value>(j).ok()) else {
+            continue;
+        };
+        let (Some(tool), Some(input)) = (event.tool_name.clone(), payload.get("tool_input").cloned()) else {
+            continue;
+        };
+        let Some(path) = touched_file(&tool, &input).map(str::to_string) else {
+            continue;
+        };
+        calls.push(FileCall {
+            session_id: event.session_id.clone(),
+            session_name: event.session_name.clone(),
+            timestamp: event.timestamp.clone(),
+            tool,
+            path,
+            input,
+            result: payload.get("tool_response").and_then(response_text),
+        });
+    }
+
+    calls
+}
+
+/// Text of a hook's tool response: a plain string, or the content or output
+/// it carries
+fn response_text(response: &Value) -> Option<String> {
+    match response {
+        Value::String(s) => Some(s.clone()),
+        _ => ["content", "stdout"]
+            .iter()
+            .find_map(|key| response.get(*key).and_then(|v| v.as_str()))
+            .map(str::to_string),
+    }
+}
+
+/// Lines of the calls' inputs and results matching `regex`, at most `limit`
+pub fn grep_calls(calls: &[FileCall], regex: &Regex, limit: usize) -> Vec<GrepHit> {
+    let mut hits = Vec::new();
+    for call in calls {
+        let mut fields = Vec::new();
+        string_fields(&call.input, "", &mut fields);
+        if let Some(result) = &call.result {
+            fields.push(("result".to_string(), result.as_str()));
+        }
+
+        for (field, text) in fields {
+            for (i, line) in text.lines().enumerate() {
+                if !regex.is_match(line) {
+                    continue;
+                }
+                if hits.len() == limit {
+                    return hits;
+                }
+                hits.push(GrepHit {
+                    session_id: call.session_id.clone(),
+                    session_name: call.session_name.clone(),
+                    timestamp: call.timestamp.clone(),
+                    tool: call.tool.clone(),
+                    path: call.path.clone(),
+                    field: field.clone(),
+                    line: i + 1,
+                    text: line.to_string(),
+                });
+            }
+        }
+    }
+    hits
+}
+
+/// String values of a tool input by field path, leaving out the file path
+/// itself (every call on the file would match a pattern in its name)
+fn string_fields<'a>(value: &'a Value, prefix: &str, out: &mut Vec<(String, &'a str)>) {
+    match value {
+        Value::String(s) => out.push((prefix.to_string(), s)),
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                string_fields(item, &format!("{}[{}]", prefix, i), out);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                if prefix.is_empty() && matches!(key.as_str(), "file_path" | "notebook_path") {
+                    continue;
+                }
+                let field = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                string_fields(item, &field, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use transcript_core::LineType;
+
+    fn line(line_type: LineType, raw: Value) -> TranscriptLine {
+        TranscriptLine {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            slug: Some("happy-dog".to_string()),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_grep_scopes_to_files_and_fields() {
+        let lines = vec![
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t1", "name": "Edit", "input": {
+                    "file_path": "/app/src/schema.rs", "old_string": "DB_VERSION = 1", "new_string": "DB_VERSION = 2"}},
+                {"type": "tool_use", "id": "t2", "name": "Bash", "input": {"command": "grep DB_VERSION src"}},
+                {"type": "tool_use", "id": "t3", "name": "Read", "input": {"file_path": "/app/src/main.rs"}}
+            ]}})),
+            line(LineType::User, json!({"message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t3", "content": "fn main() {\n    schema::DB_VERSION\n}"}
+            ]}})),
+        ];
+        // The hook event repeats t1 and adds a call the transcripts lack
+        let event = |id: &str, path: &str| HookEvent {
+            id: 1,
+            session_id: "s2".to_string(),
+            timestamp: "2026-01-02T00:00:00Z".to_string(),
+            event_type: "PostToolUse".to_string(),
+            tool_use_id: Some(id.to_string()),
+            tool_name: Some("Write".to_string()),
+            decision: None,
+            handler_results_json: None,
+            input_json: Some(json!({"tool_input": {"file_path": path, "content": "DB_VERSION = 3"}}).to_string()),
+            context_json: None,
+            file_path: String::new(),
+            line_number: 0,
+            turn_id: None,
+            turn_sequence: None,
+            session_name: None,
+            git_hash: None,
+            git_branch: None,
+            git_dirty: None,
+        };
+        let events = vec![event("t1", "/app/src/schema.rs"), event("h1", "/other/src/schema.rs")];
+
+        let calls = file_calls(&lines, &events);
+        assert_eq!(calls.len(), 3);
+
+        let regex = Regex::new("DB_VERSION").unwrap();
+        let mut schema_calls = calls.clone();
+        schema_calls.retain(|c| path_glob_match("src/schema.rs", &c.path));
+        let hits = grep_calls(&schema_calls, &regex, 10);
+        let mut found: Vec<(&str, &str)> = hits.iter().map(|h| (h.session_id.as_str(), h.field.as_str())).collect();
+        found.sort();
+        assert_eq!(found, vec![("s1", "new_string"), ("s1", "old_string"), ("s2", "content")]);
+
+        let hits = grep_calls(&calls, &regex, 10);
+        let read = hits.iter().find(|h| h.tool == "Read").unwrap();
+        assert_eq!((read.field.as_str(), read.line), ("result", 2));
+
+        assert_eq!(grep_calls(&calls, &regex, 2).len(), 2);
+    }
+}
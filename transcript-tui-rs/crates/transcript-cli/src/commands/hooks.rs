@@ -0,0 +1,25 @@
+//! Hooks command - hook-events under the `transcript` binary
+
+use anyhow::Result;
+
+use crate::cli::{Cli, FormatArg};
+
+/// Run a hook-events command with this invocation's global options
+pub fn run(cli: &Cli, command: &hook_events_cli::Command) -> Result<()> {
+    hook_events_cli::run(hook_events_cli::Cli {
+        db_path: cli.db_path.clone(),
+        profile: None,
+        format: cli.format.map(|f| match f {
+            FormatArg::Human | FormatArg::Md => hook_events_cli::cli::OutputFormat::Human,
+            FormatArg::Json => hook_events_cli::cli::OutputFormat::Json,
+            FormatArg::Minimal => hook_events_cli::cli::OutputFormat::Minimal,
+        }),
+        json: cli.json,
+        human: cli.human,
+        minimal: cli.minimal,
+        pretty: cli.pretty,
+        color: cli.color,
+        no_color: cli.no_color,
+        command: command.clone(),
+    })
+}
@@ -0,0 +1,265 @@
+//! Import command - add conversations saved in API messages format, or
+//! exported from Claude.ai
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use transcript_indexer::MessagesFormat;
+
+use crate::cli::{Cli, ImportFormat, OutputFormat};
+use crate::commands::index::{abbreviate_path, open_indexer};
+use crate::output::colors;
+
+/// The conversations file of a Claude.ai export
+const CLAUDE_AI_CONVERSATIONS: &str = "conversations.json";
+
+pub fn run(cli: &Cli, file: &Path, format: ImportFormat, session_id: Option<&str>) -> Result<()> {
+    let from_stdin = file.as_os_str() == "-";
+    let bytes = if from_stdin {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        std::fs::read(file).with_context(|| format!("Failed to read {}", file.display()))?
+    };
+
+    if format == ImportFormat::ClaudeAi {
+        if session_id.is_some() {
+            bail!("--session-id doesn't apply to Claude.ai exports (each conversation is its own session)");
+        }
+        return run_claude_ai(cli, file, &bytes);
+    }
+
+    let input: serde_json::Value =
+        serde_json::from_slice(&bytes).with_context(|| format!("{} is not valid JSON", file.display()))?;
+
+    let session_id = match session_id {
+        Some(id) => id.to_string(),
+        None if from_stdin => format!("import-{}", Utc::now().format("%Y%m%d-%H%M%S")),
+        None => format!(
+            "import-{}",
+            file.file_stem().unwrap_or(file.as_os_str()).to_string_lossy()
+        ),
+    };
+    // Messages carry no times, so they are spaced a second apart from when the
+    // file was saved
+    let start: DateTime<Utc> = std::fs::metadata(file)
+        .and_then(|m| m.modified())
+        .map(DateTime::from)
+        .unwrap_or_else(|_| Utc::now());
+    let format = match format {
+        ImportFormat::Anthropic => MessagesFormat::Anthropic,
+        ImportFormat::OpenAi => MessagesFormat::OpenAi,
+        ImportFormat::ClaudeAi => unreachable!("imported above"),
+    };
+
+    let indexer_db = open_indexer(cli)?;
+    let result = transcript_indexer::import_messages(
+        indexer_db.connection(),
+        &input,
+        format,
+        &session_id,
+        &transcript_indexer::default_imports_dir(),
+        start,
+    )?;
+    let file_path = result.file_path.to_string_lossy();
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{} {} message(s) as {} ({})",
+                colors::success("Imported"),
+                result.messages,
+                colors::colored_session(&result.session_id),
+                abbreviate_path(&file_path)
+            );
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "session_id": result.session_id,
+                "format": format.name(),
+                "messages": result.messages,
+                "lines_indexed": result.lines_indexed,
+                "file_path": file_path,
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            println!("{}", result.session_id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Import every conversation of a Claude.ai export (the zip, or its
+/// conversations.json)
+fn run_claude_ai(cli: &Cli, file: &Path, bytes: &[u8]) -> Result<()> {
+    let json = if bytes.starts_with(b"PK\x03\x04") {
+        zip_entry(bytes, CLAUDE_AI_CONVERSATIONS)
+            .with_context(|| format!("Failed to read {}", file.display()))?
+            .with_context(|| format!("{} has no {}", file.display(), CLAUDE_AI_CONVERSATIONS))?
+    } else {
+        bytes.to_vec()
+    };
+    let export: serde_json::Value = serde_json::from_slice(&json)
+        .with_context(|| format!("{} is not valid JSON", file.display()))?;
+
+    let indexer_db = open_indexer(cli)?;
+    let results = transcript_indexer::import_claude_ai(
+        indexer_db.connection(),
+        &export,
+        &transcript_indexer::default_imports_dir(),
+    )?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            for result in &results {
+                println!(
+                    "  {} ({} messages)",
+                    colors::colored_session(&result.session_id),
+                    result.messages
+                );
+            }
+            println!(
+                "{} {} conversation(s), {} message(s)",
+                colors::success("Imported"),
+                results.len(),
+                results.iter().map(|r| r.messages).sum::<usize>()
+            );
+        }
+        OutputFormat::Json => {
+            let output: Vec<_> = results
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "session_id": r.session_id,
+                        "format": "claude-ai",
+                        "messages": r.messages,
+                        "lines_indexed": r.lines_indexed,
+                        "file_path": r.file_path.to_string_lossy(),
+                    })
+                })
+                .collect();
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for result in &results {
+                println!("{}", result.session_id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The file whose name ends in `name` in a zip archive (stored or deflated;
+/// no zip64), if there is one
+fn zip_entry(zip: &[u8], name: &str) -> Result<Option<Vec<u8>>> {
+    let u16_at = |at: usize| -> Result<usize> {
+        let b = zip.get(at..at + 2).context("truncated zip archive")?;
+        Ok(u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let u32_at = |at: usize| -> Result<usize> {
+        let b = zip.get(at..at + 4).context("truncated zip archive")?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+
+    // End of central directory record: 22 bytes plus a comment of up to 64K
+    let end = (0..zip.len().saturating_sub(21))
+        .rev()
+        .take(22 + u16::MAX as usize)
+        .find(|&at| zip[at..].starts_with(b"PK\x05\x06"))
+        .context("not a zip archive (no central directory)")?;
+    let entries = u16_at(end + 10)?;
+    let mut at = u32_at(end + 16)?;
+
+    for _ in 0..entries {
+        if !zip.get(at..).is_some_and(|z| z.starts_with(b"PK\x01\x02")) {
+            bail!("corrupt zip central directory");
+        }
+        let method = u16_at(at + 10)?;
+        let compressed = u32_at(at + 20)?;
+        let name_len = u16_at(at + 28)?;
+        let extra_len = u16_at(at + 30)?;
+        let comment_len = u16_at(at + 32)?;
+        let local = u32_at(at + 42)?;
+        let entry_name = zip.get(at + 46..at + 46 + name_len).context("truncated zip archive")?;
+        at += 46 + name_len + extra_len + comment_len;
+
+        let entry_name = String::from_utf8_lossy(entry_name);
+        if entry_name != name && !entry_name.ends_with(&format!("/{}", name)) {
+            continue;
+        }
+        let start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+        let data = zip.get(start..start + compressed).context("truncated zip archive")?;
+        return match method {
+            0 => Ok(Some(data.to_vec())),
+            8 => {
+                let mut out = Vec::new();
+                flate2::read::DeflateDecoder::new(data).read_to_end(&mut out)?;
+                Ok(Some(out))
+            }
+            other => bail!("{} uses unsupported zip compression method {}", entry_name, other),
+        };
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A zip with one deflated file (CRCs left zero, which isn't checked)
+    fn zip_of(name: &str, contents: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(contents).unwrap();
+        let data = encoder.finish().unwrap();
+
+        let mut zip = Vec::new();
+        let fields = |zip: &mut Vec<u8>| {
+            zip.extend_from_slice(&[20, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            zip.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            zip.extend_from_slice(&[0, 0]);
+        };
+        zip.extend_from_slice(b"PK\x03\x04");
+        fields(&mut zip);
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(&data);
+
+        let directory = zip.len();
+        zip.extend_from_slice(b"PK\x01\x02\x14\x00");
+        fields(&mut zip);
+        zip.extend_from_slice(&[0; 14]);
+        zip.extend_from_slice(name.as_bytes());
+        let directory_len = zip.len() - directory;
+
+        zip.extend_from_slice(b"PK\x05\x06\0\0\0\0\x01\x00\x01\x00");
+        zip.extend_from_slice(&(directory_len as u32).to_le_bytes());
+        zip.extend_from_slice(&(directory as u32).to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+        zip
+    }
+
+    #[test]
+    fn test_zip_entry() {
+        let zip = zip_of("data-2025/conversations.json", b"[{\"uuid\": \"c1\"}]");
+        let entry = zip_entry(&zip, CLAUDE_AI_CONVERSATIONS).unwrap();
+        assert_eq!(entry.as_deref(), Some(&b"[{\"uuid\": \"c1\"}]"[..]));
+        assert_eq!(zip_entry(&zip, "users.json").unwrap(), None);
+        assert!(zip_entry(b"PK\x03\x04 not really", CLAUDE_AI_CONVERSATIONS).is_err());
+    }
+}
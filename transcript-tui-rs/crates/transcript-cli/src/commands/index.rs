@@ -1,8 +1,8 @@
 //! Index command - manage transcript index
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use transcript_db::TranscriptDb;
-use transcript_indexer::IndexerDb;
+use transcript_indexer::{IndexedFileKind, IndexerDb, MaintainResult, PruneResult, StaleReason};
 
 use crate::cli::{Cli, OutputFormat};
 use crate::output::colors;
@@ -11,6 +11,8 @@ pub fn status(cli: &Cli, db: Option<TranscriptDb>) -> Result<()> {
     match db {
         Some(db) => {
             let stats = db.stats()?;
+            let users = db.get_users().unwrap_or_default();
+            let (failed_files, bad_lines) = db.count_index_errors().unwrap_or_default();
 
             match cli.effective_format() {
                 OutputFormat::Human => {
@@ -47,6 +49,17 @@ pub fn status(cli: &Cli, db: Option<TranscriptDb>) -> Result<()> {
                         colors::label("Hook events"),
                         colors::format_count(stats.hook_event_count)
                     );
+                    if users.len() > 1 {
+                        let summary: Vec<String> = users
+                            .iter()
+                            .map(|(user, count)| format!("{} ({})", user, count))
+                            .collect();
+                        println!(
+                            "  {}: {}",
+                            colors::label("Users"),
+                            colors::value(&summary.join(", "))
+                        );
+                    }
 
                     if let Some(last) = &stats.last_indexed {
                         println!();
@@ -58,7 +71,17 @@ pub fn status(cli: &Cli, db: Option<TranscriptDb>) -> Result<()> {
                     }
 
                     println!();
-                    println!("{}", colors::success("Index is healthy"));
+                    if failed_files + bad_lines == 0 {
+                        println!("{}", colors::success("Index is healthy"));
+                    } else {
+                        println!(
+                            "  {}: {}",
+                            colors::label("Index errors"),
+                            colors::warning(&format!("{} files, {} lines", failed_files, bad_lines))
+                        );
+                        println!();
+                        println!("{}", colors::label("Review them with: transcript index errors"));
+                    }
                 }
 
                 OutputFormat::Json => {
@@ -70,7 +93,12 @@ pub fn status(cli: &Cli, db: Option<TranscriptDb>) -> Result<()> {
                         "line_count": stats.line_count,
                         "hook_event_count": stats.hook_event_count,
                         "last_indexed": stats.last_indexed,
-                        "status": "healthy"
+                        "users": users
+                            .iter()
+                            .map(|(user, count)| serde_json::json!({ "user": user, "sessions": count }))
+                            .collect::<Vec<_>>(),
+                        "index_errors": { "files": failed_files, "lines": bad_lines },
+                        "status": if failed_files + bad_lines == 0 { "healthy" } else { "errors" }
                     });
                     if cli.pretty {
                         println!("{}", serde_json::to_string_pretty(&output)?);
@@ -112,7 +140,7 @@ pub fn status(cli: &Cli, db: Option<TranscriptDb>) -> Result<()> {
 }
 
 pub fn build(cli: &Cli) -> Result<()> {
-    let indexer_db = IndexerDb::open_or_create_default()?;
+    let indexer_db = open_indexer(cli)?;
     let conn = indexer_db.connection();
 
     match cli.effective_format() {
@@ -141,6 +169,8 @@ pub fn build(cli: &Cli) -> Result<()> {
                 transcript_result.files_indexed,
                 transcript_result.lines_indexed
             );
+            warn_large_payloads(transcript_result.large_payloads);
+            index_adapter_logs(conn, true)?;
 
             // Index hook events
             eprintln!();
@@ -164,6 +194,7 @@ pub fn build(cli: &Cli) -> Result<()> {
                 hook_result.files_indexed,
                 hook_result.events_indexed
             );
+            warn_recovered_lines(transcript_result.recovered_lines + hook_result.recovered_lines);
 
             // Correlate turns
             eprintln!();
@@ -188,6 +219,7 @@ pub fn build(cli: &Cli) -> Result<()> {
                 transcript_indexer::index_all_transcripts(conn, None, |_, _, _, _| {})?;
             let hook_result =
                 transcript_indexer::index_all_hook_files(conn, None, |_, _, _, _| {})?;
+            let adapter_result = index_adapter_logs(conn, false)?;
             let corr_result = transcript_indexer::correlate_lines_to_turns(conn)?;
 
             let output = serde_json::json!({
@@ -195,10 +227,18 @@ pub fn build(cli: &Cli) -> Result<()> {
                 "transcripts": {
                     "files_indexed": transcript_result.files_indexed,
                     "lines_indexed": transcript_result.lines_indexed,
+                    "large_payloads": transcript_result.large_payloads,
+                    "recovered_lines": transcript_result.recovered_lines,
                 },
                 "hooks": {
                     "files_indexed": hook_result.files_indexed,
                     "events_indexed": hook_result.events_indexed,
+                    "recovered_lines": hook_result.recovered_lines,
+                },
+                "adapters": {
+                    "files_checked": adapter_result.files_checked,
+                    "files_updated": adapter_result.files_updated,
+                    "new_lines": adapter_result.new_lines,
                 },
                 "correlation": {
                     "lines_updated": corr_result.updated,
@@ -217,10 +257,11 @@ pub fn build(cli: &Cli) -> Result<()> {
                 transcript_indexer::index_all_transcripts(conn, None, |_, _, _, _| {})?;
             let hook_result =
                 transcript_indexer::index_all_hook_files(conn, None, |_, _, _, _| {})?;
+            let adapter_result = index_adapter_logs(conn, false)?;
             let corr_result = transcript_indexer::correlate_lines_to_turns(conn)?;
             println!(
                 "{} lines, {} events, {} correlated",
-                transcript_result.lines_indexed,
+                transcript_result.lines_indexed + adapter_result.new_lines,
                 hook_result.events_indexed,
                 corr_result.updated
             );
@@ -231,7 +272,7 @@ pub fn build(cli: &Cli) -> Result<()> {
 }
 
 pub fn update(cli: &Cli) -> Result<()> {
-    let indexer_db = IndexerDb::open_or_create_default()?;
+    let indexer_db = open_indexer(cli)?;
     let conn = indexer_db.connection();
 
     match cli.effective_format() {
@@ -239,8 +280,23 @@ pub fn update(cli: &Cli) -> Result<()> {
             eprintln!("{}", colors::header("Updating index..."));
             eprintln!();
 
+            // Drop rows of deleted files, and of rotated ones so they index afresh
+            eprintln!("  {} Reconciling with files on disk...", colors::label("Step 1/4:"));
+            let (_, prune_result) = transcript_indexer::prune_stale_files(conn)?;
+            eprintln!(
+                "    {} {} files pruned, -{} lines, -{} events",
+                colors::success("Done:"),
+                prune_result.files,
+                prune_result.lines,
+                prune_result.hook_events
+            );
+            if let Some(bundle) = &prune_result.bundle {
+                eprintln!("    Moved to the trash as {} (transcript trash list)", bundle);
+            }
+
             // Delta update transcripts
-            eprintln!("  {} Updating transcripts...", colors::label("Step 1/3:"));
+            eprintln!();
+            eprintln!("  {} Updating transcripts...", colors::label("Step 2/4:"));
             let transcript_result = transcript_indexer::update_transcripts(
                 conn,
                 None,
@@ -263,12 +319,14 @@ pub fn update(cli: &Cli) -> Result<()> {
                 transcript_result.files_updated,
                 transcript_result.new_lines
             );
+            warn_large_payloads(transcript_result.large_payloads);
+            index_adapter_logs(conn, true)?;
 
             // Delta update hooks
             eprintln!();
             eprintln!(
                 "  {} Updating hook events...",
-                colors::label("Step 2/3:")
+                colors::label("Step 3/4:")
             );
             let hook_result = transcript_indexer::update_hook_index(
                 conn,
@@ -292,12 +350,13 @@ pub fn update(cli: &Cli) -> Result<()> {
                 hook_result.files_updated,
                 hook_result.new_events
             );
+            warn_recovered_lines(transcript_result.recovered_lines + hook_result.recovered_lines);
 
             // Correlate turns
             eprintln!();
             eprintln!(
                 "  {} Correlating turns...",
-                colors::label("Step 3/3:")
+                colors::label("Step 4/4:")
             );
             let corr_result = transcript_indexer::correlate_lines_to_turns(conn)?;
             eprintln!(
@@ -312,23 +371,34 @@ pub fn update(cli: &Cli) -> Result<()> {
         }
 
         OutputFormat::Json => {
+            let (_, prune_result) = transcript_indexer::prune_stale_files(conn)?;
             let transcript_result =
                 transcript_indexer::update_transcripts(conn, None, |_, _, _, _, _| {})?;
             let hook_result =
                 transcript_indexer::update_hook_index(conn, None, |_, _, _, _, _| {})?;
+            let adapter_result = index_adapter_logs(conn, false)?;
             let corr_result = transcript_indexer::correlate_lines_to_turns(conn)?;
 
             let output = serde_json::json!({
                 "status": "success",
+                "pruned": prune_json(&prune_result),
                 "transcripts": {
                     "files_checked": transcript_result.files_checked,
                     "files_updated": transcript_result.files_updated,
                     "new_lines": transcript_result.new_lines,
+                    "large_payloads": transcript_result.large_payloads,
+                    "recovered_lines": transcript_result.recovered_lines,
                 },
                 "hooks": {
                     "files_checked": hook_result.files_checked,
                     "files_updated": hook_result.files_updated,
                     "new_events": hook_result.new_events,
+                    "recovered_lines": hook_result.recovered_lines,
+                },
+                "adapters": {
+                    "files_checked": adapter_result.files_checked,
+                    "files_updated": adapter_result.files_updated,
+                    "new_lines": adapter_result.new_lines,
                 },
                 "correlation": {
                     "lines_updated": corr_result.updated,
@@ -343,38 +413,451 @@ pub fn update(cli: &Cli) -> Result<()> {
         }
 
         OutputFormat::Minimal => {
+            let (_, prune_result) = transcript_indexer::prune_stale_files(conn)?;
             let transcript_result =
                 transcript_indexer::update_transcripts(conn, None, |_, _, _, _, _| {})?;
             let hook_result =
                 transcript_indexer::update_hook_index(conn, None, |_, _, _, _, _| {})?;
+            let adapter_result = index_adapter_logs(conn, false)?;
             let corr_result = transcript_indexer::correlate_lines_to_turns(conn)?;
             println!(
-                "+{} lines, +{} events, {} correlated",
-                transcript_result.new_lines,
+                "+{} lines, +{} events, {} correlated, {} pruned",
+                transcript_result.new_lines + adapter_result.new_lines,
                 hook_result.new_events,
-                corr_result.updated
+                corr_result.updated,
+                prune_result.files
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the rows of deleted and rotated files (`--dry-run` only lists them)
+pub fn prune(cli: &Cli, dry_run: bool) -> Result<()> {
+    let indexer_db = open_indexer(cli)?;
+    let conn = indexer_db.connection();
+
+    let (stale, result) = if dry_run {
+        (transcript_indexer::find_stale_files(conn)?, PruneResult::default())
+    } else {
+        transcript_indexer::prune_stale_files(conn)?
+    };
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if result.released > 0 {
+                println!(
+                    "{} ignored sessions no longer match a rule and will be indexed again",
+                    result.released
+                );
+            }
+            if stale.is_empty() {
+                println!("{}", colors::success("Every indexed file is still on disk, and none is ignored"));
+                return Ok(());
+            }
+            for file in &stale {
+                let kind = match file.kind {
+                    IndexedFileKind::Transcript => "transcript",
+                    IndexedFileKind::Hooks => "hooks",
+                };
+                println!(
+                    "  {} {:<10} {}",
+                    colors::warning(&format!("{:<9}", file.reason.as_str())),
+                    kind,
+                    abbreviate_path(&file.file_path)
+                );
+            }
+            println!();
+            if dry_run {
+                println!(
+                    "{} (run without --dry-run to remove their rows)",
+                    colors::label(&format!("{} files would be pruned", stale.len()))
+                );
+            } else {
+                println!(
+                    "{} {} sessions, {} lines, {} hook files, {} hook events moved to the trash",
+                    colors::success("Pruned:"),
+                    result.sessions,
+                    result.lines,
+                    result.hook_files,
+                    result.hook_events
+                );
+                if let Some(bundle) = &result.bundle {
+                    println!(
+                        "{}",
+                        colors::label(&format!("Undo with: transcript trash restore {}", bundle))
+                    );
+                }
+                if stale.iter().any(|f| f.reason == StaleReason::Truncated) {
+                    println!(
+                        "{}",
+                        colors::label("Truncated files are indexed again from the start by: transcript index update")
+                    );
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let files: Vec<serde_json::Value> = stale
+                .iter()
+                .map(|file| {
+                    serde_json::json!({
+                        "file_path": file.file_path,
+                        "kind": match file.kind {
+                            IndexedFileKind::Transcript => "transcript",
+                            IndexedFileKind::Hooks => "hooks",
+                        },
+                        "reason": file.reason.as_str(),
+                    })
+                })
+                .collect();
+            let output = serde_json::json!({
+                "dry_run": dry_run,
+                "files": files,
+                "pruned": prune_json(&result),
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for file in &stale {
+                println!("{}\t{}", file.reason.as_str(), file.file_path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn prune_json(result: &PruneResult) -> serde_json::Value {
+    serde_json::json!({
+        "files": result.files,
+        "sessions": result.sessions,
+        "lines": result.lines,
+        "hook_files": result.hook_files,
+        "hook_events": result.hook_events,
+        "bundle": result.bundle,
+        "released": result.released,
+    })
+}
+
+/// List the indexing error log, after retrying the failed files (`--retry`)
+/// or emptying it (`--clear`)
+pub fn errors(cli: &Cli, clear: bool, retry: bool, limit: i64) -> Result<()> {
+    let indexer_db = open_indexer(cli)?;
+    let conn = indexer_db.connection();
+
+    let retried = if retry {
+        Some(transcript_indexer::retry_failed_files(conn)?)
+    } else {
+        None
+    };
+    if clear {
+        let cleared = transcript_indexer::clear_index_errors(conn)?;
+        match cli.effective_format() {
+            OutputFormat::Json => println!("{}", serde_json::json!({ "cleared": cleared })),
+            _ => println!("{}", colors::success(&format!("Cleared {} entries", cleared))),
+        }
+        return Ok(());
+    }
+
+    let db = TranscriptDb::open(indexer_db.path())?;
+    let failures = db.get_index_errors(limit)?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if let Some(r) = &retried {
+                println!(
+                    "{} {} indexed, {} still failing, {} no longer on disk",
+                    colors::success("Retried:"),
+                    r.indexed,
+                    r.failed,
+                    r.missing
+                );
+                println!();
+            }
+            if failures.is_empty() {
+                println!("{}", colors::success("No indexing errors"));
+                return Ok(());
+            }
+            for failure in &failures {
+                let location = if failure.line_number == 0 {
+                    abbreviate_path(&failure.file_path)
+                } else {
+                    format!("{}:{}", abbreviate_path(&failure.file_path), failure.line_number)
+                };
+                let count = if failure.count > 1 {
+                    format!(" (×{})", failure.count)
+                } else {
+                    String::new()
+                };
+                println!(
+                    "{} {:<10} {}{}",
+                    colors::colored_time(&failure.occurred_at),
+                    failure.kind,
+                    location,
+                    colors::label(&count)
+                );
+                println!("  {}", colors::error(&failure.error));
+            }
+            if failures.iter().any(|f| f.line_number == 0) && retried.is_none() {
+                println!();
+                println!("{}", colors::label("Index the failed files again with: transcript index errors --retry"));
+            }
+        }
+        OutputFormat::Json => {
+            let entries: Vec<serde_json::Value> = failures
+                .iter()
+                .map(|f| {
+                    serde_json::json!({
+                        "file_path": f.file_path,
+                        "kind": f.kind,
+                        "line_number": f.line_number,
+                        "error": f.error,
+                        "count": f.count,
+                        "occurred_at": f.occurred_at,
+                    })
+                })
+                .collect();
+            let output = serde_json::json!({
+                "errors": entries,
+                "retried": retried.map(|r| serde_json::json!({
+                    "indexed": r.indexed,
+                    "failed": r.failed,
+                    "missing": r.missing,
+                })),
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for f in &failures {
+                println!("{}:{}\t{}", f.file_path, f.line_number, f.error);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check, repair, analyze and vacuum the database
+pub fn maintain(cli: &Cli) -> Result<()> {
+    use transcript_indexer::maintain as steps;
+
+    let indexer_db = open_indexer(cli)?;
+    let conn = indexer_db.connection();
+
+    let result = match cli.effective_format() {
+        OutputFormat::Human => {
+            eprintln!("{}", colors::header("Maintaining index..."));
+            eprintln!();
+
+            eprintln!("  {} Checking database integrity...", colors::label("Step 1/4:"));
+            let integrity_errors = steps::integrity_check(conn)?;
+            if integrity_errors.is_empty() {
+                eprintln!("    {} no problems found", colors::success("Done:"));
+            } else {
+                for problem in &integrity_errors {
+                    eprintln!("    {}", colors::error(problem));
+                }
+            }
+
+            eprintln!();
+            eprintln!("  {} Checking search indexes...", colors::label("Step 2/4:"));
+            let fts_rebuilt = steps::repair_fts(conn)?;
+            if fts_rebuilt.is_empty() {
+                eprintln!("    {} in sync with their tables", colors::success("Done:"));
+            } else {
+                eprintln!("    {} rebuilt {}", colors::warning("Repaired:"), fts_rebuilt.join(", "));
+            }
+
+            eprintln!();
+            eprintln!("  {} Updating query statistics...", colors::label("Step 3/4:"));
+            steps::analyze(conn)?;
+            eprintln!("    {}", colors::success("Done"));
+
+            eprintln!();
+            eprintln!("  {} Reclaiming free space...", colors::label("Step 4/4:"));
+            let bytes_before = steps::database_size(conn)?;
+            let full_vacuum = if integrity_errors.is_empty() {
+                let full = steps::vacuum(conn)?;
+                if full {
+                    eprintln!("    Switched to incremental vacuuming (one-time full VACUUM)");
+                }
+                full
+            } else {
+                eprintln!("    {}", colors::warning("Skipped: the integrity check failed"));
+                false
+            };
+            let result = MaintainResult {
+                integrity_errors,
+                fts_rebuilt,
+                bytes_before,
+                bytes_after: steps::database_size(conn)?,
+                full_vacuum,
+            };
+            eprintln!(
+                "    {} {} reclaimed ({} -> {})",
+                colors::success("Done:"),
+                colors::format_size(result.reclaimed()),
+                colors::format_size(result.bytes_before),
+                colors::format_size(result.bytes_after)
+            );
+
+            eprintln!();
+            if result.integrity_errors.is_empty() {
+                eprintln!("{}", colors::success("Maintenance complete"));
+            }
+            result
+        }
+        OutputFormat::Json => {
+            let result = transcript_indexer::maintain(conn)?;
+            let output = serde_json::json!({
+                "integrity": if result.integrity_errors.is_empty() { "ok" } else { "failed" },
+                "integrity_errors": result.integrity_errors,
+                "fts_rebuilt": result.fts_rebuilt,
+                "full_vacuum": result.full_vacuum,
+                "bytes_before": result.bytes_before,
+                "bytes_after": result.bytes_after,
+                "reclaimed": result.reclaimed(),
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            result
+        }
+        OutputFormat::Minimal => {
+            let result = transcript_indexer::maintain(conn)?;
+            println!(
+                "{}, {} fts rebuilt, {} reclaimed",
+                if result.integrity_errors.is_empty() { "ok" } else { "integrity check failed" },
+                result.fts_rebuilt.len(),
+                result.reclaimed()
             );
+            result
+        }
+    };
+
+    if !result.integrity_errors.is_empty() {
+        anyhow::bail!(
+            "Database integrity check failed ({} problems); restore a backup or rebuild it with: transcript index rebuild",
+            result.integrity_errors.len()
+        );
+    }
+    Ok(())
+}
+
+/// Encrypt (or decrypt) every database the indexer writes with the configured key
+pub fn encrypt(cli: &Cli, decrypt: bool) -> Result<()> {
+    let Some(key) = transcript_core::database_key()? else {
+        anyhow::bail!(
+            "No database key (set {} or encryption.key_command in {})",
+            transcript_core::DB_KEY_ENV,
+            transcript_core::default_config_path().display()
+        );
+    };
+
+    // The index, its archive and the trash bundles, which all share the key
+    let db_path = cli.db_path.clone().unwrap_or_else(transcript_db::default_db_path);
+    let mut files = vec![db_path.clone()];
+    let archive = transcript_db::archive_db_path(&db_path);
+    if archive.exists() {
+        files.push(archive);
+    }
+    if let Ok(entries) = std::fs::read_dir(transcript_indexer::default_trash_dir()) {
+        let mut bundles: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+            .collect();
+        bundles.sort();
+        files.extend(bundles);
+    }
+
+    let mut changed = Vec::new();
+    for file in &files {
+        let rewritten = if decrypt {
+            transcript_indexer::decrypt_database(file, &key)
+        } else {
+            transcript_indexer::encrypt_database(file, &key)
+        };
+        if rewritten.with_context(|| format!("Failed to rewrite {}", file.display()))? {
+            changed.push(file);
         }
     }
 
+    let verb = if decrypt { "Decrypted" } else { "Encrypted" };
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if changed.is_empty() {
+                println!("Nothing to do: all {} databases are already {}", files.len(), verb.to_lowercase());
+                return Ok(());
+            }
+            println!("{} {} of {} databases", colors::success(&format!("{}:", verb)), changed.len(), files.len());
+            for file in &changed {
+                println!("  {}", abbreviate_path(&file.to_string_lossy()));
+            }
+            println!();
+            if decrypt {
+                println!(
+                    "{}",
+                    colors::warning(&format!(
+                        "Unset {} (and encryption.key_command) to open them again",
+                        transcript_core::DB_KEY_ENV
+                    ))
+                );
+            } else {
+                println!("{}", colors::label("Keep the key: without it they can't be opened"));
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "decrypt": decrypt,
+                "databases": files.iter().map(|f| f.to_string_lossy()).collect::<Vec<_>>(),
+                "changed": changed.iter().map(|f| f.to_string_lossy()).collect::<Vec<_>>(),
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Minimal => println!("{}", changed.len()),
+    }
     Ok(())
 }
 
 pub fn rebuild(cli: &Cli) -> Result<()> {
-    let mut indexer_db = IndexerDb::open_or_create_default()?;
+    let mut indexer_db = open_indexer(cli)?;
+    let trash_dir = transcript_indexer::default_trash_dir();
 
     match cli.effective_format() {
         OutputFormat::Human => {
             eprintln!("{}", colors::header("Rebuilding index..."));
             eprintln!();
             eprintln!("  Clearing existing data...");
-            transcript_indexer::rebuild_index(indexer_db.connection_mut())?;
+            let bundle = transcript_indexer::rebuild_index_with_trash(indexer_db.connection_mut(), &trash_dir)?;
             eprintln!("  {}", colors::success("Cleared"));
+            if let Some(bundle) = bundle {
+                eprintln!(
+                    "  {} sessions of {} files no longer on disk moved to the trash as {}",
+                    bundle.sessions,
+                    bundle.files.len(),
+                    bundle.id
+                );
+            }
             eprintln!();
         }
 
         OutputFormat::Json | OutputFormat::Minimal => {
-            transcript_indexer::rebuild_index(indexer_db.connection_mut())?;
+            transcript_indexer::rebuild_index_with_trash(indexer_db.connection_mut(), &trash_dir)?;
         }
     }
 
@@ -409,6 +892,8 @@ fn build_with_db(cli: &Cli, indexer_db: &IndexerDb) -> Result<()> {
                 transcript_result.files_indexed,
                 transcript_result.lines_indexed
             );
+            warn_large_payloads(transcript_result.large_payloads);
+            index_adapter_logs(conn, true)?;
 
             eprintln!();
             eprintln!("  {} Indexing hook events...", colors::label("Step 2/3:"));
@@ -431,6 +916,7 @@ fn build_with_db(cli: &Cli, indexer_db: &IndexerDb) -> Result<()> {
                 hook_result.files_indexed,
                 hook_result.events_indexed
             );
+            warn_recovered_lines(transcript_result.recovered_lines + hook_result.recovered_lines);
 
             eprintln!();
             eprintln!(
@@ -454,6 +940,7 @@ fn build_with_db(cli: &Cli, indexer_db: &IndexerDb) -> Result<()> {
                 transcript_indexer::index_all_transcripts(conn, None, |_, _, _, _| {})?;
             let hook_result =
                 transcript_indexer::index_all_hook_files(conn, None, |_, _, _, _| {})?;
+            let adapter_result = index_adapter_logs(conn, false)?;
             let corr_result = transcript_indexer::correlate_lines_to_turns(conn)?;
 
             let output = serde_json::json!({
@@ -462,10 +949,18 @@ fn build_with_db(cli: &Cli, indexer_db: &IndexerDb) -> Result<()> {
                 "transcripts": {
                     "files_indexed": transcript_result.files_indexed,
                     "lines_indexed": transcript_result.lines_indexed,
+                    "large_payloads": transcript_result.large_payloads,
+                    "recovered_lines": transcript_result.recovered_lines,
                 },
                 "hooks": {
                     "files_indexed": hook_result.files_indexed,
                     "events_indexed": hook_result.events_indexed,
+                    "recovered_lines": hook_result.recovered_lines,
+                },
+                "adapters": {
+                    "files_checked": adapter_result.files_checked,
+                    "files_updated": adapter_result.files_updated,
+                    "new_lines": adapter_result.new_lines,
                 },
                 "correlation": {
                     "lines_updated": corr_result.updated,
@@ -484,10 +979,11 @@ fn build_with_db(cli: &Cli, indexer_db: &IndexerDb) -> Result<()> {
                 transcript_indexer::index_all_transcripts(conn, None, |_, _, _, _| {})?;
             let hook_result =
                 transcript_indexer::index_all_hook_files(conn, None, |_, _, _, _| {})?;
+            let adapter_result = index_adapter_logs(conn, false)?;
             let corr_result = transcript_indexer::correlate_lines_to_turns(conn)?;
             println!(
                 "rebuilt: {} lines, {} events, {} correlated",
-                transcript_result.lines_indexed,
+                transcript_result.lines_indexed + adapter_result.new_lines,
                 hook_result.events_indexed,
                 corr_result.updated
             );
@@ -498,7 +994,7 @@ fn build_with_db(cli: &Cli, indexer_db: &IndexerDb) -> Result<()> {
 }
 
 pub fn watch(cli: &Cli) -> Result<()> {
-    let mut indexer_db = IndexerDb::open_or_create_default()?;
+    let mut indexer_db = open_indexer(cli)?;
 
     match cli.effective_format() {
         OutputFormat::Human => {
@@ -511,11 +1007,12 @@ pub fn watch(cli: &Cli) -> Result<()> {
                 transcript_indexer::update_transcripts(conn, None, |_, _, _, _, _| {})?;
             let hook_result =
                 transcript_indexer::update_hook_index(conn, None, |_, _, _, _, _| {})?;
+            let adapter_result = index_adapter_logs(conn, false)?;
             let corr_result = transcript_indexer::correlate_lines_to_turns(conn)?;
 
             eprintln!(
                 "  Initial sync: +{} lines, +{} events, {} correlated",
-                transcript_result.new_lines,
+                transcript_result.new_lines + adapter_result.new_lines,
                 hook_result.new_events,
                 corr_result.updated
             );
@@ -541,12 +1038,120 @@ pub fn watch(cli: &Cli) -> Result<()> {
     Ok(())
 }
 
-/// Abbreviate a file path for display
-fn abbreviate_path(path: &str) -> String {
-    let home = std::env::var("HOME").unwrap_or_default();
-    if !home.is_empty() && path.starts_with(&home) {
-        format!("~{}", &path[home.len()..])
-    } else {
-        path.to_string()
+pub fn stdin(cli: &Cli, session_id: &str) -> Result<()> {
+    let indexer_db = open_indexer(cli)?;
+    let streams_dir = transcript_indexer::default_streams_dir();
+
+    if cli.effective_format() == OutputFormat::Human {
+        eprintln!(
+            "{}",
+            colors::header(&format!("Indexing stdin as {} (Ctrl-D to finish)...", session_id))
+        );
     }
+
+    let stdin = std::io::stdin();
+    let result = transcript_indexer::index_stream(
+        indexer_db.connection(),
+        stdin.lock(),
+        session_id,
+        &streams_dir,
+        |_| {},
+    )?;
+    let file = result.file_path.to_string_lossy();
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            eprintln!(
+                "  {} +{} lines ({} skipped) in {}",
+                colors::success("Done:"),
+                result.lines_indexed,
+                result.lines_skipped,
+                abbreviate_path(&file)
+            );
+            warn_recovered_lines(result.lines_recovered);
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "session_id": session_id,
+                "lines_read": result.lines_read,
+                "lines_indexed": result.lines_indexed,
+                "lines_skipped": result.lines_skipped,
+                "lines_recovered": result.lines_recovered,
+                "file_path": file,
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            println!("+{} lines", result.lines_indexed);
+        }
+    }
+
+    Ok(())
+}
+
+/// Index the logs of the adapters in the config file, reporting them when
+/// `verbose` (and there are any)
+fn index_adapter_logs(conn: &rusqlite::Connection, verbose: bool) -> Result<transcript_indexer::UpdateResult> {
+    let adapters = transcript_indexer::configured_adapters();
+    let result = transcript_indexer::index_adapters(conn, &adapters, |adapter, file, lines| {
+        if verbose {
+            eprintln!("    [{}] {} (+{} lines)", adapter, abbreviate_path(file), lines);
+        }
+    })?;
+    if verbose && !adapters.is_empty() {
+        eprintln!(
+            "    {} {} adapter logs checked, {} updated, +{} lines",
+            colors::success("Done:"),
+            result.files_checked,
+            result.files_updated,
+            result.new_lines
+        );
+        warn_large_payloads(result.large_payloads);
+        warn_recovered_lines(result.recovered_lines);
+    }
+    Ok(result)
+}
+
+/// Point at `stats payloads` when indexing met unusually large tool payloads
+fn warn_large_payloads(count: usize) {
+    if count > 0 {
+        eprintln!(
+            "    {}",
+            colors::warning(&format!(
+                "{} tool inputs/outputs over {} (see: transcript stats payloads)",
+                count,
+                colors::format_size(transcript_indexer::LARGE_PAYLOAD_BYTES as u64)
+            ))
+        );
+    }
+}
+
+/// Note lines that were read despite bad bytes, so a garbled file gets looked at
+fn warn_recovered_lines(count: usize) {
+    if count > 0 {
+        eprintln!(
+            "    {}",
+            colors::warning(&format!(
+                "{} lines had invalid UTF-8 or a byte-order mark; bad bytes were read as \u{fffd}",
+                count
+            ))
+        );
+    }
+}
+
+/// Open the index for writing: the `--db-path` or `--profile` database, or the default one
+pub fn open_indexer(cli: &Cli) -> Result<IndexerDb> {
+    Ok(match &cli.db_path {
+        Some(path) => IndexerDb::open_or_create(path)?,
+        None => IndexerDb::open_or_create_default()?,
+    })
+}
+
+/// Abbreviate a file path for display
+pub fn abbreviate_path(path: &str) -> String {
+    transcript_core::abbreviate_home(path)
 }
@@ -3,29 +3,14 @@
 use anyhow::{bail, Result};
 use transcript_db::TranscriptDb;
 
+use super::resolve_session_id;
 use crate::cli::{Cli, OutputFormat};
 use crate::output::{colors, human};
 
 pub fn run(cli: &Cli, db: &TranscriptDb, session: &str) -> Result<()> {
-    // Resolve session
-    let session_info = db.resolve_session(session)?;
-    let session_info = match session_info {
-        Some(info) => info,
-        None => {
-            let matches = db.find_sessions(session)?;
-            if matches.is_empty() {
-                bail!("Session not found: {}", session);
-            } else if matches.len() == 1 {
-                matches.into_iter().next().unwrap()
-            } else {
-                eprintln!("Multiple sessions match '{}':", session);
-                for m in &matches {
-                    let name = m.slug.as_deref().unwrap_or(&m.session_id);
-                    eprintln!("  - {}", name);
-                }
-                bail!("Please specify a more specific session name");
-            }
-        }
+    let session_id = resolve_session_id(db, session)?;
+    let Some(session_info) = db.get_session(&session_id)? else {
+        bail!("Session not found: {}", session);
     };
 
     match cli.effective_format() {
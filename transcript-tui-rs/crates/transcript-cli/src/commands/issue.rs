@@ -0,0 +1,369 @@
+//! Issue command - draft a bug report from a session
+//!
+//! The first prompt becomes the problem statement, tool calls the steps
+//! taken, failed tool results the error excerpts, and the session's init line
+//! (or first line carrying the same fields) the environment.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use serde_json::json;
+use transcript_core::{ContentBlock, LineType, MessageContent, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use super::export::markdown::{clip, fence, plain_text};
+use super::export::trace::{is_prompt, result_text};
+use super::turn::split_turns;
+use crate::cli::{Cli, FormatArg};
+use crate::commands::resolve_session_id;
+use crate::output::human::get_tool_preview;
+
+/// Characters kept from each error
+const MAX_ERROR_CHARS: usize = 800;
+
+/// Characters kept in the title
+const MAX_TITLE_CHARS: usize = 72;
+
+/// A tool call, summarized
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub tool: String,
+    pub preview: String,
+    pub failed: bool,
+}
+
+/// Where the session ran
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Environment {
+    pub version: Option<String>,
+    pub cwd: Option<String>,
+    pub git_branch: Option<String>,
+    pub models: Vec<String>,
+}
+
+/// Everything that goes into the issue
+#[derive(Debug, Clone)]
+pub struct IssueDraft {
+    pub session_id: String,
+    pub name: String,
+    pub turns: (usize, usize),
+    pub started: String,
+    pub ended: String,
+    pub problem: String,
+    pub steps: Vec<Step>,
+    /// Tool name and error text
+    pub errors: Vec<(String, String)>,
+    pub environment: Environment,
+}
+
+pub fn run(cli: &Cli, db: &TranscriptDb, session: &str, turns: Option<&str>) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+    let range = turns.map(parse_turn_range).transpose()?.unwrap_or((None, None));
+
+    let Some(draft) = build_draft(&session_id, &lines, range) else {
+        let numbers: Vec<String> = split_turns(&lines).iter().map(|(n, _)| n.to_string()).collect();
+        bail!(
+            "No turns in range in {} (turns: {})",
+            session,
+            if numbers.is_empty() { "none".to_string() } else { numbers.join(", ") }
+        );
+    };
+
+    // The template is meant to be piped into a tracker, so it stays Markdown
+    // unless JSON is asked for explicitly
+    if cli.json || cli.format == Some(FormatArg::Json) {
+        let value = draft_json(&draft);
+        if cli.pretty {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        } else {
+            println!("{}", serde_json::to_string(&value)?);
+        }
+    } else {
+        print!("{}", render(&draft));
+    }
+
+    Ok(())
+}
+
+/// Parse `a..b`, `a..`, `..b` or `n` into inclusive turn bounds
+pub fn parse_turn_range(spec: &str) -> Result<(Option<usize>, Option<usize>)> {
+    let bound = |s: &str| -> Result<Option<usize>> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(None);
+        }
+        match s.parse() {
+            Ok(n) => Ok(Some(n)),
+            Err(_) => bail!("Invalid turn range '{}' (expected a..b, a.., ..b or n)", spec),
+        }
+    };
+    let (from, to) = match spec.split_once("..") {
+        Some((from, to)) => (bound(from)?, bound(to)?),
+        None => {
+            let n = bound(spec)?;
+            (n, n)
+        }
+    };
+    if let (Some(a), Some(b)) = (from, to) {
+        if a > b {
+            bail!("Invalid turn range '{}': {} is after {}", spec, a, b);
+        }
+    }
+    Ok((from, to))
+}
+
+/// Collect the issue contents from the turns in `range`
+pub fn build_draft(
+    session_id: &str,
+    lines: &[TranscriptLine],
+    range: (Option<usize>, Option<usize>),
+) -> Option<IssueDraft> {
+    let selected: Vec<(usize, &[TranscriptLine])> = split_turns(lines)
+        .into_iter()
+        .filter(|(n, _)| range.0.is_none_or(|from| *n >= from) && range.1.is_none_or(|to| *n <= to))
+        .collect();
+    let (first_turn, last_turn) = (selected.first()?.0, selected.last()?.0);
+    let turn_lines: Vec<&TranscriptLine> = selected.iter().flat_map(|(_, t)| t.iter()).collect();
+
+    // Tool results arrive on later user lines: id -> (text, is_error)
+    let mut results: HashMap<String, (String, bool)> = HashMap::new();
+    for line in &turn_lines {
+        let Some(MessageContent::Blocks(blocks)) = line.message().map(|m| m.content) else {
+            continue;
+        };
+        for block in blocks {
+            if let ContentBlock::ToolResult { tool_use_id, content, is_error } = block {
+                results.insert(tool_use_id, (result_text(&content), is_error));
+            }
+        }
+    }
+
+    let problem = turn_lines
+        .iter()
+        .find(|l| is_prompt(l))
+        .and_then(|l| l.message())
+        .map(|m| plain_text(m.content.as_text().trim()))
+        .unwrap_or_default();
+
+    let mut steps = Vec::new();
+    let mut errors = Vec::new();
+    let mut models: Vec<String> = Vec::new();
+    for line in &turn_lines {
+        if line.line_type != LineType::Assistant {
+            continue;
+        }
+        if let Some(model) = &line.model {
+            if !models.contains(model) {
+                models.push(model.clone());
+            }
+        }
+        let Some(msg) = line.message() else {
+            continue;
+        };
+        for (id, name, input) in msg.content.tool_uses() {
+            let result = results.get(id);
+            let failed = result.is_some_and(|(_, is_error)| *is_error);
+            if let Some((text, true)) = result {
+                errors.push((name.to_string(), plain_text(text.trim())));
+            }
+            steps.push(Step {
+                tool: name.to_string(),
+                preview: get_tool_preview(name, input),
+                failed,
+            });
+        }
+    }
+
+    let mut environment = environment(lines);
+    environment.models = models;
+
+    Some(IssueDraft {
+        session_id: session_id.to_string(),
+        name: lines
+            .iter()
+            .find_map(|l| l.session_name.clone().or_else(|| l.slug.clone()))
+            .unwrap_or_else(|| session_id.to_string()),
+        turns: (first_turn, last_turn),
+        started: turn_lines.first().map(|l| l.timestamp.clone()).unwrap_or_default(),
+        ended: turn_lines.last().map(|l| l.timestamp.clone()).unwrap_or_default(),
+        problem,
+        steps,
+        errors,
+        environment,
+    })
+}
+
+/// Version, directory and branch from the init line, else the first line with them
+fn environment(lines: &[TranscriptLine]) -> Environment {
+    let parsed: Vec<serde_json::Value> = lines
+        .iter()
+        .filter_map(|l| serde_json::from_str(&l.raw).ok())
+        .collect();
+    let field = |key: &str| -> Option<String> {
+        let from = |v: &serde_json::Value| v.get(key).and_then(|s| s.as_str()).filter(|s| !s.is_empty()).map(String::from);
+        parsed
+            .iter()
+            .find(|v| v.get("subtype").and_then(|s| s.as_str()) == Some("init"))
+            .and_then(from)
+            .or_else(|| parsed.iter().find_map(from))
+    };
+    Environment {
+        version: field("version"),
+        cwd: field("cwd"),
+        git_branch: field("gitBranch"),
+        models: Vec::new(),
+    }
+}
+
+/// The issue as Markdown
+pub fn render(draft: &IssueDraft) -> String {
+    let mut out = String::new();
+
+    let title = draft.problem.lines().next().unwrap_or("").trim();
+    let title = if title.is_empty() { draft.name.as_str() } else { title };
+    let mut short: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    if title.chars().count() > MAX_TITLE_CHARS {
+        short.push('…');
+    }
+    let _ = writeln!(out, "# {}\n", short);
+
+    out.push_str("## Problem\n\n");
+    if draft.problem.is_empty() {
+        out.push_str("<!-- No user prompt in these turns -->\n");
+    }
+    for l in draft.problem.lines() {
+        let _ = writeln!(out, "> {}", l);
+    }
+    out.push('\n');
+
+    out.push_str("## Steps taken\n\n");
+    if draft.steps.is_empty() {
+        out.push_str("No tool calls.\n");
+    }
+    for (i, step) in draft.steps.iter().enumerate() {
+        let _ = write!(out, "{}. **{}**", i + 1, step.tool);
+        if !step.preview.is_empty() {
+            let _ = write!(out, " `{}`", step.preview.replace('`', "'"));
+        }
+        if step.failed {
+            out.push_str(" — failed");
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+
+    if !draft.errors.is_empty() {
+        out.push_str("## Errors\n\n");
+        for (tool, text) in &draft.errors {
+            let _ = write!(out, "**{}:**\n\n{}\n\n", tool, fence(&clip(text, MAX_ERROR_CHARS), ""));
+        }
+    }
+
+    out.push_str("## Expected behavior\n\n<!-- What should have happened? -->\n\n");
+
+    out.push_str("## Environment\n\n");
+    let env = &draft.environment;
+    if let Some(version) = &env.version {
+        let _ = writeln!(out, "- **Claude Code:** {}", version);
+    }
+    if !env.models.is_empty() {
+        let _ = writeln!(out, "- **Model:** {}", env.models.join(", "));
+    }
+    if let Some(cwd) = &env.cwd {
+        let _ = writeln!(out, "- **Directory:** `{}`", cwd);
+    }
+    if let Some(branch) = &env.git_branch {
+        let _ = writeln!(out, "- **Branch:** `{}`", branch);
+    }
+    let turns = if draft.turns.0 == draft.turns.1 {
+        format!("turn {}", draft.turns.0)
+    } else {
+        format!("turns {}–{}", draft.turns.0, draft.turns.1)
+    };
+    let _ = writeln!(out, "- **Session:** {} (`{}`), {}", draft.name, draft.session_id, turns);
+    let _ = writeln!(out, "- **Time:** {} → {}", draft.started, draft.ended);
+
+    out
+}
+
+fn draft_json(draft: &IssueDraft) -> serde_json::Value {
+    json!({
+        "sessionId": draft.session_id,
+        "name": draft.name,
+        "turns": [draft.turns.0, draft.turns.1],
+        "started": draft.started,
+        "ended": draft.ended,
+        "problem": draft.problem,
+        "steps": draft.steps.iter().map(|s| json!({
+            "tool": s.tool,
+            "preview": s.preview,
+            "failed": s.failed,
+        })).collect::<Vec<_>>(),
+        "errors": draft.errors.iter().map(|(tool, text)| json!({
+            "tool": tool,
+            "text": text,
+        })).collect::<Vec<_>>(),
+        "environment": {
+            "version": draft.environment.version,
+            "cwd": draft.environment.cwd,
+            "gitBranch": draft.environment.git_branch,
+            "models": draft.environment.models,
+        },
+        "markdown": render(draft),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_type: LineType, raw: serde_json::Value) -> TranscriptLine {
+        TranscriptLine {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            slug: Some("happy-dog".to_string()),
+            model: Some("claude-sonnet-4".to_string()),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_parse_turn_range() {
+        assert_eq!(parse_turn_range("2..4").unwrap(), (Some(2), Some(4)));
+        assert_eq!(parse_turn_range("3").unwrap(), (Some(3), Some(3)));
+        assert_eq!(parse_turn_range("..2").unwrap(), (None, Some(2)));
+        assert_eq!(parse_turn_range("5..").unwrap(), (Some(5), None));
+        assert!(parse_turn_range("4..2").is_err());
+        assert!(parse_turn_range("x").is_err());
+    }
+
+    #[test]
+    fn test_draft_from_failed_tool() {
+        let lines = vec![
+            line(LineType::System, json!({"type": "system", "subtype": "init", "version": "2.0.1", "cwd": "/src/app", "gitBranch": "main"})),
+            line(LineType::User, json!({"message": {"role": "user", "content": "tests fail on CI"}})),
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "cargo test"}}
+            ]}})),
+            line(LineType::User, json!({"message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "error[E0425]: cannot find value", "is_error": true}
+            ]}})),
+            line(LineType::User, json!({"message": {"role": "user", "content": "thanks"}})),
+        ];
+
+        // The init line comes before any prompt, so it is turn 1 on its own
+        let draft = build_draft("s1", &lines, (None, Some(2))).unwrap();
+        assert_eq!(draft.turns, (1, 2));
+        assert_eq!(draft.problem, "tests fail on CI");
+        assert_eq!(draft.steps, vec![Step { tool: "Bash".into(), preview: "cargo test".into(), failed: true }]);
+        assert_eq!(draft.environment.git_branch.as_deref(), Some("main"));
+
+        let doc = render(&draft);
+        assert!(doc.starts_with("# tests fail on CI\n"));
+        assert!(doc.contains("1. **Bash** `cargo test` — failed"));
+        assert!(doc.contains("error[E0425]"));
+        assert!(doc.contains("- **Claude Code:** 2.0.1"));
+        assert!(build_draft("s1", &lines, (Some(9), None)).is_none());
+    }
+}
@@ -1,18 +1,32 @@
 //! List command - show recent sessions
 
 use anyhow::Result;
-use transcript_db::TranscriptDb;
+use transcript_core::ReviewStatus;
+use transcript_db::{SessionMetadata, TranscriptDb};
 
 use crate::cli::{Cli, OutputFormat};
+use crate::commands::resolve_project;
+use crate::commands::review::colored_status;
+use crate::output::colors::{self, format_count};
 use crate::output::{human, json, minimal};
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     cli: &Cli,
     db: &TranscriptDb,
     limit: i64,
     days: Option<i64>,
     search: Option<&str>,
+    user: Option<&str>,
+    team: bool,
+    tag: Option<&str>,
+    review: Option<ReviewStatus>,
+    project: Option<&str>,
 ) -> Result<()> {
+    if team {
+        return run_team(cli, db, limit, days, search);
+    }
+
     let sessions = if let Some(pattern) = search {
         // Search for sessions matching pattern
         db.find_sessions(pattern)?
@@ -21,8 +35,63 @@ pub fn run(
         db.get_sessions(days)?
     };
 
+    // Restrict to one user's sessions (multi-home indexing)
+    let sessions = match user {
+        Some(user) => {
+            let files: std::collections::HashSet<String> =
+                db.get_session_files_for_user(user)?.into_iter().collect();
+            sessions
+                .into_iter()
+                .filter(|s| files.contains(&s.file_path))
+                .collect()
+        }
+        None => sessions,
+    };
+
+    // Restrict to tagged sessions
+    let sessions = match tag {
+        Some(tag) => {
+            let ids: std::collections::HashSet<String> =
+                db.get_session_ids_with_tag(tag)?.into_iter().collect();
+            sessions
+                .into_iter()
+                .filter(|s| ids.contains(&s.session_id))
+                .collect()
+        }
+        None => sessions,
+    };
+
+    // Restrict to one project's sessions
+    let sessions = match project {
+        Some(project) => {
+            let path = resolve_project(Some(db), project)?;
+            let ids: std::collections::HashSet<String> =
+                db.get_session_ids_in_project(&path)?.into_iter().collect();
+            sessions
+                .into_iter()
+                .filter(|s| ids.contains(&s.session_id))
+                .collect()
+        }
+        None => sessions,
+    };
+
+    // Restrict to one review status (sessions never reviewed are unreviewed)
+    let reviews = db.get_review_statuses()?;
+    let status_of = |id: &str| reviews.get(id).copied().unwrap_or_default();
+    let sessions: Vec<_> = match review {
+        Some(review) => sessions
+            .into_iter()
+            .filter(|s| status_of(&s.session_id) == review)
+            .collect(),
+        None => sessions,
+    };
+
     // Limit results
     let sessions: Vec<_> = sessions.into_iter().take(limit as usize).collect();
+    let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+    let metadata = db.get_session_metadata(&ids)?;
+    let metadata_of = |id: &str| metadata.get(id).copied().unwrap_or_default();
+    let projects = db.get_session_projects(&ids)?;
 
     match cli.effective_format() {
         OutputFormat::Human => {
@@ -35,7 +104,15 @@ pub fn run(
                 );
                 println!();
                 for session in &sessions {
-                    println!("{}", human::format_session(session));
+                    let line = format!(
+                        "{}  {}",
+                        human::format_session(session),
+                        format_metadata(&metadata_of(&session.session_id))
+                    );
+                    match status_of(&session.session_id) {
+                        ReviewStatus::Unreviewed => println!("{}", line),
+                        status => println!("{}  {}", line, colored_status(status)),
+                    }
                 }
             }
         }
@@ -43,7 +120,16 @@ pub fn run(
             // Output as JSON array to match TS CLI format
             let json_array: Vec<serde_json::Value> = sessions
                 .iter()
-                .map(|s| json::session_to_json(s))
+                .map(|s| {
+                    let mut value = json::session_to_json(s);
+                    value["review"] = serde_json::json!(status_of(&s.session_id).name());
+                    let metadata = metadata_of(&s.session_id);
+                    value["hookEvents"] = serde_json::json!(metadata.hook_events);
+                    value["turns"] = serde_json::json!(metadata.turns);
+                    value["correlation"] = serde_json::json!(metadata.correlation());
+                    value["project"] = serde_json::json!(projects.get(&s.session_id));
+                    value
+                })
                 .collect();
 
             if cli.pretty {
@@ -61,3 +147,92 @@ pub fn run(
 
     Ok(())
 }
+
+/// `12 turns, 340 hooks`, flagging sessions whose hook events haven't been
+/// correlated with their lines yet
+fn format_metadata(metadata: &SessionMetadata) -> String {
+    match metadata.correlation() {
+        "no-hooks" => "no hooks".to_string(),
+        "pending" => format!(
+            "{} hooks {}",
+            format_count(metadata.hook_events),
+            colors::warning("(not correlated)")
+        ),
+        _ => format!(
+            "{} turns, {} hooks",
+            format_count(metadata.turns),
+            format_count(metadata.hook_events)
+        ),
+    }
+}
+
+/// List sessions from the local database and attached team snapshots
+fn run_team(
+    cli: &Cli,
+    db: &TranscriptDb,
+    limit: i64,
+    days: Option<i64>,
+    search: Option<&str>,
+) -> Result<()> {
+    let pattern = search.map(|p| p.to_lowercase());
+    let sessions: Vec<_> = db
+        .get_sessions_federated(days)?
+        .into_iter()
+        .filter(|s| match &pattern {
+            Some(p) => {
+                s.session.session_id.to_lowercase().contains(p)
+                    || s.session.slug.as_deref().is_some_and(|slug| slug.to_lowercase().contains(p))
+            }
+            None => true,
+        })
+        .take(limit as usize)
+        .collect();
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if sessions.is_empty() {
+                println!("No sessions found");
+            } else {
+                println!(
+                    "{}",
+                    crate::output::colors::header(&format!(
+                        "Sessions ({}) across {}",
+                        sessions.len(),
+                        db.origins().join(", ")
+                    ))
+                );
+                println!();
+                for s in &sessions {
+                    println!(
+                        "{} {}",
+                        crate::output::colors::label(&format!("[{}]", s.origin)),
+                        human::format_session(&s.session)
+                    );
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json_array: Vec<serde_json::Value> = sessions
+                .iter()
+                .map(|s| {
+                    let mut value = json::session_to_json(&s.session);
+                    value["origin"] = serde_json::json!(s.origin);
+                    value
+                })
+                .collect();
+
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&json_array).unwrap_or_default());
+            } else {
+                println!("{}", serde_json::to_string(&json_array).unwrap_or_default());
+            }
+        }
+        OutputFormat::Minimal => {
+            for s in &sessions {
+                println!("{}\t{}", s.origin, minimal::format_session(&s.session));
+            }
+        }
+    }
+
+    Ok(())
+}
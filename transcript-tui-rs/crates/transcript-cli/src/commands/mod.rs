@@ -1,9 +1,197 @@
 //! CLI command implementations
 
+pub mod annotate;
+pub mod archive;
+pub mod blame;
+pub mod bookmarks;
+pub mod changelog;
+pub mod context_check;
+pub mod cost;
+pub mod db;
+pub mod digest;
 pub mod doctor;
+pub mod eval;
+pub mod export;
+pub mod file_history;
+pub mod grep;
+pub mod hooks;
+pub mod import;
 pub mod index;
 pub mod info;
+pub mod issue;
 pub mod list;
+pub mod projects;
+pub mod prompt;
+pub mod prune_turns;
+pub mod query;
 pub mod recall;
+pub mod review;
 pub mod search;
+pub mod serve;
+pub mod share;
+pub mod stats;
+pub mod sync;
+pub mod tag;
+pub mod thinking;
+pub mod tmux;
+pub mod tools;
+pub mod trash;
+pub mod turn;
+pub mod tui;
+pub mod usage;
 pub mod view;
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use chrono::Duration;
+use transcript_core::{path_key, SessionInfo};
+use transcript_db::TranscriptDb;
+
+/// Parse a duration like `30m`, `12h`, `7d` or `2w`
+pub fn parse_duration(spec: &str) -> Result<Duration> {
+    Ok(transcript_core::parse_duration(spec)?)
+}
+
+/// Directory for caches that can be deleted at any time
+pub fn cache_dir() -> PathBuf {
+    transcript_core::sdk_dir().join("cache")
+}
+
+/// Attach every snapshot in the team directory for federated queries
+pub fn attach_team(mut db: TranscriptDb) -> Result<TranscriptDb> {
+    let dir = transcript_db::default_team_dir();
+    if db.attach_dir(&dir)?.is_empty() {
+        bail!(
+            "No team snapshots in {} (run: transcript sync pull --federate)",
+            dir.display()
+        );
+    }
+    Ok(db)
+}
+
+/// Attach the archive database (see `transcript archive`) for federated queries
+///
+/// Without an archive the query runs on the index alone, with a note.
+pub fn attach_archive(mut db: TranscriptDb) -> Result<TranscriptDb> {
+    if !db.attach_archive()? {
+        eprintln!(
+            "No archive at {} (run: transcript archive --older-than 90d)",
+            transcript_db::archive_db_path(db.path()).display()
+        );
+    }
+    Ok(db)
+}
+
+/// Resolve a session name, ID, or partial match to a session ID
+///
+/// A name matching nothing is looked up fuzzily, so a typo still finds the
+/// session; when several match about as well, a terminal is asked which.
+pub fn resolve_session_id(db: &TranscriptDb, session: &str) -> Result<String> {
+    if let Some(info) = db.resolve_session(session)? {
+        return Ok(info.session_id);
+    }
+
+    let mut matches = db.find_sessions(session)?;
+    if matches.is_empty() {
+        matches = db.closest_sessions(session)?;
+        if let [only] = matches.as_slice() {
+            eprintln!(
+                "Using {} (closest match for '{}')",
+                only.slug.as_deref().unwrap_or(&only.session_id),
+                session
+            );
+        }
+    }
+    match matches.len() {
+        0 => bail!("Session not found: {}", session),
+        1 => Ok(matches.remove(0).session_id),
+        _ => choose_session(session, &matches),
+    }
+}
+
+/// Ask which of several matching sessions was meant, or list them and fail
+/// when there's no terminal to ask on
+fn choose_session(session: &str, matches: &[SessionInfo]) -> Result<String> {
+    eprintln!("Multiple sessions match '{}':", session);
+    let interactive = atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stderr);
+    for (i, m) in matches.iter().enumerate() {
+        let name = m.slug.as_deref().unwrap_or(&m.session_id);
+        let last = m.last_timestamp.as_deref().unwrap_or_default();
+        if interactive {
+            eprintln!("  {:>2}. {:<30} {}", i + 1, name, last.get(..16).unwrap_or(last).replace('T', " "));
+        } else {
+            eprintln!("  - {}", name);
+        }
+    }
+    if !interactive {
+        bail!("Please specify a more specific session name");
+    }
+
+    eprint!("Which one? [1-{}] ", matches.len());
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    match answer.trim().parse::<usize>() {
+        Ok(n) if (1..=matches.len()).contains(&n) => Ok(matches[n - 1].session_id.clone()),
+        _ => bail!("No session chosen"),
+    }
+}
+
+/// Directory of a `--project`: a project's name (see `transcript projects`),
+/// else a path, made absolute and taken up to its repository root when it
+/// exists here (paths from another machine are matched as given)
+pub fn resolve_project(db: Option<&TranscriptDb>, project: &str) -> Result<String> {
+    let is_path = project.contains(['/', '\\']) || project.starts_with('.') || project.starts_with('~');
+    if let (false, Some(db)) = (is_path, db) {
+        let mut matches: Vec<String> = db
+            .get_projects()?
+            .into_iter()
+            .filter(|p| p.name.eq_ignore_ascii_case(project))
+            .map(|p| p.path)
+            .collect();
+        match matches.len() {
+            0 => {}
+            1 => return Ok(matches.remove(0)),
+            _ => {
+                eprintln!("Multiple projects are named '{}':", project);
+                for path in &matches {
+                    eprintln!("  - {}", path);
+                }
+                bail!("Please specify the project's path");
+            }
+        }
+    }
+    Ok(std::fs::canonicalize(project)
+        .map(|p| path_key(&transcript_indexer::find_repo_root(&p).unwrap_or(p)))
+        .unwrap_or_else(|_| project.trim_end_matches(['/', '\\']).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_mistyped_session() {
+        let dir = std::env::temp_dir().join(format!("transcript-resolve-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("transcripts.db");
+        let mut conn = rusqlite::Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut conn).unwrap();
+        for (session_id, slug) in [("s1", "loyal-whippet"), ("s2", "happy-dog")] {
+            conn.execute(
+                "INSERT INTO sessions (file_path, session_id, slug, line_count, indexed_at) VALUES (?1, ?1, ?2, 1, '')",
+                [session_id, slug],
+            )
+            .unwrap();
+        }
+        drop(conn);
+        let db = TranscriptDb::open(&path).unwrap();
+
+        assert_eq!(resolve_session_id(&db, "loyl-whipet").unwrap(), "s1");
+        let err = resolve_session_id(&db, "zebra").unwrap_err();
+        assert_eq!(err.to_string(), "Session not found: zebra");
+
+        drop(db);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
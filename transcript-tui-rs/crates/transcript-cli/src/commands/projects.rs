@@ -0,0 +1,63 @@
+//! Projects command - sessions grouped by the repository they ran in
+
+use anyhow::Result;
+use serde_json::json;
+use transcript_db::TranscriptDb;
+
+use crate::cli::{Cli, OutputFormat};
+use crate::output::colors::{self, format_count};
+
+pub fn run(cli: &Cli, db: &TranscriptDb, limit: usize) -> Result<()> {
+    let projects: Vec<_> = db.get_projects()?.into_iter().take(limit).collect();
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if projects.is_empty() {
+                println!("No projects found");
+                return Ok(());
+            }
+            println!("{}", colors::header(&format!("Projects ({})", projects.len())));
+            println!();
+            let width = projects.iter().map(|p| p.name.chars().count()).max().unwrap_or(0);
+            for project in &projects {
+                let last = project.last_timestamp.as_deref().unwrap_or("unknown");
+                println!(
+                    "{}  {} sessions, {} lines  {}  {}",
+                    colors::colored_session(&format!("{:<width$}", project.name)),
+                    format_count(project.sessions),
+                    format_count(project.lines),
+                    colors::colored_time(&last.get(..16).unwrap_or(last).replace('T', " ")),
+                    colors::label(&project.path)
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let value: Vec<_> = projects
+                .iter()
+                .map(|p| {
+                    json!({
+                        "name": p.name,
+                        "path": p.path,
+                        "remote": p.remote,
+                        "sessions": p.sessions,
+                        "lines": p.lines,
+                        "firstTimestamp": p.first_timestamp,
+                        "lastTimestamp": p.last_timestamp,
+                    })
+                })
+                .collect();
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                println!("{}", serde_json::to_string(&value)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for project in &projects {
+                println!("{}\t{}", project.name, project.path);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+//! Prompt segment command - the active session in a shell prompt
+//!
+//! Prints `name t12 42%` (latest session of the project, its turn and
+//! context usage) or nothing, for Starship and other prompts:
+//!
+//! ```toml
+//! [custom.claude]
+//! command = "transcript prompt-segment"
+//! when = true
+//! format = "[$output]($style) "
+//! ```
+//!
+//! Prompts run it before every command, so the segment is cached per
+//! database and directory in `~/.claude-code-sdk/cache`. A cached segment is
+//! shown without opening the database while the database is unchanged, or
+//! while it is younger than `--max-age` (indexing writes on every line).
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use transcript_db::{default_db_path, TranscriptDb};
+
+use crate::cli::Cli;
+use crate::commands::cache_dir;
+
+pub fn segment(cli: &Cli, cwd: Option<&Path>, context_size: Option<u64>, max_age: u64) -> Result<()> {
+    let cwd = match cwd {
+        Some(cwd) => cwd.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    let db_path = cli.db_path.clone().unwrap_or_else(default_db_path);
+    let cache = cache_path(&db_path, &cwd, context_size);
+    let fingerprint = db_fingerprint(&db_path);
+
+    let text = match cached(&cache, &fingerprint, Duration::from_secs(max_age)) {
+        Some(text) => text,
+        None => {
+            // A prompt shows nothing rather than an error
+            let text = TranscriptDb::open(&db_path)
+                .ok()
+                .and_then(|db| segment_of(&db, &cwd, context_size))
+                .unwrap_or_default();
+            store(&cache, &fingerprint, &text);
+            text
+        }
+    };
+    if !text.is_empty() {
+        println!("{}", text);
+    }
+    Ok(())
+}
+
+/// The segment for the latest session in `cwd`, if there is one
+fn segment_of(db: &TranscriptDb, cwd: &Path, context_size: Option<u64>) -> Option<String> {
+    let session = db.latest_session_in(&cwd.to_string_lossy()).ok()??;
+    let name = session
+        .slug
+        .clone()
+        .unwrap_or_else(|| session.session_id.chars().take(8).collect());
+    let turn = db
+        .context_usage_by_turn(&session.session_id, context_size)
+        .ok()
+        .and_then(|turns| turns.last().map(|t| (t.turn, t.percent)));
+    Some(segment_text(&name, turn))
+}
+
+/// `name t12 42%`, or just the name before any usage was reported
+fn segment_text(name: &str, turn: Option<(usize, f64)>) -> String {
+    match turn {
+        Some((turn, percent)) => format!("{} t{} {:.0}%", name, turn, percent),
+        None => name.to_string(),
+    }
+}
+
+fn cache_path(db_path: &Path, cwd: &Path, context_size: Option<u64>) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    (db_path, cwd, context_size).hash(&mut hasher);
+    cache_dir().join(format!("prompt-{:016x}", hasher.finish()))
+}
+
+/// Modification times of the database and its write-ahead log, which
+/// change whenever anything is indexed
+fn db_fingerprint(db_path: &Path) -> String {
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos())
+    };
+    let mut wal = db_path.as_os_str().to_os_string();
+    wal.push("-wal");
+    format!("{}:{}", modified(db_path), modified(Path::new(&wal)))
+}
+
+/// The cached segment, if the database is unchanged since or it is recent
+fn cached(cache: &Path, fingerprint: &str, max_age: Duration) -> Option<String> {
+    let contents = std::fs::read_to_string(cache).ok()?;
+    let (stored, text) = contents.split_once('\n')?;
+    let age = std::fs::metadata(cache)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok());
+    let fresh = stored == fingerprint || age.is_some_and(|age| age < max_age);
+    fresh.then(|| text.to_string())
+}
+
+/// Cache a segment (best effort; written aside and renamed, so a prompt
+/// never reads half a file)
+fn store(cache: &Path, fingerprint: &str, text: &str) {
+    let Some(dir) = cache.parent() else {
+        return;
+    };
+    let partial = cache.with_extension(format!("{}.partial", std::process::id()));
+    let _ = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&partial, format!("{}\n{}", fingerprint, text)))
+        .and_then(|_| std::fs::rename(&partial, cache));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_cache() {
+        assert_eq!(segment_text("tender-spider", Some((12, 41.6))), "tender-spider t12 42%");
+        assert_eq!(segment_text("tender-spider", None), "tender-spider");
+
+        let dir = std::env::temp_dir().join(format!("transcript-prompt-{}", std::process::id()));
+        let cache = dir.join("prompt-test");
+        assert_eq!(cached(&cache, "1:0", Duration::ZERO), None);
+
+        store(&cache, "1:0", "tender-spider t12 42%");
+        // Same database: reused however old
+        assert_eq!(cached(&cache, "1:0", Duration::ZERO).as_deref(), Some("tender-spider t12 42%"));
+        // Changed database: reused only while recent
+        assert_eq!(cached(&cache, "2:0", Duration::ZERO), None);
+        assert!(cached(&cache, "2:0", Duration::from_secs(60)).is_some());
+
+        // No session is cached too
+        store(&cache, "3:0", "");
+        assert_eq!(cached(&cache, "3:0", Duration::ZERO).as_deref(), Some(""));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+//! Prune-turns command - tick a session's turns to keep, then export them
+//!
+//! The checklist lists each turn with its prompt; dropped turns are left out
+//! of the export. Claude JSONL keeps the thread connected by pointing lines
+//! whose parent was dropped at the nearest kept ancestor (as `export jsonl`
+//! does), so the pruned session can be resumed or imported.
+
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use transcript_core::{ContentBlock, MessageContent, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+use transcript_tui::{run_checklist, ChecklistItem};
+
+use super::export::markdown::{self, plain_text, MarkdownOptions};
+use super::export::{jsonl, trace::is_prompt};
+use super::issue::parse_turn_range;
+use super::turn::split_turns;
+use crate::cli::{Cli, PruneFormat};
+use crate::commands::resolve_session_id;
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session: &str,
+    to: PruneFormat,
+    output: Option<&Path>,
+    keep: Option<&str>,
+    options: &MarkdownOptions,
+) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+    let turns = split_turns(&lines);
+    if turns.is_empty() {
+        bail!("{} has no turns", session);
+    }
+
+    let kept_turns: Vec<bool> = match keep {
+        Some(spec) => kept_by_spec(spec, turns.iter().map(|(n, _)| *n))?,
+        None => {
+            if !atty::is(atty::Stream::Stdin) || !atty::is(atty::Stream::Stderr) {
+                bail!("The turn checklist needs a terminal; choose turns with --keep instead");
+            }
+            let mut items: Vec<ChecklistItem> = turns.iter().map(|(n, turn)| checklist_item(*n, turn)).collect();
+            if !run_checklist(&format!("Turns of {}", session), &mut items)? {
+                eprintln!("Cancelled, nothing exported");
+                return Ok(());
+            }
+            items.iter().map(|item| item.checked).collect()
+        }
+    };
+
+    let kept: Vec<&TranscriptLine> = turns
+        .iter()
+        .zip(&kept_turns)
+        .filter(|(_, &keep)| keep)
+        .flat_map(|((_, turn), _)| turn.iter())
+        .collect();
+    if kept.is_empty() {
+        let numbers: Vec<String> = turns.iter().map(|(n, _)| n.to_string()).collect();
+        bail!("No turns kept (turns: {})", numbers.join(", "));
+    }
+
+    match to {
+        PruneFormat::ClaudeJsonl => jsonl::export(cli, db, &session_id, &lines, &kept, output),
+        PruneFormat::Markdown => {
+            let kept: Vec<TranscriptLine> = kept.into_iter().cloned().collect();
+            let (document, count) = markdown::render(&session_id, &kept, options, &[]);
+            markdown::write_document(cli, &session_id, &document, count, output)
+        }
+    }
+}
+
+/// Which of the turns numbered `numbers` a `--keep` spec (`1..3,5,8..`) keeps
+fn kept_by_spec(spec: &str, numbers: impl Iterator<Item = usize>) -> Result<Vec<bool>> {
+    let ranges = spec
+        .split(',')
+        .map(parse_turn_range)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(numbers
+        .map(|n| {
+            ranges
+                .iter()
+                .any(|(from, to)| from.is_none_or(|f| n >= f) && to.is_none_or(|t| n <= t))
+        })
+        .collect())
+}
+
+/// Checklist row for a turn: its prompt, size and start time
+fn checklist_item(number: usize, turn: &[TranscriptLine]) -> ChecklistItem {
+    let prompt = turn
+        .iter()
+        .find(|line| is_prompt(line))
+        .and_then(|line| line.message())
+        .map(|message| plain_text(message.content.as_text().trim()))
+        .unwrap_or_else(|| "(no prompt)".to_string());
+    let tools: usize = turn
+        .iter()
+        .filter_map(|line| match line.message().map(|m| m.content) {
+            Some(MessageContent::Blocks(blocks)) => {
+                Some(blocks.iter().filter(|b| matches!(b, ContentBlock::ToolUse { .. })).count())
+            }
+            _ => None,
+        })
+        .sum();
+    let start = turn.first().map(|line| line.timestamp.as_str()).unwrap_or_default();
+
+    // One line of the prompt, short enough to leave room for the details
+    let mut summary = prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+    if summary.chars().count() > 50 {
+        summary = summary.chars().take(49).collect::<String>() + "…";
+    }
+
+    ChecklistItem {
+        label: format!("#{:<4}{:<50}", number, summary),
+        detail: format!(
+            "{} lines, {} tool calls, {}",
+            turn.len(),
+            tools,
+            start.get(..16).unwrap_or(start).replace('T', " ")
+        ),
+        preview: prompt,
+        checked: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kept_by_spec() {
+        assert_eq!(kept_by_spec("1..2,4", 1..=5).unwrap(), [true, true, false, true, false]);
+        assert_eq!(kept_by_spec("4..", 1..=5).unwrap(), [false, false, false, true, true]);
+        assert_eq!(kept_by_spec("9", 1..=3).unwrap(), [false; 3]);
+        assert!(kept_by_spec("two", 1..=3).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! Query command - run read-only SQL against the transcript database
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+use transcript_db::{QueryResult, TranscriptDb};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::output::{colors, csv};
+
+/// Named example queries: (name, description, sql)
+pub const EXAMPLES: &[(&str, &str, &str)] = &[
+    (
+        "tool-usage",
+        "Tool calls by tool name (from hook events)",
+        "SELECT tool_name, COUNT(*) AS calls
+         FROM hook_events
+         WHERE event_type = 'PreToolUse' AND tool_name IS NOT NULL
+         GROUP BY tool_name
+         ORDER BY calls DESC",
+    ),
+    (
+        "sessions-by-day",
+        "Sessions started per day",
+        "SELECT substr(first_timestamp, 1, 10) AS day, COUNT(*) AS sessions
+         FROM sessions
+         GROUP BY day
+         ORDER BY day DESC
+         LIMIT 30",
+    ),
+    (
+        "largest-sessions",
+        "Sessions with the most indexed lines",
+        "SELECT COALESCE(slug, session_id) AS session, line_count, last_timestamp
+         FROM sessions
+         ORDER BY line_count DESC
+         LIMIT 20",
+    ),
+    (
+        "models",
+        "Assistant messages per model",
+        "SELECT model, COUNT(*) AS messages
+         FROM lines
+         WHERE type = 'assistant' AND model IS NOT NULL
+         GROUP BY model
+         ORDER BY messages DESC",
+    ),
+    (
+        "token-usage",
+        "Output tokens per session (top 20)",
+        "SELECT session_id,
+                SUM(json_extract(raw, '$.message.usage.input_tokens')) AS input_tokens,
+                SUM(json_extract(raw, '$.message.usage.output_tokens')) AS output_tokens
+         FROM lines
+         WHERE type = 'assistant'
+         GROUP BY session_id
+         ORDER BY output_tokens DESC
+         LIMIT 20",
+    ),
+];
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    sql: Option<&str>,
+    example: Option<&str>,
+    list_examples: bool,
+    csv: bool,
+) -> Result<()> {
+    if list_examples {
+        print_examples(cli);
+        return Ok(());
+    }
+
+    let sql = match (sql, example) {
+        (Some(sql), None) => sql,
+        (None, Some(name)) => match EXAMPLES.iter().find(|(n, _, _)| *n == name) {
+            Some((_, _, sql)) => *sql,
+            None => {
+                let names: Vec<_> = EXAMPLES.iter().map(|(n, _, _)| *n).collect();
+                bail!("Unknown example '{}'. Available: {}", name, names.join(", "));
+            }
+        },
+        (Some(_), Some(_)) => bail!("Pass either a SQL statement or --example, not both"),
+        (None, None) => bail!("No SQL given. Pass a statement or --example <name> (see --examples)"),
+    };
+
+    let result = db.run_sql(sql)?;
+
+    if csv {
+        print_csv(&result);
+        return Ok(());
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => print_table(&result),
+        OutputFormat::Json => {
+            let rows: Vec<Value> = (0..result.rows.len())
+                .filter_map(|i| result.row_object(i).map(Value::Object))
+                .collect();
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&rows).unwrap_or_default());
+            } else {
+                println!("{}", serde_json::to_string(&rows).unwrap_or_default());
+            }
+        }
+        OutputFormat::Minimal => {
+            for row in &result.rows {
+                let cells: Vec<String> = row.iter().map(cell_text).collect();
+                println!("{}", cells.join("\t"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// List the named example queries
+fn print_examples(cli: &Cli) {
+    match cli.effective_format() {
+        OutputFormat::Json => {
+            let examples: Vec<Value> = EXAMPLES
+                .iter()
+                .map(|(name, description, sql)| {
+                    serde_json::json!({ "name": name, "description": description, "sql": sql })
+                })
+                .collect();
+            println!("{}", serde_json::to_string(&examples).unwrap_or_default());
+        }
+        _ => {
+            println!("{}", colors::header("Example queries"));
+            println!();
+            for (name, description, _) in EXAMPLES {
+                println!("  {:<18} {}", name, colors::label(description));
+            }
+        }
+    }
+}
+
+/// Plain-text rendering of a cell
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Print rows as an aligned table
+fn print_table(result: &QueryResult) {
+    const MAX_WIDTH: usize = 60;
+
+    let cells: Vec<Vec<String>> = result
+        .rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|v| {
+                    let text = cell_text(v).replace('\n', " ");
+                    if text.chars().count() > MAX_WIDTH {
+                        let truncated: String = text.chars().take(MAX_WIDTH - 3).collect();
+                        format!("{}...", truncated)
+                    } else {
+                        text
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = result.columns.iter().map(|c| c.chars().count()).collect();
+    for row in &cells {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<String> = result
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("{:<width$}", c, width = widths[i]))
+        .collect();
+    println!("{}", colors::header(header.join("  ").trim_end()));
+
+    for row in &cells {
+        let line: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("{:<width$}", c, width = widths[i]))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+
+    println!();
+    println!("{}", colors::label(&format!("{} row(s)", result.rows.len())));
+}
+
+/// Print rows as RFC 4180 CSV with a header
+fn print_csv(result: &QueryResult) {
+    csv::print_row(&result.columns);
+    for row in &result.rows {
+        let cells: Vec<String> = row.iter().map(cell_text).collect();
+        csv::print_row(&cells);
+    }
+}
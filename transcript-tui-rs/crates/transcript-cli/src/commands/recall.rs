@@ -1,13 +1,43 @@
 //! Recall command - unified search grouped by session
+//!
+//! Sessions are ranked by full-text relevance, by relevance decayed with age,
+//! or by how many of their turns match. `--context` pulls in the turns around
+//! each match so a result can be read without opening the session.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use anyhow::Result;
 use colored::Colorize;
 use serde::Serialize;
-use transcript_indexer::IndexerDb;
-
-use crate::cli::{Cli, OutputFormat};
+use transcript_core::{LineType, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use super::export::markdown::plain_text;
+use super::export::trace::is_prompt;
+use super::turn::split_turns;
+use crate::cli::{Cli, OutputFormat, RecallRank};
+use crate::commands::index::open_indexer;
 use crate::output::colors;
 
+/// Age at which a match counts half as much under recency ranking
+const RECENCY_HALF_LIFE_DAYS: f64 = 14.0;
+
+/// Characters kept from each context prompt and reply
+const MAX_CONTEXT_CHARS: usize = 500;
+
+/// Options for a recall search
+#[derive(Debug, Clone)]
+pub struct RecallOptions {
+    pub max_sessions: usize,
+    pub max_matches: usize,
+    pub rank: RecallRank,
+    /// Only sessions whose working directory is this path or below it
+    /// (see `commands::resolve_project`)
+    pub project: Option<String>,
+    /// Turns of context before and after each match
+    pub context: usize,
+}
+
 /// A matched line from FTS search
 struct MatchedLine {
     session_id: String,
@@ -17,6 +47,9 @@ struct MatchedLine {
     timestamp: String,
     content: String,
     rank: f64,
+    /// Higher is better; depends on the ranking mode
+    score: f64,
+    turn: Option<usize>,
     source: &'static str, // "transcript" or "hook"
 }
 
@@ -27,8 +60,11 @@ struct RecallSession {
     session_id: String,
     session_name: Option<String>,
     best_rank: f64,
+    score: f64,
     latest_timestamp: String,
     matches: Vec<RecallMatch>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    context: Vec<ContextTurn>,
 }
 
 #[derive(Serialize)]
@@ -39,16 +75,80 @@ struct RecallMatch {
     entry_type: String,
     timestamp: String,
     content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    turn: Option<usize>,
+}
+
+/// A turn shown around a match
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContextTurn {
+    turn: usize,
+    matched: bool,
+    prompt: String,
+    reply: String,
+}
+
+/// A session's lines split into turns, for turn lookups and context
+struct SessionTurns {
+    lines: Vec<TranscriptLine>,
+    /// Turn number and the range of `lines` it covers
+    turns: Vec<(usize, std::ops::Range<usize>)>,
+}
+
+impl SessionTurns {
+    fn load(db: &TranscriptDb, session_id: &str) -> Result<Self> {
+        let lines = db.get_lines(&GetLinesOptions::for_session(session_id))?;
+        let mut turns = Vec::new();
+        let mut start = 0;
+        for (number, turn) in split_turns(&lines) {
+            turns.push((number, start..start + turn.len()));
+            start += turn.len();
+        }
+        Ok(Self { lines, turns })
+    }
+
+    /// Position in `turns` of the turn holding a transcript line
+    fn position_of(&self, line_number: i64) -> Option<usize> {
+        let index = self.lines.iter().position(|l| l.line_number == line_number)?;
+        self.turns.iter().position(|(_, range)| range.contains(&index))
+    }
+
+    /// Position in `turns` of the turn with this number
+    fn position_of_turn(&self, number: usize) -> Option<usize> {
+        self.turns.iter().position(|(n, _)| *n == number)
+    }
+
+    /// First prompt and last assistant reply of the turn at `position`
+    fn summary(&self, position: usize, matched: bool) -> ContextTurn {
+        let (number, range) = &self.turns[position];
+        let lines = &self.lines[range.clone()];
+        let text = |l: &TranscriptLine| {
+            l.message()
+                .map(|m| plain_text(m.content.as_text().trim()))
+                .unwrap_or_default()
+        };
+        let prompt = lines.iter().find(|l| is_prompt(l)).map(text).unwrap_or_default();
+        let reply = lines
+            .iter()
+            .rev()
+            .filter(|l| l.line_type == LineType::Assistant)
+            .map(text)
+            .find(|t| !t.is_empty())
+            .unwrap_or_default();
+        ContextTurn {
+            turn: *number,
+            matched,
+            prompt: clip_chars(&prompt, MAX_CONTEXT_CHARS),
+            reply: clip_chars(&reply, MAX_CONTEXT_CHARS),
+        }
+    }
 }
 
-pub fn run(
-    cli: &Cli,
-    query: &str,
-    max_sessions: usize,
-    max_matches: usize,
-) -> Result<()> {
-    let indexer_db = IndexerDb::open_or_create_default()?;
+pub fn run(cli: &Cli, query: &str, options: &RecallOptions) -> Result<()> {
+    let indexer_db = open_indexer(cli)?;
     let conn = indexer_db.connection();
+    let project = options.project.as_deref();
 
     // Build FTS query - wrap each word in quotes for OR search
     let fts_query: String = query
@@ -71,16 +171,19 @@ pub fn run(
                 l.type,
                 l.timestamp,
                 l.content,
-                bm25(lines_fts) AS rank
+                bm25(lines_fts) AS rank,
+                l.turn_sequence
             FROM lines_fts fts
             JOIN lines l ON fts.rowid = l.id
             WHERE lines_fts MATCH ?1
+              AND (?2 IS NULL OR COALESCE(l.repo_root, l.cwd) = ?2 OR l.cwd = ?2
+                   OR substr(l.cwd, 1, length(?2) + 1) IN (?2 || '/', ?2 || '\'))
             ORDER BY rank
             LIMIT 200
             "#,
         )?;
 
-        let rows = stmt.query_map(rusqlite::params![fts_query], |row| {
+        let rows = stmt.query_map(rusqlite::params![fts_query, project], |row| {
             Ok(MatchedLine {
                 session_id: row.get(0)?,
                 session_name: row.get(1)?,
@@ -89,6 +192,11 @@ pub fn run(
                 timestamp: row.get(4)?,
                 content: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
                 rank: row.get(6)?,
+                score: 0.0,
+                turn: row
+                    .get::<_, Option<i64>>(7)?
+                    .filter(|&n| n > 0)
+                    .map(|n| n as usize),
                 source: "transcript",
             })
         })?;
@@ -112,16 +220,22 @@ pub fn run(
                 h.event_type,
                 h.timestamp,
                 COALESCE(h.tool_name, '') || ' ' || COALESCE(h.event_type, ''),
-                bm25(hook_events_fts) AS rank
+                bm25(hook_events_fts) AS rank,
+                h.turn_sequence
             FROM hook_events_fts fts
             JOIN hook_events h ON fts.rowid = h.id
             WHERE hook_events_fts MATCH ?1
+              AND (?2 IS NULL OR h.session_id IN (
+                  SELECT session_id FROM lines
+                  WHERE COALESCE(repo_root, cwd) = ?2 OR cwd = ?2
+                     OR substr(cwd, 1, length(?2) + 1) IN (?2 || '/', ?2 || '\')
+              ))
             ORDER BY rank
             LIMIT 200
             "#,
         )?;
 
-        let rows = stmt.query_map(rusqlite::params![fts_query], |row| {
+        let rows = stmt.query_map(rusqlite::params![fts_query, project], |row| {
             Ok(MatchedLine {
                 session_id: row.get(0)?,
                 session_name: row.get(1)?,
@@ -130,6 +244,11 @@ pub fn run(
                 timestamp: row.get(4)?,
                 content: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
                 rank: row.get(6)?,
+                score: 0.0,
+                turn: row
+                    .get::<_, Option<i64>>(7)?
+                    .filter(|&n| n > 0)
+                    .map(|n| n as usize),
                 source: "hook",
             })
         })?;
@@ -142,9 +261,26 @@ pub fn run(
         }
     }
 
+    // Transcript lines hold the turn split for density and context
+    let needs_turns = options.rank == RecallRank::Density || options.context > 0;
+    let db = if needs_turns && !all_matches.is_empty() {
+        Some(TranscriptDb::open(indexer_db.path())?)
+    } else {
+        None
+    };
+
+    let now = chrono::Utc::now();
+    for m in &mut all_matches {
+        // bm25() is negative, more negative for better matches
+        let relevance = -m.rank;
+        m.score = match options.rank {
+            RecallRank::Recency => relevance * recency_weight(&m.timestamp, now),
+            RecallRank::Bm25 | RecallRank::Density => relevance,
+        };
+    }
+
     // Group matches by session_id
-    let mut session_map: std::collections::HashMap<String, Vec<MatchedLine>> =
-        std::collections::HashMap::new();
+    let mut session_map: HashMap<String, Vec<MatchedLine>> = HashMap::new();
 
     for m in all_matches {
         session_map
@@ -153,14 +289,33 @@ pub fn run(
             .push(m);
     }
 
-    // Build session groups with best rank and latest timestamp
-    let mut sessions: Vec<RecallSession> = Vec::new();
+    // Build session groups with best score and latest timestamp
+    let mut groups: Vec<(RecallSession, Option<SessionTurns>)> = Vec::new();
 
     for (session_id, mut matches) in session_map {
-        // Sort matches within session by rank (best first)
-        matches.sort_by(|a, b| a.rank.partial_cmp(&b.rank).unwrap_or(std::cmp::Ordering::Equal));
+        let turns = match &db {
+            Some(db) => Some(SessionTurns::load(db, &session_id)?),
+            None => None,
+        };
+        if let Some(turns) = &turns {
+            for m in matches.iter_mut().filter(|m| m.source == "transcript") {
+                m.turn = turns.position_of(m.line_number).map(|p| turns.turns[p].0);
+            }
+        }
 
-        let best_rank = matches.first().map(|m| m.rank).unwrap_or(0.0);
+        // Sort matches within session by score (best first)
+        matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+        let best_rank = matches
+            .iter()
+            .map(|m| m.rank)
+            .fold(f64::INFINITY, f64::min);
+        let mut score = matches.first().map(|m| m.score).unwrap_or(0.0);
+        if options.rank == RecallRank::Density {
+            let matched: HashSet<usize> = matches.iter().filter_map(|m| m.turn).collect();
+            let total = turns.as_ref().map(|t| t.turns.len()).unwrap_or(0);
+            score = turn_density(matched.len(), total);
+        }
         let latest_timestamp = matches
             .iter()
             .map(|m| m.timestamp.as_str())
@@ -174,39 +329,59 @@ pub fn run(
         // Take top N matches per session
         let top_matches: Vec<RecallMatch> = matches
             .into_iter()
-            .take(max_matches)
+            .take(options.max_matches)
             .map(|m| RecallMatch {
                 source: m.source.to_string(),
                 line_number: m.line_number,
                 entry_type: m.entry_type,
                 timestamp: m.timestamp,
                 content: m.content,
+                turn: m.turn,
             })
             .collect();
 
-        sessions.push(RecallSession {
-            session_id,
-            session_name,
-            best_rank,
-            latest_timestamp,
-            matches: top_matches,
-        });
+        groups.push((
+            RecallSession {
+                session_id,
+                session_name,
+                best_rank,
+                score,
+                latest_timestamp,
+                matches: top_matches,
+                context: Vec::new(),
+            },
+            turns,
+        ));
     }
 
-    // Sort sessions: best BM25 rank first, then most recent
-    sessions.sort_by(|a, b| {
-        a.best_rank
-            .partial_cmp(&b.best_rank)
+    // Sort sessions: best score first, then best BM25 rank, then most recent
+    groups.sort_by(|(a, _), (b, _)| {
+        b.score
+            .partial_cmp(&a.score)
             .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.best_rank.partial_cmp(&b.best_rank).unwrap_or(std::cmp::Ordering::Equal))
             .then_with(|| b.latest_timestamp.cmp(&a.latest_timestamp))
     });
 
     // Limit sessions
-    sessions.truncate(max_sessions);
+    groups.truncate(options.max_sessions);
+
+    let sessions: Vec<RecallSession> = groups
+        .into_iter()
+        .map(|(mut session, turns)| {
+            if let Some(turns) = turns.filter(|_| options.context > 0) {
+                session.context = context_turns(&turns, &session.matches, options.context);
+            }
+            session
+        })
+        .collect();
 
     // Output
     match cli.effective_format() {
         OutputFormat::Human => {
+            if let Some(project) = &project {
+                println!("{}", colors::label(&format!("Project: {}", project)));
+            }
             if sessions.is_empty() {
                 println!("No results found for: {}", query.cyan());
             } else {
@@ -257,6 +432,27 @@ pub fn run(
                             source_tag, time, entry_type, m.line_number, content
                         );
                     }
+
+                    // Surrounding turns
+                    if !session.context.is_empty() {
+                        println!("    {}", colors::label("Context:"));
+                    }
+                    for turn in &session.context {
+                        let marker = if turn.matched { "*" } else { " " };
+                        println!(
+                            "    {} {} {}",
+                            marker,
+                            colors::label(&format!("turn {}", turn.turn)),
+                            truncate_content(&turn.prompt, 100)
+                        );
+                        if !turn.reply.is_empty() {
+                            println!(
+                                "        {} {}",
+                                "→".dimmed(),
+                                truncate_content(&turn.reply, 100).dimmed()
+                            );
+                        }
+                    }
                 }
             }
         }
@@ -282,6 +478,13 @@ pub fn run(
                         truncate_content(&m.content, 100)
                     );
                 }
+                for turn in &session.context {
+                    println!(
+                        "  [turn {}] {}",
+                        turn.turn,
+                        truncate_content(&turn.prompt, 100)
+                    );
+                }
             }
         }
     }
@@ -293,9 +496,74 @@ pub fn run(
 fn truncate_content(content: &str, max_len: usize) -> String {
     // Take first line only
     let first_line = content.lines().next().unwrap_or(content);
-    if first_line.len() > max_len {
-        format!("{}...", &first_line[..max_len])
+    if first_line.chars().count() > max_len {
+        format!("{}...", first_line.chars().take(max_len).collect::<String>())
     } else {
         first_line.to_string()
     }
 }
+
+/// Keep the first `max` characters of text
+fn clip_chars(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        format!("{}…", text.chars().take(max).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+/// Weight halving every `RECENCY_HALF_LIFE_DAYS` (unparseable timestamps count as new)
+fn recency_weight(timestamp: &str, now: chrono::DateTime<chrono::Utc>) -> f64 {
+    let Ok(at) = chrono::DateTime::parse_from_rfc3339(timestamp) else {
+        return 1.0;
+    };
+    let age_days = (now - at.with_timezone(&chrono::Utc)).num_seconds().max(0) as f64 / 86_400.0;
+    0.5_f64.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+}
+
+/// Share of a session's turns that contain a match
+fn turn_density(matched: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        matched.min(total) as f64 / total as f64
+    }
+}
+
+/// The matched turns plus `context` turns either side, in session order
+fn context_turns(turns: &SessionTurns, matches: &[RecallMatch], context: usize) -> Vec<ContextTurn> {
+    let matched: BTreeSet<usize> = matches
+        .iter()
+        .filter_map(|m| m.turn)
+        .filter_map(|n| turns.position_of_turn(n))
+        .collect();
+    let mut shown = BTreeSet::new();
+    for &p in &matched {
+        let last = (p + context).min(turns.turns.len().saturating_sub(1));
+        shown.extend(p.saturating_sub(context)..=last);
+    }
+    shown
+        .into_iter()
+        .map(|p| turns.summary(p, matched.contains(&p)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranking_helpers() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-29T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert_eq!(recency_weight("2026-01-29T00:00:00Z", now), 1.0);
+        assert!((recency_weight("2026-01-15T00:00:00Z", now) - 0.5).abs() < 1e-9);
+        assert_eq!(recency_weight("not a time", now), 1.0);
+
+        assert_eq!(turn_density(2, 4), 0.5);
+        assert_eq!(turn_density(1, 0), 0.0);
+
+        assert_eq!(truncate_content("héllo wörld", 5), "héllo...");
+    }
+}
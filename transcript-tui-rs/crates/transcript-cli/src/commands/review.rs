@@ -0,0 +1,71 @@
+//! Review command - track which agent sessions have been audited
+
+use anyhow::Result;
+use colored::Colorize;
+use serde_json::json;
+use transcript_core::ReviewStatus;
+use transcript_db::TranscriptDb;
+use transcript_indexer::IndexerDb;
+
+use super::annotate::author_or_default;
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors;
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session: &str,
+    status: Option<ReviewStatus>,
+    reviewer: Option<&str>,
+) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    if let Some(status) = status {
+        // Reviews are written through the indexer, which owns all writes
+        let indexer_db = IndexerDb::open_or_create(db.path())?;
+        let reviewer = author_or_default(reviewer);
+        transcript_indexer::set_review_status(indexer_db.connection(), &session_id, status, Some(&reviewer))?;
+    }
+    let review = db.get_review(&session_id)?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            let by = match (&review.reviewer, &review.updated_at) {
+                (Some(reviewer), Some(at)) => format!(" (by {}, {})", reviewer, at.get(..10).unwrap_or(at)),
+                _ => String::new(),
+            };
+            let message = format!("{} is {}{}", session_id, colored_status(review.status), by);
+            if status.is_some() {
+                println!("{}", colors::success(&message));
+            } else {
+                println!("{}", message);
+            }
+        }
+        OutputFormat::Json => {
+            let value = json!({
+                "sessionId": session_id,
+                "status": review.status.name(),
+                "reviewer": review.reviewer,
+                "updatedAt": review.updated_at,
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                println!("{}", serde_json::to_string(&value)?);
+            }
+        }
+        OutputFormat::Minimal => println!("{}", review.status.name()),
+    }
+
+    Ok(())
+}
+
+/// Status name colored by how much attention the session still needs
+pub fn colored_status(status: ReviewStatus) -> String {
+    match status {
+        ReviewStatus::Unreviewed => status.name().dimmed().to_string(),
+        ReviewStatus::InReview => status.name().yellow().to_string(),
+        ReviewStatus::Approved => status.name().green().to_string(),
+        ReviewStatus::Flagged => status.name().red().bold().to_string(),
+    }
+}
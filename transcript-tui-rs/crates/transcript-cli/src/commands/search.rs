@@ -1,17 +1,23 @@
 //! Search command - full-text search across transcripts
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
-use serde::Serialize;
-use transcript_db::TranscriptDb;
+use serde::{Deserialize, Serialize};
+use transcript_core::{SearchHistory, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+use transcript_indexer::IndexerDb;
 
 use crate::cli::{Cli, OutputFormat};
+use crate::commands::resolve_project;
 use crate::output::{colors, human};
 
 /// Structured search result for JSON output (matches TS CLI format)
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SearchResult {
+    /// Database the hit came from (team and archive searches only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origin: Option<String>,
     session_id: String,
     slug: Option<String>,
     session_name: Option<String>,
@@ -22,8 +28,154 @@ struct SearchResult {
     content: Option<String>,
     matched_text: String,
     raw: String,
+    /// Lines of the session before and after the hit (`--context`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    context_before: Vec<ContextLine>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    context_after: Vec<ContextLine>,
 }
 
+/// A line around a hit, for JSON output
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContextLine {
+    line_number: i64,
+    #[serde(rename = "type")]
+    entry_type: String,
+    timestamp: String,
+    content: Option<String>,
+}
+
+impl From<&TranscriptLine> for ContextLine {
+    fn from(line: &TranscriptLine) -> Self {
+        Self {
+            line_number: line.line_number,
+            entry_type: line.line_type.to_string(),
+            timestamp: line.timestamp.clone(),
+            content: line.content.clone(),
+        }
+    }
+}
+
+/// Characters of a context line's content shown in human output
+const CONTEXT_PREVIEW_CHARS: usize = 100;
+
+/// History scope shared by every `transcript search` invocation
+const HISTORY_SCOPE: &str = "search";
+
+/// Print previous queries, most recent first (`search --history`)
+pub fn history(cli: &Cli, limit: i64) -> Result<()> {
+    let history = SearchHistory::load(HISTORY_SCOPE);
+    let queries: Vec<&String> = history
+        .entries()
+        .iter()
+        .rev()
+        .take(limit.max(0) as usize)
+        .collect();
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if queries.is_empty() {
+                println!("No search history yet");
+            } else {
+                println!("{}", colors::header("Recent searches"));
+                println!();
+                for (i, query) in queries.iter().enumerate() {
+                    println!("{} {}", colors::label(&format!("{:>3}", i + 1)), query);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&queries)?);
+            } else {
+                println!("{}", serde_json::to_string(&queries)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for query in queries {
+                println!("{}", query);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Filters stored with a saved search (`search --save`)
+///
+/// `since` keeps the spec as typed, so "2h" stays relative to each run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchFilters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub today: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub team: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub include_archive: bool,
+}
+
+impl SearchFilters {
+    /// These filters, with those not set taken from `saved`
+    pub fn or(self, saved: SearchFilters) -> SearchFilters {
+        // A window given here replaces the saved one, whichever form it takes
+        let (since, today) = if self.since.is_some() || self.today {
+            (self.since, self.today)
+        } else {
+            (saved.since, saved.today)
+        };
+        SearchFilters {
+            session: self.session.or(saved.session),
+            tag: self.tag.or(saved.tag),
+            project: self.project.or(saved.project),
+            since,
+            today,
+            team: self.team || saved.team,
+            include_archive: self.include_archive || saved.include_archive,
+        }
+    }
+}
+
+/// A saved search's query and filters (`search --saved`)
+pub fn load_saved(db: &TranscriptDb, name: &str) -> Result<(String, SearchFilters)> {
+    let Some(saved) = db.get_saved_search(name)? else {
+        let names: Vec<String> = db.get_saved_searches()?.into_iter().map(|s| s.name).collect();
+        if names.is_empty() {
+            bail!("No saved search named '{}' (save one with `search <query> --save <name>`)", name);
+        }
+        bail!("No saved search named '{}' (saved: {})", name, names.join(", "));
+    };
+    let filters = serde_json::from_str(&saved.filters)
+        .with_context(|| format!("Saved search '{}' has unreadable filters", name))?;
+    Ok((saved.query, filters))
+}
+
+/// Save `query` and `filters` as `name` (`search --save`)
+pub fn save(cli: &Cli, db: &TranscriptDb, name: &str, query: &str, filters: &SearchFilters) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("Saved search name is empty");
+    }
+    // Written through the indexer, which owns all writes
+    let indexer_db = IndexerDb::open_or_create(db.path())?;
+    let added = transcript_indexer::save_search(indexer_db.connection(), name, query, &serde_json::to_string(filters)?)?;
+    if cli.effective_format() == OutputFormat::Human {
+        let verb = if added { "Saved" } else { "Updated" };
+        eprintln!("{} search '{}' (run it with --saved {})", verb, name, name);
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     cli: &Cli,
     db: &TranscriptDb,
@@ -31,6 +183,10 @@ pub fn run(
     limit: i64,
     session: Option<&str>,
     context: usize,
+    federated: bool,
+    tag: Option<&str>,
+    project: Option<&str>,
+    since: Option<&str>,
 ) -> Result<()> {
     // Resolve session if provided
     let session_id = if let Some(s) = session {
@@ -40,21 +196,56 @@ pub fn run(
         None
     };
 
-    // Perform search
-    let lines = db.search_lines(query, limit, session_id.as_deref())?;
+    // Perform search, across teammates' snapshots and the archive when attached
+    let hits: Vec<(Option<String>, TranscriptLine)> = if federated {
+        db.search_lines_federated(query, limit, since)?
+            .into_iter()
+            .map(|hit| (Some(hit.origin), hit.line))
+            .collect()
+    } else if let Some(tag) = tag {
+        db.search_lines_with_tag(query, limit, tag, since)?
+            .into_iter()
+            .map(|line| (None, line))
+            .collect()
+    } else if let Some(project) = project {
+        db.search_lines_in_project(query, limit, &resolve_project(Some(db), project)?, since)?
+            .into_iter()
+            .map(|line| (None, line))
+            .collect()
+    } else {
+        db.search_lines(query, limit, session_id.as_deref(), since)?
+            .into_iter()
+            .map(|line| (None, line))
+            .collect()
+    };
+
+    // Best effort: a read-only home shouldn't fail the search
+    let _ = SearchHistory::load(HISTORY_SCOPE).push(query);
 
     match cli.effective_format() {
         OutputFormat::Human => {
-            if lines.is_empty() {
+            if hits.is_empty() {
                 println!("No results found for: {}", query.cyan());
             } else {
                 println!(
                     "{}",
-                    colors::header(&format!("Search results for '{}' ({})", query, lines.len()))
+                    colors::header(&format!("Search results for '{}' ({})", query, hits.len()))
                 );
                 println!();
 
-                for line in &lines {
+                for (i, (origin, line)) in hits.iter().enumerate() {
+                    let (before, after) = context_lines(db, origin.as_deref(), line, context)?;
+                    if context > 0 && i > 0 {
+                        println!("{}", colors::label("--"));
+                    }
+                    for context_line in &before {
+                        print_context_line(context_line);
+                    }
+
+                    if let Some(origin) = origin {
+                        print!("{} ", colors::label(&format!("[{}]", origin)));
+                    }
+
                     // Show session name if not filtered to one session
                     if session.is_none() {
                         if let Some(name) = &line.session_name {
@@ -64,13 +255,16 @@ pub fn run(
                         }
                     }
 
-                    // Format the line
-                    if context > 0 {
-                        // Show with highlighted match
-                        println!("{}", human::format_line(line, true));
-                        highlight_match(line.content.as_deref().unwrap_or(""), query);
-                    } else {
-                        println!("{}", human::format_line(line, false));
+                    println!("{}", human::format_line(line, false));
+                    if let Some(matched) = matched_line(line.content.as_deref().unwrap_or(""), query) {
+                        println!(
+                            "  >>> {}",
+                            mark_terms(matched, query, |m| m.black().on_yellow().to_string())
+                        );
+                    }
+
+                    for context_line in &after {
+                        print_context_line(context_line);
                     }
                 }
             }
@@ -78,28 +272,27 @@ pub fn run(
 
         OutputFormat::Json => {
             // Build structured search results matching TS CLI format
-            let results: Vec<SearchResult> = lines
-                .iter()
-                .map(|line| {
-                    // Create matched_text with FTS-style markers
-                    let matched_text = highlight_text_with_markers(
-                        line.content.as_deref().unwrap_or(""),
-                        query,
-                    );
-
-                    SearchResult {
-                        session_id: line.session_id.clone(),
-                        slug: line.slug.clone(),
-                        session_name: line.session_name.clone(),
-                        line_number: line.line_number,
-                        entry_type: line.line_type.to_string(),
-                        timestamp: line.timestamp.clone(),
-                        content: line.content.clone(),
-                        matched_text,
-                        raw: line.raw.clone(),
-                    }
-                })
-                .collect();
+            let mut results: Vec<SearchResult> = Vec::with_capacity(hits.len());
+            for (origin, line) in &hits {
+                // Create matched_text with FTS-style markers
+                let matched_text = highlight_text_with_markers(line.content.as_deref().unwrap_or(""), query);
+                let (before, after) = context_lines(db, origin.as_deref(), line, context)?;
+
+                results.push(SearchResult {
+                    origin: origin.clone(),
+                    session_id: line.session_id.clone(),
+                    slug: line.slug.clone(),
+                    session_name: line.session_name.clone(),
+                    line_number: line.line_number,
+                    entry_type: line.line_type.to_string(),
+                    timestamp: line.timestamp.clone(),
+                    content: line.content.clone(),
+                    matched_text,
+                    raw: line.raw.clone(),
+                    context_before: before.iter().map(ContextLine::from).collect(),
+                    context_after: after.iter().map(ContextLine::from).collect(),
+                });
+            }
 
             if cli.pretty {
                 println!("{}", serde_json::to_string_pretty(&results)?);
@@ -109,7 +302,7 @@ pub fn run(
         }
 
         OutputFormat::Minimal => {
-            for line in &lines {
+            for (_, line) in &hits {
                 if let Some(content) = &line.content {
                     println!("{}", content);
                 }
@@ -120,62 +313,148 @@ pub fn run(
     Ok(())
 }
 
+/// The session's `context` lines before and after `line` (none for hits
+/// from teammates' snapshots or the archive, whose lines aren't in this database)
+fn context_lines(
+    db: &TranscriptDb,
+    origin: Option<&str>,
+    line: &TranscriptLine,
+    context: usize,
+) -> Result<(Vec<TranscriptLine>, Vec<TranscriptLine>)> {
+    if context == 0 || origin.is_some() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let context = context as i64;
+    let options = GetLinesOptions {
+        from_line: Some(line.line_number - context),
+        to_line: Some(line.line_number + context),
+        ..GetLinesOptions::for_session(&line.session_id)
+    };
+    let (before, after): (Vec<_>, Vec<_>) = db
+        .get_lines(&options)?
+        .into_iter()
+        .filter(|l| l.line_number != line.line_number)
+        .partition(|l| l.line_number < line.line_number);
+    Ok((before, after))
+}
+
+/// A line around a hit: its header and the start of its content, dimmed
+fn print_context_line(line: &TranscriptLine) {
+    let preview = line
+        .content
+        .as_deref()
+        .and_then(|c| c.lines().find(|l| !l.trim().is_empty()))
+        .unwrap_or_default()
+        .trim();
+    let preview = if preview.chars().count() > CONTEXT_PREVIEW_CHARS {
+        format!("{}…", preview.chars().take(CONTEXT_PREVIEW_CHARS).collect::<String>())
+    } else {
+        preview.to_string()
+    };
+    println!("{}  {}", human::format_line(line, false), colors::label(&preview));
+}
+
 /// Add FTS-style markers around matched terms (for JSON output)
 fn highlight_text_with_markers(content: &str, query: &str) -> String {
-    let terms: Vec<&str> = query.split_whitespace().collect();
-    let mut result = content.to_string();
-
-    for term in terms {
-        // Case-insensitive replacement with markers
-        let term_lower = term.to_lowercase();
-        let mut new_result = String::new();
-        let mut remaining = result.as_str();
-
-        while let Some(pos) = remaining.to_lowercase().find(&term_lower) {
-            // Add text before match
-            new_result.push_str(&remaining[..pos]);
-            // Add marked match (preserve original case)
-            new_result.push_str(">>>>");
-            new_result.push_str(&remaining[pos..pos + term.len()]);
-            new_result.push_str("<<<<");
-            // Continue after match
-            remaining = &remaining[pos + term.len()..];
+    mark_terms(content, query, |m| format!(">>>>{}<<<<", m))
+}
+
+/// Terms of a query as typed: quotes and prefix stars dropped
+fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|t| t.trim_matches(|c| c == '"' || c == '*').to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// The first line of `content` containing one of the query's terms
+fn matched_line<'a>(content: &'a str, query: &str) -> Option<&'a str> {
+    let terms = query_terms(query);
+    content.lines().find(|line| {
+        let line = line.to_lowercase();
+        terms.iter().any(|t| line.contains(t.as_str()))
+    })
+}
+
+/// Wrap every case-insensitive occurrence of the query's terms with `mark`,
+/// in one pass so marks never land inside earlier ones
+fn mark_terms(content: &str, query: &str, mark: impl Fn(&str) -> String) -> String {
+    let lower = content.to_lowercase();
+    // Lowercasing can move byte offsets outside ASCII; leave such text unmarked
+    if lower.len() != content.len() {
+        return content.to_string();
+    }
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for term in query_terms(query) {
+        let mut from = 0;
+        while let Some(pos) = lower[from..].find(&term) {
+            let start = from + pos;
+            ranges.push((start, start + term.len()));
+            from = start + term.len();
         }
-        new_result.push_str(remaining);
-        result = new_result;
     }
+    ranges.sort();
 
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end) in ranges {
+        // Overlapping terms: the earlier match wins
+        if start < last || !content.is_char_boundary(start) || !content.is_char_boundary(end) {
+            continue;
+        }
+        result.push_str(&content[last..start]);
+        result.push_str(&mark(&content[start..end]));
+        last = end;
+    }
+    result.push_str(&content[last..]);
     result
 }
 
-/// Highlight matches in content
-fn highlight_match(content: &str, query: &str) {
-    let query_lower = query.to_lowercase();
-    let content_lower = content.to_lowercase();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if let Some(pos) = content_lower.find(&query_lower) {
-        // Find line containing the match
-        let before = &content[..pos];
-        let line_start = before.rfind('\n').map(|p| p + 1).unwrap_or(0);
+    #[test]
+    fn test_terms_are_marked_case_insensitively() {
+        let marked = highlight_text_with_markers("Fix the Parser, then the parser tests", "parser fix");
+        assert_eq!(marked, ">>>>Fix<<<< the >>>>Parser<<<<, then the >>>>parser<<<< tests");
 
-        let after = &content[pos..];
-        let line_end = after.find('\n').map(|p| pos + p).unwrap_or(content.len());
+        // A term inside another's match isn't marked twice
+        assert_eq!(highlight_text_with_markers("rerun", "rerun run"), ">>>>rerun<<<<");
+        assert_eq!(highlight_text_with_markers("naïve", "naïve"), ">>>>naïve<<<<");
+    }
 
-        let matched_line = &content[line_start..line_end];
+    #[test]
+    fn test_matched_line_is_the_first_with_a_term() {
+        let content = "line one\nthe Parser failed\nparser again";
+        assert_eq!(matched_line(content, "\"parser\""), Some("the Parser failed"));
+        assert_eq!(matched_line(content, "missing"), None);
+    }
 
-        // Re-find position in the line
-        let line_lower = matched_line.to_lowercase();
-        if let Some(match_pos) = line_lower.find(&query_lower) {
-            let before_match = &matched_line[..match_pos];
-            let matched = &matched_line[match_pos..match_pos + query.len()];
-            let after_match = &matched_line[match_pos + query.len()..];
+    #[test]
+    fn test_given_filters_override_saved_ones() {
+        let saved = SearchFilters {
+            tag: Some("bug".to_string()),
+            since: Some("1w".to_string()),
+            team: true,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(json, r#"{"tag":"bug","since":"1w","team":true}"#);
+        let saved: SearchFilters = serde_json::from_str(&json).unwrap();
 
-            println!(
-                "  >>> {}{}{}",
-                before_match,
-                matched.black().on_yellow(),
-                after_match
-            );
-        }
+        let given = SearchFilters {
+            today: true,
+            ..Default::default()
+        };
+        let merged = given.or(saved.clone());
+        assert_eq!(merged.tag.as_deref(), Some("bug"));
+        assert_eq!(merged.since, None);
+        assert!(merged.today);
+        assert!(merged.team);
+
+        assert_eq!(SearchFilters::default().or(saved.clone()), saved);
     }
 }
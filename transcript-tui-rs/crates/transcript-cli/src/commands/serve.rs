@@ -0,0 +1,386 @@
+//! Serve command - a small web dashboard over the index
+//!
+//! `transcript serve` answers HTTP from this binary: the dashboard's static
+//! files (embedded at build time) and the read-only JSON API they call.
+//! Requests are handled one at a time on the one database connection, which
+//! is plenty for a few people browsing sessions and keeps this dependency-free.
+//! The flip side is that a client that connects and sends nothing holds up
+//! everyone else until its 5 second read timeout.
+//!
+//! Requests must name this server in their `Host` header (`localhost`, a
+//! loopback address or the bound address), so a web page that rebinds its
+//! own domain to 127.0.0.1 can't read transcripts through the browser.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use transcript_core::TranscriptLine;
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::output::{colors, human, json as json_output};
+
+/// Dashboard files: (path, content type, body)
+const ASSETS: &[(&str, &str, &str)] = &[
+    ("/", "text/html; charset=utf-8", include_str!("../../assets/dashboard/index.html")),
+    ("/app.js", "text/javascript; charset=utf-8", include_str!("../../assets/dashboard/app.js")),
+    ("/style.css", "text/css; charset=utf-8", include_str!("../../assets/dashboard/style.css")),
+];
+
+/// Sessions listed when the request doesn't say
+const DEFAULT_SESSION_LIMIT: usize = 200;
+
+/// Search results returned when the request doesn't say
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+
+/// Characters of a line shown in search results
+const SEARCH_PREVIEW: usize = 200;
+
+pub fn run(cli: &Cli, db: &TranscriptDb, bind: &str, port: u16) -> Result<()> {
+    let listener = TcpListener::bind((bind, port)).with_context(|| format!("Failed to listen on {}:{}", bind, port))?;
+    let local = listener.local_addr()?;
+    let url = format!("http://{}", local);
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!("{} {}", colors::success("Dashboard at"), url);
+            eprintln!("{}", colors::label("Ctrl-C to stop"));
+        }
+        OutputFormat::Json => println!("{}", json!({ "url": url })),
+        OutputFormat::Minimal => println!("{}", url),
+    }
+
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        // A client that hangs up early is its own problem; keep serving
+        if let Err(e) = handle(db, &mut stream, local) {
+            eprintln!("{}", colors::error(&format!("Request failed: {}", e)));
+        }
+    }
+    Ok(())
+}
+
+/// A response ready to write
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: &'static str, value: &Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: &'static str, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+}
+
+/// Read one request (ignoring headers other than `Host`, and the body) and
+/// answer it
+fn handle(db: &TranscriptDb, stream: &mut TcpStream, local: SocketAddr) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&*stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut host = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let response = if host.as_deref().is_some_and(|h| host_allowed(h, local.ip())) {
+        respond(db, &request_line)
+    } else {
+        Response::error("403 Forbidden", "Unexpected Host header")
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Whether a `Host` header names this server: `localhost`, a loopback
+/// address or the address it is bound to (any address when bound to all)
+fn host_allowed(host: &str, bound: IpAddr) -> bool {
+    // Drop the port: `[::1]:8080`, `127.0.0.1:8080`, `localhost:8080`
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    if name.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+    match name.parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback() || ip == bound || bound.is_unspecified(),
+        Err(_) => false,
+    }
+}
+
+/// Route a request line (`GET /path?query HTTP/1.1`)
+fn respond(db: &TranscriptDb, request_line: &str) -> Response {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Response::error("400 Bad Request", "Malformed request");
+    };
+    if method != "GET" {
+        return Response::error("405 Method Not Allowed", "Only GET is supported");
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if let Some((_, content_type, body)) = ASSETS.iter().find(|(p, _, _)| *p == path) {
+        return Response {
+            status: "200 OK",
+            content_type,
+            body: body.to_string(),
+        };
+    }
+
+    let params = parse_query(query);
+    let segments: Vec<String> = path.split('/').filter(|s| !s.is_empty()).map(percent_decode).collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let result = match segments.as_slice() {
+        ["api", "changes"] => changes(db, &params).map(Some),
+        ["api", "sessions"] => sessions(db, &params).map(Some),
+        ["api", "sessions", session, "turns"] => turns(db, session),
+        ["api", "search"] => match params.get("q").map(|q| q.trim()).filter(|q| !q.is_empty()) {
+            Some(q) => search(db, q, &params).map(Some),
+            None => return Response::error("400 Bad Request", "Missing search query (q)"),
+        },
+        _ => Ok(None),
+    };
+
+    match result {
+        Ok(Some(value)) => Response::json("200 OK", &value),
+        Ok(None) => Response::error("404 Not Found", &format!("Not found: {}", path)),
+        Err(e) => Response::error("500 Internal Server Error", &e.to_string()),
+    }
+}
+
+/// The indexer's change feed after `since` (just the latest id without it),
+/// so the dashboard can refresh when sessions gain lines
+fn changes(db: &TranscriptDb, params: &HashMap<String, String>) -> Result<Value> {
+    let latest = db.latest_change_id()?;
+    let changes = match params.get("since").and_then(|s| s.parse().ok()) {
+        Some(since) => db.changes_after(since, params.get("session").map(String::as_str))?,
+        None => Vec::new(),
+    };
+    Ok(json!({
+        "latest": latest,
+        "changes": changes
+            .iter()
+            .map(|c| json!({
+                "id": c.id,
+                "sessionId": c.session_id,
+                "maxLineId": c.max_line_id,
+                "changedAt": c.changed_at,
+            }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// Most recently active sessions first (`days` and `limit` narrow the list)
+fn sessions(db: &TranscriptDb, params: &HashMap<String, String>) -> Result<Value> {
+    let days = params.get("days").and_then(|d| d.parse().ok());
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(DEFAULT_SESSION_LIMIT);
+    let sessions = db.get_sessions(days)?;
+    Ok(Value::Array(
+        sessions.iter().take(limit).map(json_output::session_to_json).collect(),
+    ))
+}
+
+/// A session's lines grouped into turns (`None` for an unknown session)
+fn turns(db: &TranscriptDb, session: &str) -> Result<Option<Value>> {
+    let Some(info) = db.resolve_session(session)? else {
+        return Ok(None);
+    };
+    let lines = db.get_lines(&GetLinesOptions::for_session(&info.session_id))?;
+    Ok(Some(json!({
+        "session": json_output::session_to_json(&info),
+        "turns": group_turns(&lines),
+    })))
+}
+
+/// Lines as `view` shows them, with a new turn at each user prompt
+fn group_turns(lines: &[TranscriptLine]) -> Vec<Value> {
+    let mut turns: Vec<Value> = Vec::new();
+    for line in lines {
+        if human::should_hide(line) {
+            continue;
+        }
+        if line.is_prompt() || turns.is_empty() {
+            turns.push(json!({
+                "number": turns.len() + 1,
+                "started": line.timestamp,
+                "lines": [],
+            }));
+        }
+        if let Some(Value::Array(turn_lines)) = turns.last_mut().and_then(|t| t.get_mut("lines")) {
+            turn_lines.push(json!({
+                "lineNumber": line.line_number,
+                "type": line.display_type(),
+                "timestamp": line.timestamp,
+                "model": line.model,
+                "text": human::get_content(line).unwrap_or_default(),
+            }));
+        }
+    }
+    turns
+}
+
+/// FTS search across sessions, or within `session`
+fn search(db: &TranscriptDb, query: &str, params: &HashMap<String, String>) -> Result<Value> {
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(DEFAULT_SEARCH_LIMIT);
+    let session_id = match params.get("session").filter(|s| !s.is_empty()) {
+        Some(session) => db.resolve_session(session)?.map(|info| info.session_id),
+        None => None,
+    };
+    let lines = db.search_lines(query, limit, session_id.as_deref(), None)?;
+    Ok(Value::Array(
+        lines
+            .iter()
+            .map(|line| {
+                json!({
+                    "sessionId": line.session_id,
+                    "slug": line.slug,
+                    "lineNumber": line.line_number,
+                    "type": line.display_type(),
+                    "timestamp": line.timestamp,
+                    "preview": line.preview(SEARCH_PREVIEW),
+                })
+            })
+            .collect(),
+    ))
+}
+
+/// Query string parameters, decoded (later duplicates win)
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes and `+` as a space
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => match text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                Some(byte) => {
+                    out.push(byte);
+                    i += 2;
+                }
+                None => out.push(b'%'),
+            },
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transcript_core::LineType;
+
+    fn line(line_number: i64, line_type: LineType, text: &str) -> TranscriptLine {
+        let raw = match line_type {
+            LineType::Assistant => json!({"type": "assistant", "message": {"role": "assistant", "content": [{"type": "text", "text": text}]}}),
+            _ => json!({"type": "user", "message": {"role": "user", "content": text}}),
+        };
+        TranscriptLine {
+            id: line_number,
+            line_number,
+            uuid: format!("u{}", line_number),
+            timestamp: format!("2026-01-01T10:00:0{}Z", line_number),
+            content: Some(text.to_string()),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_host_allowed() {
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        assert!(host_allowed("127.0.0.1:8080", loopback));
+        assert!(host_allowed("localhost:8080", loopback));
+        assert!(host_allowed("LOCALHOST", loopback));
+        assert!(host_allowed("[::1]:8080", loopback));
+        // A rebound domain resolves here but names someone else's site
+        assert!(!host_allowed("evil.example:8080", loopback));
+        assert!(!host_allowed("localhost.evil.example", loopback));
+        assert!(!host_allowed("", loopback));
+
+        let lan: IpAddr = "192.168.1.20".parse().unwrap();
+        assert!(host_allowed("192.168.1.20:8080", lan));
+        assert!(!host_allowed("192.168.1.21:8080", lan));
+        assert!(host_allowed("192.168.1.21:8080", "0.0.0.0".parse().unwrap()));
+        assert!(!host_allowed("evil.example", "0.0.0.0".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_query_decoding() {
+        let params = parse_query("q=fix+the%20bug&session=happy-dog&empty=&bad=%zz%");
+        assert_eq!(params["q"], "fix the bug");
+        assert_eq!(params["session"], "happy-dog");
+        assert_eq!(params["empty"], "");
+        assert_eq!(params["bad"], "%zz%");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+    }
+
+    #[test]
+    fn test_group_turns() {
+        let lines = vec![
+            line(1, LineType::User, "first"),
+            line(2, LineType::Assistant, "ok"),
+            line(3, LineType::User, "Status dialog dismissed"),
+            line(4, LineType::User, "second"),
+        ];
+
+        let turns = group_turns(&lines);
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0]["lines"][1]["text"], "ok");
+        // Noise is left out, as in `view`
+        assert_eq!(turns[1]["number"], 2);
+        assert_eq!(turns[1]["lines"].as_array().unwrap().len(), 1);
+        assert_eq!(turns[1]["lines"][0]["text"], "second");
+    }
+}
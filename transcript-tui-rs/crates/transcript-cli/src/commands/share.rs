@@ -0,0 +1,393 @@
+//! Share command - encrypted, expiring session bundles for sending outside the team
+//!
+//! `transcript share` writes one session's lines to a passphrase-encrypted
+//! age file (readable with `rage`/`age -d` too) and prints a generated
+//! passphrase to send through a separate channel. `transcript open-shared`
+//! decrypts and displays a bundle without touching the index. Expiry is
+//! recorded inside the encrypted payload and enforced by `open-shared`.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use age::secrecy::SecretString;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use transcript_core::{LineType, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::parse_duration;
+use crate::output::{colors, human, json, minimal};
+
+const BUNDLE_FORMAT: &str = "transcript-share";
+const BUNDLE_VERSION: u32 = 1;
+
+/// Decrypted bundle contents
+#[derive(Serialize, Deserialize)]
+struct ShareBundle {
+    format: String,
+    version: u32,
+    session_id: String,
+    slug: Option<String>,
+    created_at: String,
+    expires_at: String,
+    lines: Vec<SharedLine>,
+}
+
+/// The indexed fields of one line (enough to render it like `view`)
+#[derive(Serialize, Deserialize)]
+struct SharedLine {
+    line_number: i64,
+    #[serde(rename = "type")]
+    line_type: LineType,
+    uuid: String,
+    parent_uuid: Option<String>,
+    timestamp: String,
+    cwd: Option<String>,
+    role: Option<String>,
+    model: Option<String>,
+    content: Option<String>,
+    raw: String,
+    turn_id: Option<String>,
+    turn_sequence: Option<i64>,
+}
+
+impl SharedLine {
+    fn from_line(line: &TranscriptLine) -> Self {
+        Self {
+            line_number: line.line_number,
+            line_type: line.line_type,
+            uuid: line.uuid.clone(),
+            parent_uuid: line.parent_uuid.clone(),
+            timestamp: line.timestamp.clone(),
+            cwd: line.cwd.clone(),
+            role: line.role.clone(),
+            model: line.model.clone(),
+            content: line.content.clone(),
+            raw: line.raw.clone(),
+            turn_id: line.turn_id.clone(),
+            turn_sequence: line.turn_sequence,
+        }
+    }
+
+    fn into_line(self, bundle_session: &str, slug: Option<&String>) -> TranscriptLine {
+        TranscriptLine {
+            id: 0,
+            line_number: self.line_number,
+            line_type: self.line_type,
+            uuid: self.uuid,
+            parent_uuid: self.parent_uuid,
+            session_id: bundle_session.to_string(),
+            timestamp: self.timestamp,
+            cwd: self.cwd,
+            slug: slug.cloned(),
+            role: self.role,
+            model: self.model,
+            content: self.content,
+            raw: self.raw,
+            turn_id: self.turn_id,
+            turn_sequence: self.turn_sequence,
+            session_name: None,
+        }
+    }
+}
+
+/// Random passphrase of six 4-character groups (~120 bits)
+fn generate_passphrase() -> String {
+    // No 0/o, 1/l/i: easy to read aloud or retype
+    const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..6)
+        .map(|_| {
+            (0..4)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Write a file readable only by the current user
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(data)?;
+    Ok(())
+}
+
+/// Create an encrypted bundle for one session
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session: &str,
+    expires: &str,
+    types: Option<&[String]>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let now = Utc::now();
+    let expires_at = expiry(now, expires)?;
+    let session_id = super::resolve_session_id(db, session)?;
+    let info = db.get_session(&session_id)?;
+    let slug = info.and_then(|i| i.slug);
+
+    let options = GetLinesOptions {
+        types: types.map(|t| t.to_vec()),
+        ..GetLinesOptions::for_session(&session_id)
+    };
+    let lines = db.get_lines(&options)?;
+    if lines.is_empty() {
+        bail!("No lines to share for session {}", session);
+    }
+
+    let bundle = ShareBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        session_id: session_id.clone(),
+        slug: slug.clone(),
+        created_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        expires_at: expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        lines: lines.iter().map(SharedLine::from_line).collect(),
+    };
+
+    let passphrase = generate_passphrase();
+    let ciphertext = encrypt_bundle(&bundle, &passphrase)?;
+
+    let path = output.map(Path::to_path_buf).unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "{}.transcript.age",
+            slug.as_deref().unwrap_or(&session_id)
+        ))
+    });
+    write_private(&path, &ciphertext)?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{}",
+                colors::success(&format!(
+                    "Shared {} ({} lines) to {}",
+                    slug.as_deref().unwrap_or(&session_id),
+                    lines.len(),
+                    path.display()
+                ))
+            );
+            println!("{} {}", colors::label("Passphrase:"), colors::value(&passphrase));
+            println!(
+                "{} {}",
+                colors::label("Expires:   "),
+                expires_at.format("%Y-%m-%d %H:%M UTC")
+            );
+            println!();
+            println!(
+                "{}",
+                colors::label("Send the passphrase separately; the recipient runs: transcript open-shared <file>")
+            );
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "passphrase": passphrase,
+                "session_id": session_id,
+                "lines": lines.len(),
+                "expires_at": bundle.expires_at,
+            });
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        OutputFormat::Minimal => println!("{}\t{}", path.display(), passphrase),
+    }
+
+    Ok(())
+}
+
+/// When a bundle shared at `now` for `expires` (e.g. `7d`) expires
+fn expiry(now: DateTime<Utc>, expires: &str) -> Result<DateTime<Utc>> {
+    now.checked_add_signed(parse_duration(expires)?)
+        .with_context(|| format!("--expires {} is further ahead than a date can be", expires))
+}
+
+/// Encrypt a bundle with a passphrase
+fn encrypt_bundle(bundle: &ShareBundle, passphrase: &str) -> Result<Vec<u8>> {
+    let plaintext = serde_json::to_vec(bundle)?;
+    let encryptor = age::Encryptor::with_user_passphrase(SecretString::from(passphrase.to_string()));
+    let mut ciphertext = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut ciphertext)?;
+    writer.write_all(&plaintext)?;
+    writer.finish()?;
+    Ok(ciphertext)
+}
+
+/// Decrypt a bundle, refusing unknown formats and bundles expired by `now`
+fn decrypt_bundle(ciphertext: &[u8], passphrase: &str, now: DateTime<Utc>) -> Result<(ShareBundle, DateTime<Utc>)> {
+    let decryptor = age::Decryptor::new(ciphertext).context("Not a share bundle")?;
+    let identity = age::scrypt::Identity::new(SecretString::from(passphrase.trim().to_string()));
+    let mut reader = decryptor
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase or not a share bundle"))?;
+    let mut plaintext = Vec::new();
+    reader.read_to_end(&mut plaintext)?;
+
+    let bundle: ShareBundle =
+        serde_json::from_slice(&plaintext).context("Bundle contents are not recognized")?;
+    if bundle.format != BUNDLE_FORMAT || bundle.version > BUNDLE_VERSION {
+        bail!(
+            "Unsupported bundle {} v{} (this version reads {} v{})",
+            bundle.format,
+            bundle.version,
+            BUNDLE_FORMAT,
+            BUNDLE_VERSION
+        );
+    }
+
+    let expires_at: DateTime<Utc> = DateTime::parse_from_rfc3339(&bundle.expires_at)
+        .context("Bundle has an invalid expiry")?
+        .with_timezone(&Utc);
+    if now > expires_at {
+        bail!(
+            "This share expired on {}",
+            expires_at.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+    Ok((bundle, expires_at))
+}
+
+/// Decrypt a bundle and print its lines like `view`
+pub fn open(cli: &Cli, file: &Path, passphrase: Option<&str>) -> Result<()> {
+    let mut ciphertext = Vec::new();
+    std::fs::File::open(file)
+        .with_context(|| format!("Failed to open {}", file.display()))?
+        .read_to_end(&mut ciphertext)?;
+
+    let passphrase = match passphrase {
+        Some(p) => p.to_string(),
+        None => rpassword::prompt_password("Passphrase: ")?,
+    };
+
+    let (bundle, expires_at) = decrypt_bundle(&ciphertext, &passphrase, Utc::now())
+        .with_context(|| format!("Failed to open {}", file.display()))?;
+
+    let format = cli.effective_format();
+    if format == OutputFormat::Human {
+        println!(
+            "{}",
+            colors::header(&format!(
+                "Shared session {} (expires {})",
+                bundle.slug.as_deref().unwrap_or(&bundle.session_id),
+                expires_at.format("%Y-%m-%d %H:%M UTC")
+            ))
+        );
+        println!();
+    }
+
+    let ShareBundle {
+        session_id,
+        slug,
+        lines,
+        ..
+    } = bundle;
+    for shared in lines {
+        let line = shared.into_line(&session_id, slug.as_ref());
+        match format {
+            OutputFormat::Human => {
+                if human::should_hide(&line) {
+                    continue;
+                }
+                let formatted = human::format_line(&line, true);
+                println!("{}", formatted);
+                if formatted.contains('\n') {
+                    println!();
+                }
+            }
+            OutputFormat::Json => println!("{}", json::format_line(&line, cli.pretty)),
+            OutputFormat::Minimal => {
+                let content = minimal::format_line(&line);
+                if !content.is_empty() {
+                    println!("{}", content);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn bundle(created: DateTime<Utc>, expires: &str) -> ShareBundle {
+        let line = SharedLine {
+            line_number: 1,
+            line_type: LineType::User,
+            uuid: "u1".to_string(),
+            parent_uuid: None,
+            timestamp: "2026-01-01T10:00:00Z".to_string(),
+            cwd: None,
+            role: Some("user".to_string()),
+            model: None,
+            content: Some("hello".to_string()),
+            raw: r#"{"type":"user"}"#.to_string(),
+            turn_id: None,
+            turn_sequence: None,
+        };
+        ShareBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            session_id: "s1".to_string(),
+            slug: Some("brave-fox".to_string()),
+            created_at: created.to_rfc3339_opts(SecondsFormat::Secs, true),
+            expires_at: expiry(created, expires).unwrap().to_rfc3339_opts(SecondsFormat::Secs, true),
+            lines: vec![line],
+        }
+    }
+
+    #[test]
+    fn test_expiry() {
+        let now = Utc::now();
+        assert_eq!(expiry(now, "7d").unwrap(), now + Duration::days(7));
+        assert!(expiry(now, "99999999w").is_err());
+        assert!(expiry(now, "7y").is_err());
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let now = Utc::now();
+        let ciphertext = encrypt_bundle(&bundle(now, "1d"), "abcd-efgh").unwrap();
+        assert!(decrypt_bundle(&ciphertext, "wrong", now).is_err());
+
+        // Surrounding whitespace from a pasted passphrase is ignored
+        let (opened, expires_at) = decrypt_bundle(&ciphertext, " abcd-efgh\n", now).unwrap();
+        assert_eq!(expires_at.timestamp(), (now + Duration::days(1)).timestamp());
+        let line = opened.lines.into_iter().next().unwrap().into_line("s1", Some(&"brave-fox".to_string()));
+        assert_eq!(line.content.as_deref(), Some("hello"));
+        assert_eq!(line.slug.as_deref(), Some("brave-fox"));
+    }
+
+    #[test]
+    fn test_expired_bundle_refused() {
+        let created = Utc::now() - Duration::days(2);
+        let ciphertext = encrypt_bundle(&bundle(created, "1d"), "abcd-efgh").unwrap();
+        let Err(err) = decrypt_bundle(&ciphertext, "abcd-efgh", Utc::now()) else {
+            panic!("an expired bundle opened");
+        };
+        assert!(err.to_string().starts_with("This share expired on"), "{}", err);
+    }
+
+    #[test]
+    fn test_generated_passphrase_shape() {
+        let passphrase = generate_passphrase();
+        assert_eq!(passphrase.split('-').count(), 6);
+        assert!(passphrase.split('-').all(|g| g.len() == 4));
+        assert_ne!(passphrase, generate_passphrase());
+    }
+}
@@ -0,0 +1,470 @@
+//! Stats command - prompt cache reuse, the largest tool payloads and API failures
+//!
+//! A cache write costs more than plain input and only pays off when later
+//! requests read it back, so groups that write much and read little point at
+//! prompts whose prefix changes between requests.
+//!
+//! Payload sizes are the other half of "why is this so big": the tool inputs
+//! and outputs that grew the transcripts (and the database) the most.
+//!
+//! API failures (errors, rate limits, overloads) are counted by type and by
+//! local hour, to tell a bad afternoon from a bad setup.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Timelike};
+use colored::Colorize;
+use serde_json::{json, Map, Value};
+use transcript_core::ApiFailureKind;
+use transcript_db::{ApiFailure, CostGroupBy, CostRow, ToolPayload, TranscriptDb};
+use transcript_indexer::LARGE_PAYLOAD_BYTES;
+
+use crate::cli::{CacheBy, Cli, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors::{self, format_count, format_size};
+
+pub fn run_cache(
+    cli: &Cli,
+    db: &TranscriptDb,
+    by: CacheBy,
+    session: Option<&str>,
+    days: Option<i64>,
+    min_reuse: f64,
+) -> Result<()> {
+    let session_id = session.map(|s| resolve_session_id(db, s)).transpose()?;
+    let group_by = match by {
+        CacheBy::Session => CostGroupBy::Session,
+        CacheBy::Project => CostGroupBy::Project,
+        CacheBy::Model => CostGroupBy::Model,
+        CacheBy::Day => CostGroupBy::Day,
+    };
+    let key_column = group_by.key_columns()[0];
+    let mut rows = db.cost_breakdown(group_by, session_id.as_deref(), days)?;
+    // Most expensive first, except days, which read better in order
+    if by == CacheBy::Day {
+        rows.sort_by(|a, b| a.keys.cmp(&b.keys));
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => print_human(&rows, key_column, days, min_reuse),
+        OutputFormat::Json => {
+            let objects: Vec<Value> = rows
+                .iter()
+                .map(|row| {
+                    let mut obj = Map::new();
+                    obj.insert(key_column.to_string(), json!(row.keys[0]));
+                    obj.insert("messages".into(), json!(row.messages));
+                    obj.insert("input_tokens".into(), json!(row.input_tokens));
+                    obj.insert("cache_creation_tokens".into(), json!(row.cache_creation_tokens));
+                    obj.insert("cache_read_tokens".into(), json!(row.cache_read_tokens));
+                    obj.insert("reuse".into(), json!(row.cache_reuse().map(|r| (r * 100.0).round() / 100.0)));
+                    obj.insert("hit_rate".into(), json!(row.cache_hit_rate().map(|r| (r * 10.0).round() / 10.0)));
+                    obj.insert("poor_reuse".into(), json!(poor_reuse(row, min_reuse)));
+                    Value::Object(obj)
+                })
+                .collect();
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&objects)?);
+            } else {
+                println!("{}", serde_json::to_string(&objects)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for row in &rows {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    row.keys[0],
+                    row.cache_creation_tokens,
+                    row.cache_read_tokens,
+                    row.cache_reuse().map(|r| format!("{:.2}", r)).unwrap_or_default()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The group wrote to the cache but read back less than `min_reuse` per token
+fn poor_reuse(row: &CostRow, min_reuse: f64) -> bool {
+    row.cache_reuse().is_some_and(|reuse| reuse < min_reuse)
+}
+
+fn print_human(rows: &[CostRow], key_column: &str, days: Option<i64>, min_reuse: f64) {
+    let title = match days {
+        Some(d) => format!("Prompt cache reuse (last {} days)", d),
+        None => "Prompt cache reuse (all time)".to_string(),
+    };
+    println!("{}", colors::header(&title));
+    println!();
+
+    if rows.is_empty() {
+        println!("{}", colors::label("No assistant usage found"));
+        return;
+    }
+
+    let headers = [key_column, "messages", "cache w", "cache r", "reuse", "hit rate"];
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|r| {
+            vec![
+                r.keys[0].clone(),
+                r.messages.to_string(),
+                format_count(r.cache_creation_tokens),
+                format_count(r.cache_read_tokens),
+                r.cache_reuse().map_or_else(|| "-".to_string(), |x| format!("{:.1}x", x)),
+                r.cache_hit_rate().map_or_else(|| "-".to_string(), |x| format!("{:.1}%", x)),
+            ]
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &cells {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    // Key left-aligned, numbers right-aligned
+    let format_row = |row: &[String]| -> String {
+        row.iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if i == 0 {
+                    format!("{:<w$}", c, w = widths[i])
+                } else {
+                    format!("{:>w$}", c, w = widths[i])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let header_cells: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    println!("{}", colors::header(format_row(&header_cells).trim_end()));
+    for (row, cells) in rows.iter().zip(&cells) {
+        let line = format_row(cells);
+        if poor_reuse(row, min_reuse) {
+            println!("{}  {}", line, colors::warning("poor reuse"));
+        } else {
+            println!("{}", line.trim_end());
+        }
+    }
+
+    let total = CostRow {
+        keys: Vec::new(),
+        sessions: 0,
+        messages: rows.iter().map(|r| r.messages).sum(),
+        input_tokens: rows.iter().map(|r| r.input_tokens).sum(),
+        output_tokens: rows.iter().map(|r| r.output_tokens).sum(),
+        cache_creation_tokens: rows.iter().map(|r| r.cache_creation_tokens).sum(),
+        cache_read_tokens: rows.iter().map(|r| r.cache_read_tokens).sum(),
+        cost_usd: 0.0,
+    };
+    let poor = rows.iter().filter(|r| poor_reuse(r, min_reuse)).count();
+    println!();
+    println!(
+        "{} {} reuse, {} of prompt tokens from cache",
+        colors::label("Total:"),
+        colors::value(&total.cache_reuse().map_or_else(|| "-".to_string(), |x| format!("{:.1}x", x))),
+        colors::value(&total.cache_hit_rate().map_or_else(|| "-".to_string(), |x| format!("{:.1}%", x)))
+    );
+    if poor > 0 {
+        println!(
+            "{}",
+            colors::warning(&format!(
+                "{} of {} read back less than {:.1}x what they wrote to the cache",
+                poor,
+                rows.len(),
+                min_reuse
+            ))
+        );
+    }
+}
+
+pub fn run_payloads(
+    cli: &Cli,
+    db: &TranscriptDb,
+    limit: usize,
+    session: Option<&str>,
+    tool: Option<&str>,
+) -> Result<()> {
+    let session_id = session.map(|s| resolve_session_id(db, s)).transpose()?;
+    let payloads = db.largest_payloads(limit, session_id.as_deref(), tool)?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => print_payloads(&payloads),
+        OutputFormat::Json => {
+            let objects: Vec<Value> = payloads
+                .iter()
+                .map(|p| {
+                    json!({
+                        "tool": p.tool_name,
+                        "direction": p.direction,
+                        "bytes": p.bytes,
+                        "sessionId": p.session_id,
+                        "slug": p.slug,
+                        "lineNumber": p.line_number,
+                        "toolUseId": p.tool_use_id,
+                        "large": p.bytes >= LARGE_PAYLOAD_BYTES,
+                    })
+                })
+                .collect();
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&objects)?);
+            } else {
+                println!("{}", serde_json::to_string(&objects)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for p in &payloads {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}",
+                    p.bytes,
+                    p.tool_name.as_deref().unwrap_or("?"),
+                    p.direction,
+                    p.session_id,
+                    p.line_number
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_payloads(payloads: &[ToolPayload]) {
+    println!("{}", colors::header("Largest tool payloads"));
+    println!();
+
+    if payloads.is_empty() {
+        println!(
+            "{}",
+            colors::label("No tool payloads recorded (sizes are recorded at index time: transcript index rebuild)")
+        );
+        return;
+    }
+
+    let headers = ["size", "tool", "dir", "session", "line"];
+    let cells: Vec<Vec<String>> = payloads
+        .iter()
+        .map(|p| {
+            vec![
+                format_size(p.bytes as u64),
+                p.tool_name.clone().unwrap_or_else(|| "?".to_string()),
+                p.direction.clone(),
+                p.slug.clone().unwrap_or_else(|| p.session_id.clone()),
+                p.line_number.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &cells {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    // Size and line right-aligned, names left-aligned
+    let format_row = |row: &[String]| -> String {
+        row.iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if i == 0 || i == 4 {
+                    format!("{:>w$}", c, w = widths[i])
+                } else {
+                    format!("{:<w$}", c, w = widths[i])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let header_cells: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    println!("{}", colors::header(format_row(&header_cells).trim_end()));
+    for (payload, cells) in payloads.iter().zip(&cells) {
+        let line = format_row(cells);
+        if payload.bytes >= LARGE_PAYLOAD_BYTES {
+            println!("{}  {}", line, colors::warning("large"));
+        } else {
+            println!("{}", line.trim_end());
+        }
+    }
+
+    let total: i64 = payloads.iter().map(|p| p.bytes).sum();
+    println!();
+    println!(
+        "{} {} in {} payloads",
+        colors::label("Total:"),
+        colors::value(&format_size(total as u64)),
+        payloads.len()
+    );
+}
+
+/// Width of the longest time-of-day bar
+const HOUR_BAR_WIDTH: usize = 30;
+
+pub fn run_errors(cli: &Cli, db: &TranscriptDb, days: i64, session: Option<&str>) -> Result<()> {
+    let session_id = session.map(|s| resolve_session_id(db, s)).transpose()?;
+    let failures = db.api_failures(session_id.as_deref(), Some(days))?;
+    let by_kind = count_by_kind(&failures);
+    let by_hour = count_by_hour(&failures);
+
+    match cli.effective_format() {
+        OutputFormat::Human => print_errors(&failures, &by_kind, &by_hour, days),
+        OutputFormat::Json => {
+            let kinds: Vec<Value> = by_kind
+                .iter()
+                .map(|(kind, k)| {
+                    json!({
+                        "kind": kind.name(),
+                        "count": k.count,
+                        "sessions": k.sessions.len(),
+                        "lastSeen": k.last_seen,
+                    })
+                })
+                .collect();
+            let output = json!({
+                "days": days,
+                "total": failures.len(),
+                "byKind": kinds,
+                "byHour": by_hour,
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for f in &failures {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    f.line.timestamp,
+                    f.kind.name(),
+                    f.line.session_id,
+                    f.line.line_number
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Failures of one kind
+#[derive(Debug, Default)]
+struct KindCount {
+    count: usize,
+    sessions: BTreeSet<String>,
+    last_seen: String,
+}
+
+fn count_by_kind(failures: &[ApiFailure]) -> BTreeMap<ApiFailureKind, KindCount> {
+    let mut by_kind: BTreeMap<ApiFailureKind, KindCount> = BTreeMap::new();
+    for f in failures {
+        let entry = by_kind.entry(f.kind).or_default();
+        entry.count += 1;
+        entry.sessions.insert(f.line.session_id.clone());
+        if f.line.timestamp > entry.last_seen {
+            entry.last_seen = f.line.timestamp.clone();
+        }
+    }
+    by_kind
+}
+
+/// Failures per local hour of the day (unparseable timestamps are left out)
+fn count_by_hour(failures: &[ApiFailure]) -> [usize; 24] {
+    let mut by_hour = [0; 24];
+    for f in failures {
+        if let Ok(time) = DateTime::parse_from_rfc3339(&f.line.timestamp) {
+            by_hour[time.with_timezone(&Local).hour() as usize] += 1;
+        }
+    }
+    by_hour
+}
+
+fn print_errors(
+    failures: &[ApiFailure],
+    by_kind: &BTreeMap<ApiFailureKind, KindCount>,
+    by_hour: &[usize; 24],
+    days: i64,
+) {
+    println!("{}", colors::header(&format!("API failures (last {} days)", days)));
+    println!();
+
+    if failures.is_empty() {
+        println!("{}", colors::success("No API errors, rate limits or overloads"));
+        return;
+    }
+
+    println!(
+        "{}",
+        colors::header(&format!("{:<12} {:>6} {:>9}  {}", "kind", "count", "sessions", "last seen"))
+    );
+    for kind in ApiFailureKind::ALL {
+        let Some(k) = by_kind.get(&kind) else {
+            continue;
+        };
+        println!(
+            "{:<12} {:>6} {:>9}  {}",
+            kind.name(),
+            format_count(k.count as i64),
+            k.sessions.len(),
+            k.last_seen.get(..16).unwrap_or(&k.last_seen).replace('T', " ")
+        );
+    }
+
+    println!();
+    println!("{}", colors::header("By time of day (local)"));
+    let max = by_hour.iter().copied().max().unwrap_or(0).max(1);
+    for (hour, &count) in by_hour.iter().enumerate() {
+        if count == 0 {
+            println!("  {:02}:00", hour);
+            continue;
+        }
+        let filled = (count * HOUR_BAR_WIDTH).div_ceil(max);
+        println!("  {:02}:00 {} {}", hour, "█".repeat(filled).red(), count);
+    }
+
+    println!();
+    println!(
+        "{} {} failures",
+        colors::label("Total:"),
+        colors::value(&format_count(failures.len() as i64))
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(input: i64, written: i64, read: i64) -> CostRow {
+        CostRow {
+            keys: vec!["s1".to_string()],
+            sessions: 1,
+            messages: 1,
+            input_tokens: input,
+            output_tokens: 0,
+            cache_creation_tokens: written,
+            cache_read_tokens: read,
+            cost_usd: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_poor_reuse() {
+        let good = row(0, 10_000, 90_000);
+        assert_eq!(good.cache_reuse(), Some(9.0));
+        assert_eq!(good.cache_hit_rate(), Some(90.0));
+        assert!(!poor_reuse(&good, 1.0));
+
+        assert!(poor_reuse(&row(1_000, 50_000, 10_000), 1.0));
+        // Nothing written: no ratio, nothing to flag
+        let uncached = row(5_000, 0, 0);
+        assert_eq!((uncached.cache_reuse(), uncached.cache_hit_rate()), (None, Some(0.0)));
+        assert!(!poor_reuse(&uncached, 1.0));
+    }
+}
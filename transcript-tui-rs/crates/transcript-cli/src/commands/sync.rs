@@ -0,0 +1,419 @@
+//! Sync command - share database snapshots through S3, SSH or a shared directory
+//!
+//! Each machine pushes a snapshot named after itself (`<name>.db`), so pushes
+//! never overwrite each other. Pulling downloads every other snapshot and
+//! merges it into the local database; merging is idempotent, so repeated
+//! pulls only add what is new. With `--federate` the snapshots are kept as-is
+//! in the team directory instead, for `--team` queries that report each hit's
+//! origin.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use transcript_db::TranscriptDb;
+use transcript_indexer::MergeResult;
+
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::index::open_indexer;
+use crate::output::colors;
+
+/// Where snapshots are stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Remote {
+    /// `s3://bucket/prefix` (via the `aws` CLI)
+    S3(String),
+    /// `[user@]host:/path` or `ssh://[user@]host/path` (via `ssh` + `rsync`)
+    Ssh { host: String, path: String },
+    /// A local or network-mounted directory
+    Dir(PathBuf),
+}
+
+impl Remote {
+    fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim().trim_end_matches('/');
+        if spec.is_empty() {
+            bail!("Remote is empty");
+        }
+
+        if spec.starts_with("s3://") {
+            return Ok(Remote::S3(spec.to_string()));
+        }
+        if let Some(rest) = spec.strip_prefix("ssh://") {
+            let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+            return Ok(Remote::Ssh {
+                host: host.to_string(),
+                path: format!("/{}", path),
+            });
+        }
+        if let Some(rest) = spec.strip_prefix("file://") {
+            return Ok(Remote::Dir(PathBuf::from(rest)));
+        }
+        // host:path (but not a bare absolute/relative path, or a Windows
+        // drive such as `C:\backup`)
+        if let Some((host, path)) = spec.split_once(':') {
+            let is_drive = host.len() == 1 && host.chars().all(|c| c.is_ascii_alphabetic());
+            if !host.is_empty() && !host.contains(['/', '\\']) && !is_drive {
+                return Ok(Remote::Ssh {
+                    host: host.to_string(),
+                    path: path.to_string(),
+                });
+            }
+        }
+        Ok(Remote::Dir(PathBuf::from(spec)))
+    }
+
+    fn display(&self) -> String {
+        match self {
+            Remote::S3(url) => url.clone(),
+            Remote::Ssh { host, path } => format!("{}:{}", host, path),
+            Remote::Dir(dir) => dir.display().to_string(),
+        }
+    }
+
+    /// Upload a file as `<remote>/<name>`
+    fn upload(&self, file: &Path, name: &str) -> Result<()> {
+        match self {
+            Remote::S3(url) => run_tool(
+                Command::new("aws")
+                    .args(["s3", "cp", "--only-show-errors"])
+                    .arg(file)
+                    .arg(format!("{}/{}", url, name)),
+            ),
+            Remote::Ssh { host, path } => {
+                run_tool(Command::new("ssh").arg(host).arg(format!("mkdir -p {}", shell_quote(path))))?;
+                run_tool(
+                    Command::new("rsync")
+                        .args(["-az", "--partial"])
+                        .arg(file)
+                        .arg(format!("{}:{}/{}", host, path, name)),
+                )
+            }
+            Remote::Dir(dir) => {
+                std::fs::create_dir_all(dir)?;
+                // Copy then rename so readers never see a partial snapshot
+                let partial = dir.join(format!(".{}.partial", name));
+                std::fs::copy(file, &partial)?;
+                std::fs::rename(&partial, dir.join(name))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Download every `*.db` snapshot into `staging`
+    fn download_all(&self, staging: &Path) -> Result<()> {
+        match self {
+            Remote::S3(url) => run_tool(
+                Command::new("aws")
+                    .args(["s3", "cp", "--only-show-errors", "--recursive"])
+                    .args(["--exclude", "*", "--include", "*.db"])
+                    .arg(format!("{}/", url))
+                    .arg(staging),
+            ),
+            Remote::Ssh { host, path } => run_tool(
+                Command::new("rsync")
+                    .args(["-az", "--include=*.db", "--exclude=*"])
+                    .arg(format!("{}:{}/", host, path))
+                    .arg(staging),
+            ),
+            Remote::Dir(dir) => {
+                if !dir.exists() {
+                    bail!("Remote directory not found: {}", dir.display());
+                }
+                for entry in std::fs::read_dir(dir)? {
+                    let path = entry?.path();
+                    if path.extension().is_some_and(|e| e == "db") {
+                        if let Some(name) = path.file_name() {
+                            std::fs::copy(&path, staging.join(name))?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Quote a word for a POSIX shell (the remote end of `ssh`)
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// Run an external tool, failing with its stderr
+fn run_tool(command: &mut Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command
+        .output()
+        .with_context(|| format!("Failed to run '{}' (is it installed and on PATH?)", program))?;
+    if !output.status.success() {
+        bail!(
+            "'{}' failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Default snapshot name: `<hostname>-<user>`
+fn default_snapshot_name() -> String {
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "user".to_string());
+    format!("{}-{}", host, user)
+}
+
+/// Snapshot name as a safe file name
+fn snapshot_file(name: Option<&str>) -> String {
+    let name = name.map(String::from).unwrap_or_else(default_snapshot_name);
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect();
+    format!("{}.db", safe)
+}
+
+/// Private scratch directory with an unpredictable name, removed on drop
+///
+/// It holds whole database snapshots, so only its owner may read it.
+fn staging(label: &str) -> Result<tempfile::TempDir> {
+    let prefix = format!("transcript-sync-{}-", label);
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix);
+    #[cfg(unix)]
+    builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o700));
+    builder.tempdir().context("Failed to create a staging directory")
+}
+
+/// Upload a snapshot of the local database
+pub fn push(cli: &Cli, db: &TranscriptDb, remote: &str, name: Option<&str>) -> Result<()> {
+    let remote = Remote::parse(remote)?;
+    let file_name = snapshot_file(name);
+
+    let staging = staging("push")?;
+    let snapshot = staging.path().join(&file_name);
+
+    // VACUUM INTO produces a consistent, compacted copy even while indexing runs
+    db.connection()
+        .execute("VACUUM INTO ?1", [snapshot.to_string_lossy()])
+        .context("Failed to snapshot database")?;
+    let size = std::fs::metadata(&snapshot).map(|m| m.len()).unwrap_or(0);
+
+    remote.upload(&snapshot, &file_name)?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{}",
+                colors::success(&format!(
+                    "Pushed {} ({}) to {}",
+                    file_name,
+                    colors::format_size(size),
+                    remote.display()
+                ))
+            );
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "remote": remote.display(),
+                "snapshot": file_name,
+                "bytes": size,
+            });
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        OutputFormat::Minimal => println!("{}", file_name),
+    }
+
+    Ok(())
+}
+
+/// Download other machines' snapshots and merge them into the local database
+pub fn pull(cli: &Cli, remote: &str, name: Option<&str>, federate: bool) -> Result<()> {
+    let remote = Remote::parse(remote)?;
+    let own_file = snapshot_file(name);
+
+    let staging = staging("pull")?;
+    remote.download_all(staging.path())?;
+
+    let mut snapshots: Vec<PathBuf> = std::fs::read_dir(staging.path())?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "db"))
+        .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy() != own_file))
+        .collect();
+    snapshots.sort();
+
+    if federate {
+        return store_team_snapshots(cli, &remote, &snapshots);
+    }
+
+    let mut indexer_db = open_indexer(cli)?;
+
+    let mut merged: Vec<(String, MergeResult)> = Vec::new();
+    for snapshot in &snapshots {
+        let label = snapshot
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let result = transcript_indexer::merge_database(indexer_db.connection_mut(), snapshot)
+            .with_context(|| format!("Failed to merge snapshot {}", label))?;
+        merged.push((label, result));
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if merged.is_empty() {
+                println!("No snapshots from other machines at {}", remote.display());
+            }
+            for (label, r) in &merged {
+                println!(
+                    "  {} {}: +{} lines, +{} sessions, +{} hook events",
+                    colors::success("Merged"),
+                    colors::value(label),
+                    colors::format_count(r.lines as i64),
+                    r.sessions,
+                    colors::format_count(r.hook_events as i64)
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let output: Vec<serde_json::Value> = merged
+                .iter()
+                .map(|(label, r)| {
+                    serde_json::json!({
+                        "snapshot": label,
+                        "lines": r.lines,
+                        "sessions": r.sessions,
+                        "hook_events": r.hook_events,
+                        "hook_files": r.hook_files,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        OutputFormat::Minimal => {
+            for (label, r) in &merged {
+                println!("{}\t{}\t{}\t{}", label, r.lines, r.sessions, r.hook_events);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace the team directory's snapshots with freshly pulled ones
+fn store_team_snapshots(cli: &Cli, remote: &Remote, snapshots: &[PathBuf]) -> Result<()> {
+    let team_dir = transcript_db::default_team_dir();
+    std::fs::create_dir_all(&team_dir)
+        .with_context(|| format!("Failed to create {}", team_dir.display()))?;
+
+    let mut stored = Vec::new();
+    for snapshot in snapshots {
+        let Some(file_name) = snapshot.file_name() else {
+            continue;
+        };
+        let partial = team_dir.join(format!(".{}.partial", file_name.to_string_lossy()));
+        std::fs::copy(snapshot, &partial)?;
+        std::fs::rename(&partial, team_dir.join(file_name))?;
+        stored.push(
+            snapshot
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        );
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if stored.is_empty() {
+                println!("No snapshots from other machines at {}", remote.display());
+            } else {
+                println!(
+                    "{} {} snapshot(s) in {}: {}",
+                    colors::success("Stored"),
+                    stored.len(),
+                    team_dir.display(),
+                    stored.join(", ")
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "team_dir": team_dir.display().to_string(),
+                "snapshots": stored,
+            });
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        OutputFormat::Minimal => {
+            for origin in &stored {
+                println!("{}", origin);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_parse() {
+        assert_eq!(
+            Remote::parse("s3://bucket/team/").unwrap(),
+            Remote::S3("s3://bucket/team".to_string())
+        );
+        assert_eq!(
+            Remote::parse("me@box:/srv/transcripts").unwrap(),
+            Remote::Ssh {
+                host: "me@box".to_string(),
+                path: "/srv/transcripts".to_string()
+            }
+        );
+        assert_eq!(
+            Remote::parse("ssh://box/srv/t").unwrap(),
+            Remote::Ssh {
+                host: "box".to_string(),
+                path: "/srv/t".to_string()
+            }
+        );
+        assert_eq!(
+            Remote::parse("/mnt/share").unwrap(),
+            Remote::Dir(PathBuf::from("/mnt/share"))
+        );
+        // Drive letters are local paths, not one-letter hosts
+        assert_eq!(
+            Remote::parse(r"C:\backup").unwrap(),
+            Remote::Dir(PathBuf::from(r"C:\backup"))
+        );
+        assert_eq!(Remote::parse("d:/team").unwrap(), Remote::Dir(PathBuf::from("d:/team")));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/srv/transcripts"), "'/srv/transcripts'");
+        assert_eq!(shell_quote("/srv/it's here"), r"'/srv/it'\''s here'");
+        // A quote can't end the word early and run the rest as a command
+        assert_eq!(shell_quote("x'; rm -rf ~; '"), r"'x'\''; rm -rf ~; '\'''");
+    }
+    #[test]
+    fn test_staging_dirs_are_private() {
+        let (first, second) = (staging("pull").unwrap(), staging("pull").unwrap());
+        // Two runs (or another user) never share a directory
+        assert_ne!(first.path(), second.path());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(first.path()).unwrap().permissions().mode() & 0o777, 0o700);
+        }
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+    }
+}
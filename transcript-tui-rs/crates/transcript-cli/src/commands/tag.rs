@@ -0,0 +1,124 @@
+//! Tag command - label sessions to find them later
+
+use anyhow::{bail, Result};
+use serde_json::json;
+use transcript_db::TranscriptDb;
+use transcript_indexer::IndexerDb;
+
+use crate::cli::{Cli, OutputFormat, TagCommand};
+use crate::commands::resolve_session_id;
+use crate::output::colors;
+
+pub fn run(cli: &Cli, db: &TranscriptDb, command: &TagCommand) -> Result<()> {
+    match command {
+        TagCommand::Add { session, tag } => {
+            let tag = validate_tag(tag)?;
+            let session_id = resolve_session_id(db, session)?;
+            // Tags are written through the indexer, which owns all writes
+            let indexer_db = IndexerDb::open_or_create(db.path())?;
+            let changed = transcript_indexer::add_tag(indexer_db.connection(), &session_id, tag)?;
+            report(cli, "added", &session_id, tag, changed)
+        }
+        TagCommand::Remove { session, tag } => {
+            let session_id = resolve_session_id(db, session)?;
+            let indexer_db = IndexerDb::open_or_create(db.path())?;
+            let changed =
+                transcript_indexer::remove_tag(indexer_db.connection(), &session_id, tag.trim())?;
+            report(cli, "removed", &session_id, tag.trim(), changed)
+        }
+        TagCommand::List { session: Some(session) } => {
+            let session_id = resolve_session_id(db, session)?;
+            let tags = db.get_tags(&session_id)?;
+            match cli.effective_format() {
+                OutputFormat::Human => {
+                    if tags.is_empty() {
+                        println!("No tags on {}", session);
+                    } else {
+                        println!("{}", tags.join(", "));
+                    }
+                }
+                OutputFormat::Json => print_json(cli, &json!(tags))?,
+                OutputFormat::Minimal => {
+                    for tag in &tags {
+                        println!("{}", tag);
+                    }
+                }
+            }
+            Ok(())
+        }
+        TagCommand::List { session: None } => {
+            let tags = db.get_tag_counts()?;
+            match cli.effective_format() {
+                OutputFormat::Human => {
+                    if tags.is_empty() {
+                        println!("No tags yet (add one with: transcript tag add <session> <tag>)");
+                    } else {
+                        println!("{}", colors::header(&format!("Tags ({})", tags.len())));
+                        println!();
+                        for (tag, count) in &tags {
+                            println!(
+                                "  {} {}",
+                                tag,
+                                colors::label(&format!("({} sessions)", count))
+                            );
+                        }
+                    }
+                }
+                OutputFormat::Json => {
+                    let value: Vec<_> = tags
+                        .iter()
+                        .map(|(tag, count)| json!({ "tag": tag, "sessions": count }))
+                        .collect();
+                    print_json(cli, &json!(value))?;
+                }
+                OutputFormat::Minimal => {
+                    for (tag, count) in &tags {
+                        println!("{}\t{}", tag, count);
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Tags are single words so they can be typed after `--tag` unquoted
+fn validate_tag(tag: &str) -> Result<&str> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        bail!("Tag is empty");
+    }
+    if tag.chars().any(|c| c.is_whitespace() || c == ',') {
+        bail!("Invalid tag '{}': tags can't contain spaces or commas", tag);
+    }
+    Ok(tag)
+}
+
+fn report(cli: &Cli, action: &str, session_id: &str, tag: &str, changed: bool) -> Result<()> {
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            let message = match (action, changed) {
+                ("added", true) => format!("Tagged {} '{}'", session_id, tag),
+                ("added", false) => format!("{} is already tagged '{}'", session_id, tag),
+                (_, true) => format!("Removed '{}' from {}", tag, session_id),
+                (_, false) => format!("{} was not tagged '{}'", session_id, tag),
+            };
+            println!("{}", message);
+        }
+        OutputFormat::Json => print_json(
+            cli,
+            &json!({ "sessionId": session_id, "tag": tag, action: changed }),
+        )?,
+        OutputFormat::Minimal => println!("{}", changed),
+    }
+    Ok(())
+}
+
+fn print_json(cli: &Cli, value: &serde_json::Value) -> Result<()> {
+    if cli.pretty {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", serde_json::to_string(value)?);
+    }
+    Ok(())
+}
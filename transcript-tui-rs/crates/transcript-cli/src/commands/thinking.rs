@@ -0,0 +1,228 @@
+//! Thinking command - a session's thinking blocks, and how much of each
+//! turn's output they took
+//!
+//! Usage reports output tokens with thinking included, so a block's share is
+//! estimated from its length (about four characters a token). Each response's
+//! usage counts once, however many lines it was written as.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+use transcript_core::{response_usage, TranscriptLine};
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use super::export::markdown::{fence, plain_text, write_document};
+use super::turn::split_turns;
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors;
+
+/// One thinking block
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThinkingBlock {
+    pub timestamp: String,
+    pub text: String,
+    /// Estimated from the text's length
+    pub tokens: u64,
+    /// Whether the block carries a signature (so can be sent back to the API)
+    pub signed: bool,
+}
+
+/// A turn's thinking blocks against its output tokens
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThinkingTurn {
+    pub turn: usize,
+    pub started: String,
+    pub prompt: Option<String>,
+    pub thinking_tokens: u64,
+    pub output_tokens: u64,
+    pub blocks: Vec<ThinkingBlock>,
+}
+
+impl ThinkingTurn {
+    /// Percentage of the turn's output spent thinking, when it has output
+    pub fn share(&self) -> Option<f64> {
+        (self.output_tokens > 0).then(|| self.thinking_tokens as f64 * 100.0 / self.output_tokens as f64)
+    }
+}
+
+pub fn run(cli: &Cli, db: &TranscriptDb, session: &str, output: Option<&Path>) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+    let turns = thinking_turns(&lines);
+
+    // A file to write, or --format md, exports the blocks for review
+    if output.is_some() || cli.wants_markdown() {
+        let with_thinking = turns.iter().filter(|t| !t.blocks.is_empty()).count();
+        return write_document(cli, &session_id, &render_markdown(&session_id, &turns), with_thinking, output);
+    }
+
+    match cli.effective_format() {
+        OutputFormat::Human => print_human(&session_id, &turns),
+        OutputFormat::Json => {
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&turns)?);
+            } else {
+                println!("{}", serde_json::to_string(&turns)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for turn in &turns {
+                println!("{}\t{}\t{}\t{}", turn.turn, turn.blocks.len(), turn.thinking_tokens, turn.output_tokens);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Thinking blocks and output tokens of each turn of a session
+pub fn thinking_turns(lines: &[TranscriptLine]) -> Vec<ThinkingTurn> {
+    split_turns(lines)
+        .into_iter()
+        .map(|(number, turn)| {
+            let mut result = ThinkingTurn {
+                turn: number,
+                started: turn.first().map(|l| l.timestamp.clone()).unwrap_or_default(),
+                prompt: None,
+                thinking_tokens: 0,
+                output_tokens: 0,
+                blocks: Vec::new(),
+            };
+            for (line, usage) in turn.iter().zip(response_usage(turn)) {
+                let Some(message) = line.message() else {
+                    continue;
+                };
+                if line.is_prompt() && result.prompt.is_none() {
+                    result.prompt = Some(plain_text(message.content.as_text().trim()));
+                    continue;
+                }
+                if let Some(usage) = usage {
+                    result.output_tokens += usage.output_tokens;
+                }
+                for (text, signature) in message.content.thinking_blocks() {
+                    if text.trim().is_empty() {
+                        continue;
+                    }
+                    let tokens = estimate_tokens(text);
+                    result.thinking_tokens += tokens;
+                    result.blocks.push(ThinkingBlock {
+                        timestamp: line.timestamp.clone(),
+                        text: text.to_string(),
+                        tokens,
+                        signed: signature.is_some_and(|s| !s.is_empty()),
+                    });
+                }
+            }
+            result
+        })
+        .collect()
+}
+
+/// Rough token count of English text or code: a token per four characters
+fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(4) as u64
+}
+
+fn print_human(session_id: &str, turns: &[ThinkingTurn]) {
+    println!("{} {}", colors::header("Thinking"), colors::colored_session(session_id));
+    println!();
+    for turn in turns {
+        let share = turn.share().map(|s| format!(" ({:.0}%)", s)).unwrap_or_default();
+        println!(
+            "  {} {}  {} block(s)  ~{} of {} output tokens{}",
+            colors::label(&format!("Turn {:>3}", turn.turn)),
+            colors::colored_time(&turn.started),
+            turn.blocks.len(),
+            colors::format_count(turn.thinking_tokens as i64),
+            colors::format_count(turn.output_tokens as i64),
+            share
+        );
+    }
+
+    let thinking: u64 = turns.iter().map(|t| t.thinking_tokens).sum();
+    let output: u64 = turns.iter().map(|t| t.output_tokens).sum();
+    let blocks: usize = turns.iter().map(|t| t.blocks.len()).sum();
+    println!();
+    if blocks == 0 {
+        println!("{}", colors::warning("No thinking blocks in this session"));
+        return;
+    }
+    println!(
+        "{} {} block(s), ~{} of {} output tokens",
+        colors::label("Total:"),
+        blocks,
+        colors::value(&colors::format_count(thinking as i64)),
+        colors::format_count(output as i64)
+    );
+}
+
+/// The thinking blocks as a Markdown document, one section per turn with any
+pub fn render_markdown(session_id: &str, turns: &[ThinkingTurn]) -> String {
+    let mut out = format!("# Thinking: {}\n\n", session_id);
+    for turn in turns.iter().filter(|t| !t.blocks.is_empty()) {
+        let _ = writeln!(out, "## Turn {}\n", turn.turn);
+        let share = turn.share().map(|s| format!(" ({:.0}%)", s)).unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "*{} · ~{} thinking of {} output tokens{}*\n",
+            turn.started, turn.thinking_tokens, turn.output_tokens, share
+        );
+        if let Some(prompt) = &turn.prompt {
+            for l in prompt.lines() {
+                let _ = writeln!(out, "> {}", l);
+            }
+            out.push('\n');
+        }
+        for (i, block) in turn.blocks.iter().enumerate() {
+            let _ = writeln!(out, "### Block {} (~{} tokens)\n", i + 1, block.tokens);
+            let _ = writeln!(out, "{}\n", fence(&plain_text(block.text.trim()), ""));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use transcript_core::LineType;
+
+    fn line(line_type: LineType, raw: serde_json::Value) -> TranscriptLine {
+        TranscriptLine {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_thinking_per_turn() {
+        let lines = vec![
+            line(LineType::User, json!({"message": {"role": "user", "content": "Fix the bug"}})),
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "The bug is in the parser.", "signature": "abc"},
+                {"type": "text", "text": "Fixed."}
+            ], "usage": {"input_tokens": 10, "output_tokens": 20}}})),
+            line(LineType::User, json!({"message": {"role": "user", "content": "Thanks"}})),
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "text", "text": "You're welcome."}
+            ], "usage": {"input_tokens": 10, "output_tokens": 5}}})),
+        ];
+
+        let turns = thinking_turns(&lines);
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].prompt.as_deref(), Some("Fix the bug"));
+        assert_eq!((turns[0].thinking_tokens, turns[0].output_tokens), (7, 20));
+        assert!(turns[0].blocks[0].signed);
+        assert_eq!((turns[1].blocks.len(), turns[1].output_tokens), (0, 5));
+
+        let markdown = render_markdown("s1", &turns);
+        assert!(markdown.contains("## Turn 1"));
+        assert!(markdown.contains("The bug is in the parser."));
+        assert!(!markdown.contains("## Turn 2"));
+    }
+}
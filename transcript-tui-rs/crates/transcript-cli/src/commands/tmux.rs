@@ -0,0 +1,189 @@
+//! Tmux command - the viewer beside Claude Code, and a status-line snippet
+//!
+//! `tmux open` splits the Claude Code pane of the current window (found by
+//! its running command) and follows the session in the viewer there.
+//! `tmux status` prints one short line for `status-right`:
+//!
+//! ```text
+//! set -g status-right '#(transcript tmux status --cwd "#{pane_current_path}")'
+//! ```
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde_json::json;
+use transcript_db::TranscriptDb;
+
+use crate::cli::{Cli, FormatArg, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors;
+
+/// A pane of the current tmux window
+struct Pane {
+    id: String,
+    active: bool,
+    command: String,
+    path: String,
+}
+
+pub fn open(cli: &Cli, db: &TranscriptDb, session: &str, below: bool, size: u8) -> Result<()> {
+    if std::env::var_os("TMUX").is_none() {
+        bail!("Not inside tmux");
+    }
+
+    // The Claude Code pane, or else the active one
+    let panes = list_panes()?;
+    let pane = panes
+        .iter()
+        .filter(|p| p.command.to_lowercase().contains("claude"))
+        .max_by_key(|p| p.active)
+        .or_else(|| panes.iter().find(|p| p.active))
+        .context("No tmux pane to split")?;
+
+    let session_id = if session == "." {
+        match db.latest_session_in(&pane.path)? {
+            Some(info) => info.session_id,
+            None => bail!("No indexed session in {} (run: transcript index update)", pane.path),
+        }
+    } else {
+        resolve_session_id(db, session)?
+    };
+
+    // This binary, so the pane runs the same version on the same database
+    let exe = std::env::current_exe()?;
+    let mut viewer = vec![exe.to_string_lossy().to_string()];
+    if let Some(db_path) = &cli.db_path {
+        viewer.push("--db-path".to_string());
+        viewer.push(db_path.to_string_lossy().to_string());
+    }
+    viewer.extend(["tui".to_string(), session_id.clone(), "--live".to_string()]);
+    let viewer: Vec<String> = viewer.iter().map(|arg| shell_quote(arg)).collect();
+
+    let output = Command::new("tmux")
+        .args(["split-window", if below { "-v" } else { "-h" }, "-P", "-F", "#{pane_id}"])
+        .args(["-l", &format!("{}%", size), "-t", &pane.id, "-c", &pane.path])
+        .arg(viewer.join(" "))
+        .output()
+        .context("Failed to run tmux")?;
+    if !output.status.success() {
+        bail!("tmux split-window failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let new_pane = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            println!(
+                "{} {} in pane {} beside {}",
+                colors::success("Opened"),
+                colors::colored_session(&session_id),
+                new_pane,
+                pane.id
+            );
+        }
+        OutputFormat::Json => println!(
+            "{}",
+            json!({ "session_id": session_id, "pane": new_pane, "beside": pane.id })
+        ),
+        OutputFormat::Minimal => println!("{}", new_pane),
+    }
+    Ok(())
+}
+
+pub fn status(cli: &Cli, db: &TranscriptDb, cwd: Option<&Path>, context_size: Option<u64>) -> Result<()> {
+    let cwd = match cwd {
+        Some(cwd) => cwd.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    let Some(session) = db.latest_session_in(&cwd.to_string_lossy())? else {
+        return Ok(());
+    };
+    let percent = db
+        .context_usage_by_turn(&session.session_id, context_size)?
+        .last()
+        .map_or(0.0, |turn| turn.percent);
+    let name = session
+        .slug
+        .clone()
+        .unwrap_or_else(|| session.session_id.chars().take(8).collect());
+
+    // Status lines run without a terminal, so JSON only when asked for
+    if cli.json || cli.format == Some(FormatArg::Json) {
+        println!(
+            "{}",
+            json!({
+                "session_id": session.session_id,
+                "name": name,
+                "context_percent": (percent * 10.0).round() / 10.0,
+            })
+        );
+    } else {
+        println!("{}", status_text(&name, percent, !cli.no_color));
+    }
+    Ok(())
+}
+
+/// `name 42%`, in tmux style tags once the context is nearly full
+fn status_text(name: &str, percent: f64, styled: bool) -> String {
+    let text = format!("{} {:.0}%", name, percent);
+    let color = match percent {
+        p if p >= 90.0 => "red",
+        p if p >= 80.0 => "yellow",
+        _ => return text,
+    };
+    if styled {
+        format!("#[fg={}]{}#[default]", color, text)
+    } else {
+        text
+    }
+}
+
+fn list_panes() -> Result<Vec<Pane>> {
+    let output = Command::new("tmux")
+        .args([
+            "list-panes",
+            "-F",
+            "#{pane_id}\t#{pane_active}\t#{pane_current_command}\t#{pane_current_path}",
+        ])
+        .output()
+        .context("Failed to run tmux")?;
+    if !output.status.success() {
+        bail!("tmux list-panes failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            Some(Pane {
+                id: fields.next()?.to_string(),
+                active: fields.next()? == "1",
+                command: fields.next()?.to_string(),
+                path: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Quote an argument for the shell tmux runs the pane command in
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+".contains(c)) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_text() {
+        assert_eq!(status_text("tender-spider", 42.4, true), "tender-spider 42%");
+        assert_eq!(status_text("tender-spider", 85.0, true), "#[fg=yellow]tender-spider 85%#[default]");
+        assert_eq!(status_text("tender-spider", 95.0, true), "#[fg=red]tender-spider 95%#[default]");
+        assert_eq!(status_text("tender-spider", 95.0, false), "tender-spider 95%");
+        assert_eq!(shell_quote("/usr/bin/transcript"), "/usr/bin/transcript");
+        assert_eq!(shell_quote("it's here"), r"'it'\''s here'");
+    }
+}
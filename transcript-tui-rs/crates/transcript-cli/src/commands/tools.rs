@@ -0,0 +1,279 @@
+//! Tools command - per-tool call counts, failure rates, durations and edited files
+//!
+//! Calls and results come from transcript lines, durations from the
+//! PreToolUse/PostToolUse hook events; the two are joined on tool_use_id.
+//! Sessions recorded without hooks still get counts and failure rates.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use chrono::DateTime;
+use serde_json::json;
+use transcript_core::{edited_file, ContentBlock, LineType, MessageContent, TranscriptLine};
+use transcript_db::{ToolTiming, TranscriptDb};
+
+use super::index::abbreviate_path;
+use super::resolve_session_id;
+use crate::cli::{Cli, OutputFormat};
+use crate::output::colors::{self, format_count};
+
+/// Statistics for one tool
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ToolStats {
+    pub name: String,
+    pub calls: usize,
+    pub failures: usize,
+    /// Calls with both hook timestamps
+    pub timed_calls: usize,
+    pub mean_duration_ms: Option<f64>,
+}
+
+impl ToolStats {
+    pub fn failure_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.calls as f64 * 100.0
+        }
+    }
+}
+
+/// Everything the report shows
+#[derive(Debug, Default)]
+pub struct ToolReport {
+    /// Most used first
+    pub tools: Vec<ToolStats>,
+    /// (path, edits), most edited first
+    pub files: Vec<(String, usize)>,
+}
+
+pub fn run(cli: &Cli, db: &TranscriptDb, session: Option<&str>, top_files: usize) -> Result<()> {
+    let session_id = session.map(|s| resolve_session_id(db, s)).transpose()?;
+    let lines = db.get_tool_lines(session_id.as_deref())?;
+    let timings = db.get_tool_timings(session_id.as_deref())?;
+    let mut report = aggregate(&lines, &timings);
+    report.files.truncate(top_files);
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            let scope = match &session_id {
+                Some(id) => colors::colored_session(id),
+                None => "all sessions".to_string(),
+            };
+            println!("{} {}", colors::header("Tool usage:"), scope);
+            if report.tools.is_empty() {
+                println!("No tool calls found");
+                return Ok(());
+            }
+            println!();
+            println!(
+                "  {}",
+                colors::label(&format!(
+                    "{:<20} {:>7} {:>7} {:>7} {:>10}",
+                    "Tool", "Calls", "Failed", "Rate", "Mean time"
+                ))
+            );
+            for tool in &report.tools {
+                println!(
+                    "  {:<20} {:>7} {:>7} {:>6.1}% {:>10}",
+                    tool.name,
+                    format_count(tool.calls as i64),
+                    format_count(tool.failures as i64),
+                    tool.failure_rate(),
+                    tool.mean_duration_ms.map(format_ms).unwrap_or_else(|| "-".to_string())
+                );
+            }
+            if !report.files.is_empty() {
+                println!();
+                println!("{}", colors::header("Most edited files"));
+                for (path, edits) in &report.files {
+                    println!("  {:>5}  {}", format_count(*edits as i64), abbreviate_path(path));
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let tools: Vec<_> = report
+                .tools
+                .iter()
+                .map(|t| {
+                    json!({
+                        "name": t.name,
+                        "calls": t.calls,
+                        "failures": t.failures,
+                        "failure_rate": (t.failure_rate() * 10.0).round() / 10.0,
+                        "timed_calls": t.timed_calls,
+                        "mean_duration_ms": t.mean_duration_ms.map(f64::round),
+                    })
+                })
+                .collect();
+            let files: Vec<_> = report
+                .files
+                .iter()
+                .map(|(path, edits)| json!({ "path": path, "edits": edits }))
+                .collect();
+            let value = json!({ "session_id": session_id, "tools": tools, "files": files });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                println!("{}", serde_json::to_string(&value)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for tool in &report.tools {
+                println!("{}\t{}\t{}", tool.name, tool.calls, tool.failures);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Join tool calls, their results and their hook timings by tool_use_id
+pub fn aggregate(lines: &[TranscriptLine], timings: &[ToolTiming]) -> ToolReport {
+    // Tool name of each call, by tool_use_id
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut stats: BTreeMap<String, ToolStats> = BTreeMap::new();
+    let mut files: HashMap<String, usize> = HashMap::new();
+    let mut failed: Vec<String> = Vec::new();
+
+    for line in lines {
+        let Some(msg) = line.message() else {
+            continue;
+        };
+        match line.line_type {
+            LineType::Assistant => {
+                for (id, name, input) in msg.content.tool_uses() {
+                    names.insert(id.to_string(), name.to_string());
+                    stats.entry(name.to_string()).or_default().calls += 1;
+                    if let Some(path) = edited_file(name, input) {
+                        *files.entry(path.to_string()).or_default() += 1;
+                    }
+                }
+            }
+            LineType::User => {
+                let MessageContent::Blocks(blocks) = msg.content else {
+                    continue;
+                };
+                for block in blocks {
+                    if let ContentBlock::ToolResult { tool_use_id, is_error: true, .. } = block {
+                        failed.push(tool_use_id);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Durations in ms per tool. Calls only seen by hooks (transcript not
+    // indexed) are counted too, named by the hook event.
+    let mut durations: HashMap<String, Vec<f64>> = HashMap::new();
+    for timing in timings {
+        if !names.contains_key(&timing.tool_use_id) {
+            let Some(name) = &timing.tool_name else {
+                continue;
+            };
+            names.insert(timing.tool_use_id.clone(), name.clone());
+            stats.entry(name.clone()).or_default().calls += 1;
+        }
+        let name = &names[&timing.tool_use_id];
+        if timing.failed {
+            failed.push(timing.tool_use_id.clone());
+        }
+        if let (Some(started), Some(finished)) = (&timing.started, &timing.finished) {
+            if let (Ok(started), Ok(finished)) =
+                (DateTime::parse_from_rfc3339(started), DateTime::parse_from_rfc3339(finished))
+            {
+                let ms = (finished - started).num_milliseconds();
+                if ms >= 0 {
+                    durations.entry(name.clone()).or_default().push(ms as f64);
+                }
+            }
+        }
+    }
+
+    // A call can fail in both places; count it once
+    failed.sort();
+    failed.dedup();
+    for id in &failed {
+        if let Some(stat) = names.get(id).and_then(|n| stats.get_mut(n)) {
+            stat.failures += 1;
+        }
+    }
+    for (name, ms) in durations {
+        if let Some(stat) = stats.get_mut(&name) {
+            stat.timed_calls = ms.len();
+            stat.mean_duration_ms = Some(ms.iter().sum::<f64>() / ms.len() as f64);
+        }
+    }
+
+    let mut tools: Vec<ToolStats> = stats
+        .into_iter()
+        .map(|(name, stat)| ToolStats { name, ..stat })
+        .collect();
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.name.cmp(&b.name)));
+    let mut files: Vec<(String, usize)> = files.into_iter().collect();
+    files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    ToolReport { tools, files }
+}
+
+/// 850ms, 1.25s, 2m05s
+fn format_ms(ms: f64) -> String {
+    match ms {
+        ms if ms < 1_000.0 => format!("{:.0}ms", ms),
+        ms if ms < 60_000.0 => format!("{:.2}s", ms / 1_000.0),
+        ms => {
+            let seconds = (ms / 1_000.0).round() as i64;
+            format!("{}m{:02}s", seconds / 60, seconds % 60)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_type: LineType, raw: serde_json::Value) -> TranscriptLine {
+        TranscriptLine::for_test(line_type, raw.to_string())
+    }
+
+    fn timing(id: &str, started: &str, finished: &str, failed: bool) -> ToolTiming {
+        ToolTiming {
+            tool_use_id: id.to_string(),
+            tool_name: Some("Bash".to_string()),
+            started: Some(started.to_string()),
+            finished: Some(finished.to_string()),
+            failed,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_joins_on_tool_use_id() {
+        let lines = vec![
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "ls"}},
+                {"type": "tool_use", "id": "t2", "name": "Bash", "input": {"command": "cargo test"}},
+                {"type": "tool_use", "id": "t3", "name": "Edit", "input": {"file_path": "/p/a.rs"}}
+            ]}})),
+            line(LineType::User, json!({"message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t2", "content": "failed", "is_error": true}
+            ]}})),
+            line(LineType::Assistant, json!({"message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t4", "name": "Edit", "input": {"file_path": "/p/a.rs"}}
+            ]}})),
+        ];
+        let timings = vec![
+            timing("t1", "2026-01-01T10:00:00Z", "2026-01-01T10:00:01Z", false),
+            timing("t2", "2026-01-01T10:00:00Z", "2026-01-01T10:00:03Z", true),
+        ];
+
+        let report = aggregate(&lines, &timings);
+        let bash = &report.tools[0];
+        assert_eq!((bash.name.as_str(), bash.calls, bash.failures), ("Bash", 2, 1));
+        assert_eq!((bash.timed_calls, bash.mean_duration_ms), (2, Some(2_000.0)));
+        assert_eq!(bash.failure_rate(), 50.0);
+        assert_eq!(report.tools[1].mean_duration_ms, None);
+        assert_eq!(report.files, vec![("/p/a.rs".to_string(), 2)]);
+        assert_eq!(format_ms(1_250.0), "1.25s");
+    }
+}
@@ -0,0 +1,142 @@
+//! Trash command - list, restore and empty bundles of removed index rows
+//!
+//! `index prune` and `index rebuild` move the rows they can't recreate into
+//! bundles in `~/.claude-code-sdk/trash` before deleting them (see
+//! `transcript_indexer::trash`); nothing is gone for good until the trash is
+//! emptied.
+
+use anyhow::Result;
+use serde_json::json;
+use transcript_indexer::TrashBundle;
+
+use crate::cli::{Cli, OutputFormat, TrashCommand};
+use crate::commands::index::{abbreviate_path, open_indexer};
+use crate::output::colors;
+
+pub fn run(cli: &Cli, command: &TrashCommand) -> Result<()> {
+    let trash_dir = transcript_indexer::default_trash_dir();
+    match command {
+        TrashCommand::List => list(cli, &transcript_indexer::list_trash(&trash_dir)?),
+        TrashCommand::Restore { id } => {
+            let bundle = transcript_indexer::find_bundle(&trash_dir, id)?;
+            let mut indexer_db = open_indexer(cli)?;
+            let result = transcript_indexer::restore_bundle(indexer_db.connection_mut(), &bundle)?;
+            match cli.effective_format() {
+                OutputFormat::Human => {
+                    println!(
+                        "{} {} sessions, {} lines, {} hook events from {}",
+                        colors::success("Restored:"),
+                        result.sessions,
+                        result.lines,
+                        result.hook_events,
+                        bundle.id
+                    );
+                }
+                OutputFormat::Json => print_json(
+                    cli,
+                    &json!({
+                        "id": bundle.id,
+                        "restored": {
+                            "sessions": result.sessions,
+                            "lines": result.lines,
+                            "hook_files": result.hook_files,
+                            "hook_events": result.hook_events,
+                        },
+                    }),
+                )?,
+                OutputFormat::Minimal => println!("{}", bundle.id),
+            }
+            Ok(())
+        }
+        TrashCommand::Empty { older_than } => {
+            let before = older_than.map(|days| chrono::Utc::now() - chrono::Duration::days(days as i64));
+            let (bundles, bytes) = transcript_indexer::empty_trash(&trash_dir, before)?;
+            match cli.effective_format() {
+                OutputFormat::Human => {
+                    if bundles == 0 {
+                        println!("Nothing to delete");
+                    } else {
+                        println!(
+                            "{} {} bundles ({})",
+                            colors::success("Deleted:"),
+                            bundles,
+                            colors::format_size(bytes)
+                        );
+                    }
+                }
+                OutputFormat::Json => print_json(cli, &json!({ "deleted": bundles, "bytes": bytes }))?,
+                OutputFormat::Minimal => println!("{}", bundles),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn list(cli: &Cli, bundles: &[TrashBundle]) -> Result<()> {
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if bundles.is_empty() {
+                println!("Trash is empty");
+                return Ok(());
+            }
+            let total: u64 = bundles.iter().map(|b| b.bytes).sum();
+            println!(
+                "{}",
+                colors::header(&format!("Trash ({} bundles, {})", bundles.len(), colors::format_size(total)))
+            );
+            println!();
+            for bundle in bundles {
+                println!(
+                    "  {}  {}  {} sessions, {} lines, {} hook events  {}",
+                    colors::value(&bundle.id),
+                    colors::format_datetime(&bundle.created_at),
+                    bundle.sessions,
+                    bundle.lines,
+                    bundle.hook_events,
+                    colors::format_size(bundle.bytes)
+                );
+                for file in &bundle.files {
+                    println!("      {}", abbreviate_path(file));
+                }
+            }
+            println!();
+            println!(
+                "{}",
+                colors::label("Restore with: transcript trash restore <id>; delete with: transcript trash empty")
+            );
+        }
+        OutputFormat::Json => {
+            let bundles: Vec<serde_json::Value> = bundles
+                .iter()
+                .map(|b| {
+                    json!({
+                        "id": b.id,
+                        "reason": b.reason,
+                        "created_at": b.created_at,
+                        "files": b.files,
+                        "sessions": b.sessions,
+                        "lines": b.lines,
+                        "hook_events": b.hook_events,
+                        "bytes": b.bytes,
+                    })
+                })
+                .collect();
+            print_json(cli, &json!(bundles))?;
+        }
+        OutputFormat::Minimal => {
+            for bundle in bundles {
+                println!("{}\t{}\t{}", bundle.id, bundle.files.len(), bundle.lines);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_json(cli: &Cli, value: &serde_json::Value) -> Result<()> {
+    if cli.pretty {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", serde_json::to_string(value)?);
+    }
+    Ok(())
+}
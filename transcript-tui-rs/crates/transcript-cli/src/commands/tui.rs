@@ -0,0 +1,20 @@
+//! Tui command - the interactive viewer under the `transcript` binary
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::cli::Cli;
+
+/// Parse `args` as transcript-tui-rs arguments and run the viewer
+///
+/// A global `--db-path` or `--profile` given before `tui` applies unless the
+/// viewer arguments set their own.
+pub fn run(cli: &Cli, args: &[String]) -> Result<()> {
+    let mut tui = transcript_tui::Cli::parse_from(
+        std::iter::once("transcript tui").chain(args.iter().map(String::as_str)),
+    );
+    if tui.db_path.is_none() && tui.profile.is_none() {
+        tui.db_path = cli.db_path.clone();
+    }
+    transcript_tui::run(tui)
+}
@@ -0,0 +1,105 @@
+//! Turn command - show one prompt/response cycle of a session
+
+use anyhow::{bail, Result};
+use transcript_core::TranscriptLine;
+use transcript_db::{GetLinesOptions, TranscriptDb};
+
+use super::export::markdown::{render_turns, MarkdownOptions};
+use super::export::trace::is_prompt;
+use super::view::print_lines;
+use crate::cli::Cli;
+use crate::commands::resolve_session_id;
+
+pub fn run(
+    cli: &Cli,
+    db: &TranscriptDb,
+    session: &str,
+    seq: usize,
+    options: &MarkdownOptions,
+) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    let lines = db.get_lines(&GetLinesOptions::for_session(&session_id))?;
+    let turns = split_turns(&lines);
+
+    let Some((_, turn)) = turns.iter().find(|(n, _)| *n == seq) else {
+        let numbers: Vec<String> = turns.iter().map(|(n, _)| n.to_string()).collect();
+        bail!(
+            "Turn {} not found in {} (turns: {})",
+            seq,
+            session,
+            if numbers.is_empty() { "none".to_string() } else { numbers.join(", ") }
+        );
+    };
+
+    if cli.wants_markdown() {
+        let mut out = String::new();
+        render_turns(&mut out, turn, options, seq, &[]);
+        print!("{}", out);
+    } else {
+        print_lines(cli, turn);
+    }
+
+    Ok(())
+}
+
+/// Split chronological lines into numbered turns
+///
+/// Each user prompt starts a turn. A turn takes its number from the hook turn
+/// sequence of its lines when it has one, otherwise it follows the turn before.
+pub fn split_turns(lines: &[TranscriptLine]) -> Vec<(usize, &[TranscriptLine])> {
+    let mut starts: Vec<usize> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i == 0 || is_prompt(line) {
+            starts.push(i);
+        }
+    }
+
+    let mut turns = Vec::with_capacity(starts.len());
+    let mut previous = 0;
+    for (k, &start) in starts.iter().enumerate() {
+        let end = starts.get(k + 1).copied().unwrap_or(lines.len());
+        let turn = &lines[start..end];
+        let number = turn
+            .iter()
+            .find_map(|l| l.turn_sequence)
+            .filter(|&n| n > 0)
+            .map(|n| n as usize)
+            .unwrap_or(previous + 1);
+        turns.push((number, turn));
+        previous = number;
+    }
+    turns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transcript_core::LineType;
+
+    fn line(line_type: LineType, raw: &str, turn_sequence: Option<i64>) -> TranscriptLine {
+        TranscriptLine {
+            turn_sequence,
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_split_turns() {
+        let prompt = r#"{"message":{"role":"user","content":"hi"}}"#;
+        let result = r#"{"message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t","content":"ok"}]}}"#;
+        let reply = r#"{"message":{"role":"assistant","content":"x"}}"#;
+        let lines = vec![
+            line(LineType::User, prompt, None),
+            line(LineType::Assistant, reply, None),
+            line(LineType::User, result, None),
+            line(LineType::Assistant, reply, None),
+            line(LineType::User, prompt, Some(5)),
+            line(LineType::Assistant, reply, Some(5)),
+            line(LineType::User, prompt, None),
+        ];
+
+        let turns = split_turns(&lines);
+        let shape: Vec<(usize, usize)> = turns.iter().map(|(n, t)| (*n, t.len())).collect();
+        assert_eq!(shape, vec![(1, 4), (5, 2), (6, 1)]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Usage command - context growth per turn as a terminal chart
+
+use anyhow::Result;
+use colored::Colorize;
+use serde_json::json;
+use transcript_db::{TranscriptDb, TurnContext};
+
+use crate::cli::{Cli, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::output::colors::{self, format_count};
+
+/// Width of the per-turn bars, in characters
+const BAR_WIDTH: usize = 30;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub fn run(cli: &Cli, db: &TranscriptDb, session: &str, context_size: Option<u64>) -> Result<()> {
+    let session_id = resolve_session_id(db, session)?;
+    let turns = db.context_usage_by_turn(&session_id, context_size)?;
+
+    match cli.effective_format() {
+        OutputFormat::Human => {
+            if turns.is_empty() {
+                println!("No token usage recorded in {}", session);
+                return Ok(());
+            }
+            let window = turns.last().map_or(0, |t| t.context_size);
+            println!(
+                "{} {} {}",
+                colors::header("Context usage:"),
+                colors::colored_session(&session_id),
+                colors::label(&format!("({} token window)", format_count(window as i64)))
+            );
+            println!("{}", sparkline(&turns).cyan());
+            println!();
+            for turn in &turns {
+                print_turn(turn);
+            }
+        }
+        OutputFormat::Json => {
+            let value = json!({
+                "session_id": session_id,
+                "context_size": turns.last().map(|t| t.context_size).or(context_size),
+                "turns": turns
+                    .iter()
+                    .map(|t| json!({
+                        "turn": t.turn,
+                        "started": t.started,
+                        "line_number": t.line_number,
+                        "context_tokens": t.context_tokens,
+                        "peak_tokens": t.peak_tokens,
+                        "percent": (t.percent * 10.0).round() / 10.0,
+                        "growth": t.growth,
+                        "compacted": t.compacted,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            if cli.pretty {
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                println!("{}", serde_json::to_string(&value)?);
+            }
+        }
+        OutputFormat::Minimal => {
+            for turn in &turns {
+                println!("{}\t{}", turn.turn, turn.context_tokens);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// One character per turn, scaled to the session's largest context
+fn sparkline(turns: &[TurnContext]) -> String {
+    let max = turns.iter().map(|t| t.context_tokens).max().unwrap_or(0).max(1);
+    turns
+        .iter()
+        .map(|t| SPARKS[(t.context_tokens * (SPARKS.len() as u64 - 1) / max) as usize])
+        .collect()
+}
+
+/// A turn's row: number, start time, bar against the window, tokens and change
+fn print_turn(turn: &TurnContext) {
+    let filled = ((turn.percent / 100.0) * BAR_WIDTH as f64).round().clamp(0.0, BAR_WIDTH as f64) as usize;
+    let bar = "█".repeat(filled);
+    let bar = match turn.percent {
+        p if p >= 80.0 => bar.red(),
+        p if p >= 50.0 => bar.yellow(),
+        _ => bar.green(),
+    };
+    let growth = if turn.growth >= 0 {
+        format!("+{}", format_count(turn.growth))
+    } else {
+        format!("-{}", format_count(-turn.growth))
+    };
+
+    print!(
+        "  {:<5} {} {}{} {:>9} {:>6} {:>9}",
+        format!("#{}", turn.turn),
+        colors::colored_time(&turn.started),
+        bar,
+        "░".repeat(BAR_WIDTH - filled).dimmed(),
+        format_count(turn.context_tokens as i64),
+        format!("{:.1}%", turn.percent),
+        growth
+    );
+    if turn.compacted {
+        print!("  {}", "⟲ compacted".magenta());
+    }
+    println!();
+}
@@ -1,46 +1,90 @@
 //! View command - display transcript lines
 
-use anyhow::{bail, Result};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{NaiveDate, NaiveTime, TimeZone};
+use transcript_core::{noise_classifier, TranscriptLine};
 use transcript_db::{GetLinesOptions, LineOrder, TranscriptDb};
 
 use crate::cli::{Cli, OutputFormat};
+use crate::commands::resolve_session_id;
+use crate::jq::{self, JqFilter};
+use crate::output::colors::colored_session;
 use crate::output::{human, json, minimal};
 
+/// How often `--follow` checks for new lines
+const FOLLOW_POLL: Duration = Duration::from_millis(500);
+
 #[allow(clippy::too_many_arguments)]
 pub fn run(
     cli: &Cli,
     db: &TranscriptDb,
-    session: &str,
+    session: Option<&str>,
+    all: bool,
+    tag: Option<&str>,
     types: Option<&[String]>,
     last: Option<i64>,
     first: Option<i64>,
     search: Option<&str>,
     from_time: Option<&str>,
     to_time: Option<&str>,
+    date: Option<&str>,
     from_line: Option<i64>,
     to_line: Option<i64>,
     reverse: bool,
+    jq_expr: Option<&str>,
+    follow: bool,
+    with_ancestors: bool,
 ) -> Result<()> {
-    // Resolve session ID from name/slug
-    let session_info = db.resolve_session(session)?;
-    let session_id = match session_info {
-        Some(info) => info.session_id,
-        None => {
-            // Try partial match
-            let matches = db.find_sessions(session)?;
-            if matches.is_empty() {
-                bail!("Session not found: {}", session);
-            } else if matches.len() == 1 {
-                matches[0].session_id.clone()
-            } else {
-                eprintln!("Multiple sessions match '{}':", session);
-                for m in &matches {
-                    let name = m.slug.as_deref().unwrap_or(&m.session_id);
-                    eprintln!("  - {}", name);
+    // Compile the jq filter up front so a typo fails before any querying
+    let jq_filter = jq_expr.map(JqFilter::compile).transpose()?;
+    let day = parse_date(date)?;
+
+    if all {
+        // The whole day unless a window is given
+        let mut options = GetLinesOptions {
+            types: types.map(<[String]>::to_vec),
+            search: search.map(String::from),
+            from_time: Some(normalize_time(from_time.unwrap_or("00:00:00"), day)),
+            to_time: to_time.map(|t| normalize_time(t, day)),
+            ..Default::default()
+        };
+        if from_time.is_none() && to_time.is_none() {
+            options.to_time = Some(normalize_time("00:00:00", day + chrono::Duration::days(1)));
+        }
+        let lines = by_time(db.get_lines(&options)?, last, first, reverse);
+        if let Some(filter) = &jq_filter {
+            print_jq(cli, filter, &lines);
+        } else {
+            print_lines_labelled(cli, &lines, true);
+        }
+        if lines.is_empty() {
+            eprintln!("No lines found matching criteria");
+        }
+        return Ok(());
+    }
+
+    let session_id = match (session, tag) {
+        (Some(session), tag) => {
+            let session_id = resolve_session_id(db, session)?;
+            if let Some(tag) = tag {
+                if !db.get_tags(&session_id)?.iter().any(|t| t == tag) {
+                    bail!("Session {} is not tagged '{}'", session, tag);
                 }
-                bail!("Please specify a more specific session name");
+            }
+            session_id
+        }
+        // Most recent session with the tag
+        (None, Some(tag)) => {
+            let ids: std::collections::HashSet<String> =
+                db.get_session_ids_with_tag(tag)?.into_iter().collect();
+            match db.get_sessions(None)?.into_iter().find(|s| ids.contains(&s.session_id)) {
+                Some(info) => info.session_id,
+                None => bail!("No sessions tagged '{}'", tag),
             }
         }
+        (None, None) => bail!("Specify a session or --tag"),
     };
 
     // Build query options
@@ -55,11 +99,11 @@ pub fn run(
     }
 
     if let Some(t) = from_time {
-        options.from_time = Some(normalize_time(t));
+        options.from_time = Some(normalize_time(t, day));
     }
 
     if let Some(t) = to_time {
-        options.to_time = Some(normalize_time(t));
+        options.to_time = Some(normalize_time(t, day));
     }
 
     if let Some(n) = from_line {
@@ -82,16 +126,168 @@ pub fn run(
     }
 
     // Fetch lines
-    let mut lines = db.get_lines(&options)?;
+    let mut lines = if with_ancestors {
+        by_time(stitch_ancestors(db, &session_id, &options)?, last, first, reverse)
+    } else {
+        db.get_lines(&options)?
+    };
 
     // If we used DESC for --last, reverse to show in chronological order
-    if last.is_some() && !reverse {
+    if last.is_some() && !reverse && !with_ancestors {
         lines.reverse();
     }
 
-    // Output based on format
+    if let Some(filter) = &jq_filter {
+        print_jq(cli, filter, &lines);
+    } else {
+        print_lines(cli, &lines);
+    }
+
+    if follow {
+        follow_from_end(db, &session_id, &mut options)?;
+        return follow_lines(cli, db, options, jq_filter.as_ref());
+    }
+
+    if lines.is_empty() {
+        eprintln!("No lines found matching criteria");
+    }
+
+    Ok(())
+}
+
+/// The lines of a session and the sessions it resumes, in time order
+///
+/// History copied into a resumed session's file (same uuid and timestamp)
+/// is shown once, from the session it was first written in.
+fn stitch_ancestors(db: &TranscriptDb, session_id: &str, options: &GetLinesOptions) -> Result<Vec<TranscriptLine>> {
+    let mut ids = db.get_session_ancestors(session_id)?;
+    ids.push(session_id.to_string());
+
+    let mut seen = std::collections::HashSet::new();
+    let mut lines = Vec::new();
+    for id in ids {
+        let options = GetLinesOptions {
+            session_id: Some(id),
+            order: LineOrder::Asc,
+            limit: None,
+            ..options.clone()
+        };
+        lines.extend(db.get_lines(&options)?.into_iter().filter(|l| seen.insert((l.uuid.clone(), l.timestamp.clone()))));
+    }
+    Ok(lines)
+}
+
+/// Lines of several sessions in time order, then `--last`/`--first`/`--reverse`
+///
+/// `--last` keeps the order chronological unless `--reverse` is also given.
+fn by_time(mut lines: Vec<TranscriptLine>, last: Option<i64>, first: Option<i64>, reverse: bool) -> Vec<TranscriptLine> {
+    // Stable, so lines without a timestamp stay with their session
+    lines.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let len = lines.len();
+    let keep = |n: i64| (n.max(0) as usize).min(len);
+    if let Some(n) = last {
+        lines.drain(..len - keep(n));
+    } else if let Some(n) = first {
+        lines.truncate(keep(n));
+    }
+    if reverse {
+        lines.reverse();
+    }
+    lines
+}
+
+/// Print lines as the indexer adds them, until interrupted
+///
+/// Lines are only seen once indexed, so this needs `transcript index watch`
+/// (or another indexer) running. Each poll checks the indexer's change feed
+/// first, so an idle session costs one small query. Re-indexed lines keep
+/// their line numbers, so polling by line number never repeats one.
+fn follow_lines(
+    cli: &Cli,
+    db: &TranscriptDb,
+    mut options: GetLinesOptions,
+    jq_filter: Option<&JqFilter>,
+) -> Result<()> {
+    if cli.effective_format() == OutputFormat::Human {
+        eprintln!("Following (Ctrl-C to stop)...");
+    }
+    let mut cursor = 0;
+    loop {
+        std::thread::sleep(FOLLOW_POLL);
+        let lines = new_lines(db, &mut options, &mut cursor)?;
+        match jq_filter {
+            Some(filter) => print_jq(cli, filter, &lines),
+            None => print_lines(cli, &lines),
+        }
+    }
+}
+
+/// Point `options` after the session's last line, whatever the filters kept
+fn follow_from_end(db: &TranscriptDb, session_id: &str, options: &mut GetLinesOptions) -> Result<()> {
+    let last_line = db
+        .get_lines(&GetLinesOptions::for_session(session_id).with_order(LineOrder::Desc).with_limit(1))?
+        .first()
+        .map_or(0, |l| l.line_number);
+    options.order = LineOrder::Asc;
+    options.limit = None;
+    options.from_line = Some(options.from_line.unwrap_or(1).max(last_line + 1));
+    Ok(())
+}
+
+/// Lines indexed since the last poll, moving `options` past them
+fn new_lines(db: &TranscriptDb, options: &mut GetLinesOptions, cursor: &mut i64) -> Result<Vec<TranscriptLine>> {
+    if !db.lines_changed(cursor, options.session_id.as_deref())? {
+        return Ok(Vec::new());
+    }
+    let lines = db.get_lines(options)?;
+    if let Some(last) = lines.last() {
+        options.from_line = Some(last.line_number + 1);
+    }
+    Ok(lines)
+}
+
+/// Print the jq filter's output for each line
+fn print_jq(cli: &Cli, filter: &JqFilter, lines: &[TranscriptLine]) {
+    // Minimal output prints strings unquoted, like `jq -r`
+    let raw = cli.effective_format() == OutputFormat::Minimal;
+    // Lines the filter doesn't apply to are common (e.g. `.toolUseResult` on
+    // plain messages), so summarize failures instead of reporting each one
+    let mut failures = 0;
+    let mut first_error = None;
+    for line in lines {
+        match filter.run(&line.raw) {
+            Ok(values) => {
+                for value in values {
+                    println!("{}", jq::format_value(&value, raw, cli.pretty));
+                }
+            }
+            Err(e) => {
+                failures += 1;
+                first_error.get_or_insert_with(|| format!("line {}: {}", line.line_number, e));
+            }
+        }
+    }
+
+    if let Some(err) = first_error {
+        eprintln!("jq filter failed on {} line(s), first: {}", failures, err);
+    }
+}
+
+/// Print lines in the effective output format
+pub fn print_lines(cli: &Cli, lines: &[TranscriptLine]) {
+    print_lines_labelled(cli, lines, false);
+}
+
+/// Print lines, each after its session's slug (or short ID) when `labelled`
+/// (JSON lines carry their `sessionId` already)
+fn print_lines_labelled(cli: &Cli, lines: &[TranscriptLine], labelled: bool) {
+    let label = |line: &TranscriptLine| {
+        line.slug
+            .clone()
+            .unwrap_or_else(|| line.session_id.chars().take(8).collect())
+    };
     let format = cli.effective_format();
-    for line in &lines {
+    for line in lines {
         match format {
             OutputFormat::Human => {
                 // Skip noise lines in human mode
@@ -99,7 +295,11 @@ pub fn run(
                     continue;
                 }
                 let formatted = human::format_line(line, true);
-                println!("{}", formatted);
+                if labelled {
+                    println!("{} {}", colored_session(&label(line)), formatted);
+                } else {
+                    println!("{}", formatted);
+                }
                 // Only add blank line if there was content (line has newline)
                 if formatted.contains('\n') {
                     println!();
@@ -109,28 +309,156 @@ pub fn run(
                 println!("{}", json::format_line(line, cli.pretty));
             }
             OutputFormat::Minimal => {
+                if noise_classifier().is_noise(line) {
+                    continue;
+                }
                 let content = minimal::format_line(line);
-                if !content.is_empty() {
+                if content.is_empty() {
+                    continue;
+                }
+                if labelled {
+                    println!("{}: {}", label(line), content);
+                } else {
                     println!("{}", content);
                 }
             }
         }
     }
+}
 
-    if lines.is_empty() {
-        eprintln!("No lines found matching criteria");
+/// The day of `--date`: today (default), yesterday or YYYY-MM-DD
+fn parse_date(date: Option<&str>) -> Result<NaiveDate> {
+    let today = chrono::Local::now().date_naive();
+    match date.map(str::trim) {
+        None | Some("today") => Ok(today),
+        Some("yesterday") => Ok(today - chrono::Duration::days(1)),
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| anyhow!("Invalid date '{}' (use today, yesterday or YYYY-MM-DD)", d)),
     }
-
-    Ok(())
 }
 
 /// Normalize time string to ISO format
-fn normalize_time(time: &str) -> String {
-    // If it looks like just a time (HH:MM:SS), prefix with today's date
-    if time.len() <= 8 && time.contains(':') && !time.contains('T') {
-        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-        format!("{}T{}", today, time)
-    } else {
-        time.to_string()
+///
+/// A bare local time (HH:MM or HH:MM:SS) is taken on `day` and converted to
+/// UTC, as timestamps are stored.
+fn normalize_time(time: &str, day: NaiveDate) -> String {
+    if time.len() > 8 || !time.contains(':') || time.contains('T') {
+        return time.to_string();
+    }
+    let parsed = NaiveTime::parse_from_str(time, "%H:%M:%S").or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"));
+    match parsed {
+        Ok(t) => {
+            let local = day.and_time(t);
+            chrono::Local
+                .from_local_datetime(&local)
+                .earliest()
+                .map_or(local, |dt| dt.naive_utc())
+                .format("%Y-%m-%dT%H:%M:%S")
+                .to_string()
+        }
+        Err(_) => format!("{}T{}", day.format("%Y-%m-%d"), time),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn add_line(conn: &Connection, session_id: &str, line_number: i64, line_type: &str) {
+        conn.execute(
+            "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path)
+             VALUES (?1, ?1 || ?2, ?2, ?3, '2026-01-01T10:00:00Z', '{}', ?1 || '.jsonl')",
+            rusqlite::params![session_id, line_number, line_type],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO changes (session_id, max_line_id, changed_at) VALUES (?1, last_insert_rowid(), '')",
+            [session_id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_follow_prints_only_new_lines() {
+        let dir = std::env::temp_dir().join(format!("transcript-follow-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("transcripts.db");
+        let mut conn = Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut conn).unwrap();
+        add_line(&conn, "s1", 1, "user");
+        add_line(&conn, "s1", 2, "assistant");
+        let db = TranscriptDb::open(&path).unwrap();
+
+        // `--last 1` shows line 2; following starts after it
+        let mut options = GetLinesOptions::for_session("s1").with_order(LineOrder::Desc).with_limit(1);
+        follow_from_end(&db, "s1", &mut options).unwrap();
+        assert_eq!((options.from_line, options.limit), (Some(3), None));
+
+        let mut cursor = db.latest_change_id().unwrap();
+        assert!(new_lines(&db, &mut options, &mut cursor).unwrap().is_empty());
+
+        add_line(&conn, "s1", 3, "user");
+        add_line(&conn, "s1", 4, "assistant");
+        let numbers = |lines: Vec<TranscriptLine>| lines.iter().map(|l| l.line_number).collect::<Vec<_>>();
+        assert_eq!(numbers(new_lines(&db, &mut options, &mut cursor).unwrap()), [3, 4]);
+        assert!(new_lines(&db, &mut options, &mut cursor).unwrap().is_empty());
+
+        // Another session's lines don't show up
+        add_line(&conn, "s2", 1, "user");
+        assert!(new_lines(&db, &mut options, &mut cursor).unwrap().is_empty());
+
+        // Filtered-out lines still move the cursor on
+        options.types = Some(vec!["user".to_string()]);
+        add_line(&conn, "s1", 5, "assistant");
+        add_line(&conn, "s1", 6, "user");
+        assert_eq!(numbers(new_lines(&db, &mut options, &mut cursor).unwrap()), [6]);
+
+        drop(db);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_window_times() {
+        let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let local_nine = chrono::Local
+            .from_local_datetime(&day.and_hms_opt(9, 0, 0).unwrap())
+            .earliest()
+            .unwrap()
+            .naive_utc()
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string();
+        // Bare times are local on the chosen day, stored as UTC
+        assert_eq!(normalize_time("09:00", day), local_nine);
+        assert_eq!(normalize_time("09:00:00", day), local_nine);
+        assert_eq!(normalize_time("2026-02-01T08:00:00Z", day), "2026-02-01T08:00:00Z");
+
+        assert_eq!(parse_date(Some("2026-03-01")).unwrap(), day);
+        assert_eq!(parse_date(None).unwrap() - parse_date(Some("yesterday")).unwrap(), chrono::Duration::days(1));
+        assert!(parse_date(Some("03/01")).is_err());
+    }
+
+    #[test]
+    fn test_interleave_by_time() {
+        let lines = [("s1", "10:05"), ("s2", "10:01"), ("s1", "10:00"), ("s2", "10:09")]
+            .iter()
+            .enumerate()
+            .map(|(i, (session_id, time))| TranscriptLine {
+                id: i as i64,
+                line_number: i as i64,
+                uuid: format!("u{}", i),
+                session_id: session_id.to_string(),
+                timestamp: format!("2026-03-01T{}:00Z", time),
+                ..TranscriptLine::for_test(transcript_core::LineType::User, "")
+            })
+            .collect::<Vec<_>>();
+        let times = |lines: Vec<TranscriptLine>| lines.iter().map(|l| l.timestamp[11..16].to_string()).collect::<Vec<_>>();
+
+        assert_eq!(times(by_time(lines.clone(), None, None, false)), ["10:00", "10:01", "10:05", "10:09"]);
+        // --last keeps chronological order unless --reverse is given too
+        assert_eq!(times(by_time(lines.clone(), Some(2), None, false)), ["10:05", "10:09"]);
+        assert_eq!(times(by_time(lines.clone(), Some(2), None, true)), ["10:09", "10:05"]);
+        assert_eq!(times(by_time(lines.clone(), None, Some(1), false)), ["10:00"]);
+        assert_eq!(times(by_time(lines, Some(10), None, false)).len(), 4);
     }
 }
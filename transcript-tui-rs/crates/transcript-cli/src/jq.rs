@@ -0,0 +1,102 @@
+//! Inline jq filters (via jaq) applied to raw transcript lines
+
+use anyhow::{anyhow, bail, Result};
+use jaq_interpret::{Ctx, Filter, FilterT, ParseCtx, RcIter, Val};
+use serde_json::Value;
+
+/// A compiled jq filter, reused across all lines
+pub struct JqFilter {
+    filter: Filter,
+}
+
+impl JqFilter {
+    /// Parse and compile a jq expression with the jq standard library available
+    pub fn compile(expr: &str) -> Result<Self> {
+        let mut defs = ParseCtx::new(Vec::new());
+        defs.insert_natives(jaq_core::core());
+        defs.insert_defs(jaq_std::std());
+
+        let (main, errs) = jaq_parse::parse(expr, jaq_parse::main());
+        if let Some(err) = errs.first() {
+            bail!("Invalid jq filter '{}': {}", expr, err);
+        }
+        let main = main.ok_or_else(|| anyhow!("Invalid jq filter '{}'", expr))?;
+
+        let filter = defs.compile(main);
+        if let Some((err, _span)) = defs.errs.first() {
+            bail!("Invalid jq filter '{}': {}", expr, err);
+        }
+
+        Ok(Self { filter })
+    }
+
+    /// Run the filter against one raw JSON line, collecting all outputs
+    pub fn run(&self, raw: &str) -> Result<Vec<Value>> {
+        let input: Value = serde_json::from_str(raw)?;
+        let inputs = RcIter::new(core::iter::empty());
+
+        let mut results = Vec::new();
+        for output in self.filter.run((Ctx::new([], &inputs), Val::from(input))) {
+            let val = output.map_err(|e| anyhow!("{}", e))?;
+            results.push(Value::from(val));
+        }
+        Ok(results)
+    }
+}
+
+/// Format a jq output value (strings unquoted when `raw` is set, like `jq -r`)
+pub fn format_value(value: &Value, raw: bool, pretty: bool) -> String {
+    match value {
+        Value::String(s) if raw => s.clone(),
+        _ if pretty => serde_json::to_string_pretty(value).unwrap_or_default(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LINE: &str = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"hi"},{"type":"tool_use","name":"Bash"}]}}"#;
+
+    #[test]
+    fn test_filter() {
+        let filter = JqFilter::compile(".type").unwrap();
+        assert_eq!(filter.run(LINE).unwrap(), [json!("assistant")]);
+
+        // The standard library is available
+        let filter = JqFilter::compile(".message.content | map(.type) | join(\",\")").unwrap();
+        assert_eq!(filter.run(LINE).unwrap(), [json!("text,tool_use")]);
+    }
+
+    #[test]
+    fn test_multiple_outputs() {
+        let filter = JqFilter::compile(".message.content[] | .type").unwrap();
+        assert_eq!(filter.run(LINE).unwrap(), [json!("text"), json!("tool_use")]);
+
+        let filter = JqFilter::compile("select(.type == \"user\")").unwrap();
+        assert!(filter.run(LINE).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_filter() {
+        let err = JqFilter::compile(".message |").err().unwrap();
+        assert!(err.to_string().starts_with("Invalid jq filter '.message |'"), "{}", err);
+
+        let err = JqFilter::compile("no_such_function").err().unwrap();
+        assert!(err.to_string().starts_with("Invalid jq filter"), "{}", err);
+
+        // Errors raised while running surface per line
+        let filter = JqFilter::compile(".type | keys").unwrap();
+        assert!(filter.run(LINE).is_err());
+    }
+
+    #[test]
+    fn test_format_value() {
+        let value = json!("text");
+        assert_eq!(format_value(&value, false, false), "\"text\"");
+        assert_eq!(format_value(&value, true, false), "text");
+        assert_eq!(format_value(&json!({"a": 1}), true, true), "{\n  \"a\": 1\n}");
+    }
+}
@@ -2,50 +2,166 @@
 
 mod cli;
 mod commands;
+mod jq;
 mod output;
+mod timings;
 
-use anyhow::Result;
-use clap::Parser;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use clap::{CommandFactory, FromArgMatches, Parser};
+use transcript_core::resolve_since;
 use transcript_db::TranscriptDb;
 
-use cli::{Cli, Command, IndexCommand};
+use cli::{Cli, Command, DbCommand, EvalCommand, ExportCommand, IndexCommand, StatsCommand, SyncCommand, TmuxCommand};
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    // Busybox-style: run as the standalone binary this one was linked as
+    match std::env::args_os().next().as_deref().and_then(applet) {
+        Some(Applet::HookEvents) => return hook_events_cli::run(hook_events_cli::Cli::parse()),
+        Some(Applet::Tui) => return transcript_tui::run(transcript_tui::Cli::parse()),
+        None => {}
+    }
+
+    let started = Instant::now();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // From here on `db_path` is the database to use, `None` the default one
+    cli.db_path = transcript_core::resolve_db_path(cli.db_path.take(), cli.profile.as_deref())?;
+
+    // Statements are timed for every command, for the slow-query log
+    let config = transcript_core::Config::load();
+    transcript_db::timing::enable(config.slow_query_ms.unwrap_or(transcript_db::timing::DEFAULT_SLOW_QUERY_MS));
+
+    let mut db_open = Duration::ZERO;
+    let result = run(&cli, &mut db_open);
+    timings::finish(&cli, matches.subcommand_name().unwrap_or_default(), db_open, started.elapsed());
+    result
+}
+
+/// Run the parsed command, adding the time spent opening the database to `db_open`
+fn run(cli: &Cli, db_open: &mut Duration) -> Result<()> {
+    if cli.wants_markdown() && !matches!(cli.command, Command::Turn { .. } | Command::Thinking { .. }) {
+        bail!("--format md is only supported by turn and thinking");
+    }
+
+    // Shell prompts run this on every command: answer from the cache
+    // without opening the database when possible
+    if let Command::PromptSegment { cwd, context_size, max_age } = &cli.command {
+        return commands::prompt::segment(cli, cwd.as_deref(), *context_size, *max_age);
+    }
 
     // Open database connection
+    let opening = Instant::now();
     let db = match &cli.db_path {
         Some(path) => TranscriptDb::open(path),
         None => TranscriptDb::open_default(),
     };
+    *db_open += opening.elapsed();
 
     // Handle commands that don't require the read-only TranscriptDb
     // (these use IndexerDb or no DB at all)
     match &cli.command {
         Command::Doctor => {
-            return commands::doctor::run(&cli, db.ok());
+            return commands::doctor::run(cli, db.ok());
+        }
+        Command::Tui { args } => {
+            return commands::tui::run(cli, args);
+        }
+        Command::Hooks(command) => {
+            return commands::hooks::run(cli, command);
+        }
+        Command::Tmux(TmuxCommand::Status { cwd, context_size }) => {
+            // A status line shows nothing rather than an error
+            return match db.ok() {
+                Some(db) => commands::tmux::status(cli, &db, cwd.as_deref(), *context_size),
+                None => Ok(()),
+            };
+        }
+        Command::ContextCheck { session, threshold, context_size, hook } => {
+            return commands::context_check::run(cli, db.as_ref().ok(), session.as_deref(), *threshold, *context_size, *hook);
         }
         Command::Index(IndexCommand::Status) => {
-            return commands::index::status(&cli, db.ok());
+            return commands::index::status(cli, db.ok());
         }
         Command::Index(IndexCommand::Build) => {
-            return commands::index::build(&cli);
+            return commands::index::build(cli);
         }
         Command::Index(IndexCommand::Update) => {
-            return commands::index::update(&cli);
+            return commands::index::update(cli);
         }
         Command::Index(IndexCommand::Rebuild) => {
-            return commands::index::rebuild(&cli);
+            return commands::index::rebuild(cli);
+        }
+        Command::Index(IndexCommand::Prune { dry_run }) => {
+            return commands::index::prune(cli, *dry_run);
+        }
+        Command::Index(IndexCommand::Maintain) => {
+            return commands::index::maintain(cli);
+        }
+        Command::Index(IndexCommand::Errors { clear, retry, limit }) => {
+            return commands::index::errors(cli, *clear, *retry, *limit);
+        }
+        Command::Index(IndexCommand::Encrypt) => {
+            return commands::index::encrypt(cli, false);
+        }
+        Command::Index(IndexCommand::Decrypt) => {
+            return commands::index::encrypt(cli, true);
         }
         Command::Index(IndexCommand::Watch) => {
-            return commands::index::watch(&cli);
+            return commands::index::watch(cli);
+        }
+        Command::Index(IndexCommand::Stdin { session_id }) => {
+            return commands::index::stdin(cli, session_id);
+        }
+        Command::Import {
+            file,
+            from,
+            session_id,
+        } => {
+            return commands::import::run(cli, file, *from, session_id.as_deref());
         }
         Command::Recall {
             query,
             max_sessions,
             max_matches,
+            rank,
+            project,
+            context,
         } => {
-            return commands::recall::run(&cli, query, *max_sessions, *max_matches);
+            return commands::recall::run(
+                cli,
+                query,
+                &commands::recall::RecallOptions {
+                    max_sessions: *max_sessions,
+                    max_matches: *max_matches,
+                    rank: *rank,
+                    project: project
+                        .as_deref()
+                        .map(|p| commands::resolve_project(db.as_ref().ok(), p))
+                        .transpose()?,
+                    context: *context,
+                },
+            );
+        }
+        Command::Search { history: true, limit, .. } => {
+            return commands::search::history(cli, *limit);
+        }
+        Command::Trash(command) => {
+            return commands::trash::run(cli, command);
+        }
+        Command::Archive { older_than, dry_run } => {
+            return commands::archive::run(cli, older_than, *dry_run);
+        }
+        Command::OpenShared { file, passphrase } => {
+            return commands::share::open(cli, file, passphrase.as_deref());
+        }
+        Command::Sync(SyncCommand::Pull {
+            remote,
+            name,
+            federate,
+        }) => {
+            return commands::sync::pull(cli, remote, name.as_deref(), *federate);
         }
         _ => {}
     }
@@ -53,49 +169,503 @@ fn main() -> Result<()> {
     // For query commands, ensure read-only database is available
     let db = db?;
 
+    // Saved search: its query, and the filters not given on the command line
+    let search = match &cli.command {
+        Command::Search {
+            query,
+            session,
+            team,
+            include_archive,
+            tag,
+            project,
+            since,
+            today,
+            saved,
+            ..
+        } => {
+            let given = commands::search::SearchFilters {
+                session: session.clone(),
+                tag: tag.clone(),
+                project: project.clone(),
+                since: since.clone(),
+                today: *today,
+                team: *team,
+                include_archive: *include_archive,
+            };
+            Some(match saved {
+                Some(name) => {
+                    let (query, filters) = commands::search::load_saved(&db, name)?;
+                    (query, given.or(filters))
+                }
+                None => (query.clone().unwrap_or_default(), given),
+            })
+        }
+        _ => None,
+    };
+
+    // Team mode: attach teammates' snapshots for federated queries
+    let db = match &cli.command {
+        Command::List { team: true, .. } => commands::attach_team(db)?,
+        Command::Search { .. } if search.as_ref().is_some_and(|(_, f)| f.team) => commands::attach_team(db)?,
+        _ => db,
+    };
+    // Archive: attach the archive database for federated search
+    let db = match &cli.command {
+        Command::Search { .. } if search.as_ref().is_some_and(|(_, f)| f.include_archive) => {
+            commands::attach_archive(db)?
+        }
+        _ => db,
+    };
+
     match &cli.command {
         Command::View {
             session,
+            all,
+            tag,
             types,
             last,
             first,
             search,
             from_time,
             to_time,
+            date,
+            since,
+            today,
             from_line,
             to_line,
             reverse,
-        } => commands::view::run(
-            &cli,
-            &db,
-            session,
-            types.as_deref(),
-            *last,
-            *first,
-            search.as_deref(),
-            from_time.as_deref(),
-            to_time.as_deref(),
-            *from_line,
-            *to_line,
-            *reverse,
-        ),
+            jq,
+            follow,
+            with_ancestors,
+        } => {
+            let start = resolve_since(since.as_deref(), *today)?;
+            commands::view::run(
+                cli,
+                &db,
+                session.as_deref(),
+                *all,
+                tag.as_deref(),
+                types.as_deref(),
+                *last,
+                *first,
+                search.as_deref(),
+                start.as_deref().or(from_time.as_deref()),
+                to_time.as_deref(),
+                date.as_deref(),
+                *from_line,
+                *to_line,
+                *reverse,
+                jq.as_deref(),
+                *follow,
+                *with_ancestors,
+            )
+        }
 
         Command::List {
             limit,
             days,
             search,
-        } => commands::list::run(&cli, &db, *limit, *days, search.as_deref()),
+            user,
+            team,
+            tag,
+            review,
+            project,
+        } => commands::list::run(
+            cli,
+            &db,
+            *limit,
+            *days,
+            search.as_deref(),
+            user.as_deref(),
+            *team,
+            tag.as_deref(),
+            review.map(Into::into),
+            project.as_deref(),
+        ),
+
+        Command::Projects { limit } => commands::projects::run(cli, &db, *limit),
+
+        Command::Info { session } => commands::info::run(cli, &db, session),
+
+        Command::PruneTurns {
+            session,
+            to,
+            output,
+            keep,
+            no_tools,
+            thinking,
+        } => commands::prune_turns::run(
+            cli,
+            &db,
+            session,
+            *to,
+            output.as_deref(),
+            keep.as_deref(),
+            &commands::export::markdown::MarkdownOptions {
+                tools: !no_tools,
+                thinking: *thinking,
+                max_output: 2000,
+                annotations: false,
+            },
+        ),
+
+        Command::Turn {
+            session,
+            seq,
+            no_tools,
+            thinking,
+            max_output,
+        } => commands::turn::run(
+            cli,
+            &db,
+            session,
+            *seq,
+            &commands::export::markdown::MarkdownOptions {
+                tools: !no_tools,
+                thinking: *thinking,
+                max_output: *max_output,
+                annotations: false,
+            },
+        ),
+
+        Command::Issue { session, turns } => {
+            commands::issue::run(cli, &db, session, turns.as_deref())
+        }
+
+        Command::Changelog { repo, since, until } => {
+            commands::changelog::run(cli, &db, repo, since, until)
+        }
 
-        Command::Info { session } => commands::info::run(&cli, &db, session),
+        Command::Digest { project, last, budget } => {
+            commands::digest::run(cli, &db, project, last, *budget)
+        }
+
+        Command::Eval(EvalCommand::Compare {
+            golden,
+            candidate,
+            rubric,
+            tolerance,
+        }) => commands::eval::run(cli, &db, golden, candidate, rubric, *tolerance),
 
         Command::Search {
-            query,
+            limit, context, save, ..
+        } => {
+            let (query, filters) = search.unwrap_or_default();
+            if let Some(name) = save {
+                commands::search::save(cli, &db, name, &query, &filters)?;
+            }
+            commands::search::run(
+                cli,
+                &db,
+                &query,
+                *limit,
+                filters.session.as_deref(),
+                *context,
+                filters.team || filters.include_archive,
+                filters.tag.as_deref(),
+                filters.project.as_deref(),
+                resolve_since(filters.since.as_deref(), filters.today)?.as_deref(),
+            )
+        }
+
+        Command::Grep {
+            pattern,
+            path,
+            session,
+            ignore_case,
             limit,
+        } => commands::grep::run(cli, &db, pattern, path.as_deref(), session.as_deref(), *ignore_case, *limit),
+
+        Command::Blame { file, lines } => commands::blame::run(cli, &db, file, lines.as_deref()),
+
+        Command::Thinking { session, output } => commands::thinking::run(cli, &db, session, output.as_deref()),
+
+        Command::FileHistory { path, at, session } => {
+            commands::file_history::run(cli, &db, path, at.as_deref(), session.as_deref())
+        }
+
+        Command::Query {
+            sql,
+            example,
+            examples,
+            csv,
+        } => commands::query::run(cli, &db, sql.as_deref(), example.as_deref(), *examples, *csv),
+
+        Command::Db(DbCommand::Explain { query }) => commands::db::explain(cli, &db, query.as_deref()),
+
+        Command::Cost {
+            by,
             session,
-            context,
-        } => commands::search::run(&cli, &db, query, *limit, session.as_deref(), *context),
+            days,
+            csv,
+        } => commands::cost::run(cli, &db, *by, session.as_deref(), *days, *csv),
+
+        Command::Tools {
+            session,
+            all: _,
+            top_files,
+        } => commands::tools::run(cli, &db, session.as_deref(), *top_files),
+
+        Command::Stats(StatsCommand::Cache {
+            by,
+            session,
+            days,
+            min_reuse,
+        }) => commands::stats::run_cache(cli, &db, *by, session.as_deref(), *days, *min_reuse),
+
+        Command::Stats(StatsCommand::Payloads { limit, session, tool }) => {
+            commands::stats::run_payloads(cli, &db, *limit, session.as_deref(), tool.as_deref())
+        }
+
+        Command::Tmux(TmuxCommand::Open { session, below, size }) => {
+            commands::tmux::open(cli, &db, session, *below, *size)
+        }
+
+        Command::Stats(StatsCommand::Errors { days, session }) => {
+            commands::stats::run_errors(cli, &db, *days, session.as_deref())
+        }
+
+        Command::Usage {
+            session,
+            context_size,
+        } => commands::usage::run(cli, &db, session, *context_size),
+
+        #[cfg(feature = "parquet")]
+        Command::Export(ExportCommand::Parquet {
+            table,
+            all,
+            output,
+            columns,
+            session,
+            from_time,
+            to_time,
+        }) => commands::export::parquet::run(
+            cli,
+            &db,
+            if *all { commands::export::ALL_TABLES } else { table },
+            output.as_deref(),
+            columns.as_deref(),
+            session.as_deref(),
+            from_time.as_deref(),
+            to_time.as_deref(),
+        ),
+
+        Command::Export(ExportCommand::Sqlite { output }) => {
+            commands::export::sqlite::run(cli, &db, output.as_deref())
+        }
+
+        Command::Export(ExportCommand::Markdown {
+            session,
+            output,
+            no_tools,
+            thinking,
+            max_output,
+            annotations,
+        }) => commands::export::markdown::run(
+            cli,
+            &db,
+            session,
+            output.as_deref(),
+            &commands::export::markdown::MarkdownOptions {
+                tools: !no_tools,
+                thinking: *thinking,
+                max_output: *max_output,
+                annotations: *annotations,
+            },
+        ),
+
+        Command::Export(ExportCommand::Template {
+            session,
+            template,
+            output,
+        }) => commands::export::template::run(cli, &db, session, template, output.as_deref()),
+
+        Command::Export(ExportCommand::Jsonl {
+            session,
+            output,
+            types,
+            from_time,
+            to_time,
+            turns,
+        }) => commands::export::jsonl::run(
+            cli,
+            &db,
+            session,
+            output.as_deref(),
+            &commands::export::jsonl::JsonlFilter {
+                types: types.clone(),
+                from_time: from_time.clone(),
+                to_time: to_time.clone(),
+                turns: turns.clone(),
+            },
+        ),
+
+        Command::Export(ExportCommand::Otlp {
+            session,
+            days,
+            endpoint,
+            headers,
+            output,
+            service_name,
+        }) => commands::export::otlp::run(
+            cli,
+            &db,
+            session.as_deref(),
+            *days,
+            endpoint.as_deref(),
+            headers,
+            output.as_deref(),
+            service_name,
+        ),
+
+        Command::Export(ExportCommand::Observability {
+            platform,
+            session,
+            days,
+            endpoint,
+            public_key,
+            api_key,
+            project,
+            output,
+        }) => commands::export::observability::run(
+            cli,
+            &db,
+            *platform,
+            session.as_deref(),
+            *days,
+            commands::export::observability::PlatformConfig {
+                endpoint: endpoint.clone(),
+                public_key: public_key.clone(),
+                api_key: api_key.clone(),
+                project: project.clone(),
+            },
+            output.as_deref(),
+        ),
+
+        Command::Serve { port, bind } => commands::serve::run(cli, &db, bind, *port),
+
+        Command::Share {
+            session,
+            expires,
+            types,
+            output,
+        } => commands::share::run(
+            cli,
+            &db,
+            session,
+            expires,
+            types.as_deref(),
+            output.as_deref(),
+        ),
+
+        Command::Sync(SyncCommand::Push { remote, name }) => {
+            commands::sync::push(cli, &db, remote, name.as_deref())
+        }
+
+        Command::Tag(command) => commands::tag::run(cli, &db, command),
+
+        Command::Review {
+            session,
+            status,
+            reviewer,
+        } => commands::review::run(cli, &db, session, status.map(Into::into), reviewer.as_deref()),
+
+        Command::Bookmarks { session } => commands::bookmarks::run(cli, &db, session),
+
+        Command::Annotate {
+            command: Some(command),
+            ..
+        } => commands::annotate::run_command(cli, &db, command),
+
+        Command::Annotate {
+            session: Some(session),
+            line: Some(line),
+            text: Some(text),
+            author,
+            ..
+        } => commands::annotate::run(cli, &db, session, *line, text, author.as_deref()),
 
         // All other commands handled above
         _ => unreachable!(),
     }
 }
+
+/// A standalone binary this one can run as when linked under its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Applet {
+    HookEvents,
+    Tui,
+}
+
+/// The applet named by argv[0] (its file name, without extension)
+fn applet(arg0: &std::ffi::OsStr) -> Option<Applet> {
+    match std::path::Path::new(arg0).file_stem()?.to_str()? {
+        "hook-events" => Some(Applet::HookEvents),
+        "transcript-tui-rs" | "transcript-tui" => Some(Applet::Tui),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    #[test]
+    fn test_applet_from_argv0() {
+        assert_eq!(applet(OsStr::new("/usr/local/bin/hook-events")), Some(Applet::HookEvents));
+        assert_eq!(applet(OsStr::new("hook-events.exe")), Some(Applet::HookEvents));
+        assert_eq!(applet(OsStr::new("./transcript-tui-rs")), Some(Applet::Tui));
+        assert_eq!(applet(OsStr::new("transcript-tui")), Some(Applet::Tui));
+        assert_eq!(applet(OsStr::new("/usr/local/bin/transcript")), None);
+        assert_eq!(applet(OsStr::new("hook-events-cli")), None);
+    }
+
+    #[test]
+    fn test_subcommands_take_applet_arguments() {
+        let cli = Cli::try_parse_from(["transcript", "--json", "hooks", "view", "loyal-whippet", "-e", "PreToolUse"]).unwrap();
+        match cli.command {
+            Command::Hooks(hook_events_cli::Command::View { session, event, .. }) => {
+                assert_eq!(session, "loyal-whippet");
+                assert_eq!(event, Some(vec!["PreToolUse".to_string()]));
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+        assert!(cli.json);
+
+        // Viewer flags pass through untouched, --help included
+        let cli = Cli::try_parse_from(["transcript", "tui", "loyal-whippet", "--line", "5", "--help"]).unwrap();
+        match cli.command {
+            Command::Tui { args } => assert_eq!(args, ["loyal-whippet", "--line", "5", "--help"]),
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_profile_is_global() {
+        let cli = Cli::try_parse_from(["transcript", "list", "--profile", "work"]).unwrap();
+        assert_eq!(cli.profile.as_deref(), Some("work"));
+        let cli = Cli::try_parse_from(["transcript", "--profile", "archive", "hooks", "view", "."]).unwrap();
+        assert_eq!(cli.profile.as_deref(), Some("archive"));
+    }
+
+    #[test]
+    fn test_hook_output_is_a_context_check_flag() {
+        let cli = Cli::try_parse_from(["transcript", "context-check", "--hook"]).unwrap();
+        assert!(matches!(cli.command, Command::ContextCheck { hook: true, .. }));
+        assert!(Cli::try_parse_from(["transcript", "list", "--hook"]).is_err());
+        assert!(Cli::try_parse_from(["transcript", "context-check", "--format", "hook-json"]).is_err());
+    }
+
+    #[test]
+    fn test_markdown_refused_by_other_commands() {
+        let cli = Cli::try_parse_from(["transcript", "--format", "md", "list"]).unwrap();
+        let err = run(&cli, &mut Duration::default()).unwrap_err();
+        assert_eq!(err.to_string(), "--format md is only supported by turn and thinking");
+
+        // An alias flag overrides it, as for turn
+        let cli = Cli::try_parse_from(["transcript", "--format", "md", "--json", "list"]).unwrap();
+        assert!(!cli.wants_markdown());
+    }
+}
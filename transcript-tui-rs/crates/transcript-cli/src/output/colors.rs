@@ -1,7 +1,8 @@
 //! ANSI color helpers for terminal output
 
+use chrono::{DateTime, Local};
 use colored::Colorize;
-use transcript_core::LineType;
+use transcript_core::{locale, LineType};
 
 /// Get colored line type indicator
 pub fn colored_type(line_type: LineType) -> String {
@@ -81,32 +82,19 @@ pub fn error(text: &str) -> String {
     format!("{} {}", "✗".red(), text)
 }
 
-/// Format size in human-readable form
+/// Format size in human-readable form, with the locale's decimal mark
 pub fn format_size(bytes: u64) -> String {
-    let bytes = bytes as f64;
-    if bytes < 1024.0 {
-        format!("{:.0} B", bytes)
-    } else if bytes < 1024.0 * 1024.0 {
-        format!("{:.1} KB", bytes / 1024.0)
-    } else if bytes < 1024.0 * 1024.0 * 1024.0 {
-        format!("{:.1} MB", bytes / (1024.0 * 1024.0))
-    } else {
-        format!("{:.1} GB", bytes / (1024.0 * 1024.0 * 1024.0))
-    }
+    locale().format_size(bytes)
 }
 
-/// Format count with comma separators
+/// Format count with the locale's thousands separators
 pub fn format_count(n: i64) -> String {
-    let s = n.to_string();
-    let chars: Vec<char> = s.chars().collect();
-    let mut result = String::new();
-
-    for (i, c) in chars.iter().enumerate() {
-        if i > 0 && (chars.len() - i) % 3 == 0 {
-            result.push(',');
-        }
-        result.push(*c);
-    }
+    locale().format_count(n)
+}
 
-    result
+/// Format an ISO timestamp as a local date and time in the locale's format
+pub fn format_datetime(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| locale().format_datetime(&t.with_timezone(&Local)))
+        .unwrap_or_else(|_| timestamp.to_string())
 }
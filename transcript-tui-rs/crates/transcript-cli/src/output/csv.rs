@@ -0,0 +1,16 @@
+//! CSV output (RFC 4180)
+
+/// Quote a field when it contains a delimiter, quote or newline
+pub fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Print one CSV record
+pub fn print_row<S: AsRef<str>>(fields: &[S]) {
+    let escaped: Vec<String> = fields.iter().map(|f| escape(f.as_ref())).collect();
+    println!("{}", escaped.join(","));
+}
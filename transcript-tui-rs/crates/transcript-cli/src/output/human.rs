@@ -2,149 +2,28 @@
 
 use colored::Colorize;
 use super::colors::*;
-use transcript_core::{LineType, SessionInfo, TranscriptLine};
-
-/// Noise patterns to skip in output
-const NOISE_PATTERNS: &[&str] = &[
-    "Status dialog dismissed",
-    "status dialog dismissed",
-];
+use transcript_core::{
+    display_text, has_ansi, is_binary, noise_classifier, parse_ansi, render_ansi, render_system, strip_ansi,
+    LineType, SessionInfo, SystemLevel, TranscriptLine,
+};
 
 /// Check if content is noise that should be skipped
 fn is_noise(content: &str) -> bool {
-    let trimmed = content.trim();
-    for pattern in NOISE_PATTERNS {
-        if trimmed == *pattern {
-            return true;
-        }
-    }
-    false
-}
-
-/// Format duration in human-readable form
-fn format_duration(ms: u64) -> String {
-    if ms < 1000 {
-        format!("{}ms", ms)
-    } else if ms < 60_000 {
-        format!("{:.1}s", ms as f64 / 1000.0)
-    } else if ms < 3_600_000 {
-        let mins = ms / 60_000;
-        let secs = (ms % 60_000) / 1000;
-        format!("{}m {}s", mins, secs)
-    } else {
-        let hours = ms / 3_600_000;
-        let mins = (ms % 3_600_000) / 60_000;
-        format!("{}h {}m", hours, mins)
-    }
+    noise_classifier().is_noise_text(content)
 }
 
-/// Extract useful content from system message JSON
+/// Render a system line by its subtype, colored by how much it matters
 fn extract_system_content(parsed: &serde_json::Value) -> Option<String> {
-    let subtype = parsed.get("subtype").and_then(|v| v.as_str())?;
-
-    match subtype {
-        "turn_duration" => {
-            let duration_ms = parsed.get("durationMs").and_then(|v| v.as_u64())?;
-            Some(format!("Turn completed in {}", format_duration(duration_ms)))
-        }
-
-        "stop_hook_summary" => {
-            let hook_count = parsed.get("hookCount").and_then(|v| v.as_u64()).unwrap_or(0);
-            let hook_errors = parsed.get("hookErrors")
-                .and_then(|v| v.as_array())
-                .map(|a| a.len())
-                .unwrap_or(0);
-            let prevented = parsed.get("preventedContinuation").and_then(|v| v.as_bool()).unwrap_or(false);
-
-            let mut parts = Vec::new();
-            if hook_count > 0 {
-                parts.push(format!("{} hook(s) ran", hook_count));
-            }
-            if hook_errors > 0 {
-                parts.push(format!("{} error(s)", hook_errors).red().to_string());
-            }
-            if prevented {
-                parts.push("continuation prevented".yellow().to_string());
-            }
-
-            if parts.is_empty() {
-                None  // Skip if nothing interesting
-            } else {
-                Some(parts.join(", "))
-            }
-        }
-
-        "local_command" => {
-            // Extract command name from content
-            if let Some(content) = parsed.get("content").and_then(|v| v.as_str()) {
-                // Check for noise
-                if is_noise(content) {
-                    return None;
-                }
-
-                // Extract command name from XML
-                if let Some(start) = content.find("<command-name>") {
-                    if let Some(end) = content.find("</command-name>") {
-                        let cmd = &content[start + 14..end];
-                        return Some(format!("/{}", cmd.trim_start_matches('/')));
-                    }
-                }
-
-                // Extract stdout if meaningful
-                if let Some(start) = content.find("<local-command-stdout>") {
-                    if let Some(end) = content.find("</local-command-stdout>") {
-                        let stdout = &content[start + 22..end];
-                        if !is_noise(stdout) && !stdout.trim().is_empty() {
-                            return Some(stdout.trim().to_string());
-                        }
-                    }
-                }
-            }
-            None
-        }
-
-        "init" => {
-            let version = parsed.get("version").and_then(|v| v.as_str()).unwrap_or("?");
-            let branch = parsed.get("gitBranch").and_then(|v| v.as_str());
-            if let Some(b) = branch {
-                Some(format!("Session started (v{}, {})", version, b))
-            } else {
-                Some(format!("Session started (v{})", version))
-            }
-        }
-
-        "api_conversation_stats" => {
-            let input = parsed.get("inputTokens").and_then(|v| v.as_u64()).unwrap_or(0);
-            let output = parsed.get("outputTokens").and_then(|v| v.as_u64()).unwrap_or(0);
-            let cache_read = parsed.get("cacheReadTokens").and_then(|v| v.as_u64()).unwrap_or(0);
-            let cache_create = parsed.get("cacheCreationTokens").and_then(|v| v.as_u64()).unwrap_or(0);
-            let cost = parsed.get("costUSD").and_then(|v| v.as_f64());
-
-            let total = input + output + cache_read + cache_create;
-            if total > 0 {
-                let mut s = format!("{} tokens", format_count(total as i64));
-                if let Some(c) = cost {
-                    s.push_str(&format!(" (${:.4})", c));
-                }
-                Some(s)
-            } else {
-                None
-            }
-        }
-
-        "context_cleared" | "context_compacted" => {
-            Some(format!("Context {}", subtype.replace("context_", "")))
-        }
-
-        _ => {
-            // For unknown subtypes, just show the subtype name
-            Some(format!("[{}]", subtype))
-        }
-    }
+    let summary = render_system(parsed)?;
+    Some(match summary.level {
+        SystemLevel::Info => summary.text,
+        SystemLevel::Warning => summary.text.yellow().to_string(),
+        SystemLevel::Error => summary.text.red().to_string(),
+    })
 }
 
 /// Get content from line, with smart extraction for system messages
-fn get_content(line: &TranscriptLine) -> Option<String> {
+pub fn get_content(line: &TranscriptLine) -> Option<String> {
     // Parse raw JSON for richer extraction
     if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&line.raw) {
         // For system messages, use smart extraction
@@ -243,7 +122,7 @@ fn get_content(line: &TranscriptLine) -> Option<String> {
 }
 
 /// Get a preview of tool input for display
-fn get_tool_preview(tool_name: &str, input: &serde_json::Value) -> String {
+pub fn get_tool_preview(tool_name: &str, input: &serde_json::Value) -> String {
     match tool_name {
         "Bash" => {
             input.get("command")
@@ -303,6 +182,18 @@ fn get_tool_preview(tool_name: &str, input: &serde_json::Value) -> String {
     }
 }
 
+/// Content ready to print: embedded ANSI colors are normalized when coloring
+/// output and stripped otherwise
+fn content_text(content: &str) -> String {
+    if !has_ansi(content) || is_binary(content) {
+        display_text(content).into_owned()
+    } else if colored::control::SHOULD_COLORIZE.should_colorize() {
+        render_ansi(&parse_ansi(content))
+    } else {
+        strip_ansi(content)
+    }
+}
+
 /// Format a transcript line for human-readable output
 pub fn format_line(line: &TranscriptLine, show_content: bool) -> String {
     let mut parts = Vec::new();
@@ -325,7 +216,7 @@ pub fn format_line(line: &TranscriptLine, show_content: bool) -> String {
     // Turn info if available
     if let Some(turn_id) = &line.turn_id {
         if let Some(seq) = line.turn_sequence {
-            let short_turn = turn_id.split(':').last().unwrap_or(turn_id);
+            let short_turn = turn_id.split(':').next_back().unwrap_or(turn_id);
             parts.push(format!("[T{}.{}]", short_turn, seq));
         }
     }
@@ -335,7 +226,7 @@ pub fn format_line(line: &TranscriptLine, show_content: bool) -> String {
     if show_content {
         if let Some(content) = get_content(line) {
             let indent = "  ";
-            let formatted_content = content
+            let formatted_content = content_text(&content)
                 .lines()
                 .map(|l| format!("{}{}", indent, l))
                 .collect::<Vec<_>>()
@@ -347,7 +238,15 @@ pub fn format_line(line: &TranscriptLine, show_content: bool) -> String {
     } else {
         // Just preview - try get_content for richer preview
         if let Some(content) = get_content(line) {
-            let preview: String = content.lines().next().unwrap_or("").chars().take(60).collect();
+            let content = strip_ansi(&content);
+            let preview: String = content
+                .lines()
+                .next()
+                .unwrap_or("")
+                .chars()
+                .take(60)
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect();
             if preview.len() < content.lines().next().unwrap_or("").len() {
                 format!("{} {}...", header, preview)
             } else {
@@ -361,11 +260,12 @@ pub fn format_line(line: &TranscriptLine, show_content: bool) -> String {
 
 /// Check if a line should be hidden (noise)
 pub fn should_hide(line: &TranscriptLine) -> bool {
+    if noise_classifier().is_noise(line) {
+        return true;
+    }
     // Hide system messages with no meaningful content
-    if line.line_type == LineType::System {
-        if get_content(line).is_none() {
-            return true;
-        }
+    if line.line_type == LineType::System && get_content(line).is_none() {
+        return true;
     }
     false
 }
@@ -375,7 +275,7 @@ pub fn format_session(session: &SessionInfo) -> String {
     let name = session
         .slug
         .as_deref()
-        .unwrap_or_else(|| &session.session_id[..8]);
+        .unwrap_or_else(|| session.session_id.get(..8).unwrap_or(&session.session_id));
 
     let time = session
         .last_timestamp
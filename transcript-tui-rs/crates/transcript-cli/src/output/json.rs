@@ -29,14 +29,3 @@ pub fn session_to_json(session: &SessionInfo) -> serde_json::Value {
         "indexedAt": session.indexed_at
     })
 }
-
-/// Output a session as JSON string (deprecated, use session_to_json for arrays)
-pub fn format_session(session: &SessionInfo, pretty: bool) -> String {
-    let json = session_to_json(session);
-
-    if pretty {
-        serde_json::to_string_pretty(&json).unwrap_or_else(|_| json.to_string())
-    } else {
-        json.to_string()
-    }
-}
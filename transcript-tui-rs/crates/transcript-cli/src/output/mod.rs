@@ -1,6 +1,7 @@
 //! Output formatting for CLI
 
 pub mod colors;
+pub mod csv;
 pub mod human;
 pub mod json;
 pub mod minimal;
@@ -0,0 +1,64 @@
+//! `--timings` and the slow-query log
+//!
+//! Every command's statements are timed (see `transcript_db::timing`). With
+//! `--timings` the phases go to stderr: opening the database, running
+//! statements, and everything else (formatting and printing) as "render".
+//! Slow statements are logged in the database either way, best effort.
+
+use std::time::Duration;
+
+use transcript_db::default_db_path;
+use transcript_indexer::IndexerDb;
+
+use crate::cli::Cli;
+
+/// Characters of a slow statement shown on stderr
+const MAX_SQL_CHARS: usize = 100;
+
+/// Report the phases of `command` and log its slow statements
+pub fn finish(cli: &Cli, command: &str, db_open: Duration, total: Duration) {
+    let (statements, query) = transcript_db::timing::query_time();
+    let slow = transcript_db::timing::take_slow_queries();
+
+    if cli.timings {
+        let render = total.saturating_sub(db_open).saturating_sub(query);
+        eprintln!(
+            "timings: db open {} │ query {} ({} statements) │ render {} │ total {}",
+            format_ms(db_open),
+            format_ms(query),
+            statements,
+            format_ms(render),
+            format_ms(total)
+        );
+        for q in &slow {
+            eprintln!("  slow: {} ms  {}", q.millis, one_line(&q.sql));
+        }
+    }
+
+    if slow.is_empty() {
+        return;
+    }
+    // Never create a database just to log into it
+    let path = cli.db_path.clone().unwrap_or_else(default_db_path);
+    if !path.exists() {
+        return;
+    }
+    let queries: Vec<(String, u64)> = slow.into_iter().map(|q| (q.sql, q.millis)).collect();
+    if let Ok(db) = IndexerDb::open_or_create(&path) {
+        let _ = transcript_indexer::record_slow_queries(db.connection(), command, &queries);
+    }
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// A statement on one line, clipped
+pub fn one_line(sql: &str) -> String {
+    let line = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > MAX_SQL_CHARS {
+        format!("{}…", line.chars().take(MAX_SQL_CHARS).collect::<String>())
+    } else {
+        line
+    }
+}
@@ -0,0 +1,252 @@
+//! ANSI escape parsing into styled fragments
+//!
+//! Tool output (cargo, pytest, git, ...) often carries its own SGR color
+//! codes. `parse_ansi` turns such text into `StyledFragment`s that every
+//! frontend maps onto its own style type, instead of each one printing raw
+//! escapes or stripping them differently. Non-SGR sequences (cursor movement,
+//! screen clears, window titles) are dropped, and remaining control characters
+//! are escaped as by `sanitize_text`.
+
+use crate::sanitize::sanitize_text;
+
+/// A color from an SGR sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColor {
+    /// 256-color palette index (0-7 normal, 8-15 bright)
+    Indexed(u8),
+    /// 24-bit color
+    Rgb(u8, u8, u8),
+}
+
+/// Text attributes set by SGR sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextStyle {
+    pub fg: Option<AnsiColor>,
+    pub bg: Option<AnsiColor>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl TextStyle {
+    /// Whether no attribute is set
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply the parameters of one SGR sequence (`ESC [ <params> m`)
+    fn apply_sgr(&mut self, params: &str) {
+        let codes: Vec<u16> = params
+            .split([';', ':'])
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+
+        let mut i = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                c @ 30..=37 => self.fg = Some(AnsiColor::Indexed((c - 30) as u8)),
+                39 => self.fg = None,
+                c @ 40..=47 => self.bg = Some(AnsiColor::Indexed((c - 40) as u8)),
+                49 => self.bg = None,
+                c @ 90..=97 => self.fg = Some(AnsiColor::Indexed((c - 90 + 8) as u8)),
+                c @ 100..=107 => self.bg = Some(AnsiColor::Indexed((c - 100 + 8) as u8)),
+                c @ (38 | 48) => {
+                    let (color, used) = extended_color(&codes[i + 1..]);
+                    if let Some(color) = color {
+                        if c == 38 {
+                            self.fg = Some(color);
+                        } else {
+                            self.bg = Some(color);
+                        }
+                    }
+                    i += used;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// SGR sequence that switches the terminal to this style
+    pub fn to_sgr(&self) -> String {
+        let mut codes: Vec<String> = vec!["0".to_string()];
+        for (on, code) in [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.reverse, "7"),
+        ] {
+            if on {
+                codes.push(code.to_string());
+            }
+        }
+        for (color, base) in [(self.fg, 38), (self.bg, 48)] {
+            match color {
+                Some(AnsiColor::Indexed(n)) => codes.push(format!("{};5;{}", base, n)),
+                Some(AnsiColor::Rgb(r, g, b)) => codes.push(format!("{};2;{};{};{}", base, r, g, b)),
+                None => {}
+            }
+        }
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+/// Parse `5;n` or `2;r;g;b` after a 38/48 code, returning the color and codes used
+fn extended_color(rest: &[u16]) -> (Option<AnsiColor>, usize) {
+    match rest {
+        [5, n, ..] => (Some(AnsiColor::Indexed(*n as u8)), 2),
+        [2, r, g, b, ..] => (Some(AnsiColor::Rgb(*r as u8, *g as u8, *b as u8)), 4),
+        _ => (None, rest.len()),
+    }
+}
+
+/// A run of text sharing one style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledFragment {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+/// Whether text contains escape sequences worth parsing
+pub fn has_ansi(text: &str) -> bool {
+    text.contains('\x1b')
+}
+
+/// Split text with embedded ANSI escapes into styled fragments
+pub fn parse_ansi(text: &str) -> Vec<StyledFragment> {
+    let mut fragments: Vec<StyledFragment> = Vec::new();
+    let mut style = TextStyle::default();
+    let mut current = String::new();
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            current.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            // CSI: parameters, then a final byte in @..~
+            Some('[') => {
+                let mut params = String::new();
+                let mut terminator = None;
+                for ch in chars.by_ref() {
+                    if ('@'..='~').contains(&ch) {
+                        terminator = Some(ch);
+                        break;
+                    }
+                    params.push(ch);
+                }
+                if terminator == Some('m') {
+                    flush(&mut current, style, &mut fragments);
+                    style.apply_sgr(&params);
+                }
+            }
+            // OSC (titles, hyperlinks): ends with BEL or ESC \
+            Some(']') => {
+                while let Some(ch) = chars.next() {
+                    if ch == '\x07' {
+                        break;
+                    }
+                    if ch == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Charset designation and similar: intermediate byte, then final
+            Some(' '..='/') => {
+                chars.next();
+            }
+            // Other two-character escapes are dropped
+            _ => {}
+        }
+    }
+    flush(&mut current, style, &mut fragments);
+    fragments
+}
+
+/// Move pending text into `fragments`, merging with the last one if styles match
+fn flush(current: &mut String, style: TextStyle, fragments: &mut Vec<StyledFragment>) {
+    if current.is_empty() {
+        return;
+    }
+    let text = sanitize_text(current).into_owned();
+    current.clear();
+    match fragments.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(&text),
+        _ => fragments.push(StyledFragment { text, style }),
+    }
+}
+
+/// Text with all escape sequences removed
+pub fn strip_ansi(text: &str) -> String {
+    parse_ansi(text).into_iter().map(|f| f.text).collect()
+}
+
+/// Re-emit fragments with normalized SGR sequences (and nothing else)
+pub fn render_ansi(fragments: &[StyledFragment]) -> String {
+    let mut out = String::new();
+    for fragment in fragments {
+        if fragment.style.is_plain() {
+            out.push_str(&fragment.text);
+        } else {
+            out.push_str(&fragment.style.to_sgr());
+            out.push_str(&fragment.text);
+            out.push_str("\x1b[0m");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sgr_colors() {
+        let fragments = parse_ansi("\x1b[1;32m   Compiling\x1b[0m foo v0.1.0");
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[0].text, "   Compiling");
+        assert!(fragments[0].style.bold);
+        assert_eq!(fragments[0].style.fg, Some(AnsiColor::Indexed(2)));
+        assert_eq!(fragments[1].text, " foo v0.1.0");
+        assert!(fragments[1].style.is_plain());
+
+        let fragments = parse_ansi("\x1b[38;5;208mx\x1b[48;2;1;2;3my");
+        assert_eq!(fragments[0].style.fg, Some(AnsiColor::Indexed(208)));
+        assert_eq!(fragments[1].style.bg, Some(AnsiColor::Rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_drops_non_sgr_sequences() {
+        assert_eq!(strip_ansi("\x1b]0;title\x07a\x1b[2Jb\x1b[1Ac\x1b(Bd"), "abcd");
+        assert_eq!(strip_ansi("\x1b]8;;http://x\x1b\\link\x1b]8;;\x1b\\"), "link");
+        // Stray control characters are still escaped
+        assert_eq!(strip_ansi("a\x07b"), "a\\x07b");
+    }
+
+    #[test]
+    fn test_render_ansi_roundtrip() {
+        let fragments = parse_ansi("ok \x1b[31mFAILED\x1b[0m done");
+        let rendered = render_ansi(&fragments);
+        assert_eq!(rendered, "ok \x1b[0;38;5;1mFAILED\x1b[0m done");
+        assert_eq!(parse_ansi(&rendered), fragments);
+    }
+}
@@ -0,0 +1,152 @@
+//! API failures recorded in a transcript
+//!
+//! Claude Code records a failed API request in a few places: `api_error` and
+//! `rate_limit` system lines, assistant messages it writes itself with
+//! `isApiErrorMessage` ("API Error: 529 ..."), and tool results of subagents
+//! whose requests were overloaded. This finds them all, so the viewers can
+//! flag them and `transcript stats errors` can count them.
+
+use serde_json::Value;
+
+use crate::types::{ContentBlock, LineType, MessageContent, TranscriptLine};
+
+/// What kind of failure a line records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ApiFailureKind {
+    /// Any other API error (500s, invalid requests, network failures)
+    ApiError,
+    /// 429s and usage limits
+    RateLimit,
+    /// 529s: the API had no capacity
+    Overloaded,
+}
+
+impl ApiFailureKind {
+    pub const ALL: [ApiFailureKind; 3] = [
+        ApiFailureKind::ApiError,
+        ApiFailureKind::RateLimit,
+        ApiFailureKind::Overloaded,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ApiFailureKind::ApiError => "api_error",
+            ApiFailureKind::RateLimit => "rate_limit",
+            ApiFailureKind::Overloaded => "overloaded",
+        }
+    }
+}
+
+/// The API failure a line records, if any
+pub fn api_failure(line: &TranscriptLine) -> Option<ApiFailureKind> {
+    match line.line_type {
+        LineType::System => {
+            let parsed: Value = serde_json::from_str(&line.raw).ok()?;
+            match parsed.get("subtype").and_then(|v| v.as_str())? {
+                "api_error" => {
+                    let error = parsed.get("error");
+                    let status = error
+                        .and_then(|e| e.get("status"))
+                        .or_else(|| parsed.get("status"))
+                        .and_then(|v| v.as_u64());
+                    match status {
+                        Some(529) => Some(ApiFailureKind::Overloaded),
+                        Some(429) => Some(ApiFailureKind::RateLimit),
+                        _ => Some(classify(&error.map(Value::to_string).unwrap_or_default())),
+                    }
+                }
+                "rate_limit" => Some(ApiFailureKind::RateLimit),
+                _ => None,
+            }
+        }
+        // Checked as text first: these are rare, and most lines are neither
+        LineType::Assistant if line.raw.contains("isApiErrorMessage") => {
+            let parsed: Value = serde_json::from_str(&line.raw).ok()?;
+            if !parsed.get("isApiErrorMessage").and_then(|v| v.as_bool()).unwrap_or(false) {
+                return None;
+            }
+            let text = line.message().map(|m| m.content.as_text()).unwrap_or_default();
+            Some(classify(&text))
+        }
+        LineType::User if line.raw.to_ascii_lowercase().contains("overloaded") => {
+            let MessageContent::Blocks(blocks) = line.message()?.content else {
+                return None;
+            };
+            blocks
+                .iter()
+                .any(|block| {
+                    matches!(block, ContentBlock::ToolResult { content, is_error: true, .. }
+                        if content.to_string().to_ascii_lowercase().contains("overloaded"))
+                })
+                .then_some(ApiFailureKind::Overloaded)
+        }
+        _ => None,
+    }
+}
+
+/// Kind of failure from an error message
+fn classify(text: &str) -> ApiFailureKind {
+    let text = text.to_ascii_lowercase();
+    if text.contains("overloaded") || text.contains("529") {
+        ApiFailureKind::Overloaded
+    } else if text.contains("rate limit") || text.contains("rate_limit") || text.contains("usage limit") || text.contains("429") {
+        ApiFailureKind::RateLimit
+    } else {
+        ApiFailureKind::ApiError
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn line(line_type: LineType, raw: Value) -> TranscriptLine {
+        TranscriptLine {
+            id: 1,
+            line_number: 1,
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_api_failures() {
+        let system = |raw: Value| api_failure(&line(LineType::System, raw));
+        assert_eq!(
+            system(json!({"subtype": "api_error", "error": {"status": 529}})),
+            Some(ApiFailureKind::Overloaded)
+        );
+        assert_eq!(
+            system(json!({"subtype": "api_error", "error": {"status": 500, "message": "Internal"}})),
+            Some(ApiFailureKind::ApiError)
+        );
+        assert_eq!(system(json!({"subtype": "rate_limit"})), Some(ApiFailureKind::RateLimit));
+        assert_eq!(system(json!({"subtype": "turn_duration", "durationMs": 5})), None);
+
+        let assistant = line(
+            LineType::Assistant,
+            json!({
+                "type": "assistant",
+                "isApiErrorMessage": true,
+                "message": {"role": "assistant", "content": [{"type": "text", "text": "API Error: Claude AI usage limit reached"}]}
+            }),
+        );
+        assert_eq!(api_failure(&assistant), Some(ApiFailureKind::RateLimit));
+
+        let result = |is_error: bool| {
+            line(
+                LineType::User,
+                json!({
+                    "type": "user",
+                    "message": {"role": "user", "content": [{
+                        "type": "tool_result", "tool_use_id": "t1", "is_error": is_error,
+                        "content": "API Error: 529 {\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}"
+                    }]}
+                }),
+            )
+        };
+        assert_eq!(api_failure(&result(true)), Some(ApiFailureKind::Overloaded));
+        // A successful result that mentions the word is not a failure
+        assert_eq!(api_failure(&result(false)), None);
+    }
+}
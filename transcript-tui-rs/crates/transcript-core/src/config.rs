@@ -0,0 +1,277 @@
+//! User settings shared by the viewers and CLIs
+//!
+//! Read from `~/.claude-code-sdk/config.json`. Every field is optional, and a
+//! missing or unreadable file gives the defaults, so settings never stop a
+//! viewer from starting. Command-line flags override the file.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::encryption::EncryptionConfig;
+use crate::ignore::IgnoreConfig;
+use crate::noise::NoiseConfig;
+use crate::platform::{expand_home, sdk_dir};
+
+/// Default config file path
+pub fn default_config_path() -> PathBuf {
+    sdk_dir().join("config.json")
+}
+
+/// Settings from the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Accessibility profile: textual markers instead of color-only signals,
+    /// simpler borders and a linear reading order
+    pub a11y: bool,
+
+    /// How session summaries are written (changelog and friends)
+    pub summarizer: SummarizerConfig,
+
+    /// Extra rules for hiding noise lines (see `noise`)
+    pub noise: NoiseConfig,
+
+    /// Sessions kept out of the index (see `ignore`)
+    pub ignore: IgnoreConfig,
+
+    /// Named databases, selected with `--profile`
+    pub profiles: BTreeMap<String, ProfileConfig>,
+
+    /// Profile used when no `--profile` or `--db-path` is given
+    pub default_profile: Option<String>,
+
+    /// Where the database key comes from (see `encryption`)
+    pub encryption: EncryptionConfig,
+
+    /// Chat logs of other tools indexed next to Claude Code transcripts
+    pub adapters: Vec<AdapterConfig>,
+
+    /// Indexing steps run on every line, in order (default: all built in;
+    /// see transcript-indexer's `enrich`)
+    pub enrichers: Option<Vec<String>>,
+
+    /// Locale for numbers and dates, e.g. `de_DE` (default: from `LC_*`;
+    /// see `locale`)
+    pub locale: Option<String>,
+
+    /// Statements slower than this many milliseconds go in the slow-query
+    /// log that `transcript doctor` reports on (default: 200)
+    pub slow_query_ms: Option<u64>,
+
+    /// Context window in tokens by model name pattern, tried before the
+    /// built-in sizes (see `context`)
+    pub context_windows: BTreeMap<String, u64>,
+}
+
+/// A named database
+///
+/// ```json
+/// {"profiles": {"work": {"db": "~/work/transcripts.db"},
+///               "archive": {"db": "/mnt/archive/transcripts.db"}},
+///  "default_profile": "work"}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// Database path (a leading `~` is the home directory)
+    pub db: String,
+}
+
+/// A directory of chat logs from another tool
+///
+/// ```json
+/// {"adapters": [{"name": "aider", "format": "openai", "dir": "~/.aider/logs"}]}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AdapterConfig {
+    /// Adapter name, also the prefix of its session IDs
+    pub name: String,
+    /// How each line of a log is read
+    pub format: AdapterFormat,
+    /// Directory searched for `*.jsonl` logs (a leading `~` is the home directory)
+    pub dir: String,
+}
+
+impl AdapterConfig {
+    /// `dir` with `~` expanded
+    pub fn dir_path(&self) -> PathBuf {
+        PathBuf::from(expand_home(self.dir.trim()))
+    }
+}
+
+/// Line format of an adapter's logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdapterFormat {
+    /// One OpenAI Chat Completions message per line
+    #[default]
+    OpenAi,
+    /// One Anthropic Messages API message per line
+    Anthropic,
+    /// Claude Code transcript lines (or bare messages), as for `index stdin`
+    Transcript,
+}
+
+/// A `--profile` that can't be used
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProfileError {
+    #[error("unknown profile '{name}' (profiles in {}: {})", default_config_path().display(), if known.is_empty() { "none".to_string() } else { known.join(", ") })]
+    Unknown { name: String, known: Vec<String> },
+
+    #[error("profile '{0}' has no \"db\" path")]
+    NoDatabase(String),
+}
+
+/// Summarizer backend and its settings
+///
+/// ```json
+/// {"summarizer": {"backend": "command", "command": "llm -m haiku 'Summarize briefly'"}}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SummarizerConfig {
+    pub backend: SummarizerBackend,
+    /// Shell command for the `command` backend: text on stdin, summary on stdout
+    pub command: Option<String>,
+    /// Messages API endpoint for the `api` backend (default: Anthropic's)
+    pub url: Option<String>,
+    /// Model for the `api` backend
+    pub model: Option<String>,
+    /// Environment variable holding the API key (default: `ANTHROPIC_API_KEY`)
+    pub api_key_env: Option<String>,
+}
+
+/// Where summaries come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummarizerBackend {
+    /// Sentences picked from the text itself (offline)
+    #[default]
+    Extractive,
+    /// An external command (needs the `external-summarizer` feature)
+    Command,
+    /// An LLM over HTTP (needs the `external-summarizer` feature)
+    Api,
+}
+
+impl Config {
+    /// Load the config from the default path
+    pub fn load() -> Self {
+        Self::load_from(default_config_path())
+    }
+
+    /// Database path of the profile `name`, or of `default_profile` when no
+    /// name is given; `Ok(None)` when neither is set
+    pub fn profile_db_path(&self, name: Option<&str>) -> Result<Option<PathBuf>, ProfileError> {
+        let Some(name) = name.or(self.default_profile.as_deref()) else {
+            return Ok(None);
+        };
+        let profile = self.profiles.get(name).ok_or_else(|| ProfileError::Unknown {
+            name: name.to_string(),
+            known: self.profiles.keys().cloned().collect(),
+        })?;
+        let db = profile.db.trim();
+        if db.is_empty() {
+            return Err(ProfileError::NoDatabase(name.to_string()));
+        }
+        Ok(Some(PathBuf::from(expand_home(db))))
+    }
+
+    /// Load the config at `path` (missing or invalid files give the defaults)
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// The database a viewer or CLI opens: `db_path` when given (`--db-path`),
+/// else the profile's; `None` means the default database
+pub fn resolve_db_path(db_path: Option<PathBuf>, profile: Option<&str>) -> Result<Option<PathBuf>, ProfileError> {
+    match db_path {
+        Some(path) => Ok(Some(path)),
+        None => Config::load().profile_db_path(profile),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_config() {
+        let dir = std::env::temp_dir().join(format!("transcript-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        assert_eq!(Config::load_from(&path), Config::default());
+
+        std::fs::write(&path, r#"{"a11y": true, "unknown": 1}"#).unwrap();
+        assert!(Config::load_from(&path).a11y);
+
+        std::fs::write(&path, r#"{"summarizer": {"backend": "command", "command": "cat"}}"#).unwrap();
+        let summarizer = Config::load_from(&path).summarizer;
+        assert_eq!(summarizer.backend, SummarizerBackend::Command);
+        assert_eq!(summarizer.command.as_deref(), Some("cat"));
+
+        std::fs::write(&path, r#"{"noise": {"subtypes": ["turn_duration"]}}"#).unwrap();
+        let noise = Config::load_from(&path).noise;
+        assert_eq!(noise.subtypes, ["turn_duration"]);
+        assert!(noise.patterns.is_empty());
+
+        std::fs::write(&path, r#"{"ignore": {"projects": ["~/clients"]}}"#).unwrap();
+        let ignore = Config::load_from(&path).ignore;
+        assert_eq!(ignore.projects, ["~/clients"]);
+        assert!(ignore.sessions.is_empty());
+
+        std::fs::write(
+            &path,
+            r#"{"profiles": {"work": {"db": "/work/t.db"}, "bare": {}}, "default_profile": "work"}"#,
+        )
+        .unwrap();
+        let config = Config::load_from(&path);
+        assert_eq!(config.profile_db_path(None).unwrap(), Some(PathBuf::from("/work/t.db")));
+        assert_eq!(config.profile_db_path(Some("bare")), Err(ProfileError::NoDatabase("bare".to_string())));
+        assert!(matches!(config.profile_db_path(Some("home")), Err(ProfileError::Unknown { known, .. }) if known == ["bare", "work"]));
+        assert_eq!(Config::default().profile_db_path(None).unwrap(), None);
+
+        std::fs::write(&path, r#"{"adapters": [{"name": "aider", "format": "openai", "dir": "/logs"}]}"#).unwrap();
+        let adapters = Config::load_from(&path).adapters;
+        assert_eq!(adapters[0].format, AdapterFormat::OpenAi);
+        assert_eq!(adapters[0].dir_path(), PathBuf::from("/logs"));
+
+        std::fs::write(&path, r#"{"enrichers": ["trim"]}"#).unwrap();
+        assert_eq!(Config::load_from(&path).enrichers, Some(vec!["trim".to_string()]));
+
+        std::fs::write(&path, r#"{"encryption": {"key_command": "pass show transcripts"}}"#).unwrap();
+        let encryption = Config::load_from(&path).encryption;
+        assert_eq!(encryption.key_command.as_deref(), Some("pass show transcripts"));
+
+        std::fs::write(&path, r#"{"context_windows": {"sonnet": 1000000}}"#).unwrap();
+        assert_eq!(Config::load_from(&path).context_windows["sonnet"], 1_000_000);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(Config::load_from(&path), Config::default());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_db_path() {
+        // --db-path wins, even over a profile that doesn't exist
+        let explicit = PathBuf::from("/tmp/explicit.db");
+        assert_eq!(resolve_db_path(Some(explicit.clone()), Some("no-such-profile")), Ok(Some(explicit)));
+
+        let config: Config = serde_json::from_str(r#"{"profiles": {"home": {"db": " ~/t.db "}}}"#).unwrap();
+        let path = config.profile_db_path(Some("home")).unwrap().unwrap();
+        assert_eq!(path, PathBuf::from(expand_home("~/t.db")));
+        assert!(!path.starts_with("~"));
+        // No default profile: the default database
+        assert_eq!(config.profile_db_path(None), Ok(None));
+    }
+}
@@ -0,0 +1,110 @@
+//! Context window sizes by model
+//!
+//! Looked up like prices (see `cost`): by the first pattern found in the
+//! model name. Patterns under `context_windows` in the config are tried
+//! before the built-in ones, which covers new models and sessions on a
+//! 1M-token beta that transcripts record under the model's usual name:
+//!
+//! ```json
+//! {"context_windows": {"sonnet-4-5": 1000000, "qwen": 32768}}
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::types::TranscriptLine;
+
+/// Context window of models matching no pattern (200K tokens)
+pub const DEFAULT_CONTEXT_SIZE: u64 = 200_000;
+
+/// Model name patterns and their context windows, most specific first
+pub const MODEL_CONTEXT_WINDOWS: &[(&str, u64)] = &[
+    // Claude Code's name for the 1M-token variants, e.g. `sonnet[1m]`
+    ("[1m]", 1_000_000),
+    ("claude", 200_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-3.5", 16_385),
+    ("gemini", 1_048_576),
+];
+
+/// Context windows by model: configured patterns, then the built-in ones
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextWindows {
+    /// Lowercase patterns from the config, longest first
+    overrides: Vec<(String, u64)>,
+}
+
+impl ContextWindows {
+    /// Windows with the config's `context_windows` patterns added
+    pub fn new(overrides: &BTreeMap<String, u64>) -> Self {
+        let mut overrides: Vec<(String, u64)> = overrides
+            .iter()
+            .filter(|(pattern, &size)| !pattern.trim().is_empty() && size > 0)
+            .map(|(pattern, &size)| (pattern.trim().to_lowercase(), size))
+            .collect();
+        // `sonnet-4-5` wins over `sonnet` whatever order the file lists them in
+        overrides.sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.len()));
+        Self { overrides }
+    }
+
+    /// Context window of `model` (the default for an unknown or missing one)
+    pub fn window(&self, model: Option<&str>) -> u64 {
+        let Some(model) = model.map(str::to_lowercase) else {
+            return DEFAULT_CONTEXT_SIZE;
+        };
+        self.overrides
+            .iter()
+            .map(|(pattern, size)| (pattern.as_str(), *size))
+            .chain(MODEL_CONTEXT_WINDOWS.iter().copied())
+            .find(|(pattern, _)| model.contains(pattern))
+            .map_or(DEFAULT_CONTEXT_SIZE, |(_, size)| size)
+    }
+}
+
+/// The context windows from the config file, loaded once
+pub fn context_windows() -> &'static ContextWindows {
+    static WINDOWS: OnceLock<ContextWindows> = OnceLock::new();
+    WINDOWS.get_or_init(|| ContextWindows::new(&Config::load().context_windows))
+}
+
+/// Context window of `model`, with the config's overrides
+pub fn context_window(model: Option<&str>) -> u64 {
+    context_windows().window(model)
+}
+
+/// Context window of the model that wrote a line
+pub fn line_context_window(line: &TranscriptLine) -> u64 {
+    match &line.model {
+        Some(model) => context_window(Some(model)),
+        None => context_window(line.message().and_then(|m| m.model).as_deref()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_window_lookup() {
+        let builtin = ContextWindows::default();
+        assert_eq!(builtin.window(Some("claude-sonnet-4-5-20250929")), 200_000);
+        assert_eq!(builtin.window(Some("claude-sonnet-4-5[1m]")), 1_000_000);
+        assert_eq!(builtin.window(Some("gpt-4o-mini")), 128_000);
+        assert_eq!(builtin.window(Some("some-local-model")), DEFAULT_CONTEXT_SIZE);
+        assert_eq!(builtin.window(None), DEFAULT_CONTEXT_SIZE);
+
+        let overrides = BTreeMap::from([
+            ("Sonnet".to_string(), 500_000),
+            ("sonnet-4-5".to_string(), 1_000_000),
+            ("ignored".to_string(), 0),
+        ]);
+        let configured = ContextWindows::new(&overrides);
+        assert_eq!(configured.window(Some("claude-sonnet-4-5-20250929")), 1_000_000);
+        assert_eq!(configured.window(Some("claude-sonnet-4-20250514")), 500_000);
+        assert_eq!(configured.window(Some("claude-opus-4-1")), 200_000);
+        assert_eq!(configured.window(Some("ignored-model")), DEFAULT_CONTEXT_SIZE);
+    }
+}
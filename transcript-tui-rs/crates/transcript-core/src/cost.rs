@@ -0,0 +1,285 @@
+//! Token pricing and cost roll-ups from assistant usage blocks
+//!
+//! Prices are USD per million tokens, looked up by the first pattern found in
+//! the model name. Cache writes cost 1.25x and cache reads 0.1x the input
+//! price. Unknown models are priced like Sonnet.
+//!
+//! Claude Code writes one line per content block of an API response, and each
+//! line repeats the response's usage, so usage is counted once per message id
+//! (see `response_usage`).
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::types::{LineType, TokenUsage, TranscriptLine};
+
+/// Cache write price relative to input
+pub const CACHE_WRITE_MULTIPLIER: f64 = 1.25;
+
+/// Cache read price relative to input
+pub const CACHE_READ_MULTIPLIER: f64 = 0.1;
+
+/// USD per million tokens for a model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Model name patterns and their prices, most specific first
+pub const MODEL_PRICES: &[(&str, ModelPricing)] = &[
+    ("opus-4-5", ModelPricing { input: 5.0, output: 25.0 }),
+    ("opus", ModelPricing { input: 15.0, output: 75.0 }),
+    ("haiku-4-5", ModelPricing { input: 1.0, output: 5.0 }),
+    ("3-haiku", ModelPricing { input: 0.25, output: 1.25 }),
+    ("haiku", ModelPricing { input: 0.8, output: 4.0 }),
+    ("sonnet", ModelPricing { input: 3.0, output: 15.0 }),
+];
+
+/// Price for models matching no pattern
+pub const DEFAULT_PRICING: ModelPricing = ModelPricing { input: 3.0, output: 15.0 };
+
+/// Look up the price of a model
+pub fn pricing_for(model: &str) -> ModelPricing {
+    MODEL_PRICES
+        .iter()
+        .find(|(pattern, _)| model.contains(pattern))
+        .map(|(_, pricing)| *pricing)
+        .unwrap_or(DEFAULT_PRICING)
+}
+
+/// SQL `CASE` mapping the `model` column to one of its prices
+pub fn price_case_sql(price: fn(&ModelPricing) -> f64) -> String {
+    let mut sql = "CASE".to_string();
+    for (pattern, pricing) in MODEL_PRICES {
+        let _ = write!(sql, " WHEN model LIKE '%{}%' THEN {:?}", pattern, price(pricing));
+    }
+    let _ = write!(sql, " ELSE {:?} END", price(&DEFAULT_PRICING));
+    sql
+}
+
+/// Estimated cost in USD of one usage block
+pub fn usage_cost(model: &str, usage: &TokenUsage) -> f64 {
+    let p = pricing_for(model);
+    (usage.input_tokens as f64 * p.input
+        + usage.output_tokens as f64 * p.output
+        + usage.cache_creation_input_tokens.unwrap_or(0) as f64 * p.input * CACHE_WRITE_MULTIPLIER
+        + usage.cache_read_input_tokens.unwrap_or(0) as f64 * p.input * CACHE_READ_MULTIPLIER)
+        / 1_000_000.0
+}
+
+/// SQL expression over a `lines` row identifying the API response its usage
+/// belongs to, matching the key `response_usage` uses (the row id when the
+/// line has no message id)
+pub const RESPONSE_KEY_SQL: &str =
+    "COALESCE(json_extract(raw, '$.message.id') || ':' || COALESCE(json_extract(raw, '$.requestId'), ''), id)";
+
+/// Usage of each line, kept only on the last line of each API response
+///
+/// Lines without a message id count on their own. Responses are told apart
+/// per session, so each session's totals stand alone.
+pub fn response_usage(lines: &[TranscriptLine]) -> Vec<Option<TokenUsage>> {
+    let mut seen = HashSet::new();
+    let mut usages: Vec<Option<TokenUsage>> = lines
+        .iter()
+        .rev()
+        .map(|line| {
+            let parsed = line.parse().ok()?;
+            let message = parsed.message?;
+            if let Some(id) = &message.id {
+                let key = (line.session_id.as_str(), id.clone(), parsed.request_id.unwrap_or_default());
+                if !seen.insert(key) {
+                    return None;
+                }
+            }
+            message.usage
+        })
+        .collect();
+    usages.reverse();
+    usages
+}
+
+/// Tokens and estimated cost summed over assistant messages
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostTotals {
+    pub messages: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl CostTotals {
+    /// Add one API response's usage
+    pub fn add(&mut self, model: &str, usage: &TokenUsage) {
+        self.messages += 1;
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.cache_creation_tokens += usage.cache_creation_input_tokens.unwrap_or(0);
+        self.cache_read_tokens += usage.cache_read_input_tokens.unwrap_or(0);
+        self.cost_usd += usage_cost(model, usage);
+    }
+}
+
+/// Period to roll costs up by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostPeriod {
+    /// Prompt/response cycle (hook turn id, or inferred from user prompts)
+    Turn,
+    /// Session
+    Session,
+    /// Calendar day (UTC)
+    Day,
+}
+
+/// Costs of one turn, session or day
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostGroup {
+    /// Turn id, session id or `YYYY-MM-DD`
+    pub key: String,
+    /// Session of the first line in the group
+    pub session_id: String,
+    /// Timestamp of the first line in the group
+    pub started: String,
+    /// Models used, in order of first use
+    pub models: Vec<String>,
+    pub totals: CostTotals,
+}
+
+/// Roll up assistant usage in `lines` (chronological) by `period`
+///
+/// Groups are returned in order of their first line. Turns use the hook turn
+/// id when lines have one; otherwise each user prompt starts a new turn, keyed
+/// `<session_id>:<n>` like hook turn ids.
+pub fn aggregate_costs(lines: &[TranscriptLine], period: CostPeriod) -> Vec<CostGroup> {
+    let mut groups: Vec<CostGroup> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut prompts: HashMap<&str, usize> = HashMap::new();
+
+    for (line, usage) in lines.iter().zip(response_usage(lines)) {
+        let key = match period {
+            CostPeriod::Session => line.session_id.clone(),
+            CostPeriod::Day => line.timestamp.chars().take(10).collect(),
+            CostPeriod::Turn => {
+                let count = prompts.entry(line.session_id.as_str()).or_insert(0);
+                if line.is_prompt() || *count == 0 {
+                    *count += 1;
+                }
+                line.turn_id
+                    .clone()
+                    .unwrap_or_else(|| format!("{}:{}", line.session_id, count))
+            }
+        };
+
+        if line.line_type != LineType::Assistant {
+            continue;
+        }
+        let (Some(model), Some(usage)) = (&line.model, usage) else {
+            continue;
+        };
+
+        let idx = *index.entry(key.clone()).or_insert_with(|| {
+            groups.push(CostGroup {
+                key,
+                session_id: line.session_id.clone(),
+                started: line.timestamp.clone(),
+                models: Vec::new(),
+                totals: CostTotals::default(),
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[idx];
+        if !group.models.contains(model) {
+            group.models.push(model.clone());
+        }
+        group.totals.add(model, &usage);
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_type: LineType, timestamp: &str, model: Option<&str>, raw: &str) -> TranscriptLine {
+        TranscriptLine {
+            timestamp: timestamp.to_string(),
+            model: model.map(String::from),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_pricing_lookup() {
+        assert_eq!(pricing_for("claude-opus-4-5-20251101").input, 5.0);
+        assert_eq!(pricing_for("claude-opus-4-1-20250805").input, 15.0);
+        assert_eq!(pricing_for("claude-3-haiku-20240307").input, 0.25);
+        assert_eq!(pricing_for("claude-3-5-haiku-20241022").input, 0.8);
+        assert_eq!(pricing_for("something-new"), DEFAULT_PRICING);
+
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: Some(1_000_000),
+            cache_read_input_tokens: Some(1_000_000),
+        };
+        // 3.00 input + 1.50 output + 3.75 cache write + 0.30 cache read
+        assert!((usage_cost("claude-sonnet-4", &usage) - 8.55).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_aggregate_by_turn_and_day() {
+        let prompt = r#"{"message":{"role":"user","content":"hi"}}"#;
+        let result = r#"{"message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t","content":"ok"}]}}"#;
+        let reply = r#"{"message":{"role":"assistant","content":"x","usage":{"input_tokens":1000000,"output_tokens":0}}}"#;
+        let lines = vec![
+            line(LineType::User, "2026-01-01T10:00:00Z", None, prompt),
+            line(LineType::Assistant, "2026-01-01T10:00:01Z", Some("claude-sonnet-4"), reply),
+            line(LineType::User, "2026-01-01T10:00:02Z", None, result),
+            line(LineType::Assistant, "2026-01-01T10:00:03Z", Some("claude-opus-4"), reply),
+            line(LineType::User, "2026-01-02T09:00:00Z", None, prompt),
+            line(LineType::Assistant, "2026-01-02T09:00:01Z", Some("claude-sonnet-4"), reply),
+        ];
+
+        let turns = aggregate_costs(&lines, CostPeriod::Turn);
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].key, "s1:1");
+        assert_eq!(turns[0].models, vec!["claude-sonnet-4", "claude-opus-4"]);
+        assert_eq!(turns[0].totals.messages, 2);
+        assert!((turns[0].totals.cost_usd - 18.0).abs() < 1e-9);
+
+        let days = aggregate_costs(&lines, CostPeriod::Day);
+        assert_eq!(days.iter().map(|g| g.key.as_str()).collect::<Vec<_>>(), ["2026-01-01", "2026-01-02"]);
+        assert_eq!(aggregate_costs(&lines, CostPeriod::Session)[0].totals.messages, 3);
+    }
+
+    #[test]
+    fn test_usage_counted_once_per_response() {
+        // One response written as a line per content block
+        let block = |text: &str, output: u64| {
+            format!(
+                r#"{{"requestId":"req_1","message":{{"id":"msg_1","role":"assistant","content":[{{"type":"text","text":"{}"}}],"usage":{{"input_tokens":100,"output_tokens":{},"cache_read_input_tokens":1000}}}}}}"#,
+                text, output
+            )
+        };
+        let other = r#"{"requestId":"req_2","message":{"id":"msg_2","role":"assistant","content":"x","usage":{"input_tokens":10,"output_tokens":1}}}"#;
+        let lines = vec![
+            line(LineType::Assistant, "2026-01-01T10:00:00Z", Some("claude-sonnet-4"), &block("a", 1)),
+            line(LineType::Assistant, "2026-01-01T10:00:01Z", Some("claude-sonnet-4"), &block("b", 1)),
+            line(LineType::Assistant, "2026-01-01T10:00:02Z", Some("claude-sonnet-4"), &block("c", 50)),
+            line(LineType::Assistant, "2026-01-01T10:00:03Z", Some("claude-sonnet-4"), other),
+        ];
+
+        let kept: Vec<bool> = response_usage(&lines).iter().map(Option::is_some).collect();
+        assert_eq!(kept, [false, false, true, true]);
+
+        let totals = &aggregate_costs(&lines, CostPeriod::Session)[0].totals;
+        assert_eq!(totals.messages, 2);
+        assert_eq!(totals.input_tokens, 110);
+        // The last line of a response carries its final output count
+        assert_eq!(totals.output_tokens, 51);
+        assert_eq!(totals.cache_read_tokens, 1000);
+    }
+}
@@ -0,0 +1,113 @@
+//! The database key, for encryption at rest
+//!
+//! Transcripts hold whatever was pasted into prompts, secrets included. When
+//! built with the `encryption` feature, transcript-db and transcript-indexer
+//! open their databases with SQLCipher, keyed from (first match wins):
+//!
+//! - the `TRANSCRIPT_DB_KEY` environment variable
+//! - the output of `encryption.key_command` in the config file, which can
+//!   read a keychain, e.g.
+//!   `security find-generic-password -s transcript-db -w` (macOS) or
+//!   `secret-tool lookup service transcript-db` (Linux)
+//!
+//! No key means a plain database, as before.
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::config::Config;
+use crate::platform::shell_command;
+
+/// Environment variable holding the database key
+pub const DB_KEY_ENV: &str = "TRANSCRIPT_DB_KEY";
+
+/// Where the database key comes from
+///
+/// ```json
+/// {"encryption": {"key_command": "secret-tool lookup service transcript-db"}}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// Shell command printing the key on stdout (used when
+    /// `TRANSCRIPT_DB_KEY` isn't set)
+    pub key_command: Option<String>,
+}
+
+/// A configured key that can't be read
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum KeyError {
+    #[error("database key command '{command}' failed: {reason}")]
+    Command { command: String, reason: String },
+
+    #[error("database key command '{0}' printed no key")]
+    Empty(String),
+}
+
+static KEY: OnceLock<Result<Option<String>, KeyError>> = OnceLock::new();
+
+/// The configured database key, if any
+///
+/// Read once per process, so a keychain prompts at most once however many
+/// connections are opened.
+pub fn database_key() -> Result<Option<String>, KeyError> {
+    KEY.get_or_init(|| {
+        match std::env::var(DB_KEY_ENV) {
+            Ok(key) if !key.is_empty() => return Ok(Some(key)),
+            _ => {}
+        }
+        match Config::load().encryption.key_command {
+            Some(command) if !command.trim().is_empty() => run_key_command(&command).map(Some),
+            _ => Ok(None),
+        }
+    })
+    .clone()
+}
+
+fn run_key_command(command: &str) -> Result<String, KeyError> {
+    let fail = |reason: String| KeyError::Command {
+        command: command.to_string(),
+        reason,
+    };
+    let output = shell_command(command)
+        .output()
+        .map_err(|e| fail(e.to_string()))?;
+    if !output.status.success() {
+        return Err(fail(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    let key = String::from_utf8_lossy(&output.stdout).trim_end_matches(['\r', '\n']).to_string();
+    if key.is_empty() {
+        return Err(KeyError::Empty(command.to_string()));
+    }
+    Ok(key)
+}
+
+/// The SQLCipher statement that unlocks a connection with `key`
+///
+/// A key written as `x'…'` (64 hex digits) is used as the raw key instead
+/// of a passphrase.
+pub fn key_pragma(key: &str) -> String {
+    format!("PRAGMA key = '{}';", key.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_pragma() {
+        assert_eq!(key_pragma("hunter2"), "PRAGMA key = 'hunter2';");
+        assert_eq!(key_pragma("it's"), "PRAGMA key = 'it''s';");
+        assert_eq!(key_pragma("x'00ff'"), "PRAGMA key = 'x''00ff''';");
+    }
+
+    #[test]
+    fn test_key_command() {
+        // Commands both sh and cmd run alike
+        assert_eq!(run_key_command("echo k3y"), Ok("k3y".to_string()));
+        assert_eq!(run_key_command("exit 0"), Err(KeyError::Empty("exit 0".to_string())));
+        assert!(matches!(run_key_command("exit 3"), Err(KeyError::Command { .. })));
+    }
+}
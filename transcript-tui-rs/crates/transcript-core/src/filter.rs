@@ -1,7 +1,10 @@
 //! Line filtering utilities
 
+use crate::noise::noise_classifier;
 use crate::types::{LineType, TranscriptLine};
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
 use std::collections::HashSet;
+use thiserror::Error;
 
 /// Filter options for transcript lines
 #[derive(Debug, Clone, Default)]
@@ -16,6 +19,8 @@ pub struct FilterOptions {
     pub session_ids: Option<Vec<String>>,
     /// Exclude types
     pub exclude_types: Option<Vec<LineType>>,
+    /// Hide lines the noise classifier flags (from the user's config)
+    pub hide_noise: bool,
 }
 
 impl FilterOptions {
@@ -23,6 +28,14 @@ impl FilterOptions {
         Self::default()
     }
 
+    /// Only user and assistant messages, without noise (text-only mode)
+    pub fn text_only() -> Self {
+        Self {
+            hide_noise: true,
+            ..Self::conversations_only()
+        }
+    }
+
     /// Filter to only user and assistant messages
     pub fn conversations_only() -> Self {
         Self {
@@ -58,8 +71,31 @@ impl FilterOptions {
         self.session_ids = Some(session_ids);
         self
     }
+
+    /// Whether any filter is set (an inactive filter keeps every line)
+    pub fn is_active(&self) -> bool {
+        self.types.is_some()
+            || self.display_types.is_some()
+            || self.search.is_some()
+            || self.session_ids.is_some()
+            || self.exclude_types.is_some()
+            || self.hide_noise
+    }
 }
 
+/// Display types in the order frontends list them
+pub const DISPLAY_TYPES: &[&str] = &[
+    "user",
+    "assistant",
+    "tool",
+    "init",
+    "system",
+    "summary",
+    "progress",
+    "file-history",
+    "unknown",
+];
+
 /// Filter transcript lines based on options
 pub fn filter_lines<'a>(
     lines: &'a [TranscriptLine],
@@ -84,6 +120,11 @@ pub fn filter_lines<'a>(
         result.retain(|line| !exclude_set.contains(&line.line_type));
     }
 
+    if options.hide_noise {
+        let noise = noise_classifier();
+        result.retain(|line| !noise.is_noise(line));
+    }
+
     // Filter by session ID
     if let Some(session_ids) = &options.session_ids {
         let session_set: HashSet<_> = session_ids.iter().map(|s| s.as_str()).collect();
@@ -106,6 +147,29 @@ pub fn filter_lines<'a>(
     result
 }
 
+/// Apply a filter, returning owned lines (all lines when the filter is inactive)
+///
+/// Shared by the ratatui and cursive frontends so both filter identically.
+pub fn apply_filter(lines: &[TranscriptLine], options: &FilterOptions) -> Vec<TranscriptLine> {
+    if options.is_active() {
+        filter_lines(lines, options).into_iter().cloned().collect()
+    } else {
+        lines.to_vec()
+    }
+}
+
+/// Display types present in the lines with their counts, in `DISPLAY_TYPES` order
+pub fn get_display_type_counts(lines: &[TranscriptLine]) -> Vec<(&'static str, usize)> {
+    let mut counts = std::collections::HashMap::new();
+    for line in lines {
+        *counts.entry(line.display_type().to_string()).or_insert(0) += 1;
+    }
+    DISPLAY_TYPES
+        .iter()
+        .filter_map(|t| counts.get(*t).map(|c| (*t, *c)))
+        .collect()
+}
+
 /// Get unique session IDs from lines
 pub fn get_session_ids(lines: &[TranscriptLine]) -> Vec<String> {
     let mut ids: Vec<String> = lines
@@ -129,6 +193,73 @@ pub fn get_type_counts(lines: &[TranscriptLine]) -> Vec<(LineType, usize)> {
     result
 }
 
+/// A duration or time that couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TimeSpecError {
+    #[error("Invalid duration '{0}' (use e.g. 30m, 12h, 7d, 2w)")]
+    Duration(String),
+    #[error("Invalid time '{0}' (use a duration like 2h or 3d, today, yesterday, YYYY-MM-DD or an ISO time)")]
+    Since(String),
+}
+
+/// Parse a duration like `45s`, `30m`, `12h`, `7d` or `2w` (too large to
+/// represent is an error too)
+pub fn parse_duration(spec: &str) -> Result<Duration, TimeSpecError> {
+    let spec = spec.trim();
+    let invalid = || TimeSpecError::Duration(spec.to_string());
+    let split = spec.char_indices().last().map_or(0, |(i, _)| i);
+    let (number, unit) = spec.split_at(split);
+    let amount: i64 = number.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?;
+    let duration = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    };
+    duration.ok_or_else(invalid)
+}
+
+/// Start of a `--since` window as a UTC timestamp (`YYYY-MM-DDTHH:MM:SS`,
+/// as timestamps are compared in the database)
+///
+/// A duration counts back from `now`; `today`, `yesterday` and `YYYY-MM-DD`
+/// start at local midnight; ISO times are used as they are.
+pub fn since_time(spec: &str, now: DateTime<Local>) -> Result<String, TimeSpecError> {
+    let spec = spec.trim();
+    let today = now.date_naive();
+    let day = match spec {
+        "today" => Some(today),
+        "yesterday" => Some(today - Duration::days(1)),
+        _ => NaiveDate::parse_from_str(spec, "%Y-%m-%d").ok(),
+    };
+    if let Some(day) = day {
+        let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+        let start = Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map_or(midnight, |t| t.naive_utc());
+        return Ok(start.format("%Y-%m-%dT%H:%M:%S").to_string());
+    }
+    if spec.contains('T') {
+        return Ok(spec.to_string());
+    }
+    let invalid = || TimeSpecError::Since(spec.to_string());
+    let duration = parse_duration(spec).map_err(|_| invalid())?;
+    let start = now.with_timezone(&Utc).checked_sub_signed(duration).ok_or_else(invalid)?;
+    Ok(start.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+/// The start of a time window from `--since` or `--today`, if either is given
+pub fn resolve_since(since: Option<&str>, today: bool) -> Result<Option<String>, TimeSpecError> {
+    match (since, today) {
+        (Some(spec), _) => since_time(spec, Local::now()).map(Some),
+        (None, true) => since_time("today", Local::now()).map(Some),
+        (None, false) => Ok(None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,20 +268,9 @@ mod tests {
         TranscriptLine {
             id: 1,
             line_number: 1,
-            line_type,
-            uuid: String::new(),
-            parent_uuid: None,
             session_id: session_id.to_string(),
-            timestamp: String::new(),
-            cwd: None,
-            slug: None,
-            role: None,
-            model: None,
             content: Some(content.to_string()),
-            raw: String::new(),
-            turn_id: None,
-            turn_sequence: None,
-            session_name: None,
+            ..TranscriptLine::for_test(line_type, "")
         }
     }
 
@@ -180,4 +300,73 @@ mod tests {
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].content.as_deref(), Some("hello world"));
     }
+
+    #[test]
+    fn test_text_only_hides_noise() {
+        let lines = vec![
+            make_line(LineType::User, "Status dialog dismissed", "s1"),
+            make_line(LineType::User, "question", "s1"),
+            make_line(LineType::System, "init", "s1"),
+        ];
+
+        let filtered = apply_filter(&lines, &FilterOptions::text_only());
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].content.as_deref(), Some("question"));
+    }
+
+    #[test]
+    fn test_apply_filter_and_display_counts() {
+        let lines = vec![
+            make_line(LineType::Assistant, "answer", "s1"),
+            make_line(LineType::User, "question", "s1"),
+            make_line(LineType::User, "follow-up", "s1"),
+        ];
+
+        assert_eq!(apply_filter(&lines, &FilterOptions::new()).len(), 3);
+        let users = apply_filter(
+            &lines,
+            &FilterOptions::new().with_display_types(vec!["user".to_string()]),
+        );
+        assert_eq!(users.len(), 2);
+
+        assert_eq!(
+            get_display_type_counts(&lines),
+            vec![("user", 2), ("assistant", 1)]
+        );
+    }
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("45s"), Ok(Duration::seconds(45)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::hours(2)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::weeks(2)));
+        assert!(parse_duration("0d").is_err());
+        assert!(parse_duration("7y").is_err());
+        assert!(parse_duration("7é").is_err());
+        assert_eq!(
+            parse_duration("9999999999999w"),
+            Err(TimeSpecError::Duration("9999999999999w".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_since_time() {
+        let now = Local.with_ymd_and_hms(2026, 3, 7, 14, 0, 0).unwrap();
+        let utc = |t: DateTime<Local>| t.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%S").to_string();
+
+        assert_eq!(since_time("2h", now).unwrap(), utc(Local.with_ymd_and_hms(2026, 3, 7, 12, 0, 0).unwrap()));
+        assert_eq!(since_time("today", now).unwrap(), utc(Local.with_ymd_and_hms(2026, 3, 7, 0, 0, 0).unwrap()));
+        assert_eq!(
+            since_time("yesterday", now).unwrap(),
+            utc(Local.with_ymd_and_hms(2026, 3, 6, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            since_time("2026-01-02", now).unwrap(),
+            utc(Local.with_ymd_and_hms(2026, 1, 2, 0, 0, 0).unwrap())
+        );
+        assert_eq!(since_time("2026-01-02T10:00:00Z", now).unwrap(), "2026-01-02T10:00:00Z");
+        assert_eq!(since_time("last week", now), Err(TimeSpecError::Since("last week".to_string())));
+        // Further back than a date can go
+        assert_eq!(since_time("999999999d", now), Err(TimeSpecError::Since("999999999d".to_string())));
+    }
 }
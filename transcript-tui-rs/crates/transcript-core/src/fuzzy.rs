@@ -0,0 +1,98 @@
+//! Fuzzy matching for pickers and name lookups
+//!
+//! A query matches a candidate when its characters appear in order,
+//! ignoring case (`tndspdr` matches `tender-spider`). Matches score higher
+//! for runs of consecutive characters and for starting words, and lower for
+//! the characters skipped in between, so the closest candidates sort first.
+
+/// Score for each matched character
+const MATCH: i64 = 16;
+/// Bonus for a character right after the previous match
+const CONSECUTIVE: i64 = 24;
+/// Bonus for a character starting a word (after `-`, `_`, `/`, `.` or a space)
+const WORD_START: i64 = 20;
+/// Penalty for each candidate character skipped between matches
+const GAP: i64 = 2;
+
+/// Scores within this of the best (about one matched character) are too
+/// close to choose between without asking
+pub const FUZZY_CLOSE: i64 = MATCH;
+
+/// How well `query` matches `candidate` (higher is better), or None when it
+/// doesn't match; an empty query matches everything with 0
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+
+    // Match greedily from each place the first character occurs, keeping the best
+    (0..text.len())
+        .filter(|&start| text[start] == query[0])
+        .filter_map(|start| score_from(&query, &text, start))
+        .max()
+}
+
+/// Score of matching `query` greedily from `text[start]`
+fn score_from(query: &[char], text: &[char], start: usize) -> Option<i64> {
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    let mut pos = start;
+    for &c in query {
+        let found = pos + text[pos..].iter().position(|&t| t == c)?;
+        score += MATCH;
+        if found == 0 || matches!(text[found - 1], '-' | '_' | '/' | '.' | ' ') {
+            score += WORD_START;
+        }
+        match prev {
+            Some(p) if found == p + 1 => score += CONSECUTIVE,
+            Some(p) => score -= GAP * (found - p - 1) as i64,
+            None => score -= GAP * found as i64,
+        }
+        prev = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+/// Indices of the items matching `query`, best first (ties keep their order)
+pub fn fuzzy_filter<T>(query: &str, items: &[T], key: impl Fn(&T) -> String) -> Vec<usize> {
+    let mut scored: Vec<(usize, i64)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| fuzzy_score(query, &key(item)).map(|score| (i, score)))
+        .collect();
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("loyl-whipet", "loyal-whippet").is_some());
+        assert!(fuzzy_score("TNDR", "tender-spider").is_some());
+        assert_eq!(fuzzy_score("xyz", "tender-spider"), None);
+        assert_eq!(fuzzy_score("redner", "tender"), None);
+
+        // Consecutive and word-start matches beat scattered ones
+        let exact = fuzzy_score("spider", "tender-spider").unwrap();
+        let scattered = fuzzy_score("spider", "sleepy-pink-dinner").unwrap();
+        assert!(exact > scattered);
+        let prefix = fuzzy_score("ten", "tender-spider").unwrap();
+        let inner = fuzzy_score("ten", "kitten-nap").unwrap();
+        assert!(prefix > inner);
+    }
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let names = ["happy-dog", "tender-spider", "hungry-dodo", "sad-cat"];
+        assert_eq!(fuzzy_filter("hd", &names, |n| n.to_string()), [0, 2]);
+        assert_eq!(fuzzy_filter("spider", &names, |n| n.to_string()), [1]);
+        assert_eq!(fuzzy_filter("", &names, |n| n.to_string()), [0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,163 @@
+//! Search history persisted across runs
+//!
+//! Each consumer keeps its own history under a scope name (`search` for the
+//! CLI command, `tui` and `cursive` for the viewers), stored one query per
+//! line, oldest first, in `~/.claude-code-sdk/history/<scope>`.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Queries kept per scope
+pub const MAX_HISTORY: usize = 100;
+
+/// Directory holding the per-scope history files
+pub fn default_history_dir() -> PathBuf {
+    crate::platform::sdk_dir().join("history")
+}
+
+/// Search history for one scope, with up/down recall state
+#[derive(Debug, Clone, Default)]
+pub struct SearchHistory {
+    path: Option<PathBuf>,
+    entries: Vec<String>,
+    /// Position while recalling (None = editing a new query)
+    pos: Option<usize>,
+}
+
+impl SearchHistory {
+    /// Load the history for a scope from the default directory
+    pub fn load(scope: &str) -> Self {
+        Self::load_from(default_history_dir().join(scope))
+    }
+
+    /// Load the history stored at `path` (missing or unreadable files are empty)
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        Self {
+            entries: read_entries(&path),
+            path: Some(path),
+            pos: None,
+        }
+    }
+
+    /// History that is never written to disk
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Queries, oldest first
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Record a query as the most recent and save it
+    ///
+    /// Entries written by other processes since loading are kept.
+    pub fn push(&mut self, query: &str) -> io::Result<()> {
+        self.pos = None;
+        let query = query.trim();
+        if query.is_empty() || query.contains('\n') {
+            return Ok(());
+        }
+
+        if let Some(path) = &self.path {
+            self.entries = read_entries(path);
+        }
+        self.entries.retain(|q| q != query);
+        self.entries.push(query.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.drain(..self.entries.len() - MAX_HISTORY);
+        }
+
+        match &self.path {
+            Some(path) => {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                let mut contents = self.entries.join("\n");
+                contents.push('\n');
+                std::fs::write(path, contents)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Step back to an older query (up arrow)
+    pub fn older(&mut self) -> Option<&str> {
+        let pos = match self.pos {
+            _ if self.entries.is_empty() => return None,
+            None => self.entries.len() - 1,
+            Some(p) => p.saturating_sub(1),
+        };
+        self.pos = Some(pos);
+        Some(&self.entries[pos])
+    }
+
+    /// Step forward to a newer query (down arrow); past the newest gives ""
+    pub fn newer(&mut self) -> Option<&str> {
+        let pos = self.pos?;
+        if pos + 1 >= self.entries.len() {
+            self.pos = None;
+            return Some("");
+        }
+        self.pos = Some(pos + 1);
+        Some(&self.entries[pos + 1])
+    }
+
+    /// Forget the recall position (a new query is being typed)
+    pub fn reset_recall(&mut self) {
+        self.pos = None;
+    }
+}
+
+fn read_entries(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .map(|s| {
+            s.lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recall_order_and_dedupe() {
+        let mut history = SearchHistory::in_memory();
+        assert_eq!(history.older(), None);
+
+        history.push("ECONNREFUSED").unwrap();
+        history.push("timeout").unwrap();
+        history.push("ECONNREFUSED").unwrap();
+        assert_eq!(history.entries(), ["timeout", "ECONNREFUSED"]);
+
+        assert_eq!(history.older(), Some("ECONNREFUSED"));
+        assert_eq!(history.older(), Some("timeout"));
+        assert_eq!(history.older(), Some("timeout"));
+        assert_eq!(history.newer(), Some("ECONNREFUSED"));
+        assert_eq!(history.newer(), Some(""));
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn test_persists_across_loads() {
+        let path = std::env::temp_dir()
+            .join(format!("transcript-history-{}", std::process::id()))
+            .join("search");
+        let _ = std::fs::remove_file(&path);
+
+        let mut first = SearchHistory::load_from(&path);
+        first.push("panic at").unwrap();
+        // A second process pushing in between is not lost
+        SearchHistory::load_from(&path).push("segfault").unwrap();
+        first.push("oom").unwrap();
+
+        let reloaded = SearchHistory::load_from(&path);
+        assert_eq!(reloaded.entries(), ["panic at", "segfault", "oom"]);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
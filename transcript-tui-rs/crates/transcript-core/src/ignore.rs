@@ -0,0 +1,101 @@
+//! Sessions kept out of the index
+//!
+//! Personal or client-confidential work can be kept out of a shared index by
+//! listing its projects, session IDs or slugs in the config file:
+//!
+//! ```json
+//! {"ignore": {"projects": ["~/clients/acme", "/work/*-private"],
+//!             "sessions": ["0f6c5a1e-..."], "slugs": ["secret-*"]}}
+//! ```
+//!
+//! A project rule matches a session's working directory and everything under
+//! it; `*` matches any run of characters in all three kinds of rule. The
+//! indexer skips matching sessions and their hook events, and `transcript
+//! index prune` removes those indexed before the rule was added.
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::noise::glob_match;
+use crate::platform::expand_home;
+
+/// Ignore rules from the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct IgnoreConfig {
+    /// Working directories (a leading `~` is the home directory)
+    pub projects: Vec<String>,
+    /// Session IDs
+    pub sessions: Vec<String>,
+    /// Session slugs (`tender-spider`)
+    pub slugs: Vec<String>,
+}
+
+impl IgnoreConfig {
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty() && self.sessions.is_empty() && self.slugs.is_empty()
+    }
+
+    pub fn ignores_session(&self, session_id: &str) -> bool {
+        !session_id.is_empty() && self.sessions.iter().any(|p| glob_match(p.trim(), session_id))
+    }
+
+    pub fn ignores_slug(&self, slug: &str) -> bool {
+        !slug.is_empty() && self.slugs.iter().any(|p| glob_match(p.trim(), slug))
+    }
+
+    /// Whether `cwd` is a listed project or inside one
+    pub fn ignores_project(&self, cwd: &str) -> bool {
+        if cwd.is_empty() {
+            return false;
+        }
+        // Windows directories match patterns written with either separator
+        let cwd = cwd.replace('\\', "/");
+        let cwd = cwd.trim_end_matches('/');
+        self.projects.iter().any(|pattern| {
+            let pattern = expand_home(pattern.trim()).replace('\\', "/");
+            let pattern = pattern.trim_end_matches('/');
+            !pattern.is_empty() && (glob_match(pattern, cwd) || glob_match(&format!("{}/*", pattern), cwd))
+        })
+    }
+
+    /// Whether a session is ignored by any of what is known about it
+    pub fn ignores(&self, session_id: &str, cwd: Option<&str>, slug: Option<&str>) -> bool {
+        self.ignores_session(session_id)
+            || cwd.is_some_and(|cwd| self.ignores_project(cwd))
+            || slug.is_some_and(|slug| self.ignores_slug(slug))
+    }
+}
+
+/// The ignore rules of the user's config, loaded once per process
+pub fn ignore_rules() -> &'static IgnoreConfig {
+    static RULES: OnceLock<IgnoreConfig> = OnceLock::new();
+    RULES.get_or_init(|| Config::load().ignore)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_rules() {
+        let rules = IgnoreConfig {
+            projects: vec!["/work/acme/".to_string(), "/src/*-private".to_string()],
+            sessions: vec!["sess-1".to_string()],
+            slugs: vec!["secret-*".to_string()],
+        };
+
+        assert!(rules.ignores_project("/work/acme"));
+        assert!(rules.ignores_project("/work/acme/api/src"));
+        assert!(!rules.ignores_project("/work/acme-public"));
+        assert!(rules.ignores_project("/src/tax-private"));
+        assert!(!rules.ignores_project(""));
+
+        assert!(rules.ignores("sess-1", None, None));
+        assert!(rules.ignores("sess-2", None, Some("secret-otter")));
+        assert!(!rules.ignores("sess-2", Some("/home/me"), Some("tender-spider")));
+        assert!(!IgnoreConfig::default().ignores("sess-1", Some("/work/acme"), None));
+    }
+}
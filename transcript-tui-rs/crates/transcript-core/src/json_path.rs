@@ -0,0 +1,209 @@
+//! jq-like path queries over a line's raw JSON
+//!
+//! Supports the small subset of jq paths useful for poking at transcript lines:
+//! - `.` - the whole value
+//! - `.message.usage` - object keys
+//! - `.message.content[0]` - array indices (negative counts from the end)
+//! - `.["odd key"]` - quoted keys
+
+use serde_json::Value;
+use thiserror::Error;
+
+/// Error parsing a path expression
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid path at position {position}: {message}")]
+pub struct JsonPathError {
+    pub position: usize,
+    pub message: String,
+}
+
+/// One step in a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Object key
+    Key(String),
+    /// Array index (negative counts from the end)
+    Index(i64),
+}
+
+/// A parsed path expression such as `.toolUseResult.stdout`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JsonPath {
+    pub segments: Vec<PathSegment>,
+}
+
+impl JsonPath {
+    /// Parse a path expression
+    pub fn parse(expr: &str) -> Result<Self, JsonPathError> {
+        let chars: Vec<char> = expr.trim().chars().collect();
+        let mut segments = Vec::new();
+        let mut pos = 0;
+
+        let err = |position: usize, message: &str| JsonPathError {
+            position,
+            message: message.to_string(),
+        };
+
+        if chars.is_empty() {
+            return Err(err(0, "empty path"));
+        }
+        if chars[0] != '.' && chars[0] != '[' {
+            return Err(err(0, "path must start with '.'"));
+        }
+
+        while pos < chars.len() {
+            match chars[pos] {
+                '.' => {
+                    pos += 1;
+                    let start = pos;
+                    while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_' || chars[pos] == '-') {
+                        pos += 1;
+                    }
+                    if pos > start {
+                        segments.push(PathSegment::Key(chars[start..pos].iter().collect()));
+                    } else if pos < chars.len() && chars[pos] != '[' {
+                        return Err(err(pos, "expected key after '.'"));
+                    } else if pos == chars.len() && start > 1 {
+                        // Only `.` on its own may end without a key
+                        return Err(err(pos, "expected key after '.'"));
+                    }
+                }
+                '[' => {
+                    pos += 1;
+                    if pos < chars.len() && chars[pos] == '"' {
+                        pos += 1;
+                        let mut key = String::new();
+                        while pos < chars.len() && chars[pos] != '"' {
+                            if chars[pos] == '\\' && pos + 1 < chars.len() {
+                                pos += 1;
+                            }
+                            key.push(chars[pos]);
+                            pos += 1;
+                        }
+                        if pos >= chars.len() {
+                            return Err(err(pos, "unterminated string"));
+                        }
+                        pos += 1; // closing quote
+                        segments.push(PathSegment::Key(key));
+                    } else {
+                        let start = pos;
+                        while pos < chars.len() && (chars[pos].is_ascii_digit() || chars[pos] == '-') {
+                            pos += 1;
+                        }
+                        let num: String = chars[start..pos].iter().collect();
+                        let index = num
+                            .parse::<i64>()
+                            .map_err(|_| err(start, "expected array index or quoted key"))?;
+                        segments.push(PathSegment::Index(index));
+                    }
+                    if pos >= chars.len() || chars[pos] != ']' {
+                        return Err(err(pos, "expected ']'"));
+                    }
+                    pos += 1;
+                }
+                _ => return Err(err(pos, "unexpected character")),
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Select the value at this path, if present
+    pub fn select<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        let mut current = value;
+        for segment in &self.segments {
+            current = match segment {
+                PathSegment::Key(key) => current.get(key.as_str())?,
+                PathSegment::Index(index) => {
+                    let arr = current.as_array()?;
+                    let i = if *index < 0 {
+                        arr.len().checked_sub(index.unsigned_abs() as usize)?
+                    } else {
+                        *index as usize
+                    };
+                    arr.get(i)?
+                }
+            };
+        }
+        Some(current)
+    }
+}
+
+/// Run a path query against raw JSON text
+///
+/// Returns `Ok(None)` when the path does not exist in the document.
+pub fn query_json(raw: &str, expr: &str) -> Result<Option<Value>, JsonPathError> {
+    let path = JsonPath::parse(expr)?;
+    let value: Value = serde_json::from_str(raw).map_err(|e| JsonPathError {
+        position: 0,
+        message: format!("line is not valid JSON: {}", e),
+    })?;
+    Ok(path.select(&value).cloned())
+}
+
+/// Format a query result for display or copying
+///
+/// Strings are shown unquoted (like `jq -r`), everything else pretty-printed.
+pub fn format_query_result(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_paths() {
+        assert!(JsonPath::parse(".").unwrap().segments.is_empty());
+        assert_eq!(
+            JsonPath::parse(".message.usage").unwrap().segments,
+            vec![PathSegment::Key("message".into()), PathSegment::Key("usage".into())]
+        );
+        assert_eq!(
+            JsonPath::parse(".message.content[-1]").unwrap().segments,
+            vec![
+                PathSegment::Key("message".into()),
+                PathSegment::Key("content".into()),
+                PathSegment::Index(-1),
+            ]
+        );
+        assert_eq!(
+            JsonPath::parse(".[\"odd key\"]").unwrap().segments,
+            vec![PathSegment::Key("odd key".into())]
+        );
+        assert!(JsonPath::parse("message").is_err());
+        assert!(JsonPath::parse(".message.").is_err());
+        assert!(JsonPath::parse(".content[0].").is_err());
+        assert!(JsonPath::parse(".a[").is_err());
+        assert!(JsonPath::parse("").is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let value = json!({
+            "message": {"usage": {"input_tokens": 10}, "content": [{"text": "a"}, {"text": "b"}]},
+            "toolUseResult": {"stdout": "ok"}
+        });
+
+        let usage = JsonPath::parse(".message.usage.input_tokens").unwrap();
+        assert_eq!(usage.select(&value), Some(&json!(10)));
+
+        let last = JsonPath::parse(".message.content[-1].text").unwrap();
+        assert_eq!(last.select(&value), Some(&json!("b")));
+
+        let missing = JsonPath::parse(".message.content[5]").unwrap();
+        assert_eq!(missing.select(&value), None);
+    }
+
+    #[test]
+    fn test_query_json_and_format() {
+        let raw = r#"{"toolUseResult":{"stdout":"hello\nworld"}}"#;
+        let result = query_json(raw, ".toolUseResult.stdout").unwrap().unwrap();
+        assert_eq!(format_query_result(&result), "hello\nworld");
+        assert_eq!(query_json(raw, ".nope").unwrap(), None);
+    }
+}
@@ -7,8 +7,42 @@ pub mod types;
 pub mod parser;
 pub mod filter;
 pub mod render;
+pub mod json_path;
+pub mod history;
+pub mod sanitize;
+pub mod ansi;
+pub mod cost;
+pub mod context;
+pub mod config;
+pub mod summarize;
+pub mod timeline;
+pub mod noise;
+pub mod ignore;
+pub mod system;
+pub mod api_failure;
+pub mod encryption;
+pub mod fuzzy;
+pub mod locale;
+pub mod platform;
 
 pub use types::*;
 pub use parser::*;
 pub use filter::*;
 pub use render::*;
+pub use json_path::*;
+pub use history::*;
+pub use sanitize::*;
+pub use ansi::*;
+pub use cost::*;
+pub use context::*;
+pub use config::*;
+pub use summarize::*;
+pub use timeline::*;
+pub use noise::*;
+pub use ignore::*;
+pub use system::*;
+pub use api_failure::*;
+pub use encryption::*;
+pub use fuzzy::*;
+pub use locale::*;
+pub use platform::*;
@@ -0,0 +1,191 @@
+//! Locale conventions for numbers, sizes and dates
+//!
+//! Numbers follow `LC_ALL`, then `LC_NUMERIC`, then `LANG`; dates follow
+//! `LC_ALL`, then `LC_TIME`, then `LANG`. `"locale"` in the config file
+//! overrides both. Unknown or unset locales (including `C` and `POSIX`) keep
+//! the defaults the output always had: `1,234`, `1.5 MB` and ISO dates.
+
+use std::sync::OnceLock;
+
+use chrono::{DateTime, TimeZone};
+
+use crate::config::Config;
+
+/// How numbers and dates are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    /// Between groups of three digits
+    pub thousands: &'static str,
+    /// Before the fraction
+    pub decimal: char,
+    /// strftime format of a date
+    pub date: &'static str,
+    /// strftime format of a time of day
+    pub time: &'static str,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            thousands: ",",
+            decimal: '.',
+            date: "%Y-%m-%d",
+            time: "%H:%M",
+        }
+    }
+}
+
+/// The process locale, from the config file or the environment
+pub fn locale() -> &'static Locale {
+    static LOCALE: OnceLock<Locale> = OnceLock::new();
+    LOCALE.get_or_init(|| Locale::from_env(Config::load().locale.as_deref()))
+}
+
+impl Locale {
+    /// Conventions of `configured` when set, else of the `LC_*` variables
+    pub fn from_env(configured: Option<&str>) -> Self {
+        if let Some(name) = configured.filter(|n| !n.trim().is_empty()) {
+            return Self::from_name(name);
+        }
+        let var = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|n| std::env::var(n).ok().filter(|v| !v.is_empty()))
+                .unwrap_or_default()
+        };
+        Self::from_names(&var(&["LC_ALL", "LC_NUMERIC", "LANG"]), &var(&["LC_ALL", "LC_TIME", "LANG"]))
+    }
+
+    /// Conventions of one locale name, e.g. `de_DE.UTF-8`
+    pub fn from_name(name: &str) -> Self {
+        Self::from_names(name, name)
+    }
+
+    /// Number conventions of `numeric` and date conventions of `time`
+    pub fn from_names(numeric: &str, time: &str) -> Self {
+        let mut locale = Self::default();
+
+        let (language, region) = split_name(numeric);
+        (locale.thousands, locale.decimal) = match (language.as_str(), region.as_str()) {
+            ("de" | "it", "CH") => ("'", '.'),
+            ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el", _) => (".", ','),
+            ("fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu", _) => ("\u{a0}", ','),
+            _ => (locale.thousands, locale.decimal),
+        };
+
+        let (language, region) = split_name(time);
+        (locale.date, locale.time) = match (language.as_str(), region.as_str()) {
+            ("en", "US") => ("%m/%d/%Y", "%-I:%M %p"),
+            ("en", "GB" | "AU" | "NZ" | "IE" | "IN") => ("%d/%m/%Y", "%H:%M"),
+            ("de" | "ru" | "pl" | "cs" | "sk" | "fi" | "nb" | "no" | "da" | "tr" | "uk", _) => ("%d.%m.%Y", "%H:%M"),
+            ("fr" | "es" | "it" | "pt" | "el", _) => ("%d/%m/%Y", "%H:%M"),
+            ("nl", _) => ("%d-%m-%Y", "%H:%M"),
+            ("ja" | "zh", _) => ("%Y/%m/%d", "%H:%M"),
+            _ => (locale.date, locale.time),
+        };
+
+        locale
+    }
+
+    /// A whole number with thousands separators
+    pub fn format_count(&self, n: i64) -> String {
+        let digits = n.unsigned_abs().to_string();
+        let mut result = String::new();
+        if n < 0 {
+            result.push('-');
+        }
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                result.push_str(self.thousands);
+            }
+            result.push(c);
+        }
+        result
+    }
+
+    /// A number with `places` decimals and the locale's decimal mark
+    pub fn format_decimal(&self, value: f64, places: usize) -> String {
+        let text = format!("{:.*}", places, value);
+        if self.decimal == '.' {
+            text
+        } else {
+            text.replace('.', &self.decimal.to_string())
+        }
+    }
+
+    /// A byte count in B, KB, MB or GB
+    pub fn format_size(&self, bytes: u64) -> String {
+        const KB: f64 = 1024.0;
+        let size = bytes as f64;
+        if size < KB {
+            format!("{} B", bytes)
+        } else if size < KB * KB {
+            format!("{} KB", self.format_decimal(size / KB, 1))
+        } else if size < KB * KB * KB {
+            format!("{} MB", self.format_decimal(size / (KB * KB), 1))
+        } else {
+            format!("{} GB", self.format_decimal(size / (KB * KB * KB), 1))
+        }
+    }
+
+    /// The date part of `time`
+    pub fn format_date<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        time.format(self.date).to_string()
+    }
+
+    /// Date and time of day of `time`
+    pub fn format_datetime<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        format!("{} {}", time.format(self.date), time.format(self.time))
+    }
+}
+
+/// Language and region of `de_DE.UTF-8@euro`: ("de", "DE")
+fn split_name(name: &str) -> (String, String) {
+    let name = name.split(['.', '@']).next().unwrap_or_default();
+    let (language, region) = name.split_once(['_', '-']).unwrap_or((name, ""));
+    (language.to_ascii_lowercase(), region.to_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_default_keeps_existing_formats() {
+        let locale = Locale::from_name("C");
+        assert_eq!(locale.format_count(1234567), "1,234,567");
+        assert_eq!(locale.format_count(-1234), "-1,234");
+        assert_eq!(locale.format_count(999), "999");
+        assert_eq!(locale.format_size(1536 * 1024), "1.5 MB");
+        assert_eq!(locale.format_size(12), "12 B");
+    }
+
+    #[test]
+    fn test_numbers_follow_locale() {
+        let de = Locale::from_name("de_DE.UTF-8");
+        assert_eq!(de.format_count(1234567), "1.234.567");
+        assert_eq!(de.format_size(1536), "1,5 KB");
+        assert_eq!(Locale::from_name("fr_FR").format_count(12345), "12\u{a0}345");
+        assert_eq!(Locale::from_name("de_CH").format_count(12345), "12'345");
+    }
+
+    #[test]
+    fn test_dates_follow_time_locale() {
+        let time = Utc.with_ymd_and_hms(2026, 3, 7, 14, 5, 0).unwrap();
+        assert_eq!(Locale::default().format_datetime(&time), "2026-03-07 14:05");
+        assert_eq!(Locale::from_name("en_US").format_datetime(&time), "03/07/2026 2:05 PM");
+        assert_eq!(Locale::from_name("de_DE").format_date(&time), "07.03.2026");
+
+        // Numbers and dates can come from different locales
+        let mixed = Locale::from_names("en_US", "de_DE");
+        assert_eq!(mixed.format_count(1000), "1,000");
+        assert_eq!(mixed.format_date(&time), "07.03.2026");
+    }
+}
@@ -0,0 +1,213 @@
+//! Noise classification: lines that carry nothing worth reading
+//!
+//! Two kinds of rule mark a line as noise. Pattern rules match its text as a
+//! whole (case-insensitive, `*` matches any run of characters), after
+//! unwrapping local command output. Subtype rules match the `subtype` of
+//! system lines. The built-in rules are extended from the config file:
+//!
+//! ```json
+//! {"noise": {"patterns": ["Compacted*"], "subtypes": ["turn_duration"]}}
+//! ```
+//!
+//! The CLI's human and minimal output, the Markdown export and the viewers'
+//! text-only mode all hide what this classifies as noise.
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::types::{LineType, TranscriptLine};
+
+/// Text that is noise whatever the line
+pub const DEFAULT_NOISE_PATTERNS: &[&str] = &["Status dialog dismissed"];
+
+/// System subtypes that are noise (none by default; see the config)
+pub const DEFAULT_NOISE_SUBTYPES: &[&str] = &[];
+
+/// Wrappers Claude Code puts around local command output
+const OUTPUT_TAGS: &[(&str, &str)] = &[
+    ("<local-command-stdout>", "</local-command-stdout>"),
+    ("<local-command-stderr>", "</local-command-stderr>"),
+];
+
+/// Noise rules added by the user, on top of the built-in ones
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct NoiseConfig {
+    pub patterns: Vec<String>,
+    pub subtypes: Vec<String>,
+}
+
+/// Decides whether a line (or a piece of text) is noise
+#[derive(Debug, Clone)]
+pub struct NoiseClassifier {
+    /// Lowercased, so matching ignores case
+    patterns: Vec<String>,
+    subtypes: Vec<String>,
+}
+
+impl Default for NoiseClassifier {
+    fn default() -> Self {
+        Self::new(&NoiseConfig::default())
+    }
+}
+
+impl NoiseClassifier {
+    /// The built-in rules plus those in `config`
+    pub fn new(config: &NoiseConfig) -> Self {
+        let patterns = DEFAULT_NOISE_PATTERNS
+            .iter()
+            .copied()
+            .chain(config.patterns.iter().map(String::as_str))
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect();
+        let subtypes = DEFAULT_NOISE_SUBTYPES
+            .iter()
+            .copied()
+            .chain(config.subtypes.iter().map(String::as_str))
+            .map(str::to_string)
+            .collect();
+        Self { patterns, subtypes }
+    }
+
+    /// Whether a system line subtype is noise
+    pub fn is_noise_subtype(&self, subtype: &str) -> bool {
+        self.subtypes.iter().any(|s| s == subtype)
+    }
+
+    /// Whether text is noise (empty text is not: there is nothing to match)
+    pub fn is_noise_text(&self, text: &str) -> bool {
+        let mut text = text.trim();
+        for (open, close) in OUTPUT_TAGS {
+            if let Some(inner) = text.strip_prefix(open).and_then(|t| t.strip_suffix(close)) {
+                text = inner.trim();
+            }
+        }
+        if text.is_empty() {
+            return false;
+        }
+        let text = text.to_lowercase();
+        self.patterns.iter().any(|pattern| glob_match(pattern, &text))
+    }
+
+    /// Whether a line is noise, by its subtype or its text
+    ///
+    /// System lines keep their text in the raw `content` field rather than
+    /// the indexed content, so both are checked.
+    pub fn is_noise(&self, line: &TranscriptLine) -> bool {
+        if let Some(text) = line.content.as_deref().filter(|c| !c.trim().is_empty()) {
+            if self.is_noise_text(text) {
+                return true;
+            }
+        }
+        if line.line_type != LineType::System {
+            return false;
+        }
+        let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&line.raw) else {
+            return false;
+        };
+        parsed
+            .get("subtype")
+            .and_then(|v| v.as_str())
+            .is_some_and(|subtype| self.is_noise_subtype(subtype))
+            || parsed
+                .get("content")
+                .and_then(|v| v.as_str())
+                .is_some_and(|text| self.is_noise_text(text))
+    }
+}
+
+/// The classifier for the user's config, loaded once per process
+pub fn noise_classifier() -> &'static NoiseClassifier {
+    static CLASSIFIER: OnceLock<NoiseClassifier> = OnceLock::new();
+    CLASSIFIER.get_or_init(|| NoiseClassifier::new(&Config::load().noise))
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: the whole text must match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Match a file path against a glob, where `*` and `**` match any run of
+/// characters; a relative glob matches below any directory, so `src/*.rs`
+/// matches `/home/me/app/src/main.rs`. Backslashes count as slashes, so
+/// globs match Windows paths too.
+pub fn path_glob_match(glob: &str, path: &str) -> bool {
+    let glob = glob.trim().replace('\\', "/").replace("**/", "*").replace("**", "*");
+    let path = path.replace('\\', "/");
+    glob_match(&glob, &path) || (!glob.starts_with(['/', '*']) && glob_match(&format!("*/{}", glob), &path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_type: LineType, content: Option<&str>, raw: &str) -> TranscriptLine {
+        TranscriptLine {
+            id: 1,
+            line_number: 1,
+            content: content.map(str::to_string),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_path_globs() {
+        assert!(path_glob_match("src/schema.rs", "/home/me/app/src/schema.rs"));
+        assert!(path_glob_match("src/**/*.rs", "/home/me/app/src/db/schema.rs"));
+        assert!(path_glob_match("src/**/*.rs", "/home/me/app/src/main.rs"));
+        assert!(path_glob_match("src/*.rs", r"C:\Users\me\app\src\main.rs"));
+        assert!(path_glob_match("*.toml", "/home/me/app/Cargo.toml"));
+        assert!(path_glob_match("/home/me/app/*", "/home/me/app/README.md"));
+        assert!(!path_glob_match("src/schema.rs", "/home/me/app/src/schema.rs.bak"));
+        assert!(!path_glob_match("app/src/schema.rs", "/home/me/webapp/src/schema.rs"));
+        assert!(!path_glob_match("/app/*", "/home/me/app/README.md"));
+    }
+
+    #[test]
+    fn test_noise_rules() {
+        let noise = NoiseClassifier::new(&NoiseConfig {
+            patterns: vec!["Compacted*".to_string(), "*(no content)".to_string()],
+            subtypes: vec!["turn_duration".to_string()],
+        });
+
+        // Built-in pattern, any case, inside local command output
+        assert!(noise.is_noise_text("status dialog dismissed"));
+        assert!(noise.is_noise_text("<local-command-stdout>Status dialog dismissed</local-command-stdout>"));
+        assert!(!noise.is_noise_text("Status dialog dismissed twice"));
+        assert!(noise.is_noise_text("Compacted (ctrl+r to see full summary)"));
+        assert!(noise.is_noise_text("reply: (no content)"));
+        assert!(!noise.is_noise_text(""));
+
+        let system = |raw: &str| line(LineType::System, None, raw);
+        assert!(noise.is_noise(&system(r#"{"type":"system","subtype":"turn_duration","durationMs":5}"#)));
+        assert!(noise.is_noise(&system(
+            r#"{"type":"system","subtype":"local_command","content":"<local-command-stdout>Status dialog dismissed</local-command-stdout>"}"#
+        )));
+        assert!(!noise.is_noise(&system(r#"{"type":"system","subtype":"init","version":"1.0"}"#)));
+        // Subtype rules are for system lines only
+        assert!(!noise.is_noise(&line(LineType::User, Some("hi"), r#"{"subtype":"turn_duration"}"#)));
+        assert!(noise.is_noise(&line(LineType::User, Some("Compacted"), "{}")));
+
+        // Without config, only the built-ins apply
+        assert!(!NoiseClassifier::default().is_noise_text("Compacted"));
+    }
+}
@@ -4,6 +4,22 @@ use crate::types::{LineType, ParsedLine, TranscriptLine};
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+/// Byte-order mark some editors and Windows tools put at the start of a file
+const BOM: char = '\u{feff}';
+
+/// Decode a line of a JSONL file without losing it to bad bytes: invalid
+/// UTF-8 becomes U+FFFD and a leading byte-order mark is dropped
+///
+/// The flag says whether the line needed either, for counting recoveries.
+pub fn decode_line(bytes: &[u8]) -> (String, bool) {
+    let text = String::from_utf8_lossy(bytes);
+    let lossy = matches!(text, std::borrow::Cow::Owned(_));
+    match text.strip_prefix(BOM) {
+        Some(rest) => (rest.to_string(), true),
+        None => (text.into_owned(), lossy),
+    }
+}
+
 /// Parse a single JSONL line into a TranscriptLine
 pub fn parse_line(raw: &str, line_number: i64, id: i64) -> Option<TranscriptLine> {
     let parsed: ParsedLine = serde_json::from_str(raw).ok()?;
@@ -21,10 +37,8 @@ pub fn parse_line(raw: &str, line_number: i64, id: i64) -> Option<TranscriptLine
     // Extract content from message
     let content = if let Some(msg) = &parsed.message {
         Some(msg.content.as_text())
-    } else if let Some(summary) = &parsed.summary {
-        Some(summary.clone())
     } else {
-        None
+        parsed.summary.clone()
     };
 
     let role = parsed.message.as_ref().map(|m| m.role.clone());
@@ -55,18 +69,17 @@ pub fn parse_file<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<TranscriptLine
     let file = std::fs::File::open(path)?;
     let reader = BufReader::new(file);
     let mut lines = Vec::new();
-    let mut line_number = 1i64;
     let mut id = 1i64;
 
-    for line_result in reader.lines() {
-        let raw = line_result?;
+    for (line_number, line_result) in (1i64..).zip(reader.split(b'\n')) {
+        let bytes = line_result?;
+        let (raw, _) = decode_line(bytes.strip_suffix(b"\r").unwrap_or(&bytes));
         if !raw.trim().is_empty() {
             if let Some(line) = parse_line(&raw, line_number, id) {
                 lines.push(line);
                 id += 1;
             }
         }
-        line_number += 1;
     }
 
     Ok(lines)
@@ -111,6 +124,13 @@ pub fn extract_text(line: &TranscriptLine) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_decode_line_recovers_bad_bytes_and_bom() {
+        assert_eq!(decode_line(b"{\"a\":1}"), ("{\"a\":1}".to_string(), false));
+        assert_eq!(decode_line(b"\xef\xbb\xbf{\"a\":1}"), ("{\"a\":1}".to_string(), true));
+        assert_eq!(decode_line(b"{\"a\":\"\xff\"}"), ("{\"a\":\"\u{fffd}\"}".to_string(), true));
+    }
+
     #[test]
     fn test_parse_user_line() {
         let raw = r#"{"type":"user","uuid":"abc","sessionId":"123","timestamp":"2024-01-01T00:00:00Z","message":{"role":"user","content":"Hello"}}"#;
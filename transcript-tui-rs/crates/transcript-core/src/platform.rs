@@ -0,0 +1,100 @@
+//! What differs between Unix and Windows: the home directory, file paths
+//! and the shell
+//!
+//! The home directory comes from the platform (`$HOME` on Unix, the user
+//! profile folder on Windows), so nothing assumes `$HOME` is set. Paths
+//! stored as keys in the index go through `path_key`, which spells one
+//! file one way: Windows paths lose the `\\?\` prefix that canonicalizing
+//! adds, and use backslashes throughout.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The user's home directory (`~` when the platform doesn't know it)
+pub fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("~"))
+}
+
+/// `~/.claude-code-sdk`, holding the database, config and caches
+pub fn sdk_dir() -> PathBuf {
+    home_dir().join(".claude-code-sdk")
+}
+
+/// Expand a leading `~` (`~/x`, or `~\x` on Windows) to the home directory
+pub fn expand_home(path: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{}{}", home_dir().to_string_lossy(), rest)
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// Abbreviate a path under the home directory to `~/...` for display
+pub fn abbreviate_home(path: &str) -> String {
+    abbreviate(path, &home_dir().to_string_lossy(), cfg!(windows))
+}
+
+fn abbreviate(path: &str, home: &str, windows: bool) -> String {
+    let home = home.trim_end_matches(['/', '\\']);
+    if home.is_empty() || home == "~" || path.len() < home.len() || !path.is_char_boundary(home.len()) {
+        return path.to_string();
+    }
+    let (head, rest) = path.split_at(home.len());
+    // Windows paths are case-insensitive; `/home/al` isn't the home of `/home/alice`
+    let same = if windows { head.eq_ignore_ascii_case(home) } else { head == home };
+    if same && (rest.is_empty() || rest.starts_with(['/', '\\'])) {
+        format!("~{}", rest)
+    } else {
+        path.to_string()
+    }
+}
+
+/// The string a file is recorded under in the index
+pub fn path_key(path: &Path) -> String {
+    key(&path.to_string_lossy(), cfg!(windows))
+}
+
+fn key(path: &str, windows: bool) -> String {
+    if !windows {
+        return path.to_string();
+    }
+    // `\\?\C:\x` is `C:\x`, and `\\?\UNC\server\share\x` is `\\server\share\x`
+    let path = path.replace('/', "\\");
+    if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", rest)
+    } else if let Some(rest) = path.strip_prefix(r"\\?\") {
+        rest.to_string()
+    } else {
+        path
+    }
+}
+
+/// A user-configured command line, run by `sh -c` (`cmd /C` on Windows)
+pub fn shell_command(command: &str) -> Command {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut cmd = Command::new(shell);
+    cmd.arg(flag).arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_across_platforms() {
+        assert_eq!(abbreviate("/home/alice/.claude/projects", "/home/alice", false), "~/.claude/projects");
+        assert_eq!(abbreviate("/home/alice2/x", "/home/alice", false), "/home/alice2/x");
+        assert_eq!(abbreviate(r"c:\users\Bob\.claude", r"C:\Users\Bob\", true), r"~\.claude");
+        assert_eq!(abbreviate("/tmp/x", "~", false), "/tmp/x");
+
+        assert_eq!(key("/home/alice/s.jsonl", false), "/home/alice/s.jsonl");
+        assert_eq!(key(r"\\?\C:\Users\bob\s.jsonl", true), r"C:\Users\bob\s.jsonl");
+        assert_eq!(key(r"\\?\UNC\nas\home\bob/s.jsonl", true), r"\\nas\home\bob\s.jsonl");
+        assert_eq!(key("C:/Users/bob/.claude/projects", true), r"C:\Users\bob\.claude\projects");
+
+        assert_eq!(expand_home("~nobody/x"), "~nobody/x");
+        assert_eq!(expand_home("~/x"), format!("{}/x", home_dir().to_string_lossy()));
+    }
+}
@@ -1,5 +1,7 @@
 //! Content rendering utilities
 
+use crate::context::line_context_window;
+use crate::system::system_summary;
 use crate::types::{LineType, TranscriptLine, ViewMode};
 
 /// Rendered content with optional styling hints
@@ -31,7 +33,7 @@ impl RenderedContent {
 pub fn render_line(line: &TranscriptLine, mode: ViewMode) -> RenderedContent {
     match mode {
         ViewMode::Json => render_json(line),
-        ViewMode::Custom => render_custom(line),
+        ViewMode::Custom | ViewMode::Thread => render_custom(line),
     }
 }
 
@@ -96,6 +98,10 @@ fn render_md(line: &TranscriptLine) -> RenderedContent {
             }
         }
         LineType::System => {
+            if let Some(summary) = system_summary(line) {
+                parts.push(summary.text);
+                parts.push(String::new());
+            }
             if let Ok(parsed) = line.parse() {
                 if let Some(subtype) = &parsed.subtype {
                     parts.push(format!("Subtype: {}", subtype));
@@ -185,13 +191,7 @@ fn render_edit_diff(line: &TranscriptLine) -> RenderedContent {
                 let old_string = input.get("old_string").and_then(|v| v.as_str()).unwrap_or("");
                 let new_string = input.get("new_string").and_then(|v| v.as_str()).unwrap_or("");
 
-                parts.push("@@ removed / added @@".to_string());
-                for old_line in old_string.lines() {
-                    parts.push(format!("- {}", old_line));
-                }
-                for new_line in new_string.lines() {
-                    parts.push(format!("+ {}", new_line));
-                }
+                parts.extend(edit_diff(old_string, new_string));
             }
         }
     }
@@ -199,6 +199,19 @@ fn render_edit_diff(line: &TranscriptLine) -> RenderedContent {
     RenderedContent::plain(parts.join("\n"))
 }
 
+/// An edit as diff lines: a hunk header, the removed lines (`- `), then the
+/// added ones (`+ `)
+pub fn edit_diff(old_string: &str, new_string: &str) -> Vec<String> {
+    let mut lines = vec!["@@ removed / added @@".to_string()];
+    for old_line in old_string.lines() {
+        lines.push(format!("- {}", old_line));
+    }
+    for new_line in new_string.lines() {
+        lines.push(format!("+ {}", new_line));
+    }
+    lines
+}
+
 /// Render Bash tool output
 fn render_bash_output(line: &TranscriptLine) -> RenderedContent {
     let mut parts = vec!["━━━ BASH ━━━".to_string()];
@@ -343,7 +356,7 @@ fn render_todo_list(line: &TranscriptLine) -> RenderedContent {
             for (_, _, input) in msg.content.tool_uses() {
                 if let Some(todos) = input.get("todos").and_then(|v| v.as_array()) {
                     let total = todos.len();
-                    let mut completed = 0;
+                    let mut completed = 0usize;
 
                     for todo in todos {
                         let content = todo.get("content").and_then(|v| v.as_str()).unwrap_or("");
@@ -362,7 +375,7 @@ fn render_todo_list(line: &TranscriptLine) -> RenderedContent {
                     }
 
                     parts.push(String::new());
-                    let percent = if total > 0 { completed * 100 / total } else { 0 };
+                    let percent = (completed * 100).checked_div(total).unwrap_or(0);
                     parts.push(format!("Progress: {}/{} ({}%)", completed, total, percent));
                 }
             }
@@ -409,5 +422,113 @@ pub fn calculate_usage_percent(line: &TranscriptLine, context_size: u64) -> Opti
     Some((total as f64 / context_size as f64) * 100.0)
 }
 
-/// Default context window size for Claude (200K tokens)
-pub const DEFAULT_CONTEXT_SIZE: u64 = 200_000;
+/// One point of a session's context usage progression
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsagePoint {
+    /// Index of the line in the slice passed to `usage_series`
+    pub index: usize,
+    /// Line number in the transcript file
+    pub line_number: i64,
+    /// Time of day (HH:MM:SS)
+    pub time: String,
+    /// Total tokens in context (input + output + cache)
+    pub tokens: u64,
+    /// Percentage of the context window
+    pub percent: f64,
+    /// The context window `percent` is of
+    pub context_size: u64,
+    /// The context was compacted since the previous point
+    pub after_compaction: bool,
+}
+
+/// Whether a line marks a context compaction (`/compact` or auto-compact)
+pub fn is_compaction_boundary(line: &TranscriptLine) -> bool {
+    match line.line_type {
+        LineType::Summary => true,
+        LineType::System => serde_json::from_str::<serde_json::Value>(&line.raw)
+            .ok()
+            .and_then(|v| v.get("subtype").and_then(|s| s.as_str()).map(|s| s == "compact_boundary"))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Per-line token progression for the usage graph, with compaction markers
+///
+/// Percentages are of `context_size` when given, else of the context window
+/// of the model that wrote each line.
+pub fn usage_series(lines: &[TranscriptLine], context_size: Option<u64>) -> Vec<UsagePoint> {
+    let mut points = Vec::new();
+    let mut compacted = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        if is_compaction_boundary(line) {
+            compacted = true;
+            continue;
+        }
+        let context_size = context_size.unwrap_or_else(|| line_context_window(line));
+        let Some(percent) = calculate_usage_percent(line, context_size) else {
+            continue;
+        };
+        let tokens = line.usage().map(|u| u.total()).unwrap_or(0);
+        if tokens == 0 {
+            continue;
+        }
+
+        points.push(UsagePoint {
+            index,
+            line_number: line.line_number,
+            time: line.format_time(),
+            tokens,
+            percent,
+            context_size,
+            after_compaction: compacted && !points.is_empty(),
+        });
+        compacted = false;
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::DEFAULT_CONTEXT_SIZE;
+
+    fn make_line(line_number: i64, line_type: LineType, raw: &str) -> TranscriptLine {
+        TranscriptLine {
+            id: line_number,
+            line_number,
+            timestamp: "2025-01-01T10:00:00.000Z".to_string(),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    fn assistant(line_number: i64, input_tokens: u64) -> TranscriptLine {
+        let raw = format!(
+            r#"{{"type":"assistant","message":{{"role":"assistant","content":[],"usage":{{"input_tokens":{},"output_tokens":0}}}}}}"#,
+            input_tokens
+        );
+        make_line(line_number, LineType::Assistant, &raw)
+    }
+
+    #[test]
+    fn test_usage_series_marks_compaction() {
+        let lines = vec![
+            make_line(1, LineType::User, r#"{"type":"user"}"#),
+            assistant(2, 100_000),
+            assistant(3, 150_000),
+            make_line(4, LineType::System, r#"{"type":"system","subtype":"compact_boundary"}"#),
+            assistant(5, 20_000),
+        ];
+
+        let series = usage_series(&lines, None);
+        assert_eq!(series.len(), 3);
+        assert_eq!(series[0].index, 1);
+        assert_eq!(series[1].percent, 75.0);
+        assert!(!series[1].after_compaction);
+        assert!(series[2].after_compaction);
+        assert_eq!(series[2].tokens, 20_000);
+        assert_eq!(series[2].context_size, DEFAULT_CONTEXT_SIZE);
+    }
+}
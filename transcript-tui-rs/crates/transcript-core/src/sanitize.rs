@@ -0,0 +1,136 @@
+//! Making tool output safe to put on a terminal
+//!
+//! Tool results can contain binary data or stray control characters that
+//! corrupt the terminal when printed. Renderers pass untrusted text through
+//! `display_text` (whole blocks) or `sanitize_text` (anything appended).
+
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Characters inspected when deciding whether text is binary
+const BINARY_SAMPLE_CHARS: usize = 4096;
+
+/// Bytes shown in the hex preview of binary content
+pub const HEX_PREVIEW_BYTES: usize = 256;
+
+/// Control characters kept as-is (`\r` is normalized, ESC is escaped)
+fn is_allowed_control(c: char) -> bool {
+    matches!(c, '\n' | '\t')
+}
+
+/// Whether text looks like binary data rather than (possibly colored) text
+///
+/// True when it contains NUL or more than 10% of the sample is control or
+/// replacement characters. ESC is not counted so ANSI-colored output passes.
+pub fn is_binary(text: &str) -> bool {
+    let mut total = 0;
+    let mut suspicious = 0;
+    for c in text.chars().take(BINARY_SAMPLE_CHARS) {
+        if c == '\0' {
+            return true;
+        }
+        total += 1;
+        if c == '\u{FFFD}' || (c.is_control() && !matches!(c, '\n' | '\t' | '\r' | '\x1b')) {
+            suspicious += 1;
+        }
+    }
+    total > 0 && suspicious * 10 > total
+}
+
+/// Escape control characters so text can't drive the terminal
+///
+/// `\r\n` and lone `\r` become newlines; other control characters (including
+/// ESC) are shown as `\xNN`. Text without any is returned unchanged.
+pub fn sanitize_text(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(|c| c.is_control() && !is_allowed_control(c)) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len() + 16);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' => out.push('\n'),
+            c if c.is_control() && !is_allowed_control(c) => {
+                let _ = write!(out, "\\x{:02x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Classic hex dump (`offset  hex bytes  |ascii|`) of the first `max` bytes
+pub fn hex_preview(bytes: &[u8], max: usize) -> String {
+    let mut out = String::new();
+    for (row, chunk) in bytes[..bytes.len().min(max)].chunks(16).enumerate() {
+        let _ = write!(out, "{:08x} ", row * 16);
+        for i in 0..16 {
+            if i == 8 {
+                out.push(' ');
+            }
+            match chunk.get(i) {
+                Some(b) => {
+                    let _ = write!(out, " {:02x}", b);
+                }
+                None => out.push_str("   "),
+            }
+        }
+        out.push_str("  |");
+        out.extend(chunk.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+    out.pop();
+    out
+}
+
+/// Text ready for display: a hex preview if binary, otherwise sanitized
+pub fn display_text(text: &str) -> Cow<'_, str> {
+    if is_binary(text) {
+        Cow::Owned(format!(
+            "[binary content, {} bytes; first {} shown]\n{}",
+            text.len(),
+            HEX_PREVIEW_BYTES.min(text.len()),
+            hex_preview(text.as_bytes(), HEX_PREVIEW_BYTES)
+        ))
+    } else {
+        sanitize_text(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_detection() {
+        assert!(!is_binary("hello\nworld\t!"));
+        assert!(!is_binary("\x1b[31merror\x1b[0m: failed"));
+        assert!(is_binary("ELF\0\x01\x02"));
+        assert!(is_binary("\x01\x02\x03\x04abcdef"));
+        assert!(!is_binary(""));
+    }
+
+    #[test]
+    fn test_sanitize_text() {
+        assert!(matches!(sanitize_text("plain\ttext\n"), Cow::Borrowed(_)));
+        assert_eq!(sanitize_text("a\r\nb\rc"), "a\nb\nc");
+        assert_eq!(sanitize_text("\x1b]0;title\x07x"), "\\x1b]0;title\\x07x");
+        assert_eq!(sanitize_text("\u{9b}2J"), "\\x9b2J");
+    }
+
+    #[test]
+    fn test_display_text_binary_preview() {
+        let shown = display_text("\0\x01AB");
+        assert!(shown.starts_with("[binary content, 4 bytes"));
+        assert!(shown.contains("00000000  00 01 41 42"));
+        assert!(shown.ends_with("|..AB|"));
+    }
+}
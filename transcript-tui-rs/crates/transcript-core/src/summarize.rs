@@ -0,0 +1,306 @@
+//! Summaries of session text
+//!
+//! The default backend is extractive: it picks the sentences whose words are
+//! most frequent in the text, so it works offline and is deterministic. With
+//! the `external-summarizer` feature, summaries can instead come from a shell
+//! command or an LLM over HTTP, chosen in the config file.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::config::{SummarizerBackend, SummarizerConfig};
+
+/// Summarizer failures
+#[derive(Debug, Error)]
+pub enum SummarizeError {
+    #[error("summarizer backend '{0}' is not built in (enable the external-summarizer feature)")]
+    Unavailable(&'static str),
+
+    #[error("summarizer backend '{backend}' needs '{setting}' in the config file")]
+    MissingSetting {
+        backend: &'static str,
+        setting: &'static str,
+    },
+
+    #[error("summarizer command failed: {0}")]
+    Command(String),
+
+    #[error("summarizer API request failed: {0}")]
+    Api(String),
+}
+
+/// Something that can shorten text
+pub trait Summarizer {
+    /// Backend name, for messages
+    fn name(&self) -> &'static str;
+
+    /// Summarize `text` in at most `max_chars` characters
+    fn summarize(&self, text: &str, max_chars: usize) -> Result<String, SummarizeError>;
+}
+
+/// Build the summarizer the config asks for
+pub fn summarizer_from_config(
+    config: &SummarizerConfig,
+) -> Result<Box<dyn Summarizer>, SummarizeError> {
+    match config.backend {
+        SummarizerBackend::Extractive => Ok(Box::new(ExtractiveSummarizer)),
+        #[cfg(feature = "external-summarizer")]
+        SummarizerBackend::Command => Ok(Box::new(external::CommandSummarizer::from_config(config)?)),
+        #[cfg(feature = "external-summarizer")]
+        SummarizerBackend::Api => Ok(Box::new(external::ApiSummarizer::from_config(config)?)),
+        #[cfg(not(feature = "external-summarizer"))]
+        SummarizerBackend::Command => Err(SummarizeError::Unavailable("command")),
+        #[cfg(not(feature = "external-summarizer"))]
+        SummarizerBackend::Api => Err(SummarizeError::Unavailable("api")),
+    }
+}
+
+/// Words too common to say what a sentence is about
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "that", "this", "with", "you", "are", "was", "have", "has", "but",
+    "not", "can", "will", "from", "its", "it's", "all", "any", "there", "then", "than", "into",
+    "now", "let", "i'll", "i've", "we'll", "our", "your", "they", "them", "what", "when",
+    "which", "also", "just", "here", "were", "been", "would", "should", "could", "does",
+];
+
+/// Picks the most representative sentences, in their original order
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractiveSummarizer;
+
+impl Summarizer for ExtractiveSummarizer {
+    fn name(&self) -> &'static str {
+        "extractive"
+    }
+
+    fn summarize(&self, text: &str, max_chars: usize) -> Result<String, SummarizeError> {
+        let sentences = split_sentences(text);
+        if sentences.is_empty() {
+            return Ok(String::new());
+        }
+
+        let mut frequency: HashMap<String, usize> = HashMap::new();
+        for sentence in &sentences {
+            for word in content_words(sentence) {
+                *frequency.entry(word).or_default() += 1;
+            }
+        }
+
+        // Mean frequency of a sentence's content words; earlier sentences win ties
+        let mut ranked: Vec<(usize, f64)> = sentences
+            .iter()
+            .enumerate()
+            .map(|(i, sentence)| {
+                let words = content_words(sentence);
+                let score = if words.is_empty() {
+                    0.0
+                } else {
+                    words.iter().map(|w| frequency[w] as f64).sum::<f64>() / words.len() as f64
+                };
+                (i, score)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(&b.0)));
+
+        let mut chosen = Vec::new();
+        let mut used = 0;
+        for (i, _) in ranked {
+            let len = sentences[i].chars().count() + usize::from(!chosen.is_empty());
+            if used + len <= max_chars {
+                chosen.push(i);
+                used += len;
+            }
+        }
+        if chosen.is_empty() {
+            return Ok(clip(sentences[0], max_chars));
+        }
+
+        chosen.sort_unstable();
+        Ok(chosen.iter().map(|&i| sentences[i]).collect::<Vec<_>>().join(" "))
+    }
+}
+
+/// Sentences and lines of `text`, trimmed, without list markers
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    for line in text.lines() {
+        let line = line.trim().trim_start_matches(['-', '*', '#', '>']).trim();
+        let mut start = 0;
+        let bytes = line.as_bytes();
+        for (i, &b) in bytes.iter().enumerate() {
+            let at_end = i + 1 == bytes.len() || bytes[i + 1] == b' ';
+            if matches!(b, b'.' | b'!' | b'?') && at_end {
+                sentences.push(line[start..=i].trim());
+                start = i + 1;
+            }
+        }
+        sentences.push(line[start..].trim());
+    }
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// Lowercased words of three letters or more that aren't stopwords
+fn content_words(sentence: &str) -> Vec<String> {
+    sentence
+        .split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '_')
+        .map(|w| w.to_lowercase())
+        .filter(|w| w.chars().count() >= 3 && !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Keep the first `max` characters, marking the cut
+fn clip(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut kept: String = text.chars().take(max.saturating_sub(1)).collect();
+    kept.push('…');
+    kept
+}
+
+#[cfg(feature = "external-summarizer")]
+mod external {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    use super::{clip, SummarizeError, Summarizer};
+    use crate::config::SummarizerConfig;
+    use crate::platform::shell_command;
+
+    const DEFAULT_API_URL: &str = "https://api.anthropic.com/v1/messages";
+    const DEFAULT_API_KEY_ENV: &str = "ANTHROPIC_API_KEY";
+
+    /// Runs a shell command with the text on stdin; its stdout is the summary
+    pub struct CommandSummarizer {
+        command: String,
+    }
+
+    impl CommandSummarizer {
+        pub fn from_config(config: &SummarizerConfig) -> Result<Self, SummarizeError> {
+            let command = config.command.clone().ok_or(SummarizeError::MissingSetting {
+                backend: "command",
+                setting: "command",
+            })?;
+            Ok(Self { command })
+        }
+    }
+
+    impl Summarizer for CommandSummarizer {
+        fn name(&self) -> &'static str {
+            "command"
+        }
+
+        fn summarize(&self, text: &str, max_chars: usize) -> Result<String, SummarizeError> {
+            let mut child = shell_command(&self.command)
+                .env("SUMMARY_MAX_CHARS", max_chars.to_string())
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| SummarizeError::Command(e.to_string()))?;
+
+            // A command that exits without reading all of stdin is fine
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            let output = child
+                .wait_with_output()
+                .map_err(|e| SummarizeError::Command(e.to_string()))?;
+            if !output.status.success() {
+                return Err(SummarizeError::Command(
+                    String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                ));
+            }
+            Ok(clip(String::from_utf8_lossy(&output.stdout).trim(), max_chars))
+        }
+    }
+
+    /// Asks an LLM through a Messages-style HTTP API
+    pub struct ApiSummarizer {
+        url: String,
+        model: String,
+        api_key: String,
+    }
+
+    impl ApiSummarizer {
+        pub fn from_config(config: &SummarizerConfig) -> Result<Self, SummarizeError> {
+            let model = config.model.clone().ok_or(SummarizeError::MissingSetting {
+                backend: "api",
+                setting: "model",
+            })?;
+            let key_env = config.api_key_env.as_deref().unwrap_or(DEFAULT_API_KEY_ENV);
+            let api_key = std::env::var(key_env)
+                .map_err(|_| SummarizeError::Api(format!("{} is not set", key_env)))?;
+            Ok(Self {
+                url: config.url.clone().unwrap_or_else(|| DEFAULT_API_URL.to_string()),
+                model,
+                api_key,
+            })
+        }
+    }
+
+    impl Summarizer for ApiSummarizer {
+        fn name(&self) -> &'static str {
+            "api"
+        }
+
+        fn summarize(&self, text: &str, max_chars: usize) -> Result<String, SummarizeError> {
+            let body = serde_json::json!({
+                "model": self.model,
+                // Roughly four characters per token, with room to finish a sentence
+                "max_tokens": (max_chars / 3).max(64),
+                "messages": [{
+                    "role": "user",
+                    "content": format!(
+                        "Summarize what happened in this coding session in at most {} characters. \
+                         Reply with the summary only.\n\n{}",
+                        max_chars, text
+                    ),
+                }],
+            });
+            let response: serde_json::Value = ureq::post(&self.url)
+                .set("x-api-key", &self.api_key)
+                .set("anthropic-version", "2023-06-01")
+                .set("content-type", "application/json")
+                .send_string(&body.to_string())
+                .map_err(|e| SummarizeError::Api(e.to_string()))?
+                .into_string()
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .ok_or_else(|| SummarizeError::Api("unreadable response".to_string()))?;
+
+            let summary: Vec<&str> = response
+                .get("content")
+                .and_then(|c| c.as_array())
+                .map(|blocks| blocks.iter().filter_map(|b| b.get("text")?.as_str()).collect())
+                .unwrap_or_default();
+            if summary.is_empty() {
+                return Err(SummarizeError::Api("response has no text".to_string()));
+            }
+            Ok(clip(summary.join("\n").trim(), max_chars))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extractive_summary() {
+        let text = "I looked at the parser first. The parser drops trailing commas in arrays.\n\
+                    - Fixed the parser to keep trailing commas\n\
+                    Thanks! All tests pass now.";
+        let summary = ExtractiveSummarizer.summarize(text, 80).unwrap();
+        assert!(summary.chars().count() <= 80);
+        assert!(summary.contains("parser"));
+        // Sentences stay in their original order
+        let summary = ExtractiveSummarizer.summarize(text, 1000).unwrap();
+        assert!(summary.starts_with("I looked at the parser first."));
+        assert!(summary.contains("Fixed the parser to keep trailing commas"));
+
+        assert_eq!(ExtractiveSummarizer.summarize("", 50).unwrap(), "");
+        assert_eq!(ExtractiveSummarizer.summarize("abcdefghij", 5).unwrap(), "abcd…");
+    }
+}
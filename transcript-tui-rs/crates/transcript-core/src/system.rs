@@ -0,0 +1,330 @@
+//! One-line summaries of system lines, keyed by subtype
+//!
+//! Claude Code writes a `system` line for turn timings, hook results, local
+//! commands, compaction, API errors and more, each with its own fields. The
+//! renderers here turn each known subtype into a short sentence so the CLI,
+//! the viewers and the exporters all describe them the same way. Subtypes
+//! without a renderer show as `[subtype]`.
+
+use serde_json::Value;
+
+use crate::noise::noise_classifier;
+use crate::types::{LineType, TranscriptLine};
+
+/// How much attention a system line deserves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SystemLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A rendered system line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemSummary {
+    pub text: String,
+    pub level: SystemLevel,
+}
+
+impl SystemSummary {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self { text: text.into(), level: SystemLevel::Info }
+    }
+
+    pub fn warning(text: impl Into<String>) -> Self {
+        Self { text: text.into(), level: SystemLevel::Warning }
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self { text: text.into(), level: SystemLevel::Error }
+    }
+}
+
+/// Renders one subtype from the parsed line (`None` when there is nothing worth showing)
+pub type SystemRenderer = fn(&Value) -> Option<SystemSummary>;
+
+/// Renderers by subtype
+pub const SYSTEM_RENDERERS: &[(&str, SystemRenderer)] = &[
+    ("turn_duration", turn_duration),
+    ("stop_hook_summary", stop_hook_summary),
+    ("local_command", local_command),
+    ("init", init),
+    ("api_conversation_stats", api_conversation_stats),
+    ("context_cleared", context_changed),
+    ("context_compacted", context_changed),
+    ("compact_boundary", compact_boundary),
+    ("api_error", api_error),
+    ("rate_limit", rate_limit),
+];
+
+/// Summarize a parsed system line by its subtype (`None` without a subtype)
+pub fn render_system(parsed: &Value) -> Option<SystemSummary> {
+    let subtype = parsed.get("subtype").and_then(|v| v.as_str())?;
+    match SYSTEM_RENDERERS.iter().find(|(name, _)| *name == subtype) {
+        Some((_, render)) => render(parsed),
+        None => Some(SystemSummary::info(format!("[{}]", subtype))),
+    }
+}
+
+/// Summarize a transcript line, if it is a system line with something to say
+pub fn system_summary(line: &TranscriptLine) -> Option<SystemSummary> {
+    if line.line_type != LineType::System {
+        return None;
+    }
+    let parsed = serde_json::from_str::<Value>(&line.raw).ok()?;
+    render_system(&parsed)
+}
+
+fn turn_duration(parsed: &Value) -> Option<SystemSummary> {
+    let duration_ms = parsed.get("durationMs").and_then(|v| v.as_u64())?;
+    Some(SystemSummary::info(format!("Turn completed in {}", format_duration_ms(duration_ms))))
+}
+
+fn stop_hook_summary(parsed: &Value) -> Option<SystemSummary> {
+    let hook_count = parsed.get("hookCount").and_then(|v| v.as_u64()).unwrap_or(0);
+    let hook_errors = parsed
+        .get("hookErrors")
+        .and_then(|v| v.as_array())
+        .map(|a| a.len())
+        .unwrap_or(0);
+    let prevented = parsed
+        .get("preventedContinuation")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let mut parts = Vec::new();
+    if hook_count > 0 {
+        parts.push(format!("{} hook(s) ran", hook_count));
+    }
+    if hook_errors > 0 {
+        parts.push(format!("{} error(s)", hook_errors));
+    }
+    if prevented {
+        parts.push("continuation prevented".to_string());
+    }
+
+    // Skip if nothing interesting
+    if parts.is_empty() {
+        return None;
+    }
+    let text = parts.join(", ");
+    Some(if hook_errors > 0 {
+        SystemSummary::error(text)
+    } else if prevented {
+        SystemSummary::warning(text)
+    } else {
+        SystemSummary::info(text)
+    })
+}
+
+fn local_command(parsed: &Value) -> Option<SystemSummary> {
+    let content = parsed.get("content").and_then(|v| v.as_str())?;
+    let noise = noise_classifier();
+    if noise.is_noise_text(content) {
+        return None;
+    }
+
+    if let Some(cmd) = between(content, "<command-name>", "</command-name>") {
+        return Some(SystemSummary::info(format!("/{}", cmd.trim_start_matches('/'))));
+    }
+
+    // Command output, if meaningful
+    let stdout = between(content, "<local-command-stdout>", "</local-command-stdout>")?.trim();
+    if stdout.is_empty() || noise.is_noise_text(stdout) {
+        return None;
+    }
+    Some(SystemSummary::info(stdout))
+}
+
+fn init(parsed: &Value) -> Option<SystemSummary> {
+    let version = parsed.get("version").and_then(|v| v.as_str()).unwrap_or("?");
+    Some(SystemSummary::info(match parsed.get("gitBranch").and_then(|v| v.as_str()) {
+        Some(branch) => format!("Session started (v{}, {})", version, branch),
+        None => format!("Session started (v{})", version),
+    }))
+}
+
+fn api_conversation_stats(parsed: &Value) -> Option<SystemSummary> {
+    let count = |key: &str| parsed.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    let total = count("inputTokens") + count("outputTokens") + count("cacheReadTokens") + count("cacheCreationTokens");
+    if total == 0 {
+        return None;
+    }
+    let mut text = format!("{} tokens", format_thousands(total));
+    if let Some(cost) = parsed.get("costUSD").and_then(|v| v.as_f64()) {
+        text.push_str(&format!(" (${:.4})", cost));
+    }
+    Some(SystemSummary::info(text))
+}
+
+fn context_changed(parsed: &Value) -> Option<SystemSummary> {
+    let subtype = parsed.get("subtype").and_then(|v| v.as_str())?;
+    Some(SystemSummary::info(format!("Context {}", subtype.trim_start_matches("context_"))))
+}
+
+/// Earlier context was summarized away; what follows sees only the summary
+fn compact_boundary(parsed: &Value) -> Option<SystemSummary> {
+    let metadata = parsed.get("compactMetadata");
+    let trigger = metadata.and_then(|m| m.get("trigger")).and_then(|v| v.as_str());
+    let pre_tokens = metadata.and_then(|m| m.get("preTokens")).and_then(|v| v.as_u64());
+
+    let details: Vec<String> = trigger
+        .map(str::to_string)
+        .into_iter()
+        .chain(pre_tokens.map(|t| format!("{} tokens before", format_thousands(t))))
+        .collect();
+    Some(SystemSummary::warning(if details.is_empty() {
+        "Conversation compacted".to_string()
+    } else {
+        format!("Conversation compacted ({})", details.join(", "))
+    }))
+}
+
+fn api_error(parsed: &Value) -> Option<SystemSummary> {
+    let error = parsed.get("error");
+    let status = error
+        .and_then(|e| e.get("status"))
+        .and_then(|v| v.as_u64())
+        .or_else(|| parsed.get("status").and_then(|v| v.as_u64()));
+    let message = error
+        .and_then(|e| {
+            e.get("message")
+                .or_else(|| e.get("error").and_then(|inner| inner.get("message")))
+                .or(Some(e))
+        })
+        .and_then(|v| v.as_str())
+        .or_else(|| parsed.get("content").and_then(|v| v.as_str()));
+
+    let mut text = match status {
+        Some(status) => format!("API error {}", status),
+        None => "API error".to_string(),
+    };
+    if let Some(message) = message.map(str::trim).filter(|m| !m.is_empty()) {
+        text.push_str(&format!(": {}", message));
+    }
+    if let Some(retry) = retry_note(parsed) {
+        text.push_str(&format!(" ({})", retry));
+    }
+    Some(SystemSummary::error(text))
+}
+
+fn rate_limit(parsed: &Value) -> Option<SystemSummary> {
+    let message = parsed
+        .get("content")
+        .or_else(|| parsed.get("message"))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|m| !m.is_empty());
+
+    let mut text = message.unwrap_or("Rate limited").to_string();
+    if let Some(resets) = parsed.get("resetsAt").and_then(|v| v.as_str()) {
+        text.push_str(&format!(" (resets {})", resets));
+    } else if let Some(retry) = retry_note(parsed) {
+        text.push_str(&format!(" ({})", retry));
+    }
+    Some(SystemSummary::warning(text))
+}
+
+/// "retry 2/10 in 4.0s" from the retry fields API errors carry
+fn retry_note(parsed: &Value) -> Option<String> {
+    let delay = parsed.get("retryInMs").and_then(|v| v.as_f64())?;
+    let delay = format_duration_ms(delay.max(0.0) as u64);
+    let attempt = parsed.get("retryAttempt").and_then(|v| v.as_u64());
+    let max = parsed.get("maxRetries").and_then(|v| v.as_u64());
+    Some(match (attempt, max) {
+        (Some(attempt), Some(max)) => format!("retry {}/{} in {}", attempt, max, delay),
+        (Some(attempt), None) => format!("retry {} in {}", attempt, delay),
+        _ => format!("retry in {}", delay),
+    })
+}
+
+/// The text between the first `open` tag and the `close` tag after it
+fn between<'a>(text: &'a str, open: &str, close: &str) -> Option<&'a str> {
+    let start = text.find(open)? + open.len();
+    let len = text[start..].find(close)?;
+    Some(&text[start..start + len])
+}
+
+/// Format a duration in milliseconds in human-readable form
+pub fn format_duration_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else if ms < 3_600_000 {
+        format!("{}m {}s", ms / 60_000, (ms % 60_000) / 1000)
+    } else {
+        format!("{}h {}m", ms / 3_600_000, (ms % 3_600_000) / 60_000)
+    }
+}
+
+/// 1234567 -> "1,234,567"
+fn format_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(value: Value) -> Option<(String, SystemLevel)> {
+        render_system(&value).map(|s| (s.text, s.level))
+    }
+
+    #[test]
+    fn test_system_renderers() {
+        assert_eq!(
+            render(json!({"subtype": "turn_duration", "durationMs": 83_000})),
+            Some(("Turn completed in 1m 23s".to_string(), SystemLevel::Info))
+        );
+        assert_eq!(
+            render(json!({"subtype": "stop_hook_summary", "hookCount": 2, "hookErrors": ["boom"]})),
+            Some(("2 hook(s) ran, 1 error(s)".to_string(), SystemLevel::Error))
+        );
+        assert_eq!(render(json!({"subtype": "stop_hook_summary", "hookCount": 0})), None);
+        assert_eq!(
+            render(json!({"subtype": "local_command", "content": "<command-name>/cost</command-name>"})),
+            Some(("/cost".to_string(), SystemLevel::Info))
+        );
+        assert_eq!(
+            render(json!({"subtype": "api_conversation_stats", "inputTokens": 1200, "outputTokens": 34})),
+            Some(("1,234 tokens".to_string(), SystemLevel::Info))
+        );
+
+        // The subtypes that used to show as [subtype]
+        assert_eq!(
+            render(json!({"subtype": "compact_boundary", "compactMetadata": {"trigger": "auto", "preTokens": 155_000}})),
+            Some((
+                "Conversation compacted (auto, 155,000 tokens before)".to_string(),
+                SystemLevel::Warning
+            ))
+        );
+        assert_eq!(
+            render(json!({
+                "subtype": "api_error",
+                "error": {"status": 529, "error": {"message": "Overloaded"}},
+                "retryInMs": 4000.0, "retryAttempt": 2, "maxRetries": 10
+            })),
+            Some(("API error 529: Overloaded (retry 2/10 in 4.0s)".to_string(), SystemLevel::Error))
+        );
+        assert_eq!(
+            render(json!({"subtype": "rate_limit", "content": "Usage limit reached", "resetsAt": "5pm"})),
+            Some(("Usage limit reached (resets 5pm)".to_string(), SystemLevel::Warning))
+        );
+
+        assert_eq!(
+            render(json!({"subtype": "something_new"})),
+            Some(("[something_new]".to_string(), SystemLevel::Info))
+        );
+        assert_eq!(render(json!({"type": "system"})), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! Merged timeline of transcript lines and hook events
+//!
+//! Hook handlers run between the lines Claude Code writes, so debugging them
+//! means reading both streams in one order. Each stream keeps its own order;
+//! they are interleaved by timestamp, with the transcript line first on a tie.
+
+use chrono::DateTime;
+
+use crate::{HookEvent, TranscriptLine};
+
+/// One row of the merged timeline
+#[derive(Debug, Clone, Copy)]
+pub enum TimelineEntry<'a> {
+    Line(&'a TranscriptLine),
+    Hook(&'a HookEvent),
+}
+
+impl TimelineEntry<'_> {
+    pub fn timestamp(&self) -> &str {
+        match self {
+            TimelineEntry::Line(line) => &line.timestamp,
+            TimelineEntry::Hook(event) => &event.timestamp,
+        }
+    }
+
+    /// Timestamp as HH:MM:SS, whatever precision and offset each stream wrote
+    pub fn format_time(&self) -> String {
+        let timestamp = self.timestamp();
+        timestamp
+            .split('T')
+            .nth(1)
+            .and_then(|time| time.get(..8))
+            .unwrap_or(timestamp)
+            .to_string()
+    }
+
+    /// Line type, or hook event type
+    pub fn kind(&self) -> &str {
+        match self {
+            TimelineEntry::Line(line) => line.display_type(),
+            TimelineEntry::Hook(event) => &event.event_type,
+        }
+    }
+
+    /// One-line description: the line's preview, or the hook's tool and input
+    pub fn summary(&self, max_len: usize) -> String {
+        match self {
+            TimelineEntry::Line(line) => line.preview(max_len),
+            TimelineEntry::Hook(event) => {
+                let mut parts = Vec::new();
+                if let Some(tool) = &event.tool_name {
+                    parts.push(tool.clone());
+                }
+                if let Some(decision) = &event.decision {
+                    parts.push(format!("-> {}", decision));
+                }
+                parts.extend(event.input_preview(max_len));
+                parts.join(" ")
+            }
+        }
+    }
+}
+
+/// Interleave a session's lines and hook events, each already in order
+pub fn merge_timeline<'a>(lines: &'a [TranscriptLine], events: &'a [HookEvent]) -> Vec<TimelineEntry<'a>> {
+    let millis = |timestamp: &str| {
+        DateTime::parse_from_rfc3339(timestamp)
+            .ok()
+            .map(|t| t.timestamp_millis())
+    };
+
+    let mut merged = Vec::with_capacity(lines.len() + events.len());
+    let (mut lines, mut events) = (lines.iter().peekable(), events.iter().peekable());
+    loop {
+        let hook_first = match (lines.peek(), events.peek()) {
+            (Some(line), Some(event)) => match (millis(&line.timestamp), millis(&event.timestamp)) {
+                (Some(line), Some(event)) => event < line,
+                // Unparseable timestamps are compared as text
+                _ => event.timestamp < line.timestamp,
+            },
+            (Some(_), None) => false,
+            (None, Some(_)) => true,
+            (None, None) => break,
+        };
+        merged.push(if hook_first {
+            TimelineEntry::Hook(events.next().expect("peeked"))
+        } else {
+            TimelineEntry::Line(lines.next().expect("peeked"))
+        });
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineType;
+
+    fn line(line_number: i64, timestamp: &str) -> TranscriptLine {
+        TranscriptLine {
+            id: line_number,
+            line_number,
+            timestamp: timestamp.to_string(),
+            ..TranscriptLine::for_test(LineType::Assistant, "")
+        }
+    }
+
+    fn hook(id: i64, timestamp: &str) -> HookEvent {
+        HookEvent {
+            id,
+            session_id: "s1".to_string(),
+            timestamp: timestamp.to_string(),
+            event_type: "PreToolUse".to_string(),
+            tool_use_id: None,
+            tool_name: Some("Bash".to_string()),
+            decision: None,
+            handler_results_json: None,
+            input_json: None,
+            context_json: None,
+            file_path: String::new(),
+            line_number: id,
+            turn_id: None,
+            turn_sequence: None,
+            session_name: None,
+            git_hash: None,
+            git_branch: None,
+            git_dirty: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_timeline() {
+        let lines = vec![
+            line(1, "2026-01-01T10:00:00.000Z"),
+            line(2, "2026-01-01T10:00:02.000Z"),
+            line(3, "2026-01-01T10:00:05.000Z"),
+        ];
+        // Offsets and Z compare as instants
+        let events = vec![
+            hook(1, "2026-01-01T10:00:01.500+00:00"),
+            hook(2, "2026-01-01T10:00:02.000+00:00"),
+            hook(3, "2026-01-01T10:00:09+00:00"),
+        ];
+
+        let order: Vec<String> = merge_timeline(&lines, &events)
+            .iter()
+            .map(|entry| match entry {
+                TimelineEntry::Line(l) => format!("L{}", l.line_number),
+                TimelineEntry::Hook(h) => format!("H{}", h.id),
+            })
+            .collect();
+        assert_eq!(order, ["L1", "H1", "L2", "H2", "L3", "H3"]);
+        assert_eq!(TimelineEntry::Hook(&events[0]).summary(20), "Bash");
+        assert_eq!(TimelineEntry::Hook(&events[2]).format_time(), "10:00:09");
+    }
+}
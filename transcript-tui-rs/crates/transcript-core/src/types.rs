@@ -1,7 +1,11 @@
 //! Core type definitions for transcript data
 
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
+use crate::ansi::{has_ansi, strip_ansi};
+
 /// Token usage statistics for a message
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TokenUsage {
@@ -52,6 +56,9 @@ pub enum ContentBlock {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptMessage {
     pub role: String,
+    /// API message id, shared by every line of one response
+    #[serde(default)]
+    pub id: Option<String>,
     #[serde(default)]
     pub content: MessageContent,
     #[serde(default)]
@@ -130,6 +137,28 @@ impl MessageContent {
     }
 }
 
+/// Tools whose input names a file they change, with the input key
+pub const EDIT_TOOLS: &[(&str, &str)] = &[
+    ("Edit", "file_path"),
+    ("MultiEdit", "file_path"),
+    ("Write", "file_path"),
+    ("NotebookEdit", "notebook_path"),
+];
+
+/// The file a tool call changes, for the edit tools
+pub fn edited_file<'a>(tool: &str, input: &'a serde_json::Value) -> Option<&'a str> {
+    let (_, key) = EDIT_TOOLS.iter().find(|(name, _)| *name == tool)?;
+    input.get(*key).and_then(|p| p.as_str())
+}
+
+/// The file a tool call reads or changes: the edit tools and `Read`
+pub fn touched_file<'a>(tool: &str, input: &'a serde_json::Value) -> Option<&'a str> {
+    match tool {
+        "Read" => input.get("file_path").and_then(|p| p.as_str()),
+        _ => edited_file(tool, input),
+    }
+}
+
 /// Line type in a transcript
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -196,6 +225,34 @@ pub struct TranscriptLine {
     pub session_name: Option<String>,
 }
 
+#[cfg(any(test, feature = "test-util"))]
+impl TranscriptLine {
+    /// A line of session `s1` for tests: its type and raw JSON, the rest empty
+    ///
+    /// Set other fields with struct update syntax:
+    /// `TranscriptLine { uuid: "u1".into(), ..TranscriptLine::for_test(LineType::User, raw) }`
+    pub fn for_test(line_type: LineType, raw: impl Into<String>) -> Self {
+        Self {
+            id: 0,
+            line_number: 0,
+            line_type,
+            uuid: String::new(),
+            parent_uuid: None,
+            session_id: "s1".to_string(),
+            timestamp: String::new(),
+            cwd: None,
+            slug: None,
+            role: None,
+            model: None,
+            content: None,
+            raw: raw.into(),
+            turn_id: None,
+            turn_sequence: None,
+            session_name: None,
+        }
+    }
+}
+
 impl TranscriptLine {
     /// Get the display type (for filtering and rendering)
     pub fn display_type(&self) -> &str {
@@ -245,17 +302,47 @@ impl TranscriptLine {
         self.message().and_then(|m| m.usage)
     }
 
+    /// Whether this is a user prompt rather than a tool_result carrier
+    pub fn is_prompt(&self) -> bool {
+        if self.line_type != LineType::User {
+            return false;
+        }
+        match self.message().map(|m| m.content) {
+            Some(MessageContent::Text(text)) => !text.trim().is_empty(),
+            Some(MessageContent::Blocks(blocks)) => blocks
+                .iter()
+                .any(|b| matches!(b, ContentBlock::Text { text } if !text.trim().is_empty())),
+            None => false,
+        }
+    }
+
+    /// Whether this line belongs to a subagent sidechain
+    pub fn is_sidechain(&self) -> bool {
+        self.raw.contains("\"isSidechain\":true")
+    }
+
     /// Get preview text for list display
     pub fn preview(&self, max_len: usize) -> String {
         if let Some(content) = &self.content {
-            let preview = content.lines().next().unwrap_or("").trim();
-            // Use char_indices for proper unicode handling
-            let chars: Vec<char> = preview.chars().collect();
+            let first = content.lines().next().unwrap_or("");
+            // Colored tool output previews as its text, not its escape codes
+            let first = if has_ansi(first) {
+                Cow::Owned(strip_ansi(first))
+            } else {
+                Cow::Borrowed(first)
+            };
+            let preview = first.trim();
+            // Use char_indices for proper unicode handling; control characters
+            // (tabs, escapes) would break single-line list rows
+            let chars: Vec<char> = preview
+                .chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect();
             if chars.len() > max_len {
                 let truncated: String = chars[..max_len.saturating_sub(3)].iter().collect();
                 format!("{}...", truncated)
             } else {
-                preview.to_string()
+                chars.into_iter().collect()
             }
         } else {
             String::new()
@@ -297,6 +384,8 @@ pub struct ParsedLine {
     pub slug: Option<String>,
     #[serde(default)]
     pub message: Option<TranscriptMessage>,
+    #[serde(rename = "requestId", default)]
+    pub request_id: Option<String>,
     #[serde(rename = "toolUseResult", default)]
     pub tool_use_result: Option<serde_json::Value>,
     #[serde(default)]
@@ -333,19 +422,23 @@ pub enum ViewMode {
     /// Smart view: MD for text, tool-specific for tools
     #[default]
     Custom,
+    /// Custom content with the line list shown as a conversation tree
+    /// (branches and subagent sidechains nested under their parent)
+    Thread,
 }
 
 impl ViewMode {
     /// Get all view modes in order
     pub fn all() -> &'static [ViewMode] {
-        &[ViewMode::Json, ViewMode::Custom]
+        &[ViewMode::Json, ViewMode::Custom, ViewMode::Thread]
     }
 
-    /// Get view mode from key (1-2)
+    /// Get view mode from key (1-3)
     pub fn from_key(key: char) -> Option<ViewMode> {
         match key {
             '1' => Some(ViewMode::Json),
             '2' => Some(ViewMode::Custom),
+            '3' => Some(ViewMode::Thread),
             _ => None,
         }
     }
@@ -355,6 +448,7 @@ impl ViewMode {
         match self {
             ViewMode::Json => "JSON",
             ViewMode::Custom => "CUSTOM",
+            ViewMode::Thread => "THREAD",
         }
     }
 }
@@ -376,6 +470,67 @@ impl FocusedPane {
     }
 }
 
+/// What each row of the list pane stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListMode {
+    /// One row per line
+    #[default]
+    Lines,
+    /// One row per turn: a prompt and everything up to the next one
+    Turns,
+}
+
+/// Where a session is in a team's audit of agent work
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReviewStatus {
+    #[default]
+    Unreviewed,
+    InReview,
+    Approved,
+    Flagged,
+}
+
+impl ReviewStatus {
+    pub fn all() -> &'static [ReviewStatus] {
+        &[
+            ReviewStatus::Unreviewed,
+            ReviewStatus::InReview,
+            ReviewStatus::Approved,
+            ReviewStatus::Flagged,
+        ]
+    }
+
+    /// Name as stored and typed on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReviewStatus::Unreviewed => "unreviewed",
+            ReviewStatus::InReview => "in-review",
+            ReviewStatus::Approved => "approved",
+            ReviewStatus::Flagged => "flagged",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ReviewStatus> {
+        Self::all().iter().copied().find(|s| s.name() == name)
+    }
+
+    /// The status after this one, for cycling through them with one key
+    pub fn next(&self) -> ReviewStatus {
+        let all = Self::all();
+        let i = all.iter().position(|s| s == self).unwrap_or(0);
+        all[(i + 1) % all.len()]
+    }
+}
+
+/// A session's review status and who set it
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SessionReview {
+    pub status: ReviewStatus,
+    pub reviewer: Option<String>,
+    /// When the status was last set (None for unreviewed sessions)
+    pub updated_at: Option<String>,
+}
+
 // ============================================================================
 // Hook Event Types
 // ============================================================================
@@ -439,10 +594,9 @@ impl HookEvent {
         }
     }
 
-    /// Calculate context usage percentage from input_json
-    /// Context window is 200K tokens for current Claude models
+    /// Calculate context usage percentage from input_json, against the
+    /// context window of the model it names (see `context`)
     pub fn context_usage(&self) -> Option<(u64, u8)> {
-        const CONTEXT_WINDOW: u64 = 200_000;
         let input_json = self.input_json.as_deref()?;
         let input: serde_json::Value = serde_json::from_str(input_json).ok()?;
 
@@ -454,7 +608,12 @@ impl HookEvent {
         let input_tokens = usage.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
         let output_tokens = usage.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
         let total = input_tokens + output_tokens;
-        let pct = ((total as f64 / CONTEXT_WINDOW as f64) * 100.0).round() as u8;
+        // A model name, or an object with its id (`{"id": ..., "display_name": ...}`)
+        let model = input.get("model")
+            .or_else(|| input.get("message").and_then(|m| m.get("model")))
+            .and_then(|m| m.as_str().or_else(|| m.get("id").and_then(|id| id.as_str())));
+        let window = crate::context::context_window(model);
+        let pct = ((total as f64 / window as f64) * 100.0).round() as u8;
         Some((total, pct))
     }
 
@@ -486,8 +645,11 @@ impl HookEvent {
         let input: serde_json::Value = serde_json::from_str(input_json).ok()?;
         let response = input.get("tool_response")?;
 
-        if let Some(stdout) = response.get("stdout").and_then(|v| v.as_str()) {
-            let preview = stdout.replace('\n', "\\n");
+        // Failed commands often leave stdout empty and explain on stderr
+        let stdout = response.get("stdout").and_then(|v| v.as_str());
+        let stderr = response.get("stderr").and_then(|v| v.as_str());
+        if let Some(output) = stdout.filter(|s| !s.is_empty()).or(stderr.filter(|s| !s.is_empty())).or(stdout) {
+            let preview = output.replace('\n', "\\n");
             return Some(truncate_str(&preview, max_len));
         }
         if let Some(content) = response.get("content").and_then(|v| v.as_str()) {
@@ -497,6 +659,34 @@ impl HookEvent {
 
         None
     }
+
+    /// Whether the tool call failed (PostToolUse only)
+    ///
+    /// Failure is `is_error: true`, `success: false` or `interrupted: true` in
+    /// the tool response, or an error message in place of a structured one.
+    pub fn tool_failed(&self) -> Option<bool> {
+        if self.event_type != "PostToolUse" {
+            return None;
+        }
+        let input_json = self.input_json.as_deref()?;
+        let input: serde_json::Value = serde_json::from_str(input_json).ok()?;
+        let response = input.get("tool_response")?;
+
+        let flag = |key: &str| response.get(key).and_then(|v| v.as_bool());
+        Some(match response {
+            serde_json::Value::String(s) => s.trim_start().starts_with("Error"),
+            _ => flag("is_error") == Some(true) || flag("success") == Some(false) || flag("interrupted") == Some(true),
+        })
+    }
+
+    /// The file an edit tool changed (PostToolUse only)
+    pub fn edited_file(&self) -> Option<String> {
+        if self.event_type != "PostToolUse" {
+            return None;
+        }
+        let input: serde_json::Value = serde_json::from_str(self.input_json.as_deref()?).ok()?;
+        edited_file(self.tool_name.as_deref()?, input.get("tool_input")?).map(str::to_string)
+    }
 }
 
 fn truncate_str(s: &str, max_len: usize) -> String {
@@ -2,24 +2,29 @@
 //!
 //! Full-featured TUI with colors, search, bookmarks, and live mode.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use cursive::align::HAlign;
 use cursive::event::Key;
-use cursive::theme::{BaseColor, Color, ColorStyle, PaletteColor, Theme};
+use cursive::theme::{BaseColor, Color, ColorStyle, Effect, PaletteColor, Style, Theme};
 use cursive::traits::*;
 use cursive::utils::markup::StyledString;
 use cursive::views::{
-    Dialog, EditView, LinearLayout, Panel, ResizedView, ScrollView,
+    BoxedView, Checkbox, Dialog, EditView, OnEventView, HideableView, LinearLayout, Panel, ResizedView, ScrollView,
     SelectView, TextView,
 };
 use cursive::Cursive;
 
-use transcript_core::{TranscriptLine, ViewMode};
-use transcript_db::{DbError, GetLinesOptions, TranscriptDb};
+use transcript_core::{
+    apply_filter, display_text, fuzzy_filter, get_display_type_counts, has_ansi, is_binary, line_context_window, locale,
+    parse_ansi, sanitize_text, system_summary, usage_series, AnsiColor, FilterOptions, SearchHistory, SystemLevel,
+    TextStyle, TranscriptLine, ViewMode,
+};
+use transcript_db::{Annotation, DbError, GetLinesOptions, TranscriptDb};
+use transcript_indexer::IndexerDb;
 
 const VERSION: &str = "0.3.0";
 
@@ -29,8 +34,9 @@ const VERSION: &str = "0.3.0";
 #[command(version = VERSION)]
 #[command(about = "Cursive-based transcript viewer for Claude Code sessions")]
 struct Cli {
-    /// Session name, ID, or file path
-    #[arg(value_name = "SESSION", required_unless_present_any = ["stats", "list"])]
+    /// Session name, ID, or file path (without one, or when several sessions
+    /// match, opens the session picker)
+    #[arg(value_name = "SESSION")]
     session: Option<String>,
 
     /// Initial view mode (1=json, 2=custom)
@@ -53,6 +59,11 @@ struct Cli {
     #[arg(long, env = "TRANSCRIPT_DB_PATH")]
     db_path: Option<std::path::PathBuf>,
 
+    /// Database profile from ~/.claude-code-sdk/config.json, not a separate
+    /// config.toml (`--db-path` wins)
+    #[arg(long, env = "TRANSCRIPT_PROFILE")]
+    profile: Option<String>,
+
     /// Screenshot mode: dump screen and exit
     #[arg(long)]
     screenshot: bool,
@@ -72,21 +83,36 @@ enum FocusedPane {
 
 /// Application state
 struct AppState {
+    /// All loaded lines (unfiltered)
+    all_lines: Vec<TranscriptLine>,
+    /// Lines shown in the list (after the type filter)
     lines: Vec<TranscriptLine>,
+    filter_opts: FilterOptions,
+    fullscreen: bool,
     current_index: usize,
     view_mode: ViewMode,
     session_name: Option<String>,
     session_id: String,
     project_path: Option<String>,
     bookmarks: HashSet<i64>,
+    /// Where bookmarks are saved (None: kept for this run only)
+    bookmark_db: Option<IndexerDb>,
+    /// Number of annotations by line uuid
+    annotated: HashMap<String, usize>,
     search_query: String,
     search_results: Vec<usize>,
     search_result_index: usize,
+    /// Previous search queries, persisted across runs
+    search_history: SearchHistory,
     live_mode: bool,
     last_max_id: i64,
+    /// Last change feed ID seen, so polls skip unchanged sessions
+    last_change_id: i64,
     status_message: Option<String>,
     focused_pane: FocusedPane,
     needs_initial_scroll: bool,
+    /// Assistant text only (`--text-only`), kept when switching sessions
+    text_only: bool,
 }
 
 impl AppState {
@@ -105,24 +131,56 @@ impl AppState {
         });
 
         Self {
+            all_lines: lines.clone(),
             lines,
+            filter_opts: FilterOptions::default(),
+            fullscreen: false,
             current_index,
             view_mode: ViewMode::Custom,
             session_name,
             session_id,
             project_path,
             bookmarks: HashSet::new(),
+            bookmark_db: None,
+            annotated: HashMap::new(),
             search_query: String::new(),
             search_results: Vec::new(),
             search_result_index: 0,
+            search_history: SearchHistory::load("cursive"),
             live_mode: false,
             last_max_id,
+            last_change_id: 0,
             status_message: None,
             focused_pane: FocusedPane::List,
             needs_initial_scroll: true,
+            text_only: false,
         }
     }
 
+    /// Keep only assistant lines with text (no tool_use), as the base set
+    /// the type filter works from
+    fn apply_text_only(&mut self) {
+        self.text_only = true;
+        self.lines = self.lines.iter().filter(|line| {
+            // Only keep assistant lines that have text content (not just tool_use)
+            if line.line_type != transcript_core::LineType::Assistant
+                || transcript_core::noise_classifier().is_noise(line)
+            {
+                return false;
+            }
+            // Check if line has text content (not just tool use)
+            if let Some(content) = &line.content {
+                // Skip lines that are only tool use markers
+                !content.starts_with("[Tool:")
+            } else {
+                false
+            }
+        }).cloned().collect();
+        self.all_lines = self.lines.clone();
+        // Reset index to end
+        self.current_index = if self.lines.is_empty() { 0 } else { self.lines.len() - 1 };
+    }
+
     fn current_line(&self) -> Option<&TranscriptLine> {
         self.lines.get(self.current_index)
     }
@@ -138,13 +196,61 @@ impl AppState {
     fn toggle_bookmark(&mut self) {
         if let Some(line) = self.current_line() {
             let id = line.id;
-            if self.bookmarks.contains(&id) {
-                self.bookmarks.remove(&id);
-                self.status_message = Some("Bookmark removed".to_string());
-            } else {
+            let (session_id, uuid, line_number) =
+                (line.session_id.clone(), line.uuid.clone(), line.line_number);
+            let added = !self.bookmarks.contains(&id);
+            if added {
                 self.bookmarks.insert(id);
                 self.status_message = Some("Bookmark added".to_string());
+            } else {
+                self.bookmarks.remove(&id);
+                self.status_message = Some("Bookmark removed".to_string());
+            }
+
+            if let Some(db) = &self.bookmark_db {
+                let result = if added {
+                    transcript_indexer::add_bookmark(db.connection(), &session_id, &uuid, line_number)
+                } else {
+                    transcript_indexer::remove_bookmark(db.connection(), &session_id, &uuid)
+                };
+                if let Err(e) = result {
+                    self.status_message = Some(format!("Bookmark not saved: {}", e));
+                }
+            }
+        }
+    }
+
+    /// Count the session's annotations by line
+    fn set_annotations(&mut self, annotations: &[Annotation]) {
+        self.annotated.clear();
+        for annotation in annotations {
+            *self.annotated.entry(annotation.uuid.clone()).or_default() += 1;
+        }
+    }
+
+    fn is_annotated(&self, line: &TranscriptLine) -> bool {
+        self.annotated.contains_key(&line.uuid)
+    }
+
+    /// Save an annotation on the current line
+    fn annotate(&mut self, text: &str) {
+        let Some(line) = self.current_line() else {
+            return;
+        };
+        let (session_id, uuid, line_number) = (line.session_id.clone(), line.uuid.clone(), line.line_number);
+        let Some(db) = &self.bookmark_db else {
+            self.status_message = Some("Annotation not saved: the database is read-only".to_string());
+            return;
+        };
+        let author = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string());
+        match transcript_indexer::add_annotation(db.connection(), &session_id, &uuid, line_number, &author, text, None) {
+            Ok(_) => {
+                *self.annotated.entry(uuid).or_default() += 1;
+                self.status_message = Some(format!("Annotated line {}", line_number));
             }
+            Err(e) => self.status_message = Some(format!("Annotation not saved: {}", e)),
         }
     }
 
@@ -194,7 +300,27 @@ impl AppState {
         }
     }
 
-    fn execute_search(&mut self) {
+    /// Show only the given display types (None shows everything)
+    fn set_type_filter(&mut self, display_types: Option<Vec<String>>) {
+        let current_id = self.current_line().map(|l| l.id);
+
+        self.filter_opts.display_types = display_types;
+        self.lines = apply_filter(&self.all_lines, &self.filter_opts);
+
+        // Stay on the same line if it is still visible
+        self.current_index = current_id
+            .and_then(|id| self.lines.iter().position(|l| l.id == id))
+            .unwrap_or(self.lines.len().saturating_sub(1));
+
+        self.refresh_search_results();
+        self.status_message = Some(match &self.filter_opts.display_types {
+            Some(_) => format!("{} of {} lines shown", self.lines.len(), self.all_lines.len()),
+            None => "Filter cleared".to_string(),
+        });
+    }
+
+    /// Recompute search matches for the current lines without moving
+    fn refresh_search_results(&mut self) {
         if self.search_query.is_empty() {
             self.search_results.clear();
             return;
@@ -215,17 +341,67 @@ impl AppState {
             })
             .map(|(i, _)| i)
             .collect();
-
         self.search_result_index = 0;
+    }
+
+    /// Search as the query is typed: jump to the first match at or after `origin`
+    fn incremental_search(&mut self, query: &str, origin: usize) {
+        self.search_query = query.to_string();
+        self.refresh_search_results();
+
+        let first_after = self
+            .search_results
+            .iter()
+            .position(|&i| i >= origin)
+            .or(if self.search_results.is_empty() { None } else { Some(0) });
 
-        if let Some(&idx) = self.search_results.first() {
-            self.current_index = idx;
-            self.status_message = Some(format!("Found {} matches", self.search_results.len()));
+        match first_after {
+            Some(pos) => {
+                self.search_result_index = pos;
+                self.current_index = self.search_results[pos];
+            }
+            None => self.current_index = origin.min(self.lines.len().saturating_sub(1)),
+        }
+
+        self.status_message = if self.search_query.is_empty() {
+            None
+        } else if self.search_results.is_empty() {
+            Some("No matches found".to_string())
+        } else {
+            Some(format!("Found {} matches", self.search_results.len()))
+        };
+    }
+
+    /// Match count shown under the search input
+    fn search_count_label(&self) -> String {
+        if self.search_query.is_empty() {
+            "Type to search (↑/↓: history)".to_string()
+        } else if self.search_results.is_empty() {
+            "No matches".to_string()
         } else {
-            self.status_message = Some("No matches found".to_string());
+            format!(
+                "Match {}/{}",
+                self.search_result_index + 1,
+                self.search_results.len()
+            )
         }
     }
 
+    /// Remember a submitted query; saving is best effort
+    fn push_search_history(&mut self, query: &str) {
+        let _ = self.search_history.push(query);
+    }
+
+    /// Step through history: `older` for up, otherwise down (past the newest clears)
+    fn recall_search_history(&mut self, older: bool) -> Option<String> {
+        let recalled = if older {
+            self.search_history.older()
+        } else {
+            self.search_history.newer()
+        };
+        recalled.map(str::to_string)
+    }
+
     fn next_search_result(&mut self) {
         if self.search_results.is_empty() {
             return;
@@ -269,13 +445,26 @@ fn main() -> Result<()> {
     }
 
     let db = open_database(&cli)?;
-    let session = cli.session.as_ref().expect("session required");
-    let (session_id, lines) = load_session(&db, session)?;
 
-    if lines.is_empty() {
-        eprintln!("No transcript lines found for: {}", session);
-        return Ok(());
-    }
+    // Without a session, or when several match, start at the session picker
+    let session = match &cli.session {
+        Some(session) => load_session(&db, session)?,
+        None => None,
+    };
+    let (session_id, lines) = match session {
+        Some((session_id, lines)) => {
+            if lines.is_empty() {
+                eprintln!("No transcript lines found for: {}", cli.session.as_deref().unwrap_or_default());
+                return Ok(());
+            }
+            (session_id, lines)
+        }
+        None if cli.screenshot => {
+            eprintln!("No single session matches: {}", cli.session.as_deref().unwrap_or_default());
+            std::process::exit(1);
+        }
+        None => (String::new(), Vec::new()),
+    };
 
     let state = Arc::new(Mutex::new(AppState::new(lines, session_id.clone())));
 
@@ -287,6 +476,13 @@ fn main() -> Result<()> {
             _ => ViewMode::Custom,
         };
         st.live_mode = cli.live;
+        st.bookmarks = db
+            .get_bookmarked_lines(&session_id)?
+            .iter()
+            .map(|l| l.id)
+            .collect();
+        let annotations = db.get_annotations(&session_id)?;
+        st.set_annotations(&annotations);
     }
 
     if cli.screenshot {
@@ -295,29 +491,18 @@ fn main() -> Result<()> {
 
     // Apply text-only filter if requested (assistant text only, no tool_use)
     if cli.text_only {
-        let mut st = state.lock().unwrap();
-        st.lines = st.lines.iter().filter(|line| {
-            // Only keep assistant lines that have text content (not just tool_use)
-            if line.line_type != transcript_core::LineType::Assistant {
-                return false;
-            }
-            // Check if line has text content (not just tool use)
-            if let Some(content) = &line.content {
-                // Skip lines that are only tool use markers
-                !content.starts_with("[Tool:")
-            } else {
-                false
-            }
-        }).cloned().collect();
-        // Reset index to end
-        st.current_index = if st.lines.is_empty() { 0 } else { st.lines.len() - 1 };
+        state.lock().unwrap().apply_text_only();
     }
 
-    run_tui(state, db, session_id)
+    // Bookmarks are saved through the indexer; opened before the terminal is
+    // taken over, since a schema migration reports on stderr
+    state.lock().unwrap().bookmark_db = IndexerDb::open_or_create(db.path()).ok();
+
+    run_tui(state, db, cli.session.as_deref().unwrap_or_default())
 }
 
 fn open_database(cli: &Cli) -> Result<TranscriptDb> {
-    let db = if let Some(path) = &cli.db_path {
+    let db = if let Some(path) = transcript_core::resolve_db_path(cli.db_path.clone(), cli.profile.as_deref())? {
         TranscriptDb::open(path)
     } else {
         TranscriptDb::open_default()
@@ -338,45 +523,37 @@ fn open_database(cli: &Cli) -> Result<TranscriptDb> {
     }
 }
 
+/// Lines of the session matching `session_input` (None: no session, or
+/// several, match)
 fn load_session(
     db: &TranscriptDb,
     session_input: &str,
-) -> Result<(String, Vec<TranscriptLine>)> {
-    let session = db
+) -> Result<Option<(String, Vec<TranscriptLine>)>> {
+    let session = match db
         .resolve_session(session_input)
-        .context("Failed to resolve session")?;
-
-    match session {
-        Some(info) => {
-            let options = GetLinesOptions::for_session(&info.session_id);
-            let lines = db.get_lines(&options).context("Failed to load lines")?;
-            Ok((info.session_id, lines))
-        }
+        .context("Failed to resolve session")?
+    {
+        Some(info) => info,
         None => {
-            let sessions = db
+            let mut sessions = db
                 .find_sessions(session_input)
                 .context("Failed to search sessions")?;
-
             if sessions.is_empty() {
-                eprintln!("Session not found: {}", session_input);
-                std::process::exit(1);
+                // A typo still opens the session it's closest to
+                sessions = db
+                    .closest_sessions(session_input)
+                    .context("Failed to search sessions")?;
             }
-
-            if sessions.len() == 1 {
-                let info = &sessions[0];
-                let options = GetLinesOptions::for_session(&info.session_id);
-                let lines = db.get_lines(&options).context("Failed to load lines")?;
-                Ok((info.session_id.clone(), lines))
-            } else {
-                eprintln!("Multiple sessions match '{}'. Be more specific:", session_input);
-                for s in &sessions {
-                    let name = s.slug.as_deref().unwrap_or(&s.session_id[..8]);
-                    eprintln!("  {} ({} lines)", name, s.line_count);
-                }
-                std::process::exit(1);
+            if sessions.len() != 1 {
+                return Ok(None);
             }
+            sessions.remove(0)
         }
-    }
+    };
+
+    let options = GetLinesOptions::for_session(&session.session_id);
+    let lines = db.get_lines(&options).context("Failed to load lines")?;
+    Ok(Some((session.session_id, lines)))
 }
 
 fn show_stats(cli: &Cli) -> Result<()> {
@@ -409,7 +586,9 @@ fn list_sessions(cli: &Cli) -> Result<()> {
     Ok(())
 }
 
-fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, session_id: String) -> Result<()> {
+/// Run the viewer; with no session open it starts at the session picker,
+/// filtered by `picker_query`
+fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, picker_query: &str) -> Result<()> {
     let mut siv = cursive::default();
 
     // Set up theme with colors
@@ -422,6 +601,11 @@ fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, session_id: String) ->
     // Update content for initial selection
     update_all(&mut siv, &state.lock().unwrap());
 
+    let db_arc = Arc::new(Mutex::new(db));
+    if state.lock().unwrap().session_id.is_empty() {
+        show_session_picker(&mut siv, Arc::clone(&state), Arc::clone(&db_arc), picker_query);
+    }
+
     // ========== Key bindings ==========
 
     // Quit
@@ -520,6 +704,12 @@ fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, session_id: String) ->
         update_all(s, &state_next_bm.lock().unwrap());
     });
 
+    // Annotations: a to write a note on the line
+    let state_annotate = Arc::clone(&state);
+    siv.add_global_callback('a', move |s| {
+        show_annotation_dialog(s, Arc::clone(&state_annotate));
+    });
+
     // Copy: c for content, y for raw JSON
     let state_copy = Arc::clone(&state);
     siv.add_global_callback('c', move |s| {
@@ -584,7 +774,6 @@ fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, session_id: String) ->
 
     // Live mode toggle: L
     let state_live = Arc::clone(&state);
-    let db_arc = Arc::new(Mutex::new(db));
     siv.add_global_callback('L', move |s| {
         {
             let mut st = state_live.lock().unwrap();
@@ -598,6 +787,54 @@ fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, session_id: String) ->
         update_all(s, &state_live.lock().unwrap());
     });
 
+    // Fullscreen content: f to toggle
+    let state_full = Arc::clone(&state);
+    siv.add_global_callback('f', move |s| {
+        let fullscreen = {
+            let mut st = state_full.lock().unwrap();
+            st.fullscreen = !st.fullscreen;
+            st.fullscreen
+        };
+        s.call_on_name("list_hideable", |view: &mut HideableView<BoxedView>| {
+            view.set_visible(!fullscreen);
+        });
+        if !fullscreen {
+            let _ = s.focus_name("list");
+        }
+        update_all(s, &state_full.lock().unwrap());
+    });
+
+    // Session picker: s
+    let state_picker = Arc::clone(&state);
+    let db_picker = Arc::clone(&db_arc);
+    siv.add_global_callback('s', move |s| {
+        show_session_picker(s, Arc::clone(&state_picker), Arc::clone(&db_picker), "");
+    });
+
+    // Type filter popup: t
+    let state_filter = Arc::clone(&state);
+    siv.add_global_callback('t', move |s| {
+        show_type_filter_dialog(s, Arc::clone(&state_filter));
+    });
+
+    // Usage graph overlay: u to toggle
+    let state_usage = Arc::clone(&state);
+    siv.add_global_callback('u', move |s| {
+        if s.find_name::<TextView>("usage_graph").is_some() {
+            s.pop_layer();
+            return;
+        }
+        let graph = build_usage_graph_styled(&state_usage.lock().unwrap(), 50);
+        s.add_layer(
+            Dialog::around(TextView::new(graph).with_name("usage_graph").scrollable())
+                .title("Usage Graph (u to close)")
+                .button("Close", |s| {
+                    s.pop_layer();
+                })
+                .max_width(90),
+        );
+    });
+
     // Help
     siv.add_global_callback('?', |s| {
         s.add_layer(
@@ -612,18 +849,23 @@ fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, session_id: String) ->
                  2           CUSTOM - Smart view (MD/Diff/etc)\n\
                  \n\
                  Search:\n\
-                 /           Open search\n\
+                 /           Search as you type (↑/↓: history)\n\
                  n/N         Next/prev match\n\
                  \n\
                  Bookmarks:\n\
                  b           Toggle bookmark\n\
                  [/]         Prev/next bookmark\n\
+                 a           Annotate line\n\
                  \n\
                  Copy:\n\
                  c           Copy content to clipboard\n\
                  y           Yank raw JSON to clipboard\n\
                  \n\
                  Other:\n\
+                 f           Toggle fullscreen content\n\
+                 t           Filter by type\n\
+                 s           Switch session\n\
+                 u           Toggle usage graph\n\
                  L           Toggle live mode\n\
                  q/Esc       Quit\n\
                  ?           Help\n\
@@ -638,7 +880,6 @@ fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, session_id: String) ->
     // Live mode refresh callback
     let state_refresh = Arc::clone(&state);
     let db_for_refresh = Arc::clone(&db_arc);
-    let session_for_refresh = session_id.clone();
     siv.set_fps(2); // 2 FPS for live mode checking
 
     siv.add_global_callback(cursive::event::Event::Refresh, move |s| {
@@ -652,15 +893,18 @@ fn run_tui(state: Arc<Mutex<AppState>>, db: TranscriptDb, session_id: String) ->
             }
         }
 
-        let should_refresh = state_refresh.lock().unwrap().live_mode;
-        if should_refresh {
+        let session_id = {
+            let st = state_refresh.lock().unwrap();
+            (st.live_mode && !st.session_id.is_empty()).then(|| st.session_id.clone())
+        };
+        if let Some(session_id) = session_id {
             let db = db_for_refresh.lock().unwrap();
 
             // Poll for new lines
             let new_count = poll_new_lines(
                 &state_refresh,
                 &db,
-                &session_for_refresh,
+                &session_id,
             );
 
             // Refresh turn data for lines missing it
@@ -712,7 +956,7 @@ fn build_ui(state: Arc<Mutex<AppState>>) -> impl View {
     for (i, line) in st.lines.iter().enumerate() {
         let is_bookmarked = st.bookmarks.contains(&line.id);
         let is_search_match = st.search_results.contains(&i);
-        let label = format_list_item_styled(line, is_bookmarked, is_search_match);
+        let label = format_list_item_styled(line, is_bookmarked, st.is_annotated(line), is_search_match);
         list_view.add_item(label, i);
     }
 
@@ -771,7 +1015,10 @@ fn build_ui(state: Arc<Mutex<AppState>>) -> impl View {
         .child(header.fixed_height(1))
         .child(
             LinearLayout::horizontal()
-                .child(ResizedView::with_fixed_width(78, list_panel))
+                .child(
+                    HideableView::new(BoxedView::boxed(ResizedView::with_fixed_width(78, list_panel)))
+                        .with_name("list_hideable"),
+                )
                 .child(content_panel.full_width())
                 .full_height()
         )
@@ -790,15 +1037,15 @@ fn get_type_color(line_type: transcript_core::LineType) -> Color {
     }
 }
 
-/// Calculate context usage percentage from token usage
+/// Calculate context usage percentage from token usage, against the window
+/// of the line's model
 fn get_context_usage(line: &TranscriptLine) -> Option<(u64, u64)> {
-    const CONTEXT_SIZE: u64 = 200_000;
     let usage = line.usage()?;
     let total = usage.total();
     if total == 0 {
         return None;
     }
-    let percent = (total as f64 / CONTEXT_SIZE as f64 * 100.0).round() as u64;
+    let percent = (total as f64 / line_context_window(line) as f64 * 100.0).round() as u64;
     Some((percent.min(100), total))
 }
 
@@ -814,7 +1061,12 @@ fn get_usage_color(percent: u64) -> Color {
     }
 }
 
-fn format_list_item_styled(line: &TranscriptLine, is_bookmarked: bool, is_search_match: bool) -> StyledString {
+fn format_list_item_styled(
+    line: &TranscriptLine,
+    is_bookmarked: bool,
+    is_annotated: bool,
+    is_search_match: bool,
+) -> StyledString {
     let mut styled = StyledString::new();
 
     // Search match indicator (1 char)
@@ -831,6 +1083,13 @@ fn format_list_item_styled(line: &TranscriptLine, is_bookmarked: bool, is_search
         styled.append_plain(" ");
     }
 
+    // Annotation indicator (1 char)
+    if is_annotated {
+        styled.append_styled("✎", ColorStyle::new(Color::Light(BaseColor::Magenta), Color::TerminalDefault));
+    } else {
+        styled.append_plain(" ");
+    }
+
     // Line number (right-aligned, 4 chars - max 9999 lines)
     let line_num = format!("{:>4}", line.line_number.min(9999));
     styled.append_styled(&line_num, ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
@@ -858,7 +1117,7 @@ fn format_list_item_styled(line: &TranscriptLine, is_bookmarked: bool, is_search
     if let Some((percent, _)) = get_context_usage(line) {
         let usage_color = get_usage_color(percent);
         styled.append_plain(" ");
-        styled.append_styled(&format!("{:>2}%", percent), ColorStyle::new(usage_color, Color::TerminalDefault));
+        styled.append_styled(format!("{:>2}%", percent), ColorStyle::new(usage_color, Color::TerminalDefault));
     } else {
         styled.append_plain("    "); // 4 spaces for alignment (space + 3 chars)
     }
@@ -893,7 +1152,7 @@ fn build_header_styled(state: &AppState) -> StyledString {
 
     // Title
     styled.append_styled(
-        &format!("Transcript: {} ", state.title()),
+        format!("Transcript: {} ", state.title()),
         ColorStyle::new(Color::Light(BaseColor::Cyan), Color::TerminalDefault),
     );
 
@@ -908,7 +1167,7 @@ fn build_header_styled(state: &AppState) -> StyledString {
     for (mode, label) in modes {
         if state.view_mode == mode {
             styled.append_styled(
-                &format!("[{}]", label),
+                format!("[{}]", label),
                 ColorStyle::new(Color::Light(BaseColor::White), Color::Dark(BaseColor::Blue)),
             );
         } else {
@@ -919,7 +1178,7 @@ fn build_header_styled(state: &AppState) -> StyledString {
 
     styled.append_plain("│ ");
     styled.append_styled(
-        &format!("v{}", VERSION),
+        format!("v{}", VERSION),
         ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault),
     );
 
@@ -964,6 +1223,7 @@ fn build_footer_styled(state: &AppState) -> StyledString {
 
     // Focus indicator
     let focus_indicator = match state.focused_pane {
+        _ if state.fullscreen => "[FULLSCREEN]",
         FocusedPane::List => "[LIST]",
         FocusedPane::Content => "[CONTENT]",
     };
@@ -975,20 +1235,33 @@ fn build_footer_styled(state: &AppState) -> StyledString {
 
     // Line position
     styled.append_styled(
-        &format!("Line {}/{}", state.current_index + 1, state.lines.len()),
+        format!(
+            "Line {}/{}",
+            locale().format_count(state.current_index as i64 + 1),
+            locale().format_count(state.lines.len() as i64)
+        ),
         ColorStyle::new(Color::Light(BaseColor::White), Color::TerminalDefault),
     );
 
     styled.append_plain(" │ ");
 
     // Mode
-    styled.append_plain(&format!("Mode: {} ", state.view_mode.name()));
+    styled.append_plain(format!("Mode: {} ", state.view_mode.name()));
+
+    // Active type filter
+    if let Some(types) = &state.filter_opts.display_types {
+        styled.append_plain("│ ");
+        styled.append_styled(
+            format!("Filter: {} ", types.join(",")),
+            ColorStyle::new(Color::Light(BaseColor::Magenta), Color::TerminalDefault),
+        );
+    }
 
     // Bookmarks count
     if !state.bookmarks.is_empty() {
         styled.append_plain("│ ");
         styled.append_styled(
-            &format!("{}★", state.bookmarks.len()),
+            format!("{}★", state.bookmarks.len()),
             ColorStyle::new(Color::Light(BaseColor::Yellow), Color::TerminalDefault),
         );
         styled.append_plain(" ");
@@ -998,7 +1271,7 @@ fn build_footer_styled(state: &AppState) -> StyledString {
     if !state.search_results.is_empty() {
         styled.append_plain("│ ");
         styled.append_styled(
-            &format!("Match {}/{}", state.search_result_index + 1, state.search_results.len()),
+            format!("Match {}/{}", state.search_result_index + 1, state.search_results.len()),
             ColorStyle::new(Color::Light(BaseColor::Cyan), Color::TerminalDefault),
         );
         styled.append_plain(" ");
@@ -1006,7 +1279,7 @@ fn build_footer_styled(state: &AppState) -> StyledString {
 
     styled.append_plain("│ ");
     styled.append_styled(
-        "?: help  q: quit",
+        "?: help  u: usage  q: quit",
         ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault),
     );
 
@@ -1022,6 +1295,56 @@ fn build_footer_styled(state: &AppState) -> StyledString {
     styled
 }
 
+/// Build the usage graph overlay: context usage per line, with compaction markers
+fn build_usage_graph_styled(state: &AppState, bar_width: usize) -> StyledString {
+    let mut styled = StyledString::new();
+    let points = usage_series(&state.lines, None);
+
+    styled.append_styled(
+        "Context Usage Over Time\n\n",
+        ColorStyle::new(Color::Light(BaseColor::Cyan), Color::TerminalDefault),
+    );
+
+    if points.is_empty() {
+        styled.append_plain("No usage data available");
+        return styled;
+    }
+
+    let max_percent = points.iter().map(|p| p.percent).fold(0.0f64, f64::max).max(1.0);
+    let dim = ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault);
+    let compaction = ColorStyle::new(Color::Light(BaseColor::Magenta), Color::TerminalDefault);
+
+    for point in &points {
+        if point.after_compaction {
+            styled.append_styled("──────── compacted ────────\n", compaction);
+        }
+
+        let marker = if point.index == state.current_index { ">" } else { " " };
+        let color = get_usage_color(point.percent.round() as u64);
+        let bar_len = ((point.percent / max_percent) * bar_width as f64) as usize;
+
+        styled.append_styled(format!("{}{} {:>5} ", marker, point.time, point.line_number), dim);
+        styled.append_styled("█".repeat(bar_len), ColorStyle::new(color, Color::TerminalDefault));
+        let tokens = if point.tokens >= 1000 {
+            format!("{}k", point.tokens / 1000)
+        } else {
+            point.tokens.to_string()
+        };
+        styled.append_styled(
+            format!(" {:.1}% ({})\n", point.percent, tokens),
+            ColorStyle::new(color, Color::TerminalDefault),
+        );
+    }
+
+    styled.append_plain("\n");
+    styled.append_styled("Green: <50% ", ColorStyle::new(get_usage_color(0), Color::TerminalDefault));
+    styled.append_styled("Yellow: 50-70% ", ColorStyle::new(get_usage_color(60), Color::TerminalDefault));
+    styled.append_styled("Red: >70% ", ColorStyle::new(get_usage_color(100), Color::TerminalDefault));
+    styled.append_styled("── compaction", compaction);
+
+    styled
+}
+
 /// Build the fixed progress bar at the bottom
 fn build_progress_bar_styled(state: &AppState) -> StyledString {
     let mut styled = StyledString::new();
@@ -1050,21 +1373,21 @@ fn build_progress_bar_styled(state: &AppState) -> StyledString {
                     _ => Color::Dark(BaseColor::White),
                 };
 
-                styled.append_styled(&"▓".repeat(pre_turn_filled), ColorStyle::new(dim_color, Color::TerminalDefault));
-                styled.append_styled(&"█".repeat(turn_delta), ColorStyle::new(bar_color, Color::TerminalDefault));
-                styled.append_styled(&"░".repeat(empty), ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
+                styled.append_styled("▓".repeat(pre_turn_filled), ColorStyle::new(dim_color, Color::TerminalDefault));
+                styled.append_styled("█".repeat(turn_delta), ColorStyle::new(bar_color, Color::TerminalDefault));
+                styled.append_styled("░".repeat(empty), ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
             } else {
                 // Single-segment bar
                 let empty = bar_width.saturating_sub(total_filled);
-                styled.append_styled(&"█".repeat(total_filled), ColorStyle::new(bar_color, Color::TerminalDefault));
-                styled.append_styled(&"░".repeat(empty), ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
+                styled.append_styled("█".repeat(total_filled), ColorStyle::new(bar_color, Color::TerminalDefault));
+                styled.append_styled("░".repeat(empty), ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
             }
 
             styled.append_plain("] ");
 
             // Percentage
             styled.append_styled(
-                &format!("{}%", percent),
+                format!("{}%", percent),
                 ColorStyle::new(bar_color, Color::TerminalDefault),
             );
 
@@ -1073,7 +1396,7 @@ fn build_progress_bar_styled(state: &AppState) -> StyledString {
                 let delta = percent.saturating_sub(turn_start);
                 if delta > 0 {
                     styled.append_styled(
-                        &format!(" (+{}%)", delta),
+                        format!(" (+{}%)", delta),
                         ColorStyle::new(bar_color, Color::TerminalDefault),
                     );
                 }
@@ -1153,145 +1476,54 @@ fn render_markdown(content: &str) -> StyledString {
     styled
 }
 
-/// Parse ANSI escape sequences and convert to Cursive StyledString
+/// Convert text with embedded ANSI escapes to a StyledString (via the shared fragment model)
 fn parse_ansi_to_styled(text: &str) -> StyledString {
     let mut styled = StyledString::new();
-    let mut current_style = ColorStyle::primary();
-    let mut chars = text.chars().peekable();
-    let mut current_text = String::new();
-
-    while let Some(c) = chars.next() {
-        if c == '\x1b' {
-            // Flush current text
-            if !current_text.is_empty() {
-                styled.append_styled(&current_text, current_style);
-                current_text.clear();
-            }
-
-            // Parse escape sequence
-            if chars.peek() == Some(&'[') {
-                chars.next(); // consume '['
-                let mut seq = String::new();
-                while let Some(&ch) = chars.peek() {
-                    if ch.is_ascii_alphabetic() {
-                        chars.next();
-                        break;
-                    }
-                    seq.push(chars.next().unwrap());
-                }
+    append_ansi(&mut styled, text, Style::none());
+    styled
+}
 
-                // Parse the sequence
-                current_style = parse_sgr_sequence(&seq);
-            }
+/// Append text with embedded ANSI escapes; unstyled runs get `base`
+fn append_ansi(styled: &mut StyledString, text: &str, base: Style) {
+    for fragment in parse_ansi(text) {
+        if fragment.style.is_plain() {
+            styled.append_styled(fragment.text, base);
         } else {
-            current_text.push(c);
+            styled.append_styled(fragment.text, fragment_style(&fragment.style));
         }
     }
+}
 
-    // Flush remaining text
-    if !current_text.is_empty() {
-        styled.append_styled(&current_text, current_style);
+/// Append tool output: a hex preview if binary, otherwise with its own colors
+fn append_tool_output(styled: &mut StyledString, text: &str, base: Style) {
+    if is_binary(text) {
+        styled.append_styled(display_text(text), base);
+    } else {
+        append_ansi(styled, text, base);
     }
-
-    styled
 }
 
-/// Parse SGR (Select Graphic Rendition) sequence and return ColorStyle
-fn parse_sgr_sequence(seq: &str) -> ColorStyle {
-    let codes: Vec<u8> = seq.split(';')
-        .filter_map(|s| s.parse().ok())
-        .collect();
-
-    let mut fg = Color::TerminalDefault;
-    let mut _bold = false;
-    let mut _italic = false;
-
-    let mut i = 0;
-    while i < codes.len() {
-        match codes[i] {
-            0 => {
-                // Reset
-                fg = Color::TerminalDefault;
-                _bold = false;
-                _italic = false;
-            }
-            1 => _bold = true,
-            3 => _italic = true,
-            30 => fg = Color::Dark(BaseColor::Black),
-            31 => fg = Color::Dark(BaseColor::Red),
-            32 => fg = Color::Dark(BaseColor::Green),
-            33 => fg = Color::Dark(BaseColor::Yellow),
-            34 => fg = Color::Dark(BaseColor::Blue),
-            35 => fg = Color::Dark(BaseColor::Magenta),
-            36 => fg = Color::Dark(BaseColor::Cyan),
-            37 => fg = Color::Dark(BaseColor::White),
-            90 => fg = Color::Light(BaseColor::Black),
-            91 => fg = Color::Light(BaseColor::Red),
-            92 => fg = Color::Light(BaseColor::Green),
-            93 => fg = Color::Light(BaseColor::Yellow),
-            94 => fg = Color::Light(BaseColor::Blue),
-            95 => fg = Color::Light(BaseColor::Magenta),
-            96 => fg = Color::Light(BaseColor::Cyan),
-            97 => fg = Color::Light(BaseColor::White),
-            38 if i + 2 < codes.len() && codes[i + 1] == 5 => {
-                // 256-color mode: 38;5;n
-                let color_code = codes[i + 2];
-                fg = ansi_256_to_cursive(color_code);
-                i += 2;
-            }
-            _ => {}
-        }
-        i += 1;
-    }
-
-    ColorStyle::new(fg, Color::TerminalDefault)
-}
-
-/// Convert ANSI 256-color code to Cursive Color
-fn ansi_256_to_cursive(code: u8) -> Color {
-    match code {
-        0 => Color::Dark(BaseColor::Black),
-        1 => Color::Dark(BaseColor::Red),
-        2 => Color::Dark(BaseColor::Green),
-        3 => Color::Dark(BaseColor::Yellow),
-        4 => Color::Dark(BaseColor::Blue),
-        5 => Color::Dark(BaseColor::Magenta),
-        6 => Color::Dark(BaseColor::Cyan),
-        7 => Color::Dark(BaseColor::White),
-        8 => Color::Light(BaseColor::Black),
-        9 => Color::Light(BaseColor::Red),
-        10 => Color::Light(BaseColor::Green),
-        11 => Color::Light(BaseColor::Yellow),
-        12 => Color::Light(BaseColor::Blue),
-        13 => Color::Light(BaseColor::Magenta),
-        14 => Color::Light(BaseColor::Cyan),
-        15 => Color::Light(BaseColor::White),
-        // For 16-255, use a reasonable approximation
-        16..=231 => {
-            // 6x6x6 color cube - simplified
-            let n = code - 16;
-            let r = n / 36;
-            let g = (n % 36) / 6;
-            let b = n % 6;
-            // Map to basic colors based on dominant channel
-            if r >= g && r >= b {
-                Color::Light(BaseColor::Red)
-            } else if g >= r && g >= b {
-                Color::Light(BaseColor::Green)
-            } else {
-                Color::Light(BaseColor::Blue)
-            }
-        }
-        // Grayscale
-        232..=255 => {
-            let gray = code - 232;
-            if gray < 12 {
-                Color::Dark(BaseColor::Black)
-            } else {
-                Color::Light(BaseColor::White)
-            }
+/// Map a parsed ANSI style onto a Cursive Style
+fn fragment_style(style: &TextStyle) -> Style {
+    let color = |c: Option<AnsiColor>| match c {
+        Some(AnsiColor::Indexed(n)) => Color::from_256colors(n),
+        Some(AnsiColor::Rgb(r, g, b)) => Color::Rgb(r, g, b),
+        None => Color::TerminalDefault,
+    };
+
+    let mut cursive_style = Style::from(ColorStyle::new(color(style.fg), color(style.bg)));
+    for (on, effect) in [
+        (style.bold, Effect::Bold),
+        (style.dim, Effect::Dim),
+        (style.italic, Effect::Italic),
+        (style.underline, Effect::Underline),
+        (style.reverse, Effect::Reverse),
+    ] {
+        if on {
+            cursive_style = cursive_style.combine(effect);
         }
     }
+    cursive_style
 }
 
 /// Check if line has thinking content
@@ -1310,7 +1542,7 @@ fn render_thinking(line: &TranscriptLine) -> StyledString {
 
     // Header with timestamp
     styled.append_styled(
-        &format!("━━━ THINKING ━━━ {} ━━━\n",
+        format!("━━━ THINKING ━━━ {} ━━━\n",
             line.format_time()
         ),
         ColorStyle::new(Color::Light(BaseColor::Magenta), Color::TerminalDefault),
@@ -1328,7 +1560,7 @@ fn render_thinking(line: &TranscriptLine) -> StyledString {
         styled.append_plain("Turn: ");
         styled.append_styled(turn_id, ColorStyle::new(Color::Light(BaseColor::Magenta), Color::TerminalDefault));
         if let Some(seq) = line.turn_sequence {
-            styled.append_styled(&format!(" (seq {})", seq), ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
+            styled.append_styled(format!(" (seq {})", seq), ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
         }
         styled.append_plain("\n");
     }
@@ -1356,7 +1588,7 @@ fn render_thinking(line: &TranscriptLine) -> StyledString {
                 if let Some(sig) = signature {
                     styled.append_plain("\n");
                     styled.append_styled(
-                        &format!("🔏 Signature: {}...\n", &sig[..32.min(sig.len())]),
+                        format!("🔏 Signature: {}...\n", &sig[..32.min(sig.len())]),
                         ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault),
                     );
                 }
@@ -1368,7 +1600,7 @@ fn render_thinking(line: &TranscriptLine) -> StyledString {
     if let Some(usage) = line.usage() {
         styled.append_plain("\n");
         styled.append_styled(
-            &format!("📊 Tokens: {} in / {} out = {} total\n",
+            format!("📊 Tokens: {} in / {} out = {} total\n",
                 usage.input_tokens,
                 usage.output_tokens,
                 usage.total()
@@ -1409,7 +1641,7 @@ fn render_edit_diff(line: &TranscriptLine) -> StyledString {
 
                     // Show unified diff header
                     styled.append_styled(
-                        &format!("@@ -{},{} +{},{} @@\n",
+                        format!("@@ -{},{} +{},{} @@\n",
                             1, old_string.lines().count(),
                             1, new_string.lines().count()
                         ),
@@ -1471,7 +1703,7 @@ fn render_bash_output(line: &TranscriptLine) -> StyledString {
         if let Some(result) = &parsed.tool_use_result {
             if let Some(stdout) = result.get("stdout").and_then(|v| v.as_str()) {
                 if !stdout.is_empty() {
-                    styled.append_plain(stdout);
+                    append_tool_output(&mut styled, stdout, Style::none());
                     if !stdout.ends_with('\n') {
                         styled.append_plain("\n");
                     }
@@ -1479,7 +1711,11 @@ fn render_bash_output(line: &TranscriptLine) -> StyledString {
             }
             if let Some(stderr) = result.get("stderr").and_then(|v| v.as_str()) {
                 if !stderr.is_empty() {
-                    styled.append_styled(stderr, ColorStyle::new(Color::Light(BaseColor::Red), Color::TerminalDefault));
+                    append_tool_output(
+                        &mut styled,
+                        stderr,
+                        Style::from(ColorStyle::new(Color::Light(BaseColor::Red), Color::TerminalDefault)),
+                    );
                     if !stderr.ends_with('\n') {
                         styled.append_plain("\n");
                     }
@@ -1583,9 +1819,9 @@ fn render_read_output(line: &TranscriptLine) -> StyledString {
 
         // Show file content if available
         if let Some(content) = &line.content {
-            for (i, line_text) in content.lines().enumerate() {
+            for (i, line_text) in display_text(content).lines().enumerate() {
                 styled.append_styled(
-                    &format!("{:>4} ", i + 1),
+                    format!("{:>4} ", i + 1),
                     ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault),
                 );
                 styled.append_plain(line_text);
@@ -1611,7 +1847,7 @@ fn render_todo_list(line: &TranscriptLine) -> StyledString {
 
                     if let Some(todos) = input.get("todos").and_then(|v| v.as_array()) {
                         let total = todos.len();
-                        let mut completed = 0;
+                        let mut completed = 0usize;
 
                         for todo in todos {
                             let content = todo.get("content").and_then(|v| v.as_str()).unwrap_or("");
@@ -1627,13 +1863,13 @@ fn render_todo_list(line: &TranscriptLine) -> StyledString {
                             };
 
                             styled.append_styled(checkbox, ColorStyle::new(color, Color::TerminalDefault));
-                            styled.append_plain(&format!(" {}\n", content));
+                            styled.append_plain(format!(" {}\n", content));
                         }
 
                         styled.append_plain("\n");
-                        let percent = if total > 0 { completed * 100 / total } else { 0 };
+                        let percent = (completed * 100).checked_div(total).unwrap_or(0);
                         styled.append_styled(
-                            &format!("Progress: {}/{} ({}%)\n", completed, total, percent),
+                            format!("Progress: {}/{} ({}%)\n", completed, total, percent),
                             ColorStyle::new(Color::Light(BaseColor::Cyan), Color::TerminalDefault),
                         );
                     }
@@ -1674,7 +1910,7 @@ fn render_grep_output(line: &TranscriptLine) -> StyledString {
 
         // Show content/matches
         if let Some(content) = &line.content {
-            styled.append_plain(content);
+            append_tool_output(&mut styled, content, Style::none());
         }
     }
     styled
@@ -1690,13 +1926,13 @@ fn render_json_value(styled: &mut StyledString, value: &serde_json::Value, inden
         }
         serde_json::Value::Bool(b) => {
             styled.append_styled(
-                &b.to_string(),
+                b.to_string(),
                 ColorStyle::new(Color::Light(BaseColor::Magenta), Color::TerminalDefault),
             );
         }
         serde_json::Value::Number(n) => {
             styled.append_styled(
-                &n.to_string(),
+                n.to_string(),
                 ColorStyle::new(Color::Light(BaseColor::Yellow), Color::TerminalDefault),
             );
         }
@@ -1711,14 +1947,14 @@ fn render_json_value(styled: &mut StyledString, value: &serde_json::Value, inden
             } else {
                 styled.append_plain("[\n");
                 for (i, item) in arr.iter().enumerate() {
-                    styled.append_plain(&format!("{}  ", indent_str));
+                    styled.append_plain(format!("{}  ", indent_str));
                     render_json_value(styled, item, indent + 1);
                     if i < arr.len() - 1 {
                         styled.append_plain(",");
                     }
                     styled.append_plain("\n");
                 }
-                styled.append_plain(&format!("{}]", indent_str));
+                styled.append_plain(format!("{}]", indent_str));
             }
         }
         serde_json::Value::Object(obj) => {
@@ -1728,10 +1964,10 @@ fn render_json_value(styled: &mut StyledString, value: &serde_json::Value, inden
                 styled.append_plain("{\n");
                 let entries: Vec<_> = obj.iter().collect();
                 for (i, (key, val)) in entries.iter().enumerate() {
-                    styled.append_plain(&format!("{}  ", indent_str));
+                    styled.append_plain(format!("{}  ", indent_str));
                     // Key in cyan
                     styled.append_styled(
-                        &format!("\"{}\"", key),
+                        format!("\"{}\"", key),
                         ColorStyle::new(Color::Light(BaseColor::Cyan), Color::TerminalDefault),
                     );
                     styled.append_plain(": ");
@@ -1741,7 +1977,7 @@ fn render_json_value(styled: &mut StyledString, value: &serde_json::Value, inden
                     }
                     styled.append_plain("\n");
                 }
-                styled.append_plain(&format!("{}}}", indent_str));
+                styled.append_plain(format!("{}}}", indent_str));
             }
         }
     }
@@ -1769,7 +2005,7 @@ fn render_write_output(line: &TranscriptLine) -> StyledString {
                     if let Some(content) = input.get("content").and_then(|v| v.as_str()) {
                         for (i, line_text) in content.lines().enumerate() {
                             styled.append_styled(
-                                &format!("{:>4} ", i + 1),
+                                format!("{:>4} ", i + 1),
                                 ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault),
                             );
                             styled.append_plain(line_text);
@@ -1800,7 +2036,8 @@ fn render_content_styled(line: &TranscriptLine, mode: ViewMode) -> StyledString
                 styled.append_plain(&line.raw);
             }
         }
-        ViewMode::Custom => {
+        // The thread layout is a transcript-tui list mode; content is the same
+        ViewMode::Custom | ViewMode::Thread => {
             // Check for thinking blocks first
             if has_thinking(line) {
                 return render_thinking(line);
@@ -1818,11 +2055,11 @@ fn render_content_styled(line: &TranscriptLine, mode: ViewMode) -> StyledString
                     _ => {
                         // For other tools, show tool name + raw input
                         styled.append_styled(
-                            &format!("━━━ {} ━━━\n\n", tool_name.to_uppercase()),
+                            format!("━━━ {} ━━━\n\n", tool_name.to_uppercase()),
                             ColorStyle::new(Color::Light(BaseColor::Cyan), Color::TerminalDefault),
                         );
                         if let Some(content) = &line.content {
-                            styled.append_plain(content);
+                            append_tool_output(&mut styled, content, Style::none());
                         }
                         return styled;
                     }
@@ -1863,9 +2100,18 @@ fn get_border_color(line_type: transcript_core::LineType) -> Color {
     }
 }
 
+/// Escape control characters in every span so content can't drive the terminal
+fn sanitize_styled(styled: StyledString) -> StyledString {
+    let mut clean = StyledString::new();
+    for span in styled.spans() {
+        clean.append_styled(sanitize_text(span.content), *span.attr);
+    }
+    clean
+}
+
 /// Add left border to content lines based on line type
 fn add_bordered_content(styled: &mut StyledString, content: &str, border_color: Color) {
-    for content_line in content.lines() {
+    for content_line in display_text(content).lines() {
         styled.append_styled(
             "┃ ",
             ColorStyle::new(border_color, Color::TerminalDefault),
@@ -1875,13 +2121,36 @@ fn add_bordered_content(styled: &mut StyledString, content: &str, border_color:
     }
 }
 
+/// Add left border to content lines, keeping embedded ANSI colors
+fn add_bordered_ansi(styled: &mut StyledString, content: &str, border_color: Color) {
+    let border = ColorStyle::new(border_color, Color::TerminalDefault);
+    styled.append_styled("┃ ", border);
+    for fragment in parse_ansi(content.trim_end_matches('\n')) {
+        let style = if fragment.style.is_plain() {
+            Style::none()
+        } else {
+            fragment_style(&fragment.style)
+        };
+        for (i, part) in fragment.text.split('\n').enumerate() {
+            if i > 0 {
+                styled.append_plain("\n");
+                styled.append_styled("┃ ", border);
+            }
+            if !part.is_empty() {
+                styled.append_styled(part, style);
+            }
+        }
+    }
+    styled.append_plain("\n");
+}
+
 /// Render line as Markdown view (used by Custom view for non-tool content)
 fn render_md_view(line: &TranscriptLine) -> StyledString {
     let mut styled = StyledString::new();
 
     // Metadata header
     styled.append_styled(
-        &format!("━━━ {} ━━━ {} ━━━\n",
+        format!("━━━ {} ━━━ {} ━━━\n",
             line.line_type.to_string().to_uppercase(),
             line.format_time()
         ),
@@ -1899,16 +2168,30 @@ fn render_md_view(line: &TranscriptLine) -> StyledString {
         styled.append_plain("Turn: ");
         styled.append_styled(turn_id, ColorStyle::new(Color::Light(BaseColor::Magenta), Color::TerminalDefault));
         if let Some(seq) = line.turn_sequence {
-            styled.append_styled(&format!(" (seq {})", seq), ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
+            styled.append_styled(format!(" (seq {})", seq), ColorStyle::new(Color::Dark(BaseColor::White), Color::TerminalDefault));
         }
         styled.append_plain("\n");
     }
 
     styled.append_plain("\n");
 
+    if let Some(summary) = system_summary(line) {
+        let color = match summary.level {
+            SystemLevel::Info => Color::TerminalDefault,
+            SystemLevel::Warning => Color::Light(BaseColor::Yellow),
+            SystemLevel::Error => Color::Light(BaseColor::Red),
+        };
+        styled.append_styled(format!("{}\n", summary.text), ColorStyle::new(color, Color::TerminalDefault));
+    }
+
     // Render markdown content with termimad, adding left border
     if let Some(content) = &line.content {
         let border_color = get_border_color(line.line_type);
+        if has_ansi(content) && !is_binary(content) {
+            // Colored tool output: keep its own colors instead of markdown
+            add_bordered_ansi(&mut styled, content, border_color);
+            return styled;
+        }
         let rendered = render_markdown(content);
         let rendered_text = rendered.source();
 
@@ -1920,7 +2203,7 @@ fn render_md_view(line: &TranscriptLine) -> StyledString {
     if let Some(usage) = line.usage() {
         styled.append_plain("\n");
         styled.append_styled(
-            &format!("📊 Tokens: {} in / {} out = {} total\n",
+            format!("📊 Tokens: {} in / {} out = {} total\n",
                 usage.input_tokens,
                 usage.output_tokens,
                 usage.total()
@@ -1948,7 +2231,7 @@ fn update_header(siv: &mut Cursive, state: &AppState) {
 
 fn update_content(siv: &mut Cursive, state: &AppState) {
     if let Some(line) = state.current_line() {
-        let content = render_content_styled(line, state.view_mode);
+        let content = sanitize_styled(render_content_styled(line, state.view_mode));
         siv.call_on_name("content", |view: &mut TextView| {
             view.set_content(content);
         });
@@ -2012,56 +2295,396 @@ fn rebuild_list(siv: &mut Cursive, state: &AppState) {
         for (i, line) in state.lines.iter().enumerate() {
             let is_bookmarked = state.bookmarks.contains(&line.id);
             let is_search_match = state.search_results.contains(&i);
-            let label = format_list_item_styled(line, is_bookmarked, is_search_match);
+            let label = format_list_item_styled(line, is_bookmarked, state.is_annotated(line), is_search_match);
             view.add_item(label, i);
         }
         view.set_selection(state.current_index);
     });
 }
 
-fn show_search_dialog(siv: &mut Cursive, state: Arc<Mutex<AppState>>) {
-    let state_submit = Arc::clone(&state);
+/// Checkbox per display type present in the session; applying rebuilds the list
+fn show_type_filter_dialog(siv: &mut Cursive, state: Arc<Mutex<AppState>>) {
+    let (counts, selected) = {
+        let st = state.lock().unwrap();
+        (
+            get_display_type_counts(&st.all_lines),
+            st.filter_opts.display_types.clone(),
+        )
+    };
 
-    siv.add_layer(
-        Dialog::new()
-            .title("Search")
-            .content(
-                EditView::new()
-                    .on_submit(move |s, query| {
-                        {
-                            let mut st = state_submit.lock().unwrap();
-                            st.search_query = query.to_string();
-                            st.execute_search();
-                        }
-                        s.pop_layer();
-                        rebuild_list(s, &state_submit.lock().unwrap());
-                        update_all(s, &state_submit.lock().unwrap());
-                    })
-                    .with_name("search_input")
-                    .fixed_width(40)
-            )
-            .button("Search", {
-                let state = Arc::clone(&state);
-                move |s| {
-                    let query = s.call_on_name("search_input", |view: &mut EditView| {
-                        view.get_content()
-                    }).unwrap();
-                    {
-                        let mut st = state.lock().unwrap();
-                        st.search_query = query.to_string();
-                        st.execute_search();
-                    }
-                    s.pop_layer();
-                    rebuild_list(s, &state.lock().unwrap());
-                    update_all(s, &state.lock().unwrap());
-                }
+    let mut checkboxes = LinearLayout::vertical();
+    for (display_type, count) in &counts {
+        let checked = selected
+            .as_ref()
+            .is_none_or(|types| types.iter().any(|t| t == display_type));
+        let mut checkbox = Checkbox::new();
+        checkbox.set_checked(checked);
+        checkboxes.add_child(
+            LinearLayout::horizontal()
+                .child(checkbox.with_name(format!("filter_{}", display_type)))
+                .child(TextView::new(format!(" {:<14}{:>6}", display_type, count))),
+        );
+    }
+
+    let types: Vec<&'static str> = counts.iter().map(|(t, _)| *t).collect();
+    let apply = move |s: &mut Cursive| {
+        let checked: Vec<String> = types
+            .iter()
+            .filter(|t| {
+                s.call_on_name(&format!("filter_{}", t), |c: &mut Checkbox| c.is_checked())
+                    .unwrap_or(false)
             })
-            .button("Cancel", |s| { s.pop_layer(); })
+            .map(|t| t.to_string())
+            .collect();
+        s.pop_layer();
+
+        // Everything (or nothing) checked means no filter
+        let filter = if checked.is_empty() || checked.len() == types.len() {
+            None
+        } else {
+            Some(checked)
+        };
+        state.lock().unwrap().set_type_filter(filter);
+        rebuild_list(s, &state.lock().unwrap());
+        update_all(s, &state.lock().unwrap());
+    };
+
+    siv.add_layer(
+        Dialog::around(checkboxes)
+            .title("Filter by type")
+            .button("Apply", apply)
+            .button("Cancel", |s| {
+                s.pop_layer();
+            }),
     );
 }
 
+/// Live search dialog: matches update and the selection jumps while typing
+fn show_search_dialog(siv: &mut Cursive, state: Arc<Mutex<AppState>>) {
+    let (initial_query, origin, label) = {
+        let mut st = state.lock().unwrap();
+        st.search_history.reset_recall();
+        (st.search_query.clone(), st.current_index, st.search_count_label())
+    };
+
+    // Re-run the search and refresh the list, content and match count
+    fn refresh(s: &mut Cursive, state: &Arc<Mutex<AppState>>, query: &str, origin: usize) {
+        let label = {
+            let mut st = state.lock().unwrap();
+            st.incremental_search(query, origin);
+            st.search_count_label()
+        };
+        s.call_on_name("search_count", |view: &mut TextView| view.set_content(label));
+        rebuild_list(s, &state.lock().unwrap());
+        update_all(s, &state.lock().unwrap());
+    }
+
+    let finish = {
+        let state = Arc::clone(&state);
+        move |s: &mut Cursive, query: &str| {
+            state.lock().unwrap().push_search_history(query);
+            s.pop_layer();
+            refresh(s, &state, query, origin);
+        }
+    };
+
+    let cancel = {
+        let state = Arc::clone(&state);
+        let initial_query = initial_query.clone();
+        move |s: &mut Cursive| {
+            s.pop_layer();
+            refresh(s, &state, &initial_query, origin);
+            let mut st = state.lock().unwrap();
+            st.current_index = origin;
+            drop(st);
+            update_all(s, &state.lock().unwrap());
+        }
+    };
+
+    let recall = |older: bool| {
+        let state = Arc::clone(&state);
+        move |s: &mut Cursive| {
+            let Some(query) = state.lock().unwrap().recall_search_history(older) else {
+                return;
+            };
+            // set_content returns the on_edit callback, which re-runs the search
+            if let Some(on_edit) =
+                s.call_on_name("search_input", |view: &mut EditView| view.set_content(query))
+            {
+                on_edit(s);
+            }
+        }
+    };
+
+    let input = EditView::new()
+        .content(initial_query)
+        .on_edit({
+            let state = Arc::clone(&state);
+            move |s, query, _cursor| refresh(s, &state, query, origin)
+        })
+        .on_submit({
+            let finish = finish.clone();
+            move |s, query| finish(s, query)
+        })
+        .with_name("search_input")
+        .fixed_width(40);
+
+    let input = OnEventView::new(input)
+        .on_event(Key::Up, recall(true))
+        .on_event(Key::Down, recall(false));
+
+    let dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(input)
+            .child(TextView::new(label).with_name("search_count")),
+    )
+    .title("Search")
+    .button("Search", move |s| {
+        let query = s
+            .call_on_name("search_input", |view: &mut EditView| view.get_content())
+            .unwrap();
+        finish(s, &query);
+    })
+    .button("Cancel", cancel.clone());
+
+    // Esc cancels the search instead of quitting
+    siv.add_layer(OnEventView::new(dialog).on_event(Key::Esc, cancel));
+}
+
+/// Write an annotation on the selected line; Enter saves, Esc cancels
+fn show_annotation_dialog(siv: &mut Cursive, state: Arc<Mutex<AppState>>) {
+    let Some(line_number) = state.lock().unwrap().current_line().map(|l| l.line_number) else {
+        return;
+    };
+
+    let save = {
+        let state = Arc::clone(&state);
+        move |s: &mut Cursive, text: &str| {
+            s.pop_layer();
+            if !text.trim().is_empty() {
+                state.lock().unwrap().annotate(text.trim());
+                rebuild_list(s, &state.lock().unwrap());
+            }
+            update_all(s, &state.lock().unwrap());
+        }
+    };
+
+    let input = EditView::new()
+        .on_submit({
+            let save = save.clone();
+            move |s, text| save(s, text)
+        })
+        .with_name("annotation_input")
+        .fixed_width(50);
+
+    let dialog = Dialog::around(input)
+        .title(format!("Annotate line {}", line_number))
+        .button("Save", move |s| {
+            let text = s
+                .call_on_name("annotation_input", |view: &mut EditView| view.get_content())
+                .unwrap();
+            save(s, &text);
+        })
+        .dismiss_button("Cancel");
+
+    // Esc cancels instead of quitting
+    siv.add_layer(OnEventView::new(dialog).on_event(Key::Esc, |s| {
+        s.pop_layer();
+    }));
+}
+
+/// A session offered by the picker
+struct PickerEntry {
+    session_id: String,
+    name: String,
+    project: Option<String>,
+    line_count: i64,
+    last_timestamp: Option<String>,
+}
+
+impl PickerEntry {
+    /// Every indexed session, most recently active first
+    fn load_all(db: &TranscriptDb) -> Result<Vec<Self>, DbError> {
+        let sessions = db.get_sessions(None)?;
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        let mut projects = db.get_session_projects(&ids)?;
+        Ok(sessions
+            .into_iter()
+            .map(|s| Self {
+                name: s
+                    .slug
+                    .clone()
+                    .unwrap_or_else(|| s.session_id.chars().take(8).collect()),
+                project: projects.remove(&s.session_id),
+                line_count: s.line_count,
+                last_timestamp: s.last_timestamp,
+                session_id: s.session_id,
+            })
+            .collect())
+    }
+
+    fn label(&self) -> StyledString {
+        let last = self.last_timestamp.as_deref().unwrap_or("unknown");
+        let mut label = StyledString::styled(format!("{:<24}", self.name), Effect::Bold);
+        label.append_styled(
+            format!(" {:<16}", self.project.as_deref().unwrap_or("-")),
+            Color::Dark(BaseColor::Yellow),
+        );
+        label.append_plain(format!(" {:>6} lines  ", self.line_count));
+        label.append_styled(
+            last.get(..16).unwrap_or(last).replace('T', " "),
+            Color::Light(BaseColor::Black),
+        );
+        label
+    }
+}
+
+/// Session picker: sessions filtered by name and project as you type (fuzzy);
+/// Enter opens the selected one, Esc closes it (quits with no session open)
+fn show_session_picker(siv: &mut Cursive, state: Arc<Mutex<AppState>>, db: Arc<Mutex<TranscriptDb>>, query: &str) {
+    let entries = match PickerEntry::load_all(&db.lock().unwrap()) {
+        Ok(entries) => Arc::new(entries),
+        Err(e) => {
+            state.lock().unwrap().status_message = Some(format!("Sessions not listed: {}", e));
+            update_all(siv, &state.lock().unwrap());
+            return;
+        }
+    };
+
+    // Refill the list with the sessions matching the query, best first
+    fn refresh(s: &mut Cursive, entries: &[PickerEntry], query: &str) {
+        let matches = fuzzy_filter(query, entries, |e| {
+            format!("{} {}", e.name, e.project.as_deref().unwrap_or_default())
+        });
+        let count = format!("{} of {} sessions", matches.len(), entries.len());
+        s.call_on_name("picker_list", |view: &mut SelectView<String>| {
+            view.clear();
+            for i in matches {
+                view.add_item(entries[i].label(), entries[i].session_id.clone());
+            }
+        });
+        s.call_on_name("picker_count", |view: &mut TextView| view.set_content(count));
+    }
+
+    let open = {
+        let state = Arc::clone(&state);
+        move |s: &mut Cursive, session_id: &str| {
+            s.pop_layer();
+            switch_session(s, &state, &db.lock().unwrap(), session_id);
+        }
+    };
+
+    let cancel = {
+        let state = Arc::clone(&state);
+        move |s: &mut Cursive| {
+            if state.lock().unwrap().session_id.is_empty() {
+                s.quit();
+            } else {
+                s.pop_layer();
+            }
+        }
+    };
+
+    let move_selection = |up: bool| {
+        move |s: &mut Cursive| {
+            s.call_on_name("picker_list", |view: &mut SelectView<String>| {
+                if up {
+                    view.select_up(1)
+                } else {
+                    view.select_down(1)
+                }
+            });
+        }
+    };
+
+    let input = EditView::new()
+        .content(query)
+        .on_edit({
+            let entries = Arc::clone(&entries);
+            move |s, query, _cursor| refresh(s, &entries, query)
+        })
+        .on_submit({
+            let open = open.clone();
+            move |s, _query| {
+                let selected = s
+                    .call_on_name("picker_list", |view: &mut SelectView<String>| view.selection())
+                    .flatten();
+                if let Some(session_id) = selected {
+                    open(s, &session_id);
+                }
+            }
+        })
+        .fixed_width(40);
+    let input = OnEventView::new(input)
+        .on_event(Key::Up, move_selection(true))
+        .on_event(Key::Down, move_selection(false));
+
+    let list = SelectView::<String>::new()
+        .on_submit(move |s, session_id: &String| open(s, session_id))
+        .with_name("picker_list")
+        .scrollable()
+        .min_width(70)
+        .max_height(20);
+
+    let dialog = Dialog::around(
+        LinearLayout::vertical()
+            .child(input)
+            .child(TextView::new("").with_name("picker_count"))
+            .child(list),
+    )
+    .title("Sessions")
+    .button("Cancel", cancel.clone());
+
+    // Esc closes the picker instead of quitting
+    siv.add_layer(OnEventView::new(dialog).on_event(Key::Esc, cancel));
+    refresh(siv, &entries, query);
+}
+
+/// Open another session in place of the current one, keeping the view
+/// settings
+fn switch_session(siv: &mut Cursive, state: &Arc<Mutex<AppState>>, db: &TranscriptDb, session_id: &str) {
+    let loaded = db
+        .get_lines(&GetLinesOptions::for_session(session_id))
+        .and_then(|lines| Ok((lines, db.get_bookmarked_lines(session_id)?, db.get_annotations(session_id)?)));
+    {
+        let mut st = state.lock().unwrap();
+        match loaded {
+            Ok((lines, bookmarked, annotations)) => {
+                let mut next = AppState::new(lines, session_id.to_string());
+                next.view_mode = st.view_mode;
+                next.live_mode = st.live_mode;
+                next.fullscreen = st.fullscreen;
+                next.focused_pane = st.focused_pane;
+                next.bookmark_db = st.bookmark_db.take();
+                next.search_history = std::mem::take(&mut st.search_history);
+                next.bookmarks = bookmarked.iter().map(|l| l.id).collect();
+                next.set_annotations(&annotations);
+                if st.text_only {
+                    next.apply_text_only();
+                }
+                if st.filter_opts.display_types.is_some() {
+                    next.set_type_filter(st.filter_opts.display_types.clone());
+                }
+                next.status_message = Some(format!("Opened {}", next.title()));
+                *st = next;
+            }
+            Err(e) => st.status_message = Some(format!("Session not opened: {}", e)),
+        }
+    }
+    rebuild_list(siv, &state.lock().unwrap());
+    update_all(siv, &state.lock().unwrap());
+}
+
 fn poll_new_lines(state: &Arc<Mutex<AppState>>, db: &TranscriptDb, session_id: &str) -> usize {
-    let last_id = state.lock().unwrap().last_max_id;
+    let (last_id, mut cursor) = {
+        let st = state.lock().unwrap();
+        (st.last_max_id, st.last_change_id)
+    };
+    let changed = db.lines_changed(&mut cursor, Some(session_id)).unwrap_or(true);
+    state.lock().unwrap().last_change_id = cursor;
+    if !changed {
+        return 0;
+    }
 
     match db.get_lines_after_id(last_id, Some(session_id)) {
         Ok(new_lines) if !new_lines.is_empty() => {
@@ -2076,11 +2699,13 @@ fn poll_new_lines(state: &Arc<Mutex<AppState>>, db: &TranscriptDb, session_id: &
             // Check if we were at the end
             let was_at_end = st.current_index + 1 >= st.lines.len();
 
-            // Add new lines
-            st.lines.extend(new_lines);
+            // Add new lines (filters are per-line, so only the new ones need filtering)
+            let visible = apply_filter(&new_lines, &st.filter_opts);
+            st.all_lines.extend(new_lines);
+            st.lines.extend(visible);
 
             // Auto-scroll to end if we were at end
-            if was_at_end {
+            if was_at_end && !st.lines.is_empty() {
                 st.current_index = st.lines.len() - 1;
             }
 
@@ -2114,6 +2739,7 @@ fn refresh_turn_data(state: &Arc<Mutex<AppState>>, db: &TranscriptDb) -> bool {
             let mut st = state.lock().unwrap();
 
             // Build a map of id -> (turn_id, turn_sequence, session_name)
+            #[allow(clippy::type_complexity)]
             let turn_map: std::collections::HashMap<i64, (Option<String>, Option<i64>, Option<String>)> =
                 turn_data
                     .into_iter()
@@ -2241,3 +2867,88 @@ fn run_screenshot(state: &AppState) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(id: i64, uuid: &str) -> TranscriptLine {
+        TranscriptLine {
+            id,
+            line_number: id,
+            uuid: uuid.to_string(),
+            timestamp: "2024-01-01T10:00:00Z".to_string(),
+            content: Some("hello".to_string()),
+            ..TranscriptLine::for_test(transcript_core::LineType::User, "")
+        }
+    }
+
+    #[test]
+    fn test_annotated_lines_are_marked() {
+        let mut state = AppState::new(vec![line(1, "u1"), line(2, "u2")], "s1".to_string());
+        let note = Annotation {
+            id: 1,
+            session_id: "s1".to_string(),
+            uuid: "u2".to_string(),
+            line_number: 2,
+            parent_id: None,
+            author: "ann".to_string(),
+            body: "check this".to_string(),
+            created_at: String::new(),
+        };
+        state.set_annotations(&[note]);
+        assert!(!state.is_annotated(&state.lines[0]));
+        assert!(state.is_annotated(&state.lines[1]));
+
+        let marked = format_list_item_styled(&state.lines[1], false, true, false);
+        assert!(marked.source().starts_with("  ✎"), "{}", marked.source());
+        let plain = format_list_item_styled(&state.lines[0], false, false, false);
+        assert!(!plain.source().contains('✎'));
+    }
+
+    #[test]
+    fn test_incremental_search() {
+        let lines = ["build the index", "run tests", "index again", "done"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| TranscriptLine {
+                content: Some(text.to_string()),
+                ..line(i as i64 + 1, &format!("u{}", i + 1))
+            })
+            .collect();
+        let mut state = AppState::new(lines, "s1".to_string());
+
+        // Each keystroke re-runs the search and jumps from where it started
+        state.incremental_search("ind", 1);
+        assert_eq!(state.search_results, [0, 2]);
+        assert_eq!(state.current_index, 2);
+        assert_eq!(state.search_count_label(), "Match 2/2");
+
+        // Past the last match it wraps to the first
+        state.incremental_search("ind", 3);
+        assert_eq!(state.current_index, 0);
+
+        // No match stays at the origin
+        state.incremental_search("indexer", 1);
+        assert_eq!(state.current_index, 1);
+        assert_eq!(state.search_count_label(), "No matches");
+
+        state.incremental_search("", 1);
+        assert_eq!(state.status_message, None);
+    }
+
+    #[test]
+    fn test_search_history_recall() {
+        let mut state = AppState::new(vec![line(1, "u1")], "s1".to_string());
+        state.search_history = SearchHistory::in_memory();
+        assert_eq!(state.recall_search_history(true), None);
+
+        state.push_search_history("first");
+        state.push_search_history("second");
+        assert_eq!(state.recall_search_history(true).as_deref(), Some("second"));
+        assert_eq!(state.recall_search_history(true).as_deref(), Some("first"));
+        assert_eq!(state.recall_search_history(false).as_deref(), Some("second"));
+        // Down past the newest clears the input
+        assert_eq!(state.recall_search_history(false).as_deref(), Some(""));
+    }
+}
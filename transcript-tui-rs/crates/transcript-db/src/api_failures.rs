@@ -0,0 +1,50 @@
+//! API failures across sessions
+//!
+//! SQL narrows the lines to those that can record a failure (`api_error` and
+//! `rate_limit` system lines, API error messages, results mentioning an
+//! overload); `transcript_core::api_failure` decides and classifies.
+
+use transcript_core::{api_failure, ApiFailureKind, TranscriptLine};
+
+use crate::connection::{DbError, TranscriptDb};
+use crate::queries::row_to_transcript_line;
+
+/// A line that records a failed API request
+#[derive(Debug, Clone)]
+pub struct ApiFailure {
+    pub kind: ApiFailureKind,
+    pub line: TranscriptLine,
+}
+
+impl TranscriptDb {
+    /// API failures in time order, optionally for one session or the last N days
+    pub fn api_failures(&self, session_id: Option<&str>, days: Option<i64>) -> Result<Vec<ApiFailure>, DbError> {
+        let sql = r#"
+            SELECT
+                id, session_id, uuid, parent_uuid, line_number, type, subtype,
+                timestamp, slug, role, model, cwd, content, raw, file_path,
+                turn_id, turn_sequence, session_name
+            FROM lines
+            WHERE ((type = 'system' AND subtype IN ('api_error', 'rate_limit'))
+                   OR (type = 'assistant' AND raw LIKE '%isApiErrorMessage%')
+                   OR (type = 'user' AND raw LIKE '%overloaded%'))
+              AND (?1 IS NULL OR session_id = ?1)
+              AND (?2 IS NULL OR timestamp >= datetime('now', ?2 || ' days'))
+            ORDER BY timestamp, id
+        "#;
+        let mut stmt = self.conn.prepare(sql)?;
+        let days = days.map(|d| format!("-{}", d));
+        let rows = stmt.query_map(rusqlite::params![session_id, days], |row| {
+            Ok(row_to_transcript_line(row))
+        })?;
+
+        let mut failures = Vec::new();
+        for row in rows {
+            let line = row?;
+            if let Some(kind) = api_failure(&line) {
+                failures.push(ApiFailure { kind, line });
+            }
+        }
+        Ok(failures)
+    }
+}
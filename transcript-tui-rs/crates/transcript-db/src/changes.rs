@@ -0,0 +1,146 @@
+//! The indexer's change feed
+//!
+//! Every batch of lines the indexer writes adds a row naming the session and
+//! its newest line id. Readers keep the id of the last change they saw and
+//! ask this small table whether anything happened, instead of each polling
+//! `lines` on its own.
+
+use crate::connection::{DbError, TranscriptDb};
+
+/// A session gained lines, up to `max_line_id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub id: i64,
+    pub session_id: String,
+    pub max_line_id: i64,
+    pub changed_at: String,
+}
+
+impl TranscriptDb {
+    /// Whether the database has a change feed (indexed by v18 or later)
+    pub fn has_changes(&self) -> bool {
+        self.has_column("changes", "max_line_id")
+    }
+
+    /// Id of the newest change (0 when there are none)
+    pub fn latest_change_id(&self) -> Result<i64, DbError> {
+        if !self.has_changes() {
+            return Ok(0);
+        }
+        Ok(self
+            .conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM changes", [], |row| row.get(0))?)
+    }
+
+    /// Changes after `after_id`, oldest first, optionally for one session
+    pub fn changes_after(&self, after_id: i64, session_id: Option<&str>) -> Result<Vec<Change>, DbError> {
+        if !self.has_changes() {
+            return Ok(Vec::new());
+        }
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, max_line_id, changed_at FROM changes
+             WHERE id > ?1 AND (?2 IS NULL OR session_id = ?2)
+             ORDER BY id",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![after_id, session_id], |row| {
+            Ok(Change {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                max_line_id: row.get(2)?,
+                changed_at: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Whether a session (or any session) gained lines since `cursor`
+    ///
+    /// Moves `cursor` to the newest change. Without a change feed this is
+    /// always true, so callers fall back to querying `lines` every time.
+    pub fn lines_changed(&self, cursor: &mut i64, session_id: Option<&str>) -> Result<bool, DbError> {
+        if !self.has_changes() {
+            return Ok(true);
+        }
+        let latest = self.latest_change_id()?;
+        if latest <= *cursor {
+            // Also true after a database reset, when ids start again
+            let reset = latest < *cursor;
+            *cursor = latest;
+            return Ok(reset);
+        }
+        let changed = session_id.is_none() || !self.changes_after(*cursor, session_id)?.is_empty();
+        *cursor = latest;
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn change(conn: &Connection, session_id: &str, max_line_id: i64) {
+        conn.execute(
+            "INSERT INTO changes (session_id, max_line_id, changed_at) VALUES (?1, ?2, '')",
+            rusqlite::params![session_id, max_line_id],
+        )
+        .unwrap();
+    }
+
+    fn feed() -> (tempfile::TempDir, Connection, TranscriptDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcripts.db");
+        let mut writer = Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut writer).unwrap();
+        let db = TranscriptDb::open(&path).unwrap();
+        (dir, writer, db)
+    }
+
+    #[test]
+    fn test_changes_after() {
+        let (_dir, writer, db) = feed();
+        assert_eq!(db.latest_change_id().unwrap(), 0);
+        change(&writer, "s1", 10);
+        change(&writer, "s2", 20);
+        change(&writer, "s1", 30);
+
+        assert_eq!(db.latest_change_id().unwrap(), 3);
+        let all: Vec<i64> = db.changes_after(0, None).unwrap().iter().map(|c| c.max_line_id).collect();
+        assert_eq!(all, vec![10, 20, 30]);
+        let s1: Vec<i64> = db.changes_after(1, Some("s1")).unwrap().iter().map(|c| c.id).collect();
+        assert_eq!(s1, vec![3]);
+        assert!(db.changes_after(3, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lines_changed_moves_cursor() {
+        let (_dir, writer, db) = feed();
+        let mut cursor = 0;
+        assert!(!db.lines_changed(&mut cursor, None).unwrap());
+
+        change(&writer, "s1", 10);
+        assert!(db.lines_changed(&mut cursor, None).unwrap());
+        assert_eq!(cursor, 1);
+        assert!(!db.lines_changed(&mut cursor, None).unwrap());
+
+        // Another session's lines move the cursor without counting as a change
+        change(&writer, "s2", 20);
+        assert!(!db.lines_changed(&mut cursor, Some("s1")).unwrap());
+        assert_eq!(cursor, 2);
+        change(&writer, "s1", 30);
+        assert!(db.lines_changed(&mut cursor, Some("s1")).unwrap());
+        assert_eq!(cursor, 3);
+    }
+
+    #[test]
+    fn test_lines_changed_after_reset() {
+        let (_dir, writer, db) = feed();
+        change(&writer, "s1", 10);
+
+        // A cursor past the newest id means the database was rebuilt
+        let mut cursor = 99;
+        assert!(db.lines_changed(&mut cursor, Some("s1")).unwrap());
+        assert_eq!(cursor, 1);
+        assert!(!db.lines_changed(&mut cursor, Some("s1")).unwrap());
+    }
+}
@@ -1,8 +1,10 @@
 //! Database connection management
 
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::{Connection, ErrorCode, OpenFlags};
 use std::path::{Path, PathBuf};
+use std::sync::Once;
 use thiserror::Error;
+use transcript_core::{database_key, key_pragma, KeyError, DB_KEY_ENV};
 
 /// Database errors
 #[derive(Error, Debug)]
@@ -18,29 +20,131 @@ pub enum DbError {
 
     #[error("Database version mismatch: expected {expected}, found {found}")]
     VersionMismatch { expected: i32, found: i32 },
+
+    #[error("Database schema v{found} is newer than this reader (v{supported}) and needs a reader of v{required} or later; upgrade transcript to open it")]
+    SchemaTooNew { found: i32, supported: i32, required: i32 },
+
+    #[error("Database has no {table}.{column} column (run: transcript index build)")]
+    MissingColumn { table: String, column: String },
+
+    #[error("Cannot attach '{origin}': {reason}")]
+    Attach { origin: String, reason: String },
+
+    #[error("Cannot read {}: it is encrypted and the key is missing or wrong (set {}), or it is not a database", .0.display(), DB_KEY_ENV)]
+    Locked(PathBuf),
+
+    #[error("A database key is set but encryption is not built in (enable the encryption feature)")]
+    EncryptionUnavailable,
+
+    #[error("{0}")]
+    Key(#[from] KeyError),
 }
 
-/// Expected database version
+/// Oldest database schema this reader can open
 pub const DB_VERSION: i32 = 8;
 
+/// Newest database schema this reader was written for (the indexer's
+/// `DB_VERSION` at the time)
+///
+/// A newer database is opened in compatibility mode when its writer declares,
+/// as `reader_compat_version`, that readers of this schema can still read it:
+/// migrations that only add tables and columns keep that promise, and the
+/// `has_column` checks cover the rest. Otherwise it is refused.
+pub const MAX_DB_VERSION: i32 = 28;
+
+/// Version of this reader, compared against the `indexer_version` metadata
+pub const READER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Whether two versions differ in a way that can change behavior
+///
+/// Follows semver compatibility: the major version must match, and for 0.x
+/// releases the minor version too. Unparseable versions never count as skew.
+pub fn is_version_skew(writer: &str, reader: &str) -> bool {
+    fn major_minor(version: &str) -> Option<(u64, u64)> {
+        let mut parts = version.trim().trim_start_matches('v').split('.');
+        Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+    }
+    match (major_minor(writer), major_minor(reader)) {
+        (Some((w_major, w_minor)), Some((r_major, r_minor))) => {
+            w_major != r_major || (w_major == 0 && w_minor != r_minor)
+        }
+        _ => false,
+    }
+}
+
+static SKEW_WARNING: Once = Once::new();
+static COMPAT_WARNING: Once = Once::new();
+
+/// How a reader handles a database of schema `found`
+///
+/// `compat` is the database's `reader_compat_version` (absent before it was
+/// recorded, when every schema change was additive). Returns the schema
+/// version when the database is only readable in compatibility mode.
+pub fn schema_compatibility(found: Option<i32>, compat: Option<i32>) -> Result<Option<i32>, DbError> {
+    match found {
+        None => Err(DbError::NotInitialized),
+        Some(v) if v < DB_VERSION => Err(DbError::VersionMismatch {
+            expected: DB_VERSION,
+            found: v,
+        }),
+        Some(v) if v <= MAX_DB_VERSION => Ok(None),
+        Some(v) => match compat {
+            Some(required) if required > MAX_DB_VERSION => Err(DbError::SchemaTooNew {
+                found: v,
+                supported: MAX_DB_VERSION,
+                required,
+            }),
+            _ => Ok(Some(v)),
+        },
+    }
+}
+
 /// Default database path
 pub fn default_db_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
-    PathBuf::from(home)
-        .join(".claude-code-sdk")
-        .join("transcripts.db")
+    transcript_core::sdk_dir().join("transcripts.db")
+}
+
+/// Archive database of the database at `db_path` (see `transcript archive`):
+/// `archive.db` beside the default `transcripts.db`, else `<name>.archive.db`
+pub fn archive_db_path(db_path: &Path) -> PathBuf {
+    let name = match db_path.file_stem().and_then(|s| s.to_str()) {
+        Some("transcripts") | None => "archive.db".to_string(),
+        Some(stem) => format!("{}.archive.db", stem),
+    };
+    db_path.with_file_name(name)
+}
+
+/// Unlock `conn` with the configured database key, if any (see
+/// `transcript_core::encryption`), and check that it can be read
+pub fn unlock(conn: &Connection, path: &Path) -> Result<(), DbError> {
+    if let Some(key) = database_key()? {
+        if !cfg!(feature = "encryption") {
+            return Err(DbError::EncryptionUnavailable);
+        }
+        conn.execute_batch(&key_pragma(&key))?;
+    }
+    match conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(())) {
+        Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == ErrorCode::NotADatabase => {
+            Err(DbError::Locked(path.to_path_buf()))
+        }
+        result => Ok(result?),
+    }
 }
 
 /// Database connection wrapper
 pub struct TranscriptDb {
     pub(crate) conn: Connection,
     path: PathBuf,
+    /// Attached read-only databases as (origin, path), see `federation`
+    pub(crate) attached: Vec<(String, PathBuf)>,
+    /// Schema version of a database newer than this reader, see `MAX_DB_VERSION`
+    compat_schema: Option<i32>,
 }
 
 impl TranscriptDb {
     /// Open the database at the default path
     pub fn open_default() -> Result<Self, DbError> {
-        Self::open(&default_db_path())
+        Self::open(default_db_path())
     }
 
     /// Open the database at a specific path
@@ -52,37 +156,78 @@ impl TranscriptDb {
         }
 
         // Open read-only with URI mode for better compatibility
-        let conn = Connection::open_with_flags(
+        let mut conn = Connection::open_with_flags(
             &path,
             OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )?;
+        unlock(&conn, &path)?;
+        crate::timing::instrument(&mut conn);
 
         // Check version
-        let db = Self { conn, path };
-        db.check_version()?;
+        let mut db = Self {
+            conn,
+            path,
+            attached: Vec::new(),
+            compat_schema: None,
+        };
+        db.compat_schema = db.check_version()?;
+
+        if let Some(found) = db.compat_schema {
+            COMPAT_WARNING.call_once(|| {
+                eprintln!(
+                    "Warning: database schema v{} is newer than this reader (v{}); reading in compatibility mode, newer data is not shown (upgrade transcript)",
+                    found, MAX_DB_VERSION
+                );
+            });
+        }
+
+        if let Some(writer) = db.version_skew() {
+            SKEW_WARNING.call_once(|| {
+                eprintln!(
+                    "Warning: database was last written by indexer v{} but this is v{}; results may differ (run: transcript index update)",
+                    writer, READER_VERSION
+                );
+            });
+        }
 
         Ok(db)
     }
 
-    /// Check database version
-    fn check_version(&self) -> Result<(), DbError> {
-        let version: Option<i32> = self
-            .conn
+    /// Version of the indexer that last wrote the database (None before it was recorded)
+    pub fn writer_version(&self) -> Option<String> {
+        self.conn
             .query_row(
-                "SELECT CAST(value AS INTEGER) FROM metadata WHERE key = 'version'",
+                "SELECT value FROM metadata WHERE key = 'indexer_version'",
                 [],
                 |row| row.get(0),
             )
-            .ok();
+            .ok()
+    }
 
-        match version {
-            None => Err(DbError::NotInitialized),
-            Some(v) if v < DB_VERSION => Err(DbError::VersionMismatch {
-                expected: DB_VERSION,
-                found: v,
-            }),
-            Some(_) => Ok(()),
-        }
+    /// The writer's version, if it differs significantly from this reader's
+    pub fn version_skew(&self) -> Option<String> {
+        self.writer_version()
+            .filter(|writer| is_version_skew(writer, READER_VERSION))
+    }
+
+    /// Schema version of a database opened in compatibility mode (newer
+    /// than this reader), if it was
+    pub fn compatibility_mode(&self) -> Option<i32> {
+        self.compat_schema
+    }
+
+    /// Check database version
+    fn check_version(&self) -> Result<Option<i32>, DbError> {
+        let metadata = |key: &str| -> Option<i32> {
+            self.conn
+                .query_row(
+                    "SELECT CAST(value AS INTEGER) FROM metadata WHERE key = ?1",
+                    [key],
+                    |row| row.get(0),
+                )
+                .ok()
+        };
+        schema_compatibility(metadata("version"), metadata("reader_compat_version"))
     }
 
     /// Get the database path
@@ -95,6 +240,29 @@ impl TranscriptDb {
         &self.conn
     }
 
+    /// Whether a table has a column (newer columns may be absent in older databases)
+    pub fn has_column(&self, table: &str, column: &str) -> bool {
+        self.conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+                [table, column],
+                |row| row.get(0),
+            )
+            .unwrap_or(false)
+    }
+
+    /// Fail with `MissingColumn` unless the column exists
+    pub(crate) fn require_column(&self, table: &str, column: &str) -> Result<(), DbError> {
+        if self.has_column(table, column) {
+            Ok(())
+        } else {
+            Err(DbError::MissingColumn {
+                table: table.to_string(),
+                column: column.to_string(),
+            })
+        }
+    }
+
     /// Get database statistics
     pub fn stats(&self) -> Result<DbStats, DbError> {
         let version: i32 = self
@@ -182,4 +350,82 @@ mod tests {
         assert!(path.to_string_lossy().contains(".claude-code-sdk"));
         assert!(path.to_string_lossy().ends_with("transcripts.db"));
     }
+
+    #[test]
+    fn test_schema_compatibility() {
+        assert!(matches!(schema_compatibility(None, None), Err(DbError::NotInitialized)));
+        // Older than any reader of this version handles
+        assert!(matches!(
+            schema_compatibility(Some(DB_VERSION - 1), None),
+            Err(DbError::VersionMismatch { .. })
+        ));
+        assert_eq!(schema_compatibility(Some(DB_VERSION), None).unwrap(), None);
+        assert_eq!(schema_compatibility(Some(MAX_DB_VERSION), Some(DB_VERSION)).unwrap(), None);
+
+        // Newer, but readable by this schema's readers
+        let newer = MAX_DB_VERSION + 3;
+        assert_eq!(schema_compatibility(Some(newer), Some(DB_VERSION)).unwrap(), Some(newer));
+        assert_eq!(schema_compatibility(Some(newer), None).unwrap(), Some(newer));
+        // Newer, and changed in a way this reader can't follow
+        assert!(matches!(
+            schema_compatibility(Some(newer), Some(newer)),
+            Err(DbError::SchemaTooNew { found, supported: MAX_DB_VERSION, required }) if found == newer && required == newer
+        ));
+    }
+
+    #[test]
+    fn test_max_version_tracks_indexer() {
+        // A database this workspace's indexer writes opens without compatibility mode
+        assert_eq!(MAX_DB_VERSION, transcript_indexer::DB_VERSION);
+    }
+
+    #[test]
+    fn test_open_newer_database() {
+        let dir = std::env::temp_dir().join(format!("transcript-db-compat-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("transcripts.db");
+        let write = |version: i32, compat: i32| {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(&format!(
+                "CREATE TABLE IF NOT EXISTS metadata (key TEXT PRIMARY KEY, value TEXT);
+                 INSERT OR REPLACE INTO metadata VALUES ('version', '{}'), ('reader_compat_version', '{}');",
+                version, compat
+            ))
+            .unwrap();
+        };
+
+        write(MAX_DB_VERSION, DB_VERSION);
+        assert_eq!(TranscriptDb::open(&path).unwrap().compatibility_mode(), None);
+
+        write(MAX_DB_VERSION + 1, DB_VERSION);
+        let db = TranscriptDb::open(&path).unwrap();
+        assert_eq!(db.compatibility_mode(), Some(MAX_DB_VERSION + 1));
+        drop(db);
+
+        write(MAX_DB_VERSION + 1, MAX_DB_VERSION + 1);
+        let err = TranscriptDb::open(&path).err().unwrap();
+        assert!(err.to_string().contains("upgrade transcript"), "{}", err);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_open_unreadable_file() {
+        // What an encrypted database looks like without its key
+        let dir = std::env::temp_dir().join(format!("transcript-db-locked-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("transcripts.db");
+        std::fs::write(&path, [0x5au8; 4096]).unwrap();
+        assert!(matches!(TranscriptDb::open(&path), Err(DbError::Locked(p)) if p == path));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_version_skew() {
+        assert!(!is_version_skew("0.1.0", "0.1.3"));
+        assert!(is_version_skew("0.1.0", "0.2.0"));
+        assert!(!is_version_skew("1.2.0", "1.5.1"));
+        assert!(is_version_skew("1.9.0", "2.0.0"));
+        assert!(!is_version_skew("dev", "0.1.0"));
+    }
 }
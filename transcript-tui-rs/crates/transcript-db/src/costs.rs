@@ -0,0 +1,243 @@
+//! Estimated cost breakdowns from assistant token usage
+//!
+//! Prices come from `transcript_core::MODEL_PRICES`, so these totals match the
+//! per-turn roll-ups in `transcript_core::cost` and the `v_costs` view. Usage
+//! counts once per API response, not once per line (see
+//! `transcript_core::response_usage`).
+
+use rusqlite::types::ToSql;
+use transcript_core::{price_case_sql, CACHE_READ_MULTIPLIER, CACHE_WRITE_MULTIPLIER, RESPONSE_KEY_SQL};
+
+use crate::connection::{DbError, TranscriptDb};
+
+/// Dimension to attribute cost to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostGroupBy {
+    /// Repository the session ran in (its working directory outside one)
+    Project,
+    /// Project and git branch
+    Branch,
+    /// Model name
+    Model,
+    /// Individual session
+    Session,
+    /// Calendar day (UTC)
+    Day,
+    /// Account the transcripts were indexed from (multi-home)
+    User,
+}
+
+impl CostGroupBy {
+    /// Names of the key columns for this grouping
+    pub fn key_columns(self) -> &'static [&'static str] {
+        match self {
+            CostGroupBy::Project => &["project"],
+            CostGroupBy::Branch => &["project", "branch"],
+            CostGroupBy::Model => &["model"],
+            CostGroupBy::Session => &["session_id"],
+            CostGroupBy::Day => &["day"],
+            CostGroupBy::User => &["user"],
+        }
+    }
+}
+
+/// Aggregated usage and estimated cost for one group
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostRow {
+    /// Values of the grouping key columns
+    pub keys: Vec<String>,
+    pub sessions: i64,
+    pub messages: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_creation_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cost_usd: f64,
+}
+
+impl CostRow {
+    /// Cached tokens read back per token written to the cache
+    ///
+    /// `None` when nothing was written (no caching, or only reads of a cache
+    /// written outside the group).
+    pub fn cache_reuse(&self) -> Option<f64> {
+        (self.cache_creation_tokens > 0)
+            .then(|| self.cache_read_tokens as f64 / self.cache_creation_tokens as f64)
+    }
+
+    /// Share of prompt tokens served from the cache, in percent
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let prompt = self.input_tokens + self.cache_creation_tokens + self.cache_read_tokens;
+        (prompt > 0).then(|| self.cache_read_tokens as f64 / prompt as f64 * 100.0)
+    }
+}
+
+impl TranscriptDb {
+    /// Estimated cost grouped by `group_by`, optionally limited to one session
+    /// and to the last N days
+    pub fn cost_breakdown(
+        &self,
+        group_by: CostGroupBy,
+        session_id: Option<&str>,
+        recent_days: Option<i64>,
+    ) -> Result<Vec<CostRow>, DbError> {
+        let has_user = self.has_column("lines", "user");
+        if group_by == CostGroupBy::User && !has_user {
+            self.require_column("lines", "user")?;
+        }
+
+        let keys = group_by.key_columns().join(", ");
+        let user_expr = if has_user {
+            "COALESCE(user, '(unknown)')"
+        } else {
+            "'(unknown)'"
+        };
+        let project_path = self.project_path_expr("");
+        let mut filters = String::new();
+        if session_id.is_some() {
+            filters.push_str(" AND session_id = ?");
+        }
+        if recent_days.is_some() {
+            filters.push_str(" AND timestamp >= datetime('now', ? || ' days')");
+        }
+        let price_in = price_case_sql(|p| p.input);
+        let price_out = price_case_sql(|p| p.output);
+
+        let sql = format!(
+            r#"
+            SELECT {keys},
+                   COUNT(DISTINCT session_id),
+                   COUNT(*),
+                   SUM(input_tokens),
+                   SUM(output_tokens),
+                   SUM(cache_creation_tokens),
+                   SUM(cache_read_tokens),
+                   SUM((input_tokens * price_in
+                        + output_tokens * price_out
+                        + cache_creation_tokens * price_in * {CACHE_WRITE_MULTIPLIER:?}
+                        + cache_read_tokens * price_in * {CACHE_READ_MULTIPLIER:?}) / 1000000.0) AS cost
+            FROM (
+                SELECT
+                    session_id,
+                    model,
+                    COALESCE({project_path}, '(unknown)') AS project,
+                    COALESCE(git_branch, json_extract(raw, '$.gitBranch'), '(none)') AS branch,
+                    substr(timestamp, 1, 10) AS day,
+                    {user_expr} AS user,
+                    COALESCE(json_extract(raw, '$.message.usage.input_tokens'), 0) AS input_tokens,
+                    COALESCE(json_extract(raw, '$.message.usage.output_tokens'), 0) AS output_tokens,
+                    COALESCE(json_extract(raw, '$.message.usage.cache_creation_input_tokens'), 0) AS cache_creation_tokens,
+                    COALESCE(json_extract(raw, '$.message.usage.cache_read_input_tokens'), 0) AS cache_read_tokens,
+                    {price_in} AS price_in,
+                    {price_out} AS price_out,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY session_id, {RESPONSE_KEY_SQL} ORDER BY line_number DESC
+                    ) AS copy
+                FROM lines
+                WHERE type = 'assistant' AND model IS NOT NULL{filters}
+            )
+            WHERE copy = 1
+            GROUP BY {keys}
+            ORDER BY cost DESC
+            "#
+        );
+
+        let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+        if let Some(session_id) = session_id {
+            params.push(Box::new(session_id.to_string()));
+        }
+        if let Some(days) = recent_days {
+            params.push(Box::new(format!("-{}", days)));
+        }
+        let param_refs: Vec<&dyn ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let key_count = group_by.key_columns().len();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(param_refs.as_slice(), |row| {
+            let mut keys = Vec::with_capacity(key_count);
+            for i in 0..key_count {
+                keys.push(row.get::<_, Option<String>>(i)?.unwrap_or_default());
+            }
+            Ok(CostRow {
+                keys,
+                sessions: row.get(key_count)?,
+                messages: row.get(key_count + 1)?,
+                input_tokens: row.get(key_count + 2)?,
+                output_tokens: row.get(key_count + 3)?,
+                cache_creation_tokens: row.get(key_count + 4)?,
+                cache_read_tokens: row.get(key_count + 5)?,
+                cost_usd: row.get::<_, Option<f64>>(key_count + 6)?.unwrap_or(0.0),
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn costs_db() -> (tempfile::TempDir, TranscriptDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcripts.db");
+        let mut conn = rusqlite::Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut conn).unwrap();
+        let lines = [
+            ("s1", "assistant", "/app/web", None, Some("main"), "claude-sonnet-4", r#"{"message":{"usage":{"input_tokens":1000000}}}"#),
+            ("s1", "user", "/app/web", None, Some("main"), "claude-sonnet-4", r#"{"message":{"usage":{"input_tokens":1000000}}}"#),
+            ("s2", "assistant", "/app/web/src", Some("/app/web"), Some("feature"), "claude-sonnet-4", r#"{"message":{"usage":{"output_tokens":100000}}}"#),
+            ("s3", "assistant", "/srv/api", None, None, "claude-opus-4-1", r#"{"gitBranch":"dev","message":{"usage":{"input_tokens":400000}}}"#),
+            // One response written as two lines, each repeating its usage
+            ("s4", "assistant", "/srv/cli", None, None, "claude-sonnet-4", r#"{"requestId":"r1","message":{"id":"m1","usage":{"input_tokens":100000}}}"#),
+            ("s4", "assistant", "/srv/cli", None, None, "claude-sonnet-4", r#"{"requestId":"r1","message":{"id":"m1","usage":{"input_tokens":100000}}}"#),
+        ];
+        for (i, (session_id, kind, cwd, repo_root, branch, model, raw)) in lines.into_iter().enumerate() {
+            conn.execute(
+                "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, cwd, repo_root, git_branch, model, raw, file_path)
+                 VALUES (?1, ?2, ?2, ?3, '2026-01-01T10:00:00Z', ?4, ?5, ?6, ?7, ?8, 'f.jsonl')",
+                rusqlite::params![session_id, i as i64, kind, cwd, repo_root, branch, model, raw],
+            )
+            .unwrap();
+        }
+        drop(conn);
+        let db = TranscriptDb::open(&path).unwrap();
+        (dir, db)
+    }
+
+    #[test]
+    fn test_cost_by_project() {
+        let (_dir, db) = costs_db();
+        let rows = db.cost_breakdown(CostGroupBy::Project, None, None).unwrap();
+        let summary: Vec<_> = rows.iter().map(|r| (r.keys[0].as_str(), r.sessions, r.cost_usd)).collect();
+        // Sessions in a subdirectory count towards their repository
+        assert_eq!(summary, [("/srv/api", 1, 6.0), ("/app/web", 2, 4.5), ("/srv/cli", 1, 0.3)]);
+        assert_eq!(rows[1].messages, 2);
+    }
+
+    #[test]
+    fn test_cost_counts_each_response_once() {
+        let (_dir, db) = costs_db();
+        let rows = db.cost_breakdown(CostGroupBy::Session, Some("s4"), None).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].messages, 1);
+        assert_eq!(rows[0].input_tokens, 100_000);
+    }
+
+    #[test]
+    fn test_cost_by_branch() {
+        let (_dir, db) = costs_db();
+        let rows = db.cost_breakdown(CostGroupBy::Branch, None, None).unwrap();
+        let keys: Vec<_> = rows.iter().map(|r| r.keys.join("@")).collect();
+        // The branch falls back to the one recorded in the raw line
+        assert_eq!(keys, ["/srv/api@dev", "/app/web@main", "/app/web@feature", "/srv/cli@(none)"]);
+
+        let rows = db.cost_breakdown(CostGroupBy::Branch, Some("s2"), None).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].output_tokens, 100_000);
+    }
+}
@@ -0,0 +1,235 @@
+//! Query plans of the library's statements (`transcript db explain`)
+//!
+//! `CANNED_QUERIES` are the statements behind the hot paths (search, session
+//! lookup, loading and following a session), written as the methods build
+//! them for their most common options. Their plans under the database's
+//! current indexes show where a slow command spends its time: a `SCAN` of
+//! `lines` reads every row of the biggest table.
+
+use rusqlite::Connection;
+
+use crate::connection::{DbError, TranscriptDb};
+
+/// A statement the library runs, by name
+#[derive(Debug, Clone, Copy)]
+pub struct CannedQuery {
+    pub name: &'static str,
+    /// The method that runs it, and for what
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// The library's hot statements (keep in step with the methods they name)
+pub const CANNED_QUERIES: &[CannedQuery] = &[
+    CannedQuery {
+        name: "search",
+        description: "search_lines: full-text search across sessions",
+        sql: "SELECT l.id, l.session_id, l.uuid, l.parent_uuid, l.line_number,
+                     l.type, l.subtype, l.timestamp, l.slug, l.role, l.model,
+                     l.cwd, l.content, l.raw, l.file_path,
+                     l.turn_id, l.turn_sequence, l.session_name
+              FROM lines_fts fts
+              JOIN lines l ON fts.rowid = l.id
+              WHERE lines_fts MATCH ?1
+                AND (?2 IS NULL OR l.timestamp >= ?2)
+              ORDER BY bm25(lines_fts)
+              LIMIT ?3",
+    },
+    CannedQuery {
+        name: "search-session",
+        description: "search_lines: full-text search in one session",
+        sql: "SELECT l.id, l.session_id, l.uuid, l.parent_uuid, l.line_number,
+                     l.type, l.subtype, l.timestamp, l.slug, l.role, l.model,
+                     l.cwd, l.content, l.raw, l.file_path,
+                     l.turn_id, l.turn_sequence, l.session_name
+              FROM lines_fts fts
+              JOIN lines l ON fts.rowid = l.id
+              WHERE lines_fts MATCH ? AND l.session_id = ?
+                AND (?3 IS NULL OR l.timestamp >= ?3)
+              ORDER BY bm25(lines_fts)
+              LIMIT ?4",
+    },
+    CannedQuery {
+        name: "search-tag",
+        description: "search_lines_with_tag: full-text search in tagged sessions",
+        sql: "SELECT l.id, l.session_id, l.line_number, l.content
+              FROM lines_fts fts
+              JOIN lines l ON fts.rowid = l.id
+              WHERE lines_fts MATCH ?1
+                AND l.session_id IN (SELECT session_id FROM tags WHERE tag = ?2)
+                AND (?3 IS NULL OR l.timestamp >= ?3)
+              ORDER BY bm25(lines_fts)
+              LIMIT ?4",
+    },
+    CannedQuery {
+        name: "sessions",
+        description: "get_sessions: every session, most recent first (list, picker)",
+        sql: "SELECT session_id, slug, file_path, line_count, first_timestamp, last_timestamp, indexed_at
+              FROM sessions
+              ORDER BY last_timestamp DESC",
+    },
+    CannedQuery {
+        name: "session",
+        description: "get_session: a session by ID or slug",
+        sql: "SELECT session_id, slug, file_path, line_count, first_timestamp, last_timestamp, indexed_at
+              FROM sessions
+              WHERE session_id = ? OR slug = ?
+              LIMIT 1",
+    },
+    CannedQuery {
+        name: "session-by-name",
+        description: "resolve_session: a session by its name",
+        sql: "SELECT DISTINCT s.session_id, s.slug, s.file_path, s.line_count,
+                     s.first_timestamp, s.last_timestamp, s.indexed_at
+              FROM sessions s
+              JOIN lines l ON s.session_id = l.session_id
+              WHERE l.session_name = ?
+              LIMIT 1",
+    },
+    CannedQuery {
+        name: "lines",
+        description: "get_lines: a session's lines in order (view, export, TUI)",
+        sql: "SELECT id, session_id, uuid, parent_uuid, line_number, type, subtype,
+                     timestamp, slug, role, model, cwd, content, raw, file_path,
+                     turn_id, turn_sequence, session_name
+              FROM lines
+              WHERE 1=1 AND session_id = ?
+              ORDER BY line_number ASC",
+    },
+    CannedQuery {
+        name: "lines-after",
+        description: "get_lines_after_id: new lines of a followed session (--follow, live mode)",
+        sql: "SELECT id, session_id, uuid, parent_uuid, line_number, type, subtype,
+                     timestamp, slug, role, model, cwd, content, raw, file_path,
+                     turn_id, turn_sequence, session_name
+              FROM lines
+              WHERE id > ? AND session_id = ?
+              ORDER BY id ASC",
+    },
+    CannedQuery {
+        name: "hook-events",
+        description: "get_hook_events: a session's hook events in order",
+        sql: "SELECT id, session_id, timestamp, event_type, tool_use_id, tool_name,
+                     decision, handler_results, input_json, context_json,
+                     file_path, line_number, turn_id, turn_sequence, session_name,
+                     git_hash, git_branch, git_dirty
+              FROM hook_events
+              WHERE 1=1 AND session_id = ?
+              ORDER BY timestamp ASC, id ASC",
+    },
+    CannedQuery {
+        name: "tool-lines",
+        description: "get_tool_lines: lines with tool calls or results (tools, payloads)",
+        sql: "SELECT id, session_id, line_number, raw
+              FROM lines
+              WHERE type IN ('assistant', 'user')
+                AND (raw LIKE '%\"tool_use\"%' OR raw LIKE '%\"tool_result\"%')
+                AND session_id = ?
+              ORDER BY session_id, line_number",
+    },
+];
+
+/// The canned query called `name`
+pub fn canned_query(name: &str) -> Option<&'static CannedQuery> {
+    CANNED_QUERIES.iter().find(|q| q.name == name)
+}
+
+/// One step of a query plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanStep {
+    pub id: i64,
+    /// The step this one is part of (0 at the top)
+    pub parent: i64,
+    /// SQLite's description, e.g. `SEARCH lines USING INDEX idx_session_id (session_id=?)`
+    pub detail: String,
+}
+
+impl PlanStep {
+    /// Whether the step reads a whole table row by row
+    ///
+    /// Full-text and other virtual tables, scans along an index and constant
+    /// rows don't count.
+    pub fn is_table_scan(&self) -> bool {
+        let Some(rest) = self.detail.strip_prefix("SCAN ") else {
+            return false;
+        };
+        !(rest.contains("VIRTUAL TABLE")
+            || rest.contains("USING INDEX")
+            || rest.contains("USING COVERING INDEX")
+            || rest.contains("USING INTEGER PRIMARY KEY")
+            || rest.starts_with("CONSTANT ROW")
+            || rest.starts_with('('))
+    }
+
+    /// Depth below the top of the plan, for indenting
+    pub fn depth(&self, plan: &[PlanStep]) -> usize {
+        let mut depth = 0;
+        let mut parent = self.parent;
+        while parent != 0 {
+            depth += 1;
+            parent = plan.iter().find(|s| s.id == parent).map_or(0, |s| s.parent);
+        }
+        depth
+    }
+}
+
+impl TranscriptDb {
+    /// SQLite's plan for `sql` under the current indexes, without running it
+    pub fn explain(&self, sql: &str) -> Result<Vec<PlanStep>, DbError> {
+        query_plan(&self.conn, sql)
+    }
+}
+
+fn query_plan(conn: &Connection, sql: &str) -> Result<Vec<PlanStep>, DbError> {
+    let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", sql.trim().trim_end_matches(';')))?;
+    // Parameters stay unbound (NULL): the plan doesn't depend on their values
+    let mut rows = stmt.raw_query();
+    let mut plan = Vec::new();
+    while let Some(row) = rows.next()? {
+        plan.push(PlanStep {
+            id: row.get(0)?,
+            parent: row.get(1)?,
+            detail: row.get(3)?,
+        });
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(detail: &str) -> PlanStep {
+        PlanStep {
+            id: 1,
+            parent: 0,
+            detail: detail.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_table_scans_are_flagged() {
+        assert!(step("SCAN lines").is_table_scan());
+        assert!(step("SCAN TABLE lines").is_table_scan());
+        assert!(!step("SCAN lines USING INDEX idx_timestamp").is_table_scan());
+        assert!(!step("SCAN lines_fts VIRTUAL TABLE INDEX 0:M1").is_table_scan());
+        assert!(!step("SEARCH lines USING INDEX idx_session_id (session_id=?)").is_table_scan());
+        assert!(!step("USE TEMP B-TREE FOR ORDER BY").is_table_scan());
+    }
+
+    #[test]
+    fn test_plan_follows_indexes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE lines (id INTEGER PRIMARY KEY, session_id TEXT, content TEXT)")
+            .unwrap();
+        let sql = "SELECT content FROM lines WHERE session_id = ?";
+
+        let plan = query_plan(&conn, sql).unwrap();
+        assert!(plan.iter().any(PlanStep::is_table_scan));
+
+        conn.execute_batch("CREATE INDEX idx_session_id ON lines(session_id)").unwrap();
+        let plan = query_plan(&conn, sql).unwrap();
+        assert!(!plan.iter().any(PlanStep::is_table_scan));
+        assert!(plan[0].detail.contains("idx_session_id"));
+    }
+}
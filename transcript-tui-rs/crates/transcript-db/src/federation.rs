@@ -0,0 +1,270 @@
+//! Team mode: read-only federation across attached databases
+//!
+//! Teammates' snapshots (see `transcript sync pull --federate`) are attached
+//! to the local connection under generated schema names and queried side by
+//! side. Every federated result carries an `origin`: `local` for this machine,
+//! otherwise the snapshot's file stem (e.g. `laptop-alice`).
+
+use std::path::{Path, PathBuf};
+
+use transcript_core::{SessionInfo, TranscriptLine};
+
+use crate::connection::{archive_db_path, schema_compatibility, DbError, TranscriptDb, DB_VERSION, MAX_DB_VERSION};
+use crate::queries::row_to_transcript_line;
+use crate::sessions::row_to_session_info;
+
+/// Origin name of the local database
+pub const LOCAL_ORIGIN: &str = "local";
+
+/// Origin name of the archive database
+pub const ARCHIVE_ORIGIN: &str = "archive";
+
+/// SQLite's default limit on attached databases
+const MAX_ATTACHED: usize = 10;
+
+/// Directory holding teammates' snapshots for federated queries
+pub fn default_team_dir() -> PathBuf {
+    transcript_core::sdk_dir().join("team")
+}
+
+/// A search hit tagged with the database it came from
+#[derive(Debug, Clone)]
+pub struct FederatedLine {
+    pub origin: String,
+    pub line: TranscriptLine,
+}
+
+/// A session tagged with the database it came from
+#[derive(Debug, Clone)]
+pub struct FederatedSession {
+    pub origin: String,
+    pub session: SessionInfo,
+}
+
+impl TranscriptDb {
+    /// Attach another transcript database (read-only) under an origin name
+    pub fn attach<P: AsRef<Path>>(&mut self, origin: &str, path: P) -> Result<(), DbError> {
+        let path = path.as_ref().to_path_buf();
+        let fail = |reason: String| DbError::Attach {
+            origin: origin.to_string(),
+            reason,
+        };
+
+        if !path.exists() {
+            return Err(DbError::NotFound(path));
+        }
+        if origin == LOCAL_ORIGIN || self.attached.iter().any(|(o, _)| o == origin) {
+            return Err(fail("origin name already in use".to_string()));
+        }
+        if self.attached.len() >= MAX_ATTACHED {
+            return Err(fail(format!("at most {} databases can be attached", MAX_ATTACHED)));
+        }
+
+        let schema = format!("fed{}", self.attached.len());
+        // With the database key, as every database the indexer writes has it
+        let key = transcript_core::database_key()?.unwrap_or_default();
+        self.conn.execute(
+            &format!("ATTACH DATABASE ?1 AS {} KEY ?2", schema),
+            [&*path.to_string_lossy(), &key],
+        )?;
+
+        let metadata = |key: &str| -> Option<i32> {
+            self.conn
+                .query_row(
+                    &format!("SELECT CAST(value AS INTEGER) FROM {}.metadata WHERE key = ?1", schema),
+                    [key],
+                    |row| row.get(0),
+                )
+                .ok()
+        };
+        let version = metadata("version");
+        let compat = metadata("reader_compat_version");
+        let problem = match schema_compatibility(version, compat) {
+            Ok(_) => None,
+            Err(DbError::SchemaTooNew { found, .. }) => Some(format!(
+                "schema v{} is newer than this reader (v{}); upgrade transcript",
+                found, MAX_DB_VERSION
+            )),
+            Err(_) => Some(format!("not a transcript database of version {} or later", DB_VERSION)),
+        };
+        if let Some(problem) = problem {
+            let _ = self.conn.execute_batch(&format!("DETACH DATABASE {}", schema));
+            return Err(fail(problem));
+        }
+
+        self.attached.push((origin.to_string(), path));
+        Ok(())
+    }
+
+    /// Attach this database's archive (see `archive_db_path`), if there is one
+    pub fn attach_archive(&mut self) -> Result<bool, DbError> {
+        let path = archive_db_path(self.path());
+        if !path.exists() {
+            return Ok(false);
+        }
+        self.attach(ARCHIVE_ORIGIN, path)?;
+        Ok(true)
+    }
+
+    /// Attach every `*.db` snapshot in a directory, returning the origins attached
+    pub fn attach_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<Vec<String>, DbError> {
+        let dir = dir.as_ref();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(|e| DbError::Attach {
+                origin: dir.display().to_string(),
+                reason: e.to_string(),
+            })?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "db"))
+            .collect();
+        paths.sort();
+
+        let mut origins = Vec::new();
+        for path in paths {
+            let origin = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.attach(&origin, &path)?;
+            origins.push(origin);
+        }
+        Ok(origins)
+    }
+
+    /// Origins queried by federated methods: local first, then attached
+    pub fn origins(&self) -> Vec<String> {
+        std::iter::once(LOCAL_ORIGIN.to_string())
+            .chain(self.attached.iter().map(|(o, _)| o.clone()))
+            .collect()
+    }
+
+    /// (origin, schema) pairs for every database
+    fn schemas(&self) -> Vec<(String, String)> {
+        std::iter::once((LOCAL_ORIGIN.to_string(), "main".to_string()))
+            .chain(
+                self.attached
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (o, _))| (o.clone(), format!("fed{}", i))),
+            )
+            .collect()
+    }
+
+    /// Sessions from every database, most recent first
+    pub fn get_sessions_federated(
+        &self,
+        recent_days: Option<i64>,
+    ) -> Result<Vec<FederatedSession>, DbError> {
+        let time_filter = if recent_days.is_some() {
+            "WHERE last_timestamp >= datetime('now', ?1 || ' days')"
+        } else {
+            ""
+        };
+
+        let mut sessions = Vec::new();
+        for (origin, schema) in self.schemas() {
+            let sql = format!(
+                r#"
+                SELECT session_id, slug, file_path, line_count,
+                       first_timestamp, last_timestamp, indexed_at
+                FROM {schema}.sessions
+                {time_filter}
+                "#
+            );
+            let mut stmt = self.conn.prepare(&sql)?;
+            let rows = match recent_days {
+                Some(days) => stmt.query_map([format!("-{}", days)], row_to_session_info)?,
+                None => stmt.query_map([], row_to_session_info)?,
+            };
+            for row in rows {
+                sessions.push(FederatedSession {
+                    origin: origin.clone(),
+                    session: row?,
+                });
+            }
+        }
+
+        sessions.sort_by(|a, b| b.session.last_timestamp.cmp(&a.session.last_timestamp));
+        Ok(sessions)
+    }
+
+    /// Full-text search across every database, best matches first
+    pub fn search_lines_federated(
+        &self,
+        query: &str,
+        limit: i64,
+        since: Option<&str>,
+    ) -> Result<Vec<FederatedLine>, DbError> {
+        let fts_query: String = query
+            .split_whitespace()
+            .map(|w| format!("\"{}\"", w.replace('"', "")))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        let mut scored: Vec<(f64, FederatedLine)> = Vec::new();
+        for (origin, schema) in self.schemas() {
+            let sql = format!(
+                r#"
+                SELECT
+                    l.id, l.session_id, l.uuid, l.parent_uuid, l.line_number,
+                    l.type, l.subtype, l.timestamp, l.slug, l.role, l.model,
+                    l.cwd, l.content, l.raw, l.file_path,
+                    l.turn_id, l.turn_sequence, l.session_name,
+                    bm25(lines_fts) AS score
+                FROM {schema}.lines_fts
+                JOIN {schema}.lines l ON lines_fts.rowid = l.id
+                WHERE lines_fts MATCH ?1
+                  AND (?3 IS NULL OR l.timestamp >= ?3)
+                ORDER BY score
+                LIMIT ?2
+                "#
+            );
+            let mut stmt = self.conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params![fts_query, limit, since], |row| {
+                Ok((row.get::<_, f64>(18)?, row_to_transcript_line(row)))
+            })?;
+            for row in rows {
+                let (score, line) = row?;
+                scored.push((
+                    score,
+                    FederatedLine {
+                        origin: origin.clone(),
+                        line,
+                    },
+                ));
+            }
+        }
+
+        // bm25: lower is better
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(scored
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .map(|(_, hit)| hit)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_team_dir() {
+        let path = default_team_dir();
+        assert!(path.ends_with(".claude-code-sdk/team"));
+    }
+
+    #[test]
+    fn test_archive_db_path() {
+        assert_eq!(
+            archive_db_path(Path::new("/h/.claude-code-sdk/transcripts.db")),
+            Path::new("/h/.claude-code-sdk/archive.db")
+        );
+        assert_eq!(archive_db_path(Path::new("/w/work.db")), Path::new("/w/work.archive.db"));
+    }
+}
@@ -396,6 +396,45 @@ impl TranscriptDb {
         Ok(count)
     }
 
+    /// Pre/PostToolUse timestamps per tool call, optionally for one session
+    pub fn get_tool_timings(&self, session_id: Option<&str>) -> Result<Vec<ToolTiming>, DbError> {
+        let mut sql = String::from(
+            r#"
+            SELECT
+                tool_use_id,
+                MAX(tool_name),
+                MIN(CASE WHEN event_type = 'PreToolUse' THEN timestamp END),
+                MAX(CASE WHEN event_type IN ('PostToolUse', 'PostToolUseFailure') THEN timestamp END),
+                MAX(event_type = 'PostToolUseFailure')
+            FROM hook_events
+            WHERE tool_use_id IS NOT NULL AND tool_use_id != ''
+              AND event_type IN ('PreToolUse', 'PostToolUse', 'PostToolUseFailure')
+            "#,
+        );
+        if session_id.is_some() {
+            sql.push_str(" AND session_id = ?");
+        }
+        sql.push_str(" GROUP BY tool_use_id");
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> = session_id.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let rows = stmt.query_map(params.as_slice(), |row| {
+            Ok(ToolTiming {
+                tool_use_id: row.get(0)?,
+                tool_name: row.get(1)?,
+                started: row.get(2)?,
+                finished: row.get(3)?,
+                failed: row.get::<_, Option<bool>>(4)?.unwrap_or(false),
+            })
+        })?;
+
+        let mut timings = Vec::new();
+        for row in rows {
+            timings.push(row?);
+        }
+        Ok(timings)
+    }
+
     /// Get file edits for a session (parses PostToolUse events for Edit/Write/NotebookEdit)
     pub fn get_session_file_edits(
         &self,
@@ -540,6 +579,19 @@ pub struct HookSessionInfoResult {
     pub tool_counts: Vec<(String, i64)>,
 }
 
+/// Hook timestamps of one tool call
+#[derive(Debug, Clone)]
+pub struct ToolTiming {
+    pub tool_use_id: String,
+    pub tool_name: Option<String>,
+    /// PreToolUse time
+    pub started: Option<String>,
+    /// PostToolUse (or PostToolUseFailure) time
+    pub finished: Option<String>,
+    /// A PostToolUseFailure event was recorded
+    pub failed: bool,
+}
+
 /// File edit result from aggregating hook events
 #[derive(Debug, Clone)]
 pub struct FileEditResult {
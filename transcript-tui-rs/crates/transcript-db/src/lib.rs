@@ -2,12 +2,33 @@
 //!
 //! This crate provides read-only access to the Claude Code SDK transcript database.
 
+pub mod api_failures;
+pub mod changes;
 pub mod connection;
+pub mod costs;
+pub mod explain;
+pub mod federation;
 pub mod hook_queries;
+pub mod payloads;
+pub mod projects;
 pub mod queries;
 pub mod sessions;
+pub mod sql;
+pub mod timing;
+pub mod usage;
 
+pub use api_failures::*;
+pub use changes::*;
 pub use connection::*;
+pub use costs::*;
+pub use explain::{canned_query, CannedQuery, PlanStep, CANNED_QUERIES};
+pub use federation::*;
 pub use hook_queries::*;
+pub use payloads::*;
+pub use projects::*;
 pub use queries::*;
+pub use sessions::SessionMetadata;
+pub use sql::*;
+pub use timing::{LoggedQuery, SlowQuery};
+pub use usage::*;
 // Session and hook queries are available via TranscriptDb methods
@@ -0,0 +1,74 @@
+//! Largest tool inputs and outputs
+//!
+//! Sizes are recorded by the indexer from the transcript as written, before
+//! the raw column is trimmed, so they show what tools actually produced.
+
+use crate::connection::{DbError, TranscriptDb};
+
+/// One tool input or output and where it was written
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolPayload {
+    pub session_id: String,
+    pub slug: Option<String>,
+    pub line_number: i64,
+    pub tool_use_id: String,
+    /// Tool name (`None` for results whose call was never indexed)
+    pub tool_name: Option<String>,
+    /// `input` (the tool_use) or `output` (the tool_result)
+    pub direction: String,
+    pub bytes: i64,
+}
+
+impl TranscriptDb {
+    /// Largest tool payloads first, optionally limited to one session and tool
+    ///
+    /// Empty on databases indexed before payload sizes were recorded.
+    pub fn largest_payloads(
+        &self,
+        limit: usize,
+        session_id: Option<&str>,
+        tool: Option<&str>,
+    ) -> Result<Vec<ToolPayload>, DbError> {
+        if !self.has_column("tool_payloads", "bytes") {
+            return Ok(Vec::new());
+        }
+
+        // A result indexed in a later delta than its call is named through the call
+        let sql = r#"
+            SELECT session_id, slug, line_number, tool_use_id, tool_name, direction, bytes
+            FROM (
+                SELECT p.session_id, p.line_number, p.tool_use_id, p.direction, p.bytes,
+                       COALESCE(p.tool_name, (
+                           SELECT i.tool_name FROM tool_payloads i
+                           WHERE i.session_id = p.session_id AND i.tool_use_id = p.tool_use_id
+                             AND i.direction = 'input'
+                       )) AS tool_name,
+                       (SELECT s.slug FROM sessions s
+                        WHERE s.session_id = p.session_id AND s.slug IS NOT NULL LIMIT 1) AS slug
+                FROM tool_payloads p
+                WHERE (?1 IS NULL OR p.session_id = ?1)
+                ORDER BY p.bytes DESC
+            )
+            WHERE (?2 IS NULL OR tool_name = ?2)
+            LIMIT ?3
+        "#;
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(rusqlite::params![session_id, tool, limit as i64], |row| {
+            Ok(ToolPayload {
+                session_id: row.get(0)?,
+                slug: row.get(1)?,
+                line_number: row.get(2)?,
+                tool_use_id: row.get(3)?,
+                tool_name: row.get(4)?,
+                direction: row.get(5)?,
+                bytes: row.get(6)?,
+            })
+        })?;
+
+        let mut payloads = Vec::new();
+        for row in rows {
+            payloads.push(row?);
+        }
+        Ok(payloads)
+    }
+}
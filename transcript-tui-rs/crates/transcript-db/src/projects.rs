@@ -0,0 +1,186 @@
+//! Sessions grouped by project
+//!
+//! A project is the repository a session ran in (a line's `repo_root`), or
+//! its working directory (`cwd`) outside a repository and on databases
+//! indexed before repository roots were recorded. A session belongs to every
+//! project it has lines in, and a directory's project takes in its
+//! subdirectories when filtering (`--project`). Its name is the repository
+//! name of the directory's git `origin` remote when the directory is a
+//! checkout on this machine, else the directory's name.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::connection::{DbError, TranscriptDb};
+
+/// One project's sessions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    /// Repository root or working directory
+    pub path: String,
+    /// Repository or directory name
+    pub name: String,
+    /// URL of the directory's git `origin` remote (checkouts on this machine)
+    pub remote: Option<String>,
+    /// Sessions with lines in the project
+    pub sessions: i64,
+    /// Lines run in the project
+    pub lines: i64,
+    /// First and last activity
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+}
+
+/// URL of a checkout's `origin` remote (else its first remote), read from
+/// `.git/config`
+pub fn git_remote(dir: &Path) -> Option<String> {
+    remote_url(&std::fs::read_to_string(dir.join(".git").join("config")).ok()?)
+}
+
+/// The `origin` (else first) remote's URL in a git config file
+fn remote_url(config: &str) -> Option<String> {
+    let mut remotes: Vec<(String, String)> = Vec::new();
+    let mut section: Option<String> = None;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line
+                .strip_prefix("[remote \"")
+                .and_then(|rest| rest.strip_suffix("\"]"))
+                .map(String::from);
+        } else if let (Some(remote), Some((key, value))) = (&section, line.split_once('=')) {
+            if key.trim() == "url" {
+                remotes.push((remote.clone(), value.trim().to_string()));
+            }
+        }
+    }
+    remotes
+        .iter()
+        .find(|(name, _)| name == "origin")
+        .or(remotes.first())
+        .map(|(_, url)| url.clone())
+}
+
+/// Name of a project: the remote's repository name, else the directory's name
+pub fn project_name(path: &str, remote: Option<&str>) -> String {
+    let last = |s: &str| {
+        s.trim_end_matches(['/', '\\'])
+            .rsplit(['/', '\\', ':'])
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    remote
+        .map(|url| last(url).trim_end_matches(".git").to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| match last(path) {
+            name if name.is_empty() => path.to_string(),
+            name => name,
+        })
+}
+
+impl TranscriptDb {
+    /// SQL expression for a line's project path, its columns prefixed with
+    /// `table` (e.g. `"l."`)
+    pub(crate) fn project_path_expr(&self, table: &str) -> String {
+        if self.has_column("lines", "repo_root") {
+            format!("COALESCE({table}repo_root, {table}cwd)")
+        } else {
+            format!("{table}cwd")
+        }
+    }
+
+    /// Every project, most recently active first
+    pub fn get_projects(&self) -> Result<Vec<Project>, DbError> {
+        let sql = format!(
+            "SELECT {path}, COUNT(DISTINCT session_id), COUNT(*), MIN(timestamp), MAX(timestamp)
+             FROM lines
+             WHERE cwd IS NOT NULL AND cwd != ''
+             GROUP BY {path}
+             ORDER BY MAX(timestamp) DESC",
+            path = self.project_path_expr("")
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            let path: String = row.get(0)?;
+            let remote = git_remote(Path::new(&path));
+            Ok(Project {
+                name: project_name(&path, remote.as_deref()),
+                path,
+                remote,
+                sessions: row.get(1)?,
+                lines: row.get(2)?,
+                first_timestamp: row.get(3)?,
+                last_timestamp: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Project name of each session: that of the project its first line ran
+    /// in (missing: no line has a working directory)
+    pub fn get_session_projects(&self, session_ids: &[&str]) -> Result<HashMap<String, String>, DbError> {
+        let mut projects = HashMap::new();
+        if session_ids.is_empty() {
+            return Ok(projects);
+        }
+
+        let placeholders: Vec<_> = session_ids.iter().map(|_| "?").collect();
+        let sql = format!(
+            "SELECT session_id, {} FROM lines WHERE id IN (
+                 SELECT MIN(id) FROM lines
+                 WHERE session_id IN ({}) AND cwd IS NOT NULL AND cwd != ''
+                 GROUP BY session_id
+             )",
+            self.project_path_expr(""),
+            placeholders.join(",")
+        );
+        let params: Vec<&dyn rusqlite::ToSql> = session_ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params.as_slice(), |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        let mut names: HashMap<String, String> = HashMap::new();
+        for row in rows {
+            let (session_id, path) = row?;
+            let name = names
+                .entry(path.clone())
+                .or_insert_with(|| project_name(&path, git_remote(Path::new(&path)).as_deref()));
+            projects.insert(session_id, name.clone());
+        }
+        Ok(projects)
+    }
+
+    /// Sessions with lines in a project, or in a directory or below
+    pub fn get_session_ids_in_project(&self, path: &str) -> Result<Vec<String>, DbError> {
+        let sql = format!(
+            "SELECT DISTINCT session_id FROM lines
+             WHERE {} = ?1 OR cwd = ?1 OR substr(cwd, 1, length(?1) + 1) IN (?1 || '/', ?1 || '\\')",
+            self.project_path_expr("")
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map([path], |row| row.get(0))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_name() {
+        assert_eq!(project_name("/home/me/src/api", None), "api");
+        assert_eq!(project_name(r"C:\Users\me\src\api\", None), "api");
+        assert_eq!(project_name("/home/me/src/api/", Some("git@github.com:acme/backend.git")), "backend");
+        assert_eq!(project_name("/w", Some("https://github.com/acme/web")), "web");
+        assert_eq!(project_name("/", None), "/");
+    }
+
+    #[test]
+    fn test_remote_url() {
+        let config = "[core]\n\tbare = false\n[remote \"fork\"]\n\turl = git@github.com:me/api.git\n\
+                      [remote \"origin\"]\n\turl = git@github.com:acme/api.git\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n";
+        assert_eq!(remote_url(config).as_deref(), Some("git@github.com:acme/api.git"));
+        assert_eq!(remote_url("[remote \"fork\"]\n\turl = /srv/api.git\n").as_deref(), Some("/srv/api.git"));
+        assert_eq!(remote_url("[core]\n\tbare = false\n"), None);
+        assert_eq!(git_remote(Path::new("/nonexistent")), None);
+    }
+}
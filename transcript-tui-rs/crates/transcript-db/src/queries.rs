@@ -3,6 +3,66 @@
 use crate::connection::{DbError, TranscriptDb};
 use transcript_core::{LineType, TranscriptLine};
 
+/// A note attached to a line (`parent_id` is set on replies)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub id: i64,
+    pub session_id: String,
+    pub uuid: String,
+    pub line_number: i64,
+    pub parent_id: Option<i64>,
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+}
+
+/// Annotations in reading order: each note followed by its replies, with
+/// the reply depth (0 for a note on the line itself)
+pub fn annotation_threads(annotations: &[Annotation]) -> Vec<(usize, &Annotation)> {
+    let mut ordered = Vec::with_capacity(annotations.len());
+    let mut stack: Vec<(usize, &Annotation)> = annotations
+        .iter()
+        .rev()
+        .filter(|a| a.parent_id.is_none_or(|p| !annotations.iter().any(|b| b.id == p)))
+        .map(|a| (0, a))
+        .collect();
+    while let Some((depth, annotation)) = stack.pop() {
+        ordered.push((depth, annotation));
+        stack.extend(
+            annotations
+                .iter()
+                .rev()
+                .filter(|a| a.parent_id == Some(annotation.id))
+                .map(|a| (depth + 1, a)),
+        );
+    }
+    ordered
+}
+
+/// A named query (`transcript search --save`); `filters` is the JSON the
+/// CLI stored with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    pub filters: String,
+    pub created_at: String,
+}
+
+/// An entry of the indexing error log: a file that failed to index
+/// (`line_number` 0) or a line that isn't JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexFailure {
+    pub file_path: String,
+    /// What indexed the file: `transcript`, `hooks`, or an adapter's name
+    pub kind: String,
+    pub line_number: i64,
+    pub error: String,
+    /// Times it was seen
+    pub count: i64,
+    pub occurred_at: String,
+}
+
 /// Options for querying lines
 #[derive(Debug, Clone, Default)]
 pub struct GetLinesOptions {
@@ -224,20 +284,17 @@ impl TranscriptDb {
         Ok(count)
     }
 
-    /// Search lines using FTS
+    /// Search lines using FTS, only those at or after `since` when given
+    ///
+    /// Lines whose annotations match follow the lines whose content does.
     pub fn search_lines(
         &self,
         query: &str,
         limit: i64,
         session_id: Option<&str>,
+        since: Option<&str>,
     ) -> Result<Vec<TranscriptLine>, DbError> {
-        // Escape FTS special characters and build query
-        let fts_query: String = query
-            .split_whitespace()
-            .filter(|w| !w.is_empty())
-            .map(|w| format!("\"{}\"", w.replace('"', "")))
-            .collect::<Vec<_>>()
-            .join(" OR ");
+        let fts_query = fts_or_query(query);
 
         let mut lines = Vec::new();
 
@@ -251,11 +308,12 @@ impl TranscriptDb {
                 FROM lines_fts fts
                 JOIN lines l ON fts.rowid = l.id
                 WHERE lines_fts MATCH ? AND l.session_id = ?
+                  AND (?3 IS NULL OR l.timestamp >= ?3)
                 ORDER BY bm25(lines_fts)
-                LIMIT ?
+                LIMIT ?4
             "#;
             let mut stmt = self.conn.prepare(sql)?;
-            let rows = stmt.query_map(rusqlite::params![fts_query, sid, limit], |row| {
+            let rows = stmt.query_map(rusqlite::params![fts_query, sid, since, limit], |row| {
                 Ok(row_to_transcript_line(row))
             })?;
             for row in rows {
@@ -270,12 +328,13 @@ impl TranscriptDb {
                     l.turn_id, l.turn_sequence, l.session_name
                 FROM lines_fts fts
                 JOIN lines l ON fts.rowid = l.id
-                WHERE lines_fts MATCH ?
+                WHERE lines_fts MATCH ?1
+                  AND (?2 IS NULL OR l.timestamp >= ?2)
                 ORDER BY bm25(lines_fts)
-                LIMIT ?
+                LIMIT ?3
             "#;
             let mut stmt = self.conn.prepare(sql)?;
-            let rows = stmt.query_map(rusqlite::params![fts_query, limit], |row| {
+            let rows = stmt.query_map(rusqlite::params![fts_query, since, limit], |row| {
                 Ok(row_to_transcript_line(row))
             })?;
             for row in rows {
@@ -283,10 +342,339 @@ impl TranscriptDb {
             }
         }
 
+        match session_id {
+            Some(sid) => self.push_annotated_lines(&mut lines, &fts_query, limit, since, "l.session_id = ?", &[&sid])?,
+            None => self.push_annotated_lines(&mut lines, &fts_query, limit, since, "1 = 1", &[])?,
+        }
+        Ok(lines)
+    }
+
+    /// Search lines using FTS, only in sessions carrying `tag`
+    pub fn search_lines_with_tag(
+        &self,
+        query: &str,
+        limit: i64,
+        tag: &str,
+        since: Option<&str>,
+    ) -> Result<Vec<TranscriptLine>, DbError> {
+        self.require_column("tags", "tag")?;
+
+        let sql = r#"
+            SELECT
+                l.id, l.session_id, l.uuid, l.parent_uuid, l.line_number,
+                l.type, l.subtype, l.timestamp, l.slug, l.role, l.model,
+                l.cwd, l.content, l.raw, l.file_path,
+                l.turn_id, l.turn_sequence, l.session_name
+            FROM lines_fts fts
+            JOIN lines l ON fts.rowid = l.id
+            WHERE lines_fts MATCH ?1
+              AND l.session_id IN (SELECT session_id FROM tags WHERE tag = ?2)
+              AND (?3 IS NULL OR l.timestamp >= ?3)
+            ORDER BY bm25(lines_fts)
+            LIMIT ?4
+        "#;
+        let mut stmt = self.conn.prepare(sql)?;
+        let fts_query = fts_or_query(query);
+        let rows = stmt.query_map(rusqlite::params![fts_query, tag, since, limit], |row| {
+            Ok(row_to_transcript_line(row))
+        })?;
+
+        let mut lines = Vec::new();
+        for row in rows {
+            lines.push(row?);
+        }
+        self.push_annotated_lines(
+            &mut lines,
+            &fts_query,
+            limit,
+            since,
+            "l.session_id IN (SELECT session_id FROM tags WHERE tag = ?)",
+            &[&tag],
+        )?;
+        Ok(lines)
+    }
+
+    /// Search lines using FTS, only those run in a project, or in a
+    /// directory or below
+    pub fn search_lines_in_project(
+        &self,
+        query: &str,
+        limit: i64,
+        project: &str,
+        since: Option<&str>,
+    ) -> Result<Vec<TranscriptLine>, DbError> {
+        let sql = format!(
+            r#"
+            SELECT
+                l.id, l.session_id, l.uuid, l.parent_uuid, l.line_number,
+                l.type, l.subtype, l.timestamp, l.slug, l.role, l.model,
+                l.cwd, l.content, l.raw, l.file_path,
+                l.turn_id, l.turn_sequence, l.session_name
+            FROM lines_fts fts
+            JOIN lines l ON fts.rowid = l.id
+            WHERE lines_fts MATCH ?1
+              AND ({} = ?2 OR l.cwd = ?2 OR substr(l.cwd, 1, length(?2) + 1) IN (?2 || '/', ?2 || '\'))
+              AND (?4 IS NULL OR l.timestamp >= ?4)
+            ORDER BY bm25(lines_fts)
+            LIMIT ?3
+        "#,
+            self.project_path_expr("l.")
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let fts_query = fts_or_query(query);
+        let rows = stmt.query_map(rusqlite::params![fts_query, project, limit, since], |row| {
+            Ok(row_to_transcript_line(row))
+        })?;
+
+        let mut lines = Vec::new();
+        for row in rows {
+            lines.push(row?);
+        }
+        let filter = format!(
+            "({} = ? OR l.cwd = ? OR substr(l.cwd, 1, length(?) + 1) = ? || '/')",
+            self.project_path_expr("l.")
+        );
+        self.push_annotated_lines(
+            &mut lines,
+            &fts_query,
+            limit,
+            since,
+            &filter,
+            &[&project, &project, &project, &project],
+        )?;
+        Ok(lines)
+    }
+
+    /// Append lines with an annotation matching an FTS query, best match
+    /// first, up to `limit` lines in total
+    ///
+    /// `since` and `filter`, the caller's condition on `l` (with `params` for
+    /// its placeholders), scope annotation matches like content matches.
+    /// Does nothing on databases without annotation search.
+    fn push_annotated_lines(
+        &self,
+        lines: &mut Vec<TranscriptLine>,
+        fts_query: &str,
+        limit: i64,
+        since: Option<&str>,
+        filter: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> Result<(), DbError> {
+        if lines.len() as i64 >= limit || !self.has_column("annotations_fts", "body") {
+            return Ok(());
+        }
+
+        let sql = format!(
+            r#"
+            SELECT
+                l.id, l.session_id, l.uuid, l.parent_uuid, l.line_number,
+                l.type, l.subtype, l.timestamp, l.slug, l.role, l.model,
+                l.cwd, l.content, l.raw, l.file_path,
+                l.turn_id, l.turn_sequence, l.session_name
+            FROM annotations_fts fts
+            JOIN annotations a ON fts.rowid = a.id
+            JOIN lines l ON l.session_id = a.session_id AND l.uuid = a.uuid
+            WHERE annotations_fts MATCH ? AND {}
+              AND (? IS NULL OR l.timestamp >= ?)
+            ORDER BY bm25(annotations_fts)
+            LIMIT ?
+        "#,
+            filter
+        );
+        let mut all: Vec<&dyn rusqlite::ToSql> = vec![&fts_query];
+        all.extend_from_slice(params);
+        all.extend([&since as &dyn rusqlite::ToSql, &since, &limit]);
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(all.as_slice(), |row| Ok(row_to_transcript_line(row)))?;
+
+        // A line with several matching notes comes back once per note
+        for row in rows {
+            let line = row?;
+            if lines.len() as i64 >= limit {
+                break;
+            }
+            if !lines.iter().any(|l| l.id == line.id) {
+                lines.push(line);
+            }
+        }
+        Ok(())
+    }
+
+    /// Annotations of a session by line, oldest first (none on databases without annotations)
+    pub fn get_annotations(&self, session_id: &str) -> Result<Vec<Annotation>, DbError> {
+        if !self.has_column("annotations", "uuid") {
+            return Ok(Vec::new());
+        }
+
+        // Notes written before replies existed have no parent
+        let parent = if self.has_column("annotations", "parent_id") {
+            "parent_id"
+        } else {
+            "NULL"
+        };
+        let sql = format!(
+            r#"
+            SELECT id, session_id, uuid, line_number, {}, author, body, created_at
+            FROM annotations
+            WHERE session_id = ?
+            ORDER BY line_number, id
+        "#,
+            parent
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map([session_id], |row| {
+            Ok(Annotation {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                uuid: row.get(2)?,
+                line_number: row.get(3)?,
+                parent_id: row.get(4)?,
+                author: row.get(5)?,
+                body: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        })?;
+
+        let mut annotations = Vec::new();
+        for row in rows {
+            annotations.push(row?);
+        }
+        Ok(annotations)
+    }
+
+    /// Bookmarked lines of a session in line order (none on databases without bookmarks)
+    pub fn get_bookmarked_lines(&self, session_id: &str) -> Result<Vec<TranscriptLine>, DbError> {
+        if !self.has_column("bookmarks", "uuid") {
+            return Ok(Vec::new());
+        }
+
+        let sql = r#"
+            SELECT
+                l.id, l.session_id, l.uuid, l.parent_uuid, l.line_number,
+                l.type, l.subtype, l.timestamp, l.slug, l.role, l.model,
+                l.cwd, l.content, l.raw, l.file_path,
+                l.turn_id, l.turn_sequence, l.session_name
+            FROM bookmarks b
+            JOIN lines l ON l.session_id = b.session_id AND l.uuid = b.uuid
+            WHERE b.session_id = ?
+            ORDER BY l.line_number
+        "#;
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map([session_id], |row| Ok(row_to_transcript_line(row)))?;
+
+        let mut lines = Vec::new();
+        for row in rows {
+            lines.push(row?);
+        }
         Ok(lines)
     }
 
+    /// Saved searches by name (none on databases without them)
+    pub fn get_saved_searches(&self) -> Result<Vec<SavedSearch>, DbError> {
+        if !self.has_column("saved_searches", "filters") {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name, query, filters, created_at FROM saved_searches ORDER BY name")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(SavedSearch {
+                name: row.get(0)?,
+                query: row.get(1)?,
+                filters: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+
+        let mut searches = Vec::new();
+        for row in rows {
+            searches.push(row?);
+        }
+        Ok(searches)
+    }
+
+    /// The search saved as `name`, if any
+    pub fn get_saved_search(&self, name: &str) -> Result<Option<SavedSearch>, DbError> {
+        Ok(self.get_saved_searches()?.into_iter().find(|s| s.name == name))
+    }
+
+    /// Lines with tool calls or tool results, optionally for one session, in file order
+    pub fn get_tool_lines(&self, session_id: Option<&str>) -> Result<Vec<TranscriptLine>, DbError> {
+        let mut sql = String::from(
+            r#"
+            SELECT
+                id, session_id, uuid, parent_uuid, line_number,
+                type, subtype, timestamp, slug, role, model,
+                cwd, content, raw, file_path,
+                turn_id, turn_sequence, session_name
+            FROM lines
+            WHERE type IN ('assistant', 'user')
+              AND (raw LIKE '%"tool_use"%' OR raw LIKE '%"tool_result"%')
+            "#,
+        );
+        if session_id.is_some() {
+            sql.push_str(" AND session_id = ?");
+        }
+        sql.push_str(" ORDER BY session_id, line_number");
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> = session_id.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let rows = stmt.query_map(params.as_slice(), |row| Ok(row_to_transcript_line(row)))?;
+
+        let mut lines = Vec::new();
+        for row in rows {
+            lines.push(row?);
+        }
+        Ok(lines)
+    }
+
+    /// Indexing error log, most recent first (none on databases without the log)
+    pub fn get_index_errors(&self, limit: i64) -> Result<Vec<IndexFailure>, DbError> {
+        if !self.has_column("index_errors", "count") {
+            return Ok(Vec::new());
+        }
+
+        let sql = r#"
+            SELECT file_path, kind, line_number, error, count, occurred_at
+            FROM index_errors
+            ORDER BY occurred_at DESC, file_path, line_number
+            LIMIT ?
+        "#;
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map([limit], |row| {
+            Ok(IndexFailure {
+                file_path: row.get(0)?,
+                kind: row.get(1)?,
+                line_number: row.get(2)?,
+                error: row.get(3)?,
+                count: row.get(4)?,
+                occurred_at: row.get(5)?,
+            })
+        })?;
+
+        let mut failures = Vec::new();
+        for row in rows {
+            failures.push(row?);
+        }
+        Ok(failures)
+    }
+
+    /// Entries in the indexing error log, as (failed files, bad lines)
+    pub fn count_index_errors(&self) -> Result<(i64, i64), DbError> {
+        if !self.has_column("index_errors", "count") {
+            return Ok((0, 0));
+        }
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) FILTER (WHERE line_number = 0), COUNT(*) FILTER (WHERE line_number > 0) FROM index_errors",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?)
+    }
+
     /// Get turn data for lines by their IDs (for refreshing turn info)
+    #[allow(clippy::type_complexity)]
     pub fn get_turn_data_for_ids(
         &self,
         ids: &[i64],
@@ -321,8 +709,18 @@ impl TranscriptDb {
     }
 }
 
+/// FTS query matching any of the words (quoted, so FTS syntax is literal)
+fn fts_or_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{}\"", w.replace('"', "")))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
 /// Convert a database row to a TranscriptLine
-fn row_to_transcript_line(row: &rusqlite::Row) -> TranscriptLine {
+pub(crate) fn row_to_transcript_line(row: &rusqlite::Row) -> TranscriptLine {
     let type_str: String = row.get(5).unwrap_or_default();
     let line_type = match type_str.as_str() {
         "user" => LineType::User,
@@ -353,3 +751,88 @@ fn row_to_transcript_line(row: &rusqlite::Row) -> TranscriptLine {
         session_name: row.get(17).ok(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database with two sessions, s2 tagged "ci", and a note on one line of each
+    fn annotated_db() -> (tempfile::TempDir, TranscriptDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcripts.db");
+        let mut conn = rusqlite::Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut conn).unwrap();
+        for (session, uuid, line, content, cwd) in [
+            ("s1", "a1", 1, "running the tests", "/work/app"),
+            ("s1", "a2", 2, "reading main.rs", "/work/app/src"),
+            ("s2", "b1", 1, "running cargo build", "/work/apps"),
+        ] {
+            conn.execute(
+                "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, cwd, content, raw, file_path)
+                 VALUES (?1, ?2, ?3, 'assistant', '2024-01-01T00:00:00Z', ?5, ?4, '{}', '/tmp/t.jsonl')",
+                rusqlite::params![session, uuid, line, content, cwd],
+            )
+            .unwrap();
+        }
+        transcript_indexer::add_tag(&conn, "s2", "ci").unwrap();
+        transcript_indexer::add_annotation(&conn, "s1", "a2", 2, "ann", "flaky retry loop", None).unwrap();
+        transcript_indexer::add_annotation(&conn, "s2", "b1", 1, "ann", "flaky on ci", None).unwrap();
+        drop(conn);
+
+        let db = TranscriptDb::open(&path).unwrap();
+        (dir, db)
+    }
+
+    fn uuids(lines: &[TranscriptLine]) -> Vec<&str> {
+        lines.iter().map(|l| l.uuid.as_str()).collect()
+    }
+
+    #[test]
+    fn test_search_finds_lines_by_annotation() {
+        let (_dir, db) = annotated_db();
+
+        // Content matches come first, then lines whose notes match
+        let found = db.search_lines("running flaky", 10, None, None).unwrap();
+        assert_eq!(found.len(), 3);
+        assert_eq!(uuids(&found[2..]), vec!["a2"]);
+
+        let found = db.search_lines("flaky", 10, None, None).unwrap();
+        let mut found = uuids(&found);
+        found.sort();
+        assert_eq!(found, vec!["a2", "b1"]);
+        assert_eq!(db.search_lines("flaky", 1, None, None).unwrap().len(), 1);
+
+        let annotations = db.get_annotations("s1").unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].body, "flaky retry loop");
+    }
+
+    #[test]
+    fn test_annotation_threads_order() {
+        let note = |id, parent_id| Annotation {
+            id,
+            session_id: "s1".to_string(),
+            uuid: "u1".to_string(),
+            line_number: 1,
+            parent_id,
+            author: "ann".to_string(),
+            body: String::new(),
+            created_at: String::new(),
+        };
+        // 1 <- 3 <- 4, 2; a reply whose parent is gone starts its own thread
+        let annotations = vec![note(1, None), note(2, None), note(3, Some(1)), note(4, Some(3)), note(5, Some(9))];
+        let order: Vec<(usize, i64)> = annotation_threads(&annotations).iter().map(|(d, a)| (*d, a.id)).collect();
+        assert_eq!(order, vec![(0, 1), (1, 3), (2, 4), (0, 2), (0, 5)]);
+    }
+
+    #[test]
+    fn test_annotation_search_applies_filters() {
+        let (_dir, db) = annotated_db();
+
+        assert_eq!(uuids(&db.search_lines("flaky", 10, Some("s1"), None).unwrap()), vec!["a2"]);
+        assert_eq!(uuids(&db.search_lines_with_tag("flaky", 10, "ci", None).unwrap()), vec!["b1"]);
+        assert!(db.search_lines_with_tag("flaky", 10, "none", None).unwrap().is_empty());
+        assert_eq!(uuids(&db.search_lines_in_project("flaky", 10, "/work/app", None).unwrap()), vec!["a2"]);
+        assert!(db.search_lines("flaky", 10, None, Some("2025-01-01")).unwrap().is_empty());
+    }
+}
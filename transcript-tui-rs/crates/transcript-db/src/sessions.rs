@@ -1,7 +1,32 @@
 //! Session management and lookup
 
+use std::collections::HashMap;
+
 use crate::connection::{DbError, TranscriptDb};
-use transcript_core::SessionInfo;
+use rusqlite::OptionalExtension;
+use transcript_core::{fuzzy_score, ReviewStatus, SessionInfo, SessionReview, FUZZY_CLOSE};
+
+/// How completely a session is indexed: its hook events, and the turns
+/// correlation found in them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionMetadata {
+    /// Hook events recorded for the session
+    pub hook_events: i64,
+    /// Turns its lines are tied to (by `index update`'s correlation step)
+    pub turns: i64,
+}
+
+impl SessionMetadata {
+    /// Correlation state: `done`, `pending` (hook events but no turns yet)
+    /// or `no-hooks`
+    pub fn correlation(&self) -> &'static str {
+        match (self.hook_events, self.turns) {
+            (0, _) => "no-hooks",
+            (_, 0) => "pending",
+            _ => "done",
+        }
+    }
+}
 
 impl TranscriptDb {
     /// Get all sessions
@@ -104,6 +129,36 @@ impl TranscriptDb {
         Ok(sessions)
     }
 
+    /// Sessions whose slug fuzzily matches `pattern` (`loyl-whipet` for
+    /// `loyal-whippet`), for when `find_sessions` finds nothing
+    ///
+    /// Only those scoring close to the best are kept: one when it clearly
+    /// wins, several when they need telling apart. A slug shared by resumed
+    /// sessions counts once, for the most recent.
+    pub fn closest_sessions(&self, pattern: &str) -> Result<Vec<SessionInfo>, DbError> {
+        let mut seen = std::collections::HashSet::new();
+        let mut scored: Vec<(SessionInfo, i64)> = self
+            .get_sessions(None)?
+            .into_iter()
+            .filter_map(|session| {
+                let score = fuzzy_score(pattern, session.slug.as_deref()?)?;
+                Some((session, score))
+            })
+            .filter(|(session, _)| seen.insert(session.slug.clone()))
+            .collect();
+        scored.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+        let Some(best) = scored.first().map(|(_, score)| *score) else {
+            return Ok(Vec::new());
+        };
+        Ok(scored
+            .into_iter()
+            .take_while(|(_, score)| *score > best - FUZZY_CLOSE)
+            .take(20)
+            .map(|(session, _)| session)
+            .collect())
+    }
+
     /// Resolve a session ID from name using the session store
     /// This tries multiple lookup strategies:
     /// 1. Direct session ID match
@@ -142,6 +197,230 @@ impl TranscriptDb {
         }
     }
 
+    /// Most recent session run in `cwd`
+    ///
+    /// Claude Code keeps a directory's transcripts in a project directory named
+    /// after it (every character but letters and digits becomes `-`), so this
+    /// only looks at file paths. Subagent transcripts, one level down, are left out.
+    pub fn latest_session_in(&self, cwd: &str) -> Result<Option<SessionInfo>, DbError> {
+        let project: String = cwd
+            .trim_end_matches(['/', '\\'])
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let sql = r#"
+            SELECT
+                session_id,
+                slug,
+                file_path,
+                line_count,
+                first_timestamp,
+                last_timestamp,
+                indexed_at
+            FROM sessions
+            WHERE file_path LIKE '%/projects/' || ?1 || '/%'
+              AND file_path NOT LIKE '%/projects/' || ?1 || '/%/%'
+            ORDER BY last_timestamp DESC
+            LIMIT 1
+        "#;
+
+        Ok(self
+            .conn
+            .query_row(sql, [project], row_to_session_info)
+            .optional()?)
+    }
+
+    /// Users with indexed sessions and their session counts (multi-home indexing)
+    pub fn get_users(&self) -> Result<Vec<(String, i64)>, DbError> {
+        if !self.has_column("sessions", "user") {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT user, COUNT(DISTINCT session_id) FROM sessions
+             WHERE user IS NOT NULL
+             GROUP BY user
+             ORDER BY user",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut users = Vec::new();
+        for row in rows {
+            users.push(row?);
+        }
+        Ok(users)
+    }
+
+    /// Transcript file paths belonging to a user
+    pub fn get_session_files_for_user(&self, user: &str) -> Result<Vec<String>, DbError> {
+        self.require_column("sessions", "user")?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT file_path FROM sessions WHERE user = ?")?;
+        let rows = stmt.query_map([user], |row| row.get(0))?;
+
+        let mut files = Vec::new();
+        for row in rows {
+            files.push(row?);
+        }
+        Ok(files)
+    }
+
+    /// Tags on a session, alphabetically (none on databases without tags)
+    pub fn get_tags(&self, session_id: &str) -> Result<Vec<String>, DbError> {
+        if !self.has_column("tags", "tag") {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM tags WHERE session_id = ? ORDER BY tag")?;
+        let rows = stmt.query_map([session_id], |row| row.get(0))?;
+
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
+
+    /// Every tag in use and how many sessions carry it
+    pub fn get_tag_counts(&self) -> Result<Vec<(String, i64)>, DbError> {
+        if !self.has_column("tags", "tag") {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT tag, COUNT(*) FROM tags
+             GROUP BY tag
+             ORDER BY tag",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
+
+    /// IDs of the sessions carrying a tag
+    pub fn get_session_ids_with_tag(&self, tag: &str) -> Result<Vec<String>, DbError> {
+        self.require_column("tags", "tag")?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT session_id FROM tags WHERE tag = ?")?;
+        let rows = stmt.query_map([tag], |row| row.get(0))?;
+
+        let mut ids = Vec::new();
+        for row in rows {
+            ids.push(row?);
+        }
+        Ok(ids)
+    }
+
+    /// A session's review status (unreviewed on databases without reviews)
+    pub fn get_review(&self, session_id: &str) -> Result<SessionReview, DbError> {
+        if !self.has_column("reviews", "status") {
+            return Ok(SessionReview::default());
+        }
+
+        let review = self
+            .conn
+            .query_row(
+                "SELECT status, reviewer, updated_at FROM reviews WHERE session_id = ?",
+                [session_id],
+                |row| {
+                    let status: String = row.get(0)?;
+                    Ok(SessionReview {
+                        status: ReviewStatus::from_name(&status).unwrap_or_default(),
+                        reviewer: row.get(1)?,
+                        updated_at: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(review.unwrap_or_default())
+    }
+
+    /// Status of every reviewed session (sessions not in the map are unreviewed)
+    pub fn get_review_statuses(&self) -> Result<HashMap<String, ReviewStatus>, DbError> {
+        if !self.has_column("reviews", "status") {
+            return Ok(HashMap::new());
+        }
+
+        let mut stmt = self.conn.prepare("SELECT session_id, status FROM reviews")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut statuses = HashMap::new();
+        for row in rows {
+            let (session_id, status) = row?;
+            statuses.insert(session_id, ReviewStatus::from_name(&status).unwrap_or_default());
+        }
+        Ok(statuses)
+    }
+
+    /// Hook event and turn counts of sessions (missing: nothing indexed)
+    pub fn get_session_metadata(&self, session_ids: &[&str]) -> Result<HashMap<String, SessionMetadata>, DbError> {
+        let mut metadata: HashMap<String, SessionMetadata> = HashMap::new();
+        if session_ids.is_empty() {
+            return Ok(metadata);
+        }
+
+        let placeholders: Vec<_> = session_ids.iter().map(|_| "?").collect();
+        let params: Vec<&dyn rusqlite::ToSql> = session_ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+        let in_list = placeholders.join(",");
+        let counts = |sql: String| -> Result<Vec<(String, i64)>, DbError> {
+            let mut stmt = self.conn.prepare(&sql)?;
+            let rows = stmt.query_map(params.as_slice(), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?.unwrap_or(0)))
+            })?;
+            Ok(rows.collect::<Result<_, _>>()?)
+        };
+
+        for (session_id, events) in counts(format!(
+            "SELECT session_id, SUM(event_count) FROM hook_files WHERE session_id IN ({}) GROUP BY session_id",
+            in_list
+        ))? {
+            metadata.entry(session_id).or_default().hook_events = events;
+        }
+        for (session_id, turns) in counts(format!(
+            "SELECT session_id, COUNT(DISTINCT turn_id) FROM lines WHERE session_id IN ({}) GROUP BY session_id",
+            in_list
+        ))? {
+            metadata.entry(session_id).or_default().turns = turns;
+        }
+        Ok(metadata)
+    }
+
+    /// Sessions a resumed session continues, oldest first (none before the
+    /// indexer recorded lineage)
+    pub fn get_session_ancestors(&self, session_id: &str) -> Result<Vec<String>, DbError> {
+        let mut ancestors = Vec::new();
+        if !self.has_column("session_lineage", "parent_session_id") {
+            return Ok(ancestors);
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT parent_session_id FROM session_lineage WHERE session_id = ?1")?;
+        let mut current = session_id.to_string();
+        while let Some(parent) = stmt.query_row([&current], |row| row.get::<_, String>(0)).optional()? {
+            // A cycle means the links are wrong; stop at the first repeat
+            if parent == session_id || ancestors.contains(&parent) {
+                break;
+            }
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+        ancestors.reverse();
+        Ok(ancestors)
+    }
+
     /// Get session IDs by session name from hook_events
     pub fn get_session_ids_by_name(&self, name: &str) -> Result<Vec<String>, DbError> {
         let sql = r#"
@@ -162,7 +441,7 @@ impl TranscriptDb {
     }
 }
 
-fn row_to_session_info(row: &rusqlite::Row) -> Result<SessionInfo, rusqlite::Error> {
+pub(crate) fn row_to_session_info(row: &rusqlite::Row) -> Result<SessionInfo, rusqlite::Error> {
     Ok(SessionInfo {
         session_id: row.get(0)?,
         slug: row.get(1).ok(),
@@ -173,3 +452,84 @@ fn row_to_session_info(row: &rusqlite::Row) -> Result<SessionInfo, rusqlite::Err
         indexed_at: row.get(6)?,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sessions_db(slugs: &[(&str, &str, &str)]) -> (tempfile::TempDir, TranscriptDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcripts.db");
+        let mut conn = rusqlite::Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut conn).unwrap();
+        for (session_id, slug, last) in slugs {
+            conn.execute(
+                "INSERT INTO sessions (file_path, session_id, slug, line_count, last_timestamp, indexed_at)
+                 VALUES (?1, ?1, ?2, 1, ?3, '')",
+                rusqlite::params![session_id, slug, last],
+            )
+            .unwrap();
+        }
+        drop(conn);
+        (dir, TranscriptDb::open(&path).unwrap())
+    }
+
+    #[test]
+    fn test_closest_sessions_ranks_typos() {
+        let (_dir, db) = sessions_db(&[
+            ("s1", "lonely-walrus", "2024-01-03T00:00:00Z"),
+            ("s2", "loyal-whippet", "2024-01-01T00:00:00Z"),
+            ("s3", "loyal-whippet", "2024-01-02T00:00:00Z"),
+            ("s4", "happy-dog", "2024-01-04T00:00:00Z"),
+        ]);
+
+        // The clear winner comes back alone, once for its resumed sessions
+        let found = db.closest_sessions("loyl-whipet").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].slug.as_deref(), Some("loyal-whippet"));
+        assert_eq!(found[0].session_id, "s3");
+    }
+
+    #[test]
+    fn test_closest_sessions_without_match() {
+        let (_dir, db) = sessions_db(&[("s1", "loyal-whippet", "2024-01-01T00:00:00Z")]);
+        assert!(db.closest_sessions("zebra").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_session_metadata() {
+        let (dir, db) = sessions_db(&[]);
+        drop(db);
+        let path = dir.path().join("transcripts.db");
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        // s1: correlated, s2: hook events only, s3: neither
+        for (file, session_id, events) in [("a.hooks", "s1", 30), ("b.hooks", "s1", 10), ("c.hooks", "s2", 5)] {
+            conn.execute(
+                "INSERT INTO hook_files (file_path, session_id, event_count, indexed_at) VALUES (?1, ?2, ?3, '')",
+                rusqlite::params![file, session_id, events],
+            )
+            .unwrap();
+        }
+        for (n, (session_id, turn_id)) in [("s1", Some("t1")), ("s1", Some("t1")), ("s1", Some("t2")), ("s2", None), ("s3", None)]
+            .into_iter()
+            .enumerate()
+        {
+            conn.execute(
+                "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path, turn_id)
+                 VALUES (?1, ?2, ?2, 'user', '', '{}', ?1, ?3)",
+                rusqlite::params![session_id, n as i64, turn_id],
+            )
+            .unwrap();
+        }
+        drop(conn);
+        let db = TranscriptDb::open(&path).unwrap();
+
+        let metadata = db.get_session_metadata(&["s1", "s2", "s3", "s4"]).unwrap();
+        assert_eq!(metadata["s1"], SessionMetadata { hook_events: 40, turns: 2 });
+        assert_eq!(metadata["s1"].correlation(), "done");
+        assert_eq!(metadata["s2"].correlation(), "pending");
+        assert_eq!(metadata["s3"].correlation(), "no-hooks");
+        assert!(!metadata.contains_key("s4"));
+        assert!(db.get_session_metadata(&[]).unwrap().is_empty());
+    }
+}
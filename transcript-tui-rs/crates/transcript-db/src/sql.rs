@@ -0,0 +1,112 @@
+//! Ad-hoc read-only SQL queries
+
+use rusqlite::types::ValueRef;
+use serde_json::Value;
+
+use crate::connection::{DbError, TranscriptDb};
+
+/// Result of an ad-hoc SQL query
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    /// Column names in select order
+    pub columns: Vec<String>,
+    /// Row values, one entry per column
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl QueryResult {
+    /// Row as a JSON object keyed by column name
+    pub fn row_object(&self, index: usize) -> Option<serde_json::Map<String, Value>> {
+        let row = self.rows.get(index)?;
+        Some(
+            self.columns
+                .iter()
+                .cloned()
+                .zip(row.iter().cloned())
+                .collect(),
+        )
+    }
+}
+
+impl TranscriptDb {
+    /// Execute arbitrary SQL with the connection locked to query-only mode
+    ///
+    /// Any statement that would write (INSERT, UPDATE, DDL, ...) fails with a
+    /// SQLite error rather than touching the database. The lock is lifted
+    /// again afterwards, so the connection's own writes keep working.
+    pub fn run_sql(&self, sql: &str) -> Result<QueryResult, DbError> {
+        self.conn.pragma_update(None, "query_only", "ON")?;
+        let result = self.query_rows(sql);
+        self.conn.pragma_update(None, "query_only", "OFF")?;
+        result
+    }
+
+    fn query_rows(&self, sql: &str) -> Result<QueryResult, DbError> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let column_count = columns.len();
+
+        let mut rows = Vec::new();
+        let mut query = stmt.query([])?;
+        while let Some(row) = query.next()? {
+            let mut values = Vec::with_capacity(column_count);
+            for i in 0..column_count {
+                values.push(value_ref_to_json(row.get_ref(i)?));
+            }
+            rows.push(values);
+        }
+
+        Ok(QueryResult { columns, rows })
+    }
+}
+
+/// Convert a SQLite value to JSON
+fn value_ref_to_json(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => Value::String(format!("<blob {} bytes>", b.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> (tempfile::TempDir, TranscriptDb) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcripts.db");
+        let mut conn = rusqlite::Connection::open(&path).unwrap();
+        transcript_indexer::init_schema(&mut conn).unwrap();
+        transcript_indexer::add_tag(&conn, "s1", "ci").unwrap();
+        drop(conn);
+        (dir, TranscriptDb::open(&path).unwrap())
+    }
+
+    #[test]
+    fn test_run_sql_selects() {
+        let (_dir, db) = test_db();
+        let result = db.run_sql("SELECT session_id, tag FROM tags").unwrap();
+        assert_eq!(result.columns, vec!["session_id", "tag"]);
+        assert_eq!(result.rows, vec![vec![Value::from("s1"), Value::from("ci")]]);
+        assert_eq!(result.row_object(0).unwrap()["tag"], "ci");
+    }
+
+    #[test]
+    fn test_run_sql_refuses_writes() {
+        let (_dir, db) = test_db();
+        assert!(db.run_sql("INSERT INTO tags (session_id, tag, created_at) VALUES ('s2', 'x', '')").is_err());
+        assert!(db.run_sql("DROP TABLE tags").is_err());
+        assert_eq!(db.run_sql("SELECT COUNT(*) FROM tags").unwrap().rows, vec![vec![Value::from(1)]]);
+
+        // Even writes the read-only file allows are refused (temp tables)
+        assert!(db.run_sql("CREATE TEMP TABLE scratch (x)").is_err());
+
+        // The lock only lasts for the query
+        let query_only: i64 = db.conn.pragma_query_value(None, "query_only", |row| row.get(0)).unwrap();
+        assert_eq!(query_only, 0);
+        db.conn.execute_batch("CREATE TEMP TABLE scratch (x)").unwrap();
+    }
+}
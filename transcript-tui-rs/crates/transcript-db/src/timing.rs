@@ -0,0 +1,111 @@
+//! Query timing for `--timings` and the slow-query log
+//!
+//! Once `enable` is called, every connection opened afterwards reports each
+//! statement's run time here (through SQLite's profile hook). The totals give
+//! the "query" phase of a command; statements slower than the threshold are
+//! kept for the indexer to record in `slow_queries`, which `doctor` reports on.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::Connection;
+
+use crate::connection::{DbError, TranscriptDb};
+
+/// Default threshold of the slow-query log
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 200;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SLOW_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_QUERY_MS);
+static QUERIES: AtomicU64 = AtomicU64::new(0);
+static QUERY_NANOS: AtomicU64 = AtomicU64::new(0);
+static SLOW: Mutex<Vec<SlowQuery>> = Mutex::new(Vec::new());
+
+/// A statement that took longer than the threshold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowQuery {
+    pub sql: String,
+    pub millis: u64,
+}
+
+/// A row of the slow-query log
+#[derive(Debug, Clone)]
+pub struct LoggedQuery {
+    pub recorded_at: String,
+    pub command: String,
+    pub sql: String,
+    pub millis: i64,
+}
+
+/// Time statements on connections opened from now on, keeping those slower
+/// than `slow_ms`
+pub fn enable(slow_ms: u64) {
+    SLOW_MS.store(slow_ms, Ordering::Relaxed);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Install the profile hook on `conn` if timing is enabled
+pub(crate) fn instrument(conn: &mut Connection) {
+    if ENABLED.load(Ordering::Relaxed) {
+        conn.profile(Some(record));
+    }
+}
+
+fn record(sql: &str, elapsed: Duration) {
+    QUERIES.fetch_add(1, Ordering::Relaxed);
+    QUERY_NANOS.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    let millis = elapsed.as_millis() as u64;
+    if millis >= SLOW_MS.load(Ordering::Relaxed) {
+        if let Ok(mut slow) = SLOW.lock() {
+            slow.push(SlowQuery {
+                sql: sql.trim().to_string(),
+                millis,
+            });
+        }
+    }
+}
+
+/// Statements run so far and their total time
+pub fn query_time() -> (u64, Duration) {
+    (
+        QUERIES.load(Ordering::Relaxed),
+        Duration::from_nanos(QUERY_NANOS.load(Ordering::Relaxed)),
+    )
+}
+
+/// Slow statements seen since the last call
+pub fn take_slow_queries() -> Vec<SlowQuery> {
+    SLOW.lock().map(|mut slow| std::mem::take(&mut *slow)).unwrap_or_default()
+}
+
+impl TranscriptDb {
+    /// Slow-query log entries since `since` (RFC 3339), slowest first (none
+    /// on databases without the log)
+    pub fn get_slow_queries(&self, since: &str, limit: i64) -> Result<Vec<LoggedQuery>, DbError> {
+        if !self.has_column("slow_queries", "millis") {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT recorded_at, command, sql, millis FROM slow_queries
+             WHERE recorded_at >= ?1
+             ORDER BY millis DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![since, limit], |row| {
+            Ok(LoggedQuery {
+                recorded_at: row.get(0)?,
+                command: row.get(1)?,
+                sql: row.get(2)?,
+                millis: row.get(3)?,
+            })
+        })?;
+
+        let mut queries = Vec::new();
+        for row in rows {
+            queries.push(row?);
+        }
+        Ok(queries)
+    }
+}
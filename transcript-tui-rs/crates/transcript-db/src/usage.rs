@@ -0,0 +1,156 @@
+//! Context growth per turn
+//!
+//! Built on `transcript_core::usage_series` (the points behind the viewers'
+//! usage graphs), rolled up to one row per turn. A turn starts at each user
+//! prompt; subagent sidechains have their own context, so their usage is left
+//! out.
+
+use transcript_core::{usage_series, TranscriptLine};
+
+use crate::connection::{DbError, TranscriptDb};
+use crate::queries::GetLinesOptions;
+
+/// Context usage at the end of one turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnContext {
+    /// Turn number (hook turn sequence when known)
+    pub turn: usize,
+    /// Timestamp of the turn's first line
+    pub started: String,
+    /// Line number of the turn's last usage report
+    pub line_number: i64,
+    /// Tokens in context at the end of the turn
+    pub context_tokens: u64,
+    /// Largest context seen during the turn
+    pub peak_tokens: u64,
+    /// `context_tokens` as a percentage of the context window
+    pub percent: f64,
+    /// The context window, of the model at the end of the turn unless given
+    pub context_size: u64,
+    /// Change from the previous turn (negative after a compaction)
+    pub growth: i64,
+    /// The context was compacted during the turn
+    pub compacted: bool,
+}
+
+/// Roll lines (in file order) up into context usage per turn
+///
+/// Turns without any usage (interrupted before a reply) are left out.
+/// Without `context_size`, each model's own window is used.
+pub fn context_by_turn(lines: &[TranscriptLine], context_size: Option<u64>) -> Vec<TurnContext> {
+    // Turn of each line: (number, index of its first line)
+    let mut turn_of = Vec::with_capacity(lines.len());
+    let mut current: Option<(usize, usize)> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if current.is_none() || (line.is_prompt() && !line.is_sidechain()) {
+            let number = line
+                .turn_sequence
+                .filter(|&n| n > 0)
+                .map(|n| n as usize)
+                .unwrap_or_else(|| current.map_or(1, |(n, _)| n + 1));
+            current = Some((number, i));
+        }
+        turn_of.push(current.expect("a turn was just started"));
+    }
+
+    let mut turns: Vec<TurnContext> = Vec::new();
+    let mut previous = 0u64;
+    let mut current_start = None;
+    let mut compacted_since = false;
+    for point in usage_series(lines, context_size) {
+        // Keep a compaction flagged on a sidechain point for the next main one
+        compacted_since |= point.after_compaction;
+        if lines[point.index].is_sidechain() {
+            continue;
+        }
+
+        let (number, start) = turn_of[point.index];
+        if current_start != Some(start) {
+            current_start = Some(start);
+            if let Some(turn) = turns.last() {
+                previous = turn.context_tokens;
+            }
+            turns.push(TurnContext {
+                turn: number,
+                started: lines[start].timestamp.clone(),
+                line_number: point.line_number,
+                context_tokens: 0,
+                peak_tokens: 0,
+                percent: 0.0,
+                context_size: 0,
+                growth: 0,
+                compacted: false,
+            });
+        }
+        let turn = turns.last_mut().expect("a turn was just started");
+        turn.line_number = point.line_number;
+        turn.context_tokens = point.tokens;
+        turn.peak_tokens = turn.peak_tokens.max(point.tokens);
+        turn.percent = point.percent;
+        turn.context_size = point.context_size;
+        turn.growth = point.tokens as i64 - previous as i64;
+        turn.compacted |= std::mem::take(&mut compacted_since);
+    }
+    turns
+}
+
+impl TranscriptDb {
+    /// Context usage per turn of a session, against a window of `context_size`
+    /// tokens, or the window of the session's model
+    pub fn context_usage_by_turn(
+        &self,
+        session_id: &str,
+        context_size: Option<u64>,
+    ) -> Result<Vec<TurnContext>, DbError> {
+        let lines = self.get_lines(&GetLinesOptions::for_session(session_id))?;
+        Ok(context_by_turn(&lines, context_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transcript_core::LineType;
+
+    fn line(line_number: i64, line_type: LineType, raw: &str) -> TranscriptLine {
+        TranscriptLine {
+            id: line_number,
+            line_number,
+            timestamp: format!("2026-01-01T10:00:{:02}Z", line_number),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    fn reply(tokens: u64, sidechain: bool) -> String {
+        format!(
+            r#"{{"isSidechain":{},"message":{{"role":"assistant","content":"ok","usage":{{"input_tokens":{},"output_tokens":0}}}}}}"#,
+            sidechain, tokens
+        )
+    }
+
+    #[test]
+    fn test_context_by_turn() {
+        let prompt = r#"{"message":{"role":"user","content":"go"}}"#;
+        let lines = vec![
+            line(1, LineType::User, prompt),
+            line(2, LineType::Assistant, &reply(10_000, false)),
+            line(3, LineType::Assistant, &reply(90_000, true)),
+            line(4, LineType::Assistant, &reply(30_000, false)),
+            line(5, LineType::User, prompt),
+            line(6, LineType::Assistant, &reply(50_000, false)),
+            line(7, LineType::Summary, "{}"),
+            line(8, LineType::Assistant, &reply(20_000, false)),
+            line(9, LineType::User, prompt),
+        ];
+
+        let turns = context_by_turn(&lines, None);
+        assert_eq!(turns.len(), 2);
+        assert_eq!((turns[0].turn, turns[0].context_tokens, turns[0].growth), (1, 30_000, 30_000));
+        assert_eq!((turns[0].percent, turns[0].context_size), (15.0, 200_000));
+        assert_eq!(context_by_turn(&lines, Some(1_000_000))[0].percent, 3.0);
+        assert!(!turns[0].compacted);
+        assert_eq!((turns[1].turn, turns[1].line_number, turns[1].peak_tokens), (2, 8, 50_000));
+        assert_eq!(turns[1].growth, -10_000);
+        assert!(turns[1].compacted);
+    }
+}
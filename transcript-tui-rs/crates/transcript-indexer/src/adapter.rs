@@ -1,213 +1,346 @@
-//! Adapter trait for extensible indexing
+//! Adapters for indexing the chat logs of tools other than Claude Code
 //!
-//! Defines a pluggable interface for indexing different data sources.
-//! Built-in adapters wrap the transcript and hook event indexers.
+//! An [`Adapter`] finds a tool's log files (Aider, Cursor, OpenAI chat
+//! exports, ...) and reads each of their lines as a transcript line or a bare
+//! Messages API message. Missing `sessionId`, `uuid`, `parentUuid`,
+//! `timestamp` and `type` fields are filled in as for streamed lines, and
+//! the lines are indexed like any transcript, so they show up in search, the
+//! viewers and the reports. Each log file is one session, read on from where
+//! the last run stopped (its cursor is kept in `adapter_cursors`).
+//!
+//! Logs of messages in OpenAI or Anthropic format, or of transcript lines,
+//! need no code: see `transcript_core::AdapterConfig`. Other sources
+//! implement the trait and are passed to [`index_adapters`].
 
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::Value;
 use std::path::{Path, PathBuf};
 
+use crate::archive;
 use crate::connection::IndexerError;
-
-/// Result of processing a file through an adapter
-#[derive(Debug, Default)]
-pub struct AdapterProcessResult {
-    pub entries_processed: usize,
-    pub byte_offset: u64,
-}
-
-/// Trait for indexing adapters
+use crate::diagnostics;
+use crate::discovery;
+use crate::import::{from_anthropic, from_openai};
+use crate::indexer::{parse_lines_with, write_transcript, IndexResult, ParsedTranscript, UpdateResult};
+use crate::prune;
+use crate::stream::normalize_stream_line;
+use transcript_core::{path_key, AdapterConfig, AdapterFormat, Config};
+
+/// A source of transcripts other than Claude Code's
 pub trait Adapter: Send + Sync {
-    /// Unique name for this adapter
+    /// Unique name, stored with each file's cursor
     fn name(&self) -> &str;
 
     /// Human-readable description
     fn description(&self) -> &str;
 
-    /// File extensions this adapter handles (e.g., [".jsonl"])
-    fn file_extensions(&self) -> &[&str];
-
-    /// Find all files this adapter can process
-    fn find_files(&self) -> Vec<PathBuf>;
-
-    /// Initialize any adapter-specific schema
-    fn init_schema(&self, conn: &Connection) -> Result<(), IndexerError>;
-
-    /// Process a file (full or delta)
-    fn process_file(
-        &self,
-        conn: &Connection,
-        file_path: &Path,
-        from_byte_offset: u64,
-        start_line: i64,
-    ) -> Result<AdapterProcessResult, IndexerError>;
-
-    /// Get the stored cursor (byte offset) for a file
-    fn get_cursor(&self, conn: &Connection, file_path: &Path) -> Result<u64, IndexerError>;
-
-    /// Save cursor position for a file
-    fn save_cursor(
-        &self,
-        conn: &Connection,
-        file_path: &Path,
-        byte_offset: u64,
-        line_count: i64,
-    ) -> Result<(), IndexerError>;
-}
-
-/// Built-in adapter for transcript JSONL files
-pub struct TranscriptLinesAdapter;
-
-impl Adapter for TranscriptLinesAdapter {
-    fn name(&self) -> &str {
-        "transcript-lines"
-    }
-
-    fn description(&self) -> &str {
-        "Indexes transcript JSONL files into lines and sessions tables"
-    }
-
-    fn file_extensions(&self) -> &[&str] {
-        &[".jsonl"]
-    }
-
-    fn find_files(&self) -> Vec<PathBuf> {
-        crate::discovery::find_transcript_files(None)
-    }
+    /// All log files to index
+    fn discover(&self) -> Vec<PathBuf>;
 
-    fn init_schema(&self, _conn: &Connection) -> Result<(), IndexerError> {
-        // Schema is handled by main init_schema
-        Ok(())
+    /// Session ID of a log file's lines (default: `<name>-<file stem>`)
+    fn session_id(&self, file_path: &Path) -> String {
+        let stem = file_path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+        format!("{}-{}", self.name(), stem)
     }
 
-    fn process_file(
-        &self,
-        conn: &Connection,
-        file_path: &Path,
-        from_byte_offset: u64,
-        start_line: i64,
-    ) -> Result<AdapterProcessResult, IndexerError> {
-        let result =
-            crate::indexer::index_transcript_file(conn, file_path, from_byte_offset, start_line)?;
-        Ok(AdapterProcessResult {
-            entries_processed: result.lines_indexed,
-            byte_offset: result.byte_offset,
-        })
-    }
+    /// A log line as a transcript line or bare message (`None` skips it)
+    fn parse_line(&self, line: &str) -> Option<Value>;
+}
 
-    fn get_cursor(&self, conn: &Connection, file_path: &Path) -> Result<u64, IndexerError> {
-        let file_path_str = file_path.to_string_lossy().to_string();
-        let offset: i64 = conn
-            .query_row(
-                "SELECT byte_offset FROM sessions WHERE file_path = ?1",
-                [&file_path_str],
-                |row| row.get(0),
-            )
-            .unwrap_or(0);
-        Ok(offset as u64)
-    }
+/// Adapter for a directory of JSONL logs, configured in the config file
+pub struct MessagesLogAdapter {
+    name: String,
+    description: String,
+    format: AdapterFormat,
+    dir: PathBuf,
+}
 
-    fn save_cursor(
-        &self,
-        _conn: &Connection,
-        _file_path: &Path,
-        _byte_offset: u64,
-        _line_count: i64,
-    ) -> Result<(), IndexerError> {
-        // Cursor is saved by index_transcript_file directly
-        Ok(())
+impl MessagesLogAdapter {
+    pub fn new(config: &AdapterConfig) -> Self {
+        let format = match config.format {
+            AdapterFormat::OpenAi => "OpenAI messages",
+            AdapterFormat::Anthropic => "Anthropic messages",
+            AdapterFormat::Transcript => "transcript lines",
+        };
+        let dir = config.dir_path();
+        Self {
+            name: config.name.trim().to_string(),
+            description: format!("{} in {}", format, dir.display()),
+            format: config.format,
+            dir,
+        }
     }
 }
 
-/// Built-in adapter for hook event JSONL files
-pub struct HookEventsAdapter;
-
-impl Adapter for HookEventsAdapter {
+impl Adapter for MessagesLogAdapter {
     fn name(&self) -> &str {
-        "hook-events"
+        &self.name
     }
 
     fn description(&self) -> &str {
-        "Indexes hook event JSONL files into hook_events and hook_files tables"
-    }
-
-    fn file_extensions(&self) -> &[&str] {
-        &[".hooks.jsonl"]
+        &self.description
     }
 
-    fn find_files(&self) -> Vec<PathBuf> {
-        crate::discovery::find_hook_files(None)
+    fn discover(&self) -> Vec<PathBuf> {
+        discovery::find_transcript_files(Some(&self.dir))
     }
 
-    fn init_schema(&self, _conn: &Connection) -> Result<(), IndexerError> {
-        // Schema is handled by main init_schema
-        Ok(())
+    fn parse_line(&self, line: &str) -> Option<Value> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        match self.format {
+            AdapterFormat::OpenAi => from_openai(&value),
+            AdapterFormat::Anthropic => from_anthropic(&value),
+            AdapterFormat::Transcript => Some(value),
+        }
     }
+}
 
-    fn process_file(
-        &self,
-        conn: &Connection,
-        file_path: &Path,
-        from_byte_offset: u64,
-        start_line: i64,
-    ) -> Result<AdapterProcessResult, IndexerError> {
-        let result =
-            crate::hook_indexer::index_hook_file(conn, file_path, from_byte_offset, start_line)?;
-        Ok(AdapterProcessResult {
-            entries_processed: result.events_indexed,
-            byte_offset: result.byte_offset,
-        })
-    }
+/// Adapters set up in the config file (entries without a name or directory
+/// are left out)
+pub fn configured_adapters() -> Vec<Box<dyn Adapter>> {
+    Config::load()
+        .adapters
+        .iter()
+        .filter(|a| !a.name.trim().is_empty() && !a.dir.trim().is_empty())
+        .map(|a| Box::new(MessagesLogAdapter::new(a)) as Box<dyn Adapter>)
+        .collect()
+}
 
-    fn get_cursor(&self, conn: &Connection, file_path: &Path) -> Result<u64, IndexerError> {
-        let file_path_str = file_path.to_string_lossy().to_string();
-        let offset: i64 = conn
-            .query_row(
-                "SELECT byte_offset FROM hook_files WHERE file_path = ?1",
-                [&file_path_str],
-                |row| row.get(0),
-            )
-            .unwrap_or(0);
-        Ok(offset as u64)
-    }
+/// Read an adapter's log file from `from_byte_offset` (no database access)
+///
+/// `previous_uuid` is the last line already indexed, for chaining parents.
+/// Lines without a timestamp are stamped with the file's modification time.
+pub fn parse_adapter_file(
+    adapter: &dyn Adapter,
+    file_path: &Path,
+    from_byte_offset: u64,
+    start_line_number: i64,
+    previous_uuid: Option<String>,
+) -> Result<ParsedTranscript, IndexerError> {
+    let session_id = adapter.session_id(file_path);
+    let modified = std::fs::metadata(file_path)
+        .and_then(|m| m.modified())
+        .map(chrono::DateTime::<chrono::Utc>::from)
+        .unwrap_or_else(|_| chrono::Utc::now())
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    let mut previous = previous_uuid;
+    parse_lines_with(file_path, from_byte_offset, start_line_number, |raw_line, line_number| {
+        let line = adapter.parse_line(raw_line)?;
+        let line = normalize_stream_line(line, &session_id, line_number, previous.as_deref(), &modified)?;
+        previous = line.get("uuid").and_then(|u| u.as_str()).map(String::from);
+        Some(line)
+    })
+}
 
-    fn save_cursor(
-        &self,
-        _conn: &Connection,
-        _file_path: &Path,
-        _byte_offset: u64,
-        _line_count: i64,
-    ) -> Result<(), IndexerError> {
-        // Cursor is saved by index_hook_file directly
-        Ok(())
+/// Index an adapter's log file from where the last run stopped
+pub fn index_adapter_file(
+    conn: &Connection,
+    adapter: &dyn Adapter,
+    file_path: &Path,
+) -> Result<IndexResult, IndexerError> {
+    let file_path_str = path_key(file_path);
+    // A cursor counts only while the file's lines are indexed (not pruned,
+    // trashed or rebuilt away)
+    let cursor: Option<(i64, i64)> = conn
+        .query_row(
+            "SELECT c.byte_offset, c.line_count FROM adapter_cursors c
+             JOIN sessions s ON s.file_path = c.file_path
+             WHERE c.file_path = ?1 AND c.adapter_name = ?2",
+            [&file_path_str, adapter.name()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let (from_offset, line_count) = cursor.map_or((0, 0), |(o, c)| (o as u64, c));
+    let previous_uuid: Option<String> = if from_offset > 0 {
+        conn.query_row(
+            "SELECT uuid FROM lines WHERE file_path = ?1 ORDER BY line_number DESC LIMIT 1",
+            [&file_path_str],
+            |row| row.get(0),
+        )
+        .optional()?
+    } else {
+        None
+    };
+
+    let parsed = parse_adapter_file(adapter, file_path, from_offset, line_count + 1, previous_uuid)?;
+    let result = write_transcript(conn, file_path, &parsed)?;
+    if result.byte_offset > from_offset {
+        conn.execute(
+            "INSERT OR REPLACE INTO adapter_cursors (file_path, adapter_name, byte_offset, line_count, last_processed)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                file_path_str,
+                adapter.name(),
+                result.byte_offset as i64,
+                parsed.line_count(),
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
     }
+    Ok(result)
 }
 
-/// Get all built-in adapters
-pub fn builtin_adapters() -> Vec<Box<dyn Adapter>> {
-    vec![
-        Box::new(TranscriptLinesAdapter),
-        Box::new(HookEventsAdapter),
-    ]
+/// Index the new lines of every adapter's log files
+///
+/// `on_progress` is called with the adapter name, file path and lines
+/// indexed for every file that had new lines.
+pub fn index_adapters<F>(
+    conn: &Connection,
+    adapters: &[Box<dyn Adapter>],
+    mut on_progress: F,
+) -> Result<UpdateResult, IndexerError>
+where
+    F: FnMut(&str, &str, usize),
+{
+    let mut result = UpdateResult::default();
+    for adapter in adapters {
+        for file in adapter.discover() {
+            result.files_checked += 1;
+            if archive::is_archived(conn, &file) || prune::is_deleted(conn, &file) {
+                continue;
+            }
+            match index_adapter_file(conn, adapter.as_ref(), &file) {
+                Ok(r) if r.lines_indexed > 0 => {
+                    diagnostics::file_indexed(conn, &file);
+                    result.files_updated += 1;
+                    result.new_lines += r.lines_indexed;
+                    result.large_payloads += r.large_payloads;
+                    result.recovered_lines += r.recovered_lines;
+                    on_progress(adapter.name(), &file.to_string_lossy(), r.lines_indexed);
+                }
+                Ok(_) => diagnostics::file_indexed(conn, &file),
+                Err(e) => diagnostics::file_failed(conn, &file, adapter.name(), &e),
+            }
+        }
+    }
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::init_schema;
+    use std::io::Write;
+
+    fn adapter(format: AdapterFormat, dir: &Path) -> MessagesLogAdapter {
+        MessagesLogAdapter::new(&AdapterConfig {
+            name: "aider".to_string(),
+            format,
+            dir: dir.to_string_lossy().to_string(),
+        })
+    }
 
     #[test]
-    fn test_builtin_adapters() {
-        let adapters = builtin_adapters();
-        assert_eq!(adapters.len(), 2);
-        assert_eq!(adapters[0].name(), "transcript-lines");
-        assert_eq!(adapters[1].name(), "hook-events");
+    fn test_parse_line_formats() {
+        let dir = Path::new("/logs");
+        let openai = adapter(AdapterFormat::OpenAi, dir);
+        let line = openai.parse_line(r#"{"role": "user", "content": "hi"}"#).unwrap();
+        assert_eq!(line["type"], "user");
+        assert!(openai.parse_line("not json").is_none());
+        assert!(openai.parse_line(r#"{"event": "start"}"#).is_none());
+
+        let transcript = adapter(AdapterFormat::Transcript, dir);
+        let line = transcript.parse_line(r#"{"type": "assistant", "message": {"role": "assistant", "content": "yo"}}"#);
+        assert_eq!(line.unwrap()["type"], "assistant");
+
+        assert_eq!(openai.session_id(Path::new("/logs/2024-06-01.jsonl")), "aider-2024-06-01");
+        assert_eq!(openai.description(), "OpenAI messages in /logs");
     }
 
     #[test]
-    fn test_adapter_file_extensions() {
-        let transcript = TranscriptLinesAdapter;
-        assert_eq!(transcript.file_extensions(), &[".jsonl"]);
+    fn test_index_adapter_delta() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = tmp.path().join("chat.jsonl");
+        std::fs::write(
+            &log,
+            "{\"role\": \"system\", \"content\": \"Be brief\"}\n{\"role\": \"user\", \"content\": \"refactor the parser\"}\n",
+        )
+        .unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+        let adapters: Vec<Box<dyn Adapter>> = vec![Box::new(adapter(AdapterFormat::OpenAi, tmp.path()))];
+
+        let result = index_adapters(&conn, &adapters, |_, _, _| {}).unwrap();
+        assert_eq!((result.files_updated, result.new_lines), (1, 2));
+
+        // Nothing new, then only the appended line (chained onto the last)
+        assert_eq!(index_adapters(&conn, &adapters, |_, _, _| {}).unwrap().new_lines, 0);
+        let mut file = std::fs::OpenOptions::new().append(true).open(&log).unwrap();
+        writeln!(file, "{{\"role\": \"assistant\", \"content\": \"Done\"}}").unwrap();
+        assert_eq!(index_adapters(&conn, &adapters, |_, _, _| {}).unwrap().new_lines, 1);
+
+        let (parent, line_number): (String, i64) = conn
+            .query_row(
+                "SELECT parent_uuid, line_number FROM lines WHERE type = 'assistant'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((parent.as_str(), line_number), ("aider-chat-2", 3));
+        let sessions: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sessions WHERE session_id = 'aider-chat'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sessions, 1);
+    }
 
-        let hooks = HookEventsAdapter;
-        assert_eq!(hooks.file_extensions(), &[".hooks.jsonl"]);
+    /// A third-party source: `> prompt` / `< reply` text logs
+    struct ArrowLog(PathBuf);
+
+    impl Adapter for ArrowLog {
+        fn name(&self) -> &str {
+            "arrow"
+        }
+
+        fn description(&self) -> &str {
+            "arrow-prefixed chat logs"
+        }
+
+        fn discover(&self) -> Vec<PathBuf> {
+            vec![self.0.clone()]
+        }
+
+        fn session_id(&self, _file_path: &Path) -> String {
+            "arrow-session".to_string()
+        }
+
+        fn parse_line(&self, line: &str) -> Option<Value> {
+            let (role, text) = match line.split_at_checked(2)? {
+                ("> ", text) => ("user", text),
+                ("< ", text) => ("assistant", text),
+                _ => return None,
+            };
+            Some(serde_json::json!({ "role": role, "content": text }))
+        }
+    }
+
+    #[test]
+    fn test_custom_adapter() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = tmp.path().join("log.txt");
+        std::fs::write(&log, "> rename the flag\n# comment\n< Renamed\n").unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+        let adapters: Vec<Box<dyn Adapter>> = vec![Box::new(ArrowLog(log))];
+
+        let mut progress = Vec::new();
+        let result = index_adapters(&conn, &adapters, |name, _, lines| progress.push((name.to_string(), lines))).unwrap();
+        assert_eq!(result.new_lines, 2);
+        assert_eq!(progress, [("arrow".to_string(), 2)]);
+
+        let mut stmt = conn
+            .prepare("SELECT session_id, type, content FROM lines ORDER BY line_number")
+            .unwrap();
+        let lines: Vec<(String, String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            lines,
+            [
+                ("arrow-session".to_string(), "user".to_string(), "rename the flag".to_string()),
+                ("arrow-session".to_string(), "assistant".to_string(), "Renamed".to_string()),
+            ]
+        );
     }
 }
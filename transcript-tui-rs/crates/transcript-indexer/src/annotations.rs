@@ -0,0 +1,140 @@
+//! Line annotations
+//!
+//! Free-text notes attached to a line, added with `transcript annotate` or
+//! the `a` key in the viewers. A reply points at the note it answers and sits
+//! on the same line. Like bookmarks they are keyed by line UUID, so they
+//! outlive rebuilds. Their text is kept in `annotations_fts` by triggers, so
+//! `transcript search` finds annotated lines by their notes.
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::connection::IndexerError;
+
+/// Annotate a line, or reply to an annotation; returns the new annotation's id
+///
+/// A reply is attached to its parent's line, whatever `uuid` and
+/// `line_number` are passed.
+pub fn add_annotation(
+    conn: &Connection,
+    session_id: &str,
+    uuid: &str,
+    line_number: i64,
+    author: &str,
+    body: &str,
+    parent_id: Option<i64>,
+) -> Result<i64, IndexerError> {
+    let (uuid, line_number) = match parent_id {
+        Some(parent) => conn
+            .query_row(
+                "SELECT uuid, line_number FROM annotations WHERE id = ?1 AND session_id = ?2",
+                rusqlite::params![parent, session_id],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+            )
+            .optional()?
+            .ok_or(IndexerError::AnnotationNotFound(parent))?,
+        None => (uuid.to_string(), line_number),
+    };
+
+    conn.execute(
+        "INSERT INTO annotations (session_id, uuid, line_number, parent_id, author, body, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            session_id,
+            uuid,
+            line_number,
+            parent_id,
+            author,
+            body,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Remove an annotation and its replies; returns how many were removed
+pub fn remove_annotation(conn: &Connection, id: i64) -> Result<usize, IndexerError> {
+    let removed = conn.execute(
+        "WITH RECURSIVE thread(id) AS (
+             SELECT ?1
+             UNION SELECT a.id FROM annotations a JOIN thread t ON a.parent_id = t.id
+         )
+         DELETE FROM annotations WHERE id IN (SELECT id FROM thread)",
+        [id],
+    )?;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::init_schema;
+
+    fn fts_matches(conn: &Connection, query: &str) -> Vec<i64> {
+        conn.prepare("SELECT rowid FROM annotations_fts WHERE annotations_fts MATCH ?1 ORDER BY rowid")
+            .unwrap()
+            .query_map([query], |row| row.get(0))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect()
+    }
+
+    #[test]
+    fn test_annotation_threads() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        let first = add_annotation(&conn, "s1", "u1", 3, "ann", "Why Bash here?", None).unwrap();
+        let reply = add_annotation(&conn, "s1", "", 0, "bob", "Grep was denied", Some(first)).unwrap();
+        add_annotation(&conn, "s1", "", 0, "ann", "Ok", Some(reply)).unwrap();
+        let other = add_annotation(&conn, "s1", "u2", 9, "ann", "Nice", None).unwrap();
+        assert!(add_annotation(&conn, "s2", "", 0, "ann", "x", Some(first)).is_err());
+
+        let line: (String, i64) = conn
+            .query_row("SELECT uuid, line_number FROM annotations WHERE id = ?1", [reply], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(line, ("u1".to_string(), 3));
+
+        crate::rebuild::rebuild_index(&mut conn).unwrap();
+        assert_eq!(fts_matches(&conn, "denied"), vec![reply]);
+
+        // Removing a note takes its replies, in the table and the search index
+        assert_eq!(remove_annotation(&conn, first).unwrap(), 3);
+        assert_eq!(remove_annotation(&conn, first).unwrap(), 0);
+        let left: Vec<i64> = conn
+            .prepare("SELECT id FROM annotations")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert_eq!(left, vec![other]);
+        assert!(fts_matches(&conn, "denied").is_empty());
+    }
+
+    #[test]
+    fn test_annotation_search_follows_edits() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        // Insert
+        let id = add_annotation(&conn, "s1", "u1", 3, "ann", "flaky retry loop", None).unwrap();
+        assert_eq!(fts_matches(&conn, "flaky"), vec![id]);
+        assert_eq!(fts_matches(&conn, "author:ann"), vec![id]);
+
+        // Update: the old text is no longer found, the new text is
+        conn.execute("UPDATE annotations SET body = 'stable now' WHERE id = ?1", [id])
+            .unwrap();
+        assert!(fts_matches(&conn, "flaky").is_empty());
+        assert_eq!(fts_matches(&conn, "stable"), vec![id]);
+
+        // Delete
+        assert_eq!(remove_annotation(&conn, id).unwrap(), 1);
+        assert!(fts_matches(&conn, "stable").is_empty());
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM annotations_fts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 0);
+    }
+}
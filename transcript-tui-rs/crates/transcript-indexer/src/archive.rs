@@ -0,0 +1,255 @@
+//! Moving old sessions to an archive database
+//!
+//! `transcript archive` keeps the everyday index small: sessions whose last
+//! line is older than a cutoff are copied, with their hook events, into a
+//! second database of the same schema (full-text search included) and then
+//! deleted here. Line IDs are kept, so tags, bookmarks and annotations still
+//! point at the right rows. Archived files are listed in `archived_files`, so
+//! updates and rebuilds don't index them again; readers query both databases
+//! with `transcript search --include-archive`.
+
+use std::path::Path;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use transcript_core::path_key;
+
+use crate::connection::{attach, IndexerDb, IndexerError};
+use crate::merge::shared_columns_in;
+
+/// Tables whose rows move with their file
+const ARCHIVED_TABLES: [&str; 4] = ["sessions", "lines", "hook_files", "hook_events"];
+
+/// A session old enough to archive
+#[derive(Debug, Clone)]
+pub struct ArchiveCandidate {
+    pub session_id: String,
+    pub slug: Option<String>,
+    pub file_path: String,
+    pub last_timestamp: String,
+    pub line_count: i64,
+}
+
+/// Rows moved to the archive
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ArchiveResult {
+    pub sessions: usize,
+    pub lines: usize,
+    pub hook_files: usize,
+    pub hook_events: usize,
+}
+
+/// Sessions whose last line is older than `before`, oldest first
+pub fn find_archivable(conn: &Connection, before: DateTime<Utc>) -> Result<Vec<ArchiveCandidate>, IndexerError> {
+    let mut stmt = conn.prepare(
+        "SELECT session_id, slug, file_path, last_timestamp, line_count FROM sessions
+         WHERE last_timestamp IS NOT NULL AND last_timestamp != '' AND last_timestamp < ?1
+         ORDER BY last_timestamp",
+    )?;
+    let rows = stmt.query_map([before.to_rfc3339_opts(SecondsFormat::Millis, true)], |row| {
+        Ok(ArchiveCandidate {
+            session_id: row.get(0)?,
+            slug: row.get(1)?,
+            file_path: row.get(2)?,
+            last_timestamp: row.get(3)?,
+            line_count: row.get(4)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// The sessions with these IDs, to archive regardless of age
+pub fn archive_candidates(conn: &Connection, session_ids: &[String]) -> Result<Vec<ArchiveCandidate>, IndexerError> {
+    let mut stmt = conn.prepare(
+        "SELECT session_id, slug, file_path, COALESCE(last_timestamp, ''), line_count FROM sessions
+         WHERE session_id = ?1",
+    )?;
+    let mut candidates = Vec::new();
+    for session_id in session_ids {
+        let rows = stmt.query_map([session_id], |row| {
+            Ok(ArchiveCandidate {
+                session_id: row.get(0)?,
+                slug: row.get(1)?,
+                file_path: row.get(2)?,
+                last_timestamp: row.get(3)?,
+                line_count: row.get(4)?,
+            })
+        })?;
+        for candidate in rows {
+            candidates.push(candidate?);
+        }
+    }
+    Ok(candidates)
+}
+
+/// Move `sessions` and their hook files into the archive at `archive_path`
+///
+/// The archive is created (or migrated) first. Rows already in the archive
+/// are kept as they are, and everything moves in one savepoint, so a failure
+/// leaves the index as it was. Must be called outside a transaction, as it
+/// attaches the archive.
+pub fn archive_sessions(
+    conn: &Connection,
+    archive_path: &Path,
+    sessions: &[ArchiveCandidate],
+) -> Result<ArchiveResult, IndexerError> {
+    if sessions.is_empty() {
+        return Ok(ArchiveResult::default());
+    }
+    drop(IndexerDb::open_or_create(archive_path)?);
+
+    attach(conn, archive_path, "archive")?;
+    conn.execute_batch("CREATE TEMP TABLE archiving (file_path TEXT PRIMARY KEY)")?;
+    conn.execute_batch("SAVEPOINT archive_sessions")?;
+    let result = move_rows(conn, archive_path, sessions);
+    match result {
+        Ok(_) => conn.execute_batch("RELEASE archive_sessions")?,
+        Err(_) => conn.execute_batch("ROLLBACK TO archive_sessions; RELEASE archive_sessions")?,
+    }
+    conn.execute_batch("DROP TABLE temp.archiving; DETACH DATABASE archive")?;
+    result
+}
+
+fn move_rows(
+    conn: &Connection,
+    archive_path: &Path,
+    sessions: &[ArchiveCandidate],
+) -> Result<ArchiveResult, IndexerError> {
+    // The transcript files, then the hook files of their sessions
+    let mut insert = conn.prepare("INSERT OR IGNORE INTO temp.archiving (file_path) VALUES (?1)")?;
+    for session in sessions {
+        insert.execute([&session.file_path])?;
+    }
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO temp.archiving (file_path)
+         SELECT file_path FROM main.hook_files WHERE session_id IN (
+             SELECT session_id FROM main.sessions WHERE file_path IN (SELECT file_path FROM temp.archiving)
+         )",
+    )?;
+
+    // Copy, keeping IDs
+    for table in ARCHIVED_TABLES {
+        let cols = shared_columns_in(conn, "archive", table, &[])?.join(", ");
+        conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO archive.{table} ({cols}) SELECT {cols} FROM main.{table}
+                 WHERE file_path IN (SELECT file_path FROM temp.archiving)"
+            ),
+            [],
+        )?;
+    }
+    // Payload sizes belong to lines (by session and UUID), not to files
+    let payloads = "EXISTS (
+        SELECT 1 FROM main.lines l
+        WHERE l.file_path IN (SELECT file_path FROM temp.archiving)
+          AND l.session_id = p.session_id AND l.uuid = p.uuid
+    )";
+    let cols = shared_columns_in(conn, "archive", "tool_payloads", &[])?;
+    let select: Vec<String> = cols.iter().map(|c| format!("p.{}", c)).collect();
+    conn.execute(
+        &format!(
+            "INSERT OR IGNORE INTO archive.tool_payloads ({}) SELECT {} FROM main.tool_payloads p WHERE {}",
+            cols.join(", "),
+            select.join(", "),
+            payloads
+        ),
+        [],
+    )?;
+
+    // Remember the files, so indexing leaves them alone from now on
+    conn.execute(
+        "INSERT OR REPLACE INTO main.archived_files (file_path, session_id, archive, archived_at)
+         SELECT file_path, session_id, ?1, ?2 FROM main.sessions
+         WHERE file_path IN (SELECT file_path FROM temp.archiving)
+         UNION ALL
+         SELECT file_path, session_id, ?1, ?2 FROM main.hook_files
+         WHERE file_path IN (SELECT file_path FROM temp.archiving)",
+        [path_key(archive_path), Utc::now().to_rfc3339()],
+    )?;
+
+    // Search entries go with their rows (the FTS delete triggers)
+    conn.execute(
+        &format!("DELETE FROM main.tool_payloads AS p WHERE {}", payloads),
+        [],
+    )?;
+    let delete = |table: &str| -> Result<usize, IndexerError> {
+        Ok(conn.execute(
+            &format!("DELETE FROM main.{table} WHERE file_path IN (SELECT file_path FROM temp.archiving)"),
+            [],
+        )?)
+    };
+    Ok(ArchiveResult {
+        lines: delete("lines")?,
+        sessions: delete("sessions")?,
+        hook_events: delete("hook_events")?,
+        hook_files: delete("hook_files")?,
+    })
+}
+
+/// Whether a file's rows were moved to the archive, so it needn't be read
+pub(crate) fn is_archived(conn: &Connection, file_path: &Path) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM archived_files WHERE file_path = ?1",
+        [path_key(file_path)],
+        |_| Ok(()),
+    )
+    .optional()
+    .ok()
+    .flatten()
+    .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_moves_old_sessions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive_path = tmp.path().join("archive.db");
+        let hot = IndexerDb::open_or_create(&tmp.path().join("transcripts.db")).unwrap();
+        let conn = hot.connection();
+        conn.execute_batch(
+            "INSERT INTO lines (id, session_id, uuid, line_number, type, timestamp, raw, file_path, content)
+             VALUES (7, 'old', 'u1', 1, 'user', '2024-01-01T00:00:00.000Z', '{}', '/p/old.jsonl', 'ancient history'),
+                    (8, 'new', 'u1', 1, 'user', '2099-01-01T00:00:00.000Z', '{}', '/p/new.jsonl', 'recent work');
+             INSERT INTO sessions (file_path, session_id, line_count, byte_offset, indexed_at, last_timestamp)
+             VALUES ('/p/old.jsonl', 'old', 1, 10, '2024-01-01', '2024-01-01T00:00:00.000Z'),
+                    ('/p/new.jsonl', 'new', 1, 10, '2099-01-01', '2099-01-01T00:00:00.000Z');
+             INSERT INTO hook_events (session_id, timestamp, event_type, file_path, line_number)
+             VALUES ('old', '2024-01-01T00:00:00Z', 'PreToolUse', '/h/old.hooks.jsonl', 1);
+             INSERT INTO hook_files (file_path, session_id, event_count, byte_offset, indexed_at)
+             VALUES ('/h/old.hooks.jsonl', 'old', 1, 10, '2024-01-01');",
+        )
+        .unwrap();
+
+        let candidates = find_archivable(conn, Utc::now()).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].session_id, "old");
+
+        let result = archive_sessions(conn, &archive_path, &candidates).unwrap();
+        assert_eq!(
+            (result.sessions, result.lines, result.hook_files, result.hook_events),
+            (1, 1, 1, 1)
+        );
+        assert!(find_archivable(conn, Utc::now()).unwrap().is_empty());
+        assert!(is_archived(conn, Path::new("/p/old.jsonl")));
+        assert!(is_archived(conn, Path::new("/h/old.hooks.jsonl")));
+        assert!(!is_archived(conn, Path::new("/p/new.jsonl")));
+
+        // Searchable in the archive, under the same line ID
+        let archive = Connection::open(&archive_path).unwrap();
+        let id: i64 = archive
+            .query_row(
+                "SELECT rowid FROM lines_fts WHERE lines_fts MATCH 'ancient'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(id, 7);
+        let hot_hits: i64 = conn
+            .query_row("SELECT COUNT(*) FROM lines_fts WHERE lines_fts MATCH 'ancient'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(hot_hits, 0);
+    }
+}
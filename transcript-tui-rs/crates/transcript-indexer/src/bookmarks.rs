@@ -0,0 +1,60 @@
+//! Line bookmarks
+//!
+//! Bookmarks are set in the viewers and listed by `transcript bookmarks`.
+//! They are stored by line UUID rather than row ID, so they outlive rebuilds.
+
+use rusqlite::Connection;
+
+use crate::connection::IndexerError;
+
+/// Bookmark a line; returns false if it was already bookmarked
+pub fn add_bookmark(
+    conn: &Connection,
+    session_id: &str,
+    uuid: &str,
+    line_number: i64,
+) -> Result<bool, IndexerError> {
+    let added = conn.execute(
+        "INSERT OR IGNORE INTO bookmarks (session_id, uuid, line_number, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![session_id, uuid, line_number, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(added > 0)
+}
+
+/// Remove a line's bookmark; returns false if it wasn't bookmarked
+pub fn remove_bookmark(conn: &Connection, session_id: &str, uuid: &str) -> Result<bool, IndexerError> {
+    let removed = conn.execute(
+        "DELETE FROM bookmarks WHERE session_id = ?1 AND uuid = ?2",
+        [session_id, uuid],
+    )?;
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::init_schema;
+
+    #[test]
+    fn test_bookmarks_survive_rebuild() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        assert!(add_bookmark(&conn, "s1", "u1", 3).unwrap());
+        assert!(!add_bookmark(&conn, "s1", "u1", 3).unwrap());
+        assert!(add_bookmark(&conn, "s1", "u2", 7).unwrap());
+        assert!(remove_bookmark(&conn, "s1", "u2").unwrap());
+
+        crate::rebuild::rebuild_index(&mut conn).unwrap();
+
+        let kept: Vec<(String, i64)> = conn
+            .prepare("SELECT uuid, line_number FROM bookmarks WHERE session_id = 's1'")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert_eq!(kept, vec![("u1".to_string(), 3)]);
+    }
+}
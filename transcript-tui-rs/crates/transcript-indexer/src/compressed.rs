@@ -0,0 +1,150 @@
+//! Gzip and zstd compressed JSONL files (`.jsonl.gz`, `.jsonl.zst`)
+//!
+//! Old session files are often compressed to save space. They are indexed
+//! like plain ones, with offsets counted in decompressed bytes. A compressed
+//! stream can't seek, so a delta read decompresses up to its offset first,
+//! and its size on disk says nothing about how much text it holds: such a
+//! file is read again only when modified since it was last indexed. Files
+//! appended to as further gzip members or zstd frames pick up where they
+//! left off; any other rewrite is found by `index prune` as truncated.
+
+use std::fs::{File, Metadata};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+/// How a file is compressed, by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    const ALL: [(Compression, &'static str); 2] = [(Compression::Gzip, ".gz"), (Compression::Zstd, ".zst")];
+
+    /// The compression of `path`, or None for a plain file
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        Self::ALL.iter().find(|(_, ext)| name.ends_with(ext)).map(|(c, _)| *c)
+    }
+
+    fn decoder(self, file: File) -> io::Result<Box<dyn Read>> {
+        Ok(match self {
+            // Every member, so gzip output appended to the file is read too
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(file)?),
+        })
+    }
+}
+
+/// A file name without its compression extension (`s.jsonl.gz` -> `s.jsonl`)
+pub fn strip_compression(name: &str) -> &str {
+    Compression::ALL
+        .iter()
+        .find_map(|(_, ext)| name.strip_suffix(ext))
+        .unwrap_or(name)
+}
+
+/// A transcript's name without `.jsonl` and any compression extension,
+/// which Claude Code makes the session ID
+pub(crate) fn session_stem(path: &Path) -> Option<&str> {
+    let name = strip_compression(path.file_name()?.to_str()?);
+    Some(name.strip_suffix(".jsonl").unwrap_or(name))
+}
+
+/// The file's contents from `offset` (in decompressed bytes)
+pub(crate) fn open_at(path: &Path, offset: u64) -> io::Result<Box<dyn BufRead>> {
+    let mut file = File::open(path)?;
+    match Compression::of(path) {
+        None => {
+            if offset > 0 {
+                file.seek(SeekFrom::Start(offset))?;
+            }
+            Ok(Box::new(BufReader::new(file)))
+        }
+        Some(compression) => {
+            let mut reader = BufReader::new(compression.decoder(file)?);
+            io::copy(&mut (&mut reader).take(offset), &mut io::sink())?;
+            Ok(Box::new(reader))
+        }
+    }
+}
+
+/// The whole file, decompressed
+pub fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open_at(path, 0)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Size of a plain file; None for a compressed one, whose size on disk
+/// isn't the length of its text
+pub(crate) fn plain_len(path: &Path) -> io::Result<Option<u64>> {
+    let metadata = std::fs::metadata(path)?;
+    Ok(Compression::of(path).is_none().then_some(metadata.len()))
+}
+
+/// Length of the file's text, decompressing a compressed file to count it
+pub(crate) fn text_len(path: &Path) -> io::Result<u64> {
+    match Compression::of(path) {
+        None => Ok(std::fs::metadata(path)?.len()),
+        Some(_) => io::copy(&mut open_at(path, 0)?, &mut io::sink()),
+    }
+}
+
+/// Whether a file indexed up to `offset` at `indexed_at` (RFC 3339) may
+/// have more to read: by size for plain files, by modification time for
+/// compressed ones
+pub(crate) fn may_have_grown(path: &Path, metadata: &Metadata, offset: u64, indexed_at: Option<&str>) -> bool {
+    if Compression::of(path).is_none() {
+        return metadata.len() > offset;
+    }
+    let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+    let indexed = indexed_at
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc));
+    match (modified, indexed) {
+        (Some(modified), Some(indexed)) => modified > indexed,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_compressed_files_read_from_offsets() {
+        let tmp = tempfile::tempdir().unwrap();
+        let text = b"{\"a\":1}\n{\"b\":2}\n";
+
+        let gz = tmp.path().join("s1.jsonl.gz");
+        let mut encoder = flate2::write::GzEncoder::new(File::create(&gz).unwrap(), flate2::Compression::default());
+        encoder.write_all(text).unwrap();
+        encoder.finish().unwrap();
+        // A second member appended later
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::OpenOptions::new().append(true).open(&gz).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(b"{\"c\":3}\n").unwrap();
+        encoder.finish().unwrap();
+
+        let zst = tmp.path().join("s2.jsonl.zst");
+        std::fs::write(&zst, zstd::encode_all(&text[..], 0).unwrap()).unwrap();
+
+        let mut rest = String::new();
+        open_at(&gz, 8).unwrap().read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "{\"b\":2}\n{\"c\":3}\n");
+        assert_eq!(text_len(&gz).unwrap(), 24);
+        assert_eq!(read_file(&zst).unwrap(), text);
+        assert_eq!(plain_len(&zst).unwrap(), None);
+
+        assert_eq!(session_stem(&gz), Some("s1"));
+        assert_eq!(strip_compression("e.hooks.jsonl.zst"), "e.hooks.jsonl");
+        assert_eq!(Compression::of(Path::new("s.jsonl")), None);
+    }
+}
@@ -1,8 +1,9 @@
 //! Read-write database connection for indexing operations
 
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::{Connection, ErrorCode, OpenFlags};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
+use transcript_core::{database_key, key_pragma, KeyError, DB_KEY_ENV};
 
 use crate::schema;
 
@@ -23,14 +24,72 @@ pub enum IndexerError {
 
     #[error("Schema migration failed: {0}")]
     Migration(String),
+
+    #[error("Import failed: {0}")]
+    Import(String),
+
+    #[error("No annotation {0} in this session")]
+    AnnotationNotFound(i64),
+
+    #[error("No trash bundle matching {0}")]
+    TrashBundleNotFound(String),
+
+    #[error("Adapter '{0}' is no longer configured")]
+    AdapterNotConfigured(String),
+
+    #[error("Database schema v{found} was written by a newer indexer (this one supports v{supported}); upgrade transcript before writing to it")]
+    SchemaTooNew { found: i32, supported: i32 },
+
+    #[error("Cannot read {}: it is encrypted and the key is missing or wrong (set {}), or it is not a database", .0.display(), DB_KEY_ENV)]
+    Locked(PathBuf),
+
+    #[error("A database key is set but encryption is not built in (enable the encryption feature)")]
+    EncryptionUnavailable,
+
+    #[error("{0}")]
+    Key(#[from] KeyError),
 }
 
 /// Default database path
 pub fn default_db_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
-    PathBuf::from(home)
-        .join(".claude-code-sdk")
-        .join("transcripts.db")
+    transcript_core::sdk_dir().join("transcripts.db")
+}
+
+/// Unlock `conn` with the configured database key, if any (see
+/// `transcript_core::encryption`), and check that it can be read
+///
+/// Must come before any other statement. Databases attached with `attach`
+/// use the same key, so archives, trash bundles and snapshots are encrypted
+/// too.
+pub(crate) fn unlock(conn: &Connection, path: &Path) -> Result<(), IndexerError> {
+    if let Some(key) = database_key()? {
+        if !cfg!(feature = "encryption") {
+            return Err(IndexerError::EncryptionUnavailable);
+        }
+        conn.execute_batch(&key_pragma(&key))?;
+    }
+    check_readable(conn, path)
+}
+
+/// Attach the database at `path` as `schema`, with the database key (a new
+/// file is created encrypted; without a key, attached files are plain)
+pub(crate) fn attach(conn: &Connection, path: &Path, schema: &str) -> Result<(), IndexerError> {
+    let key = database_key()?.unwrap_or_default();
+    conn.execute(
+        &format!("ATTACH DATABASE ?1 AS {} KEY ?2", schema),
+        [&*path.to_string_lossy(), &key],
+    )?;
+    Ok(())
+}
+
+/// `Locked` for a file SQLite can't read with the key it was given
+pub(crate) fn check_readable(conn: &Connection, path: &Path) -> Result<(), IndexerError> {
+    match conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(())) {
+        Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == ErrorCode::NotADatabase => {
+            Err(IndexerError::Locked(path.to_path_buf()))
+        }
+        result => Ok(result?),
+    }
 }
 
 /// Read-write database connection for indexing
@@ -58,10 +117,14 @@ impl IndexerDb {
                 | OpenFlags::SQLITE_OPEN_CREATE
                 | OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )?;
+        unlock(&conn, path)?;
 
         // Set WAL mode and synchronous for better concurrent performance
+        // (auto_vacuum only takes effect on a new database; existing ones are
+        // switched by `index maintain`)
         conn.execute_batch(
-            "PRAGMA journal_mode = WAL;
+            "PRAGMA auto_vacuum = INCREMENTAL;
+             PRAGMA journal_mode = WAL;
              PRAGMA synchronous = NORMAL;
              PRAGMA foreign_keys = OFF;",
         )?;
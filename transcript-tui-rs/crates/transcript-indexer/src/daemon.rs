@@ -1,17 +1,38 @@
 //! File watching daemon for real-time indexing
 //!
-//! Uses the `notify` crate with debouncing for efficient file system monitoring.
-//! Falls back to polling when native events are unreliable.
+//! Uses the `notify` crate (inotify, FSEvents, ReadDirectoryChangesW) with a
+//! short debounce, so appends are indexed within milliseconds and the daemon
+//! sleeps while nothing changes. A full rescan runs when no events arrive for
+//! `RESCAN_INTERVAL`, to catch anything the watcher missed. A directory that
+//! can't be watched is polled every `POLL_INTERVAL`, and watching it is
+//! retried each time, so the others keep the idle sleep.
+//!
+//! Each batch of new lines is announced in the `changes` table, which live
+//! viewers, `view --follow` and `serve` watch instead of polling `lines`.
 
-use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
+use notify::{RecursiveMode, Watcher};
+use notify_debouncer_mini::new_debouncer;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc;
-use std::time::Duration;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use transcript_core::path_key;
 
 use crate::connection::{IndexerDb, IndexerError};
 use crate::correlation::correlate_lines_to_turns;
-use crate::hook_indexer::index_hook_file;
-use crate::indexer::index_transcript_file;
+use crate::diagnostics::{file_indexed, record_index_error};
+use crate::compressed::strip_compression;
+use crate::hook_indexer::update_hook_file;
+use crate::indexer::update_transcript_file;
+use crate::schema::record_writer;
+
+/// Events for the same file within this window are handled once
+pub const DEBOUNCE: Duration = Duration::from_millis(20);
+
+/// Idle time after which directories are rescanned anyway
+pub const RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Poll interval for directories that can't be watched natively (yet)
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Indexer daemon that watches for file changes and auto-indexes
 pub struct IndexerDaemon {
@@ -50,92 +71,80 @@ impl IndexerDaemon {
 
         let (tx, rx) = mpsc::channel();
 
-        // Create debounced watcher (100ms debounce)
-        let mut debouncer = new_debouncer(Duration::from_millis(100), tx.clone())
-            .map_err(|e| IndexerError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-
-        // Watch both directories recursively
-        let watcher = debouncer.watcher();
-        if self.projects_dir.exists() {
-            watcher
-                .watch(&self.projects_dir, notify::RecursiveMode::Recursive)
-                .map_err(|e| {
-                    IndexerError::Io(std::io::Error::new(std::io::ErrorKind::Other, e))
-                })?;
-        }
-        if self.hooks_dir.exists() {
-            watcher
-                .watch(&self.hooks_dir, notify::RecursiveMode::Recursive)
-                .map_err(|e| {
-                    IndexerError::Io(std::io::Error::new(std::io::ErrorKind::Other, e))
-                })?;
+        let mut debouncer = new_debouncer(DEBOUNCE, tx)
+            .map_err(|e| IndexerError::Io(std::io::Error::other(e)))?;
+
+        // Watch both directories recursively; any that can't be watched yet
+        // (e.g. if creating it failed) are polled until watching succeeds
+        let mut unwatched = watch_dirs(debouncer.watcher(), &[&self.projects_dir, &self.hooks_dir]);
+        for dir in &unwatched {
+            eprintln!(
+                "[daemon] Cannot watch {}, polling every {}s",
+                dir.display(),
+                POLL_INTERVAL.as_secs()
+            );
         }
 
-        eprintln!("[daemon] Watching for changes... (Ctrl+C to stop)");
+        // Catch up on anything written while the daemon wasn't running
+        self.rescan(db);
 
-        // Also run a polling fallback every second
-        let poll_tx = tx.clone();
-        let projects_dir = self.projects_dir.clone();
-        let hooks_dir = self.hooks_dir.clone();
-
-        std::thread::spawn(move || {
-            loop {
-                std::thread::sleep(Duration::from_secs(1));
-                // Send a synthetic event to trigger polling check
-                let _ = poll_tx.send(Ok(vec![notify_debouncer_mini::DebouncedEvent {
-                    path: projects_dir.clone(),
-                    kind: DebouncedEventKind::Any,
-                }]));
-                let _ = poll_tx.send(Ok(vec![notify_debouncer_mini::DebouncedEvent {
-                    path: hooks_dir.clone(),
-                    kind: DebouncedEventKind::Any,
-                }]));
-            }
-        });
+        eprintln!("[daemon] Watching for changes... (Ctrl+C to stop)");
 
-        // Process events
+        // Process events, sleeping until one arrives or the rescan is due
+        let mut idle_since = Instant::now();
         loop {
-            match rx.recv() {
+            match rx.recv_timeout(next_timeout(&unwatched, idle_since.elapsed())) {
                 Ok(Ok(events)) => {
                     let mut indexed_hooks = false;
 
                     for event in events {
                         let path = &event.path;
 
-                        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                        if let Some(name) = path.file_name().and_then(|n| n.to_str()).map(strip_compression) {
                             if name.ends_with(".hooks.jsonl") {
-                                self.handle_hook_file_change(db, path);
-                                indexed_hooks = true;
+                                indexed_hooks |= self.handle_hook_file_change(db, path);
+                                continue;
                             } else if name.ends_with(".jsonl") {
                                 self.handle_transcript_file_change(db, path);
+                                continue;
                             }
-                        } else if path.is_dir() {
+                        }
+                        if path.is_dir() {
                             // Directory-level event - check for new/changed files
                             self.poll_directory(db, path, &mut indexed_hooks);
                         }
                     }
 
-                    // After hook events are indexed, run correlation
                     if indexed_hooks {
-                        match correlate_lines_to_turns(db.connection()) {
-                            Ok(result) => {
-                                if result.updated > 0 {
-                                    eprintln!(
-                                        "[daemon] Correlated {} lines across {} sessions",
-                                        result.updated, result.sessions
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("[daemon] Correlation error: {}", e);
-                            }
-                        }
+                        correlate(db);
                     }
+                    idle_since = Instant::now();
                 }
                 Ok(Err(errors)) => {
                     eprintln!("[daemon] Watch error: {:?}", errors);
                 }
-                Err(_) => {
+                Err(RecvTimeoutError::Timeout) => {
+                    if !unwatched.is_empty() {
+                        let polled = std::mem::take(&mut unwatched);
+                        let dirs: Vec<&Path> = polled.iter().map(PathBuf::as_path).collect();
+                        let mut indexed_hooks = false;
+                        for dir in &dirs {
+                            self.poll_directory(db, dir, &mut indexed_hooks);
+                        }
+                        if indexed_hooks {
+                            correlate(db);
+                        }
+                        unwatched = watch_dirs(debouncer.watcher(), &dirs);
+                        for dir in dirs.iter().filter(|d| !unwatched.iter().any(|u| u == *d)) {
+                            eprintln!("[daemon] Now watching {}", dir.display());
+                        }
+                    }
+                    if idle_since.elapsed() >= RESCAN_INTERVAL {
+                        self.rescan(db);
+                        idle_since = Instant::now();
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
                     eprintln!("[daemon] Channel closed, shutting down");
                     break;
                 }
@@ -145,38 +154,30 @@ impl IndexerDaemon {
         Ok(())
     }
 
-    fn handle_transcript_file_change(&self, db: &mut IndexerDb, path: &Path) {
-        if !path.exists() || !path.is_file() {
-            return;
+    /// Index anything new in both directories
+    fn rescan(&self, db: &mut IndexerDb) {
+        let mut indexed_hooks = false;
+        self.poll_directory(db, &self.projects_dir, &mut indexed_hooks);
+        self.poll_directory(db, &self.hooks_dir, &mut indexed_hooks);
+        if indexed_hooks {
+            correlate(db);
         }
+    }
 
-        let file_path_str = path.to_string_lossy().to_string();
-        let conn = db.connection();
-
-        // Get current state
-        let state: Option<(i64, i64)> = conn
-            .query_row(
-                "SELECT byte_offset, line_count FROM sessions WHERE file_path = ?1",
-                [&file_path_str],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .ok();
-
-        let file_size = match std::fs::metadata(path) {
-            Ok(m) => m.len(),
-            Err(_) => return,
-        };
-
-        let from_offset = state.map(|(o, _)| o as u64).unwrap_or(0);
-        if from_offset >= file_size {
+    fn handle_transcript_file_change(&self, db: &mut IndexerDb, path: &Path) {
+        if !path.is_file() {
             return;
         }
+        let conn = db.connection();
 
-        let start_line = state.map(|(_, c)| c + 1).unwrap_or(1);
-
-        match index_transcript_file(conn, path, from_offset, start_line) {
-            Ok(result) => {
+        match update_transcript_file(conn, path) {
+            Ok(None) => {}
+            Ok(Some(result)) => {
+                file_indexed(conn, path);
                 if result.lines_indexed > 0 {
+                    if let Err(e) = record_writer(conn) {
+                        eprintln!("[daemon] Error recording indexer version: {}", e);
+                    }
                     eprintln!(
                         "[daemon] Indexed {} new transcript lines from {}",
                         result.lines_indexed,
@@ -186,50 +187,38 @@ impl IndexerDaemon {
             }
             Err(e) => {
                 eprintln!("[daemon] Error indexing {}: {}", path.display(), e);
+                let _ = record_index_error(conn, &path_key(path), "transcript", 0, &e.to_string());
             }
         }
     }
 
-    fn handle_hook_file_change(&self, db: &mut IndexerDb, path: &Path) {
-        if !path.exists() || !path.is_file() {
-            return;
+    /// Index what a hook file gained, returning whether it had new events
+    fn handle_hook_file_change(&self, db: &mut IndexerDb, path: &Path) -> bool {
+        if !path.is_file() {
+            return false;
         }
-
-        let file_path_str = path.to_string_lossy().to_string();
         let conn = db.connection();
 
-        let state: Option<(i64, i64)> = conn
-            .query_row(
-                "SELECT byte_offset, event_count FROM hook_files WHERE file_path = ?1",
-                [&file_path_str],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .ok();
-
-        let file_size = match std::fs::metadata(path) {
-            Ok(m) => m.len(),
-            Err(_) => return,
-        };
-
-        let from_offset = state.map(|(o, _)| o as u64).unwrap_or(0);
-        if from_offset >= file_size {
-            return;
-        }
-
-        let start_line = state.map(|(_, c)| c + 1).unwrap_or(1);
-
-        match index_hook_file(conn, path, from_offset, start_line) {
-            Ok(result) => {
+        match update_hook_file(conn, path) {
+            Ok(None) => false,
+            Ok(Some(result)) => {
+                file_indexed(conn, path);
                 if result.events_indexed > 0 {
+                    if let Err(e) = record_writer(conn) {
+                        eprintln!("[daemon] Error recording indexer version: {}", e);
+                    }
                     eprintln!(
                         "[daemon] Indexed {} new hook events from {}",
                         result.events_indexed,
                         path.display()
                     );
                 }
+                result.events_indexed > 0
             }
             Err(e) => {
                 eprintln!("[daemon] Error indexing hook file {}: {}", path.display(), e);
+                let _ = record_index_error(conn, &path_key(path), "hooks", 0, &e.to_string());
+                false
             }
         }
     }
@@ -247,27 +236,7 @@ impl IndexerDaemon {
         if dir.starts_with(&self.hooks_dir) || dir == self.hooks_dir {
             let files = crate::discovery::find_hook_files(Some(&self.hooks_dir));
             for file in &files {
-                let file_path_str = file.to_string_lossy().to_string();
-                let conn = db.connection();
-
-                let state: Option<(i64,)> = conn
-                    .query_row(
-                        "SELECT byte_offset FROM hook_files WHERE file_path = ?1",
-                        [&file_path_str],
-                        |row| Ok((row.get(0)?,)),
-                    )
-                    .ok();
-
-                let file_size = match std::fs::metadata(file) {
-                    Ok(m) => m.len(),
-                    Err(_) => continue,
-                };
-
-                let offset = state.map(|(o,)| o as u64).unwrap_or(0);
-                if offset < file_size {
-                    self.handle_hook_file_change(db, file);
-                    *indexed_hooks = true;
-                }
+                *indexed_hooks |= self.handle_hook_file_change(db, file);
             }
         }
     }
@@ -278,3 +247,134 @@ impl Default for IndexerDaemon {
         Self::new()
     }
 }
+
+/// Watch each directory recursively, returning those that couldn't be watched
+fn watch_dirs(watcher: &mut dyn Watcher, dirs: &[&Path]) -> Vec<PathBuf> {
+    dirs.iter()
+        .filter(|dir| watcher.watch(dir, RecursiveMode::Recursive).is_err())
+        .map(|dir| dir.to_path_buf())
+        .collect()
+}
+
+/// How long to wait for events before polling or rescanning
+fn next_timeout(unwatched: &[PathBuf], idle: Duration) -> Duration {
+    if unwatched.is_empty() {
+        RESCAN_INTERVAL.saturating_sub(idle)
+    } else {
+        POLL_INTERVAL
+    }
+}
+
+/// Link transcript lines to turns after hook events were indexed
+fn correlate(db: &IndexerDb) {
+    match correlate_lines_to_turns(db.connection()) {
+        Ok(result) => {
+            if result.updated > 0 {
+                eprintln!(
+                    "[daemon] Correlated {} lines across {} sessions",
+                    result.updated, result.sessions
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("[daemon] Correlation error: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::RecommendedWatcher;
+
+    const LINE: &str = r#"{"sessionId":"s1","uuid":"u1","type":"user","timestamp":"2024-01-01T00:00:00Z","message":{"role":"user","content":"hi"}}"#;
+
+    fn line_count(db: &IndexerDb) -> i64 {
+        db.connection().query_row("SELECT COUNT(*) FROM lines", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_rescan_skips_archived_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let projects = tmp.path().join("projects");
+        let hooks = tmp.path().join("hooks");
+        std::fs::create_dir_all(projects.join("app")).unwrap();
+        std::fs::create_dir_all(&hooks).unwrap();
+        let file = projects.join("app").join("s1.jsonl");
+        std::fs::write(&file, format!("{}\n", LINE)).unwrap();
+
+        let mut db = IndexerDb::open_or_create(&tmp.path().join("index.db")).unwrap();
+        let daemon = IndexerDaemon::with_dirs(projects, hooks);
+        daemon.rescan(&mut db);
+        assert_eq!(line_count(&db), 1);
+
+        // Archived: its rows moved out, and a later write must not bring them back
+        db.connection()
+            .execute_batch("DELETE FROM lines; DELETE FROM sessions;")
+            .unwrap();
+        db.connection()
+            .execute(
+                "INSERT INTO archived_files (file_path, session_id, archive, archived_at)
+                 VALUES (?1, 's1', 'archive.db', '2024-02-01T00:00:00Z')",
+                [path_key(&file)],
+            )
+            .unwrap();
+        std::fs::write(&file, format!("{}\n{}\n", LINE, LINE.replace("u1", "u2"))).unwrap();
+        daemon.handle_transcript_file_change(&mut db, &file);
+        daemon.rescan(&mut db);
+        assert_eq!(line_count(&db), 0);
+    }
+
+    #[test]
+    fn test_rescan_skips_deleted_and_ignored_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let projects = tmp.path().join("projects").join("app");
+        let hooks = tmp.path().join("hooks");
+        std::fs::create_dir_all(&projects).unwrap();
+        std::fs::create_dir_all(&hooks).unwrap();
+        let deleted = projects.join("s1.jsonl");
+        let ignored = projects.join("s2.jsonl");
+        std::fs::write(&deleted, format!("{}\n", LINE)).unwrap();
+        std::fs::write(&ignored, format!("{}\n", LINE.replace("s1", "s2"))).unwrap();
+
+        let mut db = IndexerDb::open_or_create(&tmp.path().join("index.db")).unwrap();
+        db.connection()
+            .execute(
+                "INSERT INTO deleted_files (file_path, bundle, deleted_at) VALUES (?1, 'b1', '2024-02-01T00:00:00Z')",
+                [path_key(&deleted)],
+            )
+            .unwrap();
+        crate::ignore::record_ignored(db.connection(), "s2", None, None).unwrap();
+
+        let daemon = IndexerDaemon::with_dirs(tmp.path().join("projects"), hooks);
+        daemon.handle_transcript_file_change(&mut db, &deleted);
+        daemon.rescan(&mut db);
+        assert_eq!(line_count(&db), 0);
+    }
+
+    #[test]
+    fn test_missing_dir_watched_once_created() {
+        let tmp = tempfile::tempdir().unwrap();
+        let present = tmp.path().join("projects");
+        let missing = tmp.path().join("hooks");
+        std::fs::create_dir(&present).unwrap();
+
+        let mut watcher = RecommendedWatcher::new(|_| {}, notify::Config::default()).unwrap();
+        let unwatched = watch_dirs(&mut watcher, &[&present, &missing]);
+        assert_eq!(unwatched, std::slice::from_ref(&missing));
+
+        std::fs::create_dir(&missing).unwrap();
+        assert!(watch_dirs(&mut watcher, &[&missing]).is_empty());
+    }
+
+    #[test]
+    fn test_next_timeout() {
+        // Watched directories sleep until the rescan is due
+        assert_eq!(next_timeout(&[], Duration::ZERO), RESCAN_INTERVAL);
+        assert_eq!(next_timeout(&[], Duration::from_secs(45)), Duration::from_secs(15));
+        assert_eq!(next_timeout(&[], RESCAN_INTERVAL * 2), Duration::ZERO);
+
+        // Only an unwatched directory falls back to polling
+        assert_eq!(next_timeout(&[PathBuf::from("/missing")], Duration::ZERO), POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,198 @@
+//! Slow-query and indexing error logs
+//!
+//! Readers time their statements (see `transcript_db::timing`) and hand the
+//! slow ones here, as the indexer owns all writes. The log keeps the most
+//! recent `MAX_LOGGED_QUERIES` entries for `transcript doctor`.
+//!
+//! Indexing carries on past a file it can't read or write and past lines
+//! that aren't JSON. Both go to `index_errors` as well as stderr, for
+//! `transcript index errors` to list and retry: one entry per file (line 0)
+//! or line, counting how often it was seen. A file's entry goes once the file
+//! indexes cleanly; the log keeps the `MAX_INDEX_ERRORS` most recent.
+
+use std::path::Path;
+
+use rusqlite::Connection;
+use transcript_core::path_key;
+
+use crate::connection::IndexerError;
+
+/// Entries kept in the log; older ones are dropped as new ones arrive
+pub const MAX_LOGGED_QUERIES: i64 = 500;
+
+/// Log slow statements, as (SQL, milliseconds), run by `command`
+pub fn record_slow_queries(conn: &Connection, command: &str, queries: &[(String, u64)]) -> Result<(), IndexerError> {
+    if queries.is_empty() {
+        return Ok(());
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    for (sql, millis) in queries {
+        conn.execute(
+            "INSERT INTO slow_queries (recorded_at, command, sql, millis) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![now, command, sql, *millis as i64],
+        )?;
+    }
+    conn.execute(
+        "DELETE FROM slow_queries WHERE id <= (SELECT MAX(id) FROM slow_queries) - ?1",
+        [MAX_LOGGED_QUERIES],
+    )?;
+    Ok(())
+}
+
+/// Entries kept in the indexing error log
+pub const MAX_INDEX_ERRORS: i64 = 1000;
+
+/// Log an indexing error for a whole file (`line_number` 0) or one line
+///
+/// `kind` is what indexed the file, so a retry can index it the same way:
+/// `transcript`, `hooks`, or an adapter's name.
+pub fn record_index_error(
+    conn: &Connection,
+    file_path: &str,
+    kind: &str,
+    line_number: i64,
+    error: &str,
+) -> Result<(), IndexerError> {
+    conn.execute(
+        "INSERT INTO index_errors (file_path, kind, line_number, error, count, occurred_at)
+         VALUES (?1, ?2, ?3, ?4, 1, ?5)
+         ON CONFLICT (file_path, line_number) DO UPDATE
+         SET kind = excluded.kind, error = excluded.error, count = count + 1, occurred_at = excluded.occurred_at",
+        rusqlite::params![file_path, kind, line_number, error, chrono::Utc::now().to_rfc3339()],
+    )?;
+    conn.execute(
+        "DELETE FROM index_errors WHERE id NOT IN (SELECT id FROM index_errors ORDER BY occurred_at DESC LIMIT ?1)",
+        [MAX_INDEX_ERRORS],
+    )?;
+    Ok(())
+}
+
+/// Report a file that failed to index, on stderr and in the log
+pub(crate) fn file_failed(conn: &Connection, file: &Path, kind: &str, error: &IndexerError) {
+    eprintln!("Error indexing {}: {}", file.display(), error);
+    // The log is best effort: the database may be what failed
+    let _ = record_index_error(conn, &path_key(file), kind, 0, &error.to_string());
+}
+
+/// Forget a file's failure once it indexed cleanly
+pub(crate) fn file_indexed(conn: &Connection, file: &Path) {
+    let _ = conn.execute(
+        "DELETE FROM index_errors WHERE file_path = ?1 AND line_number = 0",
+        [path_key(file)],
+    );
+}
+
+/// Empty the indexing error log, returning how many entries it had
+pub fn clear_index_errors(conn: &Connection) -> Result<usize, IndexerError> {
+    Ok(conn.execute("DELETE FROM index_errors", [])?)
+}
+
+/// Files that failed to index, with what indexed them
+pub fn failed_files(conn: &Connection) -> Result<Vec<(String, String)>, IndexerError> {
+    let mut stmt = conn.prepare("SELECT file_path, kind FROM index_errors WHERE line_number = 0 ORDER BY file_path")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Outcome of retrying the files that failed to index
+#[derive(Debug, Clone, Default)]
+pub struct RetryResult {
+    pub indexed: usize,
+    pub failed: usize,
+    /// Files gone since they failed; their entries are dropped
+    pub missing: usize,
+}
+
+/// Index each failed file again the way it was indexed before, clearing the
+/// entries of those that now succeed
+pub fn retry_failed_files(conn: &Connection) -> Result<RetryResult, IndexerError> {
+    let mut result = RetryResult::default();
+    let adapters = crate::adapter::configured_adapters();
+    for (file_path, kind) in failed_files(conn)? {
+        let file = Path::new(&file_path);
+        if !file.is_file() {
+            conn.execute("DELETE FROM index_errors WHERE file_path = ?1", [&file_path])?;
+            result.missing += 1;
+            continue;
+        }
+
+        let outcome = match kind.as_str() {
+            "transcript" => crate::indexer::update_transcript_file(conn, file).map(|_| ()),
+            "hooks" => crate::hook_indexer::update_hook_file(conn, file).map(|_| ()),
+            name => match adapters.iter().find(|a| a.name() == name) {
+                Some(adapter) => crate::adapter::index_adapter_file(conn, adapter.as_ref(), file).map(|_| ()),
+                None => Err(IndexerError::AdapterNotConfigured(name.to_string())),
+            },
+        };
+        match outcome {
+            Ok(()) => {
+                file_indexed(conn, file);
+                result.indexed += 1;
+            }
+            Err(e) => {
+                file_failed(conn, file, &kind, &e);
+                result.failed += 1;
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::init_schema;
+
+    #[test]
+    fn test_slow_query_log_is_capped() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        let queries: Vec<(String, u64)> = (0..MAX_LOGGED_QUERIES + 5).map(|i| (format!("SELECT {}", i), 250)).collect();
+        record_slow_queries(&conn, "search", &queries).unwrap();
+
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM slow_queries", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, MAX_LOGGED_QUERIES);
+        let first_kept: String = conn
+            .query_row("SELECT sql FROM slow_queries ORDER BY id LIMIT 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(first_kept, "SELECT 5");
+    }
+
+    #[test]
+    fn test_index_errors_count_repeats() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        record_index_error(&conn, "/p/s1.jsonl", "transcript", 0, "disk I/O error").unwrap();
+        record_index_error(&conn, "/p/s1.jsonl", "transcript", 0, "database is locked").unwrap();
+        record_index_error(&conn, "/p/s1.jsonl", "transcript", 12, "expected value at line 1 column 1").unwrap();
+        let (entries, count, error): (i64, i64, String) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM index_errors), count, error FROM index_errors WHERE line_number = 0",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((entries, count, error.as_str()), (2, 2, "database is locked"));
+        assert_eq!(failed_files(&conn).unwrap(), [("/p/s1.jsonl".to_string(), "transcript".to_string())]);
+
+        // Indexing cleanly clears the file's failure, not its bad lines
+        file_indexed(&conn, Path::new("/p/s1.jsonl"));
+        assert!(failed_files(&conn).unwrap().is_empty());
+        assert_eq!(clear_index_errors(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_retry_drops_missing_files() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        record_index_error(&conn, "/gone/s1.jsonl", "transcript", 0, "disk I/O error").unwrap();
+        record_index_error(&conn, "/gone/s1.jsonl", "transcript", 3, "EOF while parsing").unwrap();
+        let result = retry_failed_files(&conn).unwrap();
+        assert_eq!((result.indexed, result.failed, result.missing), (0, 0, 1));
+        let left: i64 = conn.query_row("SELECT COUNT(*) FROM index_errors", [], |row| row.get(0)).unwrap();
+        assert_eq!(left, 0);
+    }
+}
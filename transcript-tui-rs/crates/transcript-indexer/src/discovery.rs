@@ -1,32 +1,155 @@
 //! File discovery for transcript and hook event JSONL files
 
 use std::path::{Path, PathBuf};
+use transcript_core::{home_dir, sdk_dir};
 use walkdir::WalkDir;
 
+use crate::compressed::strip_compression;
+
 /// Default projects directory (~/.claude/projects)
 pub fn default_projects_dir() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
-    PathBuf::from(home).join(".claude").join("projects")
+    home_dir().join(".claude").join("projects")
 }
 
 /// Default hooks directory (~/.claude/hooks)
 pub fn default_hooks_dir() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
-    PathBuf::from(home).join(".claude").join("hooks")
+    home_dir().join(".claude").join("hooks")
+}
+
+/// Directory holding transcripts piped in with `transcript index stdin`
+/// (~/.claude-code-sdk/streams)
+pub fn default_streams_dir() -> PathBuf {
+    sdk_dir().join("streams")
+}
+
+/// Directory holding conversations added with `transcript import`
+/// (~/.claude-code-sdk/imports)
+pub fn default_imports_dir() -> PathBuf {
+    sdk_dir().join("imports")
+}
+
+/// Directory holding session bundles exported from the viewer
+/// (~/.claude-code-sdk/exports)
+pub fn default_exports_dir() -> PathBuf {
+    sdk_dir().join("exports")
+}
+
+/// Directory holding rows removed from the index, until restored or emptied
+/// (~/.claude-code-sdk/trash)
+pub fn default_trash_dir() -> PathBuf {
+    sdk_dir().join("trash")
+}
+
+/// Environment variable listing extra home roots to index
+///
+/// Comma-separated `user=/path/to/home` entries; a bare path uses the
+/// directory name as the user (e.g. `alice=/home/alice,/home/bob`).
+pub const HOMES_ENV: &str = "TRANSCRIPT_HOMES";
+
+/// A home directory whose `.claude` data is indexed, and the user it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HomeRoot {
+    pub user: String,
+    pub path: PathBuf,
+}
+
+impl HomeRoot {
+    /// Parse `user=/path` or `/path` (user taken from the last path component)
+    pub fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return None;
+        }
+        let (user, path) = match spec.split_once('=') {
+            Some((user, path)) => (user.trim().to_string(), PathBuf::from(path.trim())),
+            None => {
+                let path = PathBuf::from(spec);
+                let user = path.file_name()?.to_string_lossy().to_string();
+                (user, path)
+            }
+        };
+        if user.is_empty() {
+            return None;
+        }
+        Some(Self { user, path })
+    }
+
+    /// `<home>/.claude/projects`
+    pub fn projects_dir(&self) -> PathBuf {
+        self.path.join(".claude").join("projects")
+    }
+
+    /// `<home>/.claude/hooks`
+    pub fn hooks_dir(&self) -> PathBuf {
+        self.path.join(".claude").join("hooks")
+    }
+}
+
+/// All home roots: the current user's home plus any from `TRANSCRIPT_HOMES`
+pub fn home_roots() -> Vec<HomeRoot> {
+    let mut roots = Vec::new();
+
+    // `~` is what's left when the platform has no home directory for us
+    let home = home_dir();
+    if home != Path::new("~") {
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("LOGNAME"))
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok()
+            .filter(|u| !u.is_empty())
+            .or_else(|| home.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "unknown".to_string());
+        roots.push(HomeRoot { user, path: home });
+    }
+
+    if let Ok(extra) = std::env::var(HOMES_ENV) {
+        for root in extra.split(',').filter_map(HomeRoot::parse) {
+            if !roots.iter().any(|r| r.path == root.path) {
+                roots.push(root);
+            }
+        }
+    }
+
+    roots
+}
+
+/// User owning a file, by the home root it lives under (longest match wins)
+pub fn user_for_path(path: &Path, roots: &[HomeRoot]) -> Option<String> {
+    roots
+        .iter()
+        .filter(|r| path.starts_with(&r.path))
+        .max_by_key(|r| r.path.components().count())
+        .map(|r| r.user.clone())
 }
 
 /// Find all transcript JSONL files (excluding *.hooks.jsonl)
+///
+/// With no directory, every home root's projects directory is searched, along
+/// with the streams and imports directories.
 pub fn find_transcript_files(dir: Option<&Path>) -> Vec<PathBuf> {
-    let dir = dir
-        .map(PathBuf::from)
-        .unwrap_or_else(default_projects_dir);
+    match dir {
+        Some(dir) => find_transcript_files_in(dir),
+        None => {
+            let mut files: Vec<PathBuf> = home_roots()
+                .iter()
+                .flat_map(|r| find_transcript_files_in(&r.projects_dir()))
+                .chain(find_transcript_files_in(&default_streams_dir()))
+                .chain(find_transcript_files_in(&default_imports_dir()))
+                .collect();
+            files.sort();
+            files.dedup();
+            files
+        }
+    }
+}
 
+fn find_transcript_files_in(dir: &Path) -> Vec<PathBuf> {
     if !dir.exists() {
         return Vec::new();
     }
 
     let mut files = Vec::new();
-    for entry in WalkDir::new(&dir)
+    for entry in WalkDir::new(dir)
         .follow_links(true)
         .into_iter()
         .filter_map(|e| e.ok())
@@ -34,7 +157,8 @@ pub fn find_transcript_files(dir: Option<&Path>) -> Vec<PathBuf> {
         let path = entry.path();
         if path.is_file() {
             if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                // Include .jsonl files but exclude .hooks.jsonl files
+                // Include .jsonl files, compressed or not, but exclude .hooks.jsonl files
+                let name = strip_compression(name);
                 if name.ends_with(".jsonl") && !name.ends_with(".hooks.jsonl") {
                     files.push(path.to_path_buf());
                 }
@@ -47,15 +171,30 @@ pub fn find_transcript_files(dir: Option<&Path>) -> Vec<PathBuf> {
 }
 
 /// Find all hook event JSONL files (*.hooks.jsonl)
+///
+/// With no directory, every home root's hooks directory is searched.
 pub fn find_hook_files(dir: Option<&Path>) -> Vec<PathBuf> {
-    let dir = dir.map(PathBuf::from).unwrap_or_else(default_hooks_dir);
+    match dir {
+        Some(dir) => find_hook_files_in(dir),
+        None => {
+            let mut files: Vec<PathBuf> = home_roots()
+                .iter()
+                .flat_map(|r| find_hook_files_in(&r.hooks_dir()))
+                .collect();
+            files.sort();
+            files.dedup();
+            files
+        }
+    }
+}
 
+fn find_hook_files_in(dir: &Path) -> Vec<PathBuf> {
     if !dir.exists() {
         return Vec::new();
     }
 
     let mut files = Vec::new();
-    for entry in WalkDir::new(&dir)
+    for entry in WalkDir::new(dir)
         .follow_links(true)
         .into_iter()
         .filter_map(|e| e.ok())
@@ -63,7 +202,7 @@ pub fn find_hook_files(dir: Option<&Path>) -> Vec<PathBuf> {
         let path = entry.path();
         if path.is_file() {
             if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if name.ends_with(".hooks.jsonl") {
+                if strip_compression(name).ends_with(".hooks.jsonl") {
                     files.push(path.to_path_buf());
                 }
             }
@@ -120,6 +259,22 @@ mod tests {
             .contains("session.hooks.jsonl"));
     }
 
+    #[test]
+    fn test_home_root_parse_and_user_for_path() {
+        let alice = HomeRoot::parse("alice=/srv/homes/a").unwrap();
+        assert_eq!(alice.user, "alice");
+        assert_eq!(alice.projects_dir(), PathBuf::from("/srv/homes/a/.claude/projects"));
+
+        let bob = HomeRoot::parse("/home/bob").unwrap();
+        assert_eq!(bob.user, "bob");
+        assert!(HomeRoot::parse("  ").is_none());
+
+        let roots = vec![alice, bob];
+        let file = Path::new("/home/bob/.claude/projects/x/s.jsonl");
+        assert_eq!(user_for_path(file, &roots).as_deref(), Some("bob"));
+        assert_eq!(user_for_path(Path::new("/tmp/s.jsonl"), &roots), None);
+    }
+
     #[test]
     fn test_find_transcript_files_nonexistent_dir() {
         let files = find_transcript_files(Some(Path::new("/nonexistent/path")));
@@ -0,0 +1,132 @@
+//! Encrypting and decrypting existing databases
+//!
+//! A key only applies to databases created with it: an index built before
+//! the key was set stays plain, and can't be opened while the key is set.
+//! `transcript index encrypt` (and `decrypt`, to go back) rewrites it with
+//! SQLCipher's `sqlcipher_export`, a full copy under the new key that then
+//! replaces the original. Nothing else may have the database open meanwhile.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::connection::{check_readable, IndexerError};
+use transcript_core::key_pragma;
+
+/// Encrypt the plain database at `path` with `key`, in place
+///
+/// Returns false, changing nothing, if it is already encrypted.
+pub fn encrypt_database(path: &Path, key: &str) -> Result<bool, IndexerError> {
+    if is_encrypted(path)? {
+        return Ok(false);
+    }
+    rewrite(path, None, key)?;
+    Ok(true)
+}
+
+/// Decrypt the database at `path`, encrypted with `key`, in place
+///
+/// Returns false, changing nothing, if it is already plain.
+pub fn decrypt_database(path: &Path, key: &str) -> Result<bool, IndexerError> {
+    if !is_encrypted(path)? {
+        return Ok(false);
+    }
+    rewrite(path, Some(key), "")?;
+    Ok(true)
+}
+
+/// Whether the database at `path` is encrypted: plain SQLite files start
+/// with a readable header, SQLCipher's are random bytes throughout
+pub fn is_encrypted(path: &Path) -> Result<bool, IndexerError> {
+    use std::io::Read;
+
+    if !path.exists() {
+        return Err(IndexerError::FileNotFound(path.to_path_buf()));
+    }
+    let mut header = Vec::with_capacity(16);
+    std::fs::File::open(path)?.take(16).read_to_end(&mut header)?;
+    // Empty files are new, plain databases
+    Ok(!header.is_empty() && header != b"SQLite format 3\0")
+}
+
+/// Copy the database at `path`, opened with `from`, to one keyed with `to`
+/// (`""` for plain) and put the copy in its place
+fn rewrite(path: &Path, from: Option<&str>, to: &str) -> Result<(), IndexerError> {
+    if !cfg!(feature = "encryption") {
+        return Err(IndexerError::EncryptionUnavailable);
+    }
+
+    // CREATE for the attached copy (the database itself exists)
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    if let Some(key) = from {
+        conn.execute_batch(&key_pragma(key))?;
+    }
+    check_readable(&conn, path)?;
+    // Fold the write-ahead log in, so the copy has everything
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+    let copy = sibling(path, "rekey");
+    let _ = std::fs::remove_file(&copy);
+    conn.execute("ATTACH DATABASE ?1 AS rekeyed KEY ?2", [&*copy.to_string_lossy(), to])?;
+    let exported = conn
+        .execute_batch("PRAGMA rekeyed.auto_vacuum = INCREMENTAL")
+        .and_then(|_| conn.query_row("SELECT sqlcipher_export('rekeyed')", [], |_| Ok(())));
+    conn.execute_batch("DETACH DATABASE rekeyed")?;
+    drop(conn);
+    if let Err(e) = exported {
+        let _ = std::fs::remove_file(&copy);
+        return Err(e.into());
+    }
+
+    std::fs::rename(&copy, path)?;
+    for suffix in ["wal", "shm"] {
+        let _ = std::fs::remove_file(sibling(path, suffix));
+    }
+    Ok(())
+}
+
+/// `path` with `-suffix` appended to its file name (`transcripts.db-wal`)
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!("-{}", suffix));
+    PathBuf::from(name)
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("transcripts.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE lines (content TEXT); INSERT INTO lines VALUES ('sk-secret')")
+            .unwrap();
+        drop(conn);
+
+        assert!(!is_encrypted(&path).unwrap());
+        assert!(encrypt_database(&path, "hunter2").unwrap());
+        assert!(is_encrypted(&path).unwrap());
+        assert!(!encrypt_database(&path, "hunter2").unwrap());
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(!bytes.starts_with(b"SQLite format 3"));
+        assert!(!bytes.windows(9).any(|w| w == b"sk-secret"));
+
+        // Plain opens fail; the key reads it
+        let plain = Connection::open(&path).unwrap();
+        assert!(matches!(check_readable(&plain, &path), Err(IndexerError::Locked(_))));
+        let keyed = Connection::open(&path).unwrap();
+        keyed.execute_batch(&key_pragma("hunter2")).unwrap();
+        let content: String = keyed.query_row("SELECT content FROM lines", [], |row| row.get(0)).unwrap();
+        assert_eq!(content, "sk-secret");
+        drop((plain, keyed));
+
+        assert!(matches!(decrypt_database(&path, "wrong"), Err(IndexerError::Locked(_))));
+        assert!(decrypt_database(&path, "hunter2").unwrap());
+        assert!(std::fs::read(&path).unwrap().starts_with(b"SQLite format 3"));
+    }
+}
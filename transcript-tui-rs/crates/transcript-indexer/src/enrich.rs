@@ -0,0 +1,198 @@
+//! Per-line enrichment pipeline
+//!
+//! Every transcript line indexed passes through a [`Pipeline`] of
+//! [`Enricher`]s, in order, before it is written. The built-in steps are:
+//!
+//! - `text`: searchable text for the `content` column and full-text index
+//! - `trim`: large strings cut to previews in the `raw` column
+//!
+//! Installs can choose the steps and their order with `"enrichers"` in the
+//! config file (e.g. `["trim"]` to index raw lines without search text). A
+//! program embedding the indexer can add its own steps (redaction,
+//! embeddings, ...) with [`set_pipeline`] before indexing anything.
+
+use std::sync::OnceLock;
+
+use serde_json::Value;
+
+use crate::content_trimmer::trim_raw_transcript_line;
+use crate::text_extract::extract_searchable_text;
+use transcript_core::Config;
+
+/// Built-in enricher names, in their default order
+pub const DEFAULT_ENRICHERS: &[&str] = &["text", "trim"];
+
+/// A transcript line on its way into the index
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnrichedLine {
+    /// The parsed line; later steps, the `raw` column and the tool payload
+    /// sizes see any changes made to it
+    pub value: Value,
+    /// Text for the `content` column and the full-text index
+    pub content: String,
+    /// The `raw` column (`None`: `value` as JSON)
+    pub raw: Option<String>,
+}
+
+impl EnrichedLine {
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            content: String::new(),
+            raw: None,
+        }
+    }
+}
+
+/// One step of the pipeline
+pub trait Enricher: Send + Sync {
+    /// Name used in the config file
+    fn name(&self) -> &str;
+
+    /// Add to or change the line
+    fn enrich(&self, line: &mut EnrichedLine);
+}
+
+/// `text`: extract the searchable text
+pub struct TextEnricher;
+
+impl Enricher for TextEnricher {
+    fn name(&self) -> &str {
+        "text"
+    }
+
+    fn enrich(&self, line: &mut EnrichedLine) {
+        line.content = extract_searchable_text(&line.value);
+    }
+}
+
+/// `trim`: store large strings as previews (the file keeps them whole)
+pub struct TrimEnricher;
+
+impl Enricher for TrimEnricher {
+    fn name(&self) -> &str {
+        "trim"
+    }
+
+    fn enrich(&self, line: &mut EnrichedLine) {
+        line.raw = Some(trim_raw_transcript_line(&line.value));
+    }
+}
+
+/// The built-in enricher called `name`
+pub fn builtin_enricher(name: &str) -> Option<Box<dyn Enricher>> {
+    match name {
+        "text" => Some(Box::new(TextEnricher)),
+        "trim" => Some(Box::new(TrimEnricher)),
+        _ => None,
+    }
+}
+
+/// Enrichers run in order on every line
+#[derive(Default)]
+pub struct Pipeline {
+    enrichers: Vec<Box<dyn Enricher>>,
+}
+
+impl Pipeline {
+    /// The built-in steps, in their default order
+    pub fn builtin() -> Self {
+        Self::from_names(DEFAULT_ENRICHERS).0
+    }
+
+    /// The built-in steps named, and the names that aren't built in
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> (Self, Vec<String>) {
+        let mut pipeline = Self::default();
+        let mut unknown = Vec::new();
+        for name in names {
+            match builtin_enricher(name.as_ref().trim()) {
+                Some(enricher) => pipeline.enrichers.push(enricher),
+                None => unknown.push(name.as_ref().to_string()),
+            }
+        }
+        (pipeline, unknown)
+    }
+
+    /// Add a step at the end
+    pub fn with(mut self, enricher: Box<dyn Enricher>) -> Self {
+        self.enrichers.push(enricher);
+        self
+    }
+
+    /// Step names, in order
+    pub fn names(&self) -> Vec<&str> {
+        self.enrichers.iter().map(|e| e.name()).collect()
+    }
+
+    /// Run every step on `value`
+    pub fn enrich(&self, value: Value) -> EnrichedLine {
+        let mut line = EnrichedLine::new(value);
+        for enricher in &self.enrichers {
+            enricher.enrich(&mut line);
+        }
+        line
+    }
+}
+
+static PIPELINE: OnceLock<Pipeline> = OnceLock::new();
+
+/// The pipeline used for indexing: the one set with [`set_pipeline`], else
+/// the config file's `enrichers`, else the built-in steps
+pub fn pipeline() -> &'static Pipeline {
+    PIPELINE.get_or_init(|| match Config::load().enrichers {
+        Some(names) => {
+            let (pipeline, unknown) = Pipeline::from_names(&names);
+            for name in unknown {
+                eprintln!("Unknown enricher '{}' in the config file (built in: {})", name, DEFAULT_ENRICHERS.join(", "));
+            }
+            pipeline
+        }
+        None => Pipeline::builtin(),
+    })
+}
+
+/// Use `pipeline` for everything indexed by this process
+///
+/// Fails, handing it back, once a line has been indexed (the pipeline is
+/// fixed from then on).
+pub fn set_pipeline(pipeline: Pipeline) -> Result<(), Pipeline> {
+    PIPELINE.set(pipeline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Redact;
+
+    impl Enricher for Redact {
+        fn name(&self) -> &str {
+            "redact"
+        }
+
+        fn enrich(&self, line: &mut EnrichedLine) {
+            line.content = line.content.replace("sk-secret", "[redacted]");
+            line.raw = None;
+        }
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let value = json!({"type": "user", "message": {"role": "user", "content": "key sk-secret"}});
+
+        let line = Pipeline::builtin().enrich(value.clone());
+        assert_eq!(line.content, "key sk-secret");
+        assert!(line.raw.is_some());
+        assert_eq!(Pipeline::builtin().names(), DEFAULT_ENRICHERS);
+
+        // Steps run in order, so a later one sees and can undo earlier ones
+        let line = Pipeline::builtin().with(Box::new(Redact)).enrich(value.clone());
+        assert_eq!((line.content.as_str(), line.raw), ("key [redacted]", None));
+
+        let (pipeline, unknown) = Pipeline::from_names(&["trim", "embed"]);
+        assert_eq!(pipeline.names(), ["trim"]);
+        assert_eq!(unknown, ["embed"]);
+        assert_eq!(pipeline.enrich(value).content, "");
+    }
+}
@@ -5,13 +5,18 @@
 
 use rusqlite::Connection;
 use serde_json::Value;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::Path;
+use transcript_core::path_key;
 
+use crate::archive;
+use crate::compressed;
 use crate::connection::IndexerError;
 use crate::content_trimmer::{trim_context_json, trim_handler_results, trim_input_json};
+use crate::diagnostics;
 use crate::discovery;
+use crate::ignore;
+use crate::line_reader::CompleteLines;
+use crate::prune;
 
 /// Result of indexing a single hook file
 #[derive(Debug, Default)]
@@ -19,6 +24,8 @@ pub struct HookIndexResult {
     pub events_indexed: usize,
     pub byte_offset: u64,
     pub session_id: String,
+    /// Lines read despite invalid UTF-8 or a byte-order mark
+    pub recovered_lines: usize,
 }
 
 /// Result of indexing all hook files
@@ -26,6 +33,7 @@ pub struct HookIndexResult {
 pub struct HookIndexAllResult {
     pub files_indexed: usize,
     pub events_indexed: usize,
+    pub recovered_lines: usize,
 }
 
 /// Result of delta update for hooks
@@ -34,6 +42,7 @@ pub struct HookUpdateResult {
     pub files_checked: usize,
     pub files_updated: usize,
     pub new_events: usize,
+    pub recovered_lines: usize,
 }
 
 /// Index a single hook events JSONL file (full or delta)
@@ -43,49 +52,37 @@ pub fn index_hook_file(
     from_byte_offset: u64,
     start_line_number: i64,
 ) -> Result<HookIndexResult, IndexerError> {
-    let metadata = std::fs::metadata(file_path)?;
-    let file_size = metadata.len();
-
-    if from_byte_offset >= file_size {
+    // A compressed file's length isn't known until read
+    let file_size = compressed::plain_len(file_path)?;
+    if file_size.is_some_and(|size| from_byte_offset >= size) {
         return Ok(HookIndexResult {
             events_indexed: 0,
             byte_offset: from_byte_offset,
             session_id: String::new(),
+            recovered_lines: 0,
         });
     }
 
-    let mut file = File::open(file_path)?;
-    if from_byte_offset > 0 {
-        file.seek(SeekFrom::Start(from_byte_offset))?;
-    }
-
-    let reader = BufReader::new(file);
+    let mut reader = CompleteLines::new(compressed::open_at(file_path, from_byte_offset)?, from_byte_offset);
     let mut indexed_count: usize = 0;
     let mut session_id = String::new();
     let mut first_timestamp: Option<String> = None;
     let mut last_timestamp: Option<String> = None;
     let mut line_number = start_line_number;
     let mut first_line = from_byte_offset > 0;
+    let user = discovery::user_for_path(file_path, &discovery::home_roots());
 
     let mut insert_stmt = conn.prepare_cached(
         "INSERT INTO hook_events
          (session_id, timestamp, event_type, tool_use_id, tool_name, decision,
           handler_results, input_json, context_json, file_path, line_number,
-          turn_id, turn_sequence, session_name, git_hash, git_branch, git_dirty)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+          turn_id, turn_sequence, session_name, git_hash, git_branch, git_dirty, user)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
     )?;
 
     conn.execute_batch("SAVEPOINT index_hooks")?;
 
-    for line_result in reader.lines() {
-        let raw_line = match line_result {
-            Ok(l) => l,
-            Err(_) => {
-                line_number += 1;
-                continue;
-            }
-        };
-
+    for raw_line in &mut reader {
         // Skip partial first line when reading from offset
         if first_line {
             first_line = false;
@@ -115,6 +112,20 @@ pub fn index_hook_file(
             }
         }
 
+        // Events of ignored sessions are skipped (without the slug, which
+        // hook events don't carry, unless the transcript was found ignored)
+        let cwd = parsed
+            .get("input")
+            .and_then(|input| input.get("cwd"))
+            .or_else(|| parsed.get("cwd"))
+            .and_then(|v| v.as_str());
+        if transcript_core::ignore_rules().ignores(&session_id, cwd, None)
+            || ignore::is_ignored_session(conn, &session_id)?
+        {
+            line_number += 1;
+            continue;
+        }
+
         let timestamp = parsed
             .get("timestamp")
             .and_then(|v| v.as_str())
@@ -151,7 +162,7 @@ pub fn index_hook_file(
 
         // Extract handler results
         let handler_results = parsed.get("handlerResults");
-        let handler_results_json = handler_results.map(|v| trim_handler_results(v));
+        let handler_results_json = handler_results.map(trim_handler_results);
 
         // Extract turn info from handler results
         let (turn_id, turn_sequence, session_name, git_hash, git_branch, git_dirty) =
@@ -165,9 +176,9 @@ pub fn index_hook_file(
 
         let context_json = parsed
             .get("context")
-            .map(|v| trim_context_json(v));
+            .map(trim_context_json);
 
-        let file_path_str = file_path.to_string_lossy().to_string();
+        let file_path_str = path_key(file_path);
 
         insert_stmt.execute(rusqlite::params![
             if session_id.is_empty() {
@@ -191,6 +202,7 @@ pub fn index_hook_file(
             git_hash,
             git_branch,
             git_dirty,
+            user,
         ])?;
 
         indexed_count += 1;
@@ -199,8 +211,9 @@ pub fn index_hook_file(
 
     conn.execute_batch("RELEASE index_hooks")?;
 
-    let new_byte_offset = file_size;
-    let file_path_str = file_path.to_string_lossy().to_string();
+    // A partly written last line is read again next time
+    let new_byte_offset = reader.offset;
+    let file_path_str = path_key(file_path);
     let now = chrono::Utc::now().to_rfc3339();
 
     // Update hook_files tracking table
@@ -243,10 +256,12 @@ pub fn index_hook_file(
         events_indexed: indexed_count,
         byte_offset: new_byte_offset,
         session_id,
+        recovered_lines: reader.recovered,
     })
 }
 
 /// Extract turn tracker, session naming, and git tracker data from handler results
+#[allow(clippy::type_complexity)]
 fn extract_handler_data(
     handler_results: Option<&Value>,
     parsed: &Value,
@@ -335,26 +350,54 @@ pub fn index_all_hook_files<F>(
 where
     F: FnMut(&str, usize, usize, usize),
 {
-    let files = discovery::find_hook_files(hooks_dir);
+    let mut files = discovery::find_hook_files(hooks_dir);
+    files.retain(|file| !archive::is_archived(conn, file) && !prune::is_deleted(conn, file));
     let total = files.len();
     let mut result = HookIndexAllResult::default();
 
     for (i, file) in files.iter().enumerate() {
         match index_hook_file(conn, file, 0, 1) {
             Ok(r) => {
+                diagnostics::file_indexed(conn, file);
                 result.files_indexed += 1;
                 result.events_indexed += r.events_indexed;
+                result.recovered_lines += r.recovered_lines;
                 on_progress(&file.to_string_lossy(), i + 1, total, r.events_indexed);
             }
-            Err(e) => {
-                eprintln!("Error indexing hook file {}: {}", file.display(), e);
-            }
+            Err(e) => diagnostics::file_failed(conn, file, "hooks", &e),
         }
     }
 
     Ok(result)
 }
 
+/// Index the events a hook file gained since it was last indexed; `None`
+/// when it hasn't grown or can't be read, and for archived or deleted files
+pub fn update_hook_file(conn: &Connection, file: &Path) -> Result<Option<HookIndexResult>, IndexerError> {
+    if archive::is_archived(conn, file) || prune::is_deleted(conn, file) {
+        return Ok(None);
+    }
+    let state: Option<(i64, i64, Option<String>)> = conn
+        .query_row(
+            "SELECT byte_offset, event_count, indexed_at FROM hook_files WHERE file_path = ?1",
+            [path_key(file)],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok();
+    let Ok(metadata) = std::fs::metadata(file) else {
+        return Ok(None);
+    };
+    if let Some((offset, _, indexed_at)) = &state {
+        if !compressed::may_have_grown(file, &metadata, *offset as u64, indexed_at.as_deref()) {
+            return Ok(None);
+        }
+    }
+
+    let from_offset = state.as_ref().map(|(o, _, _)| *o as u64).unwrap_or(0);
+    let start_line = state.as_ref().map(|(_, c, _)| c + 1).unwrap_or(1);
+    index_hook_file(conn, file, from_offset, start_line).map(Some)
+}
+
 /// Update hook index with only new content (delta update)
 pub fn update_hook_index<F>(
     conn: &Connection,
@@ -370,42 +413,19 @@ where
 
     for (i, file) in files.iter().enumerate() {
         result.files_checked += 1;
-        let file_path_str = file.to_string_lossy().to_string();
-
-        let state: Option<(i64, i64)> = conn
-            .query_row(
-                "SELECT byte_offset, event_count FROM hook_files WHERE file_path = ?1",
-                [&file_path_str],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .ok();
-
-        let file_size = match std::fs::metadata(file) {
-            Ok(m) => m.len(),
-            Err(_) => continue,
-        };
-
-        if let Some((offset, _)) = state {
-            if offset as u64 >= file_size {
-                on_progress(&file_path_str, i + 1, total, 0, true);
-                continue;
-            }
-        }
-
-        let from_offset = state.map(|(o, _)| o as u64).unwrap_or(0);
-        let start_line = state.map(|(_, c)| c + 1).unwrap_or(1);
-
-        match index_hook_file(conn, file, from_offset, start_line) {
-            Ok(r) => {
+        let file_path_str = path_key(file);
+        match update_hook_file(conn, file) {
+            Ok(None) => on_progress(&file_path_str, i + 1, total, 0, true),
+            Ok(Some(r)) => {
+                diagnostics::file_indexed(conn, file);
                 if r.events_indexed > 0 {
                     result.files_updated += 1;
                     result.new_events += r.events_indexed;
+                    result.recovered_lines += r.recovered_lines;
                 }
                 on_progress(&file_path_str, i + 1, total, r.events_indexed, false);
             }
-            Err(e) => {
-                eprintln!("Error updating hook file {}: {}", file.display(), e);
-            }
+            Err(e) => diagnostics::file_failed(conn, file, "hooks", &e),
         }
     }
 
@@ -431,10 +451,8 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         let file_path = tmp.path().join("events.hooks.jsonl");
 
-        let lines = vec![
-            r#"{"sessionId":"sess-1","timestamp":"2024-01-01T00:00:00Z","eventType":"PreToolUse","toolName":"Bash","toolUseId":"tu-1","input":{"command":"ls"}}"#,
-            r#"{"sessionId":"sess-1","timestamp":"2024-01-01T00:00:01Z","eventType":"PostToolUse","toolName":"Bash","toolUseId":"tu-1"}"#,
-        ];
+        let lines = [r#"{"sessionId":"sess-1","timestamp":"2024-01-01T00:00:00Z","eventType":"PreToolUse","toolName":"Bash","toolUseId":"tu-1","input":{"command":"ls"}}"#,
+            r#"{"sessionId":"sess-1","timestamp":"2024-01-01T00:00:01Z","eventType":"PostToolUse","toolName":"Bash","toolUseId":"tu-1"}"#];
         fs::write(&file_path, lines.join("\n") + "\n").unwrap();
 
         let result = index_hook_file(&conn, &file_path, 0, 1).unwrap();
@@ -447,6 +465,37 @@ mod tests {
         assert_eq!(count, 2);
     }
 
+    #[test]
+    fn test_partial_hook_line_waits_for_newline() {
+        let conn = setup_db();
+        let tmp = tempfile::tempdir().unwrap();
+        let file_path = tmp.path().join("events.hooks.jsonl");
+
+        let first = r#"{"sessionId":"sess-1","timestamp":"2024-01-01T00:00:00Z","eventType":"PreToolUse","toolName":"Bash"}"#;
+        let second = r#"{"sessionId":"sess-1","timestamp":"2024-01-01T00:00:01Z","eventType":"PostToolUse","toolName":"Bash"}"#;
+        let (head, tail) = second.split_at(40);
+        fs::write(&file_path, format!("{}\n{}", first, head)).unwrap();
+
+        let result = index_hook_file(&conn, &file_path, 0, 1).unwrap();
+        assert_eq!(result.events_indexed, 1);
+        assert_eq!(result.byte_offset, first.len() as u64 + 1);
+
+        // The handler finishes the line; the next delta picks it up whole
+        let mut f = fs::OpenOptions::new().append(true).open(&file_path).unwrap();
+        std::io::Write::write_all(&mut f, format!("{}\n", tail).as_bytes()).unwrap();
+        let result = index_hook_file(&conn, &file_path, result.byte_offset, 2).unwrap();
+        assert_eq!(result.events_indexed, 1);
+
+        let events: Vec<(String, i64)> = conn
+            .prepare("SELECT event_type, line_number FROM hook_events ORDER BY line_number")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(events, [("PreToolUse".to_string(), 1), ("PostToolUse".to_string(), 2)]);
+    }
+
     #[test]
     fn test_handler_data_extraction() {
         let conn = setup_db();
@@ -458,6 +507,7 @@ mod tests {
 
         index_hook_file(&conn, &file_path, 0, 1).unwrap();
 
+        #[allow(clippy::type_complexity)]
         let row: (
             Option<String>,
             Option<i64>,
@@ -0,0 +1,179 @@
+//! Keeping ignored sessions out of the index
+//!
+//! The rules live in the config file (see `transcript_core::ignore`). A
+//! session is matched on what its transcript says (ID, working directory,
+//! slug), so it is only known to be ignored once its file has been read. The
+//! `ignored_sessions` table remembers it: updates then skip its transcript
+//! unread, and its hook events, which don't carry the slug, are skipped too.
+//! When a rule is removed, its sessions are released and indexed again.
+
+use std::path::Path;
+
+use rusqlite::{Connection, OptionalExtension};
+use transcript_core::IgnoreConfig;
+
+use crate::compressed;
+use crate::connection::IndexerError;
+use crate::prune::{IndexedFileKind, StaleFile, StaleReason};
+
+/// Remember that a session is ignored
+pub(crate) fn record_ignored(
+    conn: &Connection,
+    session_id: &str,
+    cwd: Option<&str>,
+    slug: Option<&str>,
+) -> Result<(), IndexerError> {
+    if session_id.is_empty() {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO ignored_sessions (session_id, cwd, slug, ignored_at) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![session_id, cwd, slug, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Whether a session was found to be ignored
+pub(crate) fn is_ignored_session(conn: &Connection, session_id: &str) -> Result<bool, IndexerError> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM ignored_sessions WHERE session_id = ?1",
+            [session_id],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Whether a transcript file belongs to a known ignored session (by its name,
+/// which is the session ID), so it needn't be read
+pub(crate) fn skips_file(conn: &Connection, file_path: &Path) -> bool {
+    compressed::session_stem(file_path).is_some_and(|stem| is_ignored_session(conn, stem).unwrap_or(false))
+}
+
+/// Indexed files of sessions that `rules` ignore, with their hook files
+pub fn find_ignored_files(conn: &Connection, rules: &IgnoreConfig) -> Result<Vec<StaleFile>, IndexerError> {
+    let mut ignored = Vec::new();
+    let mut sessions = Vec::new();
+    if !rules.is_empty() {
+        let mut stmt = conn.prepare(
+            "SELECT s.file_path, s.session_id, s.slug,
+                    (SELECT cwd FROM lines l WHERE l.file_path = s.file_path AND cwd IS NOT NULL LIMIT 1)
+             FROM sessions s ORDER BY s.file_path",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (file_path, session_id, slug, cwd) = row?;
+            if rules.ignores(&session_id, cwd.as_deref(), slug.as_deref()) {
+                ignored.push(StaleFile {
+                    file_path,
+                    kind: IndexedFileKind::Transcript,
+                    reason: StaleReason::Ignored,
+                });
+                sessions.push(session_id);
+            }
+        }
+    }
+
+    // Hook files of those sessions, or of sessions ignored earlier
+    let mut stmt = conn.prepare("SELECT file_path, session_id FROM hook_files ORDER BY file_path")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (file_path, session_id) = row?;
+        if sessions.contains(&session_id)
+            || rules.ignores_session(&session_id)
+            || is_ignored_session(conn, &session_id)?
+        {
+            ignored.push(StaleFile {
+                file_path,
+                kind: IndexedFileKind::Hooks,
+                reason: StaleReason::Ignored,
+            });
+        }
+    }
+    Ok(ignored)
+}
+
+/// Forget ignored sessions that `rules` no longer match, so they are indexed again
+///
+/// Their hook files are re-read from the start as well, since their events
+/// were skipped. Returns how many sessions were released.
+pub fn release_unignored(conn: &Connection, rules: &IgnoreConfig) -> Result<usize, IndexerError> {
+    let released = {
+        let mut stmt = conn.prepare("SELECT session_id, cwd, slug FROM ignored_sessions")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+        let mut released = Vec::new();
+        for row in rows {
+            let (session_id, cwd, slug) = row?;
+            if !rules.ignores(&session_id, cwd.as_deref(), slug.as_deref()) {
+                released.push(session_id);
+            }
+        }
+        released
+    };
+
+    for session_id in &released {
+        conn.execute("DELETE FROM ignored_sessions WHERE session_id = ?1", [session_id])?;
+        conn.execute("DELETE FROM hook_files WHERE session_id = ?1", [session_id])?;
+    }
+    Ok(released.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prune::prune_files;
+    use crate::schema;
+
+    #[test]
+    fn test_ignored_sessions_are_purged_and_released() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init_schema(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path, cwd, content)
+             VALUES ('s1', 'u1', 1, 'user', '2024-01-01T00:00:00Z', '{}', '/p/s1.jsonl', '/work/acme/api', 'a'),
+                    ('s2', 'u1', 1, 'user', '2024-01-01T00:00:00Z', '{}', '/p/s2.jsonl', '/home/me', 'b');
+             INSERT INTO sessions (file_path, session_id, slug, line_count, byte_offset, indexed_at)
+             VALUES ('/p/s1.jsonl', 's1', 'calm-otter', 1, 10, '2024-01-01'),
+                    ('/p/s2.jsonl', 's2', 'tender-spider', 1, 10, '2024-01-01');
+             INSERT INTO hook_events (session_id, timestamp, event_type, file_path, line_number)
+             VALUES ('s1', '2024-01-01T00:00:00Z', 'PreToolUse', '/h/s1.hooks.jsonl', 1);
+             INSERT INTO hook_files (file_path, session_id, event_count, byte_offset, indexed_at)
+             VALUES ('/h/s1.hooks.jsonl', 's1', 1, 10, '2024-01-01');",
+        )
+        .unwrap();
+
+        let rules = IgnoreConfig {
+            projects: vec!["/work/acme".to_string()],
+            ..Default::default()
+        };
+        let files = find_ignored_files(&conn, &rules).unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.file_path.as_str()).collect();
+        assert_eq!(paths, ["/p/s1.jsonl", "/h/s1.hooks.jsonl"]);
+
+        let result = prune_files(&conn, &files, &tmp.path().join("trash")).unwrap();
+        assert_eq!((result.sessions, result.lines, result.hook_events), (1, 1, 1));
+        // Remembered, so its files are skipped from now on
+        assert!(is_ignored_session(&conn, "s1").unwrap());
+        assert!(skips_file(&conn, Path::new("/p/s1.jsonl")));
+        assert!(!skips_file(&conn, Path::new("/p/s2.jsonl")));
+
+        assert_eq!(release_unignored(&conn, &rules).unwrap(), 0);
+        assert_eq!(release_unignored(&conn, &IgnoreConfig::default()).unwrap(), 1);
+        assert!(!is_ignored_session(&conn, "s1").unwrap());
+    }
+}
@@ -0,0 +1,519 @@
+//! Importer for conversations saved in API messages format
+//!
+//! Converts an Anthropic Messages or OpenAI Chat Completions `messages` array
+//! (or a request body holding one) into transcript lines, writes them to
+//! `<imports dir>/<session-id>.jsonl` and indexes that file, so conversations
+//! captured by other tooling can be searched next to Claude Code sessions.
+//! Uuids, parents and timestamps are synthesized as for streamed lines.
+//!
+//! Claude.ai data exports (`conversations.json`) are imported the same way,
+//! one session per conversation, keeping the message ids, branches and times.
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rusqlite::Connection;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use transcript_core::path_key;
+
+use crate::connection::IndexerError;
+use crate::indexer::index_transcript_file;
+use crate::stream::{normalize_stream_line, stream_file_path};
+
+/// Shape of the messages being imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessagesFormat {
+    /// Anthropic Messages API (`system` outside the array, content blocks)
+    Anthropic,
+    /// OpenAI Chat Completions (`system`/`tool` roles, `tool_calls`)
+    OpenAi,
+}
+
+impl MessagesFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MessagesFormat::Anthropic => "anthropic",
+            MessagesFormat::OpenAi => "openai",
+        }
+    }
+}
+
+/// Result of an import
+#[derive(Debug)]
+pub struct ImportResult {
+    pub session_id: String,
+    pub messages: usize,
+    pub lines_indexed: usize,
+    pub file_path: PathBuf,
+}
+
+fn invalid(message: impl Into<String>) -> IndexerError {
+    IndexerError::Import(message.into())
+}
+
+/// Convert a messages document into transcript lines
+///
+/// Line `n` (from 1) is stamped `start` plus `n - 1` seconds.
+pub fn convert_messages(
+    input: &Value,
+    format: MessagesFormat,
+    session_id: &str,
+    start: DateTime<Utc>,
+) -> Result<Vec<Value>, IndexerError> {
+    let (messages, body) = match input {
+        Value::Array(messages) => (messages, None),
+        Value::Object(body) => match body.get("messages") {
+            Some(Value::Array(messages)) => (messages, Some(body)),
+            _ => return Err(invalid("expected a JSON array of messages or an object with \"messages\"")),
+        },
+        _ => return Err(invalid("expected a JSON array of messages or an object with \"messages\"")),
+    };
+    let model = body.and_then(|b| b.get("model")).and_then(|m| m.as_str());
+
+    let mut records = Vec::new();
+    if let Some(system) = body.and_then(|b| b.get("system")) {
+        records.push(system_line(&text_of(system)));
+    }
+    for (i, message) in messages.iter().enumerate() {
+        let record = match format {
+            MessagesFormat::Anthropic => from_anthropic(message),
+            MessagesFormat::OpenAi => from_openai(message),
+        };
+        let mut record = record.ok_or_else(|| {
+            invalid(format!("message {} is not a valid {} message", i + 1, format.name()))
+        })?;
+        if let (Some(model), Some(Value::Object(message))) = (model, record.get_mut("message")) {
+            if message.get("role").and_then(|r| r.as_str()) == Some("assistant") {
+                message.entry("model").or_insert_with(|| json!(model));
+            }
+        }
+        records.push(record);
+    }
+
+    let mut lines = Vec::with_capacity(records.len());
+    let mut previous: Option<String> = None;
+    for (i, record) in records.into_iter().enumerate() {
+        let timestamp = (start + Duration::seconds(i as i64)).to_rfc3339_opts(SecondsFormat::Millis, true);
+        let line = normalize_stream_line(record, session_id, i as i64 + 1, previous.as_deref(), &timestamp)
+            .ok_or_else(|| invalid(format!("message {} could not be converted", i + 1)))?;
+        previous = line.get("uuid").and_then(|u| u.as_str()).map(String::from);
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+/// Convert, write and index a messages document
+///
+/// Importing again under the same session id replaces the earlier import.
+pub fn import_messages(
+    conn: &Connection,
+    input: &Value,
+    format: MessagesFormat,
+    session_id: &str,
+    imports_dir: &Path,
+    start: DateTime<Utc>,
+) -> Result<ImportResult, IndexerError> {
+    let lines = convert_messages(input, format, session_id, start)?;
+    if lines.is_empty() {
+        return Err(invalid("no messages to import"));
+    }
+    write_import(conn, &lines, session_id, imports_dir)
+}
+
+/// Write converted lines to the imports directory and index them, replacing
+/// an earlier import of the same session
+fn write_import(
+    conn: &Connection,
+    lines: &[Value],
+    session_id: &str,
+    imports_dir: &Path,
+) -> Result<ImportResult, IndexerError> {
+    std::fs::create_dir_all(imports_dir)?;
+    let file_path = stream_file_path(imports_dir, session_id);
+    let mut document = String::new();
+    for line in lines {
+        document.push_str(&serde_json::to_string(line)?);
+        document.push('\n');
+    }
+    std::fs::write(&file_path, document)?;
+
+    let file_path_str = path_key(&file_path);
+    conn.execute("DELETE FROM lines WHERE file_path = ?1", [&file_path_str])?;
+    let indexed = index_transcript_file(conn, &file_path, 0, 1)?;
+
+    Ok(ImportResult {
+        session_id: session_id.to_string(),
+        messages: lines.len(),
+        lines_indexed: indexed.lines_indexed,
+        file_path,
+    })
+}
+
+/// Session ID prefix of conversations imported from Claude.ai
+pub const CLAUDE_AI_PREFIX: &str = "claude-ai-";
+
+/// Convert one conversation of a Claude.ai export into transcript lines
+///
+/// The session ID is `claude-ai-<conversation uuid>` and the slug is made
+/// from the conversation's name. Edited messages keep their branches.
+pub fn convert_claude_ai_conversation(conversation: &Value) -> Result<(String, Vec<Value>), IndexerError> {
+    let id = conversation
+        .get("uuid")
+        .and_then(|u| u.as_str())
+        .ok_or_else(|| invalid("conversation without a \"uuid\""))?;
+    let session_id = format!("{}{}", CLAUDE_AI_PREFIX, id);
+    let slug = conversation.get("name").and_then(|n| n.as_str()).map(slugify).filter(|s| !s.is_empty());
+    let created = conversation.get("created_at").and_then(|c| c.as_str()).unwrap_or_default();
+    let messages = conversation
+        .get("chat_messages")
+        .and_then(|m| m.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let known: HashSet<&str> = messages.iter().filter_map(|m| m.get("uuid")?.as_str()).collect();
+    let mut lines = Vec::with_capacity(messages.len());
+    let mut previous: Option<String> = None;
+    for (i, message) in messages.iter().enumerate() {
+        let role = match message.get("sender").and_then(|s| s.as_str()) {
+            Some("human") => "user",
+            Some("assistant") => "assistant",
+            _ => continue,
+        };
+        let uuid = message
+            .get("uuid")
+            .and_then(|u| u.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| format!("{}-{}", session_id, i + 1));
+        // The first message's parent is a placeholder id, not a message
+        let parent = match message.get("parent_message_uuid").and_then(|p| p.as_str()) {
+            Some(parent) if known.contains(parent) => Some(parent.to_string()),
+            Some(_) => None,
+            None => previous.clone(),
+        };
+        let timestamp = message.get("created_at").and_then(|c| c.as_str()).unwrap_or(created);
+
+        let mut line = json!({
+            "type": role,
+            "uuid": uuid,
+            "parentUuid": parent,
+            "sessionId": session_id,
+            "timestamp": timestamp,
+            "message": { "role": role, "content": claude_ai_blocks(message, &uuid) },
+        });
+        if let Some(slug) = &slug {
+            line["slug"] = json!(slug);
+        }
+        previous = Some(uuid);
+        lines.push(line);
+    }
+    Ok((session_id, lines))
+}
+
+/// Import every conversation of a Claude.ai export (`conversations.json`)
+///
+/// Conversations without messages are left out. Importing an export again
+/// replaces the conversations imported before.
+pub fn import_claude_ai(
+    conn: &Connection,
+    export: &Value,
+    imports_dir: &Path,
+) -> Result<Vec<ImportResult>, IndexerError> {
+    let conversations = match export {
+        Value::Array(conversations) => conversations.as_slice(),
+        Value::Object(_) if export.get("chat_messages").is_some() => std::slice::from_ref(export),
+        _ => return Err(invalid("expected a Claude.ai export: an array of conversations with \"chat_messages\"")),
+    };
+    let mut results = Vec::new();
+    for conversation in conversations {
+        let (session_id, lines) = convert_claude_ai_conversation(conversation)?;
+        if !lines.is_empty() {
+            results.push(write_import(conn, &lines, &session_id, imports_dir)?);
+        }
+    }
+    Ok(results)
+}
+
+/// A Claude.ai message's content as content blocks
+///
+/// Older exports only have `text`. Tool calls get ids (the export has none)
+/// so their results can be paired with them; attachments become text.
+fn claude_ai_blocks(message: &Value, uuid: &str) -> Value {
+    let mut blocks = Vec::new();
+    let mut tool_ids: Vec<(String, String)> = Vec::new();
+    let content = message.get("content").and_then(|c| c.as_array()).map(Vec::as_slice).unwrap_or_default();
+    for (i, block) in content.iter().enumerate() {
+        let name = block.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") => blocks.push(json!({ "type": "text", "text": block.get("text").cloned().unwrap_or_default() })),
+            Some("thinking") => {
+                blocks.push(json!({ "type": "thinking", "thinking": block.get("thinking").cloned().unwrap_or_default() }))
+            }
+            Some("tool_use") => {
+                let id = block
+                    .get("id")
+                    .and_then(|i| i.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("{}-tool-{}", uuid, i));
+                blocks.push(json!({
+                    "type": "tool_use",
+                    "id": id,
+                    "name": name,
+                    "input": block.get("input").cloned().unwrap_or_else(|| json!({})),
+                }));
+                tool_ids.push((name.to_string(), id));
+            }
+            Some("tool_result") => {
+                // Paired with the earliest open call of the same tool
+                let id = block.get("tool_use_id").and_then(|i| i.as_str()).map(String::from).or_else(|| {
+                    let open = tool_ids.iter().position(|(tool, _)| tool == name)?;
+                    Some(tool_ids.remove(open).1)
+                });
+                blocks.push(json!({
+                    "type": "tool_result",
+                    "tool_use_id": id.unwrap_or_default(),
+                    "content": text_of(block.get("content").unwrap_or(&Value::Null)),
+                    "is_error": block.get("is_error").and_then(|e| e.as_bool()).unwrap_or(false),
+                }));
+            }
+            _ => {}
+        }
+    }
+    if blocks.is_empty() {
+        if let Some(text) = message.get("text").and_then(|t| t.as_str()).filter(|t| !t.is_empty()) {
+            blocks.push(json!({ "type": "text", "text": text }));
+        }
+    }
+
+    let attachments = message.get("attachments").and_then(|a| a.as_array()).map(Vec::as_slice).unwrap_or_default();
+    for attachment in attachments {
+        let name = attachment.get("file_name").and_then(|n| n.as_str()).unwrap_or("attachment");
+        let text = attachment.get("extracted_content").and_then(|c| c.as_str()).unwrap_or_default();
+        blocks.push(json!({ "type": "text", "text": format!("[Attachment: {}]\n{}", name, text) }));
+    }
+    let files = message.get("files").and_then(|f| f.as_array()).map(Vec::as_slice).unwrap_or_default();
+    for file in files {
+        if let Some(name) = file.get("file_name").and_then(|n| n.as_str()) {
+            blocks.push(json!({ "type": "text", "text": format!("[File: {}]", name) }));
+        }
+    }
+    Value::Array(blocks)
+}
+
+/// A conversation name as a slug (`Fix the Parser!` → `fix-the-parser`)
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.chars().count() >= 60 {
+            break;
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// System prompt as a transcript system line
+fn system_line(text: &str) -> Value {
+    json!({
+        "type": "system",
+        "subtype": "system_prompt",
+        "content": text,
+        "message": { "role": "system", "content": text },
+    })
+}
+
+/// Text of a string or an array of text parts
+fn text_of(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Anthropic messages are already in transcript form
+pub(crate) fn from_anthropic(message: &Value) -> Option<Value> {
+    let role = message.get("role")?.as_str()?;
+    if !matches!(role, "user" | "assistant") || message.get("content").is_none() {
+        return None;
+    }
+    Some(json!({ "type": role, "message": message }))
+}
+
+pub(crate) fn from_openai(message: &Value) -> Option<Value> {
+    let role = message.get("role")?.as_str()?;
+    let content = message.get("content").unwrap_or(&Value::Null);
+
+    let (line_type, content) = match role {
+        "system" | "developer" => return Some(system_line(&text_of(content))),
+        "user" => ("user", openai_parts(content)),
+        "assistant" => {
+            let mut blocks = match openai_parts(content) {
+                Value::String(text) if text.is_empty() => Vec::new(),
+                Value::String(text) => vec![json!({ "type": "text", "text": text })],
+                Value::Array(blocks) => blocks,
+                _ => Vec::new(),
+            };
+            let calls = message.get("tool_calls").and_then(|c| c.as_array());
+            for call in calls.into_iter().flatten() {
+                blocks.push(tool_use(call.get("id"), call.get("function")?));
+            }
+            if let Some(function) = message.get("function_call") {
+                blocks.push(tool_use(None, function));
+            }
+            ("assistant", Value::Array(blocks))
+        }
+        // Tool output goes back to the model as a user turn
+        "tool" | "function" => {
+            let id = message.get("tool_call_id").or_else(|| message.get("name"));
+            let result = json!({
+                "type": "tool_result",
+                "tool_use_id": id.and_then(|i| i.as_str()).unwrap_or_default(),
+                "content": text_of(content),
+            });
+            ("user", json!([result]))
+        }
+        _ => return None,
+    };
+
+    let mut converted = Map::new();
+    converted.insert("role".to_string(), json!(line_type));
+    converted.insert("content".to_string(), content);
+    if let Some(model) = message.get("model") {
+        converted.insert("model".to_string(), model.clone());
+    }
+    Some(json!({ "type": line_type, "message": converted }))
+}
+
+/// OpenAI content as Anthropic content: strings stay strings, parts become blocks
+fn openai_parts(content: &Value) -> Value {
+    let Value::Array(parts) = content else {
+        return json!(content.as_str().unwrap_or_default());
+    };
+    let blocks = parts
+        .iter()
+        .filter_map(|part| match part.get("type")?.as_str()? {
+            "text" => Some(json!({ "type": "text", "text": part.get("text")? })),
+            "image_url" => Some(json!({
+                "type": "image",
+                "source": { "type": "url", "url": part.get("image_url")?.get("url")? },
+            })),
+            _ => None,
+        })
+        .collect();
+    Value::Array(blocks)
+}
+
+/// An OpenAI function call as a tool_use block (arguments are a JSON string)
+fn tool_use(id: Option<&Value>, function: &Value) -> Value {
+    let arguments = function.get("arguments").and_then(|a| a.as_str()).unwrap_or("{}");
+    let input = serde_json::from_str::<Value>(arguments).unwrap_or_else(|_| json!(arguments));
+    json!({
+        "type": "tool_use",
+        "id": id.and_then(|i| i.as_str()).unwrap_or_default(),
+        "name": function.get("name").and_then(|n| n.as_str()).unwrap_or_default(),
+        "input": input,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_convert_openai() {
+        let input = json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Weather in Paris?"},
+                {"role": "assistant", "content": null, "tool_calls": [
+                    {"id": "call_1", "type": "function",
+                     "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "18C"},
+                {"role": "assistant", "content": "18C and sunny."},
+            ]
+        });
+        let start = DateTime::parse_from_rfc3339("2026-01-01T10:00:00Z").unwrap().to_utc();
+        let lines = convert_messages(&input, MessagesFormat::OpenAi, "imp-1", start).unwrap();
+
+        let types: Vec<&str> = lines.iter().map(|l| l["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["system", "user", "assistant", "user", "assistant"]);
+        assert_eq!(lines[2]["message"]["content"][0]["input"]["city"], "Paris");
+        assert_eq!(lines[2]["message"]["model"], "gpt-4o");
+        assert_eq!(lines[3]["message"]["content"][0]["tool_use_id"], "call_1");
+        assert_eq!(lines[4]["parentUuid"], "imp-1-4");
+        assert_eq!(lines[4]["timestamp"], "2026-01-01T10:00:04.000Z");
+
+        // Tool results aren't valid in the Anthropic format's roles
+        let err = convert_messages(&input, MessagesFormat::Anthropic, "imp-1", start).unwrap_err();
+        assert!(err.to_string().contains("message 1"));
+    }
+
+    #[test]
+    fn test_import_messages_replaces() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init_schema(&mut conn).unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let start = Utc::now();
+
+        let input = json!([
+            {"role": "user", "content": "hi"},
+            {"role": "assistant", "content": [{"type": "text", "text": "hello"}]},
+        ]);
+        let result = import_messages(&conn, &input, MessagesFormat::Anthropic, "imp-2", tmp.path(), start).unwrap();
+        assert_eq!((result.messages, result.lines_indexed), (2, 2));
+
+        let input = json!([{"role": "user", "content": "only this"}]);
+        import_messages(&conn, &input, MessagesFormat::Anthropic, "imp-2", tmp.path(), start).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM lines WHERE session_id = 'imp-2'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_import_claude_ai() {
+        let export = json!([
+            {"uuid": "c1", "name": "Fix the Parser!", "created_at": "2025-03-01T09:00:00Z", "chat_messages": [
+                {"uuid": "m1", "sender": "human", "text": "Why does parsing fail?",
+                 "created_at": "2025-03-01T09:00:00Z", "parent_message_uuid": "00000000-0000-4000-8000-000000000000",
+                 "attachments": [{"file_name": "parser.rs", "extracted_content": "fn parse() {}"}]},
+                {"uuid": "m2", "sender": "assistant", "created_at": "2025-03-01T09:00:05Z", "parent_message_uuid": "m1",
+                 "content": [
+                     {"type": "tool_use", "name": "web_search", "input": {"query": "nom parser"}},
+                     {"type": "tool_result", "name": "web_search", "content": [{"type": "text", "text": "nom docs"}]},
+                     {"type": "text", "text": "Use nom."}
+                 ]},
+            ]},
+            {"uuid": "c2", "name": "", "chat_messages": []},
+        ]);
+        let (session_id, lines) = convert_claude_ai_conversation(&export[0]).unwrap();
+        assert_eq!(session_id, "claude-ai-c1");
+        assert_eq!(lines[0]["slug"], "fix-the-parser");
+        assert_eq!(lines[0]["parentUuid"], Value::Null);
+        assert_eq!(lines[0]["message"]["content"][1]["text"], "[Attachment: parser.rs]\nfn parse() {}");
+        assert_eq!(lines[1]["parentUuid"], "m1");
+        let blocks = &lines[1]["message"]["content"];
+        assert_eq!(blocks[0]["id"], "m2-tool-0");
+        assert_eq!(blocks[1]["tool_use_id"], "m2-tool-0");
+        assert_eq!(blocks[1]["content"], "nom docs");
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init_schema(&mut conn).unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let results = import_claude_ai(&conn, &export, tmp.path()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].messages, results[0].lines_indexed), (2, 2));
+        assert!(import_claude_ai(&conn, &json!({"messages": []}), tmp.path()).is_err());
+    }
+}
@@ -1,18 +1,31 @@
 //! Transcript JSONL file indexer
 //!
-//! Indexes transcript.jsonl files into the `lines` and `sessions` tables.
-//! Supports both full and delta (byte-offset) indexing.
-
+//! Indexes transcript.jsonl files into the `lines` and `sessions` tables,
+//! announcing each session's new lines in the `changes` feed and linking
+//! resumed sessions to the sessions they continue.
+//! Supports both full and delta (byte-offset) indexing. Reading a file and
+//! writing it are separate steps, so a full build can parse files in parallel
+//! while a single connection writes.
+
+use rayon::prelude::*;
 use rusqlite::Connection;
 use serde_json::Value;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
+use std::sync::mpsc;
+use transcript_core::path_key;
 
+use crate::archive;
+use crate::compressed;
 use crate::connection::IndexerError;
-use crate::content_trimmer::trim_raw_transcript_line;
+use crate::diagnostics;
 use crate::discovery;
-use crate::text_extract::extract_searchable_text;
+use crate::enrich::{self, EnrichedLine};
+use crate::ignore;
+use crate::lineage;
+use crate::line_reader::CompleteLines;
+use crate::prune;
+use crate::repo::RepoRoots;
 
 /// Line types that have zero searchable content and only consume raw storage.
 /// These are skipped during indexing to save ~44% of database size.
@@ -21,12 +34,19 @@ use crate::text_extract::extract_searchable_text;
 /// - `queue-operation`: internal queue operations
 const SKIP_TYPES: &[&str] = &["progress", "file-history-snapshot", "queue-operation"];
 
+/// Tool inputs or outputs at least this large are counted as `large_payloads`
+pub const LARGE_PAYLOAD_BYTES: i64 = 256 * 1024;
+
 /// Result of indexing a single transcript file
 #[derive(Debug, Default)]
 pub struct IndexResult {
     pub lines_indexed: usize,
     pub byte_offset: u64,
     pub session_id: String,
+    /// Tool inputs/outputs of at least `LARGE_PAYLOAD_BYTES`
+    pub large_payloads: usize,
+    /// Lines read despite invalid UTF-8 or a byte-order mark
+    pub recovered_lines: usize,
 }
 
 /// Result of indexing all transcript files
@@ -34,6 +54,8 @@ pub struct IndexResult {
 pub struct IndexAllResult {
     pub files_indexed: usize,
     pub lines_indexed: usize,
+    pub large_payloads: usize,
+    pub recovered_lines: usize,
 }
 
 /// Result of delta update
@@ -42,84 +64,140 @@ pub struct UpdateResult {
     pub files_checked: usize,
     pub files_updated: usize,
     pub new_lines: usize,
+    pub large_payloads: usize,
+    pub recovered_lines: usize,
 }
 
-/// Index a single transcript JSONL file (full or delta)
+/// Parsed files waiting for the writer during a full build
 ///
-/// - `conn`: Database connection
-/// - `file_path`: Path to the transcript JSONL file
-/// - `from_byte_offset`: Byte offset to start reading from (0 for full index)
-/// - `start_line_number`: Line number to start from (1 for full index)
-pub fn index_transcript_file(
-    conn: &Connection,
-    file_path: &Path,
-    from_byte_offset: u64,
-    start_line_number: i64,
-) -> Result<IndexResult, IndexerError> {
-    let metadata = std::fs::metadata(file_path)?;
-    let file_size = metadata.len();
+/// Bounds memory when parsing outpaces SQLite inserts.
+const PARSE_QUEUE: usize = 64;
+
+/// A transcript line ready to insert
+#[derive(Debug)]
+struct ParsedLine {
+    session_id: String,
+    uuid: String,
+    parent_uuid: Option<String>,
+    line_number: i64,
+    entry_type: String,
+    subtype: Option<String>,
+    timestamp: String,
+    slug: Option<String>,
+    role: Option<String>,
+    model: Option<String>,
+    cwd: Option<String>,
+    content: String,
+    raw: String,
+    payloads: Vec<ToolPayload>,
+}
 
-    // Nothing new to index
-    if from_byte_offset >= file_size {
-        return Ok(IndexResult {
-            lines_indexed: 0,
-            byte_offset: from_byte_offset,
-            session_id: String::new(),
-        });
-    }
+/// Size of one tool input (`tool_use`) or output (`tool_result`) on a line
+#[derive(Debug)]
+struct ToolPayload {
+    tool_use_id: String,
+    tool_name: Option<String>,
+    direction: &'static str,
+    bytes: i64,
+}
+
+/// Lines read from a transcript file, before anything is written
+#[derive(Debug)]
+pub struct ParsedTranscript {
+    lines: Vec<ParsedLine>,
+    session_id: String,
+    slug: Option<String>,
+    first_timestamp: Option<String>,
+    last_timestamp: Option<String>,
+    /// Last line number read
+    line_count: i64,
+    from_byte_offset: u64,
+    byte_offset: u64,
+    user: Option<String>,
+    /// Lines that aren't JSON: (line number, parse error)
+    bad_lines: Vec<(i64, String)>,
+    /// Lines with invalid UTF-8 or a byte-order mark, decoded leniently
+    recovered_lines: usize,
+}
 
-    let mut file = File::open(file_path)?;
-    if from_byte_offset > 0 {
-        file.seek(SeekFrom::Start(from_byte_offset))?;
+impl ParsedTranscript {
+    /// Last line number read
+    pub(crate) fn line_count(&self) -> i64 {
+        self.line_count
     }
+}
 
-    let reader = BufReader::new(file);
-    let mut indexed_count: usize = 0;
-    let mut session_id = String::new();
-    let mut slug: Option<String> = None;
-    let mut first_timestamp: Option<String> = None;
-    let mut last_timestamp: Option<String> = None;
-    let mut line_number = start_line_number;
+/// Read and extract a transcript file from `from_byte_offset` (no database access)
+///
+/// This is the CPU-heavy half of indexing (JSON parsing, text extraction and
+/// trimming), so a full build runs it on worker threads.
+pub fn parse_transcript_file(
+    file_path: &Path,
+    from_byte_offset: u64,
+    start_line_number: i64,
+) -> Result<ParsedTranscript, IndexerError> {
+    // Skip partial first line when reading from offset
     let mut first_line = from_byte_offset > 0;
+    let mut bad_lines = Vec::new();
+    let mut parsed = parse_lines_with(file_path, from_byte_offset, start_line_number, |raw_line, line_number| {
+        if std::mem::take(&mut first_line) && !raw_line.starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(raw_line)
+            .map_err(|e| bad_lines.push((line_number, e.to_string())))
+            .ok()
+    })?;
+    parsed.bad_lines = bad_lines;
+    Ok(parsed)
+}
 
-    let mut insert_stmt = conn.prepare_cached(
-        "INSERT OR REPLACE INTO lines
-         (session_id, uuid, parent_uuid, line_number, type, subtype, timestamp,
-          slug, role, model, cwd, content, raw, file_path,
-          turn_id, turn_sequence, session_name)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
-    )?;
-
-    // Wrap in explicit savepoint for batch performance
-    // (We can't use conn.transaction() here since we borrow conn for prepared stmt)
-    conn.execute_batch("SAVEPOINT index_transcript")?;
-
-    for line_result in reader.lines() {
-        let raw_line = match line_result {
-            Ok(l) => l,
-            Err(_) => {
-                line_number += 1;
-                continue;
-            }
-        };
+/// Read a file from `from_byte_offset`, turning each line into a transcript
+/// line with `convert` (given the line and its number; `None` skips it)
+pub(crate) fn parse_lines_with<F>(
+    file_path: &Path,
+    from_byte_offset: u64,
+    start_line_number: i64,
+    mut convert: F,
+) -> Result<ParsedTranscript, IndexerError>
+where
+    F: FnMut(&str, i64) -> Option<Value>,
+{
+    let file_size = compressed::plain_len(file_path)?;
+    let user = discovery::user_for_path(file_path, &discovery::home_roots());
+
+    let mut parsed_file = ParsedTranscript {
+        lines: Vec::new(),
+        session_id: String::new(),
+        slug: None,
+        first_timestamp: None,
+        last_timestamp: None,
+        line_count: start_line_number - 1,
+        from_byte_offset,
+        byte_offset: from_byte_offset.max(file_size.unwrap_or(0)),
+        user,
+        bad_lines: Vec::new(),
+        recovered_lines: 0,
+    };
+
+    // Nothing new to index (a compressed file's length isn't known until read)
+    if file_size.is_some_and(|size| from_byte_offset >= size) {
+        return Ok(parsed_file);
+    }
 
-        // Skip partial first line when reading from offset
-        if first_line {
-            first_line = false;
-            if !raw_line.starts_with('{') {
-                line_number += 1;
-                continue;
-            }
-        }
+    let mut reader = CompleteLines::new(compressed::open_at(file_path, from_byte_offset)?, from_byte_offset);
+    let mut line_number = start_line_number;
+    // Tool names by tool_use_id, for naming the results that follow
+    let mut tool_names = HashMap::new();
 
+    for raw_line in &mut reader {
         let trimmed = raw_line.trim();
         if trimmed.is_empty() {
             continue;
         }
 
-        let parsed: Value = match serde_json::from_str(trimmed) {
-            Ok(v) => v,
-            Err(_) => {
+        let parsed: Value = match convert(trimmed, line_number) {
+            Some(v) => v,
+            None => {
                 line_number += 1;
                 continue;
             }
@@ -128,12 +206,12 @@ pub fn index_transcript_file(
         // Extract fields
         if let Some(sid) = parsed.get("sessionId").and_then(|v| v.as_str()) {
             if !sid.is_empty() {
-                session_id = sid.to_string();
+                parsed_file.session_id = sid.to_string();
             }
         }
         if let Some(s) = parsed.get("slug").and_then(|v| v.as_str()) {
             if !s.is_empty() {
-                slug = Some(s.to_string());
+                parsed_file.slug = Some(s.to_string());
             }
         }
 
@@ -143,11 +221,11 @@ pub fn index_transcript_file(
             .unwrap_or("")
             .to_string();
 
-        if first_timestamp.is_none() && !timestamp.is_empty() {
-            first_timestamp = Some(timestamp.clone());
+        if parsed_file.first_timestamp.is_none() && !timestamp.is_empty() {
+            parsed_file.first_timestamp = Some(timestamp.clone());
         }
         if !timestamp.is_empty() {
-            last_timestamp = Some(timestamp.clone());
+            parsed_file.last_timestamp = Some(timestamp.clone());
         }
 
         let entry_type = parsed
@@ -162,7 +240,7 @@ pub fn index_transcript_file(
             continue;
         }
 
-        let content = extract_searchable_text(&parsed);
+        let EnrichedLine { value: parsed, content, raw } = enrich::pipeline().enrich(parsed);
 
         let uuid = parsed
             .get("uuid")
@@ -197,58 +275,228 @@ pub fn index_transcript_file(
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        let file_path_str = file_path.to_string_lossy().to_string();
-        let trimmed_raw = trim_raw_transcript_line(&parsed);
+        // Sized in full (trimming only shortens the raw column)
+        let payloads = tool_payloads(&parsed, &mut tool_names);
+        let raw = raw.unwrap_or_else(|| parsed.to_string());
 
-        insert_stmt.execute(rusqlite::params![
-            session_id,
+        parsed_file.lines.push(ParsedLine {
+            // Lines take the session id and slug seen so far
+            session_id: parsed_file.session_id.clone(),
             uuid,
             parent_uuid,
             line_number,
             entry_type,
             subtype,
             timestamp,
-            slug,
+            slug: parsed_file.slug.clone(),
             role,
             model,
             cwd,
             content,
-            trimmed_raw,
+            raw,
+            payloads,
+        });
+
+        line_number += 1;
+    }
+
+    parsed_file.line_count = line_number - 1;
+    // A partly written last line is read again next time
+    parsed_file.byte_offset = reader.offset;
+    parsed_file.recovered_lines = reader.recovered;
+    Ok(parsed_file)
+}
+
+/// Sizes of the tool_use inputs and tool_result outputs in a line's message
+fn tool_payloads(parsed: &Value, tool_names: &mut HashMap<String, String>) -> Vec<ToolPayload> {
+    let Some(blocks) = parsed
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+    else {
+        return Vec::new();
+    };
+
+    let mut payloads = Vec::new();
+    for block in blocks {
+        let size = |value: Option<&Value>| match value {
+            Some(Value::String(text)) => text.len() as i64,
+            Some(value) => value.to_string().len() as i64,
+            None => 0,
+        };
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("tool_use") => {
+                let Some(id) = block.get("id").and_then(|v| v.as_str()) else { continue };
+                let name = block.get("name").and_then(|v| v.as_str()).map(str::to_string);
+                if let Some(name) = &name {
+                    tool_names.insert(id.to_string(), name.clone());
+                }
+                payloads.push(ToolPayload {
+                    tool_use_id: id.to_string(),
+                    tool_name: name,
+                    direction: "input",
+                    bytes: size(block.get("input")),
+                });
+            }
+            Some("tool_result") => {
+                let Some(id) = block.get("tool_use_id").and_then(|v| v.as_str()) else { continue };
+                payloads.push(ToolPayload {
+                    tool_use_id: id.to_string(),
+                    tool_name: tool_names.get(id).cloned(),
+                    direction: "output",
+                    bytes: size(block.get("content")),
+                });
+            }
+            _ => {}
+        }
+    }
+    payloads
+}
+
+/// Write a parsed transcript file's lines and session row
+///
+/// A file that fails part way is rolled back, so it can be retried whole.
+pub fn write_transcript(
+    conn: &Connection,
+    file_path: &Path,
+    parsed: &ParsedTranscript,
+) -> Result<IndexResult, IndexerError> {
+    if parsed.from_byte_offset >= parsed.byte_offset {
+        return Ok(IndexResult {
+            lines_indexed: 0,
+            byte_offset: parsed.from_byte_offset,
+            session_id: String::new(),
+            large_payloads: 0,
+            recovered_lines: 0,
+        });
+    }
+
+    // Sessions matching an ignore rule are remembered instead of written
+    let cwd = parsed.lines.iter().find_map(|line| line.cwd.as_deref());
+    if transcript_core::ignore_rules().ignores(&parsed.session_id, cwd, parsed.slug.as_deref()) {
+        ignore::record_ignored(conn, &parsed.session_id, cwd, parsed.slug.as_deref())?;
+        return Ok(IndexResult {
+            lines_indexed: 0,
+            byte_offset: parsed.from_byte_offset,
+            session_id: parsed.session_id.clone(),
+            large_payloads: 0,
+            recovered_lines: 0,
+        });
+    }
+
+    // Lines that aren't JSON are skipped; the log of them is best effort
+    for (line_number, error) in &parsed.bad_lines {
+        let _ = diagnostics::record_index_error(conn, &path_key(file_path), "transcript", *line_number, error);
+    }
+
+    // Explicit savepoint for batch performance (nests inside a build's savepoint)
+    conn.execute_batch("SAVEPOINT index_transcript")?;
+    match insert_transcript(conn, file_path, parsed) {
+        Ok(()) => conn.execute_batch("RELEASE index_transcript")?,
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO index_transcript; RELEASE index_transcript")?;
+            return Err(e);
+        }
+    }
+
+    Ok(IndexResult {
+        lines_indexed: parsed.lines.len(),
+        byte_offset: parsed.byte_offset,
+        session_id: parsed.session_id.clone(),
+        large_payloads: parsed
+            .lines
+            .iter()
+            .flat_map(|line| &line.payloads)
+            .filter(|payload| payload.bytes >= LARGE_PAYLOAD_BYTES)
+            .count(),
+        recovered_lines: parsed.recovered_lines,
+    })
+}
+
+fn insert_transcript(
+    conn: &Connection,
+    file_path: &Path,
+    parsed: &ParsedTranscript,
+) -> Result<(), IndexerError> {
+    let file_path_str = path_key(file_path);
+
+    let mut insert_stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO lines
+         (session_id, uuid, parent_uuid, line_number, type, subtype, timestamp,
+          slug, role, model, cwd, content, raw, file_path,
+          turn_id, turn_sequence, session_name, user, repo_root)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+    )?;
+    let mut payload_stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO tool_payloads
+         (session_id, uuid, line_number, tool_use_id, tool_name, direction, bytes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+
+    let mut repo_roots = RepoRoots::default();
+    for line in &parsed.lines {
+        let repo_root = line.cwd.as_deref().and_then(|cwd| repo_roots.get(cwd));
+        insert_stmt.execute(rusqlite::params![
+            line.session_id,
+            line.uuid,
+            line.parent_uuid,
+            line.line_number,
+            line.entry_type,
+            line.subtype,
+            line.timestamp,
+            line.slug,
+            line.role,
+            line.model,
+            line.cwd,
+            line.content,
+            line.raw,
             file_path_str,
             Option::<String>::None, // turn_id
             Option::<i64>::None,    // turn_sequence
             Option::<String>::None, // session_name
+            parsed.user,
+            repo_root,
         ])?;
-
-        indexed_count += 1;
-        line_number += 1;
+        for payload in &line.payloads {
+            payload_stmt.execute(rusqlite::params![
+                line.session_id,
+                line.uuid,
+                line.line_number,
+                payload.tool_use_id,
+                payload.tool_name,
+                payload.direction,
+                payload.bytes,
+            ])?;
+        }
     }
 
-    conn.execute_batch("RELEASE index_transcript")?;
-
-    let new_byte_offset = file_size;
-    let file_path_str = file_path.to_string_lossy().to_string();
     let now = chrono::Utc::now().to_rfc3339();
 
+    let changed: BTreeSet<&str> = parsed.lines.iter().map(|line| line.session_id.as_str()).collect();
+    for session_id in changed {
+        record_change(conn, session_id, &now)?;
+    }
+
     // Update sessions table
-    if from_byte_offset == 0 {
+    if parsed.from_byte_offset == 0 {
         conn.execute(
             "INSERT OR REPLACE INTO sessions
-             (file_path, session_id, slug, line_count, byte_offset, first_timestamp, last_timestamp, indexed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+             (file_path, session_id, slug, line_count, byte_offset, first_timestamp, last_timestamp, indexed_at, user)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             rusqlite::params![
                 file_path_str,
-                if session_id.is_empty() {
+                if parsed.session_id.is_empty() {
                     "unknown"
                 } else {
-                    &session_id
+                    &parsed.session_id
                 },
-                slug,
-                line_number - 1,
-                new_byte_offset as i64,
-                first_timestamp,
-                last_timestamp,
+                parsed.slug,
+                parsed.line_count,
+                parsed.byte_offset as i64,
+                parsed.first_timestamp,
+                parsed.last_timestamp,
                 now,
+                parsed.user,
             ],
         )?;
     } else {
@@ -256,23 +504,65 @@ pub fn index_transcript_file(
             "UPDATE sessions SET line_count = ?1, byte_offset = ?2, last_timestamp = ?3, indexed_at = ?4
              WHERE file_path = ?5",
             rusqlite::params![
-                line_number - 1,
-                new_byte_offset as i64,
-                last_timestamp,
+                parsed.line_count,
+                parsed.byte_offset as i64,
+                parsed.last_timestamp,
                 now,
                 file_path_str,
             ],
         )?;
     }
 
-    Ok(IndexResult {
-        lines_indexed: indexed_count,
-        byte_offset: new_byte_offset,
-        session_id,
-    })
+    // A new file may be a resumed session
+    if parsed.from_byte_offset == 0 {
+        lineage::detect_file_lineage(conn, &file_path_str)?;
+    }
+
+    Ok(())
+}
+
+/// Change rows kept for readers; older ones are pruned as new ones arrive
+pub const CHANGES_KEPT: i64 = 10_000;
+
+/// Announce a session's new lines in the `changes` feed
+pub(crate) fn record_change(conn: &Connection, session_id: &str, now: &str) -> Result<(), IndexerError> {
+    let inserted = conn.execute(
+        "INSERT INTO changes (session_id, max_line_id, changed_at)
+         SELECT ?1, MAX(id), ?2 FROM lines WHERE session_id = ?1 HAVING MAX(id) IS NOT NULL",
+        rusqlite::params![session_id, now],
+    )?;
+    // Nothing inserted: last_insert_rowid() would be some other table's row
+    if inserted == 0 {
+        return Ok(());
+    }
+    conn.execute(
+        "DELETE FROM changes WHERE id <= last_insert_rowid() - ?1",
+        [CHANGES_KEPT],
+    )?;
+    Ok(())
+}
+
+/// Index a single transcript JSONL file (full or delta)
+///
+/// - `conn`: Database connection
+/// - `file_path`: Path to the transcript JSONL file
+/// - `from_byte_offset`: Byte offset to start reading from (0 for full index)
+/// - `start_line_number`: Line number to start from (1 for full index)
+pub fn index_transcript_file(
+    conn: &Connection,
+    file_path: &Path,
+    from_byte_offset: u64,
+    start_line_number: i64,
+) -> Result<IndexResult, IndexerError> {
+    let parsed = parse_transcript_file(file_path, from_byte_offset, start_line_number)?;
+    write_transcript(conn, file_path, &parsed)
 }
 
 /// Index all transcript files (full rebuild)
+///
+/// Files are parsed in parallel on the rayon pool (sized by
+/// `RAYON_NUM_THREADS`, default one per core) and written in order of
+/// completion by this thread, inside one savepoint.
 pub fn index_all_transcripts<F>(
     conn: &Connection,
     projects_dir: Option<&Path>,
@@ -281,27 +571,51 @@ pub fn index_all_transcripts<F>(
 where
     F: FnMut(&str, usize, usize, usize),
 {
-    let files = discovery::find_transcript_files(projects_dir);
+    let mut files = discovery::find_transcript_files(projects_dir);
+    files.retain(|file| {
+        !ignore::skips_file(conn, file) && !archive::is_archived(conn, file) && !prune::is_deleted(conn, file)
+    });
     let total = files.len();
     let mut result = IndexAllResult::default();
 
-    for (i, file) in files.iter().enumerate() {
-        match index_transcript_file(conn, file, 0, 1) {
-            Ok(r) => {
-                result.files_indexed += 1;
-                result.lines_indexed += r.lines_indexed;
-                on_progress(
-                    &file.to_string_lossy(),
-                    i + 1,
-                    total,
-                    r.lines_indexed,
-                );
-            }
-            Err(e) => {
-                eprintln!("Error indexing {}: {}", file.display(), e);
+    std::thread::scope(|scope| -> Result<(), IndexerError> {
+        // The receiver lives in this closure, so however the writer below
+        // returns it is dropped before the scope waits for the parsers, and a
+        // parser blocked on the full queue gets a send error instead of hanging
+        let (tx, rx) = mpsc::sync_channel(PARSE_QUEUE);
+        let files = &files;
+        scope.spawn(move || {
+            // A send fails once the writer has returned early: stop parsing
+            let _ = files.par_iter().try_for_each_with(tx, |tx, file| {
+                tx.send((file, parse_transcript_file(file, 0, 1))).map_err(drop)
+            });
+        });
+
+        conn.execute_batch("SAVEPOINT index_all")?;
+        for (i, (file, parsed)) in rx.iter().enumerate() {
+            match parsed.and_then(|p| write_transcript(conn, file, &p)) {
+                Ok(r) => {
+                    diagnostics::file_indexed(conn, file);
+                    result.files_indexed += 1;
+                    result.lines_indexed += r.lines_indexed;
+                    result.large_payloads += r.large_payloads;
+                    result.recovered_lines += r.recovered_lines;
+                    on_progress(
+                        &file.to_string_lossy(),
+                        i + 1,
+                        total,
+                        r.lines_indexed,
+                    );
+                }
+                Err(e) => diagnostics::file_failed(conn, file, "transcript", &e),
             }
         }
-    }
+        conn.execute_batch("RELEASE index_all")?;
+        Ok(())
+    })?;
+
+    // Resumed sessions written before the sessions they continue
+    lineage::detect_all_lineage(conn)?;
 
     // Update last indexed timestamp
     let now = chrono::Utc::now().to_rfc3339();
@@ -313,6 +627,34 @@ where
     Ok(result)
 }
 
+/// Index what a transcript gained since it was last indexed; `None` when
+/// it hasn't grown or can't be read, and for files of ignored, archived or
+/// deleted sessions
+pub fn update_transcript_file(conn: &Connection, file: &Path) -> Result<Option<IndexResult>, IndexerError> {
+    if ignore::skips_file(conn, file) || archive::is_archived(conn, file) || prune::is_deleted(conn, file) {
+        return Ok(None);
+    }
+    let state: Option<(i64, i64, Option<String>)> = conn
+        .query_row(
+            "SELECT byte_offset, line_count, indexed_at FROM sessions WHERE file_path = ?1",
+            [path_key(file)],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok();
+    let Ok(metadata) = std::fs::metadata(file) else {
+        return Ok(None);
+    };
+    if let Some((offset, _, indexed_at)) = &state {
+        if !compressed::may_have_grown(file, &metadata, *offset as u64, indexed_at.as_deref()) {
+            return Ok(None);
+        }
+    }
+
+    let from_offset = state.as_ref().map(|(o, _, _)| *o as u64).unwrap_or(0);
+    let start_line = state.as_ref().map(|(_, c, _)| c + 1).unwrap_or(1);
+    index_transcript_file(conn, file, from_offset, start_line).map(Some)
+}
+
 /// Update index with only new content (delta update)
 pub fn update_transcripts<F>(
     conn: &Connection,
@@ -329,45 +671,20 @@ where
     for (i, file) in files.iter().enumerate() {
         result.files_checked += 1;
 
-        let file_path_str = file.to_string_lossy().to_string();
-
-        // Get current index state
-        let state: Option<(i64, i64)> = conn
-            .query_row(
-                "SELECT byte_offset, line_count FROM sessions WHERE file_path = ?1",
-                [&file_path_str],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .ok();
-
-        // Get current file size
-        let file_size = match std::fs::metadata(file) {
-            Ok(m) => m.len(),
-            Err(_) => continue,
-        };
-
-        // Skip if file hasn't grown
-        if let Some((offset, _)) = state {
-            if offset as u64 >= file_size {
-                on_progress(&file_path_str, i + 1, total, 0, true);
-                continue;
-            }
-        }
-
-        let from_offset = state.map(|(o, _)| o as u64).unwrap_or(0);
-        let start_line = state.map(|(_, c)| c + 1).unwrap_or(1);
-
-        match index_transcript_file(conn, file, from_offset, start_line) {
-            Ok(r) => {
+        let file_path_str = path_key(file);
+        match update_transcript_file(conn, file) {
+            Ok(None) => on_progress(&file_path_str, i + 1, total, 0, true),
+            Ok(Some(r)) => {
+                diagnostics::file_indexed(conn, file);
                 if r.lines_indexed > 0 {
                     result.files_updated += 1;
                     result.new_lines += r.lines_indexed;
+                    result.large_payloads += r.large_payloads;
+                    result.recovered_lines += r.recovered_lines;
                 }
                 on_progress(&file_path_str, i + 1, total, r.lines_indexed, false);
             }
-            Err(e) => {
-                eprintln!("Error updating {}: {}", file.display(), e);
-            }
+            Err(e) => diagnostics::file_failed(conn, file, "transcript", &e),
         }
     }
 
@@ -399,10 +716,8 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         let file_path = tmp.path().join("transcript.jsonl");
 
-        let lines = vec![
-            r#"{"sessionId":"sess-1","uuid":"uuid-1","type":"user","timestamp":"2024-01-01T00:00:00Z","message":{"content":"Hello world","role":"user"}}"#,
-            r#"{"sessionId":"sess-1","uuid":"uuid-2","type":"assistant","timestamp":"2024-01-01T00:00:01Z","message":{"content":"Hi there","role":"assistant","model":"claude-3"}}"#,
-        ];
+        let lines = [r#"{"sessionId":"sess-1","uuid":"uuid-1","type":"user","timestamp":"2024-01-01T00:00:00Z","message":{"content":"Hello world","role":"user"}}"#,
+            r#"{"sessionId":"sess-1","uuid":"uuid-2","type":"assistant","timestamp":"2024-01-01T00:00:01Z","message":{"content":"Hi there","role":"assistant","model":"claude-3"}}"#];
         fs::write(&file_path, lines.join("\n") + "\n").unwrap();
 
         let result = index_transcript_file(&conn, &file_path, 0, 1).unwrap();
@@ -423,6 +738,43 @@ mod tests {
         assert_eq!(session_count, 1);
     }
 
+    #[test]
+    fn test_index_all_transcripts_in_parallel() {
+        let conn = setup_db();
+        let tmp = tempfile::tempdir().unwrap();
+
+        for f in 0..12 {
+            let dir = tmp.path().join(format!("project-{}", f % 3));
+            fs::create_dir_all(&dir).unwrap();
+            let lines: Vec<String> = (0..5)
+                .map(|n| {
+                    format!(
+                        r#"{{"sessionId":"sess-{f}","uuid":"uuid-{f}-{n}","type":"user","timestamp":"2024-01-01T00:00:0{n}Z","message":{{"content":"line {n}"}}}}"#
+                    )
+                })
+                .collect();
+            fs::write(dir.join(format!("sess-{}.jsonl", f)), lines.join("\n") + "\n").unwrap();
+        }
+
+        let mut progress = Vec::new();
+        let result = index_all_transcripts(&conn, Some(tmp.path()), |_, done, total, _| {
+            progress.push((done, total));
+        })
+        .unwrap();
+        assert_eq!(result.files_indexed, 12);
+        assert_eq!(result.lines_indexed, 60);
+        assert_eq!(progress.last(), Some(&(12, 12)));
+
+        let (lines, sessions): (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM lines), (SELECT COUNT(*) FROM sessions WHERE line_count = 5)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((lines, sessions), (60, 12));
+    }
+
     #[test]
     fn test_delta_indexing() {
         let conn = setup_db();
@@ -454,6 +806,55 @@ mod tests {
             .query_row("SELECT COUNT(*) FROM lines", [], |row| row.get(0))
             .unwrap();
         assert_eq!(count, 2);
+
+        // Each batch announced the session's newest line
+        let changes: Vec<(String, i64)> = conn
+            .prepare("SELECT session_id, max_line_id FROM changes ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        let max_id: i64 = conn.query_row("SELECT MAX(id) FROM lines", [], |row| row.get(0)).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1], ("sess-1".to_string(), max_id));
+    }
+
+    #[test]
+    fn test_truncated_trailing_line_is_not_dropped() {
+        let conn = setup_db();
+        let tmp = tempfile::tempdir().unwrap();
+        let file_path = tmp.path().join("transcript.jsonl");
+
+        let line1 = r#"{"sessionId":"sess-1","uuid":"uuid-1","type":"user","timestamp":"2024-01-01T00:00:00Z","message":{"content":"First"}}"#;
+        let line2 = r#"{"sessionId":"sess-1","uuid":"uuid-2","type":"assistant","timestamp":"2024-01-01T00:00:01Z","message":{"content":"Second"}}"#;
+        let (head, tail) = line2.split_at(50);
+
+        // Claude Code is part way through writing the second line
+        fs::write(&file_path, format!("{}\n{}", line1, head)).unwrap();
+        let result1 = index_transcript_file(&conn, &file_path, 0, 1).unwrap();
+        assert_eq!(result1.lines_indexed, 1);
+        assert_eq!(result1.byte_offset, line1.len() as u64 + 1);
+
+        // Reading again before it finishes changes nothing
+        let again = index_transcript_file(&conn, &file_path, result1.byte_offset, 2).unwrap();
+        assert_eq!((again.lines_indexed, again.byte_offset), (0, result1.byte_offset));
+
+        let mut f = fs::OpenOptions::new().append(true).open(&file_path).unwrap();
+        std::io::Write::write_all(&mut f, format!("{}\n", tail).as_bytes()).unwrap();
+        let result2 = index_transcript_file(&conn, &file_path, result1.byte_offset, 2).unwrap();
+        assert_eq!(result2.lines_indexed, 1);
+        assert_eq!(result2.byte_offset, fs::metadata(&file_path).unwrap().len());
+
+        let (uuid, line_number, line_count): (String, i64, i64) = conn
+            .query_row(
+                "SELECT l.uuid, l.line_number, s.line_count FROM lines l JOIN sessions s USING (file_path)
+                 WHERE l.line_number = 2",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((uuid.as_str(), line_number, line_count), ("uuid-2", 2, 2));
     }
 
     #[test]
@@ -479,13 +880,11 @@ not valid json
         let file_path = tmp.path().join("transcript.jsonl");
 
         // Mix of searchable and non-searchable types
-        let lines = vec![
-            r#"{"sessionId":"sess-1","uuid":"uuid-1","type":"user","timestamp":"2024-01-01T00:00:00Z","message":{"content":"Hello world","role":"user"}}"#,
+        let lines = [r#"{"sessionId":"sess-1","uuid":"uuid-1","type":"user","timestamp":"2024-01-01T00:00:00Z","message":{"content":"Hello world","role":"user"}}"#,
             r#"{"sessionId":"sess-1","uuid":"uuid-2","type":"progress","timestamp":"2024-01-01T00:00:01Z","data":{"elapsed":1.5}}"#,
             r#"{"sessionId":"sess-1","uuid":"uuid-3","type":"file-history-snapshot","timestamp":"2024-01-01T00:00:02Z","data":{"files":["a.ts"]}}"#,
             r#"{"sessionId":"sess-1","uuid":"uuid-4","type":"queue-operation","timestamp":"2024-01-01T00:00:03Z","data":{"op":"push"}}"#,
-            r#"{"sessionId":"sess-1","uuid":"uuid-5","type":"assistant","timestamp":"2024-01-01T00:00:04Z","message":{"content":"Hi there","role":"assistant","model":"claude-3"}}"#,
-        ];
+            r#"{"sessionId":"sess-1","uuid":"uuid-5","type":"assistant","timestamp":"2024-01-01T00:00:04Z","message":{"content":"Hi there","role":"assistant","model":"claude-3"}}"#];
         fs::write(&file_path, lines.join("\n") + "\n").unwrap();
 
         let result = index_transcript_file(&conn, &file_path, 0, 1).unwrap();
@@ -531,4 +930,65 @@ not valid json
             .unwrap();
         assert_eq!(count, 1);
     }
+
+    #[test]
+    fn test_tool_payload_sizes_recorded() {
+        let conn = setup_db();
+        let tmp = tempfile::tempdir().unwrap();
+        let file_path = tmp.path().join("transcript.jsonl");
+
+        let big = "x".repeat(LARGE_PAYLOAD_BYTES as usize);
+        let lines = [
+            r#"{"sessionId":"s1","uuid":"u1","type":"assistant","timestamp":"2024-01-01T00:00:00Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Read","input":{"file_path":"/a"}}]}}"#.to_string(),
+            format!(
+                r#"{{"sessionId":"s1","uuid":"u2","type":"user","timestamp":"2024-01-01T00:00:01Z","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t1","content":"{}"}}]}}}}"#,
+                big
+            ),
+        ];
+        fs::write(&file_path, lines.join("\n") + "\n").unwrap();
+
+        let result = index_transcript_file(&conn, &file_path, 0, 1).unwrap();
+        assert_eq!(result.large_payloads, 1);
+
+        let rows: Vec<(i64, String, String, i64)> = conn
+            .prepare("SELECT line_number, tool_name, direction, bytes FROM tool_payloads ORDER BY line_number")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        // Sizes are taken before the raw line is trimmed; results take the tool's name
+        assert_eq!(
+            rows,
+            vec![
+                (1, "Read".to_string(), "input".to_string(), r#"{"file_path":"/a"}"#.len() as i64),
+                (2, "Read".to_string(), "output".to_string(), LARGE_PAYLOAD_BYTES),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_change_without_lines_keeps_feed() {
+        let conn = setup_db();
+        let tmp = tempfile::tempdir().unwrap();
+        let file_path = tmp.path().join("transcript.jsonl");
+        fs::write(
+            &file_path,
+            r#"{"sessionId":"s1","uuid":"u1","type":"user","timestamp":"2024-01-01T00:00:00Z","message":{"role":"user","content":"hi"}}"#.to_string() + "\n",
+        )
+        .unwrap();
+        index_transcript_file(&conn, &file_path, 0, 1).unwrap();
+
+        // A line rowid far past the feed's ids is the last insert on the connection
+        conn.execute(
+            "INSERT INTO lines (id, session_id, uuid, line_number, type, timestamp, raw, file_path)
+             VALUES (50000, 's2', 'u2', 1, 'user', '', '{}', '/tmp/t.jsonl')",
+            [],
+        )
+        .unwrap();
+        record_change(&conn, "no-such-session", "now").unwrap();
+
+        let changes: i64 = conn.query_row("SELECT COUNT(*) FROM changes", [], |row| row.get(0)).unwrap();
+        assert_eq!(changes, 1);
+    }
 }
@@ -5,22 +5,70 @@
 //! Both share the same `~/.claude-code-sdk/transcripts.db` file.
 
 pub mod adapter;
+pub mod annotations;
+pub mod archive;
+pub mod bookmarks;
+pub mod compressed;
 pub mod connection;
 pub mod content_trimmer;
 pub mod correlation;
 pub mod daemon;
+pub mod diagnostics;
 pub mod discovery;
+pub mod encryption;
+pub mod enrich;
 pub mod hook_indexer;
+pub mod ignore;
+pub mod import;
 pub mod indexer;
+pub mod lineage;
+mod line_reader;
+pub mod maintain;
+pub mod merge;
+pub mod prune;
 pub mod rebuild;
+pub mod repo;
+pub mod reviews;
+pub mod saved_searches;
 pub mod schema;
+pub mod stream;
+pub mod tags;
+pub mod trash;
 pub mod text_extract;
 
+pub use adapter::{configured_adapters, index_adapter_file, index_adapters, Adapter, MessagesLogAdapter};
+pub use annotations::{add_annotation, remove_annotation};
+pub use archive::{archive_candidates, archive_sessions, find_archivable, ArchiveCandidate, ArchiveResult};
+pub use bookmarks::{add_bookmark, remove_bookmark};
 pub use connection::IndexerDb;
 pub use correlation::{correlate_lines_to_turns, CorrelationResult};
 pub use daemon::IndexerDaemon;
-pub use discovery::{find_hook_files, find_transcript_files};
-pub use hook_indexer::{index_all_hook_files, index_hook_file, update_hook_index, HookIndexResult};
-pub use indexer::{index_all_transcripts, index_transcript_file, update_transcripts, IndexResult};
-pub use rebuild::rebuild_index;
-pub use schema::{init_schema, migrate_schema, DB_VERSION};
+pub use diagnostics::{
+    clear_index_errors, failed_files, record_index_error, record_slow_queries, retry_failed_files, RetryResult,
+    MAX_INDEX_ERRORS,
+};
+pub use discovery::{default_exports_dir, default_imports_dir, default_streams_dir, default_trash_dir, find_hook_files, find_transcript_files};
+pub use encryption::{decrypt_database, encrypt_database, is_encrypted};
+pub use enrich::{pipeline, set_pipeline, EnrichedLine, Enricher, Pipeline};
+pub use hook_indexer::{index_all_hook_files, index_hook_file, update_hook_file, update_hook_index, HookIndexResult};
+pub use ignore::{find_ignored_files, release_unignored};
+pub use import::{convert_claude_ai_conversation, convert_messages, import_claude_ai, import_messages, ImportResult, MessagesFormat, CLAUDE_AI_PREFIX};
+pub use indexer::{
+    index_all_transcripts, index_transcript_file, update_transcript_file, update_transcripts, IndexResult, UpdateResult,
+    LARGE_PAYLOAD_BYTES,
+};
+pub use lineage::{detect_all_lineage, detect_file_lineage};
+pub use maintain::{maintain, MaintainResult};
+pub use merge::{merge_database, MergeResult};
+pub use prune::{delete_sessions, find_stale_files, prune_files, prune_stale_files, IndexedFileKind, PruneResult, StaleFile, StaleReason};
+pub use rebuild::{rebuild_index, rebuild_index_with_trash};
+pub use repo::{find_repo_root, RepoRoots};
+pub use reviews::set_review_status;
+pub use saved_searches::{remove_saved_search, save_search};
+pub use schema::{
+    check_not_newer, init_schema, init_views, migrate_schema, record_writer, reporting_views,
+    DB_VERSION, INDEXER_VERSION, READER_COMPAT_VERSION,
+};
+pub use stream::{index_stream, StreamResult};
+pub use tags::{add_tag, remove_tag};
+pub use trash::{empty_trash, export_sessions, find_bundle, list_trash, restore_bundle, trash_files, TrashBundle};
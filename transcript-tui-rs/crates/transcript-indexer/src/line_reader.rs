@@ -0,0 +1,74 @@
+//! Newline-terminated lines of a JSONL file that is still being written
+//!
+//! Claude Code and the hook handlers append to their files while the indexer
+//! reads them, so the last line may be only partly written. Its bytes are not
+//! consumed: `offset` stays at the end of the last complete line, which is
+//! where the next delta read starts, so the line is indexed once it is whole.
+//!
+//! Lines with invalid UTF-8 or a byte-order mark are decoded leniently (see
+//! `transcript_core::decode_line`) rather than dropped, and counted.
+
+use std::io::BufRead;
+
+use transcript_core::decode_line;
+
+/// Iterator over complete lines
+pub(crate) struct CompleteLines<R> {
+    reader: R,
+    buf: Vec<u8>,
+    /// Byte offset just past the last complete line read
+    pub offset: u64,
+    /// Lines that had invalid UTF-8 or a byte-order mark
+    pub recovered: usize,
+}
+
+impl<R: BufRead> CompleteLines<R> {
+    /// Read lines from `reader`, which is positioned at byte `offset`
+    pub fn new(reader: R, offset: u64) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            offset,
+            recovered: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for CompleteLines<R> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buf.clear();
+        // A read error ends the pass like a partial line: retried next time
+        let read = self.reader.read_until(b'\n', &mut self.buf).ok()?;
+        if read == 0 || self.buf.last() != Some(&b'\n') {
+            return None;
+        }
+        self.offset += read as u64;
+
+        self.buf.pop();
+        if self.buf.last() == Some(&b'\r') {
+            self.buf.pop();
+        }
+        let (line, recovered) = decode_line(&self.buf);
+        self.recovered += usize::from(recovered);
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_last_line_is_not_consumed() {
+        let data = b"{\"a\":1}\r\n\xff\xfe\n{\"b\":2}\n{\"c\":";
+        let mut lines = CompleteLines::new(&data[..], 100);
+
+        let read: Vec<String> = (&mut lines).collect();
+        assert_eq!(read, ["{\"a\":1}", "\u{fffd}\u{fffd}", "{\"b\":2}"]);
+        assert_eq!(lines.recovered, 1);
+        // Offsets count from where reading started, and stop before `{"c":`
+        assert_eq!(lines.offset, 100 + data.len() as u64 - 5);
+    }
+}
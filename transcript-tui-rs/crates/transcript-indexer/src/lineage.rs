@@ -0,0 +1,181 @@
+//! Session lineage - which earlier session a resumed session continues
+//!
+//! Resuming a conversation (`claude --resume`, `--continue`, or a compacted
+//! session carrying on) starts a new session file. Its head points back into
+//! the session it continues, by one of:
+//!
+//! - the first message's `parentUuid` (a continuation chained onto the old
+//!   session's last message)
+//! - a leading `summary` line's `leafUuid`
+//! - the first message's own `uuid` (the old history copied into the new file)
+//!
+//! The parent is the session holding one of those lines, in the same
+//! working directory, that ended last before the resumed one did (with the
+//! history copied along several resumes, every earlier session holds some of
+//! it). Links are kept in `session_lineage`.
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::connection::IndexerError;
+
+/// Link a transcript file's session to the session it resumes
+///
+/// Returns the parent found, if any. Sessions already linked are left alone.
+pub fn detect_file_lineage(conn: &Connection, file_path: &str) -> Result<Option<String>, IndexerError> {
+    let session: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT session_id, last_timestamp FROM sessions WHERE file_path = ?1",
+            [file_path],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((session_id, Some(last_timestamp))) = session else {
+        return Ok(None);
+    };
+    let linked: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM session_lineage WHERE session_id = ?1",
+        [&session_id],
+        |row| row.get(0),
+    )?;
+    if session_id.is_empty() || linked {
+        return Ok(None);
+    }
+
+    // The file's first message, and the summaries before it
+    let head: Option<(i64, String, Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT line_number, uuid, parent_uuid, cwd FROM lines
+             WHERE file_path = ?1 AND session_id = ?2 AND type != 'summary'
+             ORDER BY line_number LIMIT 1",
+            [file_path, &session_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?;
+    let Some((line_number, uuid, parent_uuid, cwd)) = head else {
+        return Ok(None);
+    };
+    let mut refs: Vec<String> = parent_uuid.into_iter().collect();
+    let mut stmt = conn.prepare(
+        "SELECT json_extract(raw, '$.leafUuid') FROM lines
+         WHERE file_path = ?1 AND type = 'summary' AND line_number < ?2
+         ORDER BY line_number",
+    )?;
+    let leaves = stmt.query_map(rusqlite::params![file_path, line_number], |row| row.get::<_, Option<String>>(0))?;
+    for leaf in leaves {
+        refs.extend(leaf?);
+    }
+    refs.push(uuid);
+
+    // Of the sessions holding one of those lines, the latest to end first
+    let placeholders = vec!["?"; refs.len()].join(", ");
+    let sql = format!(
+        "SELECT l.session_id, l.uuid FROM lines l JOIN sessions s ON s.session_id = l.session_id
+         WHERE l.uuid IN ({}) AND l.session_id NOT IN (?, '')
+           AND (? IS NULL OR l.cwd IS NULL OR l.cwd = ?)
+         GROUP BY l.session_id
+         HAVING MAX(s.last_timestamp) < ?
+         ORDER BY MAX(s.last_timestamp) DESC LIMIT 1",
+        placeholders
+    );
+    let mut params: Vec<&dyn rusqlite::ToSql> = refs.iter().map(|r| r as &dyn rusqlite::ToSql).collect();
+    params.extend([&session_id as &dyn rusqlite::ToSql, &cwd, &cwd, &last_timestamp]);
+    let parent: Option<(String, String)> = conn
+        .query_row(&sql, params.as_slice(), |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()?;
+    if let Some((parent, reference)) = &parent {
+        conn.execute(
+            "INSERT OR REPLACE INTO session_lineage (session_id, parent_session_id, parent_uuid, detected_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![session_id, parent, reference, chrono::Utc::now().to_rfc3339()],
+        )?;
+    }
+    Ok(parent.map(|(parent, _)| parent))
+}
+
+/// Link every session not yet linked (after a full build, when a resumed
+/// session's file may have been indexed before its parent's)
+///
+/// Returns the number of links found.
+pub fn detect_all_lineage(conn: &Connection) -> Result<usize, IndexerError> {
+    let mut stmt = conn.prepare(
+        "SELECT file_path FROM sessions
+         WHERE session_id NOT IN (SELECT session_id FROM session_lineage)
+         ORDER BY first_timestamp",
+    )?;
+    let files: Vec<String> = stmt.query_map([], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+    let mut found = 0;
+    for file in files {
+        if detect_file_lineage(conn, &file)?.is_some() {
+            found += 1;
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::index_transcript_file;
+    use crate::schema::init_schema;
+    use std::path::Path;
+
+    fn line(session: &str, uuid: &str, parent: Option<&str>, time: &str, cwd: &str) -> String {
+        serde_json::json!({
+            "sessionId": session, "uuid": uuid, "parentUuid": parent, "type": "user",
+            "timestamp": time, "cwd": cwd, "message": {"role": "user", "content": uuid}
+        })
+        .to_string()
+    }
+
+    fn write(dir: &Path, name: &str, lines: &[String]) -> std::path::PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_detect_lineage() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        let first = write(tmp.path(), "a.jsonl", &[
+            line("a", "a1", None, "2024-01-01T09:00:00Z", "/repo"),
+            line("a", "a2", Some("a1"), "2024-01-01T09:05:00Z", "/repo"),
+        ]);
+        // Continues a's last message
+        let second = write(tmp.path(), "b.jsonl", &[
+            r#"{"type":"summary","summary":"Fix the parser","leafUuid":"a2"}"#.to_string(),
+            line("b", "b1", Some("a2"), "2024-01-02T09:00:00Z", "/repo"),
+        ]);
+        // Copies b's history, then carries on
+        let third = write(tmp.path(), "c.jsonl", &[
+            line("c", "b1", Some("a2"), "2024-01-02T09:00:00Z", "/repo"),
+            line("c", "c1", Some("b1"), "2024-01-03T09:00:00Z", "/repo"),
+        ]);
+        // Same line, another directory
+        let other = write(tmp.path(), "d.jsonl", &[line("d", "d1", Some("a2"), "2024-01-04T09:00:00Z", "/elsewhere")]);
+
+        // Indexed out of order, so only the full pass finds the parents
+        for file in [&third, &second, &first, &other] {
+            index_transcript_file(&conn, file, 0, 1).unwrap();
+            detect_file_lineage(&conn, &file.to_string_lossy()).unwrap();
+        }
+        assert_eq!(detect_all_lineage(&conn).unwrap(), 2);
+
+        let links: Vec<(String, String, String)> = conn
+            .prepare("SELECT session_id, parent_session_id, parent_uuid FROM session_lineage ORDER BY session_id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("b".to_string(), "a".to_string(), "a2".to_string()),
+                ("c".to_string(), "b".to_string(), "b1".to_string()),
+            ]
+        );
+    }
+}
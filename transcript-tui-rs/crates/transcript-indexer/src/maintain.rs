@@ -0,0 +1,199 @@
+//! Database maintenance for `transcript index maintain`
+//!
+//! Checks the database and its search indexes, repairs search indexes that
+//! drifted from their tables, refreshes the query planner's statistics and
+//! returns free pages to the file system. Indexing only appends, but pruning,
+//! rebuilds and migrations delete, and SQLite keeps the freed pages until the
+//! database is vacuumed.
+
+use rusqlite::Connection;
+
+use crate::connection::IndexerError;
+
+/// What a maintenance run found and did
+#[derive(Debug, Default, Clone)]
+pub struct MaintainResult {
+    /// Problems reported by `PRAGMA integrity_check` (empty when sound)
+    pub integrity_errors: Vec<String>,
+    /// Search indexes that failed their check and were rebuilt
+    pub fts_rebuilt: Vec<&'static str>,
+    /// Database size before and after vacuuming
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// The database was switched to incremental vacuuming, which takes one full VACUUM
+    pub full_vacuum: bool,
+}
+
+impl MaintainResult {
+    pub fn reclaimed(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// Run every maintenance step
+///
+/// Vacuuming is skipped when the integrity check fails: copying a damaged
+/// database can lose more of it.
+pub fn maintain(conn: &Connection) -> Result<MaintainResult, IndexerError> {
+    let mut result = MaintainResult {
+        integrity_errors: integrity_check(conn)?,
+        ..Default::default()
+    };
+    result.fts_rebuilt = repair_fts(conn)?;
+    analyze(conn)?;
+    result.bytes_before = database_size(conn)?;
+    if result.integrity_errors.is_empty() {
+        result.full_vacuum = vacuum(conn)?;
+    }
+    result.bytes_after = database_size(conn)?;
+    Ok(result)
+}
+
+/// Problems found by `PRAGMA integrity_check`, none when it reports `ok`
+pub fn integrity_check(conn: &Connection) -> Result<Vec<String>, IndexerError> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut errors = Vec::new();
+    for row in rows {
+        let row = row?;
+        if row != "ok" {
+            errors.push(row);
+        }
+    }
+    Ok(errors)
+}
+
+/// Check both search indexes against their tables, rebuilding any that fail
+pub fn repair_fts(conn: &Connection) -> Result<Vec<&'static str>, IndexerError> {
+    let mut rebuilt = Vec::new();
+
+    // External content: the check also compares the index with `lines`
+    if conn
+        .execute("INSERT INTO lines_fts(lines_fts, rank) VALUES ('integrity-check', 1)", [])
+        .is_err()
+    {
+        conn.execute("INSERT INTO lines_fts(lines_fts) VALUES ('rebuild')", [])?;
+        rebuilt.push("lines_fts");
+    }
+
+    // Standalone: check the index itself, then that it has one row per event
+    let internal_ok = conn
+        .execute("INSERT INTO hook_events_fts(hook_events_fts) VALUES ('integrity-check')", [])
+        .is_ok();
+    let drifted: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM hook_events_fts WHERE rowid NOT IN (SELECT id FROM hook_events))
+             OR EXISTS (SELECT 1 FROM hook_events WHERE id NOT IN (SELECT rowid FROM hook_events_fts))",
+        [],
+        |row| row.get(0),
+    )?;
+    if !internal_ok || drifted {
+        // Same text as the hook_events_ai trigger
+        conn.execute_batch(
+            "DELETE FROM hook_events_fts;
+             INSERT INTO hook_events_fts(rowid, content)
+             SELECT id, COALESCE(event_type, '') || ' ' || COALESCE(tool_name, '') || ' ' || COALESCE(input_json, '')
+             FROM hook_events;",
+        )?;
+        rebuilt.push("hook_events_fts");
+    }
+
+    // Merge each index's segments, which makes searches faster
+    conn.execute_batch(
+        "INSERT INTO lines_fts(lines_fts) VALUES ('optimize');
+         INSERT INTO hook_events_fts(hook_events_fts) VALUES ('optimize');",
+    )?;
+
+    Ok(rebuilt)
+}
+
+/// Refresh the statistics the query planner picks indexes by
+pub fn analyze(conn: &Connection) -> Result<(), IndexerError> {
+    conn.execute_batch("ANALYZE")?;
+    Ok(())
+}
+
+/// Return free pages to the file system
+///
+/// Databases created before incremental vacuuming was turned on need one full
+/// VACUUM to switch (returns true when that happened); after that only free
+/// pages are released, which is quick. Not possible inside a transaction.
+pub fn vacuum(conn: &Connection) -> Result<bool, IndexerError> {
+    let mode: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+    // 2 = INCREMENTAL
+    let full = mode != 2;
+    if full {
+        conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+    } else {
+        conn.execute_batch("PRAGMA incremental_vacuum")?;
+    }
+    // Shrink the write-ahead log too; readers still in it just keep it for now
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    Ok(full)
+}
+
+/// Bytes in use by the database's pages
+pub fn database_size(conn: &Connection) -> Result<u64, IndexerError> {
+    let size: i64 = conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(size as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_maintain_repairs_search_and_reclaims_space() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut conn = Connection::open(tmp.path().join("index.db")).unwrap();
+        conn.execute_batch("PRAGMA journal_mode = WAL").unwrap();
+        schema::init_schema(&mut conn).unwrap();
+
+        let raw = "x".repeat(4000);
+        for i in 0..200 {
+            conn.execute(
+                "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path, content)
+                 VALUES ('s1', ?1, ?2, 'user', '2024-01-01T00:00:00Z', ?3, '/s1.jsonl', 'hello')",
+                rusqlite::params![format!("u{}", i), i, raw],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO hook_events (session_id, timestamp, event_type, file_path, line_number)
+             VALUES ('s1', '2024-01-01T00:00:00Z', 'PreToolUse', '/hooks', 1)",
+            [],
+        )
+        .unwrap();
+        conn.execute_batch(
+            "DELETE FROM lines WHERE line_number >= 10;
+             INSERT INTO lines_fts(rowid, content, session_id, slug, type) VALUES (9999, 'ghost', 's1', NULL, 'user');
+             DELETE FROM hook_events_fts;",
+        )
+        .unwrap();
+
+        let result = maintain(&conn).unwrap();
+        assert!(result.integrity_errors.is_empty());
+        assert_eq!(result.fts_rebuilt, ["lines_fts", "hook_events_fts"]);
+        assert!(result.full_vacuum);
+        assert!(result.reclaimed() > 500_000, "reclaimed {}", result.reclaimed());
+
+        let (ghosts, hooks): (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM lines_fts WHERE lines_fts MATCH 'ghost'),
+                        (SELECT COUNT(*) FROM hook_events_fts WHERE hook_events_fts MATCH 'PreToolUse')",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((ghosts, hooks), (0, 1));
+
+        // Switched over: later runs only release free pages
+        let again = maintain(&conn).unwrap();
+        assert!(again.fts_rebuilt.is_empty());
+        assert!(!again.full_vacuum);
+    }
+}
@@ -0,0 +1,217 @@
+//! Merge another transcript database into this one
+//!
+//! Used by `transcript sync pull` to combine snapshots pushed from other
+//! machines. Merging is idempotent: rows already present locally are kept
+//! (lines by `(session_id, uuid)`, sessions and hook files by `file_path`,
+//! hook events by file position), so pulling the same snapshot twice is a no-op.
+//! Merged files that aren't on this machine are recorded in `merged_files`, so
+//! pruning doesn't take their rows for deleted sessions.
+
+use rusqlite::Connection;
+use std::path::Path;
+
+use crate::connection::{attach, IndexerError};
+
+/// Rows added by a merge
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MergeResult {
+    pub lines: usize,
+    pub sessions: usize,
+    pub hook_events: usize,
+    pub hook_files: usize,
+}
+
+/// Columns present in both the local and attached table (minus excluded ones)
+pub(crate) fn shared_columns(conn: &Connection, table: &str, exclude: &[&str]) -> Result<Vec<String>, IndexerError> {
+    shared_columns_in(conn, "src", table, exclude)
+}
+
+/// Columns present in both the local table and the one in attached `schema`
+pub(crate) fn shared_columns_in(
+    conn: &Connection,
+    schema: &str,
+    table: &str,
+    exclude: &[&str],
+) -> Result<Vec<String>, IndexerError> {
+    let columns = |schema: &str| -> Result<Vec<String>, IndexerError> {
+        let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1, ?2)")?;
+        let rows = stmt.query_map([table, schema], |row| row.get::<_, String>(0))?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    };
+
+    let source = columns(schema)?;
+    Ok(columns("main")?
+        .into_iter()
+        .filter(|c| source.contains(c) && !exclude.contains(&c.as_str()))
+        .collect())
+}
+
+/// Merge all rows from the database at `source` that are not already present
+pub fn merge_database(conn: &mut Connection, source: &Path) -> Result<MergeResult, IndexerError> {
+    if !source.exists() {
+        return Err(IndexerError::FileNotFound(source.to_path_buf()));
+    }
+
+    attach(conn, source, "src")?;
+    let result = merge_attached(conn).and_then(|result| {
+        record_merged_files(conn, source)?;
+        Ok(result)
+    });
+    conn.execute_batch("DETACH DATABASE src")?;
+    result
+}
+
+/// Record the attached snapshot's files that don't exist on this machine
+fn record_merged_files(conn: &mut Connection, source: &Path) -> Result<(), IndexerError> {
+    let snapshot = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let tx = conn.transaction()?;
+    let files = tx
+        .prepare("SELECT file_path FROM src.sessions UNION SELECT file_path FROM src.hook_files")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    for file in files.iter().filter(|f| !Path::new(f).exists()) {
+        tx.execute(
+            "INSERT OR IGNORE INTO main.merged_files (file_path, snapshot, merged_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![file, snapshot, now],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub(crate) fn merge_attached(conn: &mut Connection) -> Result<MergeResult, IndexerError> {
+    let tx = conn.transaction()?;
+    let mut result = MergeResult::default();
+
+    // Lines: UNIQUE(session_id, uuid); ids are local so never copied
+    let cols = shared_columns(&tx, "lines", &["id"])?.join(", ");
+    result.lines = tx.execute(
+        &format!("INSERT OR IGNORE INTO main.lines ({cols}) SELECT {cols} FROM src.lines ORDER BY id"),
+        [],
+    )?;
+
+    // Sessions and hook files: keyed by file_path, local rows win
+    let cols = shared_columns(&tx, "sessions", &[])?.join(", ");
+    result.sessions = tx.execute(
+        &format!("INSERT OR IGNORE INTO main.sessions ({cols}) SELECT {cols} FROM src.sessions"),
+        [],
+    )?;
+
+    let cols = shared_columns(&tx, "hook_files", &[])?.join(", ");
+    result.hook_files = tx.execute(
+        &format!("INSERT OR IGNORE INTO main.hook_files ({cols}) SELECT {cols} FROM src.hook_files"),
+        [],
+    )?;
+
+    // Hook events have no natural key column; identify them by file position
+    let cols = shared_columns(&tx, "hook_events", &["id"])?;
+    let select: Vec<String> = cols.iter().map(|c| format!("s.{}", c)).collect();
+    result.hook_events = tx.execute(
+        &format!(
+            "INSERT INTO main.hook_events ({}) SELECT {} FROM src.hook_events s
+             WHERE NOT EXISTS (
+                SELECT 1 FROM main.hook_events m
+                WHERE m.session_id = s.session_id
+                  AND m.file_path = s.file_path
+                  AND m.line_number = s.line_number
+             )
+             ORDER BY s.id",
+            cols.join(", "),
+            select.join(", ")
+        ),
+        [],
+    )?;
+
+    tx.commit()?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    fn create_db(path: &Path) -> Connection {
+        let mut conn = Connection::open(path).unwrap();
+        schema::init_schema(&mut conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_merge_is_idempotent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source_path = tmp.path().join("remote.db");
+
+        let source = create_db(&source_path);
+        source
+            .execute_batch(
+                "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path, content)
+                 VALUES ('s1', 'u1', 1, 'user', '2024-01-01T00:00:00Z', '{}', '/remote/s1.jsonl', 'hello remote');
+                 INSERT INTO sessions (file_path, session_id, line_count, byte_offset, indexed_at)
+                 VALUES ('/remote/s1.jsonl', 's1', 1, 10, '2024-01-01');
+                 INSERT INTO hook_events (session_id, timestamp, event_type, file_path, line_number)
+                 VALUES ('s1', '2024-01-01T00:00:00Z', 'PreToolUse', '/remote/s1.hooks.jsonl', 1);",
+            )
+            .unwrap();
+        drop(source);
+
+        let mut local = create_db(&tmp.path().join("local.db"));
+        let first = merge_database(&mut local, &source_path).unwrap();
+        assert_eq!(first.lines, 1);
+        assert_eq!(first.sessions, 1);
+        assert_eq!(first.hook_events, 1);
+
+        let second = merge_database(&mut local, &source_path).unwrap();
+        assert_eq!(second.lines, 0);
+        assert_eq!(second.hook_events, 0);
+
+        // Merged lines are searchable through the FTS triggers
+        let hits: i64 = local
+            .query_row(
+                "SELECT COUNT(*) FROM lines_fts WHERE lines_fts MATCH 'remote'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn test_merged_files_are_not_pruned() {
+        let tmp = tempfile::tempdir().unwrap();
+        let projects = tmp.path().join("projects");
+        let local_file = projects.join("local.jsonl");
+        std::fs::create_dir_all(&projects).unwrap();
+        std::fs::write(&local_file, "{}\n").unwrap();
+        // Another machine's session whose path falls under this machine's projects
+        let remote_file = projects.join("remote.jsonl");
+
+        let source_path = tmp.path().join("laptop.db");
+        let source = create_db(&source_path);
+        for (file, session) in [(&local_file, "s1"), (&remote_file, "s2")] {
+            source
+                .execute(
+                    "INSERT INTO sessions (file_path, session_id, line_count, byte_offset, indexed_at)
+                     VALUES (?1, ?2, 1, 3, '2024-01-01')",
+                    [file.to_string_lossy().as_ref(), session],
+                )
+                .unwrap();
+        }
+        drop(source);
+
+        let mut local = create_db(&tmp.path().join("local.db"));
+        merge_database(&mut local, &source_path).unwrap();
+
+        let merged: Vec<(String, String)> = local
+            .prepare("SELECT file_path, snapshot FROM merged_files")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(merged, [(remote_file.to_string_lossy().to_string(), "laptop".to_string())]);
+        assert!(crate::prune::find_stale_files_under(&local, &[projects]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,448 @@
+//! Reconcile the index with the files on disk
+//!
+//! Indexing only ever adds rows, so a transcript or hook file that was deleted
+//! keeps its sessions, lines and search entries forever, and one that was
+//! rotated (replaced by a shorter file) stops updating because its recorded
+//! byte offset lies past its end. Pruning removes the rows of both; a rotated
+//! file is then indexed again from the start by the next update.
+//!
+//! Only files under the directories this machine indexes are considered, and
+//! files merged in from other machines or restored from the trash are left
+//! alone even there (a remote session's path can fall under the local home). Sessions matching the ignore rules are
+//! pruned too, wherever they came from (see `ignore`). Tags, bookmarks,
+//! annotations and reviews are the user's and are kept; the removed rows go to
+//! the trash first. Sessions deleted by hand (`delete_sessions`) go the same
+//! way.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OptionalExtension};
+use transcript_core::path_key;
+
+use crate::compressed;
+use crate::connection::IndexerError;
+use crate::discovery;
+use crate::ignore;
+use crate::trash;
+
+/// Which table tracks the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexedFileKind {
+    Transcript,
+    Hooks,
+}
+
+/// Why a file's rows no longer match the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaleReason {
+    /// The file is gone
+    Missing,
+    /// The file is shorter than what was indexed (rotated or rewritten)
+    Truncated,
+    /// The session matches an ignore rule
+    Ignored,
+    /// The user deleted the session (see `delete_sessions`)
+    Deleted,
+}
+
+impl StaleReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StaleReason::Missing => "missing",
+            StaleReason::Truncated => "truncated",
+            StaleReason::Ignored => "ignored",
+            StaleReason::Deleted => "deleted",
+        }
+    }
+}
+
+/// An indexed file whose rows are out of date
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleFile {
+    pub file_path: String,
+    pub kind: IndexedFileKind,
+    pub reason: StaleReason,
+}
+
+/// Rows removed by a prune
+#[derive(Debug, Default, Clone)]
+pub struct PruneResult {
+    pub files: usize,
+    pub sessions: usize,
+    pub lines: usize,
+    pub hook_files: usize,
+    pub hook_events: usize,
+    /// Trash bundle holding the removed rows
+    pub bundle: Option<String>,
+    /// Ignored sessions no rule matches any more, to be indexed again
+    pub released: usize,
+}
+
+/// Indexed files under this machine's directories that are missing or
+/// truncated, and files of sessions the ignore rules match
+pub fn find_stale_files(conn: &Connection) -> Result<Vec<StaleFile>, IndexerError> {
+    let mut dirs = vec![discovery::default_streams_dir(), discovery::default_imports_dir()];
+    for root in discovery::home_roots() {
+        dirs.push(root.projects_dir());
+        dirs.push(root.hooks_dir());
+    }
+    let mut stale = find_stale_files_under(conn, &dirs)?;
+    for file in ignore::find_ignored_files(conn, transcript_core::ignore_rules())? {
+        if !stale.iter().any(|s| s.file_path == file.file_path) {
+            stale.push(file);
+        }
+    }
+    Ok(stale)
+}
+
+/// Indexed files under `dirs` that are missing or truncated, except missing
+/// files that were merged or restored
+pub fn find_stale_files_under(conn: &Connection, dirs: &[PathBuf]) -> Result<Vec<StaleFile>, IndexerError> {
+    let kept = conn
+        .prepare("SELECT file_path FROM restored_files UNION SELECT file_path FROM merged_files")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    let stale = find_all_stale_files(conn)?;
+    Ok(stale
+        .into_iter()
+        .filter(|file| dirs.iter().any(|dir| Path::new(&file.file_path).starts_with(dir)))
+        .filter(|file| !(file.reason == StaleReason::Missing && kept.contains(&file.file_path)))
+        .collect())
+}
+
+/// Every indexed file that is missing or truncated, wherever it was indexed from
+pub(crate) fn find_all_stale_files(conn: &Connection) -> Result<Vec<StaleFile>, IndexerError> {
+    let mut stale = Vec::new();
+    for (table, kind) in [
+        ("sessions", IndexedFileKind::Transcript),
+        ("hook_files", IndexedFileKind::Hooks),
+    ] {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT file_path, byte_offset, indexed_at FROM {} ORDER BY file_path",
+            table
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<String>>(2)?))
+        })?;
+        for row in rows {
+            let (file_path, byte_offset, indexed_at) = row?;
+            let path = Path::new(&file_path);
+            let metadata = match std::fs::metadata(path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    stale.push(StaleFile { file_path, kind, reason: StaleReason::Missing });
+                    continue;
+                }
+                // Unreadable for some other reason: leave it be
+                Err(_) => continue,
+                Ok(metadata) => metadata,
+            };
+            // A compressed file's text length means decompressing all of it,
+            // so only files modified since they were indexed are measured
+            let len = if compressed::Compression::of(path).is_none() {
+                metadata.len()
+            } else if !compressed::may_have_grown(path, &metadata, byte_offset as u64, indexed_at.as_deref()) {
+                continue;
+            } else {
+                match compressed::text_len(path) {
+                    Ok(len) => len,
+                    Err(_) => continue,
+                }
+            };
+            if len < byte_offset as u64 {
+                stale.push(StaleFile { file_path, kind, reason: StaleReason::Truncated });
+            }
+        }
+    }
+    Ok(stale)
+}
+
+/// Move the rows indexed from `files` to a bundle in `trash_dir`
+///
+/// Search entries go with their rows (the FTS delete triggers). All files are
+/// removed in one savepoint, so a failure leaves the index as it was.
+pub fn prune_files(conn: &Connection, files: &[StaleFile], trash_dir: &Path) -> Result<PruneResult, IndexerError> {
+    remove_files(conn, files, trash_dir, "prune")
+}
+
+/// Move the rows of sessions, from their transcript and hook files, to a
+/// bundle in `trash_dir`
+///
+/// The files are remembered in `deleted_files`, so updates and rebuilds don't
+/// index them again while they're on disk; restoring the bundle lets them
+/// back in.
+pub fn delete_sessions(
+    conn: &Connection,
+    session_ids: &[String],
+    trash_dir: &Path,
+) -> Result<PruneResult, IndexerError> {
+    let files = session_files(conn, session_ids)?;
+    remove_files(conn, &files, trash_dir, "delete")
+}
+
+/// The transcript and hook files of sessions
+pub(crate) fn session_files(conn: &Connection, session_ids: &[String]) -> Result<Vec<StaleFile>, IndexerError> {
+    let mut files = Vec::new();
+    for (table, kind) in [
+        ("sessions", IndexedFileKind::Transcript),
+        ("hook_files", IndexedFileKind::Hooks),
+    ] {
+        let mut stmt = conn.prepare(&format!("SELECT DISTINCT file_path FROM {} WHERE session_id = ?1", table))?;
+        for session_id in session_ids {
+            for file_path in stmt.query_map([session_id], |row| row.get::<_, String>(0))? {
+                files.push(StaleFile {
+                    file_path: file_path?,
+                    kind,
+                    reason: StaleReason::Deleted,
+                });
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn remove_files(
+    conn: &Connection,
+    files: &[StaleFile],
+    trash_dir: &Path,
+    reason: &str,
+) -> Result<PruneResult, IndexerError> {
+    let paths: Vec<String> = files.iter().map(|f| f.file_path.clone()).collect();
+    let bundle = trash::trash_files(conn, trash_dir, reason, &paths)?.map(|b| b.id);
+
+    conn.execute_batch("SAVEPOINT prune_files")?;
+    match delete_files(conn, files, bundle.as_deref()) {
+        Ok(mut result) => {
+            result.bundle = bundle;
+            conn.execute_batch("RELEASE prune_files")?;
+            Ok(result)
+        }
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO prune_files; RELEASE prune_files")?;
+            Err(e)
+        }
+    }
+}
+
+/// Find and remove stale files in one go (the reconcile step of `index update`)
+///
+/// Sessions no longer matched by an ignore rule are released first.
+pub fn prune_stale_files(conn: &Connection) -> Result<(Vec<StaleFile>, PruneResult), IndexerError> {
+    let released = ignore::release_unignored(conn, transcript_core::ignore_rules())?;
+    let stale = find_stale_files(conn)?;
+    let mut result = prune_files(conn, &stale, &discovery::default_trash_dir())?;
+    result.released = released;
+    Ok((stale, result))
+}
+
+fn delete_files(conn: &Connection, files: &[StaleFile], bundle: Option<&str>) -> Result<PruneResult, IndexerError> {
+    let mut result = PruneResult::default();
+    for file in files {
+        if file.reason == StaleReason::Deleted {
+            conn.execute(
+                "INSERT OR REPLACE INTO deleted_files (file_path, bundle, deleted_at) VALUES (?1, ?2, ?3)",
+                rusqlite::params![file.file_path, bundle.unwrap_or_default(), chrono::Utc::now().to_rfc3339()],
+            )?;
+        }
+        match file.kind {
+            IndexedFileKind::Transcript => {
+                if file.reason == StaleReason::Ignored {
+                    // Remembered, so updates don't index it straight back
+                    let session: Option<(String, Option<String>, Option<String>)> = conn
+                        .query_row(
+                            "SELECT session_id, slug,
+                                    (SELECT cwd FROM lines WHERE file_path = ?1 AND cwd IS NOT NULL LIMIT 1)
+                             FROM sessions WHERE file_path = ?1",
+                            [&file.file_path],
+                            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                        )
+                        .optional()?;
+                    if let Some((session_id, slug, cwd)) = session {
+                        ignore::record_ignored(conn, &session_id, cwd.as_deref(), slug.as_deref())?;
+                    }
+                }
+                conn.execute(
+                    "DELETE FROM tool_payloads WHERE EXISTS (
+                         SELECT 1 FROM lines l
+                         WHERE l.file_path = ?1 AND l.session_id = tool_payloads.session_id
+                           AND l.uuid = tool_payloads.uuid
+                     )",
+                    [&file.file_path],
+                )?;
+                result.lines += conn.execute("DELETE FROM lines WHERE file_path = ?1", [&file.file_path])?;
+                result.sessions += conn.execute("DELETE FROM sessions WHERE file_path = ?1", [&file.file_path])?;
+            }
+            IndexedFileKind::Hooks => {
+                result.hook_events +=
+                    conn.execute("DELETE FROM hook_events WHERE file_path = ?1", [&file.file_path])?;
+                result.hook_files +=
+                    conn.execute("DELETE FROM hook_files WHERE file_path = ?1", [&file.file_path])?;
+            }
+        }
+        result.files += 1;
+    }
+    Ok(result)
+}
+
+/// Whether a file's sessions were deleted, so it mustn't be indexed
+pub(crate) fn is_deleted(conn: &Connection, file_path: &Path) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM deleted_files WHERE file_path = ?1",
+        [path_key(file_path)],
+        |_| Ok(()),
+    )
+    .optional()
+    .ok()
+    .flatten()
+    .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+    use std::fs;
+
+    #[test]
+    fn test_prune_missing_and_truncated_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let projects = tmp.path().join("projects");
+        fs::create_dir_all(&projects).unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init_schema(&mut conn).unwrap();
+
+        let path = |name: &str| projects.join(name).to_string_lossy().to_string();
+        let (missing, rotated, intact) = (path("gone.jsonl"), path("rotated.jsonl"), path("intact.jsonl"));
+        fs::write(&rotated, "{}\n").unwrap();
+        fs::write(&intact, "{}\n{}\n").unwrap();
+        // Merged from another machine: not ours to prune
+        let elsewhere = "/other-machine/.claude/projects/s.jsonl".to_string();
+
+        for (i, file) in [&missing, &rotated, &intact, &elsewhere].into_iter().enumerate() {
+            let session = format!("s{}", i);
+            conn.execute(
+                "INSERT INTO sessions (file_path, session_id, line_count, byte_offset, indexed_at)
+                 VALUES (?1, ?2, 2, 6, '2024-01-01')",
+                [file, &session],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path, content)
+                 VALUES (?1, 'u1', 1, 'user', '2024-01-01T00:00:00Z', '{}', ?2, 'hello')",
+                [&session, file],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO hook_files (file_path, session_id, event_count, byte_offset, indexed_at)
+             VALUES (?1, 's0', 1, 10, '2024-01-01')",
+            [path("gone.hooks.jsonl")],
+        )
+        .unwrap();
+
+        let stale = find_stale_files_under(&conn, std::slice::from_ref(&projects)).unwrap();
+        let found: Vec<(&str, IndexedFileKind, StaleReason)> = stale
+            .iter()
+            .map(|f| (f.file_path.rsplit(['/', '\\']).next().unwrap(), f.kind, f.reason))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("gone.jsonl", IndexedFileKind::Transcript, StaleReason::Missing),
+                ("rotated.jsonl", IndexedFileKind::Transcript, StaleReason::Truncated),
+                ("gone.hooks.jsonl", IndexedFileKind::Hooks, StaleReason::Missing),
+            ]
+        );
+
+        let trash_dir = tmp.path().join("trash");
+        let result = prune_files(&conn, &stale, &trash_dir).unwrap();
+        assert_eq!((result.files, result.sessions, result.lines, result.hook_files), (3, 2, 2, 1));
+        // The removed rows wait in the trash
+        let bundle = trash::find_bundle(&trash_dir, result.bundle.as_deref().unwrap()).unwrap();
+        assert_eq!((bundle.files.len(), bundle.lines), (3, 2));
+
+        // Rows restored from the trash stay although their file is still gone
+        conn.execute_batch(&format!(
+            "INSERT INTO sessions (file_path, session_id, line_count, byte_offset, indexed_at)
+             VALUES ('{missing}', 's0', 2, 6, '2024-01-01');
+             INSERT INTO restored_files (file_path, bundle, restored_at) VALUES ('{missing}', 'b', '2024-01-02');"
+        ))
+        .unwrap();
+        assert!(find_stale_files_under(&conn, std::slice::from_ref(&projects)).unwrap().is_empty());
+        let (lines, hits): (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM lines), (SELECT COUNT(*) FROM lines_fts WHERE lines_fts MATCH 'hello')",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        // Search entries went with their lines
+        assert_eq!((lines, hits), (2, 2));
+    }
+
+    #[test]
+    fn test_unchanged_compressed_files_are_not_read() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init_schema(&mut conn).unwrap();
+
+        // Both hold less text than was indexed, but only the one modified
+        // since it was indexed can have been cut short
+        let gzip = |name: &str| {
+            let path = tmp.path().join(name);
+            let mut encoder = flate2::write::GzEncoder::new(fs::File::create(&path).unwrap(), flate2::Compression::default());
+            std::io::Write::write_all(&mut encoder, b"{}\n").unwrap();
+            encoder.finish().unwrap();
+            path
+        };
+        let (unchanged, rotated) = (gzip("unchanged.jsonl.gz"), gzip("rotated.jsonl.gz"));
+
+        for (file, session, indexed_at) in [(&unchanged, "s1", "2999-01-01T00:00:00Z"), (&rotated, "s2", "2024-01-01T00:00:00Z")] {
+            conn.execute(
+                "INSERT INTO sessions (file_path, session_id, line_count, byte_offset, indexed_at) VALUES (?1, ?2, 2, 100, ?3)",
+                [file.to_string_lossy().as_ref(), session, indexed_at],
+            )
+            .unwrap();
+        }
+
+        let stale = find_all_stale_files(&conn).unwrap();
+        let found: Vec<(&str, StaleReason)> = stale
+            .iter()
+            .map(|f| (f.file_path.rsplit(['/', '\\']).next().unwrap(), f.reason))
+            .collect();
+        assert_eq!(found, [("rotated.jsonl.gz", StaleReason::Truncated)]);
+    }
+
+    #[test]
+    fn test_deleted_sessions_stay_out_until_restored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init_schema(&mut conn).unwrap();
+        let transcript = tmp.path().join("s1.jsonl");
+        fs::write(&transcript, "{}\n").unwrap();
+        conn.execute_batch(&format!(
+            "INSERT INTO sessions (file_path, session_id, line_count, byte_offset, indexed_at)
+             VALUES ('{t}', 's1', 1, 3, '2024-01-01'), ('/p/s2.jsonl', 's2', 1, 3, '2024-01-01');
+             INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path)
+             VALUES ('s1', 'u1', 1, 'user', '2024-01-01T00:00:00Z', '{{}}', '{t}');
+             INSERT INTO hook_files (file_path, session_id, event_count, byte_offset, indexed_at)
+             VALUES ('/h/s1.hooks.jsonl', 's1', 0, 0, '2024-01-01');",
+            t = transcript.display()
+        ))
+        .unwrap();
+
+        let trash_dir = tmp.path().join("trash");
+        let result = delete_sessions(&conn, &["s1".to_string()], &trash_dir).unwrap();
+        assert_eq!((result.files, result.sessions, result.lines, result.hook_files), (2, 1, 1, 1));
+        let sessions: i64 = conn.query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0)).unwrap();
+        assert_eq!(sessions, 1);
+        // Still on disk, but not to be indexed again
+        assert!(is_deleted(&conn, &transcript));
+        assert!(!is_deleted(&conn, Path::new("/p/s2.jsonl")));
+
+        let bundle = trash::find_bundle(&trash_dir, result.bundle.as_deref().unwrap()).unwrap();
+        assert_eq!(bundle.reason, "delete");
+        trash::restore_bundle(&mut conn, &bundle).unwrap();
+        assert!(!is_deleted(&conn, &transcript));
+    }
+}
@@ -4,14 +4,23 @@
 
 use rusqlite::Connection;
 
+use std::path::Path;
+
 use crate::connection::IndexerError;
+use crate::prune;
 use crate::schema;
+use crate::trash::{self, TrashBundle};
 
 /// Clear and rebuild the entire index
 ///
-/// This drops all tables (except metadata) and recreates them,
-/// ensuring the schema is current and all data is cleared.
+/// This drops all tables (except metadata and the user's tags, bookmarks, annotations and reviews) and recreates
+/// them, ensuring the schema is current and all indexed data is cleared.
 pub fn rebuild_index(conn: &mut Connection) -> Result<(), IndexerError> {
+    // Drop reporting views (recreated by init_schema)
+    for (name, _) in schema::reporting_views() {
+        conn.execute_batch(&format!("DROP VIEW IF EXISTS {};", name))?;
+    }
+
     // Drop triggers first (they reference tables)
     conn.execute_batch(
         "DROP TRIGGER IF EXISTS lines_ai;
@@ -34,7 +43,11 @@ pub fn rebuild_index(conn: &mut Connection) -> Result<(), IndexerError> {
          DROP TABLE IF EXISTS sessions;
          DROP TABLE IF EXISTS hook_events;
          DROP TABLE IF EXISTS hook_files;
-         DROP TABLE IF EXISTS adapter_cursors;",
+         DROP TABLE IF EXISTS adapter_cursors;
+         DROP TABLE IF EXISTS tool_payloads;
+         DROP TABLE IF EXISTS ignored_sessions;
+         DROP TABLE IF EXISTS session_lineage;
+         DROP TABLE IF EXISTS index_errors;",
     )?;
 
     // Clear last_indexed from metadata (keep the table itself)
@@ -49,6 +62,20 @@ pub fn rebuild_index(conn: &mut Connection) -> Result<(), IndexerError> {
     Ok(())
 }
 
+/// Rebuild, first moving the rows a rebuild can't recreate to the trash
+///
+/// Those are the rows of files deleted or rotated since they were indexed,
+/// including sessions merged from other machines. Returns their bundle, if any.
+pub fn rebuild_index_with_trash(conn: &mut Connection, trash_dir: &Path) -> Result<Option<TrashBundle>, IndexerError> {
+    let files: Vec<String> = prune::find_all_stale_files(conn)?
+        .into_iter()
+        .map(|f| f.file_path)
+        .collect();
+    let bundle = trash::trash_files(conn, trash_dir, "rebuild", &files)?;
+    rebuild_index(conn)?;
+    Ok(bundle)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
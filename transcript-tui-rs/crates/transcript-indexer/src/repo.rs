@@ -0,0 +1,62 @@
+//! Repository roots of working directories
+//!
+//! Sessions started in different subdirectories of one checkout belong to
+//! one project. Each line's `cwd` is stored with its `repo_root`: the nearest
+//! directory at or above it holding `.git` (a directory, or the file git
+//! writes in worktrees and submodules). Directories that aren't on this
+//! machine, or aren't in a repository, have none, and readers fall back to
+//! the `cwd`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The repository root at or above `dir`, if any
+pub fn find_repo_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find(|d| d.join(".git").exists()).map(Path::to_path_buf)
+}
+
+/// Repository roots of working directories, each looked up once
+#[derive(Debug, Default)]
+pub struct RepoRoots {
+    roots: HashMap<String, Option<String>>,
+}
+
+impl RepoRoots {
+    /// Repository root of a working directory, as a string
+    pub fn get(&mut self, cwd: &str) -> Option<String> {
+        if cwd.is_empty() {
+            return None;
+        }
+        self.roots
+            .entry(cwd.to_string())
+            .or_insert_with(|| find_repo_root(Path::new(cwd)).map(|root| root.to_string_lossy().into_owned()))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_repo_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("api");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(repo.join("crates/core/src")).unwrap();
+        // A worktree's .git is a file
+        let worktree = tmp.path().join("api-fix");
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: ../api/.git/worktrees/api-fix\n").unwrap();
+
+        assert_eq!(find_repo_root(&repo.join("crates/core/src")), Some(repo.clone()));
+        assert_eq!(find_repo_root(&repo), Some(repo.clone()));
+        assert_eq!(find_repo_root(&worktree), Some(worktree.clone()));
+
+        let mut roots = RepoRoots::default();
+        let sub = repo.join("crates").to_string_lossy().into_owned();
+        assert_eq!(roots.get(&sub), Some(repo.to_string_lossy().into_owned()));
+        assert_eq!(roots.get(""), None);
+        assert_eq!(roots.get("/nonexistent/dir"), None);
+    }
+}
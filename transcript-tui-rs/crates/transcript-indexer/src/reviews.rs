@@ -0,0 +1,58 @@
+//! Session review status
+//!
+//! A team lead marks which agent sessions have been audited. Sessions with no
+//! row are unreviewed, so setting a session back to unreviewed deletes it.
+//! Rebuilds leave reviews alone, as they do tags.
+
+use rusqlite::Connection;
+use transcript_core::ReviewStatus;
+
+use crate::connection::IndexerError;
+
+/// Set a session's review status
+pub fn set_review_status(
+    conn: &Connection,
+    session_id: &str,
+    status: ReviewStatus,
+    reviewer: Option<&str>,
+) -> Result<(), IndexerError> {
+    if status == ReviewStatus::Unreviewed {
+        conn.execute("DELETE FROM reviews WHERE session_id = ?1", [session_id])?;
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO reviews (session_id, status, reviewer, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(session_id) DO UPDATE SET
+             status = excluded.status, reviewer = excluded.reviewer, updated_at = excluded.updated_at",
+        rusqlite::params![session_id, status.name(), reviewer, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::init_schema;
+
+    #[test]
+    fn test_review_status_updates() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        set_review_status(&conn, "s1", ReviewStatus::InReview, Some("ann")).unwrap();
+        set_review_status(&conn, "s1", ReviewStatus::Flagged, Some("bob")).unwrap();
+        set_review_status(&conn, "s2", ReviewStatus::Approved, None).unwrap();
+        set_review_status(&conn, "s2", ReviewStatus::Unreviewed, None).unwrap();
+
+        crate::rebuild::rebuild_index(&mut conn).unwrap();
+
+        let rows: Vec<(String, String, Option<String>)> = conn
+            .prepare("SELECT session_id, status, reviewer FROM reviews")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert_eq!(rows, vec![("s1".to_string(), "flagged".to_string(), Some("bob".to_string()))]);
+    }
+}
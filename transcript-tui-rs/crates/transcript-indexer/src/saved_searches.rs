@@ -0,0 +1,63 @@
+//! Saved searches
+//!
+//! `transcript search --save <name>` stores a query with its filters under a
+//! name; `--saved <name>` and the TUI search dialog run it again. Filters are
+//! kept as the JSON the CLI wrote, so new ones need no migration.
+
+use rusqlite::Connection;
+
+use crate::connection::IndexerError;
+
+/// Save `query` and its `filters` (JSON) as `name`, replacing a search saved
+/// under that name before; returns false if it replaced one
+pub fn save_search(conn: &Connection, name: &str, query: &str, filters: &str) -> Result<bool, IndexerError> {
+    let existed: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM saved_searches WHERE name = ?1)",
+        [name],
+        |row| row.get(0),
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO saved_searches (name, query, filters, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![name, query, filters, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(!existed)
+}
+
+/// Forget a saved search; returns false if there was none by that name
+pub fn remove_saved_search(conn: &Connection, name: &str) -> Result<bool, IndexerError> {
+    let removed = conn.execute("DELETE FROM saved_searches WHERE name = ?1", [name])?;
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::init_schema;
+
+    #[test]
+    fn test_saved_searches_replace_and_survive_rebuild() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        assert!(save_search(&conn, "errors", "panicked", "{}").unwrap());
+        assert!(!save_search(&conn, "errors", "panicked at", r#"{"since":"1d"}"#).unwrap());
+        assert!(save_search(&conn, "todo", "TODO", "{}").unwrap());
+        assert!(remove_saved_search(&conn, "todo").unwrap());
+        assert!(!remove_saved_search(&conn, "todo").unwrap());
+
+        crate::rebuild::rebuild_index(&mut conn).unwrap();
+
+        let kept: Vec<(String, String, String)> = conn
+            .prepare("SELECT name, query, filters FROM saved_searches")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert_eq!(
+            kept,
+            vec![("errors".to_string(), "panicked at".to_string(), r#"{"since":"1d"}"#.to_string())]
+        );
+    }
+}
@@ -1,16 +1,49 @@
 //! Database schema creation and migration
 //!
-//! Creates all tables matching the TypeScript schema exactly (v10).
+//! Creates all tables matching the TypeScript schema (v10), plus the
+//! Rust-only additions from v11 (`user` columns for multi-home indexing),
+//! v12 (`tags` table for session labels), v13 (`bookmarks` table), v14
+//! (`annotations` table for line notes, searched through `annotations_fts`),
+//! v15 (`annotations.parent_id` for threaded replies), v16 (`reviews` table),
+//! v17 (`tool_payloads` table of tool input/output sizes), v18 (`changes`
+//! table that readers watch for new lines), v19 (`restored_files` table of
+//! files brought back from the trash), v20 (`ignored_sessions` table), v21
+//! (`archived_files` table of files moved to the archive), v22
+//! (`session_lineage` table linking resumed sessions to their parents),
+//! v23 (`repo_root` column on lines, grouping a repository's
+//! subdirectories into one project), v24 (`slow_queries` table, the
+//! slow-query log `doctor` reports on), v25 (`saved_searches` table of
+//! named queries), v26 (`deleted_files` table of sessions deleted from the
+//! index), v27 (`index_errors` table, the log of files and lines that
+//! failed to index) and v28 (`merged_files` table of files merged from other
+//! machines).
 
 use rusqlite::Connection;
+use transcript_core::{price_case_sql, CACHE_READ_MULTIPLIER, CACHE_WRITE_MULTIPLIER, RESPONSE_KEY_SQL};
 
 use crate::connection::IndexerError;
 
 /// Current database schema version
-pub const DB_VERSION: i32 = 10;
+pub const DB_VERSION: i32 = 28;
+
+/// Oldest reader schema (`transcript_db::MAX_DB_VERSION`) that can still read
+/// this schema, recorded as `reader_compat_version`
+///
+/// Readers older than `DB_VERSION` but not older than this open the database
+/// in compatibility mode. Raise it with a migration that renames, drops or
+/// changes the meaning of anything readers query.
+pub const READER_COMPAT_VERSION: i32 = 8;
+
+/// Version of this indexer, recorded as `indexer_version` on every write
+pub const INDEXER_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Initialize the database schema (create tables + run migrations)
+///
+/// Refuses databases written by a newer indexer: migrating them "back" would
+/// record an older version over a schema it doesn't know.
 pub fn init_schema(conn: &mut Connection) -> Result<(), IndexerError> {
+    check_not_newer(conn)?;
+
     // Metadata table
     conn.execute_batch(
         "CREATE TABLE IF NOT EXISTS metadata (
@@ -43,6 +76,8 @@ pub fn init_schema(conn: &mut Connection) -> Result<(), IndexerError> {
             git_hash TEXT,
             git_branch TEXT,
             git_dirty INTEGER,
+            user TEXT,
+            repo_root TEXT,
             UNIQUE(session_id, uuid)
         )",
     )?;
@@ -56,7 +91,8 @@ pub fn init_schema(conn: &mut Connection) -> Result<(), IndexerError> {
          CREATE INDEX IF NOT EXISTS idx_line_number ON lines(line_number);
          CREATE INDEX IF NOT EXISTS idx_lines_turn_id ON lines(turn_id);
          CREATE INDEX IF NOT EXISTS idx_lines_session_name ON lines(session_name);
-         CREATE INDEX IF NOT EXISTS idx_lines_git_hash ON lines(git_hash);",
+         CREATE INDEX IF NOT EXISTS idx_lines_git_hash ON lines(git_hash);
+         CREATE INDEX IF NOT EXISTS idx_lines_uuid ON lines(uuid);",
     )?;
 
     // FTS5 virtual table for full-text search on lines
@@ -101,7 +137,8 @@ pub fn init_schema(conn: &mut Connection) -> Result<(), IndexerError> {
             byte_offset INTEGER NOT NULL DEFAULT 0,
             first_timestamp TEXT,
             last_timestamp TEXT,
-            indexed_at TEXT NOT NULL
+            indexed_at TEXT NOT NULL,
+            user TEXT
         );
         CREATE INDEX IF NOT EXISTS idx_sessions_session_id ON sessions(session_id);",
     )?;
@@ -126,7 +163,8 @@ pub fn init_schema(conn: &mut Connection) -> Result<(), IndexerError> {
             session_name TEXT,
             git_hash TEXT,
             git_branch TEXT,
-            git_dirty INTEGER
+            git_dirty INTEGER,
+            user TEXT
         )",
     )?;
 
@@ -156,13 +194,11 @@ pub fn init_schema(conn: &mut Connection) -> Result<(), IndexerError> {
         END;
 
         CREATE TRIGGER IF NOT EXISTS hook_events_ad AFTER DELETE ON hook_events BEGIN
-            INSERT INTO hook_events_fts(hook_events_fts, rowid, content)
-            VALUES ('delete', old.id, COALESCE(old.event_type, '') || ' ' || COALESCE(old.tool_name, '') || ' ' || COALESCE(old.input_json, ''));
+            DELETE FROM hook_events_fts WHERE rowid = old.id;
         END;
 
         CREATE TRIGGER IF NOT EXISTS hook_events_au AFTER UPDATE ON hook_events BEGIN
-            INSERT INTO hook_events_fts(hook_events_fts, rowid, content)
-            VALUES ('delete', old.id, COALESCE(old.event_type, '') || ' ' || COALESCE(old.tool_name, '') || ' ' || COALESCE(old.input_json, ''));
+            DELETE FROM hook_events_fts WHERE rowid = old.id;
             INSERT INTO hook_events_fts(rowid, content)
             VALUES (new.id, COALESCE(new.event_type, '') || ' ' || COALESCE(new.tool_name, '') || ' ' || COALESCE(new.input_json, ''));
         END;",
@@ -193,18 +229,400 @@ pub fn init_schema(conn: &mut Connection) -> Result<(), IndexerError> {
         )",
     )?;
 
+    // Session tags (user data: kept across rebuilds, keyed by session ID)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            session_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (session_id, tag)
+        );
+        CREATE INDEX IF NOT EXISTS idx_tags_tag ON tags(tag);",
+    )?;
+
+    // Line bookmarks from the viewers (user data, keyed by line UUID so they
+    // survive rebuilds that renumber line IDs)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS bookmarks (
+            session_id TEXT NOT NULL,
+            uuid TEXT NOT NULL,
+            line_number INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (session_id, uuid)
+        )",
+    )?;
+
+    // Line annotations (user data like bookmarks; replies point at the note
+    // they answer through parent_id), with a standalone FTS table kept in
+    // sync by triggers so notes are found by search
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS annotations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            uuid TEXT NOT NULL,
+            line_number INTEGER NOT NULL,
+            parent_id INTEGER,
+            author TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_annotations_line ON annotations(session_id, uuid);
+
+        CREATE VIRTUAL TABLE IF NOT EXISTS annotations_fts USING fts5(
+            body,
+            author
+        );
+
+        CREATE TRIGGER IF NOT EXISTS annotations_ai AFTER INSERT ON annotations BEGIN
+            INSERT INTO annotations_fts(rowid, body, author) VALUES (new.id, new.body, new.author);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS annotations_ad AFTER DELETE ON annotations BEGIN
+            DELETE FROM annotations_fts WHERE rowid = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS annotations_au AFTER UPDATE ON annotations BEGIN
+            DELETE FROM annotations_fts WHERE rowid = old.id;
+            INSERT INTO annotations_fts(rowid, body, author) VALUES (new.id, new.body, new.author);
+        END;",
+    )?;
+
+    // Review status per session (user data; sessions without a row are unreviewed)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS reviews (
+            session_id TEXT PRIMARY KEY,
+            status TEXT NOT NULL,
+            reviewer TEXT,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_reviews_status ON reviews(status);",
+    )?;
+
+    // Byte size of each tool input and output as written to the transcript,
+    // before trimming (indexed data, rebuilt with the lines)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tool_payloads (
+            session_id TEXT NOT NULL,
+            uuid TEXT NOT NULL,
+            line_number INTEGER NOT NULL,
+            tool_use_id TEXT NOT NULL,
+            tool_name TEXT,
+            direction TEXT NOT NULL,
+            bytes INTEGER NOT NULL,
+            UNIQUE(session_id, uuid, tool_use_id, direction)
+        );
+        CREATE INDEX IF NOT EXISTS idx_tool_payloads_bytes ON tool_payloads(bytes);",
+    )?;
+
+    // Change feed: one row per indexed batch of a session's lines, so viewers
+    // check one small table instead of polling lines (kept across rebuilds so
+    // ids only grow)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            max_line_id INTEGER NOT NULL,
+            changed_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_changes_session ON changes(session_id, id);",
+    )?;
+
+    // Files whose rows were restored from the trash after the file itself was
+    // gone; pruning leaves them alone (kept across rebuilds)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS restored_files (
+            file_path TEXT PRIMARY KEY,
+            bundle TEXT NOT NULL,
+            restored_at TEXT NOT NULL
+        )",
+    )?;
+
+    // Sessions skipped because an ignore rule matched them (indexed data:
+    // rebuilt along with the lines)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ignored_sessions (
+            session_id TEXT PRIMARY KEY,
+            cwd TEXT,
+            slug TEXT,
+            ignored_at TEXT NOT NULL
+        )",
+    )?;
+
+    // Files whose rows were moved to the archive database; indexing skips
+    // them (kept across rebuilds)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS archived_files (
+            file_path TEXT PRIMARY KEY,
+            session_id TEXT NOT NULL,
+            archive TEXT NOT NULL,
+            archived_at TEXT NOT NULL
+        )",
+    )?;
+
+    // The session each resumed session continues (indexed data: rebuilt
+    // along with the lines)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_lineage (
+            session_id TEXT PRIMARY KEY,
+            parent_session_id TEXT NOT NULL,
+            parent_uuid TEXT NOT NULL,
+            detected_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_lineage_parent ON session_lineage(parent_session_id);",
+    )?;
+
+    // Statements readers found slow (diagnostics: kept across rebuilds, see
+    // `diagnostics`)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS slow_queries (
+            id INTEGER PRIMARY KEY,
+            recorded_at TEXT NOT NULL,
+            command TEXT NOT NULL,
+            sql TEXT NOT NULL,
+            millis INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_slow_queries_recorded ON slow_queries(recorded_at);",
+    )?;
+
+    // Named queries and their filters (user data: kept across rebuilds, see
+    // `saved_searches`)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS saved_searches (
+            name TEXT PRIMARY KEY,
+            query TEXT NOT NULL,
+            filters TEXT NOT NULL DEFAULT '{}',
+            created_at TEXT NOT NULL
+        )",
+    )?;
+
+    // Files of sessions deleted from the index; indexing skips them until
+    // their trash bundle is restored (kept across rebuilds)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS deleted_files (
+            file_path TEXT PRIMARY KEY,
+            bundle TEXT NOT NULL,
+            deleted_at TEXT NOT NULL
+        )",
+    )?;
+
+    // Files merged from another machine's snapshot that aren't on this one;
+    // pruning leaves them alone (kept across rebuilds)
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS merged_files (
+            file_path TEXT PRIMARY KEY,
+            snapshot TEXT NOT NULL,
+            merged_at TEXT NOT NULL
+        )",
+    )?;
+
+    // Files and lines that failed to index, one entry each (line 0 for the
+    // whole file); rebuilds drop it, as reindexing records them again
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS index_errors (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_path TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            line_number INTEGER NOT NULL DEFAULT 0,
+            error TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 1,
+            occurred_at TEXT NOT NULL,
+            UNIQUE (file_path, line_number)
+        );
+        CREATE INDEX IF NOT EXISTS idx_index_errors_occurred ON index_errors(occurred_at);",
+    )?;
+
     // Run migrations if needed
     migrate_schema(conn)?;
 
+    // Indexes on migrated columns (only valid once migrations have run)
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_lines_user ON lines(user);
+         CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions(user);
+         CREATE INDEX IF NOT EXISTS idx_hook_user ON hook_events(user);
+         CREATE INDEX IF NOT EXISTS idx_lines_repo_root ON lines(repo_root);",
+    )?;
+
+    // Reporting views (recreated every time so they track the tables)
+    init_views(conn)?;
+
     // Set version
     conn.execute(
         "INSERT OR REPLACE INTO metadata (key, value) VALUES ('version', ?1)",
         [&DB_VERSION.to_string()],
     )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO metadata (key, value) VALUES ('reader_compat_version', ?1)",
+        [&READER_COMPAT_VERSION.to_string()],
+    )?;
+    record_writer(conn)?;
 
     Ok(())
 }
 
+/// Fail when the database's schema is newer than this indexer's
+pub fn check_not_newer(conn: &Connection) -> Result<(), IndexerError> {
+    // No metadata table or version yet: a new database
+    let found: Option<i32> = conn
+        .query_row(
+            "SELECT CAST(value AS INTEGER) FROM metadata WHERE key = 'version'",
+            [],
+            |row| row.get(0),
+        )
+        .ok();
+    match found {
+        Some(found) if found > DB_VERSION => Err(IndexerError::SchemaTooNew {
+            found,
+            supported: DB_VERSION,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Record this indexer as the last writer, so readers can warn about skew
+pub fn record_writer(conn: &Connection) -> Result<(), IndexerError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO metadata (key, value) VALUES ('indexer_version', ?1)",
+        [INDEXER_VERSION],
+    )?;
+    Ok(())
+}
+
+/// Stable reporting views for external tools (Metabase, Grafana, sqlite3)
+///
+/// These are the supported interface for reading the database from outside
+/// this workspace. Column names and meanings stay fixed even when the
+/// underlying tables change; only additive changes are made here. Usage is
+/// counted once per API response, and `v_costs` prices models like
+/// `transcript_core::MODEL_PRICES`.
+pub fn reporting_views() -> Vec<(&'static str, String)> {
+    let price_in = price_case_sql(|p| p.input);
+    let price_out = price_case_sql(|p| p.output);
+    vec![
+    (
+        "v_sessions_enriched",
+        "SELECT
+            s.session_id,
+            s.slug,
+            (SELECT l.session_name FROM lines l
+              WHERE l.session_id = s.session_id AND l.session_name IS NOT NULL
+              ORDER BY l.id DESC LIMIT 1) AS session_name,
+            (SELECT l.cwd FROM lines l
+              WHERE l.session_id = s.session_id AND l.cwd IS NOT NULL
+              ORDER BY l.id LIMIT 1) AS cwd,
+            s.file_path,
+            s.first_timestamp,
+            s.last_timestamp,
+            s.line_count,
+            (SELECT COUNT(*) FROM lines l
+              WHERE l.session_id = s.session_id AND l.type = 'user') AS user_lines,
+            (SELECT COUNT(*) FROM lines l
+              WHERE l.session_id = s.session_id AND l.type = 'assistant') AS assistant_lines,
+            (SELECT COUNT(DISTINCT l.turn_id) FROM lines l
+              WHERE l.session_id = s.session_id) AS turn_count,
+            (SELECT GROUP_CONCAT(DISTINCT l.model) FROM lines l
+              WHERE l.session_id = s.session_id) AS models,
+            (SELECT COUNT(*) FROM hook_events h
+              WHERE h.session_id = s.session_id) AS hook_event_count,
+            s.user
+         FROM sessions s"
+            .to_string(),
+    ),
+    (
+        "v_turns",
+        format!(
+            "SELECT
+            session_id,
+            turn_id,
+            MIN(turn_sequence) AS turn_sequence,
+            MAX(session_name) AS session_name,
+            MIN(timestamp) AS started_at,
+            MAX(timestamp) AS ended_at,
+            COUNT(*) AS line_count,
+            SUM(type = 'user') AS user_lines,
+            SUM(type = 'assistant') AS assistant_lines,
+            COALESCE(SUM(CASE WHEN copy = 1 THEN output_tokens END), 0) AS output_tokens
+         FROM (
+            SELECT *,
+                json_extract(raw, '$.message.usage.output_tokens') AS output_tokens,
+                ROW_NUMBER() OVER (
+                    PARTITION BY session_id, {RESPONSE_KEY_SQL} ORDER BY line_number DESC
+                ) AS copy
+            FROM lines
+            WHERE turn_id IS NOT NULL
+         )
+         GROUP BY session_id, turn_id"
+        ),
+    ),
+    (
+        "v_tool_calls",
+        "SELECT
+            l.id AS line_id,
+            l.session_id,
+            l.session_name,
+            l.turn_id,
+            l.timestamp,
+            json_extract(b.value, '$.id') AS tool_use_id,
+            json_extract(b.value, '$.name') AS tool_name,
+            json_extract(b.value, '$.input.file_path') AS file_path,
+            json_extract(b.value, '$.input') AS input_json
+         FROM lines l, json_each(l.raw, '$.message.content') b
+         WHERE l.type = 'assistant'
+           AND CASE WHEN b.type = 'object' THEN json_extract(b.value, '$.type') END = 'tool_use'"
+            .to_string(),
+    ),
+    (
+        "v_costs",
+        format!(
+            "SELECT
+            session_id,
+            model,
+            messages,
+            input_tokens,
+            output_tokens,
+            cache_creation_tokens,
+            cache_read_tokens,
+            ROUND((input_tokens * price_in
+                 + output_tokens * price_out
+                 + cache_creation_tokens * price_in * {CACHE_WRITE_MULTIPLIER:?}
+                 + cache_read_tokens * price_in * {CACHE_READ_MULTIPLIER:?}) / 1000000.0, 4) AS estimated_cost_usd
+         FROM (
+            SELECT
+                session_id,
+                model,
+                COUNT(*) AS messages,
+                COALESCE(SUM(json_extract(raw, '$.message.usage.input_tokens')), 0) AS input_tokens,
+                COALESCE(SUM(json_extract(raw, '$.message.usage.output_tokens')), 0) AS output_tokens,
+                COALESCE(SUM(json_extract(raw, '$.message.usage.cache_creation_input_tokens')), 0) AS cache_creation_tokens,
+                COALESCE(SUM(json_extract(raw, '$.message.usage.cache_read_input_tokens')), 0) AS cache_read_tokens,
+                {price_in} AS price_in,
+                {price_out} AS price_out
+            FROM (
+                SELECT *,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY session_id, {RESPONSE_KEY_SQL} ORDER BY line_number DESC
+                    ) AS copy
+                FROM lines
+                WHERE type = 'assistant' AND model IS NOT NULL
+            )
+            WHERE copy = 1
+            GROUP BY session_id, model
+         )"
+        ),
+    ),
+    ]
+}
+
+/// (Re)create the reporting views
+pub fn init_views(conn: &Connection) -> Result<(), IndexerError> {
+    for (name, select) in reporting_views() {
+        conn.execute_batch(&format!(
+            "DROP VIEW IF EXISTS {name};
+             CREATE VIEW {name} AS {select};"
+        ))?;
+    }
+    Ok(())
+}
+
 /// Migrate schema from older versions to current
 pub fn migrate_schema(conn: &Connection) -> Result<(), IndexerError> {
     // Check current version
@@ -276,13 +694,11 @@ pub fn migrate_schema(conn: &Connection) -> Result<(), IndexerError> {
             END;
 
             CREATE TRIGGER IF NOT EXISTS hook_events_ad AFTER DELETE ON hook_events BEGIN
-                INSERT INTO hook_events_fts(hook_events_fts, rowid, content)
-                VALUES ('delete', old.id, COALESCE(old.event_type, '') || ' ' || COALESCE(old.tool_name, '') || ' ' || COALESCE(old.input_json, ''));
+                DELETE FROM hook_events_fts WHERE rowid = old.id;
             END;
 
             CREATE TRIGGER IF NOT EXISTS hook_events_au AFTER UPDATE ON hook_events BEGIN
-                INSERT INTO hook_events_fts(hook_events_fts, rowid, content)
-                VALUES ('delete', old.id, COALESCE(old.event_type, '') || ' ' || COALESCE(old.tool_name, '') || ' ' || COALESCE(old.input_json, ''));
+                DELETE FROM hook_events_fts WHERE rowid = old.id;
                 INSERT INTO hook_events_fts(rowid, content)
                 VALUES (new.id, COALESCE(new.event_type, '') || ' ' || COALESCE(new.tool_name, '') || ' ' || COALESCE(new.input_json, ''));
             END;",
@@ -357,10 +773,183 @@ pub fn migrate_schema(conn: &Connection) -> Result<(), IndexerError> {
         )?;
 
         eprintln!("[db] Migration v9->v10 complete");
-        eprintln!("[db] Tip: Run VACUUM to reclaim disk space");
+        eprintln!("[db] Tip: Run `transcript index maintain` to reclaim disk space");
         version = 10;
     }
 
+    // Migration v10 -> v11: Add user column (multi-home indexing)
+    // Existing rows are attributed to a user by the home root their file lives under.
+    if version == 10 {
+        eprintln!("[db] Migrating schema from v10 to v11 (user column)...");
+
+        for table in ["lines", "sessions", "hook_events"] {
+            let _ = conn.execute(&format!("ALTER TABLE {} ADD COLUMN user TEXT", table), []);
+        }
+
+        let roots = crate::discovery::home_roots();
+        let files: Vec<String> = {
+            let mut stmt = conn.prepare(
+                "SELECT file_path FROM sessions UNION SELECT file_path FROM hook_files",
+            )?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+        for file in &files {
+            if let Some(user) = crate::discovery::user_for_path(std::path::Path::new(file), &roots) {
+                for table in ["lines", "sessions", "hook_events"] {
+                    conn.execute(
+                        &format!("UPDATE {} SET user = ?1 WHERE file_path = ?2 AND user IS NULL", table),
+                        [&user, file],
+                    )?;
+                }
+            }
+        }
+
+        eprintln!("[db] Migration v10->v11 complete");
+        version = 11;
+    }
+
+    // Migration v11 -> v12: Add tags table (created above; nothing to backfill)
+    if version == 11 {
+        eprintln!("[db] Migration v11->v12 complete (tags table)");
+        version = 12;
+    }
+
+    // Migration v12 -> v13: Add bookmarks table (created above; nothing to backfill)
+    if version == 12 {
+        eprintln!("[db] Migration v12->v13 complete (bookmarks table)");
+        version = 13;
+    }
+
+    // Migration v13 -> v14: Add annotations table (created above; nothing to backfill)
+    if version == 13 {
+        eprintln!("[db] Migration v13->v14 complete (annotations table)");
+        version = 14;
+    }
+
+    // Migration v14 -> v15: Add annotations.parent_id (existing notes start threads)
+    if version == 14 {
+        let _ = conn.execute("ALTER TABLE annotations ADD COLUMN parent_id INTEGER", []);
+        eprintln!("[db] Migration v14->v15 complete (annotation replies)");
+        version = 15;
+    }
+
+    // Migration v15 -> v16: Add reviews table (created above; nothing to backfill)
+    if version == 15 {
+        eprintln!("[db] Migration v15->v16 complete (reviews table)");
+        version = 16;
+    }
+
+    // Migration v16 -> v17: Add tool_payloads table (created above; sizes are
+    // recorded as lines are indexed, so older lines need a rebuild)
+    if version == 16 {
+        eprintln!("[db] Migration v16->v17 complete (tool_payloads table; rebuild to size existing lines)");
+        version = 17;
+    }
+
+    // Migration v17 -> v18: Add changes table (created above; nothing to backfill)
+    if version == 17 {
+        eprintln!("[db] Migration v17->v18 complete (changes table)");
+        version = 18;
+    }
+
+    // Migration v18 -> v19: Add restored_files table (created above; nothing to backfill)
+    if version == 18 {
+        eprintln!("[db] Migration v18->v19 complete (restored_files table)");
+        version = 19;
+    }
+
+    // Migration v19 -> v20: Add ignored_sessions table (created above; nothing
+    // to backfill), and fix the hook_events_fts delete/update triggers: the
+    // 'delete' command is only for external content tables, so deleting or
+    // updating a hook event failed
+    if version == 19 {
+        conn.execute_batch(
+            "DROP TRIGGER IF EXISTS hook_events_ad;
+             DROP TRIGGER IF EXISTS hook_events_au;
+             CREATE TRIGGER hook_events_ad AFTER DELETE ON hook_events BEGIN
+                 DELETE FROM hook_events_fts WHERE rowid = old.id;
+             END;
+             CREATE TRIGGER hook_events_au AFTER UPDATE ON hook_events BEGIN
+                 DELETE FROM hook_events_fts WHERE rowid = old.id;
+                 INSERT INTO hook_events_fts(rowid, content)
+                 VALUES (new.id, COALESCE(new.event_type, '') || ' ' || COALESCE(new.tool_name, '') || ' ' || COALESCE(new.input_json, ''));
+             END;",
+        )?;
+        eprintln!("[db] Migration v19->v20 complete (ignored_sessions table, hook event search triggers)");
+        version = 20;
+    }
+
+    // Migration v20 -> v21: Add archived_files table (created above; nothing to backfill)
+    if version == 20 {
+        eprintln!("[db] Migration v20->v21 complete (archived_files table)");
+        version = 21;
+    }
+
+    // Migration v21 -> v22: Add session_lineage table and the lines(uuid)
+    // index it is detected with (both created above), and link the sessions
+    // already indexed
+    if version == 21 {
+        let linked = crate::lineage::detect_all_lineage(conn)?;
+        eprintln!("[db] Migration v21->v22 complete (session_lineage table, {} resumed sessions linked)", linked);
+        version = 22;
+    }
+
+    // Migration v22 -> v23: Add repo_root column to lines, backfilled from
+    // each directory's checkout where it's on this machine
+    if version == 22 {
+        eprintln!("[db] Migrating schema from v22 to v23 (repo_root column)...");
+
+        let _ = conn.execute("ALTER TABLE lines ADD COLUMN repo_root TEXT", []);
+
+        let cwds: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT DISTINCT cwd FROM lines WHERE cwd IS NOT NULL AND cwd != ''")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.filter_map(|r| r.ok()).collect()
+        };
+        let mut roots = crate::repo::RepoRoots::default();
+        let mut found = 0;
+        for cwd in &cwds {
+            if let Some(root) = roots.get(cwd) {
+                conn.execute("UPDATE lines SET repo_root = ?1 WHERE cwd = ?2", [&root, cwd])?;
+                found += 1;
+            }
+        }
+
+        eprintln!("[db] Migration v22->v23 complete ({} of {} directories in a repository)", found, cwds.len());
+        version = 23;
+    }
+
+    // Migration v23 -> v24: Add slow_queries table (created above; nothing to backfill)
+    if version == 23 {
+        eprintln!("[db] Migration v23->v24 complete (slow_queries table)");
+        version = 24;
+    }
+
+    // Migration v24 -> v25: Add saved_searches table (created above; nothing to backfill)
+    if version == 24 {
+        eprintln!("[db] Migration v24->v25 complete (saved_searches table)");
+        version = 25;
+    }
+
+    // Migration v25 -> v26: Add deleted_files table (created above; nothing to backfill)
+    if version == 25 {
+        eprintln!("[db] Migration v25->v26 complete (deleted_files table)");
+        version = 26;
+    }
+
+    // Migration v26 -> v27: Add index_errors table (created above; nothing to backfill)
+    if version == 26 {
+        eprintln!("[db] Migration v26->v27 complete (index_errors table)");
+        version = 27;
+    }
+
+    // Migration v27 -> v28: Add merged_files table (created above; nothing to backfill)
+    if version == 27 {
+        eprintln!("[db] Migration v27->v28 complete (merged_files table)");
+        version = 28;
+    }
+
     // Suppress unused variable warning
     let _ = version;
 
@@ -392,6 +981,22 @@ mod tests {
         assert!(tables.contains(&"hook_events".to_string()));
         assert!(tables.contains(&"hook_files".to_string()));
         assert!(tables.contains(&"adapter_cursors".to_string()));
+        assert!(tables.contains(&"tags".to_string()));
+        assert!(tables.contains(&"bookmarks".to_string()));
+        assert!(tables.contains(&"annotations".to_string()));
+        assert!(tables.contains(&"annotations_fts".to_string()));
+        assert!(tables.contains(&"reviews".to_string()));
+        assert!(tables.contains(&"tool_payloads".to_string()));
+        assert!(tables.contains(&"changes".to_string()));
+        assert!(tables.contains(&"restored_files".to_string()));
+        assert!(tables.contains(&"ignored_sessions".to_string()));
+        assert!(tables.contains(&"archived_files".to_string()));
+        assert!(tables.contains(&"session_lineage".to_string()));
+        assert!(tables.contains(&"slow_queries".to_string()));
+        assert!(tables.contains(&"saved_searches".to_string()));
+        assert!(tables.contains(&"deleted_files".to_string()));
+        assert!(tables.contains(&"index_errors".to_string()));
+        assert!(tables.contains(&"merged_files".to_string()));
 
         // Check version
         let version: i32 = conn
@@ -404,6 +1009,109 @@ mod tests {
         assert_eq!(version, DB_VERSION);
     }
 
+    #[test]
+    fn test_newer_schema_is_not_downgraded() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+        let compat: i32 = conn
+            .query_row(
+                "SELECT CAST(value AS INTEGER) FROM metadata WHERE key = 'reader_compat_version'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(compat, READER_COMPAT_VERSION);
+
+        let newer = DB_VERSION + 1;
+        conn.execute(
+            "UPDATE metadata SET value = ?1 WHERE key = 'version'",
+            [newer.to_string()],
+        )
+        .unwrap();
+        let err = init_schema(&mut conn).unwrap_err();
+        assert!(matches!(err, IndexerError::SchemaTooNew { found, .. } if found == newer));
+
+        // Left as the newer indexer wrote it
+        let version: i32 = conn
+            .query_row(
+                "SELECT CAST(value AS INTEGER) FROM metadata WHERE key = 'version'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(version, newer);
+    }
+
+    #[test]
+    fn test_reporting_views_are_queryable() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, model, raw, file_path, turn_id)
+             VALUES ('s1', 'u1', 1, 'assistant', '2024-01-01T00:00:00Z', 'claude-sonnet-4', ?1, '/tmp/s1.jsonl', 's1:1')",
+            [r#"{"message":{"content":[{"type":"text","text":"hi"},{"type":"tool_use","id":"t1","name":"Read","input":{"file_path":"/a.rs"}}],"usage":{"input_tokens":1000000,"output_tokens":0}}}"#],
+        )
+        .unwrap();
+
+        for (name, _) in reporting_views() {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", name), [], |row| row.get::<_, i64>(0))
+                .unwrap();
+        }
+
+        let (tool, path): (String, String) = conn
+            .query_row("SELECT tool_name, file_path FROM v_tool_calls", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(tool, "Read");
+        assert_eq!(path, "/a.rs");
+
+        let cost: f64 = conn
+            .query_row("SELECT estimated_cost_usd FROM v_costs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cost, 3.0);
+    }
+
+    #[test]
+    fn test_cost_views_match_core_pricing_and_count_responses_once() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        // One opus-4-5 response written as two lines, each repeating its usage
+        let raw = r#"{"requestId":"r1","message":{"id":"m1","content":"x","usage":{"input_tokens":1000000,"output_tokens":100}}}"#;
+        for n in 1..=2 {
+            conn.execute(
+                "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, model, raw, file_path, turn_id)
+                 VALUES ('s1', ?1, ?1, 'assistant', '2024-01-01T00:00:00Z', 'claude-opus-4-5-20251101', ?2, '/tmp/s1.jsonl', 's1:1')",
+                rusqlite::params![n, raw],
+            )
+            .unwrap();
+        }
+
+        let (messages, input, cost): (i64, i64, f64) = conn
+            .query_row("SELECT messages, input_tokens, estimated_cost_usd FROM v_costs", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!((messages, input), (1, 1_000_000));
+        let expected = transcript_core::usage_cost(
+            "claude-opus-4-5-20251101",
+            &transcript_core::TokenUsage {
+                input_tokens: 1_000_000,
+                output_tokens: 100,
+                cache_creation_input_tokens: None,
+                cache_read_input_tokens: None,
+            },
+        );
+        assert!((cost - expected).abs() < 1e-4);
+
+        let (lines, output): (i64, i64) = conn
+            .query_row("SELECT line_count, output_tokens FROM v_turns", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((lines, output), (2, 100));
+    }
+
     #[test]
     fn test_schema_is_idempotent() {
         let mut conn = Connection::open_in_memory().unwrap();
@@ -421,6 +1129,60 @@ mod tests {
         assert_eq!(version, DB_VERSION);
     }
 
+    #[test]
+    fn test_v10_database_gains_user_column() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+        // A v10 writer (the TypeScript indexer) resets the version
+        conn.execute("UPDATE metadata SET value = '10' WHERE key = 'version'", [])
+            .unwrap();
+
+        init_schema(&mut conn).unwrap();
+
+        for table in ["lines", "sessions", "hook_events"] {
+            let has_user: bool = conn
+                .query_row(
+                    &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = 'user'", table),
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert!(has_user, "{} should have a user column", table);
+        }
+    }
+
+    #[test]
+    fn test_v14_annotations_gain_parent_id() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+        // A v14 database: annotations without replies
+        conn.execute_batch(
+            "DROP TABLE annotations;
+             CREATE TABLE annotations (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 session_id TEXT NOT NULL,
+                 uuid TEXT NOT NULL,
+                 line_number INTEGER NOT NULL,
+                 author TEXT NOT NULL,
+                 body TEXT NOT NULL,
+                 created_at TEXT NOT NULL
+             );
+             UPDATE metadata SET value = '14' WHERE key = 'version';",
+        )
+        .unwrap();
+
+        init_schema(&mut conn).unwrap();
+
+        let has_parent: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('annotations') WHERE name = 'parent_id'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(has_parent);
+    }
+
     #[test]
     fn test_fts_tables_exist() {
         let mut conn = Connection::open_in_memory().unwrap();
@@ -0,0 +1,199 @@
+//! Stream indexer for transcripts piped in on stdin
+//!
+//! Lets programs other than Claude Code (e.g. wrappers around the Anthropic
+//! SDK) feed conversations into the database as they happen. Each JSONL line
+//! is filled in to look like a transcript line, appended to
+//! `<streams dir>/<session-id>.jsonl` and indexed straight away, so the stream
+//! survives `index rebuild` like any other transcript file.
+//!
+//! Lines may be transcript lines or bare Messages API messages
+//! (`{"role": "user", "content": ...}`); missing `sessionId`, `uuid`,
+//! `parentUuid`, `timestamp` and `type` fields are filled in.
+
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use std::fs::OpenOptions;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use transcript_core::{decode_line, path_key};
+
+use crate::connection::IndexerError;
+use crate::indexer::index_transcript_file;
+
+/// Result of indexing a stream
+#[derive(Debug, Default)]
+pub struct StreamResult {
+    pub lines_read: usize,
+    pub lines_indexed: usize,
+    pub lines_skipped: usize,
+    /// Lines read despite invalid UTF-8 or a byte-order mark
+    pub lines_recovered: usize,
+    pub file_path: PathBuf,
+}
+
+/// Transcript file a stream's lines are kept in
+pub fn stream_file_path(streams_dir: &Path, session_id: &str) -> PathBuf {
+    let name: String = session_id
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect();
+    streams_dir.join(format!("{}.jsonl", name))
+}
+
+/// Fill in the transcript fields a streamed line is missing
+///
+/// `previous_uuid` chains lines that don't name a parent onto the line before.
+pub fn normalize_stream_line(
+    mut value: Value,
+    session_id: &str,
+    line_number: i64,
+    previous_uuid: Option<&str>,
+    now: &str,
+) -> Option<Value> {
+    // A bare message becomes the `message` of a transcript line
+    if value.get("type").is_none() {
+        let role = value.get("role")?.as_str()?.to_string();
+        value = json!({ "type": role, "message": value });
+    }
+    let object = value.as_object_mut()?;
+
+    object.insert("sessionId".to_string(), json!(session_id));
+    if !object.get("uuid").is_some_and(|u| u.is_string()) {
+        object.insert("uuid".to_string(), json!(format!("{}-{}", session_id, line_number)));
+    }
+    if !object.contains_key("parentUuid") {
+        object.insert("parentUuid".to_string(), json!(previous_uuid));
+    }
+    if !object.get("timestamp").is_some_and(|t| t.is_string()) {
+        object.insert("timestamp".to_string(), json!(now));
+    }
+    Some(value)
+}
+
+/// Read JSONL from `reader` until it ends, appending and indexing each line
+///
+/// Running again with the same session id continues the same transcript.
+/// `on_line` is called with the uuid of every line indexed.
+pub fn index_stream<R, F>(
+    conn: &Connection,
+    reader: R,
+    session_id: &str,
+    streams_dir: &Path,
+    mut on_line: F,
+) -> Result<StreamResult, IndexerError>
+where
+    R: BufRead,
+    F: FnMut(&str),
+{
+    std::fs::create_dir_all(streams_dir)?;
+    let file_path = stream_file_path(streams_dir, session_id);
+    let file_path_str = path_key(&file_path);
+
+    // Pick up where an earlier run of this stream stopped
+    let existing = std::fs::read_to_string(&file_path).unwrap_or_default();
+    let mut line_number = existing.lines().count() as i64;
+    let mut previous_uuid: Option<String> = existing
+        .lines()
+        .rev()
+        .find_map(|l| serde_json::from_str::<Value>(l).ok()?.get("uuid")?.as_str().map(String::from));
+    let (mut offset, mut indexed_lines): (u64, i64) = conn
+        .query_row(
+            "SELECT byte_offset, line_count FROM sessions WHERE file_path = ?1",
+            [&file_path_str],
+            |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)),
+        )
+        .unwrap_or((0, 0));
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&file_path)?;
+    let mut result = StreamResult {
+        file_path: file_path.clone(),
+        ..Default::default()
+    };
+
+    for line in reader.split(b'\n') {
+        let line = line?;
+        let (line, recovered) = decode_line(line.strip_suffix(b"\r").unwrap_or(&line));
+        if line.trim().is_empty() {
+            continue;
+        }
+        result.lines_read += 1;
+        result.lines_recovered += usize::from(recovered);
+
+        let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let normalized = serde_json::from_str(line.trim()).ok().and_then(|value| {
+            normalize_stream_line(value, session_id, line_number + 1, previous_uuid.as_deref(), &now)
+        });
+        let Some(normalized) = normalized else {
+            result.lines_skipped += 1;
+            continue;
+        };
+
+        writeln!(file, "{}", serde_json::to_string(&normalized)?)?;
+        file.flush()?;
+        line_number += 1;
+        previous_uuid = normalized.get("uuid").and_then(|u| u.as_str()).map(String::from);
+
+        let indexed = index_transcript_file(conn, &file_path, offset, indexed_lines + 1)?;
+        offset = indexed.byte_offset;
+        indexed_lines = line_number;
+        result.lines_indexed += indexed.lines_indexed;
+        if let Some(uuid) = &previous_uuid {
+            on_line(uuid);
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_index_stream() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema::init_schema(&mut conn).unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+
+        let input = concat!(
+            r#"{"role":"user","content":"What is 2+2?"}"#,
+            "\n",
+            "not json\n",
+            "\n",
+            r#"{"role":"assistant","content":[{"type":"text","text":"4"}],"model":"claude-3"}"#,
+            "\n",
+        );
+        let mut seen = Vec::new();
+        let result = index_stream(&conn, input.as_bytes(), "sdk-1", tmp.path(), |uuid| {
+            seen.push(uuid.to_string())
+        })
+        .unwrap();
+        assert_eq!((result.lines_read, result.lines_indexed, result.lines_skipped), (3, 2, 1));
+        assert_eq!(seen, ["sdk-1-1", "sdk-1-2"]);
+
+        // A second run continues the same transcript
+        let more = r#"{"type":"user","message":{"role":"user","content":"Thanks"}}"#;
+        index_stream(&conn, more.as_bytes(), "sdk-1", tmp.path(), |_| {}).unwrap();
+
+        let rows: Vec<(String, Option<String>, String)> = conn
+            .prepare("SELECT uuid, parent_uuid, type FROM lines WHERE session_id = 'sdk-1' ORDER BY line_number")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("sdk-1-1".to_string(), None, "user".to_string()),
+                ("sdk-1-2".to_string(), Some("sdk-1-1".to_string()), "assistant".to_string()),
+                ("sdk-1-3".to_string(), Some("sdk-1-2".to_string()), "user".to_string()),
+            ]
+        );
+        let line_count: i64 = conn
+            .query_row("SELECT line_count FROM sessions WHERE session_id = 'sdk-1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(line_count, 3);
+    }
+}
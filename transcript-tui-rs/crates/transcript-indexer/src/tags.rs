@@ -0,0 +1,56 @@
+//! Session tags
+//!
+//! Tags are labels the user attaches to sessions ("bug-hunt", "release").
+//! Unlike the indexed tables they can't be recreated from the transcript
+//! files, so rebuilds leave them alone.
+
+use rusqlite::Connection;
+
+use crate::connection::IndexerError;
+
+/// Tag a session; returns false if it already had the tag
+pub fn add_tag(conn: &Connection, session_id: &str, tag: &str) -> Result<bool, IndexerError> {
+    let added = conn.execute(
+        "INSERT OR IGNORE INTO tags (session_id, tag, created_at) VALUES (?1, ?2, ?3)",
+        [session_id, tag, &chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(added > 0)
+}
+
+/// Untag a session; returns false if it didn't have the tag
+pub fn remove_tag(conn: &Connection, session_id: &str, tag: &str) -> Result<bool, IndexerError> {
+    let removed = conn.execute(
+        "DELETE FROM tags WHERE session_id = ?1 AND tag = ?2",
+        [session_id, tag],
+    )?;
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::init_schema;
+
+    #[test]
+    fn test_tags_survive_rebuild() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init_schema(&mut conn).unwrap();
+
+        assert!(add_tag(&conn, "s1", "release").unwrap());
+        assert!(!add_tag(&conn, "s1", "release").unwrap());
+        assert!(add_tag(&conn, "s1", "bug-hunt").unwrap());
+        assert!(remove_tag(&conn, "s1", "bug-hunt").unwrap());
+        assert!(!remove_tag(&conn, "s1", "bug-hunt").unwrap());
+
+        crate::rebuild::rebuild_index(&mut conn).unwrap();
+
+        let tags: Vec<String> = conn
+            .prepare("SELECT tag FROM tags WHERE session_id = 's1'")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect();
+        assert_eq!(tags, vec!["release"]);
+    }
+}
@@ -0,0 +1,332 @@
+//! Trash for rows removed from the index
+//!
+//! Pruning and rebuilding remove rows that can't be indexed again once their
+//! file is gone. Before they are deleted, they are copied into a bundle: a small
+//! SQLite database in `~/.claude-code-sdk/trash` with the same tables as the
+//! index (sessions, lines, tool payloads, hook files and hook events) plus a
+//! `bundle` table noting why and when. Restoring merges a bundle back the way
+//! `sync pull` merges a snapshot, so restoring twice adds nothing; emptying the
+//! trash deletes bundles for good.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::connection::{attach, unlock, IndexerError};
+use crate::merge::{self, MergeResult};
+
+/// Tables copied into a bundle, with the column naming each row's file
+const TRASHED_TABLES: [&str; 4] = ["sessions", "lines", "hook_files", "hook_events"];
+
+/// A bundle of removed rows in the trash
+#[derive(Debug, Clone)]
+pub struct TrashBundle {
+    /// File name without `.db`: creation time and reason (`20250101-120000-prune`)
+    pub id: String,
+    pub path: PathBuf,
+    /// What removed the rows (`prune`, `rebuild`)
+    pub reason: String,
+    pub created_at: String,
+    /// Transcript and hook files the rows were indexed from
+    pub files: Vec<String>,
+    pub sessions: usize,
+    pub lines: usize,
+    pub hook_events: usize,
+    /// Size of the bundle on disk
+    pub bytes: u64,
+}
+
+/// Copy the rows indexed from `files` into a new bundle in `trash_dir`
+///
+/// The rows themselves are left in place; callers delete them once this
+/// returns. Returns `None` (and writes nothing) when `files` is empty. Must be
+/// called outside a transaction, as it attaches the bundle.
+pub fn trash_files(
+    conn: &Connection,
+    trash_dir: &Path,
+    reason: &str,
+    files: &[String],
+) -> Result<Option<TrashBundle>, IndexerError> {
+    if files.is_empty() {
+        return Ok(None);
+    }
+    std::fs::create_dir_all(trash_dir)?;
+
+    let created_at = chrono::Utc::now();
+    let stamp = created_at.format("%Y%m%d-%H%M%S");
+    let mut id = format!("{}-{}", stamp, reason);
+    let mut n = 1;
+    while trash_dir.join(format!("{}.db", id)).exists() {
+        n += 1;
+        id = format!("{}-{}-{}", stamp, reason, n);
+    }
+    // Written under a temporary name so a failed copy never looks like a bundle
+    let path = trash_dir.join(format!("{}.db", id));
+    let partial = trash_dir.join(format!("{}.db.partial", id));
+    let _ = std::fs::remove_file(&partial);
+
+    attach(conn, &partial, "trash")?;
+    let copied = copy_rows(conn, reason, &created_at.to_rfc3339(), files);
+    conn.execute_batch("DETACH DATABASE trash")?;
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, &path)?;
+
+    read_bundle(&path).map(Some)
+}
+
+fn copy_rows(conn: &Connection, reason: &str, created_at: &str, files: &[String]) -> Result<(), IndexerError> {
+    conn.execute_batch(
+        "CREATE TABLE trash.bundle (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+         CREATE TABLE trash.files (file_path TEXT PRIMARY KEY);
+         CREATE TABLE trash.tool_payloads AS SELECT * FROM main.tool_payloads WHERE 0;",
+    )?;
+    for table in TRASHED_TABLES {
+        conn.execute_batch(&format!(
+            "CREATE TABLE trash.{table} AS SELECT * FROM main.{table} WHERE 0"
+        ))?;
+    }
+    conn.execute(
+        "INSERT INTO trash.bundle (key, value) VALUES ('reason', ?1), ('created_at', ?2)",
+        [reason, created_at],
+    )?;
+
+    let mut insert_file = conn.prepare("INSERT OR IGNORE INTO trash.files (file_path) VALUES (?1)")?;
+    for file in files {
+        insert_file.execute([file])?;
+    }
+    for table in TRASHED_TABLES {
+        conn.execute(
+            &format!(
+                "INSERT INTO trash.{table} SELECT * FROM main.{table}
+                 WHERE file_path IN (SELECT file_path FROM trash.files)"
+            ),
+            [],
+        )?;
+    }
+    // Payload sizes belong to lines (by session and UUID), not to files
+    conn.execute(
+        "INSERT INTO trash.tool_payloads SELECT p.* FROM main.tool_payloads p
+         WHERE EXISTS (
+             SELECT 1 FROM trash.lines l WHERE l.session_id = p.session_id AND l.uuid = p.uuid
+         )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Copy the rows of sessions into a new bundle in `dir`, leaving them in
+/// the index
+///
+/// The bundle is a database like a sync snapshot, so another machine merges
+/// it with `transcript sync pull` from a directory holding it.
+pub fn export_sessions(
+    conn: &Connection,
+    session_ids: &[String],
+    dir: &Path,
+) -> Result<Option<TrashBundle>, IndexerError> {
+    let files: Vec<String> = crate::prune::session_files(conn, session_ids)?
+        .into_iter()
+        .map(|f| f.file_path)
+        .collect();
+    trash_files(conn, dir, "export", &files)
+}
+
+/// Describe the bundle at `path`
+pub fn read_bundle(path: &Path) -> Result<TrashBundle, IndexerError> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+    unlock(&conn, path)?;
+    let meta = |key: &str| -> Result<String, IndexerError> {
+        Ok(conn.query_row("SELECT value FROM bundle WHERE key = ?1", [key], |row| row.get(0))?)
+    };
+    let count = |table: &str| -> Result<usize, IndexerError> {
+        Ok(conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get::<_, i64>(0))? as usize)
+    };
+
+    let files = conn
+        .prepare("SELECT file_path FROM files ORDER BY file_path")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    Ok(TrashBundle {
+        id: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.to_path_buf(),
+        reason: meta("reason")?,
+        created_at: meta("created_at")?,
+        files,
+        sessions: count("sessions")?,
+        lines: count("lines")?,
+        hook_events: count("hook_events")?,
+        bytes: std::fs::metadata(path)?.len(),
+    })
+}
+
+/// Bundles in `trash_dir`, newest first
+///
+/// Files that can't be read as bundles are skipped.
+pub fn list_trash(trash_dir: &Path) -> Result<Vec<TrashBundle>, IndexerError> {
+    let entries = match std::fs::read_dir(trash_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut bundles: Vec<TrashBundle> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|path| read_bundle(&path).ok())
+        .collect();
+    bundles.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.cmp(&a.id)));
+    Ok(bundles)
+}
+
+/// The bundle with `id`, or the only one whose id starts with it
+pub fn find_bundle(trash_dir: &Path, id: &str) -> Result<TrashBundle, IndexerError> {
+    let bundles = list_trash(trash_dir)?;
+    if let Some(bundle) = bundles.iter().find(|b| b.id == id) {
+        return Ok(bundle.clone());
+    }
+    let mut matches = bundles.into_iter().filter(|b| b.id.starts_with(id));
+    match (matches.next(), matches.next()) {
+        (Some(bundle), None) => Ok(bundle),
+        _ => Err(IndexerError::TrashBundleNotFound(id.to_string())),
+    }
+}
+
+/// Merge a bundle's rows back into the index and delete it from the trash
+///
+/// Rows still (or again) in the index are kept as they are. Files that are
+/// still gone are recorded in `restored_files`, so the next `index update`
+/// doesn't prune them straight back into the trash; deleted sessions' files
+/// may be indexed again.
+pub fn restore_bundle(conn: &mut Connection, bundle: &TrashBundle) -> Result<MergeResult, IndexerError> {
+    attach(conn, &bundle.path, "src")?;
+    let result = merge::merge_attached(conn).and_then(|result| {
+        restore_extras(conn, bundle)?;
+        Ok(result)
+    });
+    conn.execute_batch("DETACH DATABASE src")?;
+    let result = result?;
+
+    std::fs::remove_file(&bundle.path)?;
+    Ok(result)
+}
+
+fn restore_extras(conn: &mut Connection, bundle: &TrashBundle) -> Result<(), IndexerError> {
+    let tx = conn.transaction()?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let cols = merge::shared_columns(&tx, "tool_payloads", &[])?.join(", ");
+    tx.execute(
+        &format!("INSERT OR IGNORE INTO main.tool_payloads ({cols}) SELECT {cols} FROM src.tool_payloads"),
+        [],
+    )?;
+
+    for file in &bundle.files {
+        tx.execute("DELETE FROM deleted_files WHERE file_path = ?1", [file])?;
+        if !Path::new(file).exists() {
+            tx.execute(
+                "INSERT OR REPLACE INTO restored_files (file_path, bundle, restored_at) VALUES (?1, ?2, ?3)",
+                rusqlite::params![file, bundle.id, now],
+            )?;
+        }
+    }
+
+    // Let open viewers pick the sessions up again
+    let sessions = tx
+        .prepare("SELECT DISTINCT session_id FROM src.lines")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    for session_id in &sessions {
+        crate::indexer::record_change(&tx, session_id, &now)?;
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Delete bundles for good, all of them or those created before `before`
+///
+/// Returns how many bundles were deleted and the bytes they took up.
+pub fn empty_trash(
+    trash_dir: &Path,
+    before: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<(usize, u64), IndexerError> {
+    let mut removed = (0, 0);
+    for bundle in list_trash(trash_dir)? {
+        let expired = match before {
+            Some(before) => chrono::DateTime::parse_from_rfc3339(&bundle.created_at)
+                .map(|created| created < before)
+                .unwrap_or(false),
+            None => true,
+        };
+        if expired {
+            std::fs::remove_file(&bundle.path)?;
+            removed.0 += 1;
+            removed.1 += bundle.bytes;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_trash_and_restore_rows() {
+        let tmp = tempfile::tempdir().unwrap();
+        let trash_dir = tmp.path().join("trash");
+        let mut conn = Connection::open(tmp.path().join("index.db")).unwrap();
+        schema::init_schema(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO lines (session_id, uuid, line_number, type, timestamp, raw, file_path, content)
+             VALUES ('s1', 'u1', 1, 'user', '2024-01-01T00:00:00Z', '{}', '/gone/s1.jsonl', 'hello trash'),
+                    ('s2', 'u1', 1, 'user', '2024-01-01T00:00:00Z', '{}', '/here/s2.jsonl', 'hello');
+             INSERT INTO sessions (file_path, session_id, line_count, byte_offset, indexed_at)
+             VALUES ('/gone/s1.jsonl', 's1', 1, 10, '2024-01-01'), ('/here/s2.jsonl', 's2', 1, 10, '2024-01-01');
+             INSERT INTO tool_payloads (session_id, uuid, line_number, tool_use_id, direction, bytes)
+             VALUES ('s1', 'u1', 1, 't1', 'output', 5000);",
+        )
+        .unwrap();
+
+        assert!(trash_files(&conn, &trash_dir, "prune", &[]).unwrap().is_none());
+        let bundle = trash_files(&conn, &trash_dir, "prune", &["/gone/s1.jsonl".to_string()])
+            .unwrap()
+            .unwrap();
+        assert!(bundle.id.ends_with("-prune"));
+        assert_eq!((bundle.sessions, bundle.lines, bundle.files.len()), (1, 1, 1));
+        conn.execute_batch(
+            "DELETE FROM tool_payloads; DELETE FROM lines WHERE session_id = 's1';
+             DELETE FROM sessions WHERE session_id = 's1';",
+        )
+        .unwrap();
+
+        let found = find_bundle(&trash_dir, &bundle.id[..8]).unwrap();
+        let restored = restore_bundle(&mut conn, &found).unwrap();
+        assert_eq!((restored.lines, restored.sessions), (1, 1));
+        assert!(list_trash(&trash_dir).unwrap().is_empty());
+
+        let (hits, payloads, pinned): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM lines_fts WHERE lines_fts MATCH 'trash'),
+                        (SELECT COUNT(*) FROM tool_payloads),
+                        (SELECT COUNT(*) FROM restored_files WHERE file_path = '/gone/s1.jsonl')",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((hits, payloads, pinned), (1, 1, 1));
+
+        trash_files(&conn, &trash_dir, "rebuild", &["/here/s2.jsonl".to_string()]).unwrap();
+        assert_eq!(empty_trash(&trash_dir, Some(chrono::Utc::now() - chrono::Duration::days(1))).unwrap().0, 0);
+        assert_eq!(empty_trash(&trash_dir, None).unwrap().0, 1);
+    }
+}
@@ -0,0 +1,208 @@
+//! Co-viewing: `--broadcast` serves the viewer's frames, `--attach` mirrors them
+//!
+//! The stream is the frames as terminal output (cursor home, then each line
+//! with its colors), sent whenever the screen changes, so anything that
+//! shows a byte stream in a terminal can watch, `nc host 7777` included.
+//! Watchers see the host's screen at the host's size and can't send keys.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::buffer::Buffer;
+
+/// How long a slow watcher may hold up a frame before it is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// `:7777` is `127.0.0.1:7777`; give an address (`0.0.0.0:7777`) to share on the network
+fn socket_addr(addr: &str) -> String {
+    if addr.starts_with(':') {
+        format!("127.0.0.1{}", addr)
+    } else {
+        addr.to_string()
+    }
+}
+
+#[derive(Default)]
+struct Watchers {
+    streams: Vec<TcpStream>,
+    /// Last frame sent, for watchers joining later
+    frame: String,
+}
+
+/// Sends each changed frame to every attached watcher
+pub struct Broadcaster {
+    addr: String,
+    watchers: Arc<Mutex<Watchers>>,
+}
+
+impl Broadcaster {
+    /// Listen on `addr` and accept watchers in the background
+    pub fn bind(addr: &str) -> Result<Self> {
+        let listener = TcpListener::bind(socket_addr(addr))
+            .with_context(|| format!("Failed to listen on {}", addr))?;
+        let addr = listener.local_addr()?.to_string();
+        let watchers = Arc::new(Mutex::new(Watchers::default()));
+
+        let accepted = Arc::clone(&watchers);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                let _ = stream.set_nodelay(true);
+                let mut watchers = accepted.lock().unwrap();
+                // Clear the watcher's screen and catch it up
+                if stream
+                    .write_all(format!("\x1b[2J{}", watchers.frame).as_bytes())
+                    .is_ok()
+                {
+                    watchers.streams.push(stream);
+                }
+            }
+        });
+
+        Ok(Self { addr, watchers })
+    }
+
+    /// The address watchers attach to
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    pub fn watcher_count(&self) -> usize {
+        self.watchers.lock().unwrap().streams.len()
+    }
+
+    /// Send a drawn frame, if it changed, dropping watchers that went away
+    pub fn send(&self, buffer: &Buffer) {
+        let frame = format!(
+            "\x1b[H{}\x1b[K\x1b[J",
+            crate::buffer_to_ansi(buffer).join("\x1b[K\r\n")
+        );
+        let mut watchers = self.watchers.lock().unwrap();
+        if watchers.frame == frame {
+            return;
+        }
+        watchers
+            .streams
+            .retain_mut(|stream| stream.write_all(frame.as_bytes()).is_ok());
+        watchers.frame = frame;
+    }
+}
+
+/// Mirror a broadcast in this terminal until it ends or q / Esc / Ctrl-C
+pub fn attach(addr: &str) -> Result<()> {
+    let mut stream = TcpStream::connect(socket_addr(addr))
+        .with_context(|| format!("No broadcast at {}", addr))?;
+
+    enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    execute!(stdout, EnterAlternateScreen, Hide)?;
+
+    // Copy the stream to the screen in the background; the main thread
+    // only waits for the key that ends watching
+    let ended = Arc::new(AtomicBool::new(false));
+    let reader_ended = Arc::clone(&ended);
+    std::thread::spawn(move || {
+        let mut chunk = [0u8; 16 * 1024];
+        let mut out = std::io::stdout();
+        while let Ok(n) = stream.read(&mut chunk) {
+            if n == 0 || out.write_all(&chunk[..n]).and_then(|_| out.flush()).is_err() {
+                break;
+            }
+        }
+        reader_ended.store(true, Ordering::Relaxed);
+    });
+
+    let result = (|| -> Result<()> {
+        while !ended.load(Ordering::Relaxed) {
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                    if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Ok(())
+    })();
+
+    execute!(stdout, Show, LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+    if ended.load(Ordering::Relaxed) {
+        eprintln!("Broadcast at {} ended", addr);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    /// Read from a watcher until `expected` has arrived
+    fn read_until(stream: &mut TcpStream, expected: &str) -> String {
+        let mut received = Vec::new();
+        let mut chunk = [0u8; 4096];
+        while !String::from_utf8_lossy(&received).contains(expected) {
+            let n = stream.read(&mut chunk).expect("frame never arrived");
+            assert!(n > 0, "broadcast closed");
+            received.extend_from_slice(&chunk[..n]);
+        }
+        String::from_utf8_lossy(&received).into_owned()
+    }
+
+    #[test]
+    fn test_socket_addr() {
+        assert_eq!(socket_addr(":7777"), "127.0.0.1:7777");
+        assert_eq!(socket_addr("0.0.0.0:7777"), "0.0.0.0:7777");
+    }
+
+    #[test]
+    fn test_watchers_receive_frames() {
+        let broadcaster = Broadcaster::bind(":0").unwrap();
+        broadcaster.send(&Buffer::with_lines(["first frame"]));
+
+        // A late watcher is cleared and caught up with the last frame
+        let mut watcher = TcpStream::connect(broadcaster.addr()).unwrap();
+        watcher.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let caught_up = read_until(&mut watcher, "first frame");
+        assert!(caught_up.starts_with("\x1b[2J\x1b[H"), "{:?}", caught_up);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while broadcaster.watcher_count() == 0 {
+            assert!(Instant::now() < deadline, "watcher never registered");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // Unchanged frames aren't resent
+        broadcaster.send(&Buffer::with_lines(["first frame"]));
+        broadcaster.send(&Buffer::with_lines(["second frame"]));
+        let next = read_until(&mut watcher, "second frame");
+        assert!(!next.contains("first frame"), "{:?}", next);
+
+        // Watchers that hang up are dropped on the next frame
+        drop(watcher);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut n = 0;
+        while broadcaster.watcher_count() > 0 {
+            assert!(Instant::now() < deadline, "closed watcher never dropped");
+            n += 1;
+            broadcaster.send(&Buffer::with_lines([format!("frame {}", n)]));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Checklist screen: tick which items to keep before a command acts on them
+//! (`transcript prune-turns`)
+//!
+//! Drawn on stderr, so the command's output can still go to stdout.
+
+use std::io::{stderr, Stderr};
+
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+/// One row of the checklist
+#[derive(Debug, Clone)]
+pub struct ChecklistItem {
+    /// Row text
+    pub label: String,
+    /// Dimmed text after the label
+    pub detail: String,
+    /// Shown below the list while the row is selected
+    pub preview: String,
+    pub checked: bool,
+}
+
+/// Show the checklist until it is confirmed (Enter: true) or cancelled
+/// (q/Esc: false), leaving the choices in `items`
+pub fn run_checklist(title: &str, items: &mut [ChecklistItem]) -> Result<bool> {
+    enable_raw_mode()?;
+    let mut err = stderr();
+    execute!(err, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(err))?;
+
+    let result = checklist_loop(&mut terminal, title, items);
+
+    // Restore the terminal whatever happened
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    result
+}
+
+fn checklist_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stderr>>,
+    title: &str,
+    items: &mut [ChecklistItem],
+) -> Result<bool> {
+    let mut selected = 0;
+    loop {
+        terminal.draw(|frame| render(frame, title, items, selected))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if let Some(confirmed) = handle_key(key, items, &mut selected) {
+            return Ok(confirmed);
+        }
+    }
+}
+
+/// Apply a key press; `Some` ends the checklist (true: confirmed)
+fn handle_key(key: KeyEvent, items: &mut [ChecklistItem], selected: &mut usize) -> Option<bool> {
+    let last = items.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Some(false),
+        KeyCode::Char('q') | KeyCode::Esc => return Some(false),
+        KeyCode::Enter => return Some(true),
+        KeyCode::Char('j') | KeyCode::Down => *selected = (*selected + 1).min(last),
+        KeyCode::Char('k') | KeyCode::Up => *selected = selected.saturating_sub(1),
+        KeyCode::Char('g') | KeyCode::Home => *selected = 0,
+        KeyCode::Char('G') | KeyCode::End => *selected = last,
+        KeyCode::PageDown => *selected = (*selected + 10).min(last),
+        KeyCode::PageUp => *selected = selected.saturating_sub(10),
+        // Toggling moves on, so a run of rows is unticked key by key
+        KeyCode::Char(' ') | KeyCode::Char('x') => {
+            if let Some(item) = items.get_mut(*selected) {
+                item.checked = !item.checked;
+                *selected = (*selected + 1).min(last);
+            }
+        }
+        KeyCode::Char('a') => items.iter_mut().for_each(|i| i.checked = true),
+        KeyCode::Char('n') => items.iter_mut().for_each(|i| i.checked = false),
+        KeyCode::Char('i') => items.iter_mut().for_each(|i| i.checked = !i.checked),
+        _ => {}
+    }
+    None
+}
+
+fn render(frame: &mut Frame, title: &str, items: &[ChecklistItem], selected: usize) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),     // List
+            Constraint::Length(8),  // Preview
+            Constraint::Length(1),  // Keys
+        ])
+        .split(frame.area());
+
+    // Keep the selection in view
+    let rows = chunks[0].height.saturating_sub(2).max(1) as usize;
+    let offset = selected.saturating_sub(rows - 1);
+    let lines: Vec<Line> = items
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(rows)
+        .map(|(i, item)| {
+            let style = if i == selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            let (mark, color) = if item.checked {
+                ("[x] ", Color::Green)
+            } else {
+                ("[ ] ", Color::DarkGray)
+            };
+            Line::from(vec![
+                Span::styled(mark, style.fg(color)),
+                Span::styled(item.label.clone(), if item.checked { style } else { style.fg(Color::DarkGray) }),
+                Span::styled(format!("  {}", item.detail), style.fg(Color::DarkGray)),
+            ])
+        })
+        .collect();
+    let kept = items.iter().filter(|i| i.checked).count();
+    let list = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(" {} ({} of {} kept) ", title, kept, items.len()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    frame.render_widget(list, chunks[0]);
+
+    let preview = items.get(selected).map(|i| i.preview.as_str()).unwrap_or_default();
+    let preview = Paragraph::new(preview.to_string())
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray)));
+    frame.render_widget(preview, chunks[1]);
+
+    let keys = Paragraph::new("Space: keep/drop  a/n/i: all/none/invert  Enter: export  q: cancel")
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(keys, chunks[2]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(items: &mut [ChecklistItem], selected: &mut usize, code: KeyCode) -> Option<bool> {
+        handle_key(KeyEvent::new(code, KeyModifiers::NONE), items, selected)
+    }
+
+    fn checked(items: &[ChecklistItem]) -> Vec<bool> {
+        items.iter().map(|i| i.checked).collect()
+    }
+
+    #[test]
+    fn test_toggle_turns() {
+        let mut items: Vec<ChecklistItem> = (1..=4)
+            .map(|n| ChecklistItem {
+                label: format!("#{}", n),
+                detail: String::new(),
+                preview: String::new(),
+                checked: true,
+            })
+            .collect();
+        let mut selected = 0;
+
+        // Space unticks and moves on, so two presses drop two turns
+        assert_eq!(press(&mut items, &mut selected, KeyCode::Down), None);
+        press(&mut items, &mut selected, KeyCode::Char(' '));
+        press(&mut items, &mut selected, KeyCode::Char('x'));
+        assert_eq!(checked(&items), [true, false, false, true]);
+        assert_eq!(selected, 3);
+
+        // Toggling the last row stays on it
+        press(&mut items, &mut selected, KeyCode::Char(' '));
+        assert_eq!(selected, 3);
+        press(&mut items, &mut selected, KeyCode::Char('i'));
+        assert_eq!(checked(&items), [false, true, true, true]);
+        press(&mut items, &mut selected, KeyCode::Char('n'));
+        assert_eq!(checked(&items), [false; 4]);
+
+        assert_eq!(press(&mut items, &mut selected, KeyCode::Enter), Some(true));
+        assert_eq!(press(&mut items, &mut selected, KeyCode::Esc), Some(false));
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(handle_key(ctrl_c, &mut items, &mut selected), Some(false));
+    }
+}
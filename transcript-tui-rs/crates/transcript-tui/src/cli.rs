@@ -16,17 +16,27 @@ pub struct Cli {
     /// - A session ID (UUID)
     /// - A direct path to a .jsonl file
     /// - Comma-separated list for multi-session view
-    #[arg(value_name = "SESSION", required_unless_present_any = ["stats", "list"])]
+    ///
+    /// Without one, or when several sessions match, opens the session picker.
+    #[arg(value_name = "SESSION")]
     pub session: Option<String>,
 
     /// Start in live mode (watch for new lines)
     #[arg(short, long)]
     pub live: bool,
 
-    /// Initial view mode (1=json, 2=custom)
-    #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=2))]
+    /// Initial view mode (1=json, 2=custom, 3=thread)
+    #[arg(short, long, default_value = "2", value_parser = clap::value_parser!(u8).range(1..=3))]
     pub mode: u8,
 
+    /// Start with one list row per turn instead of per line
+    #[arg(long)]
+    pub turns: bool,
+
+    /// Start with the hook/transcript timeline pane open
+    #[arg(long)]
+    pub timeline: bool,
+
     /// Jump to specific line number
     #[arg(short = 'n', long)]
     pub line: Option<i64>,
@@ -35,14 +45,24 @@ pub struct Cli {
     #[arg(short, long, value_delimiter = ',')]
     pub types: Option<Vec<String>>,
 
-    /// Show only text content (no metadata)
+    /// Show only the conversation: user and assistant lines, without noise
     #[arg(short = 'o', long)]
     pub text_only: bool,
 
+    /// Accessibility profile: textual markers instead of color, simple borders
+    /// (also `"a11y": true` in ~/.claude-code-sdk/config.json)
+    #[arg(long)]
+    pub a11y: bool,
+
     /// Custom database path
     #[arg(long, env = "TRANSCRIPT_DB_PATH")]
     pub db_path: Option<std::path::PathBuf>,
 
+    /// Database profile from ~/.claude-code-sdk/config.json, not a separate
+    /// config.toml (`--db-path` wins)
+    #[arg(long, env = "TRANSCRIPT_PROFILE")]
+    pub profile: Option<String>,
+
     /// Show database statistics and exit
     #[arg(long)]
     pub stats: bool,
@@ -51,6 +71,19 @@ pub struct Cli {
     #[arg(long)]
     pub list: bool,
 
+    /// With --list, only show sessions with this review status
+    #[arg(long, requires = "list", value_parser = ["unreviewed", "in-review", "approved", "flagged"])]
+    pub review: Option<String>,
+
+    /// Serve this viewer's screen for others to watch with --attach
+    /// (`:7777` is local only; `0.0.0.0:7777` shares on the network)
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["screenshot", "stats", "list"])]
+    pub broadcast: Option<String>,
+
+    /// Watch a --broadcast viewer read-only (`host:7777`, or `:7777` on this machine)
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["broadcast", "screenshot", "stats", "list"])]
+    pub attach: Option<String>,
+
     /// Screenshot mode: render one frame and exit (for comparison testing)
     #[arg(long)]
     pub screenshot: bool,
@@ -69,6 +102,7 @@ impl Cli {
     pub fn view_mode(&self) -> transcript_core::ViewMode {
         match self.mode {
             1 => transcript_core::ViewMode::Json,
+            3 => transcript_core::ViewMode::Thread,
             _ => transcript_core::ViewMode::Custom,
         }
     }
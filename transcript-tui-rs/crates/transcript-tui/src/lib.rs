@@ -0,0 +1,881 @@
+//! transcript-tui-rs - High-performance transcript viewer for Claude Code
+//!
+//! This is an experimental Rust rewrite of the TypeScript transcript-tui.
+//! Also runs as `transcript tui`.
+
+mod broadcast;
+pub mod checklist;
+pub mod cli;
+
+use std::io::stdout;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{self, Event},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    buffer::Buffer,
+    prelude::*,
+};
+
+use broadcast::Broadcaster;
+pub use checklist::{run_checklist, ChecklistItem};
+pub use cli::Cli;
+use transcript_core::{Config, FilterOptions, HookEventFilter, ReviewStatus, ViewMode};
+use transcript_db::{archive_db_path, DbError, GetLinesOptions, TranscriptDb};
+use transcript_indexer::{connection::IndexerError, IndexerDb};
+use transcript_ui::{
+    components::{
+        render_annotation_input, render_content_pane, render_footer, render_header,
+        render_help_overlay, render_json_query_input, render_list_pane, render_search_input,
+        render_session_picker, render_timeline_pane, render_usage_graph,
+    },
+    event::{handle_event, AppAction, InputMode},
+    App, BulkAction, BulkPrompt, MacroStep, SessionPicker,
+};
+
+/// Run the viewer with parsed arguments
+pub fn run(cli: Cli) -> Result<()> {
+    // Mirror another viewer's broadcast
+    if let Some(addr) = &cli.attach {
+        return broadcast::attach(addr);
+    }
+
+    // Handle stats command
+    if cli.stats {
+        return show_stats(&cli);
+    }
+
+    // Handle list command
+    if cli.list {
+        return list_sessions(&cli);
+    }
+
+    // Open database
+    let db = open_database(&cli)?;
+
+    // Resolve the session; without one, or when it's ambiguous, start at
+    // the session picker
+    let session = match &cli.session {
+        Some(session) => load_session(&db, session)?,
+        None => None,
+    };
+    let mut app = match session {
+        Some((session_id, lines)) => {
+            if lines.is_empty() {
+                eprintln!("No transcript lines found for: {}", cli.session.as_deref().unwrap_or_default());
+                return Ok(());
+            }
+            open_session(&db, session_id, lines)?
+        }
+        None => {
+            let mut app = App::new(Vec::new(), String::new());
+            let query = cli.session.as_deref().unwrap_or_default();
+            app.picker = Some(SessionPicker::load(&db, query).context("Failed to list sessions")?);
+            app
+        }
+    };
+
+    app.set_view_mode(cli.view_mode());
+    app.live_mode = cli.live;
+    app.a11y = cli.a11y || Config::load().a11y;
+    app.show_timeline = cli.timeline;
+
+    // Apply type filter if specified; text-only also drops noise lines
+    let mut filter = if cli.text_only {
+        FilterOptions::text_only()
+    } else {
+        FilterOptions::new()
+    };
+    if let Some(types) = &cli.types {
+        filter = filter.with_display_types(types.clone());
+    }
+    if filter.is_active() {
+        app.apply_filter(filter);
+    }
+
+    // Jump to line if specified
+    if let Some(line_num) = cli.line {
+        if let Some(idx) = app.lines.iter().position(|l| l.line_number == line_num) {
+            app.current_index = idx;
+            app.reveal_current();
+        }
+    }
+
+    if cli.turns {
+        app.toggle_list_mode();
+        app.status_message = None;
+    }
+
+    // Screenshot mode: render one frame and exit
+    if cli.screenshot {
+        return run_screenshot(&app, cli.width, cli.height);
+    }
+
+    // Listen before the terminal is taken over, so a busy port is reported plainly
+    let broadcaster = cli.broadcast.as_deref().map(Broadcaster::bind).transpose()?;
+
+    // Run TUI
+    run_tui(app, db, broadcaster)
+}
+
+/// Open the database
+fn open_database(cli: &Cli) -> Result<TranscriptDb> {
+    let db = if let Some(path) = transcript_core::resolve_db_path(cli.db_path.clone(), cli.profile.as_deref())? {
+        TranscriptDb::open(path)
+    } else {
+        TranscriptDb::open_default()
+    };
+
+    match db {
+        Ok(db) => Ok(db),
+        Err(DbError::NotFound(path)) => {
+            eprintln!("Database not found at: {}", path.display());
+            eprintln!("Run: transcript index build");
+            std::process::exit(1);
+        }
+        Err(DbError::NotInitialized) => {
+            eprintln!("Database not initialized. Run: transcript index build");
+            std::process::exit(1);
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Load session lines from database (None: no session, or several, match)
+fn load_session(db: &TranscriptDb, session_input: &str) -> Result<Option<(String, Vec<transcript_core::TranscriptLine>)>> {
+    // Try to resolve session, else a partial match
+    let session = match db
+        .resolve_session(session_input)
+        .context("Failed to resolve session")?
+    {
+        Some(info) => info,
+        None => {
+            let mut sessions = db
+                .find_sessions(session_input)
+                .context("Failed to search sessions")?;
+            if sessions.is_empty() {
+                // A typo still opens the session it's closest to
+                sessions = db
+                    .closest_sessions(session_input)
+                    .context("Failed to search sessions")?;
+            }
+            if sessions.len() != 1 {
+                return Ok(None);
+            }
+            sessions.remove(0)
+        }
+    };
+
+    let options = GetLinesOptions::for_session(&session.session_id);
+    let lines = db.get_lines(&options).context("Failed to load lines")?;
+    Ok(Some((session.session_id, lines)))
+}
+
+/// App state for a session, with its saved bookmarks, annotations, review
+/// status and hook events
+fn open_session(db: &TranscriptDb, session_id: String, lines: Vec<transcript_core::TranscriptLine>) -> Result<App> {
+    let mut app = App::new(lines, session_id);
+    app.bookmarks = db
+        .get_bookmarked_lines(&app.session_id)?
+        .iter()
+        .map(|l| l.id)
+        .collect();
+    app.set_annotations(db.get_annotations(&app.session_id)?);
+    app.saved_searches = db.get_saved_searches()?;
+    app.review = db.get_review(&app.session_id)?.status;
+    app.hook_events = db.get_hook_events(&HookEventFilter {
+        session_id: Some(app.session_id.clone()),
+        ..Default::default()
+    })?;
+    Ok(app)
+}
+
+/// Replace the open session with another, keeping the view settings
+fn switch_session(app: &mut App, db: &TranscriptDb, session_id: &str) -> Result<()> {
+    let lines = db.get_lines(&GetLinesOptions::for_session(session_id))?;
+    let mut next = open_session(db, session_id.to_string(), lines)?;
+    next.set_view_mode(app.view_mode);
+    next.a11y = app.a11y;
+    next.live_mode = app.live_mode;
+    next.show_timeline = app.show_timeline;
+    next.macros = std::mem::take(&mut app.macros);
+    if app.filter_opts.is_active() {
+        next.apply_filter(app.filter_opts.clone());
+    }
+    if next.list_mode != app.list_mode {
+        next.toggle_list_mode();
+    }
+    *app = next;
+    Ok(())
+}
+
+/// Show database statistics
+fn show_stats(cli: &Cli) -> Result<()> {
+    let db = open_database(cli)?;
+    let stats = db.stats().context("Failed to get stats")?;
+
+    println!("Transcript Database Statistics");
+    println!("==============================");
+    println!("Version:      {}", stats.version);
+    println!("Lines:        {}", stats.line_count);
+    println!("Sessions:     {}", stats.session_count);
+    println!("Hook Events:  {}", stats.hook_event_count);
+    println!("Size:         {}", stats.format_size());
+    println!("Path:         {}", stats.db_path.display());
+    if let Some(indexed) = &stats.last_indexed {
+        println!("Last Indexed: {}", indexed);
+    }
+
+    Ok(())
+}
+
+/// List recent sessions
+fn list_sessions(cli: &Cli) -> Result<()> {
+    let db = open_database(cli)?;
+    let sessions = db.get_sessions(Some(30)).context("Failed to get sessions")?;
+    let reviews = db.get_review_statuses().context("Failed to get review statuses")?;
+    let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+    let projects = db.get_session_projects(&ids).context("Failed to get projects")?;
+    let wanted = cli.review.as_deref().and_then(ReviewStatus::from_name);
+
+    println!("Recent Sessions (last 30 days)");
+    println!("==============================");
+
+    for s in &sessions {
+        let review = reviews.get(&s.session_id).copied().unwrap_or_default();
+        if wanted.is_some_and(|w| w != review) {
+            continue;
+        }
+        let name = s.slug.as_deref().or(s.session_id.get(..8)).unwrap_or(&s.session_id);
+        let last = s.last_timestamp.as_deref().unwrap_or("unknown");
+        let project = projects.get(&s.session_id).map_or("-", String::as_str);
+        if review == ReviewStatus::Unreviewed {
+            println!("{:20} {:16} {:6} lines  {}", name, project, s.line_count, last);
+        } else {
+            println!("{:20} {:16} {:6} lines  {}  [{}]", name, project, s.line_count, last, review.name());
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the TUI application
+fn run_tui(mut app: App, db: TranscriptDb, broadcaster: Option<Broadcaster>) -> Result<()> {
+    // Bookmarks and review status are saved through the indexer; opened before the terminal is
+    // taken over, since a schema migration reports on stderr. Without a
+    // writable database they last for this run only.
+    let bookmark_db = IndexerDb::open_or_create(db.path()).ok();
+
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // Live mode polling interval
+    let poll_interval = Duration::from_millis(200);
+    let mut last_poll = std::time::Instant::now();
+
+    // Watchers, as last shown in the status line
+    let mut watching = None;
+
+    // Main event loop
+    loop {
+        if let Some(broadcaster) = &broadcaster {
+            let count = broadcaster.watcher_count();
+            if watching != Some(count) {
+                watching = Some(count);
+                app.status_message = Some(format!("Broadcasting on {} ({} watching)", broadcaster.addr(), count));
+            }
+        }
+
+        // Pick up content rendered in the background, then draw
+        app.render_cache.poll();
+        let frame = terminal.draw(|frame| ui(frame, &app))?;
+        if let Some(broadcaster) = &broadcaster {
+            broadcaster.send(frame.buffer);
+        }
+
+        // Poll for events with timeout for live mode
+        let timeout = if app.live_mode {
+            Duration::from_millis(100)
+        } else {
+            Duration::from_millis(250)
+        };
+
+        if event::poll(timeout)? {
+            // Macro commands are consumed; a replay expands into its recorded keys
+            let events: Vec<Event> = match event::read()? {
+                Event::Key(key) => {
+                    let text_input = app.input_mode() != InputMode::Normal;
+                    match app.macros.handle_key(key, text_input) {
+                        MacroStep::Consumed => Vec::new(),
+                        MacroStep::Pass(key) => vec![Event::Key(key)],
+                        MacroStep::Replay(keys) => keys.into_iter().map(Event::Key).collect(),
+                    }
+                }
+                other => vec![other],
+            };
+
+            let mut quit = false;
+            for event in events {
+                let action = handle_event(event, app.input_mode(), app.fullscreen);
+                if apply_action(&mut app, action, &mut terminal, &db, bookmark_db.as_ref())? {
+                    quit = true;
+                    break;
+                }
+            }
+            if quit {
+                break;
+            }
+        }
+
+        // Live mode: poll for new lines (once a session is open)
+        if app.live_mode && !app.session_id.is_empty() && last_poll.elapsed() >= poll_interval {
+            last_poll = std::time::Instant::now();
+            if let Err(e) = app.poll_new_lines(&db) {
+                app.error_message = Some(format!("Poll error: {}", e));
+            }
+        }
+
+        // Clear status message after a while
+        if app.status_message.is_some() {
+            // We'll let the message stay until next action
+        }
+    }
+
+    // Cleanup
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+
+    Ok(())
+}
+
+/// Apply one action to the app, returning true when the app should quit
+fn apply_action<B: Backend>(
+    app: &mut App,
+    action: AppAction,
+    terminal: &mut Terminal<B>,
+    db: &TranscriptDb,
+    bookmark_db: Option<&IndexerDb>,
+) -> Result<bool> {
+    // An open bulk prompt takes the picker's keys
+    if app.picker.as_ref().is_some_and(|p| p.bulk.is_some()) && action != AppAction::Redraw {
+        bulk_prompt_key(app, action, db, bookmark_db);
+        return Ok(false);
+    }
+
+    match action {
+        AppAction::Quit => return Ok(true),
+        AppAction::SelectPrev => app.select_prev(),
+        AppAction::SelectNext => app.select_next(),
+        AppAction::SelectFirst => app.select_first(),
+        AppAction::SelectLast => app.select_last(),
+        AppAction::PageUp => app.page_up(10),
+        AppAction::PageDown => app.page_down(10),
+        AppAction::TogglePane => app.toggle_pane(),
+        AppAction::SetViewMode(n) => {
+            if let Some(mode) = ViewMode::from_key((b'0' + n) as char) {
+                app.set_view_mode(mode);
+            }
+        }
+        AppAction::ToggleFullscreen => app.toggle_fullscreen(),
+        AppAction::ScrollUp => app.scroll_content_up(),
+        AppAction::ScrollDown => app.scroll_content_down(),
+        AppAction::ScrollLeft => app.scroll_content_left(),
+        AppAction::ScrollRight => app.scroll_content_right(),
+        AppAction::ToggleBookmark => {
+            app.toggle_bookmark();
+            if let Some(db) = bookmark_db {
+                save_bookmark(app, db);
+            }
+        }
+        AppAction::NextBookmark => app.next_bookmark(),
+        AppAction::PrevBookmark => app.prev_bookmark(),
+        AppAction::CycleReview => {
+            app.cycle_review();
+            if let Some(db) = bookmark_db {
+                save_review(app, db);
+            }
+        }
+        AppAction::StartSearch => app.start_search(),
+        AppAction::CancelSearch => app.cancel_search(),
+        AppAction::SubmitSearch => app.execute_search(),
+        AppAction::SearchInput(c) => app.search_query.push(c),
+        AppAction::SearchBackspace => {
+            app.search_query.pop();
+        }
+        AppAction::SearchHistory(older) => app.recall_search_history(older),
+        AppAction::SavedSearch(forward) => app.cycle_saved_search(forward),
+        AppAction::NextSearchResult => app.next_search_result(),
+        AppAction::PrevSearchResult => app.prev_search_result(),
+        AppAction::StartJsonQuery => app.start_json_query(),
+        AppAction::CancelJsonQuery => app.cancel_json_query(),
+        AppAction::SubmitJsonQuery => app.execute_json_query(),
+        AppAction::JsonQueryInput(c) => app.json_query_input.push(c),
+        AppAction::JsonQueryBackspace => {
+            app.json_query_input.pop();
+        }
+        AppAction::ClearJsonQuery => app.clear_json_query(),
+        AppAction::CopyJsonQuery => copy_json_query(app),
+        AppAction::StartAnnotation => app.start_annotation(),
+        AppAction::CancelAnnotation => app.cancel_annotation(),
+        AppAction::SubmitAnnotation => {
+            if let Some(text) = app.take_annotation() {
+                save_annotation(app, &text, db, bookmark_db);
+            }
+        }
+        AppAction::AnnotationInput(c) => app.annotation_input.push(c),
+        AppAction::AnnotationBackspace => {
+            app.annotation_input.pop();
+        }
+        AppAction::ToggleHelp => app.show_help = !app.show_help,
+        AppAction::ToggleUsageGraph => app.show_usage_graph = !app.show_usage_graph,
+        AppAction::ToggleTimeline => app.show_timeline = !app.show_timeline,
+        AppAction::ToggleLiveMode => app.toggle_live_mode(),
+        AppAction::ExpandContent => app.expand_content(),
+        AppAction::ToggleBranch => app.toggle_branch(),
+        AppAction::ToggleAllBranches => app.toggle_all_branches(),
+        AppAction::ToggleListMode => app.toggle_list_mode(),
+        AppAction::ExpandTurn => app.expand_turn(),
+        AppAction::OpenPicker => match SessionPicker::load(db, "") {
+            Ok(mut picker) => {
+                picker.select_session(&app.session_id);
+                app.picker = Some(picker);
+            }
+            Err(e) => app.error_message = Some(format!("Sessions not listed: {}", e)),
+        },
+        AppAction::ClosePicker => {
+            if app.session_id.is_empty() {
+                return Ok(true);
+            }
+            app.picker = None;
+        }
+        AppAction::PickSession => {
+            let picked = app.picker.as_ref().and_then(|p| p.selected_entry()).cloned();
+            if let Some(entry) = picked {
+                if entry.session_id != app.session_id {
+                    match switch_session(app, db, &entry.session_id) {
+                        Ok(()) => app.status_message = Some(format!("Opened {}", entry.name)),
+                        Err(e) => app.error_message = Some(format!("Session not opened: {}", e)),
+                    }
+                }
+                app.picker = None;
+            }
+        }
+        AppAction::PickerInput(c) => app.picker.iter_mut().for_each(|p| p.push(c)),
+        AppAction::PickerBackspace => app.picker.iter_mut().for_each(|p| p.pop()),
+        AppAction::PickerPrev => app.picker.iter_mut().for_each(|p| p.select_prev()),
+        AppAction::PickerNext => app.picker.iter_mut().for_each(|p| p.select_next()),
+        AppAction::PickerToggleMark => app.picker.iter_mut().for_each(|p| p.toggle_mark()),
+        AppAction::PickerMarkAll => app.picker.iter_mut().for_each(|p| p.toggle_mark_all()),
+        AppAction::PickerBulk => {
+            if let Some(picker) = app.picker.as_mut() {
+                if picker.marked.is_empty() {
+                    app.status_message = Some("Mark sessions with Space first".to_string());
+                } else {
+                    picker.bulk = Some(BulkPrompt::Menu);
+                }
+            }
+        }
+        AppAction::Redraw => {
+            terminal.clear()?;
+        }
+        AppAction::None => {}
+    }
+    Ok(false)
+}
+
+/// A key while the picker's bulk prompt is open
+fn bulk_prompt_key(app: &mut App, action: AppAction, db: &TranscriptDb, index_db: Option<&IndexerDb>) {
+    let Some(prompt) = app.picker.as_mut().and_then(|p| p.bulk.take()) else {
+        return;
+    };
+    let (next, run) = match (prompt, action) {
+        (_, AppAction::ClosePicker) => (None, None),
+        (BulkPrompt::Menu, AppAction::PickerInput(c)) => match BulkAction::from_key(c) {
+            Some(BulkAction::Tag) => (Some(BulkPrompt::Tag(String::new())), None),
+            Some(bulk) if bulk.needs_confirm() => (Some(BulkPrompt::Confirm(bulk)), None),
+            Some(bulk) => (None, Some((bulk, String::new()))),
+            None => (Some(BulkPrompt::Menu), None),
+        },
+        (BulkPrompt::Tag(mut tag), AppAction::PickerInput(c)) => {
+            tag.push(c);
+            (Some(BulkPrompt::Tag(tag)), None)
+        }
+        (BulkPrompt::Tag(mut tag), AppAction::PickerBackspace) => {
+            tag.pop();
+            (Some(BulkPrompt::Tag(tag)), None)
+        }
+        (BulkPrompt::Tag(tag), AppAction::PickSession) if !tag.trim().is_empty() => {
+            (None, Some((BulkAction::Tag, tag.trim().to_string())))
+        }
+        (BulkPrompt::Confirm(bulk), AppAction::PickerInput('y')) => (None, Some((bulk, String::new()))),
+        // Any other key cancels a confirmation and closes the stats
+        (BulkPrompt::Confirm(_) | BulkPrompt::Stats(_), _) => (None, None),
+        (prompt, _) => (Some(prompt), None),
+    };
+    if let Some(picker) = app.picker.as_mut() {
+        picker.bulk = next;
+    }
+    if let Some((bulk, tag)) = run {
+        run_bulk_action(app, bulk, &tag, db, index_db);
+    }
+}
+
+/// Apply a bulk action to the picker's marked sessions
+fn run_bulk_action(app: &mut App, bulk: BulkAction, tag: &str, db: &TranscriptDb, index_db: Option<&IndexerDb>) {
+    let Some(picker) = app.picker.as_mut() else {
+        return;
+    };
+    if bulk == BulkAction::Stats {
+        match picker.combined_stats(db) {
+            Ok(stats) => picker.bulk = Some(BulkPrompt::Stats(stats)),
+            Err(e) => app.error_message = Some(format!("Stats not computed: {}", e)),
+        }
+        return;
+    }
+    let Some(index_db) = index_db else {
+        app.error_message = Some("The database can't be written to".to_string());
+        return;
+    };
+
+    match change_sessions(index_db, db, bulk, tag, &picker.marked_ids()) {
+        Ok(message) => {
+            app.status_message = Some(message);
+            if let Err(e) = picker.reload(db) {
+                app.error_message = Some(format!("Sessions not listed: {}", e));
+            }
+        }
+        Err(e) => app.error_message = Some(format!("{} failed: {}", bulk.label(), e)),
+    }
+}
+
+/// Tag, archive, export or delete sessions, describing what was done
+fn change_sessions(
+    index_db: &IndexerDb,
+    db: &TranscriptDb,
+    bulk: BulkAction,
+    tag: &str,
+    session_ids: &[String],
+) -> Result<String, IndexerError> {
+    let conn = index_db.connection();
+    Ok(match bulk {
+        BulkAction::Tag => {
+            let mut added = 0;
+            for session_id in session_ids {
+                if transcript_indexer::add_tag(conn, session_id, tag)? {
+                    added += 1;
+                }
+            }
+            format!("Tagged {} sessions '{}'", added, tag)
+        }
+        BulkAction::Archive => {
+            let archive_path = archive_db_path(db.path());
+            let candidates = transcript_indexer::archive_candidates(conn, session_ids)?;
+            let moved = transcript_indexer::archive_sessions(conn, &archive_path, &candidates)?;
+            format!("Archived {} sessions to {}", moved.sessions, archive_path.display())
+        }
+        BulkAction::Export => {
+            let dir = transcript_indexer::default_exports_dir();
+            match transcript_indexer::export_sessions(conn, session_ids, &dir)? {
+                Some(bundle) => format!("Exported {} sessions to {}", bundle.sessions, bundle.path.display()),
+                None => "Nothing to export".to_string(),
+            }
+        }
+        BulkAction::Delete => {
+            let trash_dir = transcript_indexer::default_trash_dir();
+            let removed = transcript_indexer::delete_sessions(conn, session_ids, &trash_dir)?;
+            format!(
+                "Deleted {} sessions (transcript trash restore {} brings them back)",
+                removed.sessions,
+                removed.bundle.unwrap_or_default()
+            )
+        }
+        BulkAction::Stats => String::new(),
+    })
+}
+
+/// Persist the current line's bookmark state
+fn save_bookmark(app: &mut App, db: &IndexerDb) {
+    let Some(line) = app.current_line() else {
+        return;
+    };
+    let result = if app.bookmarks.contains(&line.id) {
+        transcript_indexer::add_bookmark(db.connection(), &line.session_id, &line.uuid, line.line_number)
+    } else {
+        transcript_indexer::remove_bookmark(db.connection(), &line.session_id, &line.uuid)
+    };
+    if let Err(e) = result {
+        app.error_message = Some(format!("Bookmark not saved: {}", e));
+    }
+}
+
+/// Save an annotation on the current line and reload the session's annotations
+fn save_annotation(app: &mut App, text: &str, db: &TranscriptDb, index_db: Option<&IndexerDb>) {
+    let Some(index_db) = index_db else {
+        app.error_message = Some("Annotation not saved: the database is read-only".to_string());
+        return;
+    };
+    let Some(line) = app.current_line() else {
+        return;
+    };
+    let author = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string());
+    let (session_id, uuid, line_number) = (line.session_id.clone(), line.uuid.clone(), line.line_number);
+    if let Err(e) =
+        transcript_indexer::add_annotation(index_db.connection(), &session_id, &uuid, line_number, &author, text, None)
+    {
+        app.error_message = Some(format!("Annotation not saved: {}", e));
+        return;
+    }
+    match db.get_annotations(&session_id) {
+        Ok(annotations) => app.set_annotations(annotations),
+        Err(e) => app.error_message = Some(format!("Annotations not reloaded: {}", e)),
+    }
+    app.status_message = Some(format!("Annotated line {}", line_number));
+}
+
+/// Persist the session's review status
+fn save_review(app: &mut App, db: &IndexerDb) {
+    let reviewer = std::env::var("USER").ok();
+    if let Err(e) =
+        transcript_indexer::set_review_status(db.connection(), &app.session_id, app.review, reviewer.as_deref())
+    {
+        app.error_message = Some(format!("Review status not saved: {}", e));
+    }
+}
+
+/// Copy the JSON query result (or raw JSON) for the current line to the clipboard
+fn copy_json_query(app: &mut App) {
+    let Some(text) = app.json_query_text() else {
+        app.status_message = Some("Nothing to copy".to_string());
+        return;
+    };
+
+    match arboard::Clipboard::new() {
+        Ok(mut clipboard) => {
+            if clipboard.set_text(&text).is_ok() {
+                app.status_message = Some(format!("Copied {} chars", text.chars().count()));
+            } else {
+                app.status_message = Some("Failed to copy".to_string());
+            }
+        }
+        Err(_) => {
+            app.status_message = Some("Clipboard unavailable".to_string());
+        }
+    }
+}
+
+/// Run screenshot mode: render one frame and output to stdout
+fn run_screenshot(app: &App, width: u16, height: u16) -> Result<()> {
+    use ratatui::backend::TestBackend;
+    use std::io::Write;
+
+    // Create a test backend with the specified size
+    let backend = TestBackend::new(width, height);
+    let mut terminal = Terminal::new(backend)?;
+
+    // Render one frame
+    terminal.draw(|frame| ui(frame, app))?;
+
+    // Get the buffer and convert to ANSI
+    let mut output = String::new();
+    for line in buffer_to_ansi(terminal.backend().buffer()) {
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    // Write to stdout
+    std::io::stdout().write_all(output.as_bytes())?;
+    std::io::stdout().flush()?;
+
+    Ok(())
+}
+
+/// The rows of a rendered buffer as text with ANSI styling, each ending in a reset
+fn buffer_to_ansi(buffer: &Buffer) -> Vec<String> {
+    let area = buffer.area;
+    let mut lines = Vec::with_capacity(area.height as usize);
+
+    for y in area.top()..area.bottom() {
+        // Output each cell with ANSI styling
+        let mut output = String::new();
+        let mut last_style: Option<Style> = None;
+
+        for x in area.left()..area.right() {
+            let cell = buffer.cell((x, y)).unwrap();
+            let style = cell.style();
+
+            // Only emit style codes when style changes
+            if last_style != Some(style) {
+                // Reset and apply new style
+                output.push_str("\x1b[0m");
+
+                // Foreground color
+                if let Some(fg) = style.fg {
+                    output.push_str(&color_to_ansi(fg, true));
+                }
+
+                // Background color
+                if let Some(bg) = style.bg {
+                    output.push_str(&color_to_ansi(bg, false));
+                }
+
+                // Modifiers
+                if style.add_modifier.contains(Modifier::BOLD) {
+                    output.push_str("\x1b[1m");
+                }
+                if style.add_modifier.contains(Modifier::DIM) {
+                    output.push_str("\x1b[2m");
+                }
+                if style.add_modifier.contains(Modifier::ITALIC) {
+                    output.push_str("\x1b[3m");
+                }
+                if style.add_modifier.contains(Modifier::UNDERLINED) {
+                    output.push_str("\x1b[4m");
+                }
+                if style.add_modifier.contains(Modifier::REVERSED) {
+                    output.push_str("\x1b[7m");
+                }
+
+                last_style = Some(style);
+            }
+
+            output.push_str(cell.symbol());
+        }
+        output.push_str("\x1b[0m");
+        lines.push(output);
+    }
+
+    lines
+}
+
+/// Convert ratatui Color to ANSI escape code
+fn color_to_ansi(color: Color, foreground: bool) -> String {
+    let base = if foreground { 30 } else { 40 };
+    match color {
+        Color::Black => format!("\x1b[{}m", base),
+        Color::Red => format!("\x1b[{}m", base + 1),
+        Color::Green => format!("\x1b[{}m", base + 2),
+        Color::Yellow => format!("\x1b[{}m", base + 3),
+        Color::Blue => format!("\x1b[{}m", base + 4),
+        Color::Magenta => format!("\x1b[{}m", base + 5),
+        Color::Cyan => format!("\x1b[{}m", base + 6),
+        Color::White => format!("\x1b[{}m", base + 7),
+        Color::Gray => format!("\x1b[{}m", if foreground { 90 } else { 100 }),
+        Color::DarkGray => format!("\x1b[{}m", if foreground { 90 } else { 100 }),
+        Color::LightRed => format!("\x1b[{}m", if foreground { 91 } else { 101 }),
+        Color::LightGreen => format!("\x1b[{}m", if foreground { 92 } else { 102 }),
+        Color::LightYellow => format!("\x1b[{}m", if foreground { 93 } else { 103 }),
+        Color::LightBlue => format!("\x1b[{}m", if foreground { 94 } else { 104 }),
+        Color::LightMagenta => format!("\x1b[{}m", if foreground { 95 } else { 105 }),
+        Color::LightCyan => format!("\x1b[{}m", if foreground { 96 } else { 106 }),
+        Color::Rgb(r, g, b) => {
+            if foreground {
+                format!("\x1b[38;2;{};{};{}m", r, g, b)
+            } else {
+                format!("\x1b[48;2;{};{};{}m", r, g, b)
+            }
+        }
+        Color::Indexed(i) => {
+            if foreground {
+                format!("\x1b[38;5;{}m", i)
+            } else {
+                format!("\x1b[48;5;{}m", i)
+            }
+        }
+        Color::Reset => "\x1b[0m".to_string(),
+    }
+}
+
+/// Render the UI
+fn ui(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+
+    if app.fullscreen {
+        // Fullscreen content view
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),  // Header
+                Constraint::Min(0),     // Content
+                Constraint::Length(2),  // Footer
+            ])
+            .split(area);
+
+        render_header(frame, chunks[0], app);
+        render_content_pane(frame, chunks[1], app);
+        render_footer(frame, chunks[2], app);
+    } else {
+        // Normal two-pane view
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),  // Header
+                Constraint::Min(0),     // Main content
+                Constraint::Length(2),  // Footer
+            ])
+            .split(area);
+
+        render_header(frame, chunks[0], app);
+
+        // The timeline pane takes the bottom of the main area
+        let main_area = if app.show_timeline {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage(65),  // List and content
+                    Constraint::Percentage(35),  // Timeline
+                ])
+                .split(chunks[1]);
+            render_timeline_pane(frame, rows[1], app);
+            rows[0]
+        } else {
+            chunks[1]
+        };
+
+        // Split main area into list and content panes
+        let main_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(40),  // List
+                Constraint::Percentage(60),  // Content
+            ])
+            .split(main_area);
+
+        render_list_pane(frame, main_chunks[0], app);
+        render_content_pane(frame, main_chunks[1], app);
+
+        render_footer(frame, chunks[2], app);
+    }
+
+    // Overlays
+    if app.search_mode {
+        render_search_input(frame, app);
+    }
+
+    if app.json_query_mode {
+        render_json_query_input(frame, app);
+    }
+
+    if app.annotate_mode {
+        render_annotation_input(frame, app);
+    }
+
+    if app.show_help {
+        render_help_overlay(frame);
+    }
+
+    if app.show_usage_graph {
+        render_usage_graph(frame, app);
+    }
+
+    render_session_picker(frame, app);
+}
@@ -1,8 +1,19 @@
 //! Application state and logic
 
-use std::collections::HashSet;
-use transcript_core::{FilterOptions, FocusedPane, TranscriptLine, ViewMode};
-use transcript_db::TranscriptDb;
+use std::collections::{HashMap, HashSet};
+use transcript_core::{
+    api_failure, locale, ApiFailureKind, FilterOptions, FocusedPane, HookEvent, JsonPath, ListMode, ReviewStatus, SearchHistory, TranscriptLine,
+    ViewMode,
+};
+use transcript_db::{Annotation, SavedSearch, TranscriptDb};
+
+use crate::event::InputMode;
+use crate::macros::Macros;
+use crate::picker::SessionPicker;
+use crate::render_cache::RenderCache;
+use crate::thread::{build_thread, Thread};
+use crate::turns::{files_changed, summarize_turns, turn_starts, TurnSummary};
+use crate::views;
 
 /// Application state
 pub struct App {
@@ -22,6 +33,20 @@ pub struct App {
     pub search_result_index: usize,
     /// Is search mode active
     pub search_mode: bool,
+    /// Previous queries, persisted across runs
+    pub search_history: SearchHistory,
+    /// Searches saved with `transcript search --save`, by name
+    pub saved_searches: Vec<SavedSearch>,
+    /// Saved search shown in the search input, if one was picked
+    pub saved_search_index: Option<usize>,
+    /// JSON path query being typed (e.g. `.message.usage`)
+    pub json_query_input: String,
+    /// Is JSON query input active
+    pub json_query_mode: bool,
+    /// Active JSON path query applied in the JSON view
+    pub json_query: Option<JsonPath>,
+    /// Expression of the active JSON query, as it was applied
+    pub json_query_expr: String,
     /// Session ID
     pub session_id: String,
     /// Session name (human-readable)
@@ -36,20 +61,56 @@ pub struct App {
     pub content_scroll_x: u16,
     /// Bookmarked line IDs
     pub bookmarks: HashSet<i64>,
+    /// Annotations by line uuid
+    pub annotations: HashMap<String, Vec<Annotation>>,
+    /// Annotation being written on the selected line
+    pub annotation_input: String,
+    /// Is annotation input active
+    pub annotate_mode: bool,
+    /// API failures by line ID, found once as lines load
+    pub api_failures: HashMap<i64, ApiFailureKind>,
+    /// Review status of the session
+    pub review: ReviewStatus,
     /// Show help overlay
     pub show_help: bool,
     /// Show usage graph overlay
     pub show_usage_graph: bool,
+    /// Hook events of the session, for the timeline pane
+    pub hook_events: Vec<HookEvent>,
+    /// Show the hook/transcript timeline pane
+    pub show_timeline: bool,
     /// Live mode (auto-refresh)
     pub live_mode: bool,
     /// Last max line ID for delta updates
     pub last_max_id: i64,
+    /// Last change feed ID seen, so polls skip unchanged sessions
+    pub last_change_id: i64,
     /// Filter options
     pub filter_opts: FilterOptions,
     /// Error message to display
     pub error_message: Option<String>,
     /// Status message
     pub status_message: Option<String>,
+    /// Keyboard macro registers
+    pub macros: Macros,
+    /// Rendering of the selected line's content
+    pub render_cache: RenderCache,
+    /// Chunks of a large line loaded so far, by line uuid
+    pub expanded_chunks: Option<(String, usize)>,
+    /// Accessibility profile (textual markers, simple borders)
+    pub a11y: bool,
+    /// Layout of `lines` for the thread view (empty in other modes)
+    pub thread: Thread,
+    /// Uuids of lines whose branches are collapsed in the thread view
+    pub collapsed_branches: HashSet<String>,
+    /// Whether the list shows lines or turns
+    pub list_mode: ListMode,
+    /// Turns of `lines` for the turns list (empty when listing lines)
+    pub turns: Vec<TurnSummary>,
+    /// Index of the first line of each turn in `lines`
+    pub turn_starts: Vec<usize>,
+    /// Session picker, while open
+    pub picker: Option<SessionPicker>,
 }
 
 impl App {
@@ -61,6 +122,8 @@ impl App {
         let last_max_id = lines.iter().map(|l| l.id).max().unwrap_or(0);
         // Start at last line (like TypeScript TUI)
         let initial_index = if lines.is_empty() { 0 } else { lines.len() - 1 };
+        let turn_starts = turn_starts(&lines);
+        let api_failures = find_api_failures(&lines);
 
         Self {
             all_lines: lines.clone(),
@@ -71,6 +134,13 @@ impl App {
             search_results: Vec::new(),
             search_result_index: 0,
             search_mode: false,
+            search_history: SearchHistory::load("tui"),
+            saved_searches: Vec::new(),
+            saved_search_index: None,
+            json_query_input: String::new(),
+            json_query_mode: false,
+            json_query: None,
+            json_query_expr: String::new(),
             session_id,
             session_name,
             focused_pane: FocusedPane::List,
@@ -78,23 +148,134 @@ impl App {
             content_scroll: 0,
             content_scroll_x: 0,
             bookmarks: HashSet::new(),
+            annotations: HashMap::new(),
+            annotation_input: String::new(),
+            annotate_mode: false,
+            api_failures,
+            review: ReviewStatus::default(),
             show_help: false,
             show_usage_graph: false,
+            hook_events: Vec::new(),
+            show_timeline: false,
             live_mode: false,
             last_max_id,
+            last_change_id: 0,
             filter_opts: FilterOptions::default(),
             error_message: None,
             status_message: None,
+            macros: Macros::default(),
+            render_cache: RenderCache::default(),
+            expanded_chunks: None,
+            a11y: false,
+            thread: Thread::default(),
+            collapsed_branches: HashSet::new(),
+            list_mode: ListMode::Lines,
+            turns: Vec::new(),
+            turn_starts,
+            picker: None,
         }
     }
 
+    /// What keys type into: the picker, else the search, query or annotation
+    /// being written
+    pub fn input_mode(&self) -> InputMode {
+        if self.picker.is_some() {
+            InputMode::Picker
+        } else if self.search_mode {
+            InputMode::Search
+        } else if self.json_query_mode {
+            InputMode::JsonQuery
+        } else if self.annotate_mode {
+            InputMode::Annotation
+        } else {
+            InputMode::Normal
+        }
+    }
+
+    /// Files the current line's turn has changed up to that line
+    pub fn turn_files(&self) -> Vec<String> {
+        if self.lines.is_empty() {
+            return Vec::new();
+        }
+        let turn = self.turn_starts.partition_point(|&start| start <= self.current_index);
+        let start = self.turn_starts.get(turn.saturating_sub(1)).copied().unwrap_or(0);
+        files_changed(&self.lines, &self.hook_events, start, self.current_index)
+    }
+
     /// Get the currently selected line
     pub fn current_line(&self) -> Option<&TranscriptLine> {
         self.lines.get(self.current_index)
     }
 
+    /// Chunks of the current line to show if it is large
+    pub fn content_chunks(&self) -> usize {
+        match (&self.expanded_chunks, self.current_line()) {
+            (Some((uuid, chunks)), Some(line)) if *uuid == line.uuid => *chunks,
+            _ => 1,
+        }
+    }
+
+    /// Load the next chunk of a large line
+    pub fn expand_content(&mut self) {
+        let Some(line) = self.current_line() else {
+            return;
+        };
+        if !views::large::is_large(line) {
+            self.status_message = Some("Line is already fully shown".to_string());
+            return;
+        }
+
+        let total = views::large::chunk_count(line);
+        let chunks = (self.content_chunks() + 1).min(total);
+        self.expanded_chunks = Some((line.uuid.clone(), chunks));
+        self.status_message = Some(format!("Showing chunk {}/{}", chunks, total));
+    }
+
+    /// Whether list rows aren't simply `lines` in order (turns list, thread view)
+    fn has_row_layout(&self) -> bool {
+        self.list_mode == ListMode::Turns || self.view_mode == ViewMode::Thread
+    }
+
+    /// Line selected by each list row
+    fn row_lines(&self) -> Vec<usize> {
+        if self.list_mode == ListMode::Turns {
+            self.turns.iter().map(|t| t.start).collect()
+        } else if self.view_mode == ViewMode::Thread {
+            self.thread.rows.iter().map(|r| r.index).collect()
+        } else {
+            (0..self.lines.len()).collect()
+        }
+    }
+
+    /// List row holding the selection
+    pub fn selected_row(&self) -> usize {
+        if self.list_mode == ListMode::Turns {
+            self.turns.iter().position(|t| t.contains(self.current_index)).unwrap_or(0)
+        } else if self.view_mode == ViewMode::Thread {
+            self.thread.row_of(self.current_index).unwrap_or(0)
+        } else {
+            self.current_index
+        }
+    }
+
+    /// Select the line of list row `row`
+    fn select_row(&mut self, row: usize) {
+        if let Some(&index) = self.row_lines().get(row) {
+            self.current_index = index;
+            self.content_scroll = 0;
+            self.content_scroll_x = 0;
+        }
+    }
+
     /// Move selection up
     pub fn select_prev(&mut self) {
+        if self.has_row_layout() {
+            let row = self.selected_row();
+            if row > 0 {
+                self.select_row(row - 1);
+            }
+            return;
+        }
         if self.current_index > 0 {
             self.current_index -= 1;
             self.content_scroll = 0;
@@ -104,6 +285,10 @@ impl App {
 
     /// Move selection down
     pub fn select_next(&mut self) {
+        if self.has_row_layout() {
+            self.select_row(self.selected_row() + 1);
+            return;
+        }
         if self.current_index + 1 < self.lines.len() {
             self.current_index += 1;
             self.content_scroll = 0;
@@ -113,6 +298,10 @@ impl App {
 
     /// Go to first line
     pub fn select_first(&mut self) {
+        if self.has_row_layout() {
+            self.select_row(0);
+            return;
+        }
         self.current_index = 0;
         self.content_scroll = 0;
         self.content_scroll_x = 0;
@@ -120,6 +309,10 @@ impl App {
 
     /// Go to last line
     pub fn select_last(&mut self) {
+        if self.has_row_layout() {
+            self.select_row(self.row_lines().len().saturating_sub(1));
+            return;
+        }
         if !self.lines.is_empty() {
             self.current_index = self.lines.len() - 1;
             self.content_scroll = 0;
@@ -129,12 +322,21 @@ impl App {
 
     /// Page up
     pub fn page_up(&mut self, page_size: usize) {
+        if self.has_row_layout() {
+            self.select_row(self.selected_row().saturating_sub(page_size));
+            return;
+        }
         self.current_index = self.current_index.saturating_sub(page_size);
         self.content_scroll = 0;
     }
 
     /// Page down
     pub fn page_down(&mut self, page_size: usize) {
+        if self.has_row_layout() {
+            let last = self.row_lines().len().saturating_sub(1);
+            self.select_row((self.selected_row() + page_size).min(last));
+            return;
+        }
         self.current_index = (self.current_index + page_size).min(self.lines.len().saturating_sub(1));
         self.content_scroll = 0;
     }
@@ -169,6 +371,137 @@ impl App {
         self.view_mode = mode;
         self.content_scroll = 0;
         self.content_scroll_x = 0;
+        self.rebuild_rows();
+        self.reveal_current();
+    }
+
+    /// Lay out the thread view and turns list again after `lines` or
+    /// collapsed branches change
+    pub fn rebuild_rows(&mut self) {
+        self.thread = if self.view_mode == ViewMode::Thread {
+            build_thread(&self.lines, &self.collapsed_branches)
+        } else {
+            Thread::default()
+        };
+        self.turns = if self.list_mode == ListMode::Turns {
+            summarize_turns(&self.lines)
+        } else {
+            Vec::new()
+        };
+        self.turn_starts = turn_starts(&self.lines);
+    }
+
+    /// Switch the list between lines and turns
+    pub fn toggle_list_mode(&mut self) {
+        self.list_mode = match self.list_mode {
+            ListMode::Lines => ListMode::Turns,
+            ListMode::Turns => ListMode::Lines,
+        };
+        self.rebuild_rows();
+        self.reveal_current();
+        self.status_message = Some(match self.list_mode {
+            ListMode::Lines => "Listing lines".to_string(),
+            ListMode::Turns => format!("Listing {} turns (Enter to open one)", self.turns.len()),
+        });
+    }
+
+    /// Open the selected turn in the lines list, at its first line
+    pub fn expand_turn(&mut self) {
+        if self.list_mode != ListMode::Turns {
+            return;
+        }
+        let Some(turn) = self.turns.get(self.selected_row()).cloned() else {
+            return;
+        };
+        self.list_mode = ListMode::Lines;
+        self.rebuild_rows();
+        self.current_index = turn.start;
+        self.content_scroll = 0;
+        self.content_scroll_x = 0;
+        self.reveal_current();
+        self.status_message = Some(format!(
+            "Turn {}: {} line(s) (t for turns)",
+            turn.sequence,
+            turn.end - turn.start
+        ));
+    }
+
+    /// Expand any collapsed branches hiding the selected line
+    pub fn reveal_current(&mut self) {
+        if self.view_mode != ViewMode::Thread || self.thread.row_of(self.current_index).is_some() {
+            return;
+        }
+        for i in self.thread.ancestors(self.current_index) {
+            if let Some(line) = self.lines.get(i) {
+                self.collapsed_branches.remove(&line.uuid);
+            }
+        }
+        self.rebuild_rows();
+    }
+
+    /// Collapse or expand the branches under the selected line
+    ///
+    /// Inside a branch, this collapses the branch's line and selects it.
+    pub fn toggle_branch(&mut self) {
+        if self.view_mode != ViewMode::Thread {
+            self.status_message = Some("Branches can be folded in thread view (3)".to_string());
+            return;
+        }
+        let Some(row) = self.thread.rows.get(self.thread.row_of(self.current_index).unwrap_or(0)).cloned() else {
+            return;
+        };
+
+        let owner = if row.branch_lines > 0 {
+            Some(row.index)
+        } else {
+            // The nearest line above that has this one in its branches
+            self.thread.ancestors(row.index).into_iter().find(|&i| {
+                self.thread
+                    .row_of(i)
+                    .is_some_and(|r| self.thread.rows[r].depth < row.depth)
+            })
+        };
+        let Some(owner) = owner else {
+            self.status_message = Some("No branches here".to_string());
+            return;
+        };
+
+        let uuid = self.lines[owner].uuid.clone();
+        if !self.collapsed_branches.remove(&uuid) {
+            self.collapsed_branches.insert(uuid);
+        }
+        self.current_index = owner;
+        self.content_scroll = 0;
+        self.rebuild_rows();
+    }
+
+    /// Collapse every branch, or expand them all when any is collapsed
+    pub fn toggle_all_branches(&mut self) {
+        if self.view_mode != ViewMode::Thread {
+            self.status_message = Some("Branches can be folded in thread view (3)".to_string());
+            return;
+        }
+        if self.collapsed_branches.is_empty() {
+            self.collapsed_branches = self
+                .thread
+                .rows
+                .iter()
+                .filter(|r| r.branch_lines > 0)
+                .map(|r| self.lines[r.index].uuid.clone())
+                .collect();
+            self.status_message = Some("Collapsed all branches".to_string());
+        } else {
+            self.collapsed_branches.clear();
+            self.status_message = Some("Expanded all branches".to_string());
+        }
+        self.rebuild_rows();
+        // Keep the selection on a visible line
+        while self.thread.row_of(self.current_index).is_none() {
+            match self.thread.parents.get(self.current_index).copied().flatten() {
+                Some(parent) => self.current_index = parent,
+                None => break,
+            }
+        }
     }
 
     /// Toggle fullscreen mode
@@ -188,6 +521,47 @@ impl App {
         }
     }
 
+    /// Move the session to the next review status
+    pub fn cycle_review(&mut self) {
+        self.review = self.review.next();
+        self.status_message = Some(format!("Review: {}", self.review.name()));
+    }
+
+    /// Replace the session's annotations (as loaded from the database)
+    pub fn set_annotations(&mut self, annotations: Vec<Annotation>) {
+        self.annotations.clear();
+        for annotation in annotations {
+            self.annotations.entry(annotation.uuid.clone()).or_default().push(annotation);
+        }
+    }
+
+    /// Annotations on a line, oldest first
+    pub fn annotations_for(&self, line: &TranscriptLine) -> &[Annotation] {
+        self.annotations.get(&line.uuid).map_or(&[], Vec::as_slice)
+    }
+
+    /// Start writing an annotation on the current line
+    pub fn start_annotation(&mut self) {
+        if self.current_line().is_some() {
+            self.annotate_mode = true;
+            self.annotation_input.clear();
+        }
+    }
+
+    /// Cancel the annotation being written
+    pub fn cancel_annotation(&mut self) {
+        self.annotate_mode = false;
+        self.annotation_input.clear();
+    }
+
+    /// Finish writing, returning the annotation's text unless it is blank
+    pub fn take_annotation(&mut self) -> Option<String> {
+        self.annotate_mode = false;
+        let text = std::mem::take(&mut self.annotation_input);
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
     /// Jump to next bookmark
     pub fn next_bookmark(&mut self) {
         if self.bookmarks.is_empty() {
@@ -199,6 +573,7 @@ impl App {
             if self.bookmarks.contains(&line.id) {
                 self.current_index = i;
                 self.content_scroll = 0;
+                self.reveal_current();
                 return;
             }
         }
@@ -208,6 +583,7 @@ impl App {
             if self.bookmarks.contains(&line.id) {
                 self.current_index = i;
                 self.content_scroll = 0;
+                self.reveal_current();
                 return;
             }
         }
@@ -225,6 +601,7 @@ impl App {
                 if self.bookmarks.contains(&line.id) {
                     self.current_index = i;
                     self.content_scroll = 0;
+                    self.reveal_current();
                     return;
                 }
             }
@@ -236,6 +613,7 @@ impl App {
                 if self.bookmarks.contains(&line.id) {
                     self.current_index = i;
                     self.content_scroll = 0;
+                    self.reveal_current();
                     return;
                 }
             }
@@ -263,21 +641,10 @@ impl App {
     pub fn apply_filter(&mut self, filter: FilterOptions) {
         self.filter_opts = filter.clone();
 
-        // Apply filter to all_lines
-        let filtered: Vec<TranscriptLine> = if filter.types.is_some()
-            || filter.display_types.is_some()
-            || filter.search.is_some()
-            || filter.session_ids.is_some()
-        {
-            let refs = transcript_core::filter_lines(&self.all_lines, &filter);
-            refs.into_iter().cloned().collect()
-        } else {
-            self.all_lines.clone()
-        };
-
-        self.lines = filtered;
+        self.lines = transcript_core::apply_filter(&self.all_lines, &filter);
         self.current_index = 0;
         self.content_scroll = 0;
+        self.rebuild_rows();
     }
 
     /// Clear filter
@@ -286,6 +653,7 @@ impl App {
         self.lines = self.all_lines.clone();
         self.current_index = 0;
         self.content_scroll = 0;
+        self.rebuild_rows();
     }
 
     /// Start search mode
@@ -293,6 +661,36 @@ impl App {
         self.search_mode = true;
         self.search_query.clear();
         self.search_results.clear();
+        self.search_history.reset_recall();
+        self.saved_search_index = None;
+    }
+
+    /// Replace the search input with the next (or previous) saved search
+    pub fn cycle_saved_search(&mut self, forward: bool) {
+        let count = self.saved_searches.len();
+        if count == 0 {
+            return;
+        }
+        let index = match (self.saved_search_index, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        };
+        self.saved_search_index = Some(index);
+        self.search_query = self.saved_searches[index].query.clone();
+    }
+
+    /// Replace the search input with an older (up) or newer (down) query
+    pub fn recall_search_history(&mut self, older: bool) {
+        let recalled = if older {
+            self.search_history.older()
+        } else {
+            self.search_history.newer()
+        };
+        if let Some(query) = recalled {
+            self.search_query = query.to_string();
+        }
     }
 
     /// Cancel search
@@ -309,6 +707,9 @@ impl App {
             return;
         }
 
+        // Saving is best effort: a read-only home shouldn't break searching
+        let _ = self.search_history.push(&self.search_query);
+
         let query_lower = self.search_query.to_lowercase();
         self.search_results = self
             .lines
@@ -331,6 +732,7 @@ impl App {
         if let Some(&idx) = self.search_results.first() {
             self.current_index = idx;
             self.content_scroll = 0;
+            self.reveal_current();
             self.status_message = Some(format!(
                 "Found {} matches",
                 self.search_results.len()
@@ -349,6 +751,7 @@ impl App {
         self.search_result_index = (self.search_result_index + 1) % self.search_results.len();
         self.current_index = self.search_results[self.search_result_index];
         self.content_scroll = 0;
+        self.reveal_current();
     }
 
     /// Previous search result
@@ -364,10 +767,77 @@ impl App {
         }
         self.current_index = self.search_results[self.search_result_index];
         self.content_scroll = 0;
+        self.reveal_current();
+    }
+
+    /// Start JSON query input, editing the active query if there is one
+    pub fn start_json_query(&mut self) {
+        self.json_query_mode = true;
+        self.json_query_input = self.json_query_expr.clone();
+    }
+
+    /// Cancel JSON query input (keeps any active query)
+    pub fn cancel_json_query(&mut self) {
+        self.json_query_mode = false;
+        self.json_query_input = self.json_query_expr.clone();
+    }
+
+    /// Parse and apply the typed JSON query; an empty query clears it
+    pub fn execute_json_query(&mut self) {
+        self.json_query_mode = false;
+
+        if self.json_query_input.trim().is_empty() {
+            self.clear_json_query();
+            return;
+        }
+
+        match JsonPath::parse(&self.json_query_input) {
+            Ok(path) => {
+                self.json_query = Some(path);
+                self.json_query_expr = self.json_query_input.trim().to_string();
+                self.error_message = None;
+                self.set_view_mode(ViewMode::Json);
+                self.status_message = Some(format!("Query: {}", self.json_query_expr));
+            }
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Clear the active JSON query
+    pub fn clear_json_query(&mut self) {
+        self.json_query = None;
+        self.json_query_expr.clear();
+        self.json_query_input.clear();
+        self.content_scroll = 0;
+        self.status_message = Some("Query cleared".to_string());
+    }
+
+    /// Text of the active JSON query result for the current line
+    ///
+    /// Falls back to the line's raw JSON when no query is active.
+    pub fn json_query_text(&self) -> Option<String> {
+        let line = self.current_line()?;
+        match &self.json_query {
+            Some(path) => {
+                let value: serde_json::Value = serde_json::from_str(&line.raw).ok()?;
+                path.select(&value).map(transcript_core::format_query_result)
+            }
+            None => Some(line.raw.clone()),
+        }
     }
 
     /// Poll for new lines (for live mode)
     pub fn poll_new_lines(&mut self, db: &TranscriptDb) -> Result<usize, transcript_db::DbError> {
+        // Hook events arrive on their own, between lines
+        let last_hook_id = self.hook_events.iter().map(|e| e.id).max().unwrap_or(0);
+        self.hook_events
+            .extend(db.get_hook_events_after_id(last_hook_id, Some(&self.session_id), None, None)?);
+
+        if !db.lines_changed(&mut self.last_change_id, Some(&self.session_id))? {
+            return Ok(0);
+        }
         let new_lines = db.get_lines_after_id(self.last_max_id, Some(&self.session_id))?;
 
         if new_lines.is_empty() {
@@ -383,22 +853,18 @@ impl App {
         }
 
         // Add new lines
+        self.api_failures.extend(find_api_failures(&new_lines));
         self.all_lines.extend(new_lines.iter().cloned());
 
-        // Re-apply filter
-        if self.filter_opts.types.is_some()
-            || self.filter_opts.display_types.is_some()
-            || self.filter_opts.search.is_some()
-        {
-            let refs = transcript_core::filter_lines(&self.all_lines, &self.filter_opts);
-            self.lines = refs.into_iter().cloned().collect();
-        } else {
-            self.lines.extend(new_lines);
-        }
+        // Filters are per-line, so only the new lines need filtering
+        self.lines
+            .extend(transcript_core::apply_filter(&new_lines, &self.filter_opts));
 
         // Auto-scroll to end if we were at end
+        self.rebuild_rows();
         if was_at_end && !self.lines.is_empty() {
             self.current_index = self.lines.len() - 1;
+            self.reveal_current();
         }
 
         self.status_message = Some(format!("{} new line(s)", count));
@@ -417,8 +883,13 @@ impl App {
 
     /// Get status line info
     pub fn status_info(&self) -> String {
+        let count = |n: usize| locale().format_count(n as i64);
+        let position = match self.list_mode {
+            ListMode::Lines => format!("Line {}/{}", count(self.current_index + 1), count(self.lines.len())),
+            ListMode::Turns => format!("Turn {}/{}", count(self.selected_row() + 1), count(self.turns.len())),
+        };
         let mut parts = vec![
-            format!("Line {}/{}", self.current_index + 1, self.lines.len()),
+            position,
             format!("Mode: {}", self.view_mode.name()),
         ];
 
@@ -426,10 +897,26 @@ impl App {
             parts.push("LIVE".to_string());
         }
 
+        if let Some(reg) = self.macros.recording() {
+            parts.push(format!("REC @{}", reg));
+        }
+
         if !self.bookmarks.is_empty() {
             parts.push(format!("{} bookmarks", self.bookmarks.len()));
         }
 
+        if !self.annotations.is_empty() {
+            parts.push(format!("{} annotated lines", self.annotations.len()));
+        }
+
+        if self.review != ReviewStatus::Unreviewed {
+            parts.push(format!("Review: {}", self.review.name()));
+        }
+
+        if self.json_query.is_some() {
+            parts.push(format!("Query {}", self.json_query_expr));
+        }
+
         if !self.search_results.is_empty() {
             parts.push(format!(
                 "Match {}/{}",
@@ -441,3 +928,95 @@ impl App {
         parts.join(" | ")
     }
 }
+
+/// Lines that record an API failure, by line ID
+fn find_api_failures(lines: &[TranscriptLine]) -> HashMap<i64, ApiFailureKind> {
+    lines
+        .iter()
+        .filter_map(|line| api_failure(line).map(|kind| (line.id, kind)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(id: i64, uuid: &str) -> TranscriptLine {
+        TranscriptLine {
+            id,
+            line_number: id,
+            uuid: uuid.to_string(),
+            ..TranscriptLine::for_test(transcript_core::LineType::User, "")
+        }
+    }
+
+    fn annotation(id: i64, uuid: &str, body: &str) -> Annotation {
+        Annotation {
+            id,
+            session_id: "s1".to_string(),
+            uuid: uuid.to_string(),
+            line_number: id,
+            parent_id: None,
+            author: "ann".to_string(),
+            body: body.to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_cancelled_json_query_keeps_applied_expression() {
+        let mut app = App::new(vec![line(1, "u1")], "s1".to_string());
+        app.start_json_query();
+        app.json_query_input.push_str(".message");
+        app.execute_json_query();
+        assert_eq!(app.json_query_expr, ".message");
+
+        // An edit that is cancelled leaves the applied query and its text alone
+        app.start_json_query();
+        assert_eq!(app.json_query_input, ".message");
+        app.json_query_input.push_str(".usage");
+        app.cancel_json_query();
+        assert_eq!(app.json_query, Some(JsonPath::parse(".message").unwrap()));
+        assert!(app.status_info().contains("Query .message"));
+        assert_eq!(app.json_query_input, ".message");
+
+        // A query that doesn't parse keeps the previous one
+        app.json_query_input = ".message.".to_string();
+        app.execute_json_query();
+        assert!(app.error_message.is_some());
+        assert_eq!(app.json_query_expr, ".message");
+    }
+
+    #[test]
+    fn test_annotation_input() {
+        let mut app = App::new(vec![line(1, "u1"), line(2, "u2")], "s1".to_string());
+
+        app.start_annotation();
+        assert_eq!(app.input_mode(), InputMode::Annotation);
+        app.annotation_input.push_str("  check this  ");
+        assert_eq!(app.take_annotation().as_deref(), Some("check this"));
+        assert_eq!(app.input_mode(), InputMode::Normal);
+        assert!(app.annotation_input.is_empty());
+
+        // Blank and cancelled notes are not saved
+        app.start_annotation();
+        app.annotation_input.push_str("   ");
+        assert_eq!(app.take_annotation(), None);
+        app.start_annotation();
+        app.annotation_input.push_str("draft");
+        app.cancel_annotation();
+        assert!(!app.annotate_mode);
+        assert!(app.annotation_input.is_empty());
+    }
+
+    #[test]
+    fn test_annotations_by_line() {
+        let mut app = App::new(vec![line(1, "u1"), line(2, "u2")], "s1".to_string());
+        app.set_annotations(vec![annotation(1, "u2", "first"), annotation(2, "u2", "second")]);
+
+        assert!(app.annotations_for(&app.lines[0]).is_empty());
+        let notes: Vec<&str> = app.annotations_for(&app.lines[1]).iter().map(|a| a.body.as_str()).collect();
+        assert_eq!(notes, vec!["first", "second"]);
+        assert!(app.status_info().contains("1 annotated lines"));
+    }
+}
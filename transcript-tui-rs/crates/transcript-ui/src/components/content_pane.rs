@@ -1,30 +1,58 @@
 //! Content pane component for displaying selected line content
 
+use std::path::Path;
+
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
+    widgets::{Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
 };
 
+use transcript_core::{TranscriptLine, ViewMode};
+use transcript_db::annotation_threads;
+
+use super::pane_block;
 use crate::{views, App};
 
 /// Render the content pane
 pub fn render_content_pane(frame: &mut Frame, area: Rect, app: &App) {
     let is_focused = app.focused_pane == transcript_core::FocusedPane::Content || app.fullscreen;
 
-    let block = Block::default()
-        .title(format!(" Content ({}) ", app.view_mode.name()))
-        .borders(Borders::ALL)
-        .border_style(if is_focused {
-            Style::default().fg(Color::Cyan)
-        } else {
-            Style::default().fg(Color::DarkGray)
-        });
+    let query = match (&app.json_query, app.view_mode) {
+        (Some(path), ViewMode::Json) => Some(path),
+        _ => None,
+    };
+
+    let title = if query.is_some() {
+        format!(" Content ({}) {} ", app.view_mode.name(), app.json_query_expr)
+    } else {
+        format!(" Content ({}) ", app.view_mode.name())
+    };
+
+    let block = pane_block(title, is_focused, app.a11y);
 
     let inner = block.inner(area);
 
-    // Get rendered content for current line
+    // Get rendered content for current line (large lines render in the background)
     let content = if let Some(line) = app.current_line() {
-        views::render_content(line, app.view_mode, inner.width as usize)
+        let mut content = turn_files_lines(app, line);
+        content.extend(annotation_lines(app, line));
+        content.extend(match query {
+            Some(path) => views::sanitize_lines(views::raw::render_query(line, path)),
+            None => match app.render_cache.get(
+                line,
+                app.view_mode,
+                inner.width as usize,
+                app.content_chunks(),
+                app.a11y,
+            ) {
+                Some(rendered) => rendered.to_vec(),
+                None => vec![Line::from(Span::styled(
+                    format!("Rendering {} KB...", line.raw.len() / 1024),
+                    Style::default().fg(Color::DarkGray),
+                ))],
+            },
+        });
+        content
     } else {
         vec![Line::from("No line selected")]
     };
@@ -59,3 +87,65 @@ pub fn render_content_pane(frame: &mut Frame, area: Rect, app: &App) {
         );
     }
 }
+
+/// Files the turn has changed so far, so a long turn's reach stays in view
+fn turn_files_lines(app: &App, line: &TranscriptLine) -> Vec<Line<'static>> {
+    let files = app.turn_files();
+    if files.is_empty() {
+        return Vec::new();
+    }
+
+    let cwd = line.cwd.as_deref().map(Path::new);
+    let names: Vec<String> = files
+        .iter()
+        .map(|file| {
+            let path = Path::new(file);
+            cwd.and_then(|cwd| path.strip_prefix(cwd).ok())
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        })
+        .collect();
+    let heading = if app.a11y { "[FILES]" } else { "Δ Turn changed" };
+    views::sanitize_lines(vec![
+        Line::from(vec![
+            Span::styled(
+                format!("{} {} file{}: ", heading, files.len(), if files.len() == 1 { "" } else { "s" }),
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(names.join(", "), Style::default().fg(Color::Cyan)),
+        ]),
+        Line::from(""),
+    ])
+}
+
+/// The line's annotation threads, shown above its content
+fn annotation_lines(app: &App, line: &TranscriptLine) -> Vec<Line<'static>> {
+    let annotations = app.annotations_for(line);
+    if annotations.is_empty() {
+        return Vec::new();
+    }
+
+    let accent = Style::default().fg(Color::Magenta);
+    let heading = if app.a11y { "[NOTES]" } else { "✎ Annotations" };
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{} ({})", heading, annotations.len()),
+        accent.add_modifier(Modifier::BOLD),
+    ))];
+    for (depth, annotation) in annotation_threads(annotations) {
+        let indent = "  ".repeat(depth + 1);
+        lines.push(Line::from(vec![
+            Span::raw(indent.clone()),
+            Span::styled(format!("#{} {}", annotation.id, annotation.author), accent),
+            Span::styled(
+                format!(" {}", annotation.created_at.get(..16).unwrap_or_default().replace('T', " ")),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+        for text in annotation.body.lines() {
+            lines.push(Line::from(format!("{}  {}", indent, text)));
+        }
+    }
+    lines.push(Line::from(""));
+    views::sanitize_lines(lines)
+}
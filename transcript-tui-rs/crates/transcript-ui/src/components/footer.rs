@@ -13,12 +13,18 @@ pub fn render_footer(frame: &mut Frame, area: Rect, app: &App) {
 
     let help_hint = "?: help  q: quit";
 
+    let (error_label, live_label) = if app.a11y {
+        ("[ERR]", if app.live_mode { "[LIVE] " } else { "" })
+    } else {
+        ("ERROR:", "")
+    };
+
     let message = if let Some(msg) = &app.status_message {
-        format!("{} │ {} │ {}", status, msg, help_hint)
+        format!("{}{} │ {} │ {}", live_label, status, msg, help_hint)
     } else if let Some(err) = &app.error_message {
-        format!("{} │ ERROR: {} │ {}", status, err, help_hint)
+        format!("{}{} │ {} {} │ {}", live_label, status, error_label, err, help_hint)
     } else {
-        format!("{} │ {}", status, help_hint)
+        format!("{}{} │ {}", live_label, status, help_hint)
     };
 
     let style = if app.error_message.is_some() {
@@ -11,10 +11,11 @@ use crate::App;
 pub fn render_header(frame: &mut Frame, area: Rect, app: &App) {
     let title = app.title();
 
-    let view_modes = ["1:JSON", "2:CUSTOM"];
+    let view_modes = ["1:JSON", "2:CUSTOM", "3:THREAD"];
     let current_mode = match app.view_mode {
         transcript_core::ViewMode::Json => 0,
         transcript_core::ViewMode::Custom => 1,
+        transcript_core::ViewMode::Thread => 2,
     };
 
     let modes_display: String = view_modes
@@ -2,58 +2,189 @@
 
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{List, ListItem, ListState},
 };
-use transcript_core::{LineType, TranscriptLine};
+use transcript_core::{ApiFailureKind, ListMode, LineType, TranscriptLine, ViewMode};
 
-use crate::App;
+use super::pane_block;
+use crate::turns::{format_duration, format_tokens};
+use crate::{App, ThreadRow, TurnSummary};
 
 /// Render the list pane
 pub fn render_list_pane(frame: &mut Frame, area: Rect, app: &App) {
     let is_focused = app.focused_pane == transcript_core::FocusedPane::List;
 
-    let block = Block::default()
-        .title(" Lines ")
-        .borders(Borders::ALL)
-        .border_style(if is_focused {
-            Style::default().fg(Color::Cyan)
-        } else {
-            Style::default().fg(Color::DarkGray)
-        });
+    let title = match app.list_mode {
+        ListMode::Lines => " Lines ",
+        ListMode::Turns => " Turns ",
+    };
+    let block = pane_block(title.to_string(), is_focused, app.a11y);
 
     let inner = block.inner(area);
 
-    // Generate list items
-    let items: Vec<ListItem> = app
-        .lines
-        .iter()
-        .enumerate()
-        .map(|(i, line)| {
-            let is_selected = i == app.current_index;
-            let is_bookmarked = app.bookmarks.contains(&line.id);
-            let is_search_match = app.search_results.contains(&i);
-            format_list_item(line, is_selected, is_bookmarked, is_search_match, inner.width as usize)
-        })
-        .collect();
+    let items = match app.list_mode {
+        ListMode::Lines => line_items(app, inner.width as usize),
+        ListMode::Turns => app
+            .turns
+            .iter()
+            .map(|turn| format_turn_item(app, turn, inner.width as usize))
+            .collect(),
+    };
 
-    let list = List::new(items)
+    let mut list = List::new(items)
         .block(block)
         .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    if app.a11y {
+        list = list.highlight_symbol("> ");
+    }
 
     // Create list state for selection
     let mut state = ListState::default();
-    state.select(Some(app.current_index));
+    state.select(Some(app.selected_row()));
 
     frame.render_stateful_widget(list, area, &mut state);
 }
 
+/// One item per line, or per visible line of the thread view
+fn line_items(app: &App, width: usize) -> Vec<ListItem<'static>> {
+    // Line indices in display order, with their place in the thread view
+    let rows: Vec<(usize, Option<&ThreadRow>)> = if app.view_mode == ViewMode::Thread {
+        app.thread.rows.iter().map(|r| (r.index, Some(r))).collect()
+    } else {
+        (0..app.lines.len()).map(|i| (i, None)).collect()
+    };
+
+    rows.iter()
+        .map(|&(i, row)| {
+            let line = &app.lines[i];
+            let is_selected = i == app.current_index;
+            let is_bookmarked = app.bookmarks.contains(&line.id);
+            let notes = app.annotations_for(line).len();
+            let is_search_match = app.search_results.contains(&i);
+            let tree = row.map(|r| tree_prefix(r, app.a11y)).unwrap_or_default();
+            let failure = app.api_failures.get(&line.id).copied();
+            if app.a11y {
+                format_accessible_item(line, &tree, is_bookmarked, notes, is_search_match, failure, width)
+            } else {
+                format_list_item(line, &tree, is_selected, is_bookmarked, notes > 0, is_search_match, failure, width)
+            }
+        })
+        .collect()
+}
+
+/// Format a turn row: [bookmark][annotated] #seq prompt ... tools tokens duration
+fn format_turn_item(app: &App, turn: &TurnSummary, width: usize) -> ListItem<'static> {
+    let lines = &app.lines[turn.start..turn.end];
+    let is_bookmarked = lines.iter().any(|l| app.bookmarks.contains(&l.id));
+    let notes: usize = lines.iter().map(|l| app.annotations_for(l).len()).sum();
+    let is_search_match = app.search_results.iter().any(|&i| turn.contains(i));
+    let duration = turn.duration.map(format_duration).unwrap_or_else(|| "-".to_string());
+    let tokens = format_tokens(turn.tokens);
+
+    if app.a11y {
+        let mut text = String::new();
+        if is_bookmarked {
+            text.push_str("[★] ");
+        }
+        if notes > 0 {
+            text.push_str(&format!("[NOTES {}] ", notes));
+        }
+        if is_search_match {
+            text.push_str("[MATCH] ");
+        }
+        text.push_str(&format!("[TURN {}] ", turn.sequence));
+        let stats = format!(" {} tools, {} tokens, {}", turn.tool_calls, tokens, duration);
+        // Room for the "> " highlight symbol and a gap before the content pane
+        let preview_width = width.saturating_sub(text.chars().count() + stats.chars().count() + 3);
+        text.push_str(&clip(&turn.prompt, preview_width));
+        text.push_str(&stats);
+        return ListItem::new(Line::from(text));
+    }
+
+    let sequence = format!("#{:<3} ", turn.sequence);
+    let stats = format!(" ⚒{:<3}{:>6} {:>6}", turn.tool_calls, tokens, duration);
+    let preview_width = width.saturating_sub(3 + sequence.chars().count() + stats.chars().count());
+    let preview = clip(&turn.prompt, preview_width);
+    let padding = " ".repeat(preview_width.saturating_sub(preview.chars().count()));
+
+    let spans = vec![
+        Span::styled(
+            if is_bookmarked { "★" } else { " " },
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled(
+            if notes > 0 { "✎ " } else { "  " },
+            Style::default().fg(Color::Magenta),
+        ),
+        Span::styled(sequence, Style::default().fg(Color::Cyan)),
+        Span::styled(
+            preview + &padding,
+            if is_search_match {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            },
+        ),
+        Span::styled(stats, Style::default().fg(Color::DarkGray)),
+    ];
+    ListItem::new(Line::from(spans))
+}
+
+/// First `max` characters of a one-line preview, with control characters blanked
+fn clip(text: &str, max: usize) -> String {
+    let chars: Vec<char> = text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    if chars.len() > max {
+        format!("{}...", chars[..max.saturating_sub(3)].iter().collect::<String>())
+    } else {
+        chars.into_iter().collect()
+    }
+}
+
+/// Indentation and branch markers for a thread view row
+fn tree_prefix(row: &ThreadRow, a11y: bool) -> String {
+    let mut prefix = "  ".repeat(row.depth);
+    if a11y {
+        if row.sidechain {
+            prefix.push_str("[SUBAGENT] ");
+        }
+        if row.collapsed {
+            prefix.push_str(&format!("[FOLDED +{}] ", row.branch_lines));
+        } else if row.branch_lines > 0 {
+            prefix.push_str("[BRANCHES] ");
+        }
+    } else {
+        if row.collapsed {
+            prefix.push_str(&format!("▸ +{} ", row.branch_lines));
+        } else if row.branch_lines > 0 {
+            prefix.push_str("▾ ");
+        }
+        if row.sidechain {
+            prefix.push_str("↳ ");
+        }
+    }
+    prefix
+}
+
+/// Red badge text for a line recording an API failure
+fn failure_badge(kind: ApiFailureKind) -> &'static str {
+    match kind {
+        ApiFailureKind::ApiError => " API ERR ",
+        ApiFailureKind::RateLimit => " RATE LIMIT ",
+        ApiFailureKind::Overloaded => " OVERLOADED ",
+    }
+}
+
 /// Format a single list item to match TypeScript TUI screenshot format:
-/// [bookmark]  [time:8] [type:3] [preview]
+/// [bookmark][annotated] [time:8] [type:3] [failure badge] [preview]
+#[allow(clippy::too_many_arguments)]
 fn format_list_item(
     line: &TranscriptLine,
+    tree: &str,
     is_selected: bool,
     is_bookmarked: bool,
+    is_annotated: bool,
     _is_search_match: bool,
+    failure: Option<ApiFailureKind>,
     width: usize,
 ) -> ListItem<'static> {
     // Type abbreviation (3 chars to match TypeScript screenshot)
@@ -82,8 +213,10 @@ fn format_list_item(
     let time = line.format_time();
 
     // Calculate preview width
-    let prefix_len = 3 + 8 + 1 + 3 + 1; // "★  HH:MM:SS USR "
-    let preview_width = width.saturating_sub(prefix_len);
+    let prefix_len = 3 + 8 + 1 + 3 + 1; // "★✎ HH:MM:SS USR "
+    let badge = failure.map(failure_badge);
+    let badge_len = badge.map_or(0, |b| b.chars().count() + 1);
+    let preview_width = width.saturating_sub(prefix_len + tree.chars().count() + badge_len);
     let preview = line.preview(preview_width);
 
     let style = if is_selected {
@@ -94,19 +227,113 @@ fn format_list_item(
         Style::default()
     };
 
-    let spans = vec![
+    let mut spans = vec![
         Span::styled(
-            format!("{}  ", bookmark_char),
+            bookmark_char,
             if is_bookmarked {
                 Style::default().fg(Color::Yellow)
             } else {
                 style
             },
         ),
+        Span::styled(
+            if is_annotated { "✎ " } else { "  " },
+            if is_annotated {
+                Style::default().fg(Color::Magenta)
+            } else {
+                style
+            },
+        ),
         Span::styled(format!("{} ", time), style.fg(Color::DarkGray)),
         Span::styled(format!("{} ", type_str), style.fg(type_color)),
-        Span::styled(preview, style),
+        Span::styled(tree.to_string(), style.fg(Color::Cyan)),
     ];
+    if let Some(badge) = badge {
+        spans.push(Span::styled(
+            badge,
+            Style::default().bg(Color::Red).fg(Color::White).add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(" ", style));
+    }
+    spans.push(Span::styled(preview, style));
 
     ListItem::new(Line::from(spans))
 }
+
+/// Format a list item for the accessibility profile:
+/// [markers] [TYPE] [time] [preview], with every signal spelled out
+fn format_accessible_item(
+    line: &TranscriptLine,
+    tree: &str,
+    is_bookmarked: bool,
+    notes: usize,
+    is_search_match: bool,
+    failure: Option<ApiFailureKind>,
+    width: usize,
+) -> ListItem<'static> {
+    let type_str = match line.line_type {
+        LineType::User => "[USER]",
+        LineType::Assistant => "[ASSISTANT]",
+        LineType::System => "[SYSTEM]",
+        LineType::Summary => "[SUMMARY]",
+        LineType::Progress => "[PROGRESS]",
+        LineType::FileHistorySnapshot => "[SNAPSHOT]",
+        LineType::Unknown => "[UNKNOWN]",
+    };
+
+    let mut text = String::new();
+    if is_bookmarked {
+        text.push_str("[★] ");
+    }
+    if notes > 0 {
+        text.push_str(&format!("[NOTES {}] ", notes));
+    }
+    if is_search_match {
+        text.push_str("[MATCH] ");
+    }
+    if let Some(kind) = failure {
+        text.push_str(&format!("[{}] ", failure_badge(kind).trim()));
+    }
+    text.push_str(type_str);
+    text.push(' ');
+    text.push_str(&line.format_time());
+    text.push(' ');
+    text.push_str(tree);
+
+    // Room for the "> " highlight symbol and a gap before the content pane
+    let preview_width = width.saturating_sub(text.chars().count() + 3);
+    text.push_str(&line.preview(preview_width));
+
+    ListItem::new(Line::from(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item_text(item: ListItem) -> String {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 60, 1));
+        Widget::render(List::new(vec![item]), buffer.area, &mut buffer);
+        buffer.content.iter().map(|c| c.symbol()).collect()
+    }
+
+    #[test]
+    fn test_annotated_lines_are_marked() {
+        let line = TranscriptLine {
+            id: 1,
+            line_number: 1,
+            uuid: "u1".to_string(),
+            timestamp: "2024-01-01T10:00:00Z".to_string(),
+            content: Some("hello".to_string()),
+            ..TranscriptLine::for_test(LineType::User, "")
+        };
+
+        let marked = item_text(format_list_item(&line, "", false, true, true, false, None, 60));
+        assert!(marked.starts_with("★✎ "), "{}", marked);
+        let plain = item_text(format_list_item(&line, "", false, false, false, false, None, 60));
+        assert!(!plain.contains('✎'), "{}", plain);
+
+        let accessible = item_text(format_accessible_item(&line, "", false, 2, false, None, 60));
+        assert!(accessible.starts_with("[NOTES 2] [USER]"), "{}", accessible);
+    }
+}
@@ -6,6 +6,7 @@ pub mod list_pane;
 pub mod content_pane;
 pub mod search;
 pub mod overlays;
+pub mod timeline_pane;
 
 pub use header::*;
 pub use footer::*;
@@ -13,3 +14,32 @@ pub use list_pane::*;
 pub use content_pane::*;
 pub use search::*;
 pub use overlays::*;
+pub use timeline_pane::*;
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders},
+};
+
+/// Bordered pane block, highlighted when focused
+///
+/// With `a11y` only the title rule is drawn and focus is spelled out, since
+/// it would otherwise be shown by border color alone.
+pub(crate) fn pane_block(title: String, focused: bool, a11y: bool) -> Block<'static> {
+    if a11y {
+        let title = if focused {
+            format!(" {}[FOCUS] ", title.trim_start())
+        } else {
+            title
+        };
+        return Block::default().title(title).borders(Borders::TOP);
+    }
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(if focused {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        })
+}
@@ -1,11 +1,18 @@
-//! Overlay components (help, usage graph)
+//! Overlay components (help, usage graph, session picker)
 
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
-use crate::{event::HELP_TEXT, App};
+use transcript_core::usage_series;
+
+use crate::{
+    event::HELP_TEXT,
+    picker::{BulkAction, BulkPrompt, BulkStats},
+    turns::format_tokens,
+    App,
+};
 
 /// Render the help overlay
 pub fn render_help_overlay(frame: &mut Frame) {
@@ -32,18 +39,7 @@ pub fn render_usage_graph(frame: &mut Frame, app: &App) {
     // Clear background
     frame.render_widget(Clear, area);
 
-    // Collect usage data
-    let mut usage_data: Vec<(String, f64)> = Vec::new();
-    let context_size = 200_000u64; // 200K tokens
-
-    for line in &app.lines {
-        if let Some(usage) = line.usage() {
-            let total = usage.total();
-            let percent = (total as f64 / context_size as f64) * 100.0;
-            let time = line.format_time();
-            usage_data.push((time, percent));
-        }
-    }
+    let usage_data = usage_series(&app.lines, None);
 
     // Create a simple text-based visualization
     let mut lines: Vec<Line> = vec![
@@ -60,29 +56,38 @@ pub fn render_usage_graph(frame: &mut Frame, app: &App) {
         // Find max for scaling
         let max_percent = usage_data
             .iter()
-            .map(|(_, p)| *p)
+            .map(|p| p.percent)
             .fold(0.0f64, f64::max)
             .max(1.0);
 
         let bar_width = (area.width as usize).saturating_sub(20);
 
-        for (time, percent) in usage_data.iter().take(30) {
+        for point in usage_data.iter().take(30) {
             // Limit to 30 entries
+            if point.after_compaction {
+                lines.push(Line::from(Span::styled(
+                    "──────── compacted ────────",
+                    Style::default().fg(Color::Magenta),
+                )));
+            }
+
+            let (time, percent) = (&point.time, &point.percent);
             let bar_len = ((percent / max_percent) * bar_width as f64) as usize;
             let bar = "█".repeat(bar_len);
 
-            let color = if *percent < 50.0 {
-                Color::Green
+            let (color, level) = if *percent < 50.0 {
+                (Color::Green, "")
             } else if *percent < 70.0 {
-                Color::Yellow
+                (Color::Yellow, " [WARN]")
             } else {
-                Color::Red
+                (Color::Red, " [HIGH]")
             };
 
             lines.push(Line::from(vec![
                 Span::styled(format!("{} ", time), Style::default().fg(Color::DarkGray)),
                 Span::styled(bar, Style::default().fg(color)),
                 Span::styled(format!(" {:.1}%", percent), Style::default().fg(color)),
+                Span::raw(if app.a11y { level } else { "" }),
             ]));
         }
 
@@ -90,7 +95,8 @@ pub fn render_usage_graph(frame: &mut Frame, app: &App) {
         lines.push(Line::from(vec![
             Span::styled("Green: <50% ", Style::default().fg(Color::Green)),
             Span::styled("Yellow: 50-70% ", Style::default().fg(Color::Yellow)),
-            Span::styled("Red: >70%", Style::default().fg(Color::Red)),
+            Span::styled("Red: >70% ", Style::default().fg(Color::Red)),
+            Span::styled("── compaction", Style::default().fg(Color::Magenta)),
         ]));
     }
 
@@ -106,6 +112,152 @@ pub fn render_usage_graph(frame: &mut Frame, app: &App) {
     frame.render_widget(paragraph, area);
 }
 
+/// Render the session picker: over the viewer, or filling the screen when
+/// no session is open yet
+pub fn render_session_picker(frame: &mut Frame, app: &App) {
+    let Some(picker) = &app.picker else {
+        return;
+    };
+    let area = if app.session_id.is_empty() {
+        frame.area()
+    } else {
+        centered_rect(80, 80, frame.area())
+    };
+
+    // Clear background
+    frame.render_widget(Clear, area);
+
+    let mut lines: Vec<Line> = vec![Line::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Cyan)),
+        Span::raw(picker.query.clone()),
+        Span::styled("_", Style::default().fg(Color::DarkGray)),
+    ])];
+    if let Some(prompt) = &picker.bulk {
+        lines.extend(bulk_prompt_lines(prompt, picker.marked.len()));
+    }
+    lines.push(Line::from(""));
+
+    // Keep the selection in view
+    let rows = (area.height as usize).saturating_sub(lines.len() + 2).max(1);
+    let offset = picker.selected.saturating_sub(rows - 1);
+    let name_width = picker.entries.iter().map(|e| e.name.chars().count()).max().unwrap_or(0).min(32);
+    let project_width = picker
+        .entries
+        .iter()
+        .map(|e| e.project.as_deref().map_or(1, |p| p.chars().count()))
+        .max()
+        .unwrap_or(0)
+        .min(20);
+
+    if picker.matches.is_empty() {
+        lines.push(Line::from(Span::styled("  No matching sessions", Style::default().fg(Color::DarkGray))));
+    }
+    for (pos, &i) in picker.matches.iter().enumerate().skip(offset).take(rows) {
+        let entry = &picker.entries[i];
+        let selected = pos == picker.selected;
+        let marked = picker.marked.contains(&entry.session_id);
+        let last = entry.last_timestamp.as_deref().unwrap_or("unknown");
+        let marker = format!(
+            "{}{}",
+            if selected && app.a11y { ">" } else { " " },
+            if marked { "*" } else { " " }
+        );
+        let style = if selected {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::REVERSED)
+        } else if marked {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}{:<name_width$}", marker, entry.name), style.bold()),
+            Span::styled(
+                format!("  {:<project_width$}", entry.project.as_deref().unwrap_or("-")),
+                style.fg(if selected { Color::Cyan } else { Color::Yellow }),
+            ),
+            Span::styled(format!("  {:>6} lines", entry.line_count), style),
+            Span::styled(
+                format!("  {}", last.get(..16).unwrap_or(last).replace('T', " ")),
+                style.fg(if selected { Color::Cyan } else { Color::DarkGray }),
+            ),
+        ]));
+    }
+
+    let marked = if picker.marked.is_empty() {
+        String::new()
+    } else {
+        format!(", {} marked", picker.marked.len())
+    };
+    let title = format!(
+        " Sessions ({}/{}{}) - type to filter, Enter to open, Space to mark, Tab for actions, Esc to {} ",
+        picker.matches.len(),
+        picker.entries.len(),
+        marked,
+        if app.session_id.is_empty() { "quit" } else { "close" }
+    );
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+
+    frame.render_widget(paragraph, area);
+}
+
+/// The picker's bulk action prompt, under its query
+fn bulk_prompt_lines(prompt: &BulkPrompt, marked: usize) -> Vec<Line<'static>> {
+    let accent = Style::default().fg(Color::Magenta);
+    let dim = Style::default().fg(Color::DarkGray);
+    match prompt {
+        BulkPrompt::Menu => {
+            let mut spans = vec![Span::styled(format!("{} marked: ", marked), accent.bold())];
+            for action in BulkAction::ALL {
+                spans.push(Span::styled(format!("[{}] ", action.key()), accent));
+                spans.push(Span::raw(format!("{}  ", action.label())));
+            }
+            spans.push(Span::styled("Esc to cancel", dim));
+            vec![Line::from(spans)]
+        }
+        BulkPrompt::Tag(tag) => vec![Line::from(vec![
+            Span::styled(format!("Tag {} sessions: ", marked), accent.bold()),
+            Span::raw(tag.clone()),
+            Span::styled("_  Enter to add, Esc to cancel", dim),
+        ])],
+        BulkPrompt::Confirm(action) => vec![Line::from(vec![
+            Span::styled(format!("{} {} sessions? ", action.label(), marked), accent.bold()),
+            Span::styled("y to confirm, any other key to cancel", dim),
+        ])],
+        BulkPrompt::Stats(stats) => bulk_stats_lines(stats, accent, dim),
+    }
+}
+
+fn bulk_stats_lines(stats: &BulkStats, accent: Style, dim: Style) -> Vec<Line<'static>> {
+    let date = |ts: &Option<String>| {
+        ts.as_deref()
+            .map_or("unknown".to_string(), |t| t.get(..10).unwrap_or(t).to_string())
+    };
+    let tokens = |n: i64| format_tokens(n.max(0) as u64);
+    vec![
+        Line::from(Span::styled(format!("{} sessions combined", stats.sessions), accent.bold())),
+        Line::from(format!(
+            "  {} lines, {} assistant messages, last active {} to {}",
+            stats.lines,
+            stats.messages,
+            date(&stats.first_active),
+            date(&stats.last_active)
+        )),
+        Line::from(format!(
+            "  {} in, {} out, {} cache read, ~${:.2}",
+            tokens(stats.input_tokens),
+            tokens(stats.output_tokens),
+            tokens(stats.cache_read_tokens),
+            stats.cost_usd
+        )),
+        Line::from(Span::styled("  Esc to close", dim)),
+    ]
+}
+
 /// Helper to create a centered rect with percentage width and height
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let width = r.width * percent_x / 100;
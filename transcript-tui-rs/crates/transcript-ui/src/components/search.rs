@@ -7,31 +7,111 @@ use ratatui::{
 
 use crate::App;
 
+/// Saved searches listed under the search input
+const MAX_SAVED_SHOWN: usize = 6;
+
 /// Render the search input popup
 pub fn render_search_input(frame: &mut Frame, app: &App) {
     if !app.search_mode {
         return;
     }
 
+    // Saved searches are listed under the input, for Tab to pick from
+    let saved = app.saved_searches.len().min(MAX_SAVED_SHOWN) as u16;
+
     // Center the popup
-    let area = centered_rect(60, 3, frame.area());
+    let area = centered_rect(60, 3 + saved, frame.area());
 
     // Clear the background
     frame.render_widget(Clear, area);
 
+    let title = if saved > 0 {
+        " Search (↑/↓: history, Tab: saved) "
+    } else {
+        " Search (↑/↓: history) "
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let mut text = vec![Line::styled(
+        format!("/{}_", app.search_query),
+        Style::default().fg(Color::Yellow),
+    )];
+    // Keep the picked search in view when there are more than fit
+    let first = app
+        .saved_search_index
+        .map_or(0, |i| (i + 1).saturating_sub(MAX_SAVED_SHOWN));
+    for (i, search) in app.saved_searches.iter().enumerate().skip(first).take(MAX_SAVED_SHOWN) {
+        let style = if app.saved_search_index == Some(i) {
+            Style::default().fg(Color::Black).bg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        text.push(Line::styled(format!(" {}: {}", search.name, search.query), style));
+    }
+
+    frame.render_widget(Paragraph::new(text).block(block), area);
+
+    // Position cursor at end of input
+    let cursor_x = area.x + 2 + app.search_query.len() as u16;
+    let cursor_y = area.y + 1;
+    frame.set_cursor_position(Position::new(cursor_x, cursor_y));
+}
+
+/// Render the JSON query input popup
+pub fn render_json_query_input(frame: &mut Frame, app: &App) {
+    if !app.json_query_mode {
+        return;
+    }
+
+    let area = centered_rect(60, 3, frame.area());
+
+    frame.render_widget(Clear, area);
+
     let block = Block::default()
-        .title(" Search ")
+        .title(" JSON Query (Enter: apply, empty clears) ")
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
 
-    let input = Paragraph::new(format!("/{}_", app.search_query))
+    let input = Paragraph::new(format!(":{}_", app.json_query_input))
         .style(Style::default().fg(Color::Yellow))
         .block(block);
 
     frame.render_widget(input, area);
 
-    // Position cursor at end of input
-    let cursor_x = area.x + 2 + app.search_query.len() as u16;
+    let cursor_x = area.x + 2 + app.json_query_input.chars().count() as u16;
+    let cursor_y = area.y + 1;
+    frame.set_cursor_position(Position::new(cursor_x, cursor_y));
+}
+
+/// Render the annotation input popup
+pub fn render_annotation_input(frame: &mut Frame, app: &App) {
+    if !app.annotate_mode {
+        return;
+    }
+
+    let area = centered_rect(60, 3, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let title = match app.current_line() {
+        Some(line) => format!(" Annotate line {} (Enter: save, Esc: cancel) ", line.line_number),
+        None => " Annotate ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+
+    let input = Paragraph::new(format!("{}_", app.annotation_input))
+        .style(Style::default().fg(Color::Yellow))
+        .block(block);
+
+    frame.render_widget(input, area);
+
+    let cursor_x = area.x + 1 + app.annotation_input.chars().count() as u16;
     let cursor_y = area.y + 1;
     frame.set_cursor_position(Position::new(cursor_x, cursor_y));
 }
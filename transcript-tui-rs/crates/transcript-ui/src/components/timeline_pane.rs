@@ -0,0 +1,81 @@
+//! Timeline pane: hook events interleaved with the lines around the selection
+
+use ratatui::{
+    prelude::*,
+    widgets::{List, ListItem, ListState},
+};
+use transcript_core::{merge_timeline, TimelineEntry};
+
+use super::pane_block;
+use crate::App;
+
+/// Render the timeline pane, scrolled to keep the current line in view
+pub fn render_timeline_pane(frame: &mut Frame, area: Rect, app: &App) {
+    let title = format!(" Timeline ({} hook events) ", app.hook_events.len());
+    let block = pane_block(title, false, app.a11y);
+    let inner = block.inner(area);
+
+    let timeline = merge_timeline(&app.lines, &app.hook_events);
+    let selected = app.current_line().and_then(|current| {
+        timeline
+            .iter()
+            .position(|entry| matches!(entry, TimelineEntry::Line(line) if line.id == current.id))
+    });
+
+    let width = inner.width as usize;
+    let items: Vec<ListItem> = timeline
+        .iter()
+        .map(|entry| timeline_item(entry, app.a11y, width))
+        .collect();
+
+    let mut list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    if app.a11y {
+        list = list.highlight_symbol("> ");
+    }
+
+    // Center the selection, so the hooks on both sides of it show
+    let half = (inner.height as usize) / 2;
+    let mut state = ListState::default()
+        .with_offset(selected.unwrap_or(0).saturating_sub(half))
+        .with_selected(selected);
+
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// One row: time, source, event or line type, summary
+fn timeline_item(entry: &TimelineEntry, a11y: bool, width: usize) -> ListItem<'static> {
+    let time = entry.format_time();
+    let (source, color) = match entry {
+        TimelineEntry::Hook(event) => ("hook".to_string(), event_color(&event.event_type)),
+        TimelineEntry::Line(line) => (format!("#{}", line.line_number), Color::DarkGray),
+    };
+    let kind = format!("{:<18} ", entry.kind());
+    let prefix_len = 9 + 7 + kind.chars().count();
+    let summary = entry.summary(width.saturating_sub(prefix_len + 3));
+
+    if a11y {
+        let marker = if matches!(entry, TimelineEntry::Hook(_)) { "[HOOK]" } else { "[LINE]" };
+        return ListItem::new(Line::from(format!("{} {} {}{}", time, marker, kind, summary)));
+    }
+
+    ListItem::new(Line::from(vec![
+        Span::styled(format!("{} ", time), Style::default().fg(Color::DarkGray)),
+        Span::styled(format!("{:<6} ", source), Style::default().fg(Color::DarkGray)),
+        Span::styled(kind, Style::default().fg(color)),
+        Span::raw(summary),
+    ]))
+}
+
+/// Hook events in the colors hook-events uses; transcript lines stay plain
+fn event_color(event_type: &str) -> Color {
+    match event_type {
+        "UserPromptSubmit" | "UserPromptSubmitHook" => Color::Green,
+        "PreToolUse" | "PreSubagentToolUse" => Color::Yellow,
+        "PostToolUse" | "PostSubagentToolUse" => Color::Cyan,
+        "SessionStart" => Color::Magenta,
+        "SessionEnd" | "Stop" | "SubagentStop" => Color::Red,
+        _ => Color::White,
+    }
+}
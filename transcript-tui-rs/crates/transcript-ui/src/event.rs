@@ -39,6 +39,10 @@ pub enum AppAction {
     NextBookmark,
     /// Jump to previous bookmark
     PrevBookmark,
+    /// Cycle the session's review status
+    CycleReview,
+    /// Toggle the hook/transcript timeline pane
+    ToggleTimeline,
     /// Start search
     StartSearch,
     /// Cancel search
@@ -49,36 +53,114 @@ pub enum AppAction {
     SearchInput(char),
     /// Search backspace
     SearchBackspace,
+    /// Recall an older (true) or newer (false) search query
+    SearchHistory(bool),
+    /// Fill the search input with the next (true) or previous saved search
+    SavedSearch(bool),
     /// Next search result
     NextSearchResult,
     /// Previous search result
     PrevSearchResult,
+    /// Start JSON path query
+    StartJsonQuery,
+    /// Cancel JSON query input
+    CancelJsonQuery,
+    /// Submit JSON query
+    SubmitJsonQuery,
+    /// JSON query input character
+    JsonQueryInput(char),
+    /// JSON query backspace
+    JsonQueryBackspace,
+    /// Clear the active JSON query
+    ClearJsonQuery,
+    /// Copy JSON query result (or raw JSON) to clipboard
+    CopyJsonQuery,
+    /// Start writing an annotation on the selected line
+    StartAnnotation,
+    /// Cancel the annotation being written
+    CancelAnnotation,
+    /// Save the annotation being written
+    SubmitAnnotation,
+    /// Annotation input character
+    AnnotationInput(char),
+    /// Annotation backspace
+    AnnotationBackspace,
     /// Toggle help overlay
     ToggleHelp,
     /// Toggle usage graph
     ToggleUsageGraph,
     /// Toggle live mode
     ToggleLiveMode,
+    /// Load the next chunk of a large line
+    ExpandContent,
+    /// Collapse/expand the branches at the selection (thread view)
+    ToggleBranch,
+    /// Collapse/expand every branch (thread view)
+    ToggleAllBranches,
+    /// Switch the list between lines and turns
+    ToggleListMode,
+    /// Open the selected turn in the lines list
+    ExpandTurn,
+    /// Open the session picker
+    OpenPicker,
+    /// Close the session picker (quits when no session is open)
+    ClosePicker,
+    /// Open the session selected in the picker
+    PickSession,
+    /// Picker query input character
+    PickerInput(char),
+    /// Picker query backspace
+    PickerBackspace,
+    /// Select the previous session in the picker
+    PickerPrev,
+    /// Select the next session in the picker
+    PickerNext,
+    /// Mark or unmark the selected session in the picker
+    PickerToggleMark,
+    /// Mark or unmark every matching session in the picker
+    PickerMarkAll,
+    /// Open the bulk action menu for the marked sessions
+    PickerBulk,
     /// Redraw screen
     Redraw,
     /// No action
     None,
 }
 
+/// What keys currently type into, which decides their bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputMode {
+    /// Keys are commands
+    #[default]
+    Normal,
+    /// Typing a search
+    Search,
+    /// Typing a JSON path query
+    JsonQuery,
+    /// Writing an annotation
+    Annotation,
+    /// Filtering the session picker
+    Picker,
+}
+
 /// Handle a terminal event and return the corresponding action
-pub fn handle_event(event: Event, search_mode: bool, fullscreen: bool) -> AppAction {
+pub fn handle_event(event: Event, mode: InputMode, fullscreen: bool) -> AppAction {
     match event {
-        Event::Key(key) => handle_key(key, search_mode, fullscreen),
+        Event::Key(key) => handle_key(key, mode, fullscreen),
         Event::Resize(_, _) => AppAction::Redraw,
         _ => AppAction::None,
     }
 }
 
 /// Handle a key event
-fn handle_key(key: KeyEvent, search_mode: bool, fullscreen: bool) -> AppAction {
-    // Search mode has different bindings
-    if search_mode {
-        return handle_search_key(key);
+fn handle_key(key: KeyEvent, mode: InputMode, fullscreen: bool) -> AppAction {
+    // The picker, search, query and annotation input have different bindings
+    match mode {
+        InputMode::Normal => {}
+        InputMode::Picker => return handle_picker_key(key),
+        InputMode::Search => return handle_search_key(key),
+        InputMode::JsonQuery => return handle_query_key(key),
+        InputMode::Annotation => return handle_annotation_key(key),
     }
 
     // Check for Ctrl modifiers
@@ -113,13 +195,7 @@ fn handle_key(key: KeyEvent, search_mode: bool, fullscreen: bool) -> AppAction {
             }
         }
         KeyCode::Char('h') | KeyCode::Left => AppAction::ScrollLeft,
-        KeyCode::Char('l') | KeyCode::Right => {
-            if !fullscreen {
-                AppAction::ScrollRight
-            } else {
-                AppAction::ScrollRight
-            }
-        }
+        KeyCode::Char('l') | KeyCode::Right => AppAction::ScrollRight,
         KeyCode::Char('g') => AppAction::SelectFirst,
         KeyCode::Char('G') => AppAction::SelectLast,
         KeyCode::PageUp => AppAction::PageUp,
@@ -144,16 +220,36 @@ fn handle_key(key: KeyEvent, search_mode: bool, fullscreen: bool) -> AppAction {
         KeyCode::Char(' ') => AppAction::ToggleBookmark,
         KeyCode::Char(']') => AppAction::NextBookmark,
         KeyCode::Char('[') => AppAction::PrevBookmark,
+        KeyCode::Char('a') => AppAction::StartAnnotation,
+        KeyCode::Char('R') => AppAction::CycleReview,
 
         // Search
         KeyCode::Char('/') => AppAction::StartSearch,
         KeyCode::Char('n') => AppAction::NextSearchResult,
         KeyCode::Char('N') => AppAction::PrevSearchResult,
 
+        // JSON path query
+        KeyCode::Char(':') => AppAction::StartJsonQuery,
+        KeyCode::Char('x') => AppAction::ClearJsonQuery,
+        KeyCode::Char('y') => AppAction::CopyJsonQuery,
+
         // Overlays
         KeyCode::Char('?') => AppAction::ToggleHelp,
         KeyCode::Char('u') => AppAction::ToggleUsageGraph,
+        KeyCode::Char('T') => AppAction::ToggleTimeline,
         KeyCode::Char('L') => AppAction::ToggleLiveMode,
+        KeyCode::Char('e') => AppAction::ExpandContent,
+
+        // Thread branches
+        KeyCode::Char('o') => AppAction::ToggleBranch,
+        KeyCode::Char('O') => AppAction::ToggleAllBranches,
+
+        // Turns list
+        KeyCode::Char('t') => AppAction::ToggleListMode,
+        KeyCode::Enter => AppAction::ExpandTurn,
+
+        // Sessions
+        KeyCode::Char('s') => AppAction::OpenPicker,
 
         // Redraw
         KeyCode::Char('r') => AppAction::Redraw,
@@ -168,10 +264,16 @@ fn handle_search_key(key: KeyEvent) -> AppAction {
         KeyCode::Esc => AppAction::CancelSearch,
         KeyCode::Enter => AppAction::SubmitSearch,
         KeyCode::Backspace => AppAction::SearchBackspace,
+        KeyCode::Up => AppAction::SearchHistory(true),
+        KeyCode::Down => AppAction::SearchHistory(false),
+        KeyCode::Tab => AppAction::SavedSearch(true),
+        KeyCode::BackTab => AppAction::SavedSearch(false),
         KeyCode::Char(c) => {
             if key.modifiers.contains(KeyModifiers::CONTROL) {
                 match c {
                     'c' | 'g' => AppAction::CancelSearch,
+                    'p' => AppAction::SearchHistory(true),
+                    'n' => AppAction::SearchHistory(false),
                     _ => AppAction::None,
                 }
             } else {
@@ -182,6 +284,73 @@ fn handle_search_key(key: KeyEvent) -> AppAction {
     }
 }
 
+/// Handle key events in JSON query mode
+fn handle_query_key(key: KeyEvent) -> AppAction {
+    match key.code {
+        KeyCode::Esc => AppAction::CancelJsonQuery,
+        KeyCode::Enter => AppAction::SubmitJsonQuery,
+        KeyCode::Backspace => AppAction::JsonQueryBackspace,
+        KeyCode::Char(c) => {
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                match c {
+                    'c' | 'g' => AppAction::CancelJsonQuery,
+                    _ => AppAction::None,
+                }
+            } else {
+                AppAction::JsonQueryInput(c)
+            }
+        }
+        _ => AppAction::None,
+    }
+}
+
+/// Handle key events while writing an annotation
+fn handle_annotation_key(key: KeyEvent) -> AppAction {
+    match key.code {
+        KeyCode::Esc => AppAction::CancelAnnotation,
+        KeyCode::Enter => AppAction::SubmitAnnotation,
+        KeyCode::Backspace => AppAction::AnnotationBackspace,
+        KeyCode::Char(c) => {
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                match c {
+                    'c' | 'g' => AppAction::CancelAnnotation,
+                    _ => AppAction::None,
+                }
+            } else {
+                AppAction::AnnotationInput(c)
+            }
+        }
+        _ => AppAction::None,
+    }
+}
+
+/// Handle key events in the session picker
+fn handle_picker_key(key: KeyEvent) -> AppAction {
+    match key.code {
+        KeyCode::Esc => AppAction::ClosePicker,
+        KeyCode::Enter => AppAction::PickSession,
+        KeyCode::Backspace => AppAction::PickerBackspace,
+        KeyCode::Up => AppAction::PickerPrev,
+        KeyCode::Down => AppAction::PickerNext,
+        KeyCode::Tab => AppAction::PickerBulk,
+        KeyCode::Char(' ') => AppAction::PickerToggleMark,
+        KeyCode::Char(c) => {
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                match c {
+                    'c' | 'g' => AppAction::ClosePicker,
+                    'p' | 'k' => AppAction::PickerPrev,
+                    'n' | 'j' => AppAction::PickerNext,
+                    'a' => AppAction::PickerMarkAll,
+                    _ => AppAction::None,
+                }
+            } else {
+                AppAction::PickerInput(c)
+            }
+        }
+        _ => AppAction::None,
+    }
+}
+
 /// Key binding help text
 pub const HELP_TEXT: &str = r#"
 ╭─────────────────────────────────────────╮
@@ -195,35 +364,77 @@ pub const HELP_TEXT: &str = r#"
 │  g/G         Go to first/last line      │
 │  PgUp/PgDn   Page up/down               │
 │  Tab         Switch panes               │
+│  t           List turns / lines         │
+│  Enter       Open turn in lines list    │
 │  h/l, ←/→    Scroll content left/right  │
 │                                         │
 │  View Modes                             │
 │  ──────────                             │
 │  1           Raw JSON                   │
 │  2           Human-readable             │
-│  3           Minimal (text only)        │
-│  4           Conversation context       │
-│  5           Markdown rendered          │
+│  3           Thread (branches nested)   │
 │  f           Toggle fullscreen          │
+│  o           Fold/unfold branch         │
+│  O           Fold/unfold all branches   │
 │                                         │
 │  Search                                 │
 │  ──────                                 │
 │  /           Start search               │
 │  n/N         Next/prev search result    │
 │  Esc         Cancel search              │
+│  Tab         Saved search (while typing)│
+│                                         │
+│  JSON Query                             │
+│  ──────────                             │
+│  :           Query path (.message.usage)│
+│  x           Clear query                │
+│  y           Copy query result / raw    │
 │                                         │
 │  Bookmarks                              │
 │  ─────────                              │
 │  Space       Toggle bookmark            │
 │  [/]         Prev/next bookmark         │
+│  a           Annotate line              │
+│  R           Cycle review status        │
+│                                         │
+│  Macros                                 │
+│  ──────                                 │
+│  Q<a-z>      Record macro (Q to stop)   │
+│  @<a-z>, @@  Replay macro / last macro  │
 │                                         │
 │  Other                                  │
 │  ─────                                  │
 │  u           Usage graph                │
+│  s           Switch session             │
+│  Space, Tab  Mark / act on (in s)       │
+│  T           Hook/transcript timeline   │
 │  L           Toggle live mode           │
+│  e           Load more of a large line  │
 │  r           Redraw screen              │
 │  ?           Show this help             │
 │  q, Esc      Quit                       │
 │                                         │
 ╰─────────────────────────────────────────╯
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_annotation_keys() {
+        // 'a' starts an annotation; while writing, keys are text
+        assert_eq!(handle_event(key(KeyCode::Char('a')), InputMode::Normal, false), AppAction::StartAnnotation);
+        assert_eq!(handle_event(key(KeyCode::Char('q')), InputMode::Annotation, false), AppAction::AnnotationInput('q'));
+        assert_eq!(handle_event(key(KeyCode::Backspace), InputMode::Annotation, false), AppAction::AnnotationBackspace);
+        assert_eq!(handle_event(key(KeyCode::Enter), InputMode::Annotation, false), AppAction::SubmitAnnotation);
+        assert_eq!(handle_event(key(KeyCode::Esc), InputMode::Annotation, false), AppAction::CancelAnnotation);
+
+        // While searching, 'a' is typed
+        assert_eq!(handle_event(key(KeyCode::Char('a')), InputMode::Search, false), AppAction::SearchInput('a'));
+    }
+}
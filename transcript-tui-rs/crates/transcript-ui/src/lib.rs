@@ -4,8 +4,18 @@
 
 pub mod app;
 pub mod event;
+pub mod macros;
+pub mod picker;
+pub mod render_cache;
+pub mod thread;
+pub mod turns;
 pub mod components;
 pub mod views;
 
 pub use app::*;
 pub use event::*;
+pub use macros::*;
+pub use picker::*;
+pub use render_cache::*;
+pub use thread::*;
+pub use turns::*;
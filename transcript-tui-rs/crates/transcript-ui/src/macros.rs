@@ -0,0 +1,140 @@
+//! Keyboard macros: record key sequences into registers and replay them
+//!
+//! `Q<reg>` starts recording into register `a`-`z` and `Q` stops (`q` stays
+//! quit); `@<reg>` replays a register and `@@` replays the last one used.
+//! Keys are recorded before mapping, so replays drive search and query input
+//! exactly as typed.
+
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// What the caller should do with a key after macro handling
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroStep {
+    /// Key was a macro command; nothing else to do
+    Consumed,
+    /// Handle the key normally
+    Pass(KeyEvent),
+    /// Handle these keys in order (a register replay)
+    Replay(Vec<KeyEvent>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Record,
+    Replay,
+}
+
+/// Macro registers and recording state
+#[derive(Debug, Default)]
+pub struct Macros {
+    registers: HashMap<char, Vec<KeyEvent>>,
+    recording: Option<(char, Vec<KeyEvent>)>,
+    pending: Option<Pending>,
+    last_replayed: Option<char>,
+}
+
+impl Macros {
+    /// Register being recorded, if any
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(reg, _)| *reg)
+    }
+
+    /// Whether a register key is awaited after `Q` or `@`
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Process a key; `text_input` is set while a search or query is being typed
+    pub fn handle_key(&mut self, key: KeyEvent, text_input: bool) -> MacroStep {
+        let plain = !key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        if let Some(pending) = self.pending.take() {
+            let KeyCode::Char(c) = key.code else {
+                return MacroStep::Consumed;
+            };
+            return match pending {
+                Pending::Record if c.is_ascii_lowercase() => {
+                    self.recording = Some((c, Vec::new()));
+                    MacroStep::Consumed
+                }
+                Pending::Replay => {
+                    let reg = if c == '@' { self.last_replayed } else { Some(c) };
+                    match reg.and_then(|r| self.registers.get(&r).map(|keys| (r, keys))) {
+                        Some((reg, keys)) => {
+                            let keys = keys.clone();
+                            self.last_replayed = Some(reg);
+                            MacroStep::Replay(keys)
+                        }
+                        None => MacroStep::Consumed,
+                    }
+                }
+                _ => MacroStep::Consumed,
+            };
+        }
+
+        if plain && !text_input {
+            match key.code {
+                KeyCode::Char('Q') => {
+                    match self.recording.take() {
+                        Some((reg, keys)) => {
+                            self.registers.insert(reg, keys);
+                        }
+                        None => self.pending = Some(Pending::Record),
+                    }
+                    return MacroStep::Consumed;
+                }
+                // Replays aren't recorded, so a macro can't invoke itself
+                KeyCode::Char('@') if self.recording.is_none() => {
+                    self.pending = Some(Pending::Replay);
+                    return MacroStep::Consumed;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((_, keys)) = &mut self.recording {
+            keys.push(key);
+        }
+        MacroStep::Pass(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut macros = Macros::default();
+        assert_eq!(macros.handle_key(key('Q'), false), MacroStep::Consumed);
+        assert_eq!(macros.handle_key(key('a'), false), MacroStep::Consumed);
+        assert_eq!(macros.recording(), Some('a'));
+
+        // Typed text (even 'Q') is recorded while searching
+        for c in ['/', 'Q', 'n'] {
+            let text_input = c != '/';
+            assert_eq!(macros.handle_key(key(c), text_input), MacroStep::Pass(key(c)));
+        }
+        assert_eq!(macros.handle_key(key('@'), false), MacroStep::Pass(key('@')));
+        assert_eq!(macros.handle_key(key('Q'), false), MacroStep::Consumed);
+        assert_eq!(macros.recording(), None);
+
+        let recorded = vec![key('/'), key('Q'), key('n'), key('@')];
+        macros.handle_key(key('@'), false);
+        assert_eq!(macros.handle_key(key('a'), false), MacroStep::Replay(recorded.clone()));
+        macros.handle_key(key('@'), false);
+        assert_eq!(macros.handle_key(key('@'), false), MacroStep::Replay(recorded));
+
+        // Unknown register does nothing
+        macros.handle_key(key('@'), false);
+        assert_eq!(macros.handle_key(key('z'), false), MacroStep::Consumed);
+    }
+}
@@ -0,0 +1,341 @@
+//! Session picker: choose a session to open by fuzzy-searching its name
+//! and project
+//!
+//! Sessions can also be marked (Space) for a bulk action (Tab): tag, archive,
+//! export as a bundle, delete, or stats of them combined. The picker keeps the
+//! marks and the prompt; the viewer runs the action.
+
+use std::collections::HashSet;
+
+use transcript_core::fuzzy_filter;
+use transcript_db::{CostGroupBy, DbError, TranscriptDb};
+
+/// One session offered by the picker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PickerEntry {
+    pub session_id: String,
+    /// Slug, or the start of the session ID
+    pub name: String,
+    /// Project name (see `transcript projects`)
+    pub project: Option<String>,
+    pub line_count: i64,
+    pub last_timestamp: Option<String>,
+}
+
+/// Picker state: every session, and those matching the query
+#[derive(Debug, Clone, Default)]
+pub struct SessionPicker {
+    /// Sessions, most recently active first
+    pub entries: Vec<PickerEntry>,
+    /// Text typed so far
+    pub query: String,
+    /// Indices into `entries` matching the query, best first
+    pub matches: Vec<usize>,
+    /// Selected position in `matches`
+    pub selected: usize,
+    /// IDs of the sessions marked for a bulk action
+    pub marked: HashSet<String>,
+    /// Bulk action prompt, while open
+    pub bulk: Option<BulkPrompt>,
+}
+
+/// An action on every marked session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    Tag,
+    Archive,
+    Export,
+    Delete,
+    Stats,
+}
+
+impl BulkAction {
+    pub const ALL: [BulkAction; 5] = [
+        BulkAction::Tag,
+        BulkAction::Archive,
+        BulkAction::Export,
+        BulkAction::Delete,
+        BulkAction::Stats,
+    ];
+
+    /// Key choosing the action in the menu
+    pub fn key(self) -> char {
+        match self {
+            BulkAction::Tag => 't',
+            BulkAction::Archive => 'a',
+            BulkAction::Export => 'e',
+            BulkAction::Delete => 'd',
+            BulkAction::Stats => 's',
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BulkAction::Tag => "Tag",
+            BulkAction::Archive => "Archive",
+            BulkAction::Export => "Export bundle",
+            BulkAction::Delete => "Delete (to the trash)",
+            BulkAction::Stats => "Combined stats",
+        }
+    }
+
+    pub fn from_key(c: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.key() == c)
+    }
+
+    /// Whether the action takes sessions out of the index, so asks first
+    pub fn needs_confirm(self) -> bool {
+        matches!(self, BulkAction::Archive | BulkAction::Delete)
+    }
+}
+
+/// Where the bulk action prompt is at
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkPrompt {
+    /// Choosing an action
+    Menu,
+    /// Typing the tag to add
+    Tag(String),
+    /// Waiting for `y`
+    Confirm(BulkAction),
+    /// The marked sessions' combined stats
+    Stats(BulkStats),
+}
+
+/// Stats of the marked sessions together
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BulkStats {
+    pub sessions: usize,
+    pub lines: i64,
+    /// Assistant messages with token usage
+    pub messages: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cost_usd: f64,
+    /// Earliest and latest last activity
+    pub first_active: Option<String>,
+    pub last_active: Option<String>,
+}
+
+impl SessionPicker {
+    /// Picker over the given sessions, filtered by an initial query
+    pub fn new(entries: Vec<PickerEntry>, query: &str) -> Self {
+        let mut picker = Self {
+            entries,
+            query: query.to_string(),
+            ..Default::default()
+        };
+        picker.refilter();
+        picker
+    }
+
+    /// Picker over every indexed session
+    pub fn load(db: &TranscriptDb, query: &str) -> Result<Self, DbError> {
+        let sessions = db.get_sessions(None)?;
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        let mut projects = db.get_session_projects(&ids)?;
+        let entries = sessions
+            .into_iter()
+            .map(|s| PickerEntry {
+                name: s
+                    .slug
+                    .clone()
+                    .unwrap_or_else(|| s.session_id.chars().take(8).collect()),
+                project: projects.remove(&s.session_id),
+                line_count: s.line_count,
+                last_timestamp: s.last_timestamp,
+                session_id: s.session_id,
+            })
+            .collect();
+        Ok(Self::new(entries, query))
+    }
+
+    /// The selected session, if any match
+    pub fn selected_entry(&self) -> Option<&PickerEntry> {
+        self.matches.get(self.selected).map(|&i| &self.entries[i])
+    }
+
+    /// Type a character into the query
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.refilter();
+    }
+
+    /// Delete the query's last character
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.refilter();
+    }
+
+    /// Select the previous match
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Select the next match
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Select a session when it matches
+    pub fn select_session(&mut self, session_id: &str) {
+        if let Some(pos) = self.matches.iter().position(|&i| self.entries[i].session_id == session_id) {
+            self.selected = pos;
+        }
+    }
+
+    /// Mark or unmark the selected session, then move on to the next
+    pub fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_entry().map(|e| e.session_id.clone()) else {
+            return;
+        };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+        self.select_next();
+    }
+
+    /// Mark every match, or unmark them when all are marked
+    pub fn toggle_mark_all(&mut self) {
+        let ids: Vec<String> = self.matches.iter().map(|&i| self.entries[i].session_id.clone()).collect();
+        if ids.iter().all(|id| self.marked.contains(id)) {
+            for id in &ids {
+                self.marked.remove(id);
+            }
+        } else {
+            self.marked.extend(ids);
+        }
+    }
+
+    /// Marked session IDs, in list order
+    pub fn marked_ids(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| self.marked.contains(&e.session_id))
+            .map(|e| e.session_id.clone())
+            .collect()
+    }
+
+    /// Stats of the marked sessions together: lines and activity from the
+    /// list, tokens and cost from their assistant messages
+    pub fn combined_stats(&self, db: &TranscriptDb) -> Result<BulkStats, DbError> {
+        let marked: Vec<&PickerEntry> = self.entries.iter().filter(|e| self.marked.contains(&e.session_id)).collect();
+        let mut stats = BulkStats {
+            sessions: marked.len(),
+            lines: marked.iter().map(|e| e.line_count).sum(),
+            first_active: marked.iter().filter_map(|e| e.last_timestamp.clone()).min(),
+            last_active: marked.iter().filter_map(|e| e.last_timestamp.clone()).max(),
+            ..Default::default()
+        };
+        for row in db.cost_breakdown(CostGroupBy::Session, None, None)? {
+            if !row.keys.first().is_some_and(|id| self.marked.contains(id)) {
+                continue;
+            }
+            stats.messages += row.messages;
+            stats.input_tokens += row.input_tokens;
+            stats.output_tokens += row.output_tokens;
+            stats.cache_read_tokens += row.cache_read_tokens;
+            stats.cost_usd += row.cost_usd;
+        }
+        Ok(stats)
+    }
+
+    /// Reload the sessions after a bulk action, keeping the query and
+    /// forgetting the marks
+    pub fn reload(&mut self, db: &TranscriptDb) -> Result<(), DbError> {
+        *self = Self::load(db, &self.query)?;
+        Ok(())
+    }
+
+    fn refilter(&mut self) {
+        self.matches = fuzzy_filter(&self.query, &self.entries, |e| {
+            format!("{} {}", e.name, e.project.as_deref().unwrap_or_default())
+        });
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, name: &str, project: &str) -> PickerEntry {
+        PickerEntry {
+            session_id: id.to_string(),
+            name: name.to_string(),
+            project: Some(project.to_string()),
+            line_count: 10,
+            last_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_picker_filters_and_selects() {
+        let mut picker = SessionPicker::new(
+            vec![
+                entry("1", "happy-dog", "api"),
+                entry("2", "tender-spider", "web"),
+                entry("3", "hungry-dodo", "api"),
+            ],
+            "",
+        );
+        assert_eq!(picker.matches, [0, 1, 2]);
+        picker.select_next();
+        picker.select_next();
+        picker.select_next();
+        assert_eq!(picker.selected_entry().unwrap().session_id, "3");
+
+        // Typing filters, by name or project, and selects the best match
+        for c in "tndr".chars() {
+            picker.push(c);
+        }
+        assert_eq!(picker.selected_entry().unwrap().session_id, "2");
+        picker.pop();
+        picker.pop();
+        picker.pop();
+        picker.pop();
+        for c in "api".chars() {
+            picker.push(c);
+        }
+        assert_eq!(picker.matches, [0, 2]);
+        picker.select_session("3");
+        assert_eq!(picker.selected_entry().unwrap().session_id, "3");
+
+        picker.push('z');
+        assert_eq!(picker.selected_entry(), None);
+    }
+
+    #[test]
+    fn test_picker_marks_sessions() {
+        let mut picker = SessionPicker::new(
+            vec![
+                entry("1", "happy-dog", "api"),
+                entry("2", "tender-spider", "web"),
+                entry("3", "hungry-dodo", "api"),
+            ],
+            "",
+        );
+        // Marking moves on, so Space Space marks a run
+        picker.toggle_mark();
+        picker.toggle_mark();
+        assert_eq!(picker.marked_ids(), ["1", "2"]);
+        picker.select_prev();
+        picker.toggle_mark();
+        assert_eq!(picker.marked_ids(), ["1"]);
+
+        // All of the matches, then none of them
+        for c in "api".chars() {
+            picker.push(c);
+        }
+        picker.toggle_mark_all();
+        assert_eq!(picker.marked_ids(), ["1", "3"]);
+        picker.toggle_mark_all();
+        assert!(picker.marked_ids().is_empty());
+
+        assert_eq!(BulkAction::from_key('d'), Some(BulkAction::Delete));
+        assert!(BulkAction::Delete.needs_confirm() && !BulkAction::Export.needs_confirm());
+    }
+}
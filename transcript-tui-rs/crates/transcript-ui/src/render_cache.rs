@@ -0,0 +1,175 @@
+//! Cached content rendering, off the UI thread for big lines
+//!
+//! The content pane used to re-render the selected line on every frame. The
+//! cache keeps the last rendering, and lines over `BACKGROUND_RENDER_BYTES` are
+//! rendered on a worker thread while the pane shows a placeholder.
+
+use std::cell::RefCell;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+
+use ratatui::text::Line;
+use transcript_core::{TranscriptLine, ViewMode};
+
+use crate::views;
+
+/// Lines larger than this are rendered in the background
+pub const BACKGROUND_RENDER_BYTES: usize = 32 * 1024;
+
+/// Everything that affects a rendering
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RenderKey {
+    id: i64,
+    uuid: String,
+    mode: ViewMode,
+    width: usize,
+    chunks: usize,
+    a11y: bool,
+}
+
+type Rendered = Arc<Vec<Line<'static>>>;
+
+/// Rendering of the selected line; `None` while a worker is busy with it
+pub struct RenderCache {
+    current: RefCell<Option<(RenderKey, Option<Rendered>)>>,
+    tx: Sender<(RenderKey, Rendered)>,
+    rx: Receiver<(RenderKey, Rendered)>,
+}
+
+impl Default for RenderCache {
+    fn default() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            current: RefCell::new(None),
+            tx,
+            rx,
+        }
+    }
+}
+
+/// Render a line, summarizing it when it is very large
+fn render(key: &RenderKey, line: &TranscriptLine) -> Vec<Line<'static>> {
+    if views::large::is_large(line) {
+        views::sanitize_lines(views::large::render(line, key.width, key.chunks))
+    } else {
+        views::render_content(line, key.mode, key.width, key.a11y)
+    }
+}
+
+impl RenderCache {
+    /// Rendered content for a line, or `None` if it is still being rendered
+    pub fn get(
+        &self,
+        line: &TranscriptLine,
+        mode: ViewMode,
+        width: usize,
+        chunks: usize,
+        a11y: bool,
+    ) -> Option<Rendered> {
+        let key = RenderKey {
+            id: line.id,
+            uuid: line.uuid.clone(),
+            mode,
+            width,
+            chunks,
+            a11y,
+        };
+
+        if let Some((cached, rendered)) = &*self.current.borrow() {
+            if *cached == key {
+                return rendered.clone();
+            }
+        }
+
+        if line.raw.len() <= BACKGROUND_RENDER_BYTES {
+            let rendered = Arc::new(render(&key, line));
+            *self.current.borrow_mut() = Some((key, Some(rendered.clone())));
+            return Some(rendered);
+        }
+
+        // Results for keys that are no longer current are dropped in `poll`
+        let tx = self.tx.clone();
+        let line = line.clone();
+        let worker_key = key.clone();
+        std::thread::spawn(move || {
+            let rendered = render(&worker_key, &line);
+            let _ = tx.send((worker_key, Arc::new(rendered)));
+        });
+        *self.current.borrow_mut() = Some((key, None));
+        None
+    }
+
+    /// Collect finished background renders
+    pub fn poll(&self) {
+        let mut current = self.current.borrow_mut();
+        while let Ok((key, rendered)) = self.rx.try_recv() {
+            if let Some((cached, slot @ None)) = &mut *current {
+                if *cached == key {
+                    *slot = Some(rendered);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn line(id: i64, raw: String) -> TranscriptLine {
+        TranscriptLine {
+            id,
+            line_number: id,
+            uuid: format!("u{}", id),
+            content: Some("hello".to_string()),
+            ..TranscriptLine::for_test(transcript_core::LineType::Assistant, raw)
+        }
+    }
+
+    #[test]
+    fn test_small_line_renders_inline_once() {
+        let cache = RenderCache::default();
+        let small = line(1, r#"{"type":"assistant"}"#.to_string());
+        let first = cache.get(&small, ViewMode::Json, 80, 1, false).unwrap();
+        let again = cache.get(&small, ViewMode::Json, 80, 1, false).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        // A different width is a different rendering
+        let narrow = cache.get(&small, ViewMode::Json, 40, 1, false).unwrap();
+        assert!(!Arc::ptr_eq(&first, &narrow));
+    }
+
+    #[test]
+    fn test_big_line_renders_in_background() {
+        let cache = RenderCache::default();
+        let big = line(2, format!(r#"{{"text":"{}"}}"#, "x".repeat(BACKGROUND_RENDER_BYTES)));
+        assert!(cache.get(&big, ViewMode::Json, 80, 1, false).is_none());
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let rendered = loop {
+            cache.poll();
+            if let Some(rendered) = cache.get(&big, ViewMode::Json, 80, 1, false) {
+                break rendered;
+            }
+            assert!(Instant::now() < deadline, "background render never finished");
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        assert!(!rendered.is_empty());
+    }
+
+    #[test]
+    fn test_stale_background_render_is_dropped() {
+        let cache = RenderCache::default();
+        let big = line(3, "y".repeat(BACKGROUND_RENDER_BYTES + 1));
+        assert!(cache.get(&big, ViewMode::Json, 80, 1, false).is_none());
+        // Moving on before the worker finishes: its result must not replace this one
+        let small = line(4, "{}".to_string());
+        let current = cache.get(&small, ViewMode::Json, 80, 1, false).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        cache.poll();
+        let after = cache.get(&small, ViewMode::Json, 80, 1, false).unwrap();
+        assert!(Arc::ptr_eq(&current, &after));
+    }
+}
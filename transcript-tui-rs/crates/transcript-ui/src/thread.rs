@@ -0,0 +1,211 @@
+//! Conversation tree for the thread view
+//!
+//! Lines are linked by `parent_uuid`. A linear conversation stays flat: a
+//! line's last ordinary child continues at the same depth, and any other
+//! children (retries, edits, subagent sidechains) are nested one level deeper
+//! as branches. Sidechain lines with no parent are hung under the last line
+//! that started a subagent before them.
+
+use std::collections::{HashMap, HashSet};
+
+use transcript_core::TranscriptLine;
+
+/// Tools that run a subagent on a sidechain
+const SUBAGENT_TOOLS: &[&str] = &["\"name\":\"Task\"", "\"name\":\"Agent\""];
+
+/// A visible row of the thread view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadRow {
+    /// Index into the lines the thread was built from
+    pub index: usize,
+    /// Nesting level (0 for the main conversation)
+    pub depth: usize,
+    /// Lines in the branches under this line (0 when it has none)
+    pub branch_lines: usize,
+    /// Whether those branches are hidden
+    pub collapsed: bool,
+    /// Whether the line is part of a subagent sidechain
+    pub sidechain: bool,
+}
+
+/// Lines laid out as a tree, with collapsed branches left out
+#[derive(Debug, Default)]
+pub struct Thread {
+    pub rows: Vec<ThreadRow>,
+    /// Tree parent of each line (by index), after sidechain attachment
+    pub parents: Vec<Option<usize>>,
+}
+
+impl Thread {
+    /// Row showing line `index`, if it is visible
+    pub fn row_of(&self, index: usize) -> Option<usize> {
+        self.rows.iter().position(|r| r.index == index)
+    }
+
+    /// Lines above `index` in the tree, nearest first
+    pub fn ancestors(&self, index: usize) -> Vec<usize> {
+        let mut ancestors = Vec::new();
+        let mut current = self.parents.get(index).copied().flatten();
+        while let Some(i) = current {
+            if ancestors.contains(&i) {
+                break;
+            }
+            ancestors.push(i);
+            current = self.parents[i];
+        }
+        ancestors
+    }
+}
+
+fn starts_subagent(line: &TranscriptLine) -> bool {
+    line.raw.contains("\"tool_use\"") && SUBAGENT_TOOLS.iter().any(|t| line.raw.contains(t))
+}
+
+/// Build the tree; branches under lines whose uuid is in `collapsed` are hidden
+pub fn build_thread(lines: &[TranscriptLine], collapsed: &HashSet<String>) -> Thread {
+    let by_uuid: HashMap<&str, usize> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| (l.uuid.as_str(), i))
+        .collect();
+    let sidechain: Vec<bool> = lines.iter().map(TranscriptLine::is_sidechain).collect();
+
+    let mut parents = vec![None; lines.len()];
+    let mut last_spawner = None;
+    for (i, line) in lines.iter().enumerate() {
+        parents[i] = match line.parent_uuid.as_deref().and_then(|p| by_uuid.get(p)) {
+            Some(&p) if p != i => Some(p),
+            _ if sidechain[i] => last_spawner,
+            _ => None,
+        };
+        if !sidechain[i] && starts_subagent(line) {
+            last_spawner = Some(i);
+        }
+    }
+
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); lines.len()];
+    let mut roots = Vec::new();
+    for (i, parent) in parents.iter().enumerate() {
+        match parent {
+            Some(p) => children[*p].push(i),
+            None => roots.push(i),
+        }
+    }
+
+    // The continuation is the last child on the same side of the sidechain
+    // boundary; everything else under a line is a branch
+    let continuation = |i: usize| -> Option<usize> {
+        children[i].iter().rev().copied().find(|&c| sidechain[c] == sidechain[i])
+    };
+
+    let mut subtree_size = vec![1usize; lines.len()];
+    for i in (0..lines.len()).rev() {
+        // Children always come after their parent, except in broken files
+        for &c in &children[i] {
+            if c > i {
+                subtree_size[i] += subtree_size[c];
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut visited = vec![false; lines.len()];
+    let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|&r| (r, 0)).collect();
+    while let Some((i, depth)) = stack.pop() {
+        if std::mem::replace(&mut visited[i], true) {
+            continue;
+        }
+        let next = continuation(i);
+        let branches: Vec<usize> = children[i].iter().copied().filter(|&c| Some(c) != next).collect();
+        let branch_lines = branches.iter().map(|&b| subtree_size[b]).sum();
+        let is_collapsed = branch_lines > 0 && collapsed.contains(&lines[i].uuid);
+
+        rows.push(ThreadRow {
+            index: i,
+            depth,
+            branch_lines,
+            collapsed: is_collapsed,
+            sidechain: sidechain[i],
+        });
+
+        // Branches are shown right under their line, before the conversation goes on
+        if let Some(n) = next {
+            stack.push((n, depth));
+        }
+        if is_collapsed {
+            let mut hidden = branches;
+            while let Some(h) = hidden.pop() {
+                if !std::mem::replace(&mut visited[h], true) {
+                    hidden.extend(&children[h]);
+                }
+            }
+        } else {
+            stack.extend(branches.iter().rev().map(|&b| (b, depth + 1)));
+        }
+    }
+
+    // Lines caught in a parent cycle are never reached from a root
+    for (i, seen) in visited.iter().enumerate() {
+        if !seen {
+            rows.push(ThreadRow {
+                index: i,
+                depth: 0,
+                branch_lines: 0,
+                collapsed: false,
+                sidechain: sidechain[i],
+            });
+        }
+    }
+
+    Thread { rows, parents }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use transcript_core::LineType;
+
+    fn line(uuid: &str, parent: Option<&str>, raw: &str) -> TranscriptLine {
+        TranscriptLine {
+            uuid: uuid.to_string(),
+            parent_uuid: parent.map(str::to_string),
+            ..TranscriptLine::for_test(LineType::User, raw.to_string())
+        }
+    }
+
+    fn layout(thread: &Thread) -> Vec<(usize, usize)> {
+        thread.rows.iter().map(|r| (r.index, r.depth)).collect()
+    }
+
+    #[test]
+    fn test_build_thread() {
+        let task = r#"{"message":{"content":[{"type":"tool_use","name":"Task","input":{}}]}}"#;
+        let side = r#"{"isSidechain":true}"#;
+        let lines = vec![
+            line("u1", None, "{}"),
+            line("a1", Some("u1"), task),
+            line("s1", None, side),
+            line("s2", Some("s1"), side),
+            line("r1", Some("a1"), "{}"),
+            // A retry of u1's reply: a1 becomes a branch, a2 carries on
+            line("a2", Some("u1"), "{}"),
+            line("u3", Some("a2"), "{}"),
+        ];
+
+        let thread = build_thread(&lines, &HashSet::new());
+        assert_eq!(
+            layout(&thread),
+            [(0, 0), (1, 1), (2, 2), (3, 2), (4, 1), (5, 0), (6, 0)]
+        );
+        assert_eq!(thread.rows[0].branch_lines, 4);
+        assert_eq!(thread.rows[1].branch_lines, 2);
+        assert!(thread.rows[2].sidechain);
+        assert_eq!(thread.ancestors(3), [2, 1, 0]);
+
+        let collapsed: HashSet<String> = ["a1".to_string()].into();
+        let thread = build_thread(&lines, &collapsed);
+        assert_eq!(layout(&thread), [(0, 0), (1, 1), (4, 1), (5, 0), (6, 0)]);
+        assert!(thread.rows[1].collapsed);
+        assert_eq!(thread.row_of(2), None);
+    }
+}
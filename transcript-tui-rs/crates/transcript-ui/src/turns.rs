@@ -0,0 +1,236 @@
+//! Turn summaries for the turns list
+//!
+//! A turn is a user prompt and every line up to the next prompt (prompts a
+//! subagent is given don't count). Turns are numbered by the hook turn
+//! sequence when the lines have one, otherwise they count on from the turn
+//! before.
+
+use chrono::DateTime;
+use transcript_core::{edited_file, merge_timeline, response_usage, HookEvent, LineType, TimelineEntry, TranscriptLine};
+
+/// One row of the turns list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnSummary {
+    pub sequence: usize,
+    /// First line of the turn (index into the lines it was built from)
+    pub start: usize,
+    /// One past the last line
+    pub end: usize,
+    /// First line of the prompt, trimmed
+    pub prompt: String,
+    pub tool_calls: usize,
+    /// Total tokens over the turn's assistant messages
+    pub tokens: u64,
+    /// Seconds from the first line to the last
+    pub duration: Option<i64>,
+}
+
+impl TurnSummary {
+    pub fn contains(&self, index: usize) -> bool {
+        (self.start..self.end).contains(&index)
+    }
+}
+
+/// Split lines (in file order) into turns
+pub fn summarize_turns(lines: &[TranscriptLine]) -> Vec<TurnSummary> {
+    let mut turns: Vec<TurnSummary> = Vec::new();
+    for (i, (line, usage)) in lines.iter().zip(response_usage(lines)).enumerate() {
+        if turns.is_empty() || (line.is_prompt() && !line.is_sidechain()) {
+            let sequence = line
+                .turn_sequence
+                .filter(|&n| n > 0)
+                .map(|n| n as usize)
+                .unwrap_or_else(|| turns.last().map_or(1, |t| t.sequence + 1));
+            let prompt = line
+                .message()
+                .filter(|_| line.line_type == LineType::User)
+                .map(|m| m.content.as_text())
+                .unwrap_or_default();
+            turns.push(TurnSummary {
+                sequence,
+                start: i,
+                end: i,
+                prompt: prompt.trim().lines().next().unwrap_or_default().to_string(),
+                tool_calls: 0,
+                tokens: 0,
+                duration: None,
+            });
+        }
+
+        let turn = turns.last_mut().expect("a turn was just started");
+        turn.end = i + 1;
+        if line.line_type == LineType::Assistant {
+            if let Some(msg) = line.message() {
+                turn.tool_calls += msg.content.tool_uses().len();
+                turn.tokens += usage.map_or(0, |u| u.total());
+            }
+        }
+    }
+
+    for turn in &mut turns {
+        let first = DateTime::parse_from_rfc3339(&lines[turn.start].timestamp);
+        let last = DateTime::parse_from_rfc3339(&lines[turn.end - 1].timestamp);
+        if let (Ok(first), Ok(last)) = (first, last) {
+            turn.duration = Some((last - first).num_seconds());
+        }
+    }
+    turns
+}
+
+/// Index of the first line of each turn
+pub fn turn_starts(lines: &[TranscriptLine]) -> Vec<usize> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| *i == 0 || (line.is_prompt() && !line.is_sidechain()))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Files changed in the turn starting at `start`, up to line `index`, in the
+/// order they were first changed
+///
+/// Taken from the PostToolUse events of edit tools. Sessions recorded without
+/// hooks fall back to the edit tool calls in the transcript.
+pub fn files_changed(lines: &[TranscriptLine], hook_events: &[HookEvent], start: usize, index: usize) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    let mut add = |path: String| {
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    };
+
+    if hook_events.is_empty() {
+        for line in &lines[start..=index] {
+            let Some(msg) = line.message() else {
+                continue;
+            };
+            for (_, name, input) in msg.content.tool_uses() {
+                if let Some(path) = edited_file(name, input) {
+                    add(path.to_string());
+                }
+            }
+        }
+    } else {
+        let mut inside = false;
+        for entry in merge_timeline(lines, hook_events) {
+            match entry {
+                TimelineEntry::Line(line) => {
+                    inside |= std::ptr::eq(line, &lines[start]);
+                    if std::ptr::eq(line, &lines[index]) {
+                        break;
+                    }
+                }
+                TimelineEntry::Hook(event) if inside => {
+                    if let Some(path) = event.edited_file() {
+                        add(path);
+                    }
+                }
+                TimelineEntry::Hook(_) => {}
+            }
+        }
+    }
+    files
+}
+
+/// Compact duration: 45s, 3m05s, 1h02m
+pub fn format_duration(seconds: i64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s.max(0)),
+        s if s < 3600 => format!("{}m{:02}s", s / 60, s % 60),
+        s => format!("{}h{:02}m", s / 3600, s % 3600 / 60),
+    }
+}
+
+/// Compact token count: 950, 12.3k, 1.2M
+pub fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        t if t < 1_000 => t.to_string(),
+        t if t < 1_000_000 => format!("{:.1}k", t as f64 / 1_000.0),
+        t => format!("{:.1}M", t as f64 / 1_000_000.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(line_type: LineType, timestamp: &str, raw: &str) -> TranscriptLine {
+        TranscriptLine {
+            timestamp: timestamp.to_string(),
+            ..TranscriptLine::for_test(line_type, raw.to_string())
+        }
+    }
+
+    #[test]
+    fn test_summarize_turns() {
+        let prompt = r#"{"message":{"role":"user","content":"Fix the build\nIt fails on CI"}}"#;
+        let tool = r#"{"message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{}}],"usage":{"input_tokens":100,"output_tokens":20}}}"#;
+        let result = r#"{"message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}"#;
+        let sidechain = r#"{"isSidechain":true,"message":{"role":"user","content":"look around"}}"#;
+        let lines = vec![
+            line(LineType::User, "2026-01-01T10:00:00Z", prompt),
+            line(LineType::Assistant, "2026-01-01T10:00:05Z", tool),
+            line(LineType::User, "2026-01-01T10:00:06Z", result),
+            line(LineType::User, "2026-01-01T10:00:07Z", sidechain),
+            line(LineType::Assistant, "2026-01-01T10:01:10Z", tool),
+            line(LineType::User, "2026-01-01T10:02:00Z", prompt),
+        ];
+
+        let turns = summarize_turns(&lines);
+        assert_eq!(turns.len(), 2);
+        assert_eq!((turns[0].start, turns[0].end), (0, 5));
+        assert_eq!(turns[0].prompt, "Fix the build");
+        assert_eq!((turns[0].tool_calls, turns[0].tokens), (2, 240));
+        assert_eq!(format_duration(turns[0].duration.unwrap()), "1m10s");
+        assert_eq!((turns[1].sequence, turns[1].start), (2, 5));
+        assert_eq!(format_tokens(12_345), "12.3k");
+    }
+
+    #[test]
+    fn test_files_changed() {
+        let prompt = r#"{"message":{"role":"user","content":"go"}}"#;
+        let edit = |path: &str| {
+            format!(
+                r#"{{"message":{{"role":"assistant","content":[{{"type":"tool_use","id":"t","name":"Edit","input":{{"file_path":"{}"}}}}]}}}}"#,
+                path
+            )
+        };
+        let lines = vec![
+            line(LineType::User, "2026-01-01T10:00:00Z", prompt),
+            line(LineType::Assistant, "2026-01-01T10:00:01Z", &edit("/p/a.rs")),
+            line(LineType::User, "2026-01-01T10:00:02Z", prompt),
+            line(LineType::Assistant, "2026-01-01T10:00:03Z", &edit("/p/b.rs")),
+            line(LineType::Assistant, "2026-01-01T10:00:04Z", &edit("/p/c.rs")),
+            line(LineType::Assistant, "2026-01-01T10:00:05Z", &edit("/p/b.rs")),
+        ];
+        assert_eq!(turn_starts(&lines), vec![0, 2]);
+        assert_eq!(files_changed(&lines, &[], 2, 4), vec!["/p/b.rs", "/p/c.rs"]);
+        assert_eq!(files_changed(&lines, &[], 2, 5), vec!["/p/b.rs", "/p/c.rs"]);
+
+        // With hooks, their PostToolUse events are used instead
+        let event = HookEvent {
+            id: 1,
+            session_id: "s1".to_string(),
+            timestamp: "2026-01-01T10:00:03.500Z".to_string(),
+            event_type: "PostToolUse".to_string(),
+            tool_use_id: None,
+            tool_name: Some("Write".to_string()),
+            decision: None,
+            handler_results_json: None,
+            input_json: Some(r#"{"tool_input":{"file_path":"/p/new.rs"}}"#.to_string()),
+            context_json: None,
+            file_path: String::new(),
+            line_number: 1,
+            turn_id: None,
+            turn_sequence: None,
+            session_name: None,
+            git_hash: None,
+            git_branch: None,
+            git_dirty: None,
+        };
+        let events = [event];
+        assert!(files_changed(&lines, &events, 2, 3).is_empty());
+        assert_eq!(files_changed(&lines, &events, 2, 4), vec!["/p/new.rs"]);
+    }
+}
@@ -1,7 +1,7 @@
 //! Conversation context view mode
 
 use ratatui::prelude::*;
-use transcript_core::{LineType, TranscriptLine};
+use transcript_core::{display_text, LineType, TranscriptLine};
 
 /// Render line in conversation context format
 pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
@@ -20,7 +20,7 @@ pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
 
     // Content with indentation
     if let Some(content) = &line.content {
-        for content_line in content.lines() {
+        for content_line in display_text(content).lines() {
             lines.push(Line::from(format!("   {}", content_line)));
         }
     }
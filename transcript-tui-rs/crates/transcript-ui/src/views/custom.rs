@@ -9,9 +9,9 @@
 //! - TodoWrite: Task list with progress
 
 use ratatui::prelude::*;
-use transcript_core::TranscriptLine;
+use transcript_core::{display_text, TranscriptLine};
 
-use super::human;
+use super::{human, text_lines};
 
 /// Get tool name from a line (if it contains tool_use)
 fn get_tool_name(line: &TranscriptLine) -> Option<String> {
@@ -27,12 +27,12 @@ fn get_tool_name(line: &TranscriptLine) -> Option<String> {
 }
 
 /// Render custom view for a transcript line
-pub fn render(line: &TranscriptLine, width: usize) -> Vec<Line<'static>> {
+pub fn render(line: &TranscriptLine, width: usize, a11y: bool) -> Vec<Line<'static>> {
     // Check if this is a tool use line
     if let Some(tool_name) = get_tool_name(line) {
         match tool_name.as_str() {
             "Edit" => return render_edit_diff(line, width),
-            "Bash" => return render_bash_output(line, width),
+            "Bash" => return render_bash_output(line, width, a11y),
             "Read" => return render_read_output(line, width),
             "Write" => return render_write_output(line, width),
             "Grep" => return render_grep_output(line, width),
@@ -92,8 +92,8 @@ fn render_edit_diff(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>>
     lines
 }
 
-/// Render Bash tool output
-fn render_bash_output(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
+/// Render Bash tool output (stderr in red, or marked `[ERR]` with `a11y`)
+fn render_bash_output(line: &TranscriptLine, _width: usize, a11y: bool) -> Vec<Line<'static>> {
     let mut lines = vec![
         Line::from(vec![
             Span::styled("━━━ BASH ━━━", Style::default().fg(Color::Cyan)),
@@ -117,19 +117,18 @@ fn render_bash_output(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>
         if let Some(result) = &parsed.tool_use_result {
             if let Some(stdout) = result.get("stdout").and_then(|v| v.as_str()) {
                 if !stdout.is_empty() {
-                    for stdout_line in stdout.lines() {
-                        lines.push(Line::from(stdout_line.to_string()));
-                    }
+                    lines.extend(text_lines(stdout, Style::default()));
                 }
             }
             if let Some(stderr) = result.get("stderr").and_then(|v| v.as_str()) {
                 if !stderr.is_empty() {
-                    for stderr_line in stderr.lines() {
-                        lines.push(Line::from(Span::styled(
-                            stderr_line.to_string(),
-                            Style::default().fg(Color::Red),
-                        )));
+                    let mut stderr_lines = text_lines(stderr, Style::default().fg(Color::Red));
+                    if a11y {
+                        for stderr_line in &mut stderr_lines {
+                            stderr_line.spans.insert(0, Span::raw("[ERR] "));
+                        }
                     }
+                    lines.extend(stderr_lines);
                 }
             }
         }
@@ -162,7 +161,7 @@ fn render_read_output(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>
 
     // Show file content with line numbers
     if let Some(content) = &line.content {
-        for (i, content_line) in content.lines().enumerate() {
+        for (i, content_line) in display_text(content).lines().enumerate() {
             lines.push(Line::from(vec![
                 Span::styled(
                     format!("{:>4} ", i + 1),
@@ -196,7 +195,7 @@ fn render_write_output(line: &TranscriptLine, _width: usize) -> Vec<Line<'static
                 }
 
                 if let Some(content) = input.get("content").and_then(|v| v.as_str()) {
-                    for (i, content_line) in content.lines().enumerate() {
+                    for (i, content_line) in display_text(content).lines().enumerate() {
                         lines.push(Line::from(vec![
                             Span::styled(
                                 format!("{:>4} ", i + 1),
@@ -242,9 +241,7 @@ fn render_grep_output(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>
 
     lines.push(Line::from(""));
     if let Some(content) = &line.content {
-        for content_line in content.lines() {
-            lines.push(Line::from(content_line.to_string()));
-        }
+        lines.extend(text_lines(content, Style::default()));
     }
 
     lines
@@ -279,7 +276,7 @@ fn render_glob_output(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>
 
     lines.push(Line::from(""));
     if let Some(content) = &line.content {
-        for file in content.lines() {
+        for file in display_text(content).lines() {
             if !file.trim().is_empty() {
                 lines.push(Line::from(vec![
                     Span::styled("  ├─ ", Style::default().fg(Color::DarkGray)),
@@ -306,7 +303,7 @@ fn render_todo_list(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>>
             for (_, _, input) in msg.content.tool_uses() {
                 if let Some(todos) = input.get("todos").and_then(|v| v.as_array()) {
                     let total = todos.len();
-                    let mut completed = 0;
+                    let mut completed = 0usize;
 
                     for todo in todos {
                         let content = todo.get("content").and_then(|v| v.as_str()).unwrap_or("");
@@ -328,7 +325,7 @@ fn render_todo_list(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>>
                     }
 
                     lines.push(Line::from(""));
-                    let percent = if total > 0 { completed * 100 / total } else { 0 };
+                    let percent = (completed * 100).checked_div(total).unwrap_or(0);
                     lines.push(Line::from(Span::styled(
                         format!("Progress: {}/{} ({}%)", completed, total, percent),
                         Style::default().fg(Color::Cyan),
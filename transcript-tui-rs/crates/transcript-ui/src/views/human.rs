@@ -1,7 +1,9 @@
 //! Human-readable view mode
 
 use ratatui::prelude::*;
-use transcript_core::{LineType, TranscriptLine};
+use transcript_core::{display_text, system_summary, LineType, SystemLevel, TranscriptLine};
+
+use super::text_lines;
 
 /// Render line in human-readable format
 pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
@@ -56,9 +58,7 @@ pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
     match line.line_type {
         LineType::User | LineType::Assistant => {
             if let Some(content) = &line.content {
-                for content_line in content.lines() {
-                    lines.push(Line::from(content_line.to_string()));
-                }
+                lines.extend(text_lines(content, Style::default()));
             }
 
             // Parse for tool uses and token usage
@@ -130,6 +130,15 @@ pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
             }
         }
         LineType::System => {
+            if let Some(summary) = system_summary(line) {
+                let color = match summary.level {
+                    SystemLevel::Info => Color::White,
+                    SystemLevel::Warning => Color::Yellow,
+                    SystemLevel::Error => Color::Red,
+                };
+                lines.push(Line::from(Span::styled(summary.text, Style::default().fg(color).bold())));
+                lines.push(Line::from(""));
+            }
             if let Ok(parsed) = line.parse() {
                 if let Some(subtype) = &parsed.subtype {
                     lines.push(Line::from(vec![
@@ -149,7 +158,7 @@ pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
         LineType::Summary => {
             if let Ok(parsed) = line.parse() {
                 if let Some(summary) = &parsed.summary {
-                    for summary_line in summary.lines() {
+                    for summary_line in display_text(summary).lines() {
                         lines.push(Line::from(summary_line.to_string()));
                     }
                 }
@@ -157,9 +166,7 @@ pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
         }
         _ => {
             if let Some(content) = &line.content {
-                for content_line in content.lines() {
-                    lines.push(Line::from(content_line.to_string()));
-                }
+                lines.extend(text_lines(content, Style::default()));
             }
         }
     }
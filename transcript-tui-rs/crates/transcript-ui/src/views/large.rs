@@ -0,0 +1,117 @@
+//! Summarized view for very large lines
+//!
+//! Pretty-printing and highlighting a multi-megabyte line (e.g. a minified JSON
+//! tool result) takes seconds, so lines over `LARGE_LINE_BYTES` show a header
+//! and their raw JSON in `CHUNK_BYTES` chunks, loaded one at a time with `e`.
+
+use ratatui::prelude::*;
+use transcript_core::{locale, TranscriptLine};
+
+/// Lines larger than this are summarized instead of fully rendered
+pub const LARGE_LINE_BYTES: usize = 256 * 1024;
+
+/// Text revealed per expansion
+pub const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Whether a line is rendered by this view
+pub fn is_large(line: &TranscriptLine) -> bool {
+    line.raw.len() > LARGE_LINE_BYTES
+}
+
+/// Number of chunks needed to show the whole line
+pub fn chunk_count(line: &TranscriptLine) -> usize {
+    line.raw.len().div_ceil(CHUNK_BYTES).max(1)
+}
+
+fn format_bytes(bytes: usize) -> String {
+    locale().format_size(bytes as u64)
+}
+
+/// Render a summary header plus the first `chunks` chunks of raw JSON
+pub fn render(line: &TranscriptLine, width: usize, chunks: usize) -> Vec<Line<'static>> {
+    let text = line.raw.as_str();
+    let total = chunk_count(line);
+    let chunks = chunks.clamp(1, total);
+
+    // Cut on a char boundary at or before the chunk limit
+    let mut end = (chunks * CHUNK_BYTES).min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("━━━ LARGE LINE ({}) ━━━", line.line_type),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format!(
+                "Showing {} of {} raw JSON (chunk {}/{})",
+                format_bytes(end),
+                format_bytes(text.len()),
+                chunks,
+                total
+            ),
+            dim,
+        )),
+        Line::from(""),
+    ];
+
+    // Hard-wrap long physical lines so minified JSON doesn't become one
+    // enormous line for the paragraph to wrap on every frame
+    let width = width.max(20);
+    for physical in text[..end].lines() {
+        let chars: Vec<char> = physical.chars().collect();
+        if chars.is_empty() {
+            lines.push(Line::from(""));
+        }
+        for piece in chars.chunks(width) {
+            lines.push(Line::from(piece.iter().collect::<String>()));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        if chunks < total {
+            format!("── {} more; press e to load the next chunk ──", format_bytes(text.len() - end))
+        } else {
+            "── end of line ──".to_string()
+        },
+        dim,
+    )));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect()).collect()
+    }
+
+    #[test]
+    fn test_render_in_chunks() {
+        let line = TranscriptLine {
+            id: 1,
+            line_number: 1,
+            uuid: "u1".to_string(),
+            ..TranscriptLine::for_test(transcript_core::LineType::User, "é".repeat(LARGE_LINE_BYTES / 2 + 1))
+        };
+        assert!(is_large(&line));
+        assert_eq!(chunk_count(&line), 5);
+
+        let first = text(&render(&line, 80, 1));
+        assert!(first[1].ends_with("(chunk 1/5)"), "{}", first[1]);
+        assert!(first.last().unwrap().contains("press e to load the next chunk"));
+        // Wrapped to the width, and cut on a char boundary
+        assert!(first[3..first.len() - 2].iter().all(|l| l.chars().count() <= 80));
+        let shown: usize = first[3..first.len() - 2].iter().map(|l| l.chars().count()).sum();
+        assert_eq!(shown, CHUNK_BYTES / 2);
+
+        let all = text(&render(&line, 80, 99));
+        assert!(all[1].ends_with("(chunk 5/5)"));
+        assert_eq!(all.last().unwrap(), "── end of line ──");
+    }
+}
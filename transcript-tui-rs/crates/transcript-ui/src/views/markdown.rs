@@ -38,12 +38,11 @@ fn render_markdown(text: &str) -> Vec<Line<'static>> {
                     ));
                     style_stack.push(Style::default().fg(Color::Cyan).bold());
                 }
-                Tag::Paragraph => {
+                Tag::Paragraph
                     // Start new paragraph
-                    if !current_line.is_empty() {
+                    if !current_line.is_empty() => {
                         lines.push(Line::from(std::mem::take(&mut current_line)));
                     }
-                }
                 Tag::CodeBlock(kind) => {
                     if !current_line.is_empty() {
                         lines.push(Line::from(std::mem::take(&mut current_line)));
@@ -71,11 +70,10 @@ fn render_markdown(text: &str) -> Vec<Line<'static>> {
                     // Store URL for later
                     let _ = dest_url;
                 }
-                Tag::List(_) => {
-                    if !current_line.is_empty() {
+                Tag::List(_)
+                    if !current_line.is_empty() => {
                         lines.push(Line::from(std::mem::take(&mut current_line)));
                     }
-                }
                 Tag::Item => {
                     if !current_line.is_empty() {
                         lines.push(Line::from(std::mem::take(&mut current_line)));
@@ -1,8 +1,9 @@
 //! View mode renderers
 //!
-//! Two view modes:
+//! Three view modes:
 //! - JSON: Raw JSON with syntax highlighting
 //! - CUSTOM: Smart view (MD for text, tool-specific for tools)
+//! - THREAD: CUSTOM content, with the line list laid out as a tree
 
 pub mod raw;  // JSON view
 pub mod human;  // MD view (used by CUSTOM for non-tool content)
@@ -13,13 +14,93 @@ pub mod minimal;
 pub mod context;
 pub mod markdown;
 
+// Summarized view for very large lines
+pub mod large;
+
+use std::borrow::Cow;
+
 use ratatui::prelude::*;
-use transcript_core::{TranscriptLine, ViewMode};
+use transcript_core::{display_text, is_binary, parse_ansi, sanitize_text, AnsiColor, TextStyle, TranscriptLine, ViewMode};
 
 /// Render content for a line in the specified view mode
-pub fn render_content(line: &TranscriptLine, mode: ViewMode, width: usize) -> Vec<Line<'static>> {
-    match mode {
+///
+/// With `a11y`, signals shown only by color also get a textual marker.
+pub fn render_content(line: &TranscriptLine, mode: ViewMode, width: usize, a11y: bool) -> Vec<Line<'static>> {
+    let lines = match mode {
         ViewMode::Json => raw::render(line, width),
-        ViewMode::Custom => custom::render(line, width),
+        ViewMode::Custom | ViewMode::Thread => custom::render(line, width, a11y),
+    };
+    sanitize_lines(lines)
+}
+
+/// Escape control characters in every span so content can't drive the terminal
+pub fn sanitize_lines(mut lines: Vec<Line<'static>>) -> Vec<Line<'static>> {
+    for line in &mut lines {
+        for span in &mut line.spans {
+            if let Cow::Owned(clean) = sanitize_text(&span.content) {
+                span.content = Cow::Owned(clean);
+            }
+        }
+    }
+    lines
+}
+
+/// Lines for tool output, keeping its embedded ANSI colors on top of `base`
+pub fn text_lines(text: &str, base: Style) -> Vec<Line<'static>> {
+    if is_binary(text) {
+        return display_text(text)
+            .lines()
+            .map(|l| Line::from(Span::styled(l.to_string(), base)))
+            .collect();
+    }
+
+    let mut lines = vec![Line::default()];
+    for fragment in parse_ansi(text) {
+        let style = base.patch(fragment_style(&fragment.style));
+        for (i, part) in fragment.text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Line::default());
+            }
+            if !part.is_empty() {
+                if let Some(last) = lines.last_mut() {
+                    last.spans.push(Span::styled(part.to_string(), style));
+                }
+            }
+        }
+    }
+    // Like `str::lines`, a trailing newline doesn't start another line
+    if lines.len() > 1 && lines.last().is_some_and(|l| l.spans.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+fn ansi_color(color: AnsiColor) -> Color {
+    match color {
+        AnsiColor::Indexed(n) => Color::Indexed(n),
+        AnsiColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+/// Map a parsed ANSI style onto ratatui
+fn fragment_style(style: &TextStyle) -> Style {
+    let mut out = Style::default();
+    if let Some(fg) = style.fg {
+        out = out.fg(ansi_color(fg));
+    }
+    if let Some(bg) = style.bg {
+        out = out.bg(ansi_color(bg));
+    }
+    for (on, modifier) in [
+        (style.bold, Modifier::BOLD),
+        (style.dim, Modifier::DIM),
+        (style.italic, Modifier::ITALIC),
+        (style.underline, Modifier::UNDERLINED),
+        (style.reverse, Modifier::REVERSED),
+    ] {
+        if on {
+            out = out.add_modifier(modifier);
+        }
     }
+    out
 }
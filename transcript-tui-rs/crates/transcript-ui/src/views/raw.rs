@@ -1,7 +1,7 @@
 //! Raw JSON view mode
 
 use ratatui::prelude::*;
-use transcript_core::TranscriptLine;
+use transcript_core::{display_text, JsonPath, TranscriptLine};
 
 /// Render line as raw JSON with syntax highlighting
 pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
@@ -14,10 +14,37 @@ pub fn render(line: &TranscriptLine, _width: usize) -> Vec<Line<'static>> {
     // Apply syntax highlighting
     json_text
         .lines()
-        .map(|l| highlight_json_line(l))
+        .map(highlight_json_line)
         .collect()
 }
 
+/// Render only the value at `path` within the line's raw JSON
+pub fn render_query(line: &TranscriptLine, path: &JsonPath) -> Vec<Line<'static>> {
+    let value = match serde_json::from_str::<serde_json::Value>(&line.raw) {
+        Ok(value) => value,
+        Err(_) => {
+            return vec![Line::from(Span::styled(
+                "(line is not valid JSON)",
+                Style::default().fg(Color::Red),
+            ))]
+        }
+    };
+
+    match path.select(&value) {
+        // Plain strings read better unquoted (stdout, file content, ...)
+        Some(serde_json::Value::String(s)) => display_text(s).lines().map(|l| Line::from(l.to_string())).collect(),
+        Some(selected) => serde_json::to_string_pretty(selected)
+            .unwrap_or_default()
+            .lines()
+            .map(highlight_json_line)
+            .collect(),
+        None => vec![Line::from(Span::styled(
+            "(no value at this path)",
+            Style::default().fg(Color::DarkGray),
+        ))],
+    }
+}
+
 /// Apply JSON syntax highlighting to a single line
 fn highlight_json_line(text: &str) -> Line<'static> {
     let mut spans = Vec::new();